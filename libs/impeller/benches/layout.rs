@@ -0,0 +1,73 @@
+//! Compares recording a swarm's positions entity-major and element-major, to pick an archetype's
+//! [`ColumnLayout`]: `cargo bench -p impeller --bench layout`.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use impeller::{
+    ArchetypeName, ColumnLayout, ComponentId, ComponentType, Metadata, PrimitiveTy, World,
+};
+
+const ENTITIES: usize = 10_000;
+const TICKS: usize = 200;
+
+fn swarm(layout: ColumnLayout) -> (World, ComponentId) {
+    let metadata = Metadata {
+        name: "world_pos".into(),
+        component_type: ComponentType {
+            primitive_ty: PrimitiveTy::F64,
+            shape: smallvec::smallvec![7],
+        },
+        tags: None,
+        asset: false,
+    };
+    let id = metadata.component_id();
+    let archetype_name = ArchetypeName::from("swarm");
+    let mut world = World::default();
+    world.component_map.insert(id, (archetype_name, metadata));
+    world.layouts.insert(archetype_name, layout);
+    world.entity_ids.insert(
+        archetype_name,
+        (0..ENTITIES as u64).flat_map(u64::to_le_bytes).collect(),
+    );
+    let values = (0..ENTITIES * 7).map(|i| i as f64).collect::<Vec<_>>();
+    world
+        .host
+        .insert(id, bytemuck::cast_slice(&values).to_vec());
+    (world, id)
+}
+
+/// Records `TICKS` ticks, then reads the x coordinate of every entity at every tick back out of
+/// the history.
+fn run(layout: ColumnLayout) -> (Duration, Duration) {
+    let (mut world, id) = swarm(layout);
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        let column = world.host.get_mut(&id).unwrap();
+        column[0] = column[0].wrapping_add(1);
+        world.advance_tick();
+    }
+    let record = start.elapsed();
+
+    let start = Instant::now();
+    let mut sum = 0.0;
+    for buffers in &world.history {
+        let values: &[f64] = bytemuck::cast_slice(&buffers[&id]);
+        sum += match layout {
+            ColumnLayout::EntityMajor => values.iter().step_by(7).sum::<f64>(),
+            ColumnLayout::ElementMajor => values[..ENTITIES].iter().sum::<f64>(),
+        };
+    }
+    black_box(sum);
+    (record, start.elapsed())
+}
+
+fn main() {
+    for layout in [ColumnLayout::EntityMajor, ColumnLayout::ElementMajor] {
+        let (record, read) = run(layout);
+        println!(
+            "{layout:?}: record {:.1} us/tick, read one element {:.1} us/tick",
+            record.as_secs_f64() * 1e6 / TICKS as f64,
+            read.as_secs_f64() * 1e6 / TICKS as f64,
+        );
+    }
+}
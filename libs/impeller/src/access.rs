@@ -0,0 +1,313 @@
+//! Encryption and access control for connections to a [`crate::server::TcpServer`], for sims
+//! served across networks that not everyone on them should be able to read or drive.
+//!
+//! Servers can wrap connections in TLS, and can require clients to present a shared token as
+//! their first message, with [`ControlMsg::Auth`]. Both are off by default, and both can be
+//! configured from the environment:
+//!
+//! - `ELODIN_TLS_CERT` and `ELODIN_TLS_KEY`: the PEM certificate chain and key a server presents
+//! - `ELODIN_TLS_CA`: the PEM certificates a client trusts, which turns TLS on for the client
+//! - `ELODIN_TLS_SERVER_NAME`: the name the client expects on the certificate, rather than the
+//!   server's IP address
+//! - `ELODIN_TOKEN`: the token a server requires, and a client presents
+//!
+//! TLS needs the `tls` feature; without it, setting any of the `ELODIN_TLS_*` variables is an
+//! error rather than a silently unencrypted connection.
+#[cfg(feature = "tls")]
+use std::fs::File;
+use std::io;
+#[cfg(feature = "tls")]
+use std::io::BufReader;
+use std::net::SocketAddr;
+#[cfg(feature = "tls")]
+use std::path::Path;
+use std::sync::Arc;
+
+use bytes::{Bytes, BytesMut};
+use futures::StreamExt;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerConfig};
+#[cfg(feature = "tls")]
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+use crate::{ControlMsg, Error, Packet, Payload};
+
+/// A byte stream a connection runs over, either a plain socket or a TLS session on top of one.
+pub trait Transport: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Transport for T {}
+
+/// What a server asks of the clients connecting to it.
+#[derive(Clone, Default)]
+pub struct Access {
+    #[cfg(feature = "tls")]
+    pub tls: Option<TlsAcceptor>,
+    pub token: Option<Arc<str>>,
+}
+
+impl Access {
+    /// Reads the server's certificate, key and token from the environment, see the module docs.
+    pub fn from_env() -> Result<Self, Error> {
+        let access = Self::token_from_env();
+        match (env("ELODIN_TLS_CERT"), env("ELODIN_TLS_KEY")) {
+            #[cfg(feature = "tls")]
+            (Some(cert), Some(key)) => Ok(access.tls(tls_acceptor(cert, key)?)),
+            #[cfg(not(feature = "tls"))]
+            (Some(_), Some(_)) => Err(tls_unavailable()),
+            (None, None) => Ok(access),
+            _ => Err(config_error(
+                "ELODIN_TLS_CERT and ELODIN_TLS_KEY have to be set together",
+            )),
+        }
+    }
+
+    /// Reads just the token from the environment, for servers that don't speak TLS.
+    pub fn token_from_env() -> Self {
+        Self {
+            #[cfg(feature = "tls")]
+            tls: None,
+            token: env("ELODIN_TOKEN").map(Arc::from),
+        }
+    }
+
+    pub fn token(mut self, token: impl Into<Arc<str>>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, acceptor: TlsAcceptor) -> Self {
+        self.tls = Some(acceptor);
+        self
+    }
+
+    /// Runs the TLS handshake with a newly accepted socket, if the server uses TLS.
+    pub async fn accept(&self, socket: TcpStream) -> Result<Box<dyn Transport>, Error> {
+        #[cfg(feature = "tls")]
+        if let Some(acceptor) = &self.tls {
+            return Ok(Box::new(acceptor.accept(socket).await?));
+        }
+        Ok(Box::new(socket))
+    }
+
+    /// Whether `packet`, the first one a client sent, lets it in.
+    pub fn admits<B>(&self, packet: &Packet<Payload<B>>) -> bool {
+        match (&self.token, &packet.payload) {
+            (None, _) => true,
+            (Some(expected), Payload::ControlMsg(ControlMsg::Auth { token })) => {
+                token_eq(token, expected)
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks the first message from a client carries the right token, if the server needs one.
+    pub async fn authenticate(
+        &self,
+        rx: &mut (impl futures::Stream<Item = Result<BytesMut, io::Error>> + Unpin),
+    ) -> Result<(), Error> {
+        if self.token.is_none() {
+            return Ok(());
+        }
+        let buf = rx.next().await.ok_or(Error::ConnectionClosed)??;
+        let packet = Packet::<Payload<Bytes>>::parse(buf.freeze())?;
+        if self.admits(&packet) {
+            Ok(())
+        } else {
+            Err(Error::Unauthorized)
+        }
+    }
+}
+
+/// How a client reaches a server set up with [`Access`].
+#[derive(Clone, Default)]
+pub struct Credentials {
+    #[cfg(feature = "tls")]
+    pub tls: Option<TlsConnector>,
+    /// The name the server's certificate has to be for, rather than the address connected to.
+    #[cfg(feature = "tls")]
+    pub server_name: Option<ServerName<'static>>,
+    pub token: Option<String>,
+}
+
+impl Credentials {
+    /// Reads the trusted certificates, server name and token from the environment, see the
+    /// module docs.
+    #[cfg(feature = "tls")]
+    pub fn from_env() -> Result<Self, Error> {
+        let tls = env("ELODIN_TLS_CA").map(tls_connector).transpose()?;
+        let server_name = env("ELODIN_TLS_SERVER_NAME")
+            .map(|name| ServerName::try_from(name).map_err(|err| config_error(err.to_string())))
+            .transpose()?;
+        Ok(Self {
+            tls,
+            server_name,
+            ..Self::token_from_env()
+        })
+    }
+
+    /// Reads the token from the environment, see the module docs.
+    #[cfg(not(feature = "tls"))]
+    pub fn from_env() -> Result<Self, Error> {
+        if env("ELODIN_TLS_CA").is_some() || env("ELODIN_TLS_SERVER_NAME").is_some() {
+            return Err(tls_unavailable());
+        }
+        Ok(Self::token_from_env())
+    }
+
+    /// Reads just the token from the environment, for clients that don't speak TLS.
+    pub fn token_from_env() -> Self {
+        Self {
+            #[cfg(feature = "tls")]
+            tls: None,
+            #[cfg(feature = "tls")]
+            server_name: None,
+            token: env("ELODIN_TOKEN"),
+        }
+    }
+
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, connector: TlsConnector) -> Self {
+        self.tls = Some(connector);
+        self
+    }
+
+    /// Opens a connection to `addr`, running the TLS handshake if the client uses TLS.
+    pub async fn connect(&self, addr: SocketAddr) -> Result<Box<dyn Transport>, Error> {
+        let socket = TcpStream::connect(addr).await?;
+        socket.set_nodelay(true)?;
+        #[cfg(feature = "tls")]
+        if let Some(connector) = &self.tls {
+            let name = self
+                .server_name
+                .clone()
+                .unwrap_or_else(|| ServerName::IpAddress(addr.ip().into()));
+            return Ok(Box::new(connector.connect(name, socket).await?));
+        }
+        Ok(Box::new(socket))
+    }
+
+    /// The message to open the connection with, if the client has a token.
+    pub fn auth_packet<B>(&self) -> Option<Packet<Payload<B>>> {
+        let token = self.token.clone()?;
+        Some(Packet::control(ControlMsg::Auth { token }))
+    }
+}
+
+/// Loads a PEM certificate chain and private key into an acceptor for a server.
+#[cfg(feature = "tls")]
+pub fn tls_acceptor(cert: impl AsRef<Path>, key: impl AsRef<Path>) -> Result<TlsAcceptor, Error> {
+    let certs = read_certs(cert.as_ref())?;
+    let key = read_key(key.as_ref())?;
+    let config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Builds a connector for clients that trust the PEM certificates in `ca`.
+#[cfg(feature = "tls")]
+pub fn tls_connector(ca: impl AsRef<Path>) -> Result<TlsConnector, Error> {
+    let mut roots = RootCertStore::empty();
+    for cert in read_certs(ca.as_ref())? {
+        roots.add(cert)?;
+    }
+    let config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+#[cfg(feature = "tls")]
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let certs = rustls_pemfile::certs(&mut reader).collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err(config_error(format!(
+            "no certificates in {}",
+            path.display()
+        )));
+    }
+    Ok(certs)
+}
+
+#[cfg(feature = "tls")]
+fn read_key(path: &Path) -> Result<PrivateKeyDer<'static>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::private_key(&mut reader)?
+        .ok_or_else(|| config_error(format!("no private key in {}", path.display())))
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn config_error(reason: impl Into<String>) -> Error {
+    Error::AccessConfig(reason.into())
+}
+
+#[cfg(not(feature = "tls"))]
+fn tls_unavailable() -> Error {
+    config_error("the tls feature is needed to use ELODIN_TLS_* variables")
+}
+
+/// Compares tokens in time that doesn't depend on where they differ.
+fn token_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
+
+    #[test]
+    fn test_token_eq() {
+        assert!(token_eq("hunter2", "hunter2"));
+        assert!(!token_eq("hunter2", "hunter3"));
+        assert!(!token_eq("hunter2", "hunter"));
+    }
+
+    #[tokio::test]
+    async fn test_authenticate() {
+        use futures::SinkExt;
+
+        let access = Access::default().token("secret");
+        for (credentials, ok) in [
+            (Credentials::default().token("secret"), true),
+            (Credentials::default().token("guess"), false),
+            (Credentials::default(), false),
+        ] {
+            let (client, server) = tokio::io::duplex(1024);
+            let mut tx = FramedWrite::new(client, LengthDelimitedCodec::new());
+            let packet = credentials
+                .auth_packet::<Bytes>()
+                .unwrap_or_else(|| Packet::control(ControlMsg::Connect));
+            let mut buf = BytesMut::new();
+            packet.write(&mut buf).unwrap();
+            tx.send(buf.freeze()).await.unwrap();
+
+            let mut rx = FramedRead::new(server, LengthDelimitedCodec::new());
+            let res = access.authenticate(&mut rx).await;
+            assert_eq!(res.is_ok(), ok, "{res:?}");
+        }
+
+        // servers without a token let anyone in, without waiting for a message
+        let (_client, server) = tokio::io::duplex(1024);
+        let mut rx = FramedRead::new(server, LengthDelimitedCodec::new());
+        Access::default().authenticate(&mut rx).await.unwrap();
+    }
+}
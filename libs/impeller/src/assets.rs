@@ -3,7 +3,7 @@ use crate::{concat_str, Asset, ComponentType};
 use alloc::vec::Vec;
 use bytes::Bytes;
 use core::marker::PhantomData;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::Component;
 
@@ -77,6 +77,11 @@ impl AssetStore {
         Some(val)
     }
 
+    /// Decodes the asset behind `handle`.
+    pub fn get<A: Asset + DeserializeOwned>(&self, handle: Handle<A>) -> Option<A> {
+        postcard::from_bytes(&self.value(handle)?.inner).ok()
+    }
+
     pub fn gen<C>(&self, handle: Handle<C>) -> Option<usize> {
         let val = self.data.get(handle.id as usize)?;
         Some(val.generation)
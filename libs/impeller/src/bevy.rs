@@ -24,7 +24,7 @@ use nox::Tensor;
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
 use std::marker::PhantomData;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::warn;
 
 use bevy::{
@@ -64,6 +64,10 @@ pub struct Simulating(pub bool);
 #[derive(bevy::prelude::Resource)]
 pub struct TimeStep(pub Duration);
 
+/// When the current [`Tick`] arrived, for extrapolating poses until the next one.
+#[derive(bevy::prelude::Resource)]
+pub struct TickReceivedAt(pub Instant);
+
 impl ColumnMsg<Bytes> {
     pub fn load_into_bevy(
         &self,
@@ -296,6 +300,7 @@ pub struct RecvSystemArgs<'w, 's> {
     exit: EventWriter<'w, AppExit>,
     max_tick_res: ResMut<'w, MaxTick>,
     tick_res: ResMut<'w, Tick>,
+    tick_received_at: ResMut<'w, TickReceivedAt>,
     simulating_res: ResMut<'w, Simulating>,
 }
 
@@ -318,6 +323,7 @@ fn recv_system(args: RecvSystemArgs) {
         mut exit,
         mut max_tick_res,
         mut tick_res,
+        mut tick_received_at,
         mut simulating_res,
     } = args;
 
@@ -406,6 +412,9 @@ fn recv_system(args: RecvSystemArgs) {
                 simulating,
             }) => {
                 max_tick_res.0 = *max_tick;
+                if tick_res.0 != *tick {
+                    tick_received_at.0 = Instant::now();
+                }
                 tick_res.0 = *tick;
                 simulating_res.0 = *simulating;
             }
@@ -449,6 +458,7 @@ impl Plugin for ImpellerSubscribePlugin {
         app.insert_resource(AssetMap::default());
         app.insert_resource(MaxTick(0));
         app.insert_resource(Tick(0));
+        app.insert_resource(TickReceivedAt(Instant::now()));
         app.insert_resource(Simulating(false));
         app.insert_resource(TimeStep(Duration::default()));
         app.insert_resource(ImpellerRx(self.rx.clone()));
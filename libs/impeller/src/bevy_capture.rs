@@ -0,0 +1,413 @@
+//! Renders [`Capture`] viewpoints offscreen and saves them at the requested ticks.
+//!
+//! Each capture gets its own camera drawing into an image. Requested ticks are queued with the
+//! camera pose at that tick, and one is taken per frame: the camera is placed, the image is
+//! copied into a mappable buffer after the cameras have drawn, read back on the render world,
+//! and handed to the main world to be written out.
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::render::{
+    camera::RenderTarget,
+    render_asset::{RenderAssetUsages, RenderAssets},
+    render_graph::{self, NodeRunError, RenderGraph, RenderGraphContext, RenderLabel},
+    render_resource::{
+        Buffer, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d,
+        ImageCopyBuffer, ImageDataLayout, Maintain, MapMode, TextureDimension, TextureFormat,
+        TextureUsages,
+    },
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+    texture::{BevyDefault, GpuImage},
+    Extract, Render, RenderApp, RenderSet,
+};
+use big_space::GridCell;
+use tracing::warn;
+
+use crate::{
+    bevy::{EntityMap, Tick, TickReceivedAt},
+    well_known::{Capture, CaptureFormat, Viewport, WorldPos},
+};
+
+/// How long a video capture waits for another frame before encoding the frames it has, for runs
+/// that end before the capture's last tick.
+const FINALIZE_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, _app: &mut App) {}
+
+    // the render sub-app is only guaranteed to exist once every plugin is built
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            warn!("captures need a renderer");
+            return;
+        };
+        let (tx, rx) = flume::unbounded();
+        render_app
+            .world_mut()
+            .resource_mut::<RenderGraph>()
+            .add_node(CaptureCopy, CaptureCopyNode);
+        render_app
+            .world_mut()
+            .resource_mut::<RenderGraph>()
+            .add_node_edge(bevy::render::graph::CameraDriverLabel, CaptureCopy);
+        render_app
+            .insert_resource(CaptureSender(tx))
+            .add_systems(ExtractSchedule, extract_copiers)
+            .add_systems(Render, read_back_frames.after(RenderSet::Render));
+        app.insert_resource(CaptureReceiver(rx)).add_systems(
+            Update,
+            (
+                spawn_capture_cameras,
+                queue_frames,
+                request_frame,
+                save_frames,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Copies the image a capture camera draws into when a tick is requested.
+#[derive(Component, Clone)]
+struct FrameCopier {
+    capture: Entity,
+    image: Handle<Image>,
+    buffer: Buffer,
+    /// The ticks drawn this frame, sent by the main world and read back in order by the render
+    /// world.
+    requests: flume::Receiver<u64>,
+}
+
+#[derive(Component)]
+struct CaptureCamera {
+    capture: Entity,
+    /// The requested ticks not yet drawn, with the camera's pose at each.
+    queue: VecDeque<(u64, Transform)>,
+    requests: flume::Sender<u64>,
+}
+
+/// The frames a capture has written, so a video can be encoded once they stop coming.
+#[derive(Component, Default)]
+struct CaptureProgress {
+    saved: usize,
+    last_saved: Option<Instant>,
+    encoded: bool,
+}
+
+struct Frame {
+    capture: Entity,
+    tick: u64,
+    data: Vec<u8>,
+}
+
+#[derive(Resource, Deref)]
+struct CaptureReceiver(flume::Receiver<Frame>);
+
+#[derive(Resource, Deref)]
+struct CaptureSender(flume::Sender<Frame>);
+
+#[derive(Resource, Default, Deref)]
+struct FrameCopiers(Vec<FrameCopier>);
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, RenderLabel)]
+struct CaptureCopy;
+
+#[derive(Default)]
+struct CaptureCopyNode;
+
+fn padded_bytes_per_row(width: u32) -> usize {
+    RenderDevice::align_copy_bytes_per_row(width as usize * 4)
+}
+
+fn spawn_capture_cameras(
+    mut commands: Commands,
+    captures: Query<(Entity, &Capture), Added<Capture>>,
+    mut images: ResMut<Assets<Image>>,
+    render_device: Res<RenderDevice>,
+) {
+    for (entity, capture) in captures.iter() {
+        if let Err(err) = std::fs::create_dir_all(&capture.output) {
+            warn!(?err, output = %capture.output, "failed to create capture directory");
+            continue;
+        }
+        let size = Extent3d {
+            width: capture.width,
+            height: capture.height,
+            ..Default::default()
+        };
+        let mut image = Image::new_fill(
+            size,
+            TextureDimension::D2,
+            &[0; 4],
+            TextureFormat::bevy_default(),
+            RenderAssetUsages::default(),
+        );
+        image.texture_descriptor.usage |= TextureUsages::COPY_SRC
+            | TextureUsages::RENDER_ATTACHMENT
+            | TextureUsages::TEXTURE_BINDING;
+        let image = images.add(image);
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("capture_buffer"),
+            size: (padded_bytes_per_row(capture.width) * capture.height as usize) as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let (tx, rx) = flume::unbounded();
+        commands.entity(entity).insert(CaptureProgress::default());
+        commands.spawn((
+            Camera3dBundle {
+                camera: Camera {
+                    target: RenderTarget::Image(image.clone()),
+                    hdr: capture.viewport.hdr,
+                    ..default()
+                },
+                projection: Projection::Perspective(PerspectiveProjection {
+                    fov: capture.viewport.fov.to_radians(),
+                    ..default()
+                }),
+                ..default()
+            },
+            GridCell::<i128>::default(),
+            CaptureCamera {
+                capture: entity,
+                queue: VecDeque::new(),
+                requests: tx,
+            },
+            FrameCopier {
+                capture: entity,
+                image,
+                buffer,
+                requests: rx,
+            },
+        ));
+    }
+}
+
+/// Queues the current tick on every capture that wants it, with the camera placed against the
+/// tracked entity's pose at that tick rather than the pose being displayed.
+fn queue_frames(
+    tick: Res<Tick>,
+    tick_received_at: Res<TickReceivedAt>,
+    mut cameras: Query<&mut CaptureCamera>,
+    captures: Query<&Capture>,
+    entities: Query<&WorldPos>,
+    entity_map: Res<EntityMap>,
+) {
+    // `Tick` is written on every control message, but this only moves when the tick does
+    if !tick_received_at.is_changed() {
+        return;
+    }
+    for mut camera in cameras.iter_mut() {
+        let Ok(capture) = captures.get(camera.capture) else {
+            continue;
+        };
+        if !capture.ticks.contains(tick.0) {
+            continue;
+        }
+        let tracked = capture
+            .viewport
+            .track_entity
+            .and_then(|id| entity_map.get(&id))
+            .and_then(|entity| entities.get(*entity).ok());
+        let transform = viewport_transform(&capture.viewport, tracked);
+        camera.queue.push_back((tick.0, transform));
+    }
+}
+
+/// Takes one queued tick per capture a frame, placing the camera to draw it.
+fn request_frame(mut cameras: Query<(&mut CaptureCamera, &mut Transform)>) {
+    for (mut camera, mut transform) in cameras.iter_mut() {
+        let Some((tick, pose)) = camera.queue.pop_front() else {
+            continue;
+        };
+        *transform = pose;
+        let _ = camera.requests.send(tick);
+    }
+}
+
+/// Places the camera like the editor places a viewport: at `pos`, relative to the tracked
+/// entity if there is one.
+fn viewport_transform(viewport: &Viewport, tracked: Option<&WorldPos>) -> Transform {
+    let [x, y, z] = viewport.pos.parts().map(|x| x.into_buf());
+    let offset = Vec3::new(x, z, -y);
+    let [i, j, k, w] = viewport.rotation.parts().map(|x| x.into_buf());
+    let rotation = Quat::from_xyzw(i, j, k, w);
+    let Some(pos) = tracked else {
+        return Transform::from_translation(offset).with_rotation(rotation);
+    };
+    let origin = pos.bevy_pos().as_vec3();
+    if viewport.track_rotation {
+        let att = pos.bevy_att().as_quat();
+        Transform::from_translation(origin + att * offset).with_rotation(att * rotation)
+    } else {
+        Transform::from_translation(origin + offset).with_rotation(rotation)
+    }
+}
+
+fn save_frames(
+    receiver: Res<CaptureReceiver>,
+    mut captures: Query<(&Capture, &mut CaptureProgress)>,
+) {
+    for frame in receiver.try_iter() {
+        let Ok((capture, mut progress)) = captures.get_mut(frame.capture) else {
+            continue;
+        };
+        let path = Path::new(&capture.output).join(format!("{:08}.png", frame.tick));
+        if let Err(err) = save_png(capture, frame.data, &path) {
+            warn!(?err, ?path, "failed to save capture");
+            continue;
+        }
+        progress.saved += 1;
+        progress.last_saved = Some(Instant::now());
+        progress.encoded = false;
+        if let CaptureFormat::Mp4 { fps } = capture.format {
+            if capture.ticks.last() == Some(frame.tick) {
+                encode_mp4(&capture.output, fps);
+                progress.encoded = true;
+            }
+        }
+    }
+    // a run can stop short of the last tick, so encode whatever arrived once the frames stop
+    for (capture, mut progress) in captures.iter_mut() {
+        let CaptureFormat::Mp4 { fps } = capture.format else {
+            continue;
+        };
+        let stalled = progress
+            .last_saved
+            .is_some_and(|at| at.elapsed() >= FINALIZE_TIMEOUT);
+        if progress.saved > 0 && !progress.encoded && stalled {
+            encode_mp4(&capture.output, fps);
+            progress.encoded = true;
+        }
+    }
+}
+
+fn save_png(capture: &Capture, data: Vec<u8>, path: &Path) -> Result<(), String> {
+    let row = capture.width as usize * 4;
+    // rows are padded out to the copy alignment, strip them back to the image width
+    let data = if padded_bytes_per_row(capture.width) == row {
+        data
+    } else {
+        data.chunks(padded_bytes_per_row(capture.width))
+            .flat_map(|chunk| &chunk[..row])
+            .copied()
+            .collect()
+    };
+    let image = Image::new(
+        Extent3d {
+            width: capture.width,
+            height: capture.height,
+            ..Default::default()
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::bevy_default(),
+        RenderAssetUsages::MAIN_WORLD,
+    );
+    let image = image.try_into_dynamic().map_err(|err| err.to_string())?;
+    image.to_rgba8().save(path).map_err(|err| err.to_string())
+}
+
+fn encode_mp4(output: &str, fps: u32) {
+    let video = PathBuf::from(format!("{}.mp4", output.trim_end_matches('/')));
+    let frames = Path::new(output).join("*.png");
+    let status = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-framerate", &fps.to_string()])
+        .args(["-pattern_type", "glob", "-i"])
+        .arg(&frames)
+        .args(["-pix_fmt", "yuv420p"])
+        .arg(&video)
+        .status();
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => warn!(?status, ?video, "ffmpeg failed to encode capture"),
+        Err(err) => warn!(
+            ?err,
+            "failed to run ffmpeg, the capture frames are left as PNGs"
+        ),
+    }
+}
+
+fn extract_copiers(mut commands: Commands, copiers: Extract<Query<&FrameCopier>>) {
+    commands.insert_resource(FrameCopiers(copiers.iter().cloned().collect()));
+}
+
+impl render_graph::Node for CaptureCopyNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let Some(copiers) = world.get_resource::<FrameCopiers>() else {
+            return Ok(());
+        };
+        let gpu_images = world.resource::<RenderAssets<GpuImage>>();
+        for copier in copiers.iter() {
+            if copier.requests.is_empty() {
+                continue;
+            }
+            let Some(image) = gpu_images.get(&copier.image) else {
+                continue;
+            };
+            let mut encoder = render_context
+                .render_device()
+                .create_command_encoder(&CommandEncoderDescriptor::default());
+            encoder.copy_texture_to_buffer(
+                image.texture.as_image_copy(),
+                ImageCopyBuffer {
+                    buffer: &copier.buffer,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_bytes_per_row(image.size.x) as u32),
+                        rows_per_image: None,
+                    },
+                },
+                Extent3d {
+                    width: image.size.x,
+                    height: image.size.y,
+                    depth_or_array_layers: 1,
+                },
+            );
+            world
+                .resource::<RenderQueue>()
+                .submit(std::iter::once(encoder.finish()));
+        }
+        Ok(())
+    }
+}
+
+fn read_back_frames(
+    copiers: Res<FrameCopiers>,
+    render_device: Res<RenderDevice>,
+    sender: Res<CaptureSender>,
+) {
+    for copier in copiers.iter() {
+        let Ok(tick) = copier.requests.try_recv() else {
+            continue;
+        };
+        let slice = copier.buffer.slice(..);
+        let (tx, rx) = flume::bounded(1);
+        slice.map_async(MapMode::Read, move |res| {
+            let _ = tx.send(res);
+        });
+        render_device.poll(Maintain::wait()).panic_on_timeout();
+        match rx.recv() {
+            Ok(Ok(())) => {
+                let data = slice.get_mapped_range().to_vec();
+                copier.buffer.unmap();
+                let _ = sender.send(Frame {
+                    capture: copier.capture,
+                    tick,
+                    data,
+                });
+            }
+            _ => warn!("failed to map capture buffer"),
+        }
+    }
+}
@@ -3,12 +3,13 @@ use std::{marker::PhantomData, ops::DerefMut};
 use crate::{
     bevy::{
         AppExt, AssetAdapter, ComponentValueMap, EntityMap, ImpellerSubscribePlugin, SimPeer,
-        Subscriptions,
+        Simulating, Subscriptions, TickReceivedAt, TimeStep,
     },
+    bevy_capture::CapturePlugin,
     client::MsgPair,
     well_known::{
-        self, BodyAxes, EntityMetadata, Glb, Line3d, Material, Mesh as ImpellerMesh, Panel,
-        VectorArrow, WorldPos,
+        self, BodyAxes, Capture, EntityMetadata, Glb, Line3d, Material, Mesh as ImpellerMesh,
+        Panel, VectorArrow, WorldPos, WorldVel,
     },
     EntityId,
 };
@@ -43,6 +44,7 @@ impl Plugin for SyncPlugin {
             .insert_resource(self.subscriptions.clone())
             .insert_resource(EntityMap::default())
             .add_impeller_component::<WorldPos>()
+            .add_impeller_component::<WorldVel>()
             .add_impeller_component::<well_known::Camera>()
             .add_impeller_asset::<VectorArrow>(Box::new(SyncPostcardAdapter::<VectorArrow>::new(
                 None,
@@ -50,6 +52,7 @@ impl Plugin for SyncPlugin {
             .add_impeller_asset::<BodyAxes>(Box::new(SyncPostcardAdapter::<BodyAxes>::new(None)))
             .add_impeller_asset::<Panel>(Box::new(SyncPostcardAdapter::<Panel>::new(None)))
             .add_impeller_asset::<Line3d>(Box::new(SyncPostcardAdapter::<Line3d>::new(None)))
+            .add_impeller_asset::<Capture>(Box::new(SyncPostcardAdapter::<Capture>::new(None)))
             .add_impeller_asset::<EntityMetadata>(Box::new(
                 SyncPostcardAdapter::<EntityMetadata>::new(None),
             ))
@@ -61,7 +64,47 @@ impl Plugin for SyncPlugin {
             )))
             .add_impeller_asset::<Glb>(Box::new(SyncPostcardAdapter::<Glb>::new(
                 self.enable_pbr.then_some(sync_glb),
-            )));
+            )))
+            .add_systems(
+                Update,
+                (insert_display_pos, extrapolate_display_pos).chain(),
+            );
+        if self.enable_pbr {
+            app.add_plugins(CapturePlugin);
+        }
+    }
+}
+
+/// The pose to draw an entity at this frame.
+///
+/// Sim ticks and display frames rarely line up, so drawing `WorldPos` directly stutters.
+/// Entities that also stream a [`WorldVel`] are extrapolated from their last tick by the wall
+/// time since it arrived, capped at one time step so a stalled sim doesn't drift away.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct DisplayPos(pub WorldPos);
+
+fn insert_display_pos(mut commands: Commands, query: Query<Entity, Added<WorldPos>>) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(DisplayPos::default());
+    }
+}
+
+fn extrapolate_display_pos(
+    mut query: Query<(&WorldPos, Option<&WorldVel>, &mut DisplayPos)>,
+    tick_received_at: Res<TickReceivedAt>,
+    time_step: Res<TimeStep>,
+    simulating: Res<Simulating>,
+) {
+    let dt = if simulating.0 {
+        tick_received_at.0.elapsed().min(time_step.0).as_secs_f64()
+    } else {
+        0.0
+    };
+    for (pos, vel, mut display_pos) in query.iter_mut() {
+        display_pos.0 = match vel {
+            Some(vel) => pos.extrapolate(vel, dt),
+            None => *pos,
+        };
     }
 }
 
@@ -12,7 +12,11 @@ pub struct Demux {
 }
 
 impl Demux {
-    pub fn handle<B: Buf + Slice>(&mut self, packet: Packet<Payload<B>>) -> Result<Msg<B>, Error> {
+    /// Decodes `packet`, decompressing column values as their stream's metadata asks for.
+    pub fn handle<B>(&mut self, packet: Packet<Payload<B>>) -> Result<Msg<B>, Error>
+    where
+        B: Buf + Slice + AsRef<[u8]> + From<alloc::vec::Vec<u8>>,
+    {
         match packet.payload {
             Payload::ControlMsg(ControlMsg::OpenStream {
                 stream_id,
@@ -30,6 +34,8 @@ impl Demux {
                     .streams
                     .get(&packet.stream_id)
                     .ok_or(Error::StreamNotFound(packet.stream_id))?;
+                #[cfg(feature = "std")]
+                let payload = payload.decompress(metadata)?;
                 Ok(Msg::Column(ColumnMsg {
                     metadata: metadata.clone(),
                     payload,
@@ -1,5 +1,6 @@
 use bytes::{Buf, Bytes, BytesMut};
 use std::io;
+use std::net::SocketAddr;
 use tokio::net::{
     tcp::{OwnedReadHalf, OwnedWriteHalf},
     TcpStream,
@@ -7,6 +8,7 @@ use tokio::net::{
 use tokio_util::codec::{Framed, FramedRead, FramedWrite, LengthDelimitedCodec};
 
 use crate::{
+    access::{Credentials, Transport},
     client::{Demux, Msg},
     ser_de::Slice,
     Error, Packet, Payload,
@@ -98,3 +100,17 @@ where
         AsyncClient::new(Framed::new(stream, LengthDelimitedCodec::default()))
     }
 }
+
+pub type SecureClient = AsyncClient<Framed<Box<dyn Transport>, LengthDelimitedCodec>>;
+
+impl SecureClient {
+    /// Connects to a server with `credentials`, presenting the token before anything else.
+    pub async fn connect(addr: SocketAddr, credentials: &Credentials) -> Result<Self, Error> {
+        let stream = credentials.connect(addr).await?;
+        let mut client = AsyncClient::from_stream(stream);
+        if let Some(packet) = credentials.auth_packet::<Bytes>() {
+            client.send(packet).await?;
+        }
+        Ok(client)
+    }
+}
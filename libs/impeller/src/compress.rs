@@ -0,0 +1,216 @@
+//! Lossless compression of column values, configured per component with [`crate::Metadata::set_compression`].
+//!
+//! Raw floats compress poorly, since their low mantissa bits look random. XOR encoding each value against the
+//! previous one zeroes out the sign, exponent and leading mantissa bits that slowly varying values share, leaving
+//! long runs of zero bytes for LZ4 or Zstd to squeeze.
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{ColumnPayload, Error, Metadata};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Codec {
+    /// Fast enough to run on every telemetry packet.
+    Lz4,
+    /// Slower, but smaller, which suits recordings.
+    Zstd,
+}
+
+/// How a column's values are compressed, written in metadata as e.g. `"xor+zstd"`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Compression {
+    /// XOR each value with the previous one before compressing.
+    pub xor: bool,
+    pub codec: Option<Codec>,
+}
+
+impl Compression {
+    pub const LZ4: Compression = Compression {
+        xor: false,
+        codec: Some(Codec::Lz4),
+    };
+    pub const ZSTD: Compression = Compression {
+        xor: false,
+        codec: Some(Codec::Zstd),
+    };
+
+    pub fn xor(mut self) -> Self {
+        self.xor = true;
+        self
+    }
+}
+
+impl FromStr for Compression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut compression = Compression::default();
+        for part in s.split('+').map(str::trim) {
+            match part.to_ascii_lowercase().as_str() {
+                "xor" => compression.xor = true,
+                "lz4" => compression.codec = Some(Codec::Lz4),
+                "zstd" => compression.codec = Some(Codec::Zstd),
+                "" | "none" => {}
+                _ => return Err(Error::ParsingError),
+            }
+        }
+        Ok(compression)
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let codec = match self.codec {
+            Some(Codec::Lz4) => "lz4",
+            Some(Codec::Zstd) => "zstd",
+            None => "none",
+        };
+        if self.xor {
+            write!(f, "xor+{codec}")
+        } else {
+            write!(f, "{codec}")
+        }
+    }
+}
+
+/// XORs every `stride` byte long value in `buf` with the one before it, in place.
+pub fn xor_encode(buf: &mut [u8], stride: usize) {
+    if stride == 0 {
+        return;
+    }
+    // back to front, so each value is XORed with the original previous value
+    for i in (stride..buf.len()).rev() {
+        buf[i] ^= buf[i - stride];
+    }
+}
+
+/// Undoes [`xor_encode`].
+pub fn xor_decode(buf: &mut [u8], stride: usize) {
+    if stride == 0 {
+        return;
+    }
+    for i in stride..buf.len() {
+        buf[i] ^= buf[i - stride];
+    }
+}
+
+/// Compresses `buf`, holding values `stride` bytes long.
+pub fn compress(buf: &[u8], compression: Compression, stride: usize) -> Result<Vec<u8>, Error> {
+    let mut buf = buf.to_vec();
+    if compression.xor {
+        xor_encode(&mut buf, stride);
+    }
+    match compression.codec {
+        #[cfg(feature = "compression")]
+        Some(Codec::Lz4) => Ok(lz4::block::compress(&buf, None, true)?),
+        #[cfg(feature = "compression")]
+        Some(Codec::Zstd) => Ok(zstd::bulk::compress(&buf, 0)?),
+        #[cfg(not(feature = "compression"))]
+        Some(_) => Err(Error::CodecUnavailable),
+        None => Ok(buf),
+    }
+}
+
+/// Undoes [`compress`].
+pub fn decompress(buf: &[u8], compression: Compression, stride: usize) -> Result<Vec<u8>, Error> {
+    let mut buf = match compression.codec {
+        #[cfg(feature = "compression")]
+        Some(Codec::Lz4) => lz4::block::decompress(buf, None)?,
+        #[cfg(feature = "compression")]
+        Some(Codec::Zstd) => zstd::stream::decode_all(buf)?,
+        #[cfg(not(feature = "compression"))]
+        Some(_) => return Err(Error::CodecUnavailable),
+        None => buf.to_vec(),
+    };
+    if compression.xor {
+        xor_decode(&mut buf, stride);
+    }
+    Ok(buf)
+}
+
+impl<B: AsRef<[u8]> + From<Vec<u8>>> ColumnPayload<B> {
+    /// Decompresses the values of a column sent by a component with `metadata`.
+    pub fn decompress(self, metadata: &Metadata) -> Result<Self, Error> {
+        let Some(compression) = metadata.compression() else {
+            return Ok(self);
+        };
+        let stride = metadata.component_type.size();
+        let value_buf = decompress(self.value_buf.as_ref(), compression, stride)?;
+        Ok(ColumnPayload {
+            value_buf: value_buf.into(),
+            ..self
+        })
+    }
+}
+
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_roundtrip() {
+        // a slowly drifting signal, like a position sampled every tick
+        let values = (0..1000)
+            .map(|i| 7.0e6 + (i as f64 * 1e-3).sin())
+            .collect::<Vec<f64>>();
+        let buf: &[u8] = bytemuck::cast_slice(&values);
+        for s in ["lz4", "zstd", "xor+lz4", "xor+zstd", "xor"] {
+            let compression = s.parse::<Compression>().unwrap();
+            assert_eq!(
+                compression.to_string().parse::<Compression>().unwrap(),
+                compression
+            );
+            let packed = compress(buf, compression, 8).unwrap();
+            assert_eq!(decompress(&packed, compression, 8).unwrap(), buf);
+        }
+
+        let raw = compress(buf, Compression::ZSTD, 8).unwrap();
+        let xor = compress(buf, Compression::ZSTD.xor(), 8).unwrap();
+        assert!(xor.len() < raw.len());
+        assert_eq!(
+            "XOR+zstd".parse::<Compression>().unwrap(),
+            Compression::ZSTD.xor()
+        );
+        assert!("gzip".parse::<Compression>().is_err());
+    }
+
+    #[test]
+    fn test_demux_decompresses() {
+        use crate::client::{Demux, Msg};
+        use crate::{ComponentType, Packet, Payload, PrimitiveTy, StreamId};
+
+        let mut metadata = Metadata {
+            name: "altitude".into(),
+            component_type: ComponentType {
+                primitive_ty: PrimitiveTy::F64,
+                shape: Default::default(),
+            },
+            tags: None,
+            asset: false,
+        };
+        metadata.set_compression(Compression::LZ4.xor());
+        let values: &[u8] = bytemuck::cast_slice(&[100.0f64, 100.5, 101.0]);
+        let value_buf = compress(values, Compression::LZ4.xor(), 8).unwrap();
+
+        let mut demux = Demux::default();
+        let stream_id = StreamId(7);
+        demux
+            .handle(Packet::<Payload<bytes::Bytes>>::start_stream(
+                stream_id, metadata,
+            ))
+            .unwrap();
+        let packet = Packet {
+            stream_id,
+            payload: Payload::Column(ColumnPayload {
+                time: 0,
+                len: 3,
+                entity_buf: bytemuck::cast_slice(&[0u64, 1, 2]).to_vec().into(),
+                value_buf: value_buf.into(),
+            }),
+        };
+        let Msg::Column(col) = demux.handle(packet).unwrap() else {
+            panic!("expected a column");
+        };
+        assert_eq!(&col.payload.value_buf[..], values);
+    }
+}
@@ -45,6 +45,15 @@ pub enum Error {
     ComponentNotFound,
     #[error("asset not found")]
     AssetNotFound,
+    #[cfg(feature = "tls")]
+    #[error("tls {0}")]
+    Tls(#[from] tokio_rustls::rustls::Error),
+    #[error("access config: {0}")]
+    AccessConfig(String),
+    #[error("unauthorized")]
+    Unauthorized,
+    #[error("the compression feature is needed for lz4 and zstd")]
+    CodecUnavailable,
 }
 
 impl From<try_buf::ErrorKind> for Error {
@@ -0,0 +1,182 @@
+//! The order a column's values are laid out in, chosen per archetype with
+//! [`crate::Archetype::layout`].
+//!
+//! Host columns, and the buffers handed to XLA, are always [`ColumnLayout::EntityMajor`], since XLA
+//! expects an entity-major `[N, ...]` shape and anything else would add a transpose to every tick.
+//! The layout only applies to the ticks recorded in [`World::history`], which is where a long run's
+//! data lives. Every reader still sees entity-major columns: [`World::column_at_tick`] and
+//! [`World::polars`] convert back on the way out.
+use std::borrow::Cow;
+
+use crate::world::{Buffers, ColumnRef, World};
+use crate::{ComponentId, ComponentType, Error};
+
+/// The order a column's values are laid out in.
+///
+/// [`ColumnLayout::EntityMajor`] places each entity's whole component value after the previous one.
+/// [`ColumnLayout::ElementMajor`] instead keeps each element of the component contiguous across every
+/// entity, which suits consumers that only read a few elements of a large number of entities, like
+/// plotting one axis of a swarm's positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColumnLayout {
+    #[default]
+    EntityMajor,
+    ElementMajor,
+}
+
+/// Converts `buf`, holding values of `ty`, from the `from` layout to the `to` layout.
+pub fn convert_layout<'a>(
+    buf: &'a [u8],
+    ty: &ComponentType,
+    from: ColumnLayout,
+    to: ColumnLayout,
+) -> Cow<'a, [u8]> {
+    let elem_size = ty.primitive_ty.size();
+    let elems = ty.size() / elem_size;
+    let len = buf.len().checked_div(ty.size()).unwrap_or(0);
+    if from == to || elems <= 1 || len <= 1 {
+        return Cow::Borrowed(buf);
+    }
+    // a row-major transpose of a `rows x cols` matrix of elements
+    let (rows, cols) = match from {
+        ColumnLayout::EntityMajor => (len, elems),
+        ColumnLayout::ElementMajor => (elems, len),
+    };
+    let mut out = vec![0; buf.len()];
+    for row in 0..rows {
+        for col in 0..cols {
+            let src = (row * cols + col) * elem_size;
+            let dst = (col * rows + row) * elem_size;
+            out[dst..dst + elem_size].copy_from_slice(&buf[src..src + elem_size]);
+        }
+    }
+    Cow::Owned(out)
+}
+
+impl World {
+    /// The layout `id`'s column is recorded in, the one its archetype chose.
+    pub fn layout(&self, id: ComponentId) -> ColumnLayout {
+        self.component_map
+            .get(&id)
+            .and_then(|(archetype_name, _)| self.layouts.get(archetype_name))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The host columns, each in its archetype's layout, ready to be pushed onto the history.
+    pub(crate) fn host_snapshot(&self) -> Buffers {
+        self.host
+            .iter()
+            .map(|(id, buf)| (*id, self.record_column(*id, buf).into_owned()))
+            .collect()
+    }
+
+    /// Converts an entity-major column of `id` to the layout it's recorded in.
+    pub(crate) fn record_column<'a>(&self, id: ComponentId, buf: &'a [u8]) -> Cow<'a, [u8]> {
+        match self.component_map.get(&id) {
+            Some((_, metadata)) => convert_layout(
+                buf,
+                &metadata.component_type,
+                ColumnLayout::EntityMajor,
+                self.layout(id),
+            ),
+            None => Cow::Borrowed(buf),
+        }
+    }
+
+    /// Converts a recorded column of `id` back to entity-major.
+    pub(crate) fn read_recorded<'a>(&self, id: ComponentId, buf: &'a [u8]) -> Cow<'a, [u8]> {
+        match self.component_map.get(&id) {
+            Some((_, metadata)) => convert_layout(
+                buf,
+                &metadata.component_type,
+                self.layout(id),
+                ColumnLayout::EntityMajor,
+            ),
+            None => Cow::Borrowed(buf),
+        }
+    }
+
+    /// A recorded tick with every column converted back to entity-major.
+    pub(crate) fn recorded_state<'a>(&self, buffers: &'a Buffers) -> Cow<'a, Buffers> {
+        if self
+            .layouts
+            .values()
+            .all(|l| *l == ColumnLayout::EntityMajor)
+        {
+            return Cow::Borrowed(buffers);
+        }
+        Cow::Owned(
+            buffers
+                .iter()
+                .map(|(id, buf)| (*id, self.read_recorded(*id, buf).into_owned()))
+                .collect(),
+        )
+    }
+}
+
+impl<'a, B: 'a + AsRef<[u8]>> ColumnRef<'a, B> {
+    /// Copies the column out in `layout`.
+    pub fn to_layout(&self, layout: ColumnLayout) -> Vec<u8> {
+        convert_layout(
+            self.column.as_ref(),
+            &self.metadata.component_type,
+            ColumnLayout::EntityMajor,
+            layout,
+        )
+        .into_owned()
+    }
+}
+
+impl<'a> ColumnRef<'a, &'a mut Vec<u8>> {
+    /// Overwrites the whole column with `buf`, given in `layout`.
+    pub fn copy_from_layout(&mut self, buf: &[u8], layout: ColumnLayout) -> Result<(), Error> {
+        if buf.len() != self.column.len() {
+            return Err(Error::ValueSizeMismatch);
+        }
+        let buf = convert_layout(
+            buf,
+            &self.metadata.component_type,
+            layout,
+            ColumnLayout::EntityMajor,
+        );
+        self.column.copy_from_slice(&buf);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrimitiveTy;
+
+    #[test]
+    fn test_convert_layout() {
+        let ty = ComponentType {
+            primitive_ty: PrimitiveTy::U16,
+            shape: smallvec::smallvec![3],
+        };
+        let entity_major: Vec<u8> = [1u16, 2, 3, 4, 5, 6]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let element_major = convert_layout(
+            &entity_major,
+            &ty,
+            ColumnLayout::EntityMajor,
+            ColumnLayout::ElementMajor,
+        );
+        let values: Vec<u16> = element_major
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        assert_eq!(values, [1, 4, 2, 5, 3, 6]);
+        let round_trip = convert_layout(
+            &element_major,
+            &ty,
+            ColumnLayout::ElementMajor,
+            ColumnLayout::EntityMajor,
+        );
+        assert_eq!(round_trip, entity_major);
+    }
+}
@@ -5,10 +5,14 @@ extern crate alloc;
 #[cfg(feature = "bevy")]
 pub mod bevy;
 #[cfg(feature = "bevy")]
+pub mod bevy_capture;
+#[cfg(feature = "bevy")]
 pub mod bevy_sync;
 #[cfg(feature = "nox")]
 pub mod nox;
 
+#[cfg(feature = "tokio")]
+pub mod access;
 pub mod assets;
 pub mod client;
 pub mod error;
@@ -36,6 +40,21 @@ pub use const_fnv1a_hash;
 pub use bytes;
 pub use ndarray;
 
+#[cfg(feature = "std")]
+pub mod compress;
+#[cfg(feature = "std")]
+pub mod quantize;
+
+#[cfg(feature = "std")]
+mod layout;
+#[cfg(feature = "std")]
+pub use layout::*;
+
+#[cfg(feature = "std")]
+mod retention;
+#[cfg(feature = "std")]
+pub use retention::*;
+
 #[cfg(feature = "std")]
 mod world;
 #[cfg(feature = "std")]
@@ -1,7 +1,7 @@
 use core::mem;
 use nox::{
-    Array, ArrayDim, ArrayRepr, ConstDim, Dim, Field, OwnedRepr, Quaternion, SpatialForce,
-    SpatialInertia, SpatialMotion, SpatialTransform, Tensor,
+    Array, ArrayDim, ArrayRepr, ConstDim, Dim, Field, OrbitalElements, OwnedRepr, Quaternion,
+    SpatialForce, SpatialInertia, SpatialMotion, SpatialTransform, Tensor,
 };
 use nox::{Const, Dyn};
 use smallvec::{smallvec, SmallVec};
@@ -250,6 +250,42 @@ where
     }
 }
 
+impl<T: Field + PrimitiveTyElement, R: OwnedRepr> Component for OrbitalElements<T, R> {
+    const NAME: &'static str = concat_str!("orbital_elements_", T::PRIMITIVE_TY.display_str());
+    fn component_type() -> ComponentType {
+        ComponentType {
+            primitive_ty: T::PRIMITIVE_TY,
+            shape: smallvec![6],
+        }
+    }
+}
+
+impl<T: Field + PrimitiveTyElement> ValueRepr for OrbitalElements<T, ArrayRepr>
+where
+    Array<T, Const<6>>: ValueRepr,
+{
+    type ValueDim = <Array<T, Const<6>> as ValueRepr>::ValueDim;
+
+    fn fixed_dim_component_value(&self) -> ComponentValue<'_, Self::ValueDim> {
+        self.inner.fixed_dim_component_value()
+    }
+
+    fn component_value(&self) -> ComponentValue<'_> {
+        self.inner.component_value()
+    }
+
+    fn from_component_value<Dim: ndarray::Dimension>(
+        value: crate::ComponentValue<'_, Dim>,
+    ) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        Some(OrbitalElements {
+            inner: Tensor::from_component_value(value)?,
+        })
+    }
+}
+
 impl<T: Field + PrimitiveTyElement, R: OwnedRepr> Component for nox::SpatialInertia<T, R> {
     const NAME: &'static str = concat_str!("spatial_inertia_", T::PRIMITIVE_TY.display_str());
     fn component_type() -> ComponentType {
@@ -11,6 +11,10 @@ use std::collections::HashMap;
 use std::time::Duration;
 use std::{fs::File, path::Path};
 
+#[cfg(feature = "compression")]
+use crate::compress::Codec;
+use crate::compress::{xor_decode, xor_encode};
+use crate::quantize::{dequantize, quantize};
 use crate::world::{Buffers, ColumnRef, TimeStep, World};
 use crate::{ArchetypeName, ComponentId, ComponentType, EntityId, Error, Metadata, PrimitiveTy};
 
@@ -21,15 +25,27 @@ impl<'a, B: 'a + AsRef<[u8]>> ColumnRef<'a, B> {
 }
 
 impl World {
+    /// The ticks kept by the world's [`crate::Retention`] as data frames.
     pub fn polars(&self) -> Result<PolarsWorld, Error> {
-        let host = self.history.is_empty().then_some(&self.host);
+        let history = self
+            .history
+            .iter()
+            .map(|buffers| self.recorded_state(buffers))
+            .collect::<Vec<_>>();
+        let host = self.history.is_empty().then_some((self.tick, &self.host));
+        let states = self
+            .history_ticks
+            .iter()
+            .copied()
+            .zip(history.iter().map(|buffers| &**buffers))
+            .chain(host)
+            .collect::<Vec<_>>();
         PolarsWorld::new(
             &self.component_map,
             self.run_time_step.0,
             self.sim_time_step.0,
             &self.entity_ids,
-            &self.history,
-            host,
+            &states,
             self.default_playback_speed,
             self.max_tick,
         )
@@ -92,8 +108,7 @@ impl PolarsWorld {
         run_time_step: Duration,
         sim_time_step: Duration,
         entity_ids: &ustr::UstrMap<Vec<u8>>,
-        history: &[Buffers],
-        host: Option<&Buffers>,
+        states: &[(u64, &Buffers)],
         default_playback_speed: f64,
         max_ticks: u64,
     ) -> Result<Self, Error> {
@@ -107,15 +122,16 @@ impl PolarsWorld {
                 archetype_metadata
             },
         );
-        let ticks = history.len() + host.iter().len();
+        let ticks = states.len();
         let mut archetypes = ustr::UstrMap::default();
         for (archetype_name, components) in archetype_metadata.iter() {
             let entity_buf = &entity_ids[archetype_name];
             let len = entity_buf.len() / std::mem::size_of::<EntityId>();
 
             let entity_series = to_series(&entity_buf.repeat(ticks), &EntityId::metadata())?;
-            let tick_series = (0..ticks)
-                .flat_map(|tick| std::iter::repeat(tick as u64).take(len))
+            let tick_series = states
+                .iter()
+                .flat_map(|(tick, _)| std::iter::repeat(*tick).take(len))
                 .collect::<Series>()
                 .with_name("tick");
 
@@ -123,10 +139,9 @@ impl PolarsWorld {
                 .iter()
                 .map(|metadata| {
                     let component_id = metadata.component_id();
-                    let buf = history
+                    let buf = states
                         .iter()
-                        .chain(host.into_iter())
-                        .map(|buffers| buffers[&component_id].as_slice())
+                        .map(|(_, buffers)| buffers[&component_id].as_slice())
                         .collect::<Vec<&[u8]>>()
                         .concat();
                     to_series(&buf, metadata)
@@ -177,20 +192,30 @@ impl PolarsWorld {
             .collect()
     }
 
+    /// The state at every tick, with ticks dropped by a [`crate::Retention`] holding the kept tick before them.
     pub fn history(&self) -> Result<Vec<Buffers>, Error> {
         let ticks = self.tick() + 1;
         let mut history = std::iter::repeat_with(Buffers::default)
             .take(ticks as usize)
             .collect::<Vec<_>>();
         for df in self.archetypes.values() {
+            let mut kept = df
+                .column("tick")?
+                .u64()?
+                .into_no_null_iter()
+                .collect::<Vec<_>>();
+            kept.dedup();
             df.get_columns()
                 .iter()
                 .filter(|s| s.name() != "tick" && s.name() != EntityId::NAME)
                 .for_each(|series| {
                     let component_id = ComponentId::new(series.name());
                     let buf = series.to_bytes();
-                    for (index, chunk) in buf.chunks_exact(buf.len() / ticks as usize).enumerate() {
-                        history[index].insert(component_id, chunk.to_vec());
+                    for (index, chunk) in buf.chunks_exact(buf.len() / kept.len()).enumerate() {
+                        let end = kept.get(index + 1).copied().unwrap_or(ticks);
+                        for tick in kept[index]..end {
+                            history[tick as usize].insert(component_id, chunk.to_vec());
+                        }
                     }
                 });
         }
@@ -205,7 +230,17 @@ impl PolarsWorld {
         for (archetype_name, df) in &mut self.archetypes {
             let path = path.join(format!("{}.parquet", archetype_name));
             let file = std::fs::File::create(&path)?;
-            ParquetWriter::new(file).finish(df)?;
+            let components = &self.metadata.archetypes[archetype_name];
+            let writer =
+                ParquetWriter::new(file).with_compression(parquet_compression(components)?);
+            if components
+                .iter()
+                .any(|m| m.quantization().is_some() || m.compression().is_some())
+            {
+                writer.finish(&mut encode_df(df, components)?)?;
+            } else {
+                writer.finish(df)?;
+            }
         }
         Ok(())
     }
@@ -218,7 +253,8 @@ impl PolarsWorld {
         for name in metadata.archetypes.keys() {
             let path = path.join(format!("{}.parquet", name));
             let file = File::open(&path)?;
-            let df = polars::prelude::ParquetReader::new(file).finish()?;
+            let mut df = polars::prelude::ParquetReader::new(file).finish()?;
+            decode_df(&mut df, &metadata.archetypes[name])?;
             archetypes.insert(*name, df);
         }
         Ok(Self {
@@ -282,6 +318,93 @@ pub fn to_series(buf: &[u8], metadata: &Metadata) -> Result<Series, Error> {
     Series::from_arrow(&metadata.name, array).map_err(Error::from)
 }
 
+/// Replaces the columns of quantized components with integer columns, see [`crate::quantize`], and XOR encodes
+/// each tick of a column against the one before it where asked to, see [`crate::compress`].
+fn encode_df(df: &DataFrame, components: &[Metadata]) -> Result<DataFrame, Error> {
+    let mut df = df.clone();
+    let ticks = df.column("tick")?.n_unique()?.max(1);
+    for metadata in components {
+        let xor = metadata.compression().is_some_and(|c| c.xor);
+        let step = metadata.quantization();
+        if !xor && step.is_none() {
+            continue;
+        }
+        let mut stored = metadata.clone();
+        let mut buf = df.column(&metadata.name)?.to_bytes();
+        if let Some(step) = step {
+            let steps = quantize(&buf, metadata.component_type.primitive_ty, step)
+                .expect("only floats are quantized");
+            buf = bytemuck::cast_slice(&steps).to_vec();
+            stored.component_type.primitive_ty = PrimitiveTy::I64;
+        }
+        if xor {
+            xor_encode(&mut buf, buf.len() / ticks);
+            stored.component_type.primitive_ty = xor_ty(stored.component_type.primitive_ty);
+        }
+        df.with_column(to_series(&buf, &stored)?)?;
+    }
+    Ok(df)
+}
+
+fn decode_df(df: &mut DataFrame, components: &[Metadata]) -> Result<(), Error> {
+    let ticks = df.column("tick")?.n_unique()?.max(1);
+    for metadata in components {
+        let xor = metadata.compression().is_some_and(|c| c.xor);
+        let step = metadata.quantization();
+        if !xor && step.is_none() {
+            continue;
+        }
+        let mut buf = df.column(&metadata.name)?.to_bytes();
+        if xor {
+            xor_decode(&mut buf, buf.len() / ticks);
+        }
+        if let Some(step) = step {
+            let steps = bytemuck::pod_collect_to_vec::<u8, i64>(&buf);
+            buf = dequantize(&steps, metadata.component_type.primitive_ty, step)
+                .expect("only floats are quantized");
+        }
+        df.with_column(to_series(&buf, metadata)?)?;
+    }
+    Ok(())
+}
+
+/// XOR encoded floats are no longer meaningful as floats, so they're stored as integers of the same width.
+fn xor_ty(ty: PrimitiveTy) -> PrimitiveTy {
+    match ty {
+        PrimitiveTy::F64 => PrimitiveTy::U64,
+        PrimitiveTy::F32 => PrimitiveTy::U32,
+        ty => ty,
+    }
+}
+
+/// The codec of an archetype's parquet file, Zstd if any of its components asks for it, else LZ4 if any do.
+#[cfg(feature = "compression")]
+fn parquet_compression(components: &[Metadata]) -> Result<ParquetCompression, Error> {
+    let codecs = components
+        .iter()
+        .filter_map(|m| m.compression()?.codec)
+        .collect::<Vec<_>>();
+    if codecs.contains(&Codec::Zstd) {
+        Ok(ParquetCompression::Zstd(None))
+    } else if codecs.contains(&Codec::Lz4) {
+        Ok(ParquetCompression::Lz4Raw)
+    } else {
+        Ok(ParquetCompression::default())
+    }
+}
+
+/// Without the compression feature only XOR encoding is available, so components asking for a codec are an error.
+#[cfg(not(feature = "compression"))]
+fn parquet_compression(components: &[Metadata]) -> Result<ParquetCompression, Error> {
+    if components
+        .iter()
+        .any(|m| m.compression().is_some_and(|c| c.codec.is_some()))
+    {
+        return Err(Error::CodecUnavailable);
+    }
+    Ok(ParquetCompression::default())
+}
+
 fn prim_array<T: polars_arrow::types::NativeType>(buf: &[u8]) -> Box<dyn Array> {
     let buf = bytemuck::cast_slice::<_, T>(buf);
     Box::new(PrimitiveArray::from_slice(buf))
@@ -0,0 +1,70 @@
+//! Lossy quantization of floating point components, configured per component with [`crate::Metadata::set_quantization`].
+//!
+//! Quantized values are stored as integer multiples of the quantization step, which compress far better
+//! than raw floats. The step is kept in the component's metadata, so readers can always dequantize.
+use crate::PrimitiveTy;
+
+/// Converts a buffer of `ty` values into integer multiples of `step`, or returns `None` if `ty` isn't a float.
+pub fn quantize(buf: &[u8], ty: PrimitiveTy, step: f64) -> Option<Vec<i64>> {
+    let steps = match ty {
+        PrimitiveTy::F64 => bytemuck::pod_collect_to_vec::<u8, f64>(buf)
+            .into_iter()
+            .map(|v| (v / step).round() as i64)
+            .collect(),
+        PrimitiveTy::F32 => bytemuck::pod_collect_to_vec::<u8, f32>(buf)
+            .into_iter()
+            .map(|v| (v as f64 / step).round() as i64)
+            .collect(),
+        _ => return None,
+    };
+    Some(steps)
+}
+
+/// Converts integer multiples of `step` back into a buffer of `ty` values.
+pub fn dequantize(steps: &[i64], ty: PrimitiveTy, step: f64) -> Option<Vec<u8>> {
+    let buf = match ty {
+        PrimitiveTy::F64 => {
+            let values = steps.iter().map(|s| *s as f64 * step).collect::<Vec<_>>();
+            bytemuck::cast_slice(&values).to_vec()
+        }
+        PrimitiveTy::F32 => {
+            let values = steps
+                .iter()
+                .map(|s| (*s as f64 * step) as f32)
+                .collect::<Vec<_>>();
+            bytemuck::cast_slice(&values).to_vec()
+        }
+        _ => return None,
+    };
+    Some(buf)
+}
+
+/// Rounds every value in `buf` to the nearest multiple of `step` in place, so it matches what a reader would dequantize.
+pub fn snap(buf: &mut [u8], ty: PrimitiveTy, step: f64) {
+    if let Some(snapped) = quantize(buf, ty, step).and_then(|steps| dequantize(&steps, ty, step)) {
+        buf.copy_from_slice(&snapped);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantize_roundtrip() {
+        let values = [1.23456f64, -0.0004, 2000.0];
+        let buf = bytemuck::cast_slice(&values).to_vec();
+        let steps = quantize(&buf, PrimitiveTy::F64, 1e-3).unwrap();
+        assert_eq!(steps, vec![1235, 0, 2_000_000]);
+        let out = dequantize(&steps, PrimitiveTy::F64, 1e-3).unwrap();
+        let out: &[f64] = bytemuck::cast_slice(&out);
+        for (a, b) in out.iter().zip(values) {
+            assert!((a - b).abs() <= 0.5e-3);
+        }
+
+        let mut snapped = buf.clone();
+        snap(&mut snapped, PrimitiveTy::F64, 1e-3);
+        assert_eq!(snapped, dequantize(&steps, PrimitiveTy::F64, 1e-3).unwrap());
+        assert!(quantize(&buf, PrimitiveTy::U64, 1e-3).is_none());
+    }
+}
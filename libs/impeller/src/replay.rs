@@ -8,8 +8,8 @@ use tracing::warn;
 
 use crate::client::{ColumnMsg, MsgPair};
 use crate::{
-    client::Msg, query::MetadataStore, world::World, ColumnPayload, ComponentId, ControlMsg,
-    EntityId, Error, Handle, Metadata, Packet, Payload, Query, StreamId,
+    client::Msg, compress, quantize, query::MetadataStore, world::World, ColumnPayload,
+    ComponentId, ControlMsg, EntityId, Error, Handle, Metadata, Packet, Payload, Query, StreamId,
 };
 
 #[derive(Debug, Clone)]
@@ -390,17 +390,19 @@ fn send_sub(
         }
     } else {
         let packet = if entity_ids.is_empty() {
+            let mut value_buf = col.column.to_vec();
+            snap_quantized(&mut value_buf, col.metadata);
             Packet {
                 stream_id: sub.stream_id,
                 payload: Payload::Column(ColumnPayload {
                     time: tick,
                     len: col.len() as u32,
-                    entity_buf: col.entities.clone().into(),
-                    value_buf: col.column.clone().into(),
+                    entity_buf: col.entities.to_vec().into(),
+                    value_buf: compress_values(value_buf.into(), col.metadata)?,
                 }),
             }
         } else {
-            let col_entity_ids: &[EntityId] = bytemuck::cast_slice(col.entities);
+            let col_entity_ids: &[EntityId] = bytemuck::cast_slice(&col.entities);
             let mut entity_iter = col_entity_ids.iter();
             let mut entity_buf = BytesMut::with_capacity(mem::size_of::<u64>() * entity_ids.len());
             let comp_size = col.metadata.component_type.size();
@@ -415,13 +417,14 @@ fn send_sub(
                 value_buf
                     .extend_from_slice(&col.column[index * comp_size..(index + 1) * comp_size]);
             }
+            snap_quantized(&mut value_buf, col.metadata);
             Packet {
                 stream_id: sub.stream_id,
                 payload: Payload::Column(ColumnPayload {
                     time: tick,
                     len: len as u32,
                     entity_buf: entity_buf.freeze(),
-                    value_buf: value_buf.freeze(),
+                    value_buf: compress_values(value_buf.freeze(), col.metadata)?,
                 }),
             }
         };
@@ -431,3 +434,19 @@ fn send_sub(
     }
     Ok(())
 }
+
+/// Snaps the values of quantized components to their quantization step, so live telemetry matches recordings.
+fn snap_quantized(buf: &mut [u8], metadata: &Metadata) {
+    if let Some(step) = metadata.quantization() {
+        quantize::snap(buf, metadata.component_type.primitive_ty, step);
+    }
+}
+
+/// Compresses the values of components that ask for it, see [`crate::compress`].
+fn compress_values(buf: Bytes, metadata: &Metadata) -> Result<Bytes, Error> {
+    let Some(compression) = metadata.compression() else {
+        return Ok(buf);
+    };
+    let stride = metadata.component_type.size();
+    Ok(compress::compress(&buf, compression, stride)?.into())
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::world::Buffers;
+use crate::{ArchetypeName, ComponentId, Metadata, PrimitiveTy};
+
+/// Which ticks of a run stay in [`crate::World::history`], and so make it into a recording, so
+/// long runs stay a manageable size.
+///
+/// Every tick is kept by default. With decimation only the first `keep_first` ticks and every
+/// `every`th tick after them are kept, along with any tick where a value moved by more than
+/// `threshold` since the last kept one, so short transients survive. The first and last ticks are
+/// always kept, and a dropped tick reads back as the kept tick before it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Retention {
+    pub keep_first: u64,
+    pub every: u64,
+    /// The absolute change in any numeric value that keeps a tick regardless of decimation.
+    pub threshold: Option<f64>,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            keep_first: 0,
+            every: 1,
+            threshold: None,
+        }
+    }
+}
+
+impl Retention {
+    /// Keeps every `every`th tick.
+    pub fn every(every: u64) -> Self {
+        Self {
+            every,
+            ..Default::default()
+        }
+    }
+
+    pub fn keep_first(mut self, ticks: u64) -> Self {
+        self.keep_first = ticks;
+        self
+    }
+
+    pub fn on_change(mut self, threshold: f64) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    pub fn keeps_all(&self) -> bool {
+        self.every <= 1
+    }
+
+    /// Whether `tick`, holding `state`, is kept once a later tick is recorded, given the state of
+    /// the last kept tick before it.
+    pub fn keeps(
+        &self,
+        tick: u64,
+        state: &Buffers,
+        last_kept: Option<&Buffers>,
+        component_map: &HashMap<ComponentId, (ArchetypeName, Metadata)>,
+    ) -> bool {
+        if self.keeps_all() || tick < self.keep_first || (tick - self.keep_first) % self.every == 0
+        {
+            return true;
+        }
+        match (self.threshold, last_kept) {
+            (Some(threshold), Some(last)) => moved(last, state, component_map, threshold),
+            _ => false,
+        }
+    }
+
+    /// The indices of the `states` to record, in order.
+    pub fn kept(
+        &self,
+        states: &[&Buffers],
+        component_map: &HashMap<ComponentId, (ArchetypeName, Metadata)>,
+    ) -> Vec<usize> {
+        if self.keeps_all() {
+            return (0..states.len()).collect();
+        }
+        let mut kept: Vec<usize> = vec![];
+        for (tick, state) in states.iter().enumerate() {
+            let last_kept = kept.last().map(|index| states[*index]);
+            if tick + 1 == states.len() || self.keeps(tick as u64, state, last_kept, component_map)
+            {
+                kept.push(tick);
+            }
+        }
+        kept
+    }
+}
+
+/// Whether any value in `to` differs from `from` by more than `threshold`.
+fn moved(
+    from: &Buffers,
+    to: &Buffers,
+    component_map: &HashMap<ComponentId, (ArchetypeName, Metadata)>,
+    threshold: f64,
+) -> bool {
+    to.iter().any(|(id, to)| {
+        let Some(from) = from.get(id) else {
+            return true;
+        };
+        let ty = component_map
+            .get(id)
+            .map(|(_, metadata)| metadata.component_type.primitive_ty);
+        match ty.and_then(|ty| Some((values(from, ty)?, values(to, ty)?))) {
+            Some((from, to)) if from.len() == to.len() => {
+                from.iter().zip(&to).any(|(a, b)| (a - b).abs() > threshold)
+            }
+            _ => from != to,
+        }
+    })
+}
+
+fn values(buf: &[u8], ty: PrimitiveTy) -> Option<Vec<f64>> {
+    fn cast<T: bytemuck::Pod + Into<f64>>(buf: &[u8]) -> Vec<f64> {
+        bytemuck::pod_collect_to_vec::<u8, T>(buf)
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+    let values = match ty {
+        PrimitiveTy::F64 => cast::<f64>(buf),
+        PrimitiveTy::F32 => cast::<f32>(buf),
+        PrimitiveTy::U8 => cast::<u8>(buf),
+        PrimitiveTy::U16 => cast::<u16>(buf),
+        PrimitiveTy::U32 => cast::<u32>(buf),
+        PrimitiveTy::I8 => cast::<i8>(buf),
+        PrimitiveTy::I16 => cast::<i16>(buf),
+        PrimitiveTy::I32 => cast::<i32>(buf),
+        PrimitiveTy::U64 => bytemuck::pod_collect_to_vec::<u8, u64>(buf)
+            .into_iter()
+            .map(|v| v as f64)
+            .collect(),
+        PrimitiveTy::I64 => bytemuck::pod_collect_to_vec::<u8, i64>(buf)
+            .into_iter()
+            .map(|v| v as f64)
+            .collect(),
+        PrimitiveTy::Bool => return None,
+    };
+    Some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ComponentType, World};
+
+    #[test]
+    fn test_retention() {
+        let id = ComponentId::new("x");
+        let metadata = Metadata {
+            name: "x".into(),
+            component_type: ComponentType {
+                primitive_ty: PrimitiveTy::F64,
+                shape: smallvec::smallvec![],
+            },
+            tags: None,
+            asset: false,
+        };
+        let component_map = HashMap::from([(id, (ArchetypeName::from("a"), metadata))]);
+        // flat, apart from a spike at tick 13
+        let states = (0..20)
+            .map(|tick| {
+                let x = if tick == 13 { 5.0 } else { tick as f64 * 1e-3 };
+                Buffers::from([(id, x.to_le_bytes().to_vec())])
+            })
+            .collect::<Vec<_>>();
+        let states = states.iter().collect::<Vec<_>>();
+
+        let all = Retention::default().kept(&states, &component_map);
+        assert_eq!(all, (0..20).collect::<Vec<_>>());
+        let decimated = Retention::every(5).keep_first(3);
+        assert_eq!(
+            decimated.kept(&states, &component_map),
+            [0, 1, 2, 3, 8, 13, 18, 19]
+        );
+        let adaptive = Retention::every(10).on_change(1.0);
+        assert_eq!(adaptive.kept(&states, &component_map), [0, 10, 13, 14, 19]);
+
+        // the world drops the same ticks from its history as they're recorded
+        let mut world = World {
+            component_map,
+            retention: Retention::every(5).keep_first(3),
+            ..Default::default()
+        };
+        world
+            .entity_ids
+            .insert(ArchetypeName::from("a"), 0u64.to_le_bytes().to_vec());
+        world.host = (*states[0]).clone();
+        world.ensure_history();
+        for state in &states[1..] {
+            world.host = (**state).clone();
+            world.written.insert(id);
+            world.advance_tick();
+        }
+        assert_eq!(world.history_ticks, [0, 1, 2, 3, 8, 13, 18, 19]);
+        assert_eq!(world.history.len(), world.history_ticks.len());
+        let held = world.column_at_tick(id, 10).unwrap();
+        assert_eq!(&*held.column, &states[8][&id][..]);
+        assert_eq!(held.last_changed, 8);
+    }
+}
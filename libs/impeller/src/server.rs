@@ -5,6 +5,7 @@ use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
 use tracing::{info_span, Instrument};
 
 use crate::{
+    access::Access,
     client::{AsyncClient, Msg, MsgPair},
     ControlMsg, Error, Packet, Payload,
 };
@@ -12,6 +13,7 @@ use crate::{
 pub struct TcpServer {
     tx: flume::Sender<MsgPair>,
     listener: tokio::net::TcpListener,
+    access: Access,
 }
 
 impl TcpServer {
@@ -21,28 +23,60 @@ impl TcpServer {
     ) -> Result<Self, Error> {
         tracing::info!(%addr, "listening");
         let listener = tokio::net::TcpListener::bind(addr).await?;
-        Ok(Self { tx, listener })
+        Ok(Self {
+            tx,
+            listener,
+            access: Access::default(),
+        })
+    }
+
+    /// Encrypts connections and turns away clients without the right token, see [`Access`].
+    pub fn with_access(mut self, access: Access) -> Self {
+        self.access = access;
+        self
     }
 
     pub async fn run(self) -> Result<(), Error> {
         loop {
             let (socket, addr) = self.listener.accept().await?;
             tracing::info!(%addr, "accepted connection");
-            let (rx_socket, tx_socket) = socket.into_split();
             tokio::spawn(
-                handle_socket(
-                    self.tx.clone(),
-                    tx_socket,
-                    rx_socket,
-                    iter::empty(),
-                    iter::empty(),
-                )
-                .instrument(info_span!("conn", %addr).or_current()),
+                handle_connection(self.tx.clone(), socket, self.access.clone())
+                    .instrument(info_span!("conn", %addr).or_current()),
             );
         }
     }
 }
 
+async fn handle_connection(
+    incoming_tx: flume::Sender<MsgPair>,
+    socket: tokio::net::TcpStream,
+    access: Access,
+) -> Result<(), Error> {
+    let stream = access.accept(socket).await.inspect_err(|err| {
+        tracing::warn!(?err, "tls handshake failed");
+    })?;
+    let (rx_socket, tx_socket) = tokio::io::split(stream);
+    let mut rx = FramedRead::new(
+        tokio::io::BufReader::with_capacity(0x8000, rx_socket),
+        LengthDelimitedCodec::new(),
+    );
+    access.authenticate(&mut rx).await.inspect_err(|err| {
+        tracing::warn!(?err, "rejected connection");
+    })?;
+    handle_stream_sink(
+        incoming_tx,
+        FramedWrite::new(
+            tokio::io::BufWriter::with_capacity(0x8000, tx_socket),
+            LengthDelimitedCodec::new(),
+        ),
+        rx,
+        iter::empty(),
+        iter::empty(),
+    )
+    .await
+}
+
 pub async fn handle_socket(
     incoming_tx: flume::Sender<MsgPair>,
     tx_socket: impl tokio::io::AsyncWrite + Unpin,
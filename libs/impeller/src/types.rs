@@ -11,6 +11,8 @@ use smallvec::SmallVec;
 use crate::query::MetadataStore;
 #[cfg(feature = "std")]
 use crate::world::World;
+#[cfg(feature = "std")]
+use crate::ColumnLayout;
 use crate::Handle;
 #[cfg(feature = "std")]
 type HashSet<T> = std::collections::HashSet<T>;
@@ -508,6 +510,11 @@ pub trait Archetype {
     fn name() -> ArchetypeName;
     fn components() -> Vec<Metadata>;
     fn insert_into_world(self, world: &mut World);
+
+    /// The layout the archetype's columns are recorded in.
+    fn layout() -> ColumnLayout {
+        ColumnLayout::EntityMajor
+    }
 }
 
 pub trait ValueRepr {
@@ -604,6 +611,11 @@ pub enum ControlMsg {
         time_range: Range<u64>,
         query: Query,
     },
+    /// The token a client presents before anything else to a server that requires one.
+    #[cfg(feature = "std")]
+    Auth {
+        token: String,
+    },
 }
 
 impl ControlMsg {
@@ -692,6 +704,104 @@ impl Metadata {
             .unwrap_or_default()
     }
 
+    /// Returns the SI unit of the component's values, e.g `"m/s"`.
+    pub fn unit(&self) -> Option<&str> {
+        self.tags
+            .as_ref()
+            .and_then(|t| t.get("unit"))
+            .and_then(TagValue::as_str)
+    }
+
+    pub fn set_unit(&mut self, unit: impl ToString) {
+        self.tags_mut()
+            .insert("unit".to_string(), TagValue::String(unit.to_string()));
+    }
+
+    /// Returns the human-readable label to display instead of the component name.
+    pub fn label(&self) -> Option<&str> {
+        self.tags
+            .as_ref()
+            .and_then(|t| t.get("label"))
+            .and_then(TagValue::as_str)
+    }
+
+    pub fn set_label(&mut self, label: impl ToString) {
+        self.tags_mut()
+            .insert("label".to_string(), TagValue::String(label.to_string()));
+    }
+
+    /// Returns the expected `(min, max)` range of the component's values, if either end is set.
+    /// A missing end is unbounded.
+    pub fn bounds(&self) -> Option<(f64, f64)> {
+        let min = self.f64_tag("min");
+        let max = self.f64_tag("max");
+        if min.is_none() && max.is_none() {
+            return None;
+        }
+        Some((
+            min.unwrap_or(f64::NEG_INFINITY),
+            max.unwrap_or(f64::INFINITY),
+        ))
+    }
+
+    pub fn set_bounds(&mut self, min: f64, max: f64) {
+        // tags must be hashable, so the bounds are stored in their string form
+        let tags = self.tags_mut();
+        tags.insert("min".to_string(), TagValue::String(min.to_string()));
+        tags.insert("max".to_string(), TagValue::String(max.to_string()));
+    }
+
+    /// Returns true if `value` lies outside of the expected range of the component.
+    pub fn is_out_of_bounds(&self, value: f64) -> bool {
+        self.bounds()
+            .map(|(min, max)| value < min || value > max)
+            .unwrap_or(false)
+    }
+
+    /// Returns the step the component's values are rounded to in recordings and telemetry, see [`crate::quantize`].
+    pub fn quantization(&self) -> Option<f64> {
+        let step = self.f64_tag("quantize")?;
+        let is_float = matches!(
+            self.component_type.primitive_ty,
+            PrimitiveTy::F64 | PrimitiveTy::F32
+        );
+        (is_float && step > 0.0 && step.is_finite()).then_some(step)
+    }
+
+    /// Sets the quantization step of the component, e.g `1e-3` to keep positions to the millimeter.
+    pub fn set_quantization(&mut self, step: f64) {
+        self.tags_mut()
+            .insert("quantize".to_string(), TagValue::String(step.to_string()));
+    }
+
+    /// Returns how the component's values are compressed in recordings and telemetry, see [`crate::compress`].
+    pub fn compression(&self) -> Option<crate::compress::Compression> {
+        self.tags
+            .as_ref()
+            .and_then(|t| t.get("compress"))
+            .and_then(TagValue::as_str)
+            .and_then(|s| s.parse().ok())
+    }
+
+    /// Compresses the component's values, e.g. `Compression::ZSTD.xor()` for a slowly changing float.
+    pub fn set_compression(&mut self, compression: crate::compress::Compression) {
+        self.tags_mut().insert(
+            "compress".to_string(),
+            TagValue::String(compression.to_string()),
+        );
+    }
+
+    fn f64_tag(&self, key: &str) -> Option<f64> {
+        self.tags
+            .as_ref()
+            .and_then(|t| t.get(key))
+            .and_then(|v| match v {
+                TagValue::Int(v) => Some(*v as f64),
+                TagValue::String(s) => s.parse().ok(),
+                _ => None,
+            })
+    }
+
     pub fn component_name(&self) -> &str {
         &self.name
     }
@@ -766,4 +876,46 @@ mod tests {
         assert_eq!(ty.to_string(), "f64:[3,4]");
         assert_eq!(shapeless_ty.to_string(), "f64");
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_metadata_display_tags() {
+        let mut metadata = Metadata {
+            name: "altitude".into(),
+            component_type: ComponentType::f64(),
+            tags: None,
+            asset: false,
+        };
+        assert_eq!(metadata.unit(), None);
+        assert_eq!(metadata.bounds(), None);
+        assert!(!metadata.is_out_of_bounds(1e9));
+
+        metadata.set_unit("m");
+        metadata.set_label("Altitude");
+        metadata.set_bounds(-100.0, 1.5e5);
+        assert_eq!(metadata.unit(), Some("m"));
+        assert_eq!(metadata.label(), Some("Altitude"));
+        assert_eq!(metadata.bounds(), Some((-100.0, 1.5e5)));
+        assert!(metadata.is_out_of_bounds(2e5));
+        assert!(!metadata.is_out_of_bounds(0.0));
+
+        metadata.tags_mut().remove("min");
+        metadata
+            .tags_mut()
+            .insert("max".to_string(), TagValue::Int(10));
+        assert_eq!(metadata.bounds(), Some((f64::NEG_INFINITY, 10.0)));
+
+        assert_eq!(metadata.quantization(), None);
+        metadata.set_quantization(1e-3);
+        assert_eq!(metadata.quantization(), Some(1e-3));
+        metadata.component_type = ComponentType::u64();
+        assert_eq!(metadata.quantization(), None);
+
+        assert_eq!(metadata.compression(), None);
+        metadata.set_compression(crate::compress::Compression::LZ4.xor());
+        assert_eq!(
+            metadata.compression(),
+            Some(crate::compress::Compression::LZ4.xor())
+        );
+    }
 }
@@ -0,0 +1,65 @@
+use nox::{OwnedRepr, Scalar, Table2};
+use serde::{Deserialize, Serialize};
+
+use crate::Asset;
+
+/// Lift and drag coefficients tabulated over Mach number and angle of attack.
+///
+/// Both tables share the same grid, with Mach along the first axis and alpha, in radians, along
+/// the second.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AeroTable {
+    pub lift: Table2,
+    pub drag: Table2,
+}
+
+impl AeroTable {
+    /// Builds the tables from row-major coefficient grids, one row per Mach breakpoint.
+    pub fn new(
+        mach: Vec<f64>,
+        alpha: Vec<f64>,
+        cl: Vec<f64>,
+        cd: Vec<f64>,
+    ) -> Result<Self, nox::Error> {
+        Ok(AeroTable {
+            lift: Table2::new([mach.clone(), alpha.clone()], cl)?,
+            drag: Table2::new([mach, alpha], cd)?,
+        })
+    }
+
+    /// Returns `(cl, cd)` at the given Mach number and angle of attack.
+    pub fn coefficients<R: OwnedRepr>(
+        &self,
+        mach: Scalar<f64, R>,
+        alpha: Scalar<f64, R>,
+    ) -> (Scalar<f64, R>, Scalar<f64, R>) {
+        let cl = self.lift.eval([mach.clone(), alpha.clone()]);
+        let cd = self.drag.eval([mach, alpha]);
+        (cl, cd)
+    }
+}
+
+impl Asset for AeroTable {
+    const ASSET_NAME: &'static str = "aero_table";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nox::ArrayRepr;
+
+    #[test]
+    fn test_aero_table() {
+        let table = AeroTable::new(
+            vec![0.5, 2.0],
+            vec![0.0, 0.2],
+            vec![0.0, 1.0, 0.0, 0.4],
+            vec![0.02, 0.06, 0.1, 0.2],
+        )
+        .unwrap();
+        let (cl, cd) = table.coefficients::<ArrayRepr>(1.25.into(), 0.1.into());
+        assert!((cl.into_buf() - 0.35).abs() < 1e-12);
+        assert!((cd.into_buf() - 0.095).abs() < 1e-12);
+        assert!(AeroTable::new(vec![0.5], vec![0.0], vec![], vec![]).is_err());
+    }
+}
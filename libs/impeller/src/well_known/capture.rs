@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Asset;
+
+use super::Viewport;
+
+/// A request to render a viewpoint offscreen at selected ticks of a run and save the frames,
+/// so reports can include the same visuals from every run of a campaign.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "bevy", derive(bevy::prelude::Component))]
+pub struct Capture {
+    pub viewport: Viewport,
+    pub width: u32,
+    pub height: u32,
+    pub ticks: CaptureTicks,
+    /// The directory frames are written to, as `<tick>.png`.
+    pub output: String,
+    pub format: CaptureFormat,
+}
+
+impl Asset for Capture {
+    const ASSET_NAME: &'static str = "capture";
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum CaptureTicks {
+    List(Vec<u64>),
+    /// Every `step` ticks from `start` up to and including `end`.
+    Every {
+        start: u64,
+        step: u64,
+        end: u64,
+    },
+}
+
+impl CaptureTicks {
+    pub fn contains(&self, tick: u64) -> bool {
+        match self {
+            CaptureTicks::List(ticks) => ticks.contains(&tick),
+            CaptureTicks::Every { start, step, end } => {
+                (*start..=*end).contains(&tick) && (tick - start) % (*step).max(1) == 0
+            }
+        }
+    }
+
+    /// Returns the last tick captured, after which a video can be encoded.
+    pub fn last(&self) -> Option<u64> {
+        match self {
+            CaptureTicks::List(ticks) => ticks.iter().max().copied(),
+            CaptureTicks::Every { start, step, end } => {
+                (start <= end).then(|| end - (end - start) % (*step).max(1))
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum CaptureFormat {
+    Png,
+    /// PNG frames, also encoded into `<output>.mp4` with `ffmpeg` after the last one.
+    Mp4 {
+        fps: u32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_ticks() {
+        let ticks = CaptureTicks::Every {
+            start: 10,
+            step: 25,
+            end: 100,
+        };
+        assert!(ticks.contains(10) && ticks.contains(85));
+        assert!(!ticks.contains(0) && !ticks.contains(11) && !ticks.contains(110));
+        assert_eq!(ticks.last(), Some(85));
+
+        let ticks = CaptureTicks::List(vec![3, 40, 7]);
+        assert!(ticks.contains(7) && !ticks.contains(8));
+        assert_eq!(ticks.last(), Some(40));
+        assert_eq!(CaptureTicks::List(vec![]).last(), None);
+    }
+}
@@ -7,14 +7,20 @@ use crate::{ComponentType, ComponentValue, PrimitiveTy, ValueRepr};
 #[cfg(feature = "bevy")]
 mod bevy_conv;
 
+mod aero;
 mod camera;
+mod capture;
 mod metadata;
 mod pbr;
+mod terrain;
 mod viewer;
 
+pub use aero::*;
 pub use camera::*;
+pub use capture::*;
 pub use metadata::*;
 pub use pbr::*;
+pub use terrain::*;
 pub use viewer::*;
 
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
@@ -67,6 +73,85 @@ impl ValueRepr for WorldPos {
     }
 }
 
+impl WorldPos {
+    /// Predicts the pose `dt` seconds ahead, assuming `vel` stays constant.
+    ///
+    /// The attitude is stepped the same way the six-dof integrator steps it, so a viewer
+    /// extrapolating between ticks lands close to where the next tick will put the entity.
+    pub fn extrapolate(&self, vel: &WorldVel, dt: f64) -> WorldPos {
+        let [qx, qy, qz, qw] = self.att.parts().map(Tensor::into_buf);
+        let [wx, wy, wz] = vel.ang.parts().map(|x| x.into_buf() * dt / 2.0);
+        // q + (ω dt / 2) ⊗ q, with ω as a pure quaternion
+        let att = Quaternion::new(
+            qw - wx * qx - wy * qy - wz * qz,
+            qx + wx * qw + wy * qz - wz * qy,
+            qy + wy * qw + wz * qx - wx * qz,
+            qz + wz * qw + wx * qy - wy * qx,
+        )
+        .normalize();
+        let [x, y, z] = self.pos.parts().map(Tensor::into_buf);
+        let [vx, vy, vz] = vel.vel.parts().map(Tensor::into_buf);
+        WorldPos {
+            att,
+            pos: Vector3::new(x + vx * dt, y + vy * dt, z + vz * dt),
+        }
+    }
+}
+
+/// The world-frame velocity of an entity, streamed next to [`WorldPos`] so viewers can
+/// extrapolate poses between sim ticks.
+///
+/// Laid out like `SpatialMotion`: angular velocity first, then linear velocity.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(bevy::prelude::Component))]
+pub struct WorldVel {
+    pub ang: Vector3<f64, ArrayRepr>,
+    pub vel: Vector3<f64, ArrayRepr>,
+}
+
+impl crate::Component for WorldVel {
+    const NAME: &'static str = "world_vel";
+    const ASSET: bool = false;
+
+    fn component_type() -> ComponentType {
+        ComponentType {
+            primitive_ty: PrimitiveTy::F64,
+            shape: smallvec![6],
+        }
+    }
+}
+
+impl ValueRepr for WorldVel {
+    type ValueDim = ndarray::Ix1;
+
+    fn fixed_dim_component_value(&self) -> ComponentValue<'_, Self::ValueDim> {
+        let [wx, wy, wz] = self.ang.parts().map(Tensor::into_buf);
+        let [x, y, z] = self.vel.parts().map(Tensor::into_buf);
+        let arr = array![wx, wy, wz, x, y, z];
+        ComponentValue::F64(CowArray::from(arr))
+    }
+
+    fn from_component_value<D: ndarray::Dimension>(
+        value: crate::ComponentValue<'_, D>,
+    ) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        let crate::ComponentValue::F64(arr) = value else {
+            return None;
+        };
+        if arr.shape() != [6] {
+            return None;
+        }
+        let arr = arr.into_dimensionality::<Ix1>().ok()?;
+        let arr = arr.as_slice()?;
+        Some(WorldVel {
+            ang: Vector3::new(arr[0], arr[1], arr[2]),
+            vel: Vector3::new(arr[3], arr[4], arr[5]),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -82,4 +167,35 @@ mod tests {
         let world_pos_2 = WorldPos::from_component_value(val).unwrap();
         assert_eq!(world_pos, world_pos_2);
     }
+
+    #[test]
+    fn test_world_vel() {
+        let world_vel = WorldVel {
+            ang: Vector3::new(0.1, 0.2, 0.3),
+            vel: Vector3::new(1.0, 2.0, 3.0),
+        };
+        let val = world_vel.component_value();
+        let world_vel_2 = WorldVel::from_component_value(val).unwrap();
+        assert_eq!(world_vel, world_vel_2);
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        let world_pos = WorldPos {
+            att: Quaternion::identity(),
+            pos: Vector3::new(1.0, 0.0, 0.0),
+        };
+        let world_vel = WorldVel {
+            ang: Vector3::new(0.0, 0.0, 0.1),
+            vel: Vector3::new(0.0, 2.0, 0.0),
+        };
+        let next = world_pos.extrapolate(&world_vel, 0.5);
+        assert_eq!(next.pos, Vector3::new(1.0, 1.0, 0.0));
+        let [_, _, qz, qw] = next.att.parts().map(Tensor::into_buf);
+        // a small rotation about z by ω dt = 0.05 rad
+        assert!((2.0 * qz.atan2(qw) - 0.05).abs() < 1e-4);
+
+        let still = world_pos.extrapolate(&world_vel, 0.0);
+        assert_eq!(still, world_pos);
+    }
 }
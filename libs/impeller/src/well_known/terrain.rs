@@ -0,0 +1,160 @@
+use nox::{OwnedRepr, Scalar, Table2, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::Asset;
+
+use super::{Mesh, MeshData, MeshInner};
+
+/// Terrain elevations sampled on a regular grid in the world's x/y plane, with z up.
+///
+/// The elevation table has y along its first axis and x along its second, so heights are stored
+/// one row per y sample. `geodetic_origin`, when set, is the latitude and longitude in radians and
+/// the altitude in meters of the world origin, tying the local grid to a map.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Heightfield {
+    pub elevation: Table2,
+    pub geodetic_origin: Option<[f64; 3]>,
+}
+
+impl Heightfield {
+    /// Builds a heightfield from row-major elevations with `cols` samples per row, starting at
+    /// `origin` and stepping by `spacing` along x and y.
+    pub fn new(
+        origin: [f64; 2],
+        spacing: [f64; 2],
+        cols: usize,
+        heights: Vec<f64>,
+    ) -> Result<Self, nox::Error> {
+        if cols == 0 || heights.len() % cols != 0 {
+            return Err(nox::Error::InvalidTable);
+        }
+        let rows = heights.len() / cols;
+        let axis = |n: usize, i: usize| (0..n).map(|k| origin[i] + k as f64 * spacing[i]).collect();
+        Ok(Heightfield {
+            elevation: Table2::new([axis(rows, 1), axis(cols, 0)], heights)?,
+            geodetic_origin: None,
+        })
+    }
+
+    pub fn with_geodetic_origin(mut self, lat: f64, lon: f64, alt: f64) -> Self {
+        self.geodetic_origin = Some([lat, lon, alt]);
+        self
+    }
+
+    /// The bilinearly interpolated height at `(x, y)`, held at the edge value past the grid.
+    pub fn height<R: OwnedRepr>(&self, x: Scalar<f64, R>, y: Scalar<f64, R>) -> Scalar<f64, R> {
+        self.elevation.eval([y, x])
+    }
+
+    /// The upward unit normal at `(x, y)`.
+    ///
+    /// The slopes are central differences a small fraction of a cell apart, which match the
+    /// bilinear surface exactly away from the cell edges.
+    pub fn normal<R: OwnedRepr>(&self, x: Scalar<f64, R>, y: Scalar<f64, R>) -> Vector3<f64, R> {
+        let [dy, dx] = self.spacing().map(|s| s * 1e-3);
+        let (dx, dy) = (Scalar::<f64, R>::from(dx), Scalar::<f64, R>::from(dy));
+        let east = self.height(&x + &dx, y.clone());
+        let west = self.height(&x - &dx, y.clone());
+        let (north, south) = (self.height(x.clone(), &y + &dy), self.height(x, &y - &dy));
+        let gx = (east - west) / (&dx + &dx);
+        let gy = (north - south) / (&dy + &dy);
+        Vector3::new(-gx, -gy, 1.0).normalize()
+    }
+
+    /// A triangle mesh of the grid for the viewer, with one vertex per sample.
+    ///
+    /// Like every mesh, it's laid out in the viewer's y-up frame, so it lines up with the terrain
+    /// when spawned at the world origin.
+    pub fn mesh(&self) -> Mesh {
+        let [ys, xs] = [&self.elevation.axes()[0], &self.elevation.axes()[1]];
+        let heights = self.elevation.values();
+        let (rows, cols) = (ys.len(), xs.len());
+        let [dy, dx] = self.spacing();
+        let at = |r: usize, c: usize| heights[r * cols + c];
+        let y_up = |[x, y, z]: [f64; 3]| [x as f32, z as f32, -y as f32];
+
+        let mut positions = Vec::with_capacity(rows * cols);
+        let mut normals = Vec::with_capacity(rows * cols);
+        let mut uvs = Vec::with_capacity(rows * cols);
+        for (r, y) in ys.iter().enumerate() {
+            for (c, x) in xs.iter().enumerate() {
+                positions.push(y_up([*x, *y, at(r, c)]));
+                let gx = (at(r, (c + 1).min(cols - 1)) - at(r, c.saturating_sub(1))) / (2.0 * dx);
+                let gy = (at((r + 1).min(rows - 1), c) - at(r.saturating_sub(1), c)) / (2.0 * dy);
+                let norm = (gx * gx + gy * gy + 1.0).sqrt();
+                normals.push(y_up([-gx / norm, -gy / norm, 1.0 / norm]));
+                uvs.push([
+                    c as f32 / (cols - 1).max(1) as f32,
+                    r as f32 / (rows - 1).max(1) as f32,
+                ]);
+            }
+        }
+        let mut indices = Vec::with_capacity(6 * rows.saturating_sub(1) * cols.saturating_sub(1));
+        for r in 1..rows {
+            for c in 1..cols {
+                let i = ((r - 1) * cols + c - 1) as u32;
+                let (right, up) = (i + 1, i + cols as u32);
+                indices.extend_from_slice(&[i, right, up, right, up + 1, up]);
+            }
+        }
+        Mesh {
+            inner: MeshInner::Data(MeshData {
+                mesh_type: 3,
+                positions: Some(positions),
+                normals: Some(normals),
+                uvs: Some(uvs),
+                tangents: None,
+                colors: None,
+                joint_weights: None,
+                joint_indices: None,
+                indices: Some(indices),
+            }),
+        }
+    }
+
+    /// The grid spacing along y and x, or one meter along an axis with a single sample.
+    fn spacing(&self) -> [f64; 2] {
+        [0, 1].map(|i| match self.elevation.axes()[i].as_slice() {
+            [a, b, ..] => b - a,
+            _ => 1.0,
+        })
+    }
+}
+
+impl Asset for Heightfield {
+    const ASSET_NAME: &'static str = "heightfield";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nox::{ArrayRepr, Tensor};
+
+    #[test]
+    fn test_heightfield() {
+        // z = 0.5 x + 0.25 y on a 3x2 grid, which bilinear interpolation reproduces exactly
+        let heights = [0.0, 1.0, 2.0]
+            .iter()
+            .flat_map(|y| [0.0, 2.0].map(|x| 0.5 * x + 0.25 * y))
+            .collect();
+        let field = Heightfield::new([0.0, 0.0], [2.0, 1.0], 2, heights).unwrap();
+        let h = field.height::<ArrayRepr>(1.0.into(), 1.5.into()).into_buf();
+        assert!((h - 0.875).abs() < 1e-12);
+        let n = field.normal::<ArrayRepr>(0.5.into(), 0.5.into());
+        let [nx, ny, nz] = n.parts().map(Tensor::into_buf);
+        let expected = [-0.5, -0.25, 1.0].map(|v| v / 1.3125f64.sqrt());
+        assert!((nx - expected[0]).abs() < 1e-9);
+        assert!((ny - expected[1]).abs() < 1e-9);
+        assert!((nz - expected[2]).abs() < 1e-9);
+
+        let MeshInner::Data(data) = field.mesh().inner else {
+            panic!("heightfield meshes are always data");
+        };
+        assert_eq!(data.positions.as_ref().unwrap().len(), 6);
+        assert_eq!(data.indices.as_ref().unwrap().len(), 12);
+        assert_eq!(data.positions.unwrap()[5], [2.0, 1.5, -2.0]);
+
+        assert!(Heightfield::new([0.0, 0.0], [1.0, 1.0], 2, vec![0.0; 3]).is_err());
+        assert!(Heightfield::new([0.0, 0.0], [-1.0, 1.0], 2, vec![0.0; 4]).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::time::Duration;
 
@@ -23,9 +24,19 @@ impl Default for TimeStep {
 pub struct World {
     pub host: Buffers,
     pub history: Vec<Buffers>,
+    /// The tick each entry of `history` was recorded at, since [`World::retention`] drops ticks
+    /// from it as the run goes.
+    pub history_ticks: Vec<u64>,
     pub entity_ids: ustr::UstrMap<Vec<u8>>,
     pub dirty_components: HashSet<ComponentId>,
+    /// Columns written since the last tick, the only ones [`World::advance_tick`] checks for
+    /// changes.
+    pub written: HashSet<ComponentId>,
+    /// The ticks each column took on a new value at, in order, see [`ColumnRef::changed_since`].
+    pub changes: HashMap<ComponentId, Vec<u64>>,
     pub component_map: HashMap<ComponentId, (ArchetypeName, Metadata)>,
+    /// The layout each archetype's columns are recorded in, see [`ColumnLayout`].
+    pub layouts: HashMap<ArchetypeName, ColumnLayout>,
     pub assets: AssetStore,
     pub tick: u64,
     pub entity_len: u64,
@@ -33,6 +44,9 @@ pub struct World {
     pub run_time_step: TimeStep,
     pub default_playback_speed: f64,
     pub max_tick: u64,
+    /// Which ticks `history`, and so [`World::polars`] and the recordings built from it, keep.
+    /// It applies to ticks as they're recorded, so set it before running.
+    pub retention: Retention,
 }
 
 impl Default for World {
@@ -40,9 +54,13 @@ impl Default for World {
         Self {
             host: Default::default(),
             history: Default::default(),
+            history_ticks: Default::default(),
             entity_ids: Default::default(),
             dirty_components: Default::default(),
+            written: Default::default(),
+            changes: Default::default(),
             component_map: Default::default(),
+            layouts: Default::default(),
             assets: Default::default(),
             tick: Default::default(),
             entity_len: Default::default(),
@@ -50,6 +68,7 @@ impl Default for World {
             sim_time_step: Default::default(),
             default_playback_speed: 1.0,
             max_tick: u64::MAX,
+            retention: Default::default(),
         }
     }
 }
@@ -58,6 +77,8 @@ pub struct ColumnRef<'a, B: 'a> {
     pub column: B,
     pub entities: B,
     pub metadata: &'a Metadata,
+    /// The tick the column last changed at, or a later one when that isn't known.
+    pub last_changed: u64,
 }
 
 pub struct Entity<'a> {
@@ -111,12 +132,17 @@ impl World {
             .max();
         let entity_len = max_entity_id.map(|id| id + 1).unwrap_or(0);
         let dirty_components = host.keys().copied().collect();
+        let changes = host.keys().map(|id| (*id, vec![tick])).collect();
         Self {
             host,
+            history_ticks: (0..tick).collect(),
             history,
             entity_ids,
             dirty_components,
+            written: Default::default(),
+            changes,
             component_map,
+            layouts: Default::default(),
             assets: asset_store,
             tick,
             entity_len,
@@ -124,6 +150,7 @@ impl World {
             sim_time_step,
             default_playback_speed,
             max_tick,
+            retention: Default::default(),
         }
     }
 
@@ -139,11 +166,13 @@ impl World {
 
     pub fn insert_with_id<A: Archetype + 'static>(&mut self, archetype: A, entity_id: EntityId) {
         let archetype_name = A::name();
+        self.layouts.insert(archetype_name, A::layout());
         for metadata in A::components() {
             let id = metadata.component_id();
             self.component_map.insert(id, (archetype_name, metadata));
             self.host.entry(id).or_default();
             self.dirty_components.insert(id);
+            self.mark_changed(id, self.tick);
         }
         self.entity_ids
             .entry(archetype_name)
@@ -168,6 +197,7 @@ impl World {
             column,
             entities,
             metadata,
+            last_changed: self.last_changed(id),
         })
     }
 
@@ -176,31 +206,71 @@ impl World {
         let column = self.host.get_mut(&id)?;
         let entities = self.entity_ids.get_mut(table_id)?;
         self.dirty_components.insert(id);
+        self.written.insert(id);
         Some(ColumnRef {
             column,
             entities,
             metadata,
+            last_changed: self.tick,
         })
     }
 
+    /// `component_id`'s column at `tick`, converted back to entity-major if its archetype records
+    /// another layout.
     pub fn column_at_tick(
         &self,
         component_id: ComponentId,
         tick: u64,
-    ) -> Option<ColumnRef<'_, &Vec<u8>>> {
-        if tick == self.tick {
-            return self.column_by_id(component_id);
-        }
-        let column = self.history.get(tick as usize)?.get(&component_id)?;
+    ) -> Option<ColumnRef<'_, Cow<'_, [u8]>>> {
         let (archetype_name, metadata) = self.component_map.get(&component_id)?;
         let entities = self.entity_ids.get(archetype_name)?;
+        let (column, last_changed) = if tick == self.tick {
+            let column = self.host.get(&component_id)?;
+            (
+                Cow::Borrowed(column.as_slice()),
+                self.last_changed(component_id),
+            )
+        } else if tick < self.tick {
+            // a tick the retention dropped holds the kept tick before it
+            let index = self
+                .history_ticks
+                .partition_point(|kept| *kept <= tick)
+                .checked_sub(1)?;
+            let column = self.history.get(index)?.get(&component_id)?;
+            let last_changed = self.changed_at(component_id, self.history_ticks[index]);
+            (self.read_recorded(component_id, column), last_changed)
+        } else {
+            return None;
+        };
         Some(ColumnRef {
             column,
-            entities,
+            entities: Cow::Borrowed(entities.as_slice()),
             metadata,
+            last_changed,
         })
     }
 
+    /// The first tick `id` held its current value at.
+    pub fn last_changed(&self, id: ComponentId) -> u64 {
+        self.changed_at(id, self.tick)
+    }
+
+    /// The first tick `id` held the value it had at `tick` at.
+    pub fn changed_at(&self, id: ComponentId, tick: u64) -> u64 {
+        let Some(changes) = self.changes.get(&id) else {
+            return tick;
+        };
+        let index = changes.partition_point(|change| *change <= tick);
+        index.checked_sub(1).map(|i| changes[i]).unwrap_or(tick)
+    }
+
+    fn mark_changed(&mut self, id: ComponentId, tick: u64) {
+        let changes = self.changes.entry(id).or_default();
+        if changes.last() < Some(&tick) {
+            changes.push(tick);
+        }
+    }
+
     pub fn entity_ids(&self) -> HashSet<EntityId> {
         self.entity_ids
             .values()
@@ -228,14 +298,41 @@ impl World {
     }
 
     pub fn advance_tick(&mut self) {
-        self.history.push(self.host.clone());
+        let snapshot = self.host_snapshot();
+        for id in std::mem::take(&mut self.written) {
+            let last = self.history.last().and_then(|last| last.get(&id));
+            if last != snapshot.get(&id) {
+                self.mark_changed(id, self.tick + 1);
+            }
+        }
+        self.drop_unkept_tick();
+        self.history.push(snapshot);
+        self.history_ticks.push(self.tick + 1);
         self.tick += 1;
     }
 
+    /// Drops the latest tick from the history if the retention doesn't keep it, now that a later
+    /// one is about to be recorded.
+    fn drop_unkept_tick(&mut self) {
+        let (Some(&tick), [.., last_kept, latest]) =
+            (self.history_ticks.last(), self.history.as_slice())
+        else {
+            return;
+        };
+        if !self
+            .retention
+            .keeps(tick, latest, Some(last_kept), &self.component_map)
+        {
+            self.history.pop();
+            self.history_ticks.pop();
+        }
+    }
+
     pub fn ensure_history(&mut self) {
         if self.history.is_empty() {
             // Push the initial state into history
-            self.history.push(self.host.clone());
+            self.history.push(self.host_snapshot());
+            self.history_ticks.push(self.tick);
         }
     }
 }
@@ -246,9 +343,13 @@ impl Clone for World {
         Self {
             host: self.host.clone(),
             history: self.history.clone(),
+            history_ticks: self.history_ticks.clone(),
             entity_ids: self.entity_ids.clone(),
             dirty_components,
+            written: self.written.clone(),
+            changes: self.changes.clone(),
             component_map: self.component_map.clone(),
+            layouts: self.layouts.clone(),
             assets: self.assets.clone(),
             tick: self.tick,
             entity_len: self.entity_len,
@@ -256,6 +357,7 @@ impl Clone for World {
             sim_time_step: self.sim_time_step,
             default_playback_speed: self.default_playback_speed,
             max_tick: self.max_tick,
+            retention: self.retention.clone(),
         }
     }
 }
@@ -312,6 +414,12 @@ impl<'a, B: 'a + AsRef<[u8]>> ColumnRef<'a, B> {
         self.len() == 0
     }
 
+    /// Whether the column's values differ from the ones it held at `tick`, so sinks that already
+    /// have that tick can skip sending or writing it again.
+    pub fn changed_since(&self, tick: u64) -> bool {
+        self.last_changed > tick
+    }
+
     #[cfg(feature = "xla")]
     pub fn buffer_ty(&self) -> ::nox::ArrayTy {
         let mut shape = self.metadata.component_type.shape.clone();
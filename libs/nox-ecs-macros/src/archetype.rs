@@ -11,6 +11,9 @@ pub struct Archetype {
     ident: Ident,
     generics: Generics,
     data: ast::Data<(), Field>,
+    /// Record the archetype's columns element-major, see `impeller::ColumnLayout`.
+    #[darling(default)]
+    element_major: bool,
 }
 
 #[derive(Debug, FromField)]
@@ -26,6 +29,7 @@ pub fn archetype(input: TokenStream) -> TokenStream {
         ident,
         generics,
         data,
+        element_major,
     } = Archetype::from_derive_input(&input).unwrap();
     let fields = data.take_struct().unwrap();
     let tys = fields.iter().map(|f| f.ty.clone()).collect::<Vec<_>>();
@@ -35,6 +39,13 @@ pub fn archetype(input: TokenStream) -> TokenStream {
         .collect::<Vec<_>>();
     let where_clause = &generics.where_clause;
     let name = ident.to_string().to_case(Case::Snake);
+    let layout = element_major.then(|| {
+        quote! {
+            fn layout() -> #crate_name::impeller::ColumnLayout {
+                #crate_name::impeller::ColumnLayout::ElementMajor
+            }
+        }
+    });
     quote! {
         impl #crate_name::impeller::Archetype for #ident #generics #where_clause {
             fn name() -> #crate_name::impeller::ArchetypeName {
@@ -51,6 +62,8 @@ pub fn archetype(input: TokenStream) -> TokenStream {
                    self.#idents.insert_into_world(world);
                 )*
             }
+
+            #layout
         }
     }
     .into()
@@ -77,7 +77,11 @@ fn main() {
     let time_step = std::time::Duration::from_secs_f64(1.0 / 240.0);
     let exec = world
         .builder()
-        .tick_pipeline(six_dof(|| gravity, Integrator::SemiImplicit))
+        .tick_pipeline(six_dof(
+            || gravity,
+            Integrator::SemiImplicit,
+            Attitude::Quaternion,
+        ))
         .sim_time_step(time_step)
         .build()
         .unwrap();
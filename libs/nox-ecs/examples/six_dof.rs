@@ -68,7 +68,7 @@ fn main() {
     let time_step = std::time::Duration::from_secs_f64(1.0 / 240.0);
     let exec = world
         .builder()
-        .tick_pipeline(six_dof(|| gravity, Integrator::Rk4))
+        .tick_pipeline(six_dof(|| gravity, Integrator::Rk4, Attitude::Quaternion))
         .run_time_step(time_step)
         .build()
         .unwrap();
@@ -0,0 +1,888 @@
+//! Runs a simulation headless for a fixed number of ticks, so CI jobs and batch farms don't need their own main loop.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use impeller::World;
+use nox::Client;
+
+use crate::{Compiled, Error, RunConfig, Seed, WorldExec, RUN_CONFIG_FILE};
+
+/// Configures a headless run, see [`Batch::run`].
+#[derive(Clone, Debug, Default)]
+pub struct Batch {
+    pub ticks: u64,
+    /// Overwrites every [`Seed`] in the world before running.
+    pub seed: Option<u64>,
+    /// The directory the final state is written to.
+    pub output: Option<PathBuf>,
+    /// Writes a checkpoint to `output/checkpoints/<tick>` every `checkpoint_interval` ticks.
+    pub checkpoint_interval: Option<u64>,
+    /// Seeds the world with [`RunConfig::sample_seed`] unless `seed` is set, and is written into `output`
+    /// so the run can be found and reproduced later.
+    pub config: Option<RunConfig>,
+    /// Streams telemetry to clients connecting to this address while running.
+    #[cfg(feature = "tokio")]
+    pub stream: Option<std::net::SocketAddr>,
+    /// Ends the run before `ticks` once any of these holds, checked after every tick.
+    pub stop_conditions: Vec<StopCondition>,
+    /// Evaluated on the final world of every run, and summarized across a campaign.
+    pub metrics: Vec<Metric>,
+    /// Caps the cost of [`Batch::run_campaign`].
+    pub budget: Budget,
+    /// The runs of a campaign that keep their full history.
+    pub recording: Recording,
+    /// Cancels the run, or runs of a campaign, at the next tick boundary.
+    pub cancel: CancelToken,
+}
+
+/// Cancels [`Batch`] runs cooperatively at the next tick boundary, e.g. from a campaign controller
+/// on another thread or a signal handler. Clones share their state.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<CancelState>);
+
+#[derive(Debug, Default)]
+struct CancelState {
+    all: AtomicBool,
+    runs: Mutex<BTreeSet<u64>>,
+}
+
+impl CancelToken {
+    /// The stop reason of a run ended by its token.
+    pub const STOP_REASON: &'static str = "cancelled";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every run, including the ones a campaign hasn't dispatched yet.
+    pub fn cancel(&self) {
+        self.0.all.store(true, Ordering::Relaxed);
+    }
+
+    /// Cancels the run with `run_id`, letting the rest of the campaign go on.
+    pub fn cancel_run(&self, run_id: u64) {
+        let mut runs = self.0.runs.lock().unwrap_or_else(PoisonError::into_inner);
+        runs.insert(run_id);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.all.load(Ordering::Relaxed)
+    }
+
+    pub fn is_run_cancelled(&self, run_id: u64) -> bool {
+        let runs = self.0.runs.lock().unwrap_or_else(PoisonError::into_inner);
+        self.is_cancelled() || runs.contains(&run_id)
+    }
+}
+
+/// Ends a [`Batch`] run as soon as its outcome is decided, e.g. once a vehicle has landed.
+#[derive(Clone)]
+pub struct StopCondition {
+    /// Reported as the reason the run stopped.
+    pub name: String,
+    predicate: Arc<dyn Fn(&World) -> Result<bool, Error> + Send + Sync>,
+}
+
+impl StopCondition {
+    /// Stops the run once `predicate` holds for the world at the end of a tick.
+    pub fn new(
+        name: impl Into<String>,
+        predicate: impl Fn(&World) -> Result<bool, Error> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            predicate: Arc::new(predicate),
+        }
+    }
+
+    /// Stops the run once `limit` of simulation time has passed.
+    pub fn time_limit(limit: Duration) -> Self {
+        Self::new("time_limit", move |world| {
+            Ok(world.sim_time_step.0.mul_f64(world.tick as f64) >= limit)
+        })
+    }
+
+    pub fn holds(&self, world: &World) -> Result<bool, Error> {
+        (self.predicate)(world)
+    }
+}
+
+impl fmt::Debug for StopCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StopCondition")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A scalar outcome of a run, e.g. the miss distance, evaluated on its final world.
+#[derive(Clone)]
+pub struct Metric {
+    pub name: String,
+    f: Arc<dyn Fn(&World) -> Result<f64, Error> + Send + Sync>,
+}
+
+impl Metric {
+    pub fn new(
+        name: impl Into<String>,
+        f: impl Fn(&World) -> Result<f64, Error> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            f: Arc::new(f),
+        }
+    }
+
+    pub fn eval(&self, world: &World) -> Result<f64, Error> {
+        (self.f)(world)
+    }
+}
+
+impl fmt::Debug for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metric")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Caps the cost of a campaign. The budget is checked before each run is dispatched, so a run that
+/// has started always finishes and the statistics are never skewed by truncated runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Budget {
+    pub wall_time: Option<Duration>,
+    /// The ticks summed over every run.
+    pub ticks: Option<u64>,
+}
+
+impl Budget {
+    /// The name of the limit that has been spent, if any.
+    fn exceeded(&self, wall_time: Duration, ticks: u64) -> Option<&'static str> {
+        if self.wall_time.is_some_and(|limit| wall_time >= limit) {
+            Some("wall_time")
+        } else if self.ticks.is_some_and(|limit| ticks >= limit) {
+            Some("ticks")
+        } else {
+            None
+        }
+    }
+}
+
+/// Picks the runs of a campaign that keep their full history, so they can be replayed and plotted,
+/// while the rest record only their metrics in `sample.json`. Every run is recorded unless a
+/// selection is made.
+#[derive(Clone, Default)]
+pub struct Recording {
+    select: Option<Arc<dyn Fn(&RunConfig) -> bool + Send + Sync>>,
+    worst: Option<(String, usize)>,
+}
+
+impl Recording {
+    /// Records the runs whose config `predicate` accepts, e.g. the nominal run.
+    pub fn runs(predicate: impl Fn(&RunConfig) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            select: Some(Arc::new(predicate)),
+            ..Default::default()
+        }
+    }
+
+    /// Also records the `count` runs with the highest value of `metric`. Each run is recorded while
+    /// it ranks among them, and pruned back to its metrics once `count` worse runs have finished.
+    pub fn worst(mut self, metric: impl Into<String>, count: usize) -> Self {
+        self.worst = Some((metric.into(), count));
+        self
+    }
+
+    fn records_all(&self) -> bool {
+        self.select.is_none() && self.worst.is_none()
+    }
+
+    fn selects(&self, config: &RunConfig) -> bool {
+        self.select.as_ref().is_some_and(|select| select(config))
+    }
+}
+
+impl fmt::Debug for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recording")
+            .field("worst", &self.worst)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Batch {
+    pub fn new(ticks: u64) -> Self {
+        Self {
+            ticks,
+            ..Default::default()
+        }
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn output(mut self, output: impl Into<PathBuf>) -> Self {
+        self.output = Some(output.into());
+        self
+    }
+
+    pub fn checkpoint_interval(mut self, interval: u64) -> Self {
+        self.checkpoint_interval = Some(interval);
+        self
+    }
+
+    pub fn config(mut self, config: RunConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    #[cfg(feature = "tokio")]
+    pub fn stream(mut self, addr: std::net::SocketAddr) -> Self {
+        self.stream = Some(addr);
+        self
+    }
+
+    pub fn stop_when(mut self, condition: StopCondition) -> Self {
+        self.stop_conditions.push(condition);
+        self
+    }
+
+    pub fn metric(mut self, metric: Metric) -> Self {
+        self.metrics.push(metric);
+        self
+    }
+
+    pub fn budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    pub fn recording(mut self, recording: Recording) -> Self {
+        self.recording = recording;
+        self
+    }
+
+    pub fn cancel_token(mut self, cancel: CancelToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Compiles `exec` and runs it for `ticks` as fast as possible, without waiting on the run time step,
+    /// or until one of the stop conditions holds or the run is cancelled.
+    pub fn run(&self, exec: WorldExec, client: Client) -> Result<BatchSummary, Error> {
+        self.run_recorded(exec, client, true)
+    }
+
+    /// Runs like [`Batch::run`], writing only the run's config and metrics into `output` unless
+    /// `record` is set.
+    fn run_recorded(
+        &self,
+        mut exec: WorldExec,
+        client: Client,
+        record: bool,
+    ) -> Result<BatchSummary, Error> {
+        let seed = self
+            .seed
+            .or_else(|| self.config.as_ref().map(RunConfig::sample_seed));
+        if let Some(seed) = seed {
+            seed_world(&mut exec.world, seed);
+        }
+        let mut exec = exec.compile(client)?;
+        let start = Instant::now();
+        let mut checkpoints = vec![];
+        let mut ticks = 0;
+        let mut stop_reason = self.cancelled();
+
+        #[cfg(feature = "tokio")]
+        if let Some(addr) = self.stream {
+            let mut impeller_exec = crate::spawn_tcp_exec(addr, exec)?;
+            while ticks < self.ticks && stop_reason.is_none() {
+                stop_reason = self.step(impeller_exec.exec_mut(), &mut checkpoints)?;
+                impeller_exec.sync();
+                ticks += 1;
+            }
+            let exec = impeller_exec.exec_mut();
+            return self.finish(exec, checkpoints, start, ticks, stop_reason, record);
+        }
+
+        while ticks < self.ticks && stop_reason.is_none() {
+            stop_reason = self.step(&mut exec, &mut checkpoints)?;
+            ticks += 1;
+        }
+        self.finish(&mut exec, checkpoints, start, ticks, stop_reason, record)
+    }
+
+    /// Runs up to `runs` Monte Carlo runs one after another, each built by `build` from its
+    /// [`RunConfig`] and written to `output/<run_id>`, until the budget is spent. Runs are dispatched
+    /// in id order, so a campaign cut short still covers a prefix of the run ids, and its metrics are
+    /// summarized over the runs that completed. Only the runs picked by `recording` keep their full
+    /// history.
+    ///
+    /// A run cancelled through `cancel` before it's dispatched is skipped, and one cancelled while
+    /// running stops at the next tick boundary with [`CancelToken::STOP_REASON`] in its config. Either
+    /// way it's left out of the metrics, and the campaign moves on to the next run.
+    pub fn run_campaign(
+        &self,
+        runs: u64,
+        mut build: impl FnMut(&RunConfig) -> Result<WorldExec, Error>,
+        client: Client,
+    ) -> Result<CampaignSummary, Error> {
+        let template = self.config.clone().unwrap_or_else(|| RunConfig {
+            seed: self.seed.unwrap_or_default(),
+            ..Default::default()
+        });
+        if let Some((metric, _)) = &self.recording.worst {
+            if !self.metrics.iter().any(|m| &m.name == metric) {
+                return Err(Error::UnknownMetric(metric.clone()));
+            }
+        }
+        let start = Instant::now();
+        let mut summaries = vec![];
+        let mut ticks = 0;
+        let mut budget_exceeded = None;
+        let mut selected = vec![];
+        let mut cancelled = vec![];
+        // The runs recorded only while they rank among the worst, as (metric value, run id).
+        let mut worst: Vec<(f64, u64)> = vec![];
+        for run_id in 0..runs {
+            budget_exceeded = self.budget.exceeded(start.elapsed(), ticks);
+            if budget_exceeded.is_some() {
+                break;
+            }
+            if self.cancel.is_run_cancelled(run_id) {
+                cancelled.push(run_id);
+                continue;
+            }
+            let mut config = RunConfig {
+                run_id,
+                ..template.clone()
+            };
+            let exec = build(&config)?;
+            if self.config.is_none() {
+                config.time_step = exec.world.sim_time_step.0.as_secs_f64();
+            }
+            let select = self.recording.records_all() || self.recording.selects(&config);
+            let record = select || self.recording.worst.is_some();
+            let run = Batch {
+                seed: None,
+                output: self.output.as_ref().map(|dir| dir.join(run_id.to_string())),
+                checkpoint_interval: self.checkpoint_interval.filter(|_| record),
+                config: Some(config),
+                ..self.clone()
+            };
+            let summary = run.run_recorded(exec, client.clone(), record)?;
+            ticks += summary.ticks;
+            let was_cancelled = summary.stop_reason.as_deref() == Some(CancelToken::STOP_REASON);
+            if select {
+                selected.push(run_id);
+            } else if was_cancelled {
+                // a partial run doesn't rank among the worst, so it keeps only its config
+                if let Some(output) = self.output.as_ref().filter(|_| record) {
+                    prune_history(&output.join(run_id.to_string()))?;
+                }
+            } else if let Some((metric, count)) = &self.recording.worst {
+                worst.push((summary.metrics[metric], run_id));
+                worst.sort_by(|a, b| b.0.total_cmp(&a.0));
+                if worst.len() > *count {
+                    let (_, pruned) = worst.pop().expect("worst is non-empty");
+                    if let Some(output) = &self.output {
+                        prune_history(&output.join(pruned.to_string()))?;
+                    }
+                }
+            }
+            if was_cancelled {
+                cancelled.push(run_id);
+            } else {
+                summaries.push(summary);
+            }
+        }
+        let mut recorded = selected;
+        recorded.extend(worst.into_iter().map(|(_, run_id)| run_id));
+        recorded.sort_unstable();
+        let metrics = self
+            .metrics
+            .iter()
+            .map(|metric| {
+                let values = summaries
+                    .iter()
+                    .map(|summary| summary.metrics[&metric.name])
+                    .collect::<Vec<_>>();
+                (metric.name.clone(), MetricStats::new(&values))
+            })
+            .collect();
+        Ok(CampaignSummary {
+            requested: runs,
+            runs: summaries,
+            ticks,
+            wall_time: start.elapsed(),
+            budget_exceeded,
+            metrics,
+            recorded,
+            cancelled,
+        })
+    }
+
+    /// The stop reason of a cancelled run, checked at every tick boundary.
+    fn cancelled(&self) -> Option<String> {
+        let cancelled = match &self.config {
+            Some(config) => self.cancel.is_run_cancelled(config.run_id),
+            None => self.cancel.is_cancelled(),
+        };
+        cancelled.then(|| CancelToken::STOP_REASON.to_string())
+    }
+
+    /// Runs a single tick, returning the name of the stop condition that ends the run, if any.
+    fn step(
+        &self,
+        exec: &mut WorldExec<Compiled>,
+        checkpoints: &mut Vec<PathBuf>,
+    ) -> Result<Option<String>, Error> {
+        exec.run()?;
+        if let (Some(output), Some(interval)) = (&self.output, self.checkpoint_interval) {
+            if interval > 0 && exec.tick() % interval == 0 {
+                let dir = output.join("checkpoints").join(exec.tick().to_string());
+                exec.write_to_dir(&dir)?;
+                checkpoints.push(dir);
+            }
+        }
+        for condition in &self.stop_conditions {
+            if condition.holds(&exec.world)? {
+                return Ok(Some(condition.name.clone()));
+            }
+        }
+        Ok(self.cancelled())
+    }
+
+    fn finish(
+        &self,
+        exec: &mut WorldExec<Compiled>,
+        checkpoints: Vec<PathBuf>,
+        start: Instant,
+        ticks: u64,
+        stop_reason: Option<String>,
+        record: bool,
+    ) -> Result<BatchSummary, Error> {
+        let wall_time = start.elapsed();
+        let metrics = self
+            .metrics
+            .iter()
+            .map(|metric| Ok((metric.name.clone(), metric.eval(&exec.world)?)))
+            .collect::<Result<BTreeMap<_, _>, Error>>()?;
+        if let Some(output) = &self.output {
+            if record {
+                exec.write_to_dir(output)?;
+            }
+            if let Some(config) = &self.config {
+                let integrator = exec.tick_exec.integrator().map(str::to_string);
+                let config = RunConfig {
+                    integrator: config.integrator.clone().or(integrator),
+                    stop_reason: stop_reason.clone(),
+                    metrics: metrics.clone(),
+                    ..config.clone()
+                };
+                config.write_to_dir(output)?;
+            }
+        }
+        let sim_time = exec.world.sim_time_step.0.mul_f64(ticks as f64);
+        Ok(BatchSummary {
+            ticks,
+            sim_time,
+            wall_time,
+            real_time_factor: sim_time.as_secs_f64() / wall_time.as_secs_f64(),
+            output: self.output.clone(),
+            checkpoints,
+            stop_reason,
+            metrics,
+        })
+    }
+}
+
+/// Removes everything but the config and metrics from a run's output directory.
+fn prune_history(dir: &Path) -> Result<(), Error> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name() == RUN_CONFIG_FILE {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            std::fs::remove_dir_all(entry.path())?;
+        } else {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Gives every entity with a [`Seed`] its own seed, counting up from `seed` in entity order.
+pub fn seed_world(world: &mut World, seed: u64) {
+    let Some(column) = world.column_mut::<Seed>() else {
+        return;
+    };
+    for (i, value) in column.column.chunks_exact_mut(8).enumerate() {
+        value.copy_from_slice(&seed.wrapping_add(i as u64).to_le_bytes());
+    }
+}
+
+/// What a [`Batch`] run did, printed once it exits.
+#[derive(Clone, Debug)]
+pub struct BatchSummary {
+    /// The ticks actually run, fewer than requested if a stop condition ended the run.
+    pub ticks: u64,
+    pub sim_time: Duration,
+    pub wall_time: Duration,
+    pub real_time_factor: f64,
+    pub output: Option<PathBuf>,
+    pub checkpoints: Vec<PathBuf>,
+    /// The stop condition that ended the run early, if any.
+    pub stop_reason: Option<String>,
+    pub metrics: BTreeMap<String, f64>,
+}
+
+impl fmt::Display for BatchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "ticks:            {}", self.ticks)?;
+        writeln!(f, "sim time:         {:.3} s", self.sim_time.as_secs_f64())?;
+        writeln!(f, "wall time:        {:.3} s", self.wall_time.as_secs_f64())?;
+        writeln!(f, "real_time_factor: {:.3}", self.real_time_factor)?;
+        writeln!(f, "checkpoints:      {}", self.checkpoints.len())?;
+        if let Some(reason) = &self.stop_reason {
+            writeln!(f, "stopped by:       {}", reason)?;
+        }
+        for (name, value) in &self.metrics {
+            writeln!(f, "{:<18}{:.6}", format!("{}:", name), value)?;
+        }
+        if let Some(output) = &self.output {
+            writeln!(f, "output:           {}", output.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// What [`Batch::run_campaign`] did, including the statistics of a campaign cut short by its budget.
+#[derive(Clone, Debug)]
+pub struct CampaignSummary {
+    /// The runs asked for, of which only `runs.len()` ran if the budget was spent first.
+    pub requested: u64,
+    /// The completed runs, in run id order.
+    pub runs: Vec<BatchSummary>,
+    /// The ticks summed over every run.
+    pub ticks: u64,
+    pub wall_time: Duration,
+    /// The budget limit that stopped the campaign from dispatching every requested run, if any.
+    pub budget_exceeded: Option<&'static str>,
+    pub metrics: BTreeMap<String, MetricStats>,
+    /// The ids of the runs that kept their full history, see [`Recording`].
+    pub recorded: Vec<u64>,
+    /// The ids of the runs skipped or stopped by the campaign's [`CancelToken`].
+    pub cancelled: Vec<u64>,
+}
+
+impl fmt::Display for CampaignSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "runs:             {}/{}",
+            self.runs.len(),
+            self.requested
+        )?;
+        writeln!(f, "ticks:            {}", self.ticks)?;
+        writeln!(f, "wall time:        {:.3} s", self.wall_time.as_secs_f64())?;
+        writeln!(f, "recorded:         {}", self.recorded.len())?;
+        if !self.cancelled.is_empty() {
+            writeln!(f, "cancelled:        {}", self.cancelled.len())?;
+        }
+        if let Some(limit) = self.budget_exceeded {
+            writeln!(f, "budget spent:     {}", limit)?;
+        }
+        for (name, stats) in &self.metrics {
+            let Some(mean) = stats.mean else {
+                writeln!(f, "{:<18}no runs", format!("{}:", name))?;
+                continue;
+            };
+            write!(f, "{:<18}{:.6}", format!("{}:", name), mean)?;
+            match stats.ci95 {
+                Some((low, high)) => writeln!(f, " (95% CI {:.6} .. {:.6})", low, high)?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The statistics of a metric over the completed runs of a campaign.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MetricStats {
+    pub count: usize,
+    /// The mean, once there's at least one run.
+    pub mean: Option<f64>,
+    /// The sample standard deviation.
+    pub std_dev: f64,
+    /// The 95% confidence interval of the mean, from Student's t distribution, once there are at
+    /// least two runs.
+    pub ci95: Option<(f64, f64)>,
+}
+
+impl MetricStats {
+    pub fn new(values: &[f64]) -> Self {
+        let count = values.len();
+        let mean = (count > 0).then(|| values.iter().sum::<f64>() / count as f64);
+        let Some(mean) = mean.filter(|_| count >= 2) else {
+            return Self {
+                count,
+                mean,
+                std_dev: 0.0,
+                ci95: None,
+            };
+        };
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (count - 1) as f64;
+        let std_dev = variance.sqrt();
+        let half_width = t_975(count - 1) * std_dev / (count as f64).sqrt();
+        Self {
+            count,
+            mean: Some(mean),
+            std_dev,
+            ci95: Some((mean - half_width, mean + half_width)),
+        }
+    }
+}
+
+/// The 97.5th percentile of Student's t distribution with `df` degrees of freedom, tabulated up to
+/// 30 and approximated from the normal quantile beyond that.
+fn t_975(df: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+    match TABLE.get(df.wrapping_sub(1)) {
+        Some(t) => *t,
+        None => {
+            let z = 1.959964;
+            z + (z.powi(3) + z) / (4.0 * df as f64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Component, ComponentArray, IntoSystemExt};
+    use nox::{Op, OwnedRepr, Scalar};
+    use nox_ecs_macros::ReprMonad;
+
+    #[derive(Component, ReprMonad)]
+    struct A<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+    fn increment(a: ComponentArray<A>) -> ComponentArray<A> {
+        a.map(|a: A| A(a.0 + 1.0)).unwrap()
+    }
+
+    #[test]
+    fn test_batch() {
+        let mut world = increment.world();
+        world.spawn(A(0.0.into()));
+        world.spawn(Seed::zero());
+        world.spawn(Seed::zero());
+        let exec = world.build().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let summary = Batch::new(5)
+            .seed(42)
+            .output(dir.path())
+            .checkpoint_interval(2)
+            .run(exec, Client::cpu().unwrap())
+            .unwrap();
+        assert_eq!(summary.ticks, 5);
+        assert_eq!(summary.checkpoints.len(), 2);
+        assert!(summary.checkpoints[1].ends_with("checkpoints/4"));
+        assert!(summary.checkpoints[1].join("world").exists());
+
+        let exec = WorldExec::read_from_dir(dir.path()).unwrap();
+        let seeds = exec.world.column::<Seed>().unwrap();
+        let seeds = bytemuck::pod_collect_to_vec::<u8, u64>(seeds.column);
+        assert_eq!(seeds, vec![42, 43]);
+    }
+
+    #[test]
+    fn test_batch_stop_conditions() {
+        let mut world = increment.world();
+        world.spawn(A(0.0.into()));
+        let exec = world.build().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let reached = StopCondition::new("reached_three", |world| {
+            let a = world.column::<A>().ok_or(Error::ComponentNotFound)?;
+            Ok(bytemuck::pod_collect_to_vec::<u8, f64>(a.column)[0] >= 3.0)
+        });
+        let summary = Batch::new(10)
+            .output(dir.path())
+            .config(RunConfig::new(0, 0, 1.0 / 60.0))
+            .stop_when(StopCondition::time_limit(Duration::from_secs(60)))
+            .stop_when(reached)
+            .run(exec, Client::cpu().unwrap())
+            .unwrap();
+        assert_eq!(summary.ticks, 3);
+        assert_eq!(summary.stop_reason.as_deref(), Some("reached_three"));
+        let config = RunConfig::read_from_dir(dir.path()).unwrap();
+        assert_eq!(config.stop_reason.as_deref(), Some("reached_three"));
+
+        let mut world = increment.world();
+        world.spawn(A(0.0.into()));
+        let exec = world
+            .sim_time_step(Duration::from_secs_f64(0.02))
+            .build()
+            .unwrap();
+        let summary = Batch::new(10)
+            .stop_when(StopCondition::time_limit(Duration::from_millis(50)))
+            .run(exec, Client::cpu().unwrap())
+            .unwrap();
+        assert_eq!(summary.ticks, 3);
+        assert_eq!(summary.stop_reason.as_deref(), Some("time_limit"));
+    }
+
+    #[test]
+    fn test_campaign_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let final_a = Metric::new("a", |world| {
+            let a = world.column::<A>().ok_or(Error::ComponentNotFound)?;
+            Ok(bytemuck::pod_collect_to_vec::<u8, f64>(a.column)[0])
+        });
+        let summary = Batch::new(3)
+            .output(dir.path())
+            .metric(final_a)
+            .budget(Budget {
+                ticks: Some(7),
+                ..Default::default()
+            })
+            .run_campaign(
+                10,
+                |config| {
+                    let mut world = increment.world();
+                    world.spawn(A((config.run_id as f64).into()));
+                    world.build()
+                },
+                Client::cpu().unwrap(),
+            )
+            .unwrap();
+        assert_eq!(summary.runs.len(), 3);
+        assert_eq!(summary.ticks, 9);
+        assert_eq!(summary.budget_exceeded, Some("ticks"));
+
+        let stats = summary.metrics["a"];
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.mean, Some(4.0));
+        assert_eq!(stats.std_dev, 1.0);
+        let (low, high) = stats.ci95.unwrap();
+        assert!((high - 4.0 - 4.303 / 3.0f64.sqrt()).abs() < 1e-9);
+        assert!((4.0 - low - 4.303 / 3.0f64.sqrt()).abs() < 1e-9);
+
+        let config = RunConfig::read_from_dir(dir.path().join("1")).unwrap();
+        assert_eq!(config.run_id, 1);
+        assert_eq!(config.metrics["a"], 4.0);
+        assert!(!dir.path().join("3").exists());
+
+        // a budget spent before the first run still reports every metric
+        let summary = Batch::new(3)
+            .metric(Metric::new("a", |_| Ok(0.0)))
+            .budget(Budget {
+                wall_time: Some(Duration::ZERO),
+                ..Default::default()
+            })
+            .run_campaign(10, |_| increment.world().build(), Client::cpu().unwrap())
+            .unwrap();
+        assert!(summary.runs.is_empty());
+        assert_eq!(summary.budget_exceeded, Some("wall_time"));
+        let stats = summary.metrics["a"];
+        assert_eq!((stats.count, stats.mean, stats.ci95), (0, None, None));
+        assert!(summary.to_string().contains("no runs"));
+    }
+
+    #[test]
+    fn test_campaign_cancel() {
+        let dir = tempfile::tempdir().unwrap();
+        let cancel = CancelToken::new();
+        cancel.cancel_run(1);
+        // stands in for a controller cancelling run 2 once it's underway
+        let controller = cancel.clone();
+        let watch = StopCondition::new("never", move |world| {
+            let a = world.column::<A>().ok_or(Error::ComponentNotFound)?;
+            if bytemuck::pod_collect_to_vec::<u8, f64>(a.column)[0] == 21.0 {
+                controller.cancel_run(2);
+            }
+            Ok(false)
+        });
+        let build = |config: &RunConfig| {
+            let mut world = increment.world();
+            world.spawn(A((10.0 * config.run_id as f64).into()));
+            world.build()
+        };
+        let summary = Batch::new(5)
+            .output(dir.path())
+            .stop_when(watch)
+            .cancel_token(cancel.clone())
+            .run_campaign(4, build, Client::cpu().unwrap())
+            .unwrap();
+        assert_eq!(summary.cancelled, vec![1, 2]);
+        let completed = summary.runs.iter().map(|run| run.ticks).collect::<Vec<_>>();
+        assert_eq!(completed, vec![5, 5]);
+        assert_eq!(summary.ticks, 11);
+        assert!(!dir.path().join("1").exists());
+        let config = RunConfig::read_from_dir(dir.path().join("2")).unwrap();
+        assert_eq!(
+            config.stop_reason.as_deref(),
+            Some(CancelToken::STOP_REASON)
+        );
+
+        cancel.cancel();
+        let summary = Batch::new(5)
+            .cancel_token(cancel)
+            .run_campaign(3, build, Client::cpu().unwrap())
+            .unwrap();
+        assert!(summary.runs.is_empty());
+        assert_eq!(summary.cancelled, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_campaign_recording() {
+        let dir = tempfile::tempdir().unwrap();
+        let final_a = Metric::new("a", |world| {
+            let a = world.column::<A>().ok_or(Error::ComponentNotFound)?;
+            Ok(bytemuck::pod_collect_to_vec::<u8, f64>(a.column)[0])
+        });
+        let x0 = [0.0, 5.0, 1.0, 7.0, 2.0];
+        let summary = Batch::new(2)
+            .output(dir.path())
+            .metric(final_a)
+            .recording(Recording::runs(|config| config.run_id == 0).worst("a", 2))
+            .run_campaign(
+                5,
+                |config| {
+                    let mut world = increment.world();
+                    world.spawn(A(x0[config.run_id as usize].into()));
+                    world.build()
+                },
+                Client::cpu().unwrap(),
+            )
+            .unwrap();
+        assert_eq!(summary.recorded, vec![0, 1, 3]);
+        for run_id in 0..5 {
+            let run_dir = dir.path().join(run_id.to_string());
+            let config = RunConfig::read_from_dir(&run_dir).unwrap();
+            assert_eq!(config.metrics["a"], x0[run_id as usize] + 2.0);
+            let recorded = summary.recorded.contains(&run_id);
+            assert_eq!(run_dir.join("world").exists(), recorded);
+            assert_eq!(run_dir.join("tick_exec").exists(), recorded);
+        }
+
+        let result = Batch::new(2)
+            .recording(Recording::default().worst("miss", 1))
+            .run_campaign(1, |_| increment.world().build(), Client::cpu().unwrap());
+        assert!(matches!(result, Err(Error::UnknownMetric(_))));
+    }
+}
@@ -0,0 +1,505 @@
+//! A bounding volume hierarchy over entity bounds, for broadphase collision, ray casts and visibility queries.
+//!
+//! The tree lives on the host and is maintained incrementally between ticks: leaves store bounds
+//! enlarged by a margin, so small motions don't touch the tree at all, larger motions refit the
+//! leaf's ancestors in place, and the tree is only rebuilt once refitting has degraded it noticeably.
+//! This keeps mostly static scenes almost free to maintain.
+use std::collections::HashMap;
+
+use impeller::EntityId;
+
+use crate::graph::Edge;
+
+/// An axis aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: [f64; 3],
+    pub max: [f64; 3],
+}
+
+impl Aabb {
+    pub fn new(min: [f64; 3], max: [f64; 3]) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_sphere(center: [f64; 3], radius: f64) -> Self {
+        Self {
+            min: center.map(|c| c - radius),
+            max: center.map(|c| c + radius),
+        }
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: [0, 1, 2].map(|i| self.min[i].min(other.min[i])),
+            max: [0, 1, 2].map(|i| self.max[i].max(other.max[i])),
+        }
+    }
+
+    pub fn expand(&self, margin: f64) -> Aabb {
+        Aabb {
+            min: self.min.map(|c| c - margin),
+            max: self.max.map(|c| c + margin),
+        }
+    }
+
+    pub fn center(&self) -> [f64; 3] {
+        [0, 1, 2].map(|i| (self.min[i] + self.max[i]) / 2.0)
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let [x, y, z] = [0, 1, 2].map(|i| self.max[i] - self.min[i]);
+        2.0 * (x * y + y * z + z * x)
+    }
+
+    pub fn contains(&self, other: &Aabb) -> bool {
+        (0..3).all(|i| self.min[i] <= other.min[i] && other.max[i] <= self.max[i])
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    /// Returns the distance along the ray at which it enters the box, if it does so before `max_toi`.
+    ///
+    /// `dir` doesn't have to be normalized, the distance is measured in multiples of it.
+    pub fn ray_toi(&self, origin: [f64; 3], dir: [f64; 3], max_toi: f64) -> Option<f64> {
+        let mut t_min = 0.0f64;
+        let mut t_max = max_toi;
+        for i in 0..3 {
+            let inv = 1.0 / dir[i];
+            let a = (self.min[i] - origin[i]) * inv;
+            let b = (self.max[i] - origin[i]) * inv;
+            // a zero direction yields `NaN` when the origin lies on a slab, `max`/`min` ignore it
+            t_min = t_min.max(a.min(b));
+            t_max = t_max.min(a.max(b));
+            if t_min > t_max {
+                return None;
+            }
+        }
+        Some(t_min)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    aabb: Aabb,
+    parent: Option<usize>,
+    kind: NodeKind,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum NodeKind {
+    Leaf(EntityId),
+    Internal([usize; 2]),
+}
+
+/// A dynamic bounding volume hierarchy keyed by entity.
+#[derive(Clone, Debug)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    leaves: HashMap<EntityId, usize>,
+    margin: f64,
+    rebuild_threshold: f64,
+    rebuild_cost: f64,
+}
+
+impl Default for Bvh {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+impl Bvh {
+    /// Creates an empty tree whose leaves are enlarged by `margin` on every side.
+    ///
+    /// The margin should be about the distance a typical entity moves over a few ticks.
+    pub fn new(margin: f64) -> Self {
+        Self {
+            nodes: vec![],
+            free: vec![],
+            root: None,
+            leaves: HashMap::new(),
+            margin,
+            rebuild_threshold: 1.5,
+            rebuild_cost: 0.0,
+        }
+    }
+
+    /// Sets how much worse than right after the last rebuild the tree may get before [`Bvh::maintain`] rebuilds it.
+    pub fn rebuild_threshold(mut self, threshold: f64) -> Self {
+        self.rebuild_threshold = threshold;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Returns the enlarged bounds stored for `entity`.
+    pub fn fat_aabb(&self, entity: EntityId) -> Option<Aabb> {
+        self.leaves.get(&entity).map(|&leaf| self.nodes[leaf].aabb)
+    }
+
+    /// Inserts `entity`, or updates its bounds if it is already in the tree.
+    pub fn insert(&mut self, entity: EntityId, aabb: Aabb) {
+        if self.leaves.contains_key(&entity) {
+            self.update(entity, aabb);
+            return;
+        }
+        let leaf = self.alloc(Node {
+            aabb: aabb.expand(self.margin),
+            parent: None,
+            kind: NodeKind::Leaf(entity),
+        });
+        self.leaves.insert(entity, leaf);
+        self.insert_leaf(leaf);
+    }
+
+    pub fn remove(&mut self, entity: EntityId) -> bool {
+        let Some(leaf) = self.leaves.remove(&entity) else {
+            return false;
+        };
+        self.remove_leaf(leaf);
+        self.free.push(leaf);
+        true
+    }
+
+    /// Updates the bounds of `entity`, returning true if the tree had to be refit.
+    ///
+    /// Bounds that still fit into the enlarged leaf are a no-op, otherwise the leaf and its ancestors
+    /// are refit in place without changing the structure of the tree.
+    pub fn update(&mut self, entity: EntityId, aabb: Aabb) -> bool {
+        let Some(&leaf) = self.leaves.get(&entity) else {
+            self.insert(entity, aabb);
+            return true;
+        };
+        if self.nodes[leaf].aabb.contains(&aabb) {
+            return false;
+        }
+        self.nodes[leaf].aabb = aabb.expand(self.margin);
+        self.refit_ancestors(self.nodes[leaf].parent);
+        true
+    }
+
+    /// Returns the surface area heuristic cost of the tree, the summed surface area of its internal nodes.
+    ///
+    /// The cost is proportional to the expected number of nodes visited by a query.
+    pub fn cost(&self) -> f64 {
+        let mut cost = 0.0;
+        let mut stack = self.root.into_iter().collect::<Vec<_>>();
+        while let Some(index) = stack.pop() {
+            if let NodeKind::Internal(children) = self.nodes[index].kind {
+                cost += self.nodes[index].aabb.surface_area();
+                stack.extend(children);
+            }
+        }
+        cost
+    }
+
+    /// Rebuilds the tree if refitting degraded it past the rebuild threshold, returning true if it did.
+    ///
+    /// Call this once per tick, after updating the bounds of every moving entity.
+    pub fn maintain(&mut self) -> bool {
+        if self.cost() <= self.rebuild_threshold * self.rebuild_cost {
+            return false;
+        }
+        self.rebuild();
+        true
+    }
+
+    /// Rebuilds the tree from scratch, splitting the leaves at the median of their longest axis.
+    pub fn rebuild(&mut self) {
+        let mut leaves = self
+            .leaves
+            .iter()
+            .map(|(&entity, &leaf)| (entity, self.nodes[leaf].aabb))
+            .collect::<Vec<_>>();
+        self.nodes.clear();
+        self.free.clear();
+        self.leaves.clear();
+        self.root = self.build(&mut leaves, None);
+        self.rebuild_cost = self.cost();
+    }
+
+    /// Returns every entity whose enlarged bounds intersect `aabb`.
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<EntityId> {
+        let mut out = vec![];
+        let mut stack = self.root.into_iter().collect::<Vec<_>>();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.aabb.intersects(aabb) {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf(entity) => out.push(entity),
+                NodeKind::Internal(children) => stack.extend(children),
+            }
+        }
+        out
+    }
+
+    /// Returns the pairs of entities whose enlarged bounds overlap, for the narrow phase to check.
+    pub fn overlapping_pairs(&self) -> Vec<Edge> {
+        let mut pairs = vec![];
+        for (&entity, &leaf) in &self.leaves {
+            for other in self.query_aabb(&self.nodes[leaf].aabb) {
+                if entity < other {
+                    pairs.push(Edge::new(entity, other));
+                }
+            }
+        }
+        pairs.sort_by_key(|edge| (edge.from, edge.to));
+        pairs
+    }
+
+    /// Casts a ray and returns the closest entity it hits along with the distance to the hit.
+    ///
+    /// The tree only culls entities whose bounds the ray misses, `hit` computes the exact distance
+    /// at which the ray hits an entity's shape, or `None` if it misses it. A visibility query is a ray
+    /// cast towards the target, with `max_toi` set to its distance.
+    pub fn ray_cast(
+        &self,
+        origin: [f64; 3],
+        dir: [f64; 3],
+        max_toi: f64,
+        mut hit: impl FnMut(EntityId) -> Option<f64>,
+    ) -> Option<(EntityId, f64)> {
+        let mut best: Option<(EntityId, f64)> = None;
+        let mut stack = self.root.into_iter().collect::<Vec<_>>();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let max_toi = best.map(|(_, toi)| toi).unwrap_or(max_toi);
+            if node.aabb.ray_toi(origin, dir, max_toi).is_none() {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf(entity) => {
+                    if let Some(toi) = hit(entity).filter(|toi| *toi <= max_toi) {
+                        best = Some((entity, toi));
+                    }
+                }
+                NodeKind::Internal(children) => stack.extend(children),
+            }
+        }
+        best
+    }
+
+    fn alloc(&mut self, node: Node) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn build(&mut self, leaves: &mut [(EntityId, Aabb)], parent: Option<usize>) -> Option<usize> {
+        match leaves {
+            [] => None,
+            [(entity, aabb)] => {
+                let leaf = self.alloc(Node {
+                    aabb: *aabb,
+                    parent,
+                    kind: NodeKind::Leaf(*entity),
+                });
+                self.leaves.insert(*entity, leaf);
+                Some(leaf)
+            }
+            _ => {
+                let bounds = leaves
+                    .iter()
+                    .map(|(_, aabb)| Aabb::new(aabb.center(), aabb.center()))
+                    .reduce(|a, b| a.union(&b))
+                    .expect("leaves not empty");
+                let extent = [0, 1, 2].map(|i| bounds.max[i] - bounds.min[i]);
+                let axis = (0..3)
+                    .max_by(|&a, &b| extent[a].total_cmp(&extent[b]))
+                    .expect("axes not empty");
+                leaves.sort_by(|(_, a), (_, b)| a.center()[axis].total_cmp(&b.center()[axis]));
+                let (left, right) = leaves.split_at_mut(leaves.len() / 2);
+                let index = self.alloc(Node {
+                    aabb: bounds,
+                    parent,
+                    kind: NodeKind::Internal([0, 0]),
+                });
+                let left = self.build(left, Some(index)).expect("left half not empty");
+                let right = self
+                    .build(right, Some(index))
+                    .expect("right half not empty");
+                self.nodes[index].aabb = self.nodes[left].aabb.union(&self.nodes[right].aabb);
+                self.nodes[index].kind = NodeKind::Internal([left, right]);
+                Some(index)
+            }
+        }
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        let Some(root) = self.root else {
+            self.root = Some(leaf);
+            self.nodes[leaf].parent = None;
+            return;
+        };
+        // descend towards the sibling that grows the tree's surface area the least
+        let aabb = self.nodes[leaf].aabb;
+        let mut index = root;
+        while let NodeKind::Internal(children) = self.nodes[index].kind {
+            let area = self.nodes[index].aabb.surface_area();
+            let combined = self.nodes[index].aabb.union(&aabb).surface_area();
+            let cost = 2.0 * combined;
+            let inheritance = 2.0 * (combined - area);
+            let child_cost = |child: usize| {
+                let node = &self.nodes[child];
+                let grown = node.aabb.union(&aabb).surface_area();
+                match node.kind {
+                    NodeKind::Leaf(_) => grown + inheritance,
+                    NodeKind::Internal(_) => grown - node.aabb.surface_area() + inheritance,
+                }
+            };
+            let [a, b] = children.map(child_cost);
+            if cost < a && cost < b {
+                break;
+            }
+            index = if a < b { children[0] } else { children[1] };
+        }
+
+        let sibling = index;
+        let old_parent = self.nodes[sibling].parent;
+        let parent = self.alloc(Node {
+            aabb: self.nodes[sibling].aabb.union(&aabb),
+            parent: old_parent,
+            kind: NodeKind::Internal([sibling, leaf]),
+        });
+        self.nodes[sibling].parent = Some(parent);
+        self.nodes[leaf].parent = Some(parent);
+        match old_parent {
+            Some(old_parent) => {
+                self.replace_child(old_parent, sibling, parent);
+                self.refit_ancestors(Some(old_parent));
+            }
+            None => self.root = Some(parent),
+        }
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        let Some(parent) = self.nodes[leaf].parent else {
+            self.root = None;
+            return;
+        };
+        let NodeKind::Internal(children) = self.nodes[parent].kind else {
+            unreachable!("parent of a node is always internal");
+        };
+        let sibling = if children[0] == leaf {
+            children[1]
+        } else {
+            children[0]
+        };
+        let grandparent = self.nodes[parent].parent;
+        self.nodes[sibling].parent = grandparent;
+        match grandparent {
+            Some(grandparent) => {
+                self.replace_child(grandparent, parent, sibling);
+                self.refit_ancestors(Some(grandparent));
+            }
+            None => self.root = Some(sibling),
+        }
+        self.free.push(parent);
+    }
+
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        if let NodeKind::Internal(children) = &mut self.nodes[parent].kind {
+            for child in children.iter_mut().filter(|c| **c == old) {
+                *child = new;
+            }
+        }
+    }
+
+    fn refit_ancestors(&mut self, mut index: Option<usize>) {
+        while let Some(i) = index {
+            if let NodeKind::Internal([a, b]) = self.nodes[i].kind {
+                self.nodes[i].aabb = self.nodes[a].aabb.union(&self.nodes[b].aabb);
+            }
+            index = self.nodes[i].parent;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box(center: [f64; 3]) -> Aabb {
+        Aabb::from_sphere(center, 0.5)
+    }
+
+    #[test]
+    fn test_bvh_queries() {
+        let mut bvh = Bvh::new(0.1);
+        for i in 0..16 {
+            bvh.insert(EntityId(i), unit_box([i as f64 * 2.0, 0.0, 0.0]));
+        }
+        assert_eq!(bvh.len(), 16);
+        assert!(bvh.overlapping_pairs().is_empty());
+
+        let mut hits = bvh.query_aabb(&Aabb::new([3.0, -1.0, -1.0], [7.0, 1.0, 1.0]));
+        hits.sort();
+        assert_eq!(hits, vec![EntityId(2), EntityId(3)]);
+
+        // entity 4 occludes everything behind it
+        let hit = bvh.ray_cast([7.0, 0.0, 0.0], [1.0, 0.0, 0.0], 100.0, |e| {
+            Some(e.0 as f64 * 2.0 - 0.5 - 7.0)
+        });
+        assert_eq!(hit, Some((EntityId(4), 0.5)));
+        let hit = bvh.ray_cast([7.0, 0.0, 0.0], [0.0, 1.0, 0.0], 100.0, |_| Some(0.0));
+        assert_eq!(hit, None);
+
+        bvh.update(EntityId(1), unit_box([0.5, 0.0, 0.0]));
+        let pairs = bvh.overlapping_pairs();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].from, pairs[0].to), (EntityId(0), EntityId(1)));
+
+        assert!(bvh.remove(EntityId(0)));
+        assert!(!bvh.remove(EntityId(0)));
+        assert!(bvh.overlapping_pairs().is_empty());
+        assert_eq!(bvh.len(), 15);
+    }
+
+    #[test]
+    fn test_bvh_maintain() {
+        let mut bvh = Bvh::new(0.1);
+        for i in 0..32 {
+            bvh.insert(EntityId(i), unit_box([i as f64 * 2.0, 0.0, 0.0]));
+        }
+        bvh.rebuild();
+        assert!(!bvh.maintain());
+
+        // small motions stay within the enlarged leaves
+        assert!(!bvh.update(EntityId(3), unit_box([6.05, 0.0, 0.0])));
+
+        // scrambling the scene degrades the refit tree until it is rebuilt
+        for i in 0..32 {
+            bvh.update(
+                EntityId(i),
+                unit_box([((i * 17) % 32) as f64 * 2.0, 0.0, 0.0]),
+            );
+        }
+        let degraded = bvh.cost();
+        assert!(bvh.maintain());
+        assert!(bvh.cost() < degraded);
+        assert_eq!(bvh.len(), 32);
+
+        let mut hits = bvh.query_aabb(&unit_box([34.0, 0.0, 0.0]));
+        hits.sort();
+        assert_eq!(hits, vec![EntityId(1)]);
+    }
+}
@@ -0,0 +1,250 @@
+use std::ops::Add;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::Error;
+
+/// Julian date of J2000, 2000-01-01 12:00:00 TT.
+pub const J2000: f64 = 2451545.0;
+/// TT - TAI, in seconds.
+const TT_TAI: f64 = 32.184;
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// The dates leap seconds took effect, and TAI - UTC from then on.
+const LEAP_SECONDS: [(i64, u32, f64); 28] = [
+    (1972, 1, 10.0),
+    (1972, 7, 11.0),
+    (1973, 1, 12.0),
+    (1974, 1, 13.0),
+    (1975, 1, 14.0),
+    (1976, 1, 15.0),
+    (1977, 1, 16.0),
+    (1978, 1, 17.0),
+    (1979, 1, 18.0),
+    (1980, 1, 19.0),
+    (1981, 7, 20.0),
+    (1982, 7, 21.0),
+    (1983, 7, 22.0),
+    (1985, 7, 23.0),
+    (1988, 1, 24.0),
+    (1990, 1, 25.0),
+    (1991, 1, 26.0),
+    (1992, 7, 27.0),
+    (1993, 7, 28.0),
+    (1994, 7, 29.0),
+    (1996, 1, 30.0),
+    (1997, 7, 31.0),
+    (1999, 1, 32.0),
+    (2006, 1, 33.0),
+    (2009, 1, 34.0),
+    (2012, 7, 35.0),
+    (2015, 7, 36.0),
+    (2017, 1, 37.0),
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeScale {
+    /// Coordinated Universal Time, which follows the Earth's rotation with leap seconds.
+    Utc,
+    /// International Atomic Time.
+    Tai,
+    /// Terrestrial Time, the uniform scale ephemerides are computed in.
+    #[default]
+    Tt,
+}
+
+impl FromStr for TimeScale {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "utc" => Ok(TimeScale::Utc),
+            "tai" => Ok(TimeScale::Tai),
+            "tt" => Ok(TimeScale::Tt),
+            _ => Err(Error::UnknownTimeScale(s.to_string())),
+        }
+    }
+}
+
+/// An instant, stored as TT seconds since J2000 so adding durations never has to deal with leap
+/// seconds.
+///
+/// UTC is counted like a Julian date, with 86400 second days, so during a leap second two UTC
+/// times map to the same instant.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Epoch {
+    tt: f64,
+}
+
+impl Epoch {
+    pub const J2000: Epoch = Epoch { tt: 0.0 };
+
+    /// The instant `seconds` after J2000 (2000-01-01 12:00:00) in `scale`.
+    pub fn from_seconds(seconds: f64, scale: TimeScale) -> Self {
+        let tt = match scale {
+            TimeScale::Tt => seconds,
+            TimeScale::Tai => seconds + TT_TAI,
+            TimeScale::Utc => seconds + leap_seconds(seconds) + TT_TAI,
+        };
+        Epoch { tt }
+    }
+
+    pub fn from_jd(jd: f64, scale: TimeScale) -> Self {
+        Self::from_seconds((jd - J2000) * SECONDS_PER_DAY, scale)
+    }
+
+    /// The instant at a UTC calendar date and time of day.
+    pub fn from_utc(year: i64, month: u32, day: u32, hour: u32, minute: u32, second: f64) -> Self {
+        let days = days_from_civil(year, month, day) - days_from_civil(2000, 1, 1);
+        let seconds = days as f64 * SECONDS_PER_DAY - SECONDS_PER_DAY / 2.0
+            + (hour * 3600 + minute * 60) as f64
+            + second;
+        Self::from_seconds(seconds, TimeScale::Utc)
+    }
+
+    /// Seconds since J2000 in `scale`.
+    pub fn seconds(&self, scale: TimeScale) -> f64 {
+        let tai = self.tt - TT_TAI;
+        match scale {
+            TimeScale::Tt => self.tt,
+            TimeScale::Tai => tai,
+            TimeScale::Utc => tai - self.leap_seconds(),
+        }
+    }
+
+    pub fn jd(&self, scale: TimeScale) -> f64 {
+        J2000 + self.seconds(scale) / SECONDS_PER_DAY
+    }
+
+    /// TAI - UTC at this instant, in seconds.
+    pub fn leap_seconds(&self) -> f64 {
+        // TAI - UTC only changes at whole seconds, so guessing from the TAI time and correcting
+        // once lands on the right entry except inside the leap second itself
+        let tai = self.tt - TT_TAI;
+        leap_seconds(tai - leap_seconds(tai))
+    }
+}
+
+impl Add<Duration> for Epoch {
+    type Output = Epoch;
+
+    fn add(self, rhs: Duration) -> Self::Output {
+        Epoch {
+            tt: self.tt + rhs.as_secs_f64(),
+        }
+    }
+}
+
+/// TAI - UTC at `utc` seconds since J2000. Before 1972 UTC was steered with fractional offsets,
+/// which aren't modelled, so the first value is used.
+fn leap_seconds(utc: f64) -> f64 {
+    let j2000 = days_from_civil(2000, 1, 1);
+    LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|(year, month, _)| {
+            let start = (days_from_civil(*year, *month, 1) - j2000) as f64 * SECONDS_PER_DAY;
+            utc >= start - SECONDS_PER_DAY / 2.0
+        })
+        .map_or(LEAP_SECONDS[0].2, |(_, _, delta)| *delta)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Maps simulation time onto calendar time, and controls how fast wall-clock time drives the
+/// simulation through [`crate::WorldExec::step`].
+#[derive(Clone, Debug)]
+pub struct Clock {
+    /// The instant of tick zero.
+    pub epoch: Epoch,
+    /// Simulated seconds per wall-clock second.
+    pub time_scale: f64,
+    pub paused: bool,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self {
+            epoch: Epoch::J2000,
+            time_scale: 1.0,
+            paused: false,
+        }
+    }
+}
+
+impl Clock {
+    pub fn new(epoch: Epoch) -> Self {
+        Self {
+            epoch,
+            ..Default::default()
+        }
+    }
+
+    pub fn time_scale(mut self, time_scale: f64) -> Self {
+        self.time_scale = time_scale;
+        self
+    }
+
+    /// The instant reached `time` into the simulation.
+    pub fn epoch_at(&self, time: Duration) -> Epoch {
+        self.epoch + time
+    }
+
+    /// How much simulated time `wall` of wall-clock time is worth, none while paused.
+    pub fn sim_duration(&self, wall: Duration) -> Duration {
+        if self.paused {
+            Duration::ZERO
+        } else {
+            wall.mul_f64(self.time_scale.max(0.0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epoch_time_scales() {
+        let j2000 = Epoch::from_utc(2000, 1, 1, 11, 58, 55.816);
+        assert!(j2000.seconds(TimeScale::Tt).abs() < 1e-6);
+        assert_eq!(j2000.leap_seconds(), 32.0);
+
+        // the 2016 leap second, TAI - UTC steps from 36 to 37
+        let before = Epoch::from_utc(2016, 12, 31, 23, 59, 59.0);
+        let after = Epoch::from_utc(2017, 1, 1, 0, 0, 0.0);
+        assert_eq!(before.leap_seconds(), 36.0);
+        assert_eq!(after.leap_seconds(), 37.0);
+        let elapsed = after.seconds(TimeScale::Tai) - before.seconds(TimeScale::Tai);
+        assert!((elapsed - 2.0).abs() < 1e-6);
+
+        let epoch = Epoch::from_jd(2460389.625, TimeScale::Utc);
+        assert!((epoch.jd(TimeScale::Utc) - 2460389.625).abs() < 1e-9);
+        let tt_minus_utc = epoch.seconds(TimeScale::Tt) - epoch.seconds(TimeScale::Utc);
+        assert!((tt_minus_utc - 69.184).abs() < 1e-6);
+        assert_eq!("UTC".parse::<TimeScale>().unwrap(), TimeScale::Utc);
+        assert!("gps".parse::<TimeScale>().is_err());
+    }
+
+    #[test]
+    fn test_clock() {
+        let mut clock = Clock::new(Epoch::J2000).time_scale(10.0);
+        let epoch = clock.epoch_at(Duration::from_secs(60));
+        assert_eq!(epoch.seconds(TimeScale::Tt), 60.0);
+        assert_eq!(
+            clock.sim_duration(Duration::from_secs(2)),
+            Duration::from_secs(20)
+        );
+        clock.paused = true;
+        assert_eq!(clock.sim_duration(Duration::from_secs(2)), Duration::ZERO);
+    }
+}
@@ -0,0 +1,128 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use impeller::const_fnv1a_hash::fnv1a_hash_128;
+use nox::xla::{self, HloModuleProto, PjRtLoadedExecutable};
+use nox::Client;
+
+use crate::Error;
+
+/// The length of the key that starts every entry.
+const KEY_LEN: usize = std::mem::size_of::<u128>();
+
+/// The number of executables loaded from, and compiled into, a [`CompileCache`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// A persistent on-disk cache of compiled XLA executables.
+///
+/// Executables are keyed by a hash of their HLO module and the platform and compile options of the client
+/// that compiled them, so a changed system, a different backend or disabled optimizations are always recompiled.
+/// The key also covers the versions of XLA and of this crate, and every entry stores its full key, which is
+/// checked on load, so an entry is never mistaken for another whose key shares its file name.
+#[derive(Clone)]
+pub struct CompileCache {
+    dir: PathBuf,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl CompileCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            hits: Default::default(),
+            misses: Default::default(),
+        }
+    }
+
+    /// Returns a cache in the user's cache directory.
+    pub fn user_cache() -> Option<Self> {
+        let dirs = directories::ProjectDirs::from("systems", "elodin", "cli")?;
+        Some(Self::new(dirs.cache_dir().join("xla")))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Computes the cache key of `hlo_module` when compiled by `client`.
+    pub fn key(client: &Client, hlo_module: &HloModuleProto) -> Result<u128, Error> {
+        let (platform_name, platform_version) = client.platform();
+        let options = client.compile_options().to_bytes()?;
+        let mut buf = hlo_module.to_bytes();
+        for part in [
+            platform_name.as_bytes(),
+            platform_version.as_bytes(),
+            options.as_slice(),
+            xla::VERSION.as_bytes(),
+            env!("CARGO_PKG_VERSION").as_bytes(),
+        ] {
+            buf.push(0);
+            buf.extend_from_slice(part);
+        }
+        Ok(fnv1a_hash_128(&buf, None))
+    }
+
+    /// Loads the executable for `hlo_module` from the cache, or compiles and stores it on a miss.
+    ///
+    /// Entries that fail to load, e.g because they were written by a different XLA version, are recompiled and overwritten.
+    pub fn compile(
+        &self,
+        client: &Client,
+        hlo_module: &HloModuleProto,
+    ) -> Result<PjRtLoadedExecutable, Error> {
+        let key = Self::key(client, hlo_module)?;
+        let path = self.dir.join(format!("{:016x}.xla", key as u64));
+        if let Ok(bytes) = std::fs::read(&path) {
+            if bytes.get(..KEY_LEN) != Some(&key.to_le_bytes()[..]) {
+                tracing::warn!(?path, "cached executable has a different key");
+            } else {
+                match client.load_executable(&bytes[KEY_LEN..]) {
+                    Ok(exec) => {
+                        self.hits.fetch_add(1, Ordering::Relaxed);
+                        return Ok(exec);
+                    }
+                    Err(err) => tracing::warn!(?err, ?path, "failed to load cached executable"),
+                }
+            }
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let exec = client.compile(&hlo_module.computation())?;
+        // a failure to store the executable only costs a recompile next time
+        if let Err(err) = self.store(&path, key, &exec) {
+            tracing::warn!(?err, ?path, "failed to cache executable");
+        }
+        Ok(exec)
+    }
+
+    /// Removes every cached executable.
+    pub fn clear(&self) -> Result<(), Error> {
+        match std::fs::remove_dir_all(&self.dir) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn store(&self, path: &Path, key: u128, exec: &PjRtLoadedExecutable) -> Result<(), Error> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut bytes = key.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&exec.serialize()?);
+        // write then rename, so concurrent processes never load a partially written entry
+        let tmp_path = path.with_extension(format!("tmp{}", std::process::id()));
+        std::fs::write(&tmp_path, bytes)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
@@ -9,6 +9,10 @@ pub trait Component:
 #[derive(Component, ReprMonad)]
 pub struct WorldPos<R: OwnedRepr = Op>(pub nox::SpatialTransform<f64, R>);
 
+/// Keplerian elements of an orbiting entity, see [`nox::OrbitalElements`].
+#[derive(Component, ReprMonad)]
+pub struct OrbitalElements<R: OwnedRepr = Op>(pub nox::OrbitalElements<f64, R>);
+
 #[derive(Component, ReprMonad)]
 pub struct Seed<R: OwnedRepr = Op>(pub Scalar<u64, R>);
 
@@ -0,0 +1,290 @@
+//! Lockstep co-simulation with an external participant, which advances in fixed steps alongside
+//! the simulation and trades component values with it at every step boundary.
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use impeller::{ComponentId, EntityId};
+use serde::{Deserialize, Serialize};
+
+use crate::dense_output::entity_value;
+use crate::{set_value, Error, TickHooks, World};
+
+/// A message of the co-simulation protocol.
+///
+/// The simulation opens with [`CoSimMsg::Hello`], advertising its step and the values it trades,
+/// and the participant answers with [`CoSimMsg::Ready`] and the step it wants to advance by,
+/// which has to be a whole number of simulation steps. From then on the simulation sends a
+/// [`CoSimMsg::Step`] at every boundary, starting with the initial state, and waits for the
+/// matching [`CoSimMsg::Ack`] before going on.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CoSimMsg {
+    Hello {
+        time_step: f64,
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+    },
+    Ready {
+        time_step: f64,
+    },
+    /// The simulation's outputs at `tick`, in the order they were advertised.
+    Step {
+        tick: u64,
+        time: f64,
+        outputs: Vec<Vec<f64>>,
+    },
+    /// The participant's inputs for the step starting at `tick`, held until the next boundary.
+    Ack {
+        tick: u64,
+        inputs: Vec<Vec<f64>>,
+    },
+}
+
+/// A connection to the other side of a co-simulation.
+pub trait CoSimLink: Send {
+    fn send(&mut self, msg: &CoSimMsg) -> Result<(), Error>;
+    fn recv(&mut self) -> Result<CoSimMsg, Error>;
+}
+
+/// Newline delimited JSON over TCP.
+pub struct TcpLink {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl TcpLink {
+    pub fn new(stream: TcpStream) -> Result<Self, Error> {
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    /// Waits for a participant to connect on `addr`.
+    pub fn accept(addr: impl ToSocketAddrs) -> Result<Self, Error> {
+        let (stream, _) = TcpListener::bind(addr)?.accept()?;
+        Self::new(stream)
+    }
+
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self, Error> {
+        Self::new(TcpStream::connect(addr)?)
+    }
+
+    /// Gives up on a participant that takes longer than `timeout` to answer.
+    pub fn timeout(self, timeout: Duration) -> Result<Self, Error> {
+        self.writer.set_read_timeout(Some(timeout))?;
+        Ok(self)
+    }
+}
+
+impl CoSimLink for TcpLink {
+    fn send(&mut self, msg: &CoSimMsg) -> Result<(), Error> {
+        let mut line = serde_json::to_vec(msg)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)?;
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<CoSimMsg, Error> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(Error::ChannelClosed);
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+/// An in-process link, for participants running on another thread.
+pub struct ChannelLink {
+    tx: flume::Sender<CoSimMsg>,
+    rx: flume::Receiver<CoSimMsg>,
+}
+
+impl ChannelLink {
+    pub fn pair() -> (Self, Self) {
+        let (a_tx, a_rx) = flume::unbounded();
+        let (b_tx, b_rx) = flume::unbounded();
+        (Self { tx: a_tx, rx: b_rx }, Self { tx: b_tx, rx: a_rx })
+    }
+}
+
+impl CoSimLink for ChannelLink {
+    fn send(&mut self, msg: &CoSimMsg) -> Result<(), Error> {
+        self.tx.send(msg.clone()).map_err(|_| Error::ChannelClosed)
+    }
+
+    fn recv(&mut self) -> Result<CoSimMsg, Error> {
+        self.rx.recv().map_err(|_| Error::ChannelClosed)
+    }
+}
+
+/// The values traded with a co-simulation participant, each an f64 component of one entity.
+#[derive(Clone, Debug, Default)]
+pub struct CoSimulation {
+    inputs: Vec<(String, ComponentId, EntityId)>,
+    outputs: Vec<(String, ComponentId, EntityId)>,
+}
+
+impl CoSimulation {
+    /// A value the participant writes at every boundary.
+    pub fn input(mut self, name: impl Into<String>, id: ComponentId, entity: EntityId) -> Self {
+        self.inputs.push((name.into(), id, entity));
+        self
+    }
+
+    /// A value sent to the participant at every boundary.
+    pub fn output(mut self, name: impl Into<String>, id: ComponentId, entity: EntityId) -> Self {
+        self.outputs.push((name.into(), id, entity));
+        self
+    }
+
+    /// Negotiates the step with the participant and trades the initial state, then registers a
+    /// post-tick hook that blocks at every boundary until the participant has caught up.
+    pub fn install(
+        self,
+        world: &mut World,
+        hooks: &mut TickHooks,
+        mut link: impl CoSimLink + 'static,
+    ) -> Result<(), Error> {
+        let h = world.sim_time_step.0.as_secs_f64();
+        link.send(&CoSimMsg::Hello {
+            time_step: h,
+            inputs: self.inputs.iter().map(|(name, ..)| name.clone()).collect(),
+            outputs: self.outputs.iter().map(|(name, ..)| name.clone()).collect(),
+        })?;
+        let CoSimMsg::Ready { time_step } = link.recv()? else {
+            return Err(Error::CoSim("expected the participant to be ready".into()));
+        };
+        let ratio = time_step / h;
+        let every = ratio.round();
+        if every < 1.0 || (ratio - every).abs() > 1e-9 * ratio {
+            return Err(Error::CoSim(format!(
+                "participant step {time_step} is not a multiple of the simulation step {h}"
+            )));
+        }
+        let every = every as u64;
+        self.exchange(world, &mut link)?;
+
+        let link = Arc::new(Mutex::new(link));
+        hooks.add_post_tick(move |ctx| {
+            if ctx.tick % every != 0 {
+                return Ok(());
+            }
+            let mut link = link.lock().map_err(|_| Error::HookPoisoned)?;
+            self.exchange(ctx.world, &mut *link)
+        });
+        Ok(())
+    }
+
+    fn exchange(&self, world: &mut World, link: &mut impl CoSimLink) -> Result<(), Error> {
+        let tick = world.tick;
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|(_, id, entity)| entity_value(world, *id, *entity, tick))
+            .collect::<Result<Vec<_>, _>>()?;
+        let time = world.sim_time_step.0.as_secs_f64() * tick as f64;
+        link.send(&CoSimMsg::Step {
+            tick,
+            time,
+            outputs,
+        })?;
+        let CoSimMsg::Ack {
+            tick: acked,
+            inputs,
+        } = link.recv()?
+        else {
+            return Err(Error::CoSim("expected an ack".into()));
+        };
+        if acked != tick || inputs.len() != self.inputs.len() {
+            return Err(Error::CoSim(format!(
+                "ack for tick {acked} with {} inputs doesn't match tick {tick}",
+                inputs.len()
+            )));
+        }
+        for ((_, id, entity), value) in self.inputs.iter().zip(inputs) {
+            set_value(world, *id, *entity, &value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Component, Query, WorldExt};
+    use impeller::ComponentExt;
+    use nox::{Op, OwnedRepr, Scalar};
+    use nox_ecs_macros::{Archetype, ReprMonad};
+
+    #[derive(Component, ReprMonad)]
+    struct Command<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+    #[derive(Component, ReprMonad)]
+    struct Total<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+    #[derive(Archetype)]
+    struct Accumulator {
+        command: Command,
+        total: Total,
+    }
+
+    fn accumulate(q: Query<(Command, Total)>) -> Query<Total> {
+        q.map(|c: Command, t: Total| Total(t.0 + c.0)).unwrap()
+    }
+
+    #[test]
+    fn test_lockstep() {
+        let mut world = World::default();
+        let entity = world
+            .spawn(Accumulator {
+                command: Command(0.0.into()),
+                total: Total(0.0.into()),
+            })
+            .id();
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(accumulate)
+            .sim_time_step(Duration::from_millis(10))
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+
+        // a participant stepping at 50 Hz, commanding one more than the total it last saw
+        let (sim, mut participant) = ChannelLink::pair();
+        let remote = std::thread::spawn(move || {
+            let mut seen = vec![];
+            let CoSimMsg::Hello { time_step, .. } = participant.recv().unwrap() else {
+                panic!("expected hello");
+            };
+            assert_eq!(time_step, 0.01);
+            participant
+                .send(&CoSimMsg::Ready { time_step: 0.02 })
+                .unwrap();
+            while let Ok(CoSimMsg::Step { tick, outputs, .. }) = participant.recv() {
+                seen.push((tick, outputs[0][0]));
+                let inputs = vec![vec![outputs[0][0] + 1.0]];
+                participant.send(&CoSimMsg::Ack { tick, inputs }).unwrap();
+            }
+            seen
+        });
+        CoSimulation::default()
+            .input("command", Command::COMPONENT_ID, entity)
+            .output("total", Total::COMPONENT_ID, entity)
+            .install(&mut exec.world, &mut exec.hooks, sim)
+            .unwrap();
+        for _ in 0..6 {
+            exec.run().unwrap();
+        }
+        drop(exec);
+
+        // each command is held for two ticks
+        let seen = remote.join().unwrap();
+        assert_eq!(seen, [(0, 0.0), (2, 2.0), (4, 8.0), (6, 26.0)]);
+    }
+}
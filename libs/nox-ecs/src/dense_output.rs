@@ -0,0 +1,179 @@
+//! Interpolation of component trajectories between ticks, from the per-tick history the world
+//! records, for sampling sensors or events at arbitrary times and for smooth playback.
+use impeller::{ComponentExt, ComponentId, EntityId, PrimitiveTy};
+
+use crate::{Error, World, WorldPos};
+
+/// Samples component `value` of `entity` at `time` seconds, with a cubic Hermite spline through
+/// the recorded ticks that uses component `derivative` as the slope at each tick.
+///
+/// The two components must hold the same number of `f64` elements, like a position and velocity.
+/// `time` must lie between the first tick and the current one.
+pub fn interpolate(
+    world: &World,
+    value: ComponentId,
+    derivative: ComponentId,
+    entity: EntityId,
+    time: f64,
+) -> Result<Vec<f64>, Error> {
+    let (k, s, h) = bracket(world, time)?;
+    let p0 = entity_value(world, value, entity, k)?;
+    let p1 = entity_value(world, value, entity, k + 1)?;
+    let m0 = entity_value(world, derivative, entity, k)?;
+    let m1 = entity_value(world, derivative, entity, k + 1)?;
+    if m0.len() != p0.len() {
+        return Err(Error::ValueSizeMismatch);
+    }
+    Ok(hermite(&p0, &m0, &p1, &m1, s, h))
+}
+
+/// Like [`interpolate`], but without derivatives, so only continuous in value.
+pub fn interpolate_linear(
+    world: &World,
+    value: ComponentId,
+    entity: EntityId,
+    time: f64,
+) -> Result<Vec<f64>, Error> {
+    let (k, s, _) = bracket(world, time)?;
+    let p0 = entity_value(world, value, entity, k)?;
+    let p1 = entity_value(world, value, entity, k + 1)?;
+    Ok(p0.iter().zip(&p1).map(|(a, b)| a + s * (b - a)).collect())
+}
+
+/// Samples the pose of `entity` at `time`, as `[x, y, z, w, px, py, pz]` like `WorldPos`.
+///
+/// The position follows a Hermite spline using the linear part of `vel`, a `SpatialMotion`
+/// component such as `WorldVel`; the attitude is normalized linear interpolation between ticks.
+pub fn interpolate_pose(
+    world: &World,
+    vel: ComponentId,
+    entity: EntityId,
+    time: f64,
+) -> Result<Vec<f64>, Error> {
+    let (k, s, h) = bracket(world, time)?;
+    let p0 = entity_value(world, WorldPos::COMPONENT_ID, entity, k)?;
+    let p1 = entity_value(world, WorldPos::COMPONENT_ID, entity, k + 1)?;
+    let v0 = entity_value(world, vel, entity, k)?;
+    let v1 = entity_value(world, vel, entity, k + 1)?;
+    if p0.len() != 7 || v0.len() != 6 {
+        return Err(Error::ValueSizeMismatch);
+    }
+    // take the shorter way round, since q and -q are the same attitude
+    let dot: f64 = p0[..4].iter().zip(&p1[..4]).map(|(a, b)| a * b).sum();
+    let sign = if dot < 0.0 { -1.0 } else { 1.0 };
+    let mut q: Vec<f64> = p0[..4]
+        .iter()
+        .zip(&p1[..4])
+        .map(|(a, b)| a + s * (sign * b - a))
+        .collect();
+    let norm = q.iter().map(|x| x * x).sum::<f64>().sqrt();
+    q.iter_mut().for_each(|x| *x /= norm);
+    let pos = hermite(&p0[4..], &v0[3..], &p1[4..], &v1[3..], s, h);
+    q.extend(pos);
+    Ok(q)
+}
+
+/// Finds the tick `k` before `time`, the fraction `s` of the way to tick `k + 1`, and the step.
+fn bracket(world: &World, time: f64) -> Result<(u64, f64, f64), Error> {
+    let h = world.sim_time_step.0.as_secs_f64();
+    if h == 0.0 {
+        return Err(Error::ZeroTimeStep);
+    }
+    let t = time / h;
+    if world.tick == 0 || !(0.0..=world.tick as f64).contains(&t) {
+        return Err(Error::TimeOutOfRange(time));
+    }
+    let k = (t.floor() as u64).min(world.tick - 1);
+    Ok((k, t - k as f64, h))
+}
+
+pub(crate) fn entity_value(
+    world: &World,
+    id: ComponentId,
+    entity: EntityId,
+    tick: u64,
+) -> Result<Vec<f64>, Error> {
+    let col = world
+        .column_at_tick(id, tick)
+        .ok_or(Error::ComponentNotFound)?;
+    if col.metadata.component_type.primitive_ty != PrimitiveTy::F64 {
+        return Err(Error::ValueSizeMismatch);
+    }
+    let stride = col.metadata.component_type.shape.iter().product::<i64>() as usize;
+    let index = col
+        .entity_ids()
+        .position(|id| id == entity)
+        .ok_or(Error::EntityNotFound(entity))?;
+    let bytes = &col.column[index * stride * 8..(index + 1) * stride * 8];
+    Ok(bytes
+        .chunks_exact(8)
+        .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+        .collect())
+}
+
+fn hermite(p0: &[f64], m0: &[f64], p1: &[f64], m1: &[f64], s: f64, h: f64) -> Vec<f64> {
+    let s2 = s * s;
+    let s3 = s2 * s;
+    let h00 = 2.0 * s3 - 3.0 * s2 + 1.0;
+    let h10 = s3 - 2.0 * s2 + s;
+    let h01 = -2.0 * s3 + 3.0 * s2;
+    let h11 = s3 - s2;
+    (0..p0.len())
+        .map(|i| h00 * p0[i] + h10 * h * m0[i] + h01 * p1[i] + h11 * h * m1[i])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_mass::{point_mass, PointMass, Position, Velocity};
+    use crate::{Integrator, WorldExt};
+    use nox::tensor;
+
+    #[test]
+    fn test_interpolate() {
+        let mut world = World::default();
+        let entity = world
+            .spawn(PointMass::new(
+                tensor![0.0, 0.0, 0.0],
+                tensor![1.0, 0.0, 0.0],
+                1.0,
+            ))
+            .id();
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(point_mass(|| (), Integrator::Rk4))
+            .sim_time_step(std::time::Duration::from_secs_f64(0.1))
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        exec.run().unwrap();
+        exec.run().unwrap();
+
+        let world = &exec.world;
+        let pos = interpolate(
+            world,
+            Position::COMPONENT_ID,
+            Velocity::COMPONENT_ID,
+            entity,
+            0.15,
+        )
+        .unwrap();
+        assert!((pos[0] - 0.15).abs() < 1e-12);
+        let pos = interpolate_linear(world, Position::COMPONENT_ID, entity, 0.05).unwrap();
+        assert!((pos[0] - 0.05).abs() < 1e-12);
+        assert!(matches!(
+            interpolate_linear(world, Position::COMPONENT_ID, entity, 0.3),
+            Err(Error::TimeOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_hermite() {
+        // x(t) = t^3 is reproduced exactly by a cubic hermite spline
+        let x = hermite(&[1.0], &[3.0], &[8.0], &[12.0], 0.5, 1.0);
+        assert!((x[0] - 1.5f64.powi(3)).abs() < 1e-12);
+    }
+}
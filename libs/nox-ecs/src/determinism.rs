@@ -0,0 +1,189 @@
+//! Compares runs of the same simulation tick by tick, to track down nondeterminism.
+//!
+//! Every tick of a run is summarized as a hash per component column. Hashes are small enough to be written
+//! to a file, so runs on different machines can be compared as well.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+use impeller::const_fnv1a_hash::fnv1a_hash_64;
+use impeller::{ComponentId, World};
+use nox::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{Compiled, Error, WorldExec};
+
+/// The hash of every component column at every tick of a run.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateHashes {
+    pub ticks: Vec<BTreeMap<ComponentId, u64>>,
+}
+
+impl StateHashes {
+    /// Hashes the recorded history of `world`, starting with its initial state.
+    pub fn from_world(world: &World) -> Self {
+        let ticks = world
+            .history
+            .iter()
+            .map(|buffers| {
+                buffers
+                    .iter()
+                    .map(|(id, buf)| (*id, fnv1a_hash_64(buf, None)))
+                    .collect()
+            })
+            .collect();
+        Self { ticks }
+    }
+
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer(file, self)?;
+        Ok(())
+    }
+
+    pub fn read_from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Returns the first tick at which the two runs differ, or `None` if they agree for as long as both ran.
+    pub fn first_divergence(&self, other: &StateHashes) -> Option<Divergence> {
+        self.ticks
+            .iter()
+            .zip(&other.ticks)
+            .enumerate()
+            .find_map(|(tick, (a, b))| {
+                let components = a
+                    .keys()
+                    .chain(b.keys())
+                    .filter(|id| a.get(id) != b.get(id))
+                    .copied()
+                    .collect::<BTreeSet<_>>();
+                (!components.is_empty()).then(|| Divergence {
+                    tick: tick as u64,
+                    components: components.into_iter().collect(),
+                    systems: vec![],
+                })
+            })
+    }
+}
+
+/// The first tick at which two runs stopped agreeing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    pub tick: u64,
+    /// The components whose state differs at `tick`.
+    pub components: Vec<ComponentId>,
+    /// The systems that write to any of `components`, and so could have produced the difference.
+    pub systems: Vec<String>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "runs diverged at tick {} in components [", self.tick)?;
+        for (i, id) in self.components.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", id.0)?;
+        }
+        write!(f, "]")?;
+        if !self.systems.is_empty() {
+            write!(f, " written by {}", self.systems.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: crate::ExecState> WorldExec<S> {
+    pub fn state_hashes(&self) -> StateHashes {
+        StateHashes::from_world(&self.world)
+    }
+
+    /// Compares this run against the hashes of another run of the same simulation.
+    ///
+    /// A divergence in the initial state is reported without systems, as it was caused by how the world was built.
+    pub fn first_divergence(&self, other: &StateHashes) -> Option<Divergence> {
+        let mut divergence = self.state_hashes().first_divergence(other)?;
+        if divergence.tick > 0 {
+            divergence.systems = self
+                .tick_exec
+                .metadata
+                .systems
+                .iter()
+                .filter(|system| {
+                    system
+                        .outputs
+                        .iter()
+                        .any(|id| divergence.components.contains(id))
+                })
+                .map(|system| system.name.clone())
+                .collect();
+        }
+        Some(divergence)
+    }
+}
+
+/// Builds the simulation twice with `build`, runs both for `ticks`, and returns the first point at which they diverge.
+pub fn audit(
+    build: impl Fn() -> Result<WorldExec, Error>,
+    client: &Client,
+    ticks: u64,
+) -> Result<Option<Divergence>, Error> {
+    let mut runs = (0..2)
+        .map(|_| {
+            let mut exec = build()?.compile(client.clone())?;
+            for _ in 0..ticks {
+                exec.run()?;
+            }
+            Ok(exec)
+        })
+        .collect::<Result<Vec<WorldExec<Compiled>>, Error>>()?;
+    let b = runs.pop().expect("two runs").state_hashes();
+    let a = runs.pop().expect("two runs");
+    Ok(a.first_divergence(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Component, ComponentArray, IntoSystemExt};
+    use impeller::ComponentExt;
+    use nox::{Op, OwnedRepr, Scalar};
+    use nox_ecs_macros::ReprMonad;
+
+    #[derive(Component, ReprMonad)]
+    struct A<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+    fn increment(a: ComponentArray<A>) -> ComponentArray<A> {
+        a.map(|a: A| A(a.0 + 1.0)).unwrap()
+    }
+
+    fn build() -> Result<WorldExec, Error> {
+        let mut world = increment.world();
+        world.spawn(A(0.0.into()));
+        world.build()
+    }
+
+    #[test]
+    fn test_audit() {
+        let client = Client::cpu().unwrap();
+        assert_eq!(audit(build, &client, 5).unwrap(), None);
+
+        let mut exec = build().unwrap().compile(client).unwrap();
+        for _ in 0..3 {
+            exec.run().unwrap();
+        }
+        let mut hashes = exec.state_hashes();
+        assert_eq!(hashes.ticks.len(), 4);
+        assert_eq!(exec.first_divergence(&hashes), None);
+
+        let id = A::COMPONENT_ID;
+        *hashes.ticks[2].get_mut(&id).unwrap() ^= 1;
+        let divergence = exec.first_divergence(&hashes).unwrap();
+        assert_eq!(divergence.tick, 2);
+        assert_eq!(divergence.components, vec![id]);
+        assert_eq!(divergence.systems.len(), 1);
+        assert!(divergence.systems[0].ends_with("::increment"));
+    }
+}
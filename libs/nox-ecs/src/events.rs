@@ -0,0 +1,384 @@
+//! Events triggered when a function of a component crosses zero, located between ticks.
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use impeller::{ComponentId, EntityId, PrimitiveTy};
+
+use crate::{interpolate, interpolate_linear, Error, TickHooks, World};
+
+/// Which sign changes of an event function trigger it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Crossing {
+    /// From negative to non-negative.
+    Rising,
+    /// From positive to non-positive, like vertical velocity at apogee.
+    Falling,
+    #[default]
+    Either,
+}
+
+type EventFn = Arc<dyn Fn(&[f64]) -> f64 + Send + Sync>;
+
+/// An event function `g(x)` of the value `x` of a component, which triggers when `g` crosses zero.
+///
+/// The crossing is bracketed by the last two ticks and then refined by root-finding on the
+/// interpolated trajectory, so the reported time is not quantized to the tick grid.
+#[derive(Clone)]
+pub struct ZeroCrossing {
+    pub name: String,
+    value: ComponentId,
+    derivative: Option<ComponentId>,
+    f: EventFn,
+    pub direction: Crossing,
+    pub tolerance: f64,
+    pub once: bool,
+}
+
+impl ZeroCrossing {
+    pub fn new(
+        name: impl Into<String>,
+        value: ComponentId,
+        f: impl Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            value,
+            derivative: None,
+            f: Arc::new(f),
+            direction: Crossing::Either,
+            tolerance: 1e-9,
+            once: false,
+        }
+    }
+
+    /// Interpolates with a Hermite spline using `derivative` as the rate of change of the value,
+    /// rather than linearly between ticks.
+    pub fn derivative(mut self, derivative: ComponentId) -> Self {
+        self.derivative = Some(derivative);
+        self
+    }
+
+    pub fn direction(mut self, direction: Crossing) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Sets the width of the time interval, in seconds, that the crossing is narrowed down to.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Only triggers the first time the function crosses zero for each entity.
+    pub fn once(mut self) -> Self {
+        self.once = true;
+        self
+    }
+
+    /// Evaluates the event function for `entity` at `time`, which must be within the recorded ticks.
+    pub fn eval(&self, world: &World, entity: EntityId, time: f64) -> Result<f64, Error> {
+        let x = match self.derivative {
+            Some(derivative) => interpolate(world, self.value, derivative, entity, time)?,
+            None => interpolate_linear(world, self.value, entity, time)?,
+        };
+        Ok((self.f)(&x))
+    }
+
+    /// Returns the time at which the event triggered for `entity` during the last tick, if it did.
+    pub fn locate(&self, world: &World, entity: EntityId) -> Result<Option<f64>, Error> {
+        if world.tick == 0 {
+            return Ok(None);
+        }
+        let h = world.sim_time_step.0.as_secs_f64();
+        let (mut a, mut b) = ((world.tick - 1) as f64 * h, world.tick as f64 * h);
+        let (mut ga, mut gb) = (self.eval(world, entity, a)?, self.eval(world, entity, b)?);
+        let triggered = match self.direction {
+            Crossing::Rising => ga < 0.0 && gb >= 0.0,
+            Crossing::Falling => ga > 0.0 && gb <= 0.0,
+            Crossing::Either => (ga < 0.0 && gb >= 0.0) || (ga > 0.0 && gb <= 0.0),
+        };
+        if !triggered {
+            return Ok(None);
+        }
+        // Illinois variant of regula falsi: halve the weight of an endpoint retained twice in a row
+        let mut side = 0;
+        for _ in 0..64 {
+            if b - a <= self.tolerance || gb == 0.0 {
+                break;
+            }
+            let t = (a * gb - b * ga) / (gb - ga);
+            let g = self.eval(world, entity, t)?;
+            if (g < 0.0) == (ga < 0.0) && g != 0.0 {
+                (a, ga) = (t, g);
+                if side == -1 {
+                    gb /= 2.0;
+                }
+                side = -1;
+            } else {
+                (b, gb) = (t, g);
+                if side == 1 {
+                    ga /= 2.0;
+                }
+                side = 1;
+            }
+        }
+        Ok(Some(b))
+    }
+}
+
+/// An occurrence of a [`ZeroCrossing`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub name: String,
+    pub entity: EntityId,
+    /// The simulation time of the crossing, in seconds.
+    pub time: f64,
+}
+
+type Action = Box<dyn FnMut(&Event, &mut World) -> Result<(), Error> + Send>;
+
+/// Checks a set of [`ZeroCrossing`]s against every entity that has their component after each tick,
+/// along with events scheduled at fixed times, and runs the actions registered for them.
+#[derive(Default)]
+pub struct EventDetector {
+    events: Vec<ZeroCrossing>,
+    timers: Vec<Event>,
+    actions: Vec<(String, Action)>,
+    fired: HashSet<(String, EntityId)>,
+}
+
+impl EventDetector {
+    pub fn event(mut self, event: ZeroCrossing) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    /// Schedules an event for `entity` at `time` seconds, like a separation at a planned time.
+    ///
+    /// It triggers after the tick that reaches `time`, and reports the exact time rather than the
+    /// tick's.
+    pub fn at_time(mut self, name: impl Into<String>, entity: EntityId, time: f64) -> Self {
+        self.timers.push(Event {
+            name: name.into(),
+            entity,
+            time,
+        });
+        self
+    }
+
+    /// Runs `action` whenever an event called `name` triggers, before the next tick.
+    pub fn on(
+        mut self,
+        name: impl Into<String>,
+        action: impl FnMut(&Event, &mut World) -> Result<(), Error> + Send + 'static,
+    ) -> Self {
+        self.actions.push((name.into(), Box::new(action)));
+        self
+    }
+
+    /// Returns the events that triggered during the last tick, in order of time.
+    ///
+    /// One-shot events are remembered, so they are not returned again by later calls.
+    pub fn detect(&mut self, world: &World) -> Result<Vec<Event>, Error> {
+        let mut events = vec![];
+        for event in &self.events {
+            let col = world
+                .column_by_id(event.value)
+                .ok_or(Error::ComponentNotFound)?;
+            for entity in col.entity_ids() {
+                if event.once && self.fired.contains(&(event.name.clone(), entity)) {
+                    continue;
+                }
+                if let Some(time) = event.locate(world, entity)? {
+                    if event.once {
+                        self.fired.insert((event.name.clone(), entity));
+                    }
+                    events.push(Event {
+                        name: event.name.clone(),
+                        entity,
+                        time,
+                    });
+                }
+            }
+        }
+        if world.tick > 0 {
+            let h = world.sim_time_step.0.as_secs_f64();
+            let (start, end) = ((world.tick - 1) as f64 * h, world.tick as f64 * h);
+            // the first tick also picks up timers scheduled at or before the start
+            let first = world.tick == 1;
+            events.extend(
+                self.timers
+                    .iter()
+                    .filter(|timer| (first || timer.time > start) && timer.time <= end)
+                    .cloned(),
+            );
+        }
+        events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(events)
+    }
+
+    /// Runs the actions registered with [`EventDetector::on`] for `event`.
+    pub fn trigger(&mut self, event: &Event, world: &mut World) -> Result<(), Error> {
+        for (name, action) in &mut self.actions {
+            if *name == event.name {
+                action(event, world)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Registers a post-tick hook that runs the actions for each triggered event, then passes it to
+    /// `handler`. Either may change the world before the next tick, for example to stage a rocket
+    /// or cut off its engine.
+    pub fn install(
+        mut self,
+        hooks: &mut TickHooks,
+        mut handler: impl FnMut(&Event, &mut World) -> Result<(), Error> + Send + 'static,
+    ) {
+        hooks.add_post_tick(move |ctx| {
+            for event in self.detect(ctx.world)? {
+                self.trigger(&event, ctx.world)?;
+                handler(&event, ctx.world)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Overwrites the value of the f64 component `id` of `entity` on the host, for event actions; it
+/// is copied to the client before the next tick.
+pub fn set_value(
+    world: &mut World,
+    id: ComponentId,
+    entity: EntityId,
+    value: &[f64],
+) -> Result<(), Error> {
+    let mut col = world.column_by_id_mut(id).ok_or(Error::ComponentNotFound)?;
+    let ty = &col.metadata.component_type;
+    let stride = ty.shape.iter().product::<i64>() as usize;
+    if ty.primitive_ty != PrimitiveTy::F64 || stride != value.len() {
+        return Err(Error::ValueSizeMismatch);
+    }
+    let index = col
+        .entity_ids()
+        .position(|id| id == entity)
+        .ok_or(Error::EntityNotFound(entity))?;
+    let bytes = &mut col.column[index * stride * 8..(index + 1) * stride * 8];
+    for (out, x) in bytes.chunks_exact_mut(8).zip(value) {
+        out.copy_from_slice(&x.to_le_bytes());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_mass::{point_mass, LinearForce, PointMass, Position, Velocity};
+    use crate::{Integrator, Query, WorldExt};
+    use impeller::ComponentExt;
+    use nox::{tensor, Vector};
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_zero_crossing() {
+        fn gravity(q: Query<LinearForce>) -> Query<LinearForce> {
+            q.map(|_: LinearForce| {
+                let g: Vector<f64, 3> = tensor![0.0, 0.0, -10.0].into();
+                LinearForce(g)
+            })
+            .unwrap()
+        }
+
+        let mut world = World::default();
+        world.spawn(PointMass::new(
+            tensor![0.0, 0.0, 0.0],
+            tensor![0.0, 0.0, 10.0],
+            1.0,
+        ));
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(point_mass(|| gravity, Integrator::Rk4))
+            .sim_time_step(std::time::Duration::from_secs_f64(0.3))
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+
+        let apogee = ZeroCrossing::new("apogee", Velocity::COMPONENT_ID, |v| v[2])
+            .direction(Crossing::Falling);
+        let ground = ZeroCrossing::new("ground", Position::COMPONENT_ID, |p| p[2])
+            .derivative(Velocity::COMPONENT_ID)
+            .direction(Crossing::Falling);
+        let events = Arc::new(Mutex::new(vec![]));
+        let log = events.clone();
+        EventDetector::default()
+            .event(apogee)
+            .event(ground)
+            .install(&mut exec.hooks, move |event, _| {
+                log.lock().unwrap().push(event.clone());
+                Ok(())
+            });
+        for _ in 0..8 {
+            exec.run().unwrap();
+        }
+
+        // z = 10t - 5t^2, with apogee at t = 1 and landing at t = 2, both between ticks
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].name, "apogee");
+        assert!((events[0].time - 1.0).abs() < 1e-6);
+        assert_eq!(events[1].name, "ground");
+        assert!((events[1].time - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_triggers() {
+        let mut world = World::default();
+        let entity = world
+            .spawn(PointMass::new(
+                tensor![0.0, 0.0, 0.0],
+                tensor![0.0, 0.0, 1.0],
+                1.0,
+            ))
+            .id();
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(point_mass(|| (), Integrator::Rk4))
+            .sim_time_step(std::time::Duration::from_secs_f64(0.3))
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+
+        // sends the body back to the ground the first time it climbs past 1m
+        let climb = ZeroCrossing::new("reset", Position::COMPONENT_ID, |p| p[2] - 1.0)
+            .direction(Crossing::Rising)
+            .once();
+        let events = Arc::new(Mutex::new(vec![]));
+        let log = events.clone();
+        EventDetector::default()
+            .event(climb)
+            .at_time("separation", entity, 0.25)
+            .on("reset", |event, world| {
+                set_value(world, Position::COMPONENT_ID, event.entity, &[0.0; 3])
+            })
+            .install(&mut exec.hooks, move |event, _| {
+                log.lock().unwrap().push(event.clone());
+                Ok(())
+            });
+        for _ in 0..8 {
+            exec.run().unwrap();
+        }
+
+        let events = events.lock().unwrap();
+        let names: Vec<_> = events.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["separation", "reset"]);
+        assert_eq!(events[0].time, 0.25);
+        assert!((events[1].time - 1.0).abs() < 1e-9);
+        // reset after the fourth tick, then climbed past 1m again without triggering
+        let col = exec.world.column::<Position>().unwrap();
+        assert!((col.typed_buf::<f64>().unwrap()[2] - 1.2).abs() < 1e-9);
+    }
+}
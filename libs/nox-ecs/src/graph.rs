@@ -1,6 +1,9 @@
 use bytes::Buf;
 use impeller::{ComponentId, ComponentType, ComponentValue, EntityId};
-use nox::{xla::Literal, ArrayTy, Builder, CompFn, Const, Noxpr, NoxprFn, NoxprTy, Op, ReprMonad};
+use nox::{
+    xla::Literal, ArrayTy, Builder, CompFn, Const, JacobianSparsity, Noxpr, NoxprFn, NoxprTy, Op,
+    ReprMonad,
+};
 use std::{collections::BTreeMap, marker::PhantomData};
 
 use crate::{Component, ComponentArray, ComponentGroup, Query};
@@ -219,6 +222,29 @@ pub fn exprs_from_edges_queries<A, B>(
 }
 
 impl<E> GraphQuery<E> {
+    /// The jacobian sparsity of a system over `query`'s entities, with `block` state elements
+    /// each, where an entity's derivatives depend on its own state and on the state of every
+    /// entity its edges point to, the way [`GraphQuery::edge_fold`] folds them.
+    ///
+    /// Edges to entities outside `query` are ignored.
+    pub fn jacobian_sparsity<A>(
+        &self,
+        query: &Query<A>,
+        block: usize,
+    ) -> Result<JacobianSparsity, crate::Error> {
+        let index = |id: &EntityId| query.entity_map.get(id).copied();
+        let edges = self
+            .edges
+            .iter()
+            .filter_map(|edge| Some((index(&edge.from)?, index(&edge.to)?)));
+        let interactions = (0..query.len).map(|i| (i, i)).chain(edges);
+        Ok(JacobianSparsity::from_blocks(
+            query.len,
+            block,
+            interactions,
+        )?)
+    }
+
     /// Folds over each edge of a graph, using the `from` EntityId
     /// to form th resulting `ComponentArray`
     pub fn edge_fold<I: ComponentGroup, F: ComponentGroup, T: ComponentGroup>(
@@ -498,4 +524,29 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_jacobian_sparsity() {
+        let ids = [EntityId(3), EntityId(7), EntityId(9)];
+        let query = Query::<()> {
+            exprs: vec![],
+            entity_map: ids.iter().enumerate().map(|(i, id)| (*id, i)).collect(),
+            len: ids.len(),
+            phantom_data: PhantomData,
+        };
+        let graph = GraphQuery::<Edge> {
+            edges: vec![
+                Edge::new(ids[0], ids[1]),
+                Edge::new(ids[1], ids[0]),
+                Edge::new(ids[2], EntityId(42)),
+            ],
+            phantom_data: PhantomData,
+        };
+        let sparsity = graph.jacobian_sparsity(&query, 2).unwrap();
+        assert_eq!(sparsity.rows(), 6);
+        // the two linked entities' blocks, both ways, on top of the three diagonal blocks
+        assert_eq!(sparsity.nnz(), 4 * 5);
+        // the unlinked entity's columns can share colors with the others
+        assert_eq!(sparsity.color_count(), 4);
+    }
 }
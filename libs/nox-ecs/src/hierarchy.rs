@@ -0,0 +1,348 @@
+//! Names and a parent-child hierarchy for entities, so viewers and logs can show
+//! `sat1/solar_panel_left` rather than an opaque id.
+//!
+//! Names come from the [`EntityMetadata`] asset set with [`crate::Entity::metadata`], and the
+//! hierarchy from a [`Parent`] component, which is queried and streamed like any other. Entities
+//! that also have an [`AttachOffset`] are carried along by their parent, see [`attach`].
+use std::collections::{BTreeMap, HashSet};
+use std::marker::PhantomData;
+
+use impeller::well_known::EntityMetadata;
+use impeller::{ComponentId, EntityId, Handle};
+use nox::{Noxpr, Op, OwnedRepr, Scalar, SpatialTransform};
+use nox_ecs_macros::ReprMonad;
+
+use crate::query::filter_index;
+use crate::system::{SystemBuilder, SystemParam};
+use crate::{update_var, Component, Error, Query, World, WorldPos};
+
+/// The id of the entity this one belongs to.
+#[derive(Component, Clone, ReprMonad)]
+pub struct Parent<R: OwnedRepr = Op>(pub Scalar<u64, R>);
+
+impl Parent {
+    pub fn new(parent: impl Into<EntityId>) -> Self {
+        Parent(parent.into().0.into())
+    }
+}
+
+/// Reads the `u64` an entity holds in the column with component `C`.
+fn entity_u64<C: impeller::Component + 'static>(world: &World, entity: EntityId) -> Option<u64> {
+    let col = world.column::<C>()?;
+    let index = col.entity_ids().position(|id| id == entity)?;
+    let bytes = col.column.get(index * 8..(index + 1) * 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+pub fn entity_name(world: &World, entity: EntityId) -> Option<String> {
+    let handle = entity_u64::<Handle<EntityMetadata>>(world, entity)?;
+    let metadata = world.assets.get(Handle::<EntityMetadata>::new(handle))?;
+    Some(metadata.name)
+}
+
+pub fn parent_of(world: &World, entity: EntityId) -> Option<EntityId> {
+    entity_u64::<Parent>(world, entity).map(EntityId)
+}
+
+/// The entities whose parent is `entity`, in id order.
+pub fn children(world: &World, entity: EntityId) -> Vec<EntityId> {
+    let mut children = world
+        .entity_ids()
+        .into_iter()
+        .filter(|&id| parent_of(world, id) == Some(entity))
+        .collect::<Vec<_>>();
+    children.sort();
+    children
+}
+
+/// The names of `entity` and its ancestors joined root first with `/`, e.g.
+/// `sat1/solar_panel_left`. Unnamed entities show up as their id, and a cycle of parents ends the
+/// path where it first repeats.
+pub fn entity_path(world: &World, entity: EntityId) -> String {
+    let mut segments = vec![];
+    let mut seen = HashSet::new();
+    let mut next = Some(entity);
+    while let Some(id) = next {
+        if !seen.insert(id) {
+            break;
+        }
+        segments.push(entity_name(world, id).unwrap_or_else(|| id.0.to_string()));
+        next = parent_of(world, id);
+    }
+    segments.reverse();
+    segments.join("/")
+}
+
+/// Looks an entity up by its [`entity_path`].
+pub fn find_entity(world: &World, path: &str) -> Option<EntityId> {
+    let mut ids = world.entity_ids().into_iter().collect::<Vec<_>>();
+    ids.sort();
+    ids.into_iter().find(|&id| entity_path(world, id) == path)
+}
+
+/// Where an entity sits in its [`Parent`]'s body frame, for [`attach`].
+#[derive(Component, ReprMonad)]
+pub struct AttachOffset<R: OwnedRepr = Op>(pub SpatialTransform<f64, R>);
+
+/// The `(child, parent)` pairs of the entities with an [`AttachOffset`], grouped by how many
+/// attachments separate them from a free body, so a group's parents are all placed before it.
+pub struct Attachments {
+    pub levels: Vec<Vec<(EntityId, EntityId)>>,
+}
+
+impl Attachments {
+    pub fn of(world: &World) -> Result<Self, Error> {
+        let mut levels: Vec<Vec<(EntityId, EntityId)>> = vec![];
+        let Some(col) = world.column::<AttachOffset>() else {
+            return Ok(Self { levels });
+        };
+        let attached = col
+            .entity_ids()
+            .filter_map(|child| Some((child, parent_of(world, child)?)))
+            .collect::<BTreeMap<_, _>>();
+        for (&child, &parent) in &attached {
+            let mut depth = 0;
+            let mut next = parent;
+            while let Some(&grandparent) = attached.get(&next) {
+                depth += 1;
+                if depth >= attached.len() {
+                    return Err(Error::ParentCycle(child));
+                }
+                next = grandparent;
+            }
+            if levels.len() <= depth {
+                levels.resize_with(depth + 1, Vec::new);
+            }
+            levels[depth].push((child, parent));
+        }
+        Ok(Self { levels })
+    }
+}
+
+impl SystemParam for Attachments {
+    type Item = Self;
+
+    fn init(_builder: &mut SystemBuilder) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn param(builder: &SystemBuilder) -> Result<Self::Item, Error> {
+        Attachments::of(builder.world)
+    }
+
+    fn component_ids() -> impl Iterator<Item = ComponentId> {
+        std::iter::empty()
+    }
+
+    // only read when building the system, so there's nothing to write back
+    fn output(&self, _builder: &mut SystemBuilder) -> Result<Noxpr, Error> {
+        Ok(Noxpr::tuple(vec![]))
+    }
+}
+
+/// Places every entity with an [`AttachOffset`] rigidly on its [`Parent`], setting its `WorldPos`
+/// to the parent's composed with the offset, for sensor mounts, antennas and the like that don't
+/// need a constraint solve.
+///
+/// The hierarchy is read when the system is built. Pipe it after whatever moves the parents.
+pub fn attach(
+    attachments: Attachments,
+    pos: Query<WorldPos>,
+    offsets: Query<AttachOffset>,
+) -> Query<WorldPos> {
+    let mut pos = pos;
+    for level in &attachments.levels {
+        let level = level
+            .iter()
+            .filter(|(child, parent)| {
+                pos.entity_map.contains_key(child)
+                    && pos.entity_map.contains_key(parent)
+                    && offsets.entity_map.contains_key(child)
+            })
+            .collect::<Vec<_>>();
+        if level.is_empty() {
+            continue;
+        }
+        let parents = level
+            .iter()
+            .map(|(_, parent)| pos.entity_map[parent] as u32)
+            .collect::<Vec<_>>();
+        let children = level
+            .iter()
+            .map(|(child, _)| offsets.entity_map[child] as u32)
+            .collect::<Vec<_>>();
+        let joined = Query::<(WorldPos, AttachOffset)> {
+            exprs: vec![
+                filter_index(&parents, &pos.exprs[0]),
+                filter_index(&children, &offsets.exprs[0]),
+            ],
+            entity_map: level
+                .iter()
+                .enumerate()
+                .map(|(i, (child, _))| (*child, i))
+                .collect(),
+            len: level.len(),
+            phantom_data: PhantomData,
+        };
+        let placed = joined
+            .map(|parent: WorldPos, offset: AttachOffset| WorldPos(parent.0 * offset.0))
+            .unwrap();
+        pos.exprs[0] = update_var(
+            &pos.entity_map,
+            &placed.entity_map,
+            &pos.exprs[0],
+            &placed.exprs[0],
+        );
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WorldExt;
+    use approx::assert_relative_eq;
+    use impeller::well_known::Color;
+    use nox::{tensor, ArrayRepr};
+    use nox_ecs_macros::Archetype;
+
+    #[derive(Component, ReprMonad)]
+    struct Mass<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+    #[derive(Archetype)]
+    struct Part {
+        mass: Mass,
+        parent: Parent,
+    }
+
+    #[derive(Archetype)]
+    struct Mount {
+        pos: WorldPos,
+        parent: Parent,
+        offset: AttachOffset,
+    }
+
+    fn mount(parent: EntityId, offset: [f64; 3]) -> Mount {
+        let [x, y, z] = offset;
+        Mount {
+            pos: WorldPos(SpatialTransform {
+                inner: tensor![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0].into(),
+            }),
+            parent: Parent::new(parent),
+            offset: AttachOffset(SpatialTransform {
+                inner: tensor![0.0, 0.0, 0.0, 1.0, x, y, z].into(),
+            }),
+        }
+    }
+
+    fn named(name: &str) -> EntityMetadata {
+        EntityMetadata {
+            name: name.to_string(),
+            color: Color::WHITE,
+        }
+    }
+
+    #[test]
+    fn test_entity_path() {
+        let mut world = World::default();
+        let sat = world.spawn(Mass(10.0.into())).metadata(named("sat1")).id();
+        let panel = world
+            .spawn(Part {
+                mass: Mass(1.0.into()),
+                parent: Parent::new(sat),
+            })
+            .metadata(named("solar_panel_left"))
+            .id();
+        let hinge = world
+            .spawn(Part {
+                mass: Mass(0.1.into()),
+                parent: Parent::new(panel),
+            })
+            .id();
+
+        assert_eq!(entity_name(&world, sat).as_deref(), Some("sat1"));
+        assert_eq!(entity_name(&world, hinge), None);
+        assert_eq!(parent_of(&world, sat), None);
+        assert_eq!(children(&world, sat), [panel]);
+        assert_eq!(entity_path(&world, panel), "sat1/solar_panel_left");
+        assert_eq!(entity_path(&world, hinge), "sat1/solar_panel_left/2");
+        assert_eq!(find_entity(&world, "sat1/solar_panel_left"), Some(panel));
+        assert_eq!(find_entity(&world, "solar_panel_left"), None);
+    }
+
+    #[test]
+    fn test_parent_cycle() {
+        let mut world = World::default();
+        let a = world
+            .spawn(Part {
+                mass: Mass(1.0.into()),
+                parent: Parent::new(EntityId(1)),
+            })
+            .metadata(named("a"))
+            .id();
+        world
+            .spawn(Part {
+                mass: Mass(1.0.into()),
+                parent: Parent::new(a),
+            })
+            .metadata(named("b"));
+        assert_eq!(entity_path(&world, a), "b/a");
+    }
+
+    #[test]
+    fn test_attach() {
+        let mut world = World::default();
+        // turned a quarter turn about z, and placed at x = 1
+        let half = std::f64::consts::FRAC_1_SQRT_2;
+        let body = world
+            .spawn(WorldPos(SpatialTransform {
+                inner: tensor![0.0, 0.0, half, half, 1.0, 0.0, 0.0].into(),
+            }))
+            .id();
+        // spawned before its own parent, which is on the body
+        let tip = world.spawn(mount(EntityId(2), [1.0, 0.0, 0.0])).id();
+        let boom = world.spawn(mount(body, [0.0, 2.0, 0.0])).id();
+        assert_eq!(boom, EntityId(2));
+
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(attach)
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        exec.run().unwrap();
+        let col = exec.world.column::<WorldPos>().unwrap();
+        let pos = col
+            .typed_iter::<SpatialTransform<f64, ArrayRepr>>()
+            .collect::<BTreeMap<_, _>>();
+        assert_relative_eq!(
+            pos[&body].inner,
+            tensor![0.0, 0.0, half, half, 1.0, 0.0, 0.0],
+            epsilon = 1e-12
+        );
+        // the boom points along the body's y axis, which is the world's -x
+        assert_relative_eq!(
+            pos[&boom].inner,
+            tensor![0.0, 0.0, half, half, -1.0, 0.0, 0.0],
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            pos[&tip].inner,
+            tensor![0.0, 0.0, half, half, -1.0, 1.0, 0.0],
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_attach_cycle() {
+        let mut world = World::default();
+        world.spawn(mount(EntityId(1), [1.0, 0.0, 0.0]));
+        world.spawn(mount(EntityId(0), [1.0, 0.0, 0.0]));
+        assert!(matches!(
+            Attachments::of(&world),
+            Err(Error::ParentCycle(_))
+        ));
+    }
+}
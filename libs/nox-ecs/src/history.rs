@@ -1 +1,238 @@
+//! Opt-in ring buffers of past component values, for systems that need to look back in time
+//! (finite differences, delayed measurements) without storing copies by hand.
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
 
+use impeller::{ComponentExt, ComponentId, EntityId, Metadata, World};
+use nox::Noxpr;
+use smallvec::SmallVec;
+
+use crate::{
+    update_var, Component, ComponentArray, ErasedSystem, Error, IntoSystem, System, SystemBuilder,
+    SystemParam,
+};
+
+/// The id of the column holding the history of `C`.
+pub fn history_id<C: impeller::Component>() -> ComponentId {
+    ComponentId::new(&history_name::<C>())
+}
+
+fn history_name<C: impeller::Component>() -> String {
+    format!("{}_history", C::NAME)
+}
+
+/// Starts keeping the last `depth` values of `C` for every entity that has it.
+///
+/// The buffer starts out filled with the current value, and is only updated by [`record_history`],
+/// which has to run at the start of the tick.
+pub fn track_history<C: impeller::Component>(world: &mut World, depth: usize) -> Result<(), Error> {
+    assert!(depth > 0, "history depth must be at least one tick");
+    let (archetype_name, metadata) = world
+        .component_map
+        .get(&C::COMPONENT_ID)
+        .cloned()
+        .ok_or(Error::ComponentNotFound)?;
+    let column = world
+        .host
+        .get(&C::COMPONENT_ID)
+        .ok_or(Error::ComponentNotFound)?;
+    let value_size = metadata.component_type.size();
+    let buf = column
+        .chunks_exact(value_size)
+        .flat_map(|value| value.repeat(depth))
+        .collect::<Vec<u8>>();
+
+    let mut component_type = metadata.component_type;
+    component_type.shape.insert(0, depth as i64);
+    let id = history_id::<C>();
+    let metadata = Metadata {
+        name: history_name::<C>().into(),
+        component_type,
+        tags: None,
+        asset: false,
+    };
+    world.component_map.insert(id, (archetype_name, metadata));
+    world.host.insert(id, buf);
+    world.dirty_components.insert(id);
+    Ok(())
+}
+
+/// A system that pushes the current value of `C` into its history, see [`track_history`].
+pub fn record_history<C: Component + 'static>() -> impl System<Arg = (), Ret = ()> {
+    let record =
+        |history: History<C>, value: ComponentArray<C>| -> History<C> { history.push(&value) };
+    ErasedSystem::new(record.into_system())
+}
+
+/// The last values of `C` for every entity, usable as a system parameter.
+pub struct History<C> {
+    buffer: Noxpr,
+    len: usize,
+    depth: usize,
+    entity_map: BTreeMap<EntityId, usize>,
+    phantom_data: PhantomData<C>,
+}
+
+impl<C> Clone for History<C> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer.clone(),
+            len: self.len,
+            depth: self.depth,
+            entity_map: self.entity_map.clone(),
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<C: Component> History<C> {
+    /// How many ticks back the history reaches.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The values of `C` from `ticks_back` ticks ago, where a `ticks_back` of 1 is the previous tick.
+    pub fn get(&self, ticks_back: usize) -> ComponentArray<C> {
+        assert!(
+            (1..=self.depth).contains(&ticks_back),
+            "history only reaches {} ticks back",
+            self.depth
+        );
+        let shape = self
+            .buffer
+            .shape()
+            .expect("history buffer must be an array");
+        let index = ticks_back as i64 - 1;
+        let start: SmallVec<_> = shape
+            .iter()
+            .enumerate()
+            .map(|(i, _)| if i == 1 { index } else { 0 })
+            .collect();
+        let mut stop = shape.clone();
+        stop[1] = index + 1;
+        let mut value_shape = shape.clone();
+        value_shape.remove(1);
+        let buffer = self
+            .buffer
+            .clone()
+            .slice(start, stop, shape.iter().map(|_| 1).collect())
+            .reshape(value_shape);
+        ComponentArray {
+            buffer,
+            len: self.len,
+            entity_map: self.entity_map.clone(),
+            phantom_data: PhantomData,
+            component_id: C::COMPONENT_ID,
+        }
+    }
+
+    /// The value of `C` for `entity` from `ticks_back` ticks ago.
+    pub fn entity(&self, entity: EntityId, ticks_back: usize) -> Option<C> {
+        let offset = *self.entity_map.get(&entity)?;
+        Some(self.get(ticks_back).get(offset as i64))
+    }
+
+    /// Pushes `value` in as the newest entry, dropping the oldest one.
+    pub fn push(self, value: &ComponentArray<C>) -> Self {
+        let shape = self
+            .buffer
+            .shape()
+            .expect("history buffer must be an array");
+        let mut value_shape = shape.clone();
+        value_shape[1] = 1;
+        let newest = value.buffer.clone().reshape(value_shape);
+        let mut stop = shape.clone();
+        stop[1] -= 1;
+        let older = self.buffer.clone().slice(
+            shape.iter().map(|_| 0).collect(),
+            stop,
+            shape.iter().map(|_| 1).collect(),
+        );
+        Self {
+            buffer: Noxpr::concat_in_dim(vec![newest, older], 1),
+            ..self
+        }
+    }
+}
+
+impl<C: Component + 'static> SystemParam for History<C> {
+    type Item = Self;
+
+    fn init(builder: &mut SystemBuilder) -> Result<(), Error> {
+        builder.init_with_column(history_id::<C>())
+    }
+
+    fn param(builder: &SystemBuilder) -> Result<Self::Item, Error> {
+        let var = builder
+            .vars
+            .get(&history_id::<C>())
+            .ok_or(Error::ComponentNotFound)?;
+        let depth = var
+            .buffer
+            .shape()
+            .and_then(|shape| shape.get(1).copied())
+            .ok_or(Error::ComponentNotFound)?;
+        Ok(History {
+            buffer: var.buffer.clone(),
+            len: var.len,
+            depth: depth as usize,
+            entity_map: var.entity_map.clone(),
+            phantom_data: PhantomData,
+        })
+    }
+
+    fn component_ids() -> impl Iterator<Item = ComponentId> {
+        std::iter::once(history_id::<C>())
+    }
+
+    fn output(&self, builder: &mut SystemBuilder) -> Result<Noxpr, Error> {
+        if let Some(var) = builder.vars.get(&history_id::<C>()) {
+            if var.entity_map != self.entity_map {
+                return Ok(update_var(
+                    &var.entity_map,
+                    &self.entity_map,
+                    &var.buffer,
+                    &self.buffer,
+                ));
+            }
+        }
+        Ok(self.buffer.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WorldExt;
+    use nox::{Op, OwnedRepr, Scalar};
+    use nox_ecs_macros::ReprMonad;
+
+    #[derive(crate::Component, ReprMonad)]
+    struct X<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+    #[test]
+    fn test_history() {
+        // x[t] = x[t - 1] + x[t - 2]
+        let fib = |x: ComponentArray<X>, history: History<X>| -> ComponentArray<X> {
+            let buffer = x.buffer.clone() + history.get(2).buffer;
+            ComponentArray { buffer, ..x }
+        };
+        let mut world = World::default();
+        world.spawn(X(1.0.into()));
+        track_history::<X>(&mut world, 3).unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(record_history::<X>().pipe(fib))
+            .build()
+            .unwrap()
+            .compile(nox::Client::cpu().unwrap())
+            .unwrap();
+        for _ in 0..5 {
+            exec.run().unwrap();
+        }
+        let x = exec.world.column::<X>().unwrap();
+        assert_eq!(x.typed_buf::<f64>().unwrap(), &[13.0]);
+        let history = exec.world.column_by_id(history_id::<X>()).unwrap();
+        assert_eq!(history.typed_buf::<f64>().unwrap(), &[8.0, 5.0, 3.0]);
+    }
+}
@@ -0,0 +1,69 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{Error, World};
+
+/// The state handed to a tick hook.
+pub struct TickContext<'a> {
+    /// The current tick of the world.
+    pub tick: u64,
+    /// The simulation time at the current tick.
+    pub time: Duration,
+    /// The host copy of the world. Components mutated through `column_mut` are copied back to the client
+    /// before the next tick executes.
+    pub world: &'a mut World,
+}
+
+type Hook = Arc<Mutex<dyn FnMut(&mut TickContext<'_>) -> Result<(), Error> + Send>>;
+
+/// Host-side callbacks that run before and after every tick of a [`crate::WorldExec`].
+///
+/// Hooks are shared between forks of the same exec.
+#[derive(Clone, Default)]
+pub struct TickHooks {
+    pre_tick: Vec<Hook>,
+    post_tick: Vec<Hook>,
+}
+
+impl TickHooks {
+    /// Registers a hook that runs before each tick is executed.
+    pub fn add_pre_tick(
+        &mut self,
+        hook: impl FnMut(&mut TickContext<'_>) -> Result<(), Error> + Send + 'static,
+    ) {
+        self.pre_tick.push(Arc::new(Mutex::new(hook)));
+    }
+
+    /// Registers a hook that runs after each tick has been executed and copied back to the host.
+    pub fn add_post_tick(
+        &mut self,
+        hook: impl FnMut(&mut TickContext<'_>) -> Result<(), Error> + Send + 'static,
+    ) {
+        self.post_tick.push(Arc::new(Mutex::new(hook)));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pre_tick.is_empty() && self.post_tick.is_empty()
+    }
+
+    pub(crate) fn run_pre_tick(&self, world: &mut World) -> Result<(), Error> {
+        run_hooks(&self.pre_tick, world)
+    }
+
+    pub(crate) fn run_post_tick(&self, world: &mut World) -> Result<(), Error> {
+        run_hooks(&self.post_tick, world)
+    }
+}
+
+fn run_hooks(hooks: &[Hook], world: &mut World) -> Result<(), Error> {
+    for hook in hooks {
+        let mut ctx = TickContext {
+            tick: world.tick,
+            time: world.sim_time_step.0.mul_f64(world.tick as f64),
+            world: &mut *world,
+        };
+        let mut hook = hook.lock().map_err(|_| Error::HookPoisoned)?;
+        (*hook)(&mut ctx)?;
+    }
+    Ok(())
+}
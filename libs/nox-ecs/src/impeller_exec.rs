@@ -55,13 +55,26 @@ impl ImpellerExec {
         if self.simulating && self.exec.world.tick < self.exec.world.max_tick {
             self.exec.run()?;
         }
+        self.sync();
+        Ok(())
+    }
+
+    /// Sends the latest state to connected clients and handles their messages, at most once per output time step.
+    pub fn sync(&mut self) {
         let output_time_step = self.output_time_step();
         if self.last_tick.elapsed() >= output_time_step {
             self.last_tick += output_time_step;
             self.send();
             self.recv();
         }
-        Ok(())
+    }
+
+    pub fn exec(&self) -> &WorldExec<Compiled> {
+        &self.exec
+    }
+
+    pub fn exec_mut(&mut self) -> &mut WorldExec<Compiled> {
+        &mut self.exec
     }
 
     pub fn send(&mut self) {
@@ -228,20 +241,9 @@ pub fn spawn_tcp_server(
 ) -> Result<(), Error> {
     use std::time::{Duration, Instant};
 
-    use impeller::server::TcpServer;
-
-    let (tx, rx) = flume::unbounded();
     let exec = exec.compile(client)?;
-    let mut impeller_exec = ImpellerExec::new(exec, rx);
+    let mut impeller_exec = spawn_tcp_exec(socket_addr, exec)?;
     let time_step = impeller_exec.run_time_step();
-    std::thread::spawn(move || {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async move {
-            let server = TcpServer::bind(tx, socket_addr).await.unwrap();
-            server.run().await
-        })
-        .unwrap();
-    });
     let mut start = Instant::now();
     loop {
         impeller_exec.run()?;
@@ -255,3 +257,27 @@ pub fn spawn_tcp_server(
         }
     }
 }
+
+/// Starts a TCP server on `socket_addr` in the background, and returns an [`ImpellerExec`] serving its clients.
+///
+/// TLS and token authentication are configured from the environment, see [`impeller::access`].
+#[cfg(feature = "tokio")]
+pub fn spawn_tcp_exec(
+    socket_addr: std::net::SocketAddr,
+    exec: WorldExec<Compiled>,
+) -> Result<ImpellerExec, Error> {
+    use impeller::access::Access;
+    use impeller::server::TcpServer;
+
+    let access = Access::from_env()?;
+    let (tx, rx) = flume::unbounded();
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let server = TcpServer::bind(tx, socket_addr).await.unwrap();
+            server.with_access(access).run().await
+        })
+        .unwrap();
+    });
+    Ok(ImpellerExec::new(exec, rx))
+}
@@ -0,0 +1,289 @@
+//! Inputs written into components between ticks by an outside process, like flight software
+//! closing the loop against the simulation.
+use std::collections::VecDeque;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use impeller::{ComponentId, EntityId};
+
+use crate::{set_value, Error, TickHooks, World};
+
+/// What an input port writes on ticks between samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputPolicy {
+    /// Keeps writing the latest sample, like a zero-order hold.
+    #[default]
+    Hold,
+    /// Extends the line through the two latest samples.
+    Extrapolate,
+}
+
+/// A value for an input port, in effect from `time` seconds of simulation time.
+///
+/// Samples stamped in the future wait until the simulation gets there, so a controller running
+/// ahead can queue commands. A sample without a time takes effect on the next tick.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputSample {
+    pub time: Option<f64>,
+    pub value: Vec<f64>,
+}
+
+/// A component of one entity that is driven from outside the simulation.
+#[derive(Clone, Debug)]
+pub struct InputPort {
+    pub name: String,
+    component: ComponentId,
+    entity: EntityId,
+    pub policy: InputPolicy,
+    timeout: Option<(Duration, Vec<f64>)>,
+    pending: Vec<(f64, Vec<f64>)>,
+    history: VecDeque<(f64, Vec<f64>)>,
+}
+
+impl InputPort {
+    pub fn new(name: impl Into<String>, component: ComponentId, entity: EntityId) -> Self {
+        Self {
+            name: name.into(),
+            component,
+            entity,
+            policy: InputPolicy::Hold,
+            timeout: None,
+            pending: vec![],
+            history: VecDeque::with_capacity(2),
+        }
+    }
+
+    pub fn policy(mut self, policy: InputPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Writes `fallback` instead once no sample has arrived for `timeout`, so a stalled
+    /// controller doesn't leave an actuator commanding its last value forever.
+    pub fn timeout(mut self, timeout: Duration, fallback: Vec<f64>) -> Self {
+        self.timeout = Some((timeout, fallback));
+        self
+    }
+
+    fn push(&mut self, time: f64, value: Vec<f64>) {
+        let index = self.pending.partition_point(|(t, _)| *t <= time);
+        self.pending.insert(index, (time, value));
+    }
+
+    /// The value the port commands at `time`, if it has received anything yet.
+    fn value_at(&mut self, time: f64) -> Option<Vec<f64>> {
+        let ready = self.pending.partition_point(|(t, _)| *t <= time);
+        for sample in self.pending.drain(..ready) {
+            if self.history.len() == 2 {
+                self.history.pop_front();
+            }
+            self.history.push_back(sample);
+        }
+        let (t1, x1) = self.history.back()?;
+        if let Some((timeout, fallback)) = &self.timeout {
+            if time - t1 > timeout.as_secs_f64() {
+                return Some(fallback.clone());
+            }
+        }
+        match (self.policy, self.history.front()) {
+            (InputPolicy::Extrapolate, Some((t0, x0))) if t1 > t0 && x0.len() == x1.len() => {
+                let s = (time - t1) / (t1 - t0);
+                Some(x0.iter().zip(x1).map(|(a, b)| b + (b - a) * s).collect())
+            }
+            _ => Some(x1.clone()),
+        }
+    }
+}
+
+/// Sends samples to the ports of a [`ControlInputs`], from any thread.
+#[derive(Clone)]
+pub struct InputSender {
+    tx: flume::Sender<(String, InputSample)>,
+}
+
+impl InputSender {
+    /// Queues `value` for the port `name`, in effect from `time` seconds.
+    pub fn send_at(
+        &self,
+        name: impl Into<String>,
+        time: f64,
+        value: Vec<f64>,
+    ) -> Result<(), Error> {
+        let sample = InputSample {
+            time: Some(time),
+            value,
+        };
+        self.send(name, sample)
+    }
+
+    pub fn send(&self, name: impl Into<String>, sample: InputSample) -> Result<(), Error> {
+        self.tx
+            .send((name.into(), sample))
+            .map_err(|_| Error::ChannelClosed)
+    }
+
+    /// Listens for samples on a UDP socket, one per datagram, in a background thread.
+    ///
+    /// A datagram is text of the form `<port> <time> <values...>`, with `-` as the time of a
+    /// sample that takes effect on the next tick, like `torque 12.5 0.0 0.01 -0.2`. Malformed
+    /// datagrams are logged and dropped.
+    pub fn listen_udp(&self, addr: impl ToSocketAddrs) -> Result<std::net::SocketAddr, Error> {
+        let socket = UdpSocket::bind(addr)?;
+        let local = socket.local_addr()?;
+        let sender = self.clone();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 1500];
+            loop {
+                let Ok(len) = socket.recv(&mut buf) else {
+                    break;
+                };
+                let Some((name, sample)) =
+                    std::str::from_utf8(&buf[..len]).ok().and_then(parse_sample)
+                else {
+                    tracing::warn!(len, "malformed input datagram");
+                    continue;
+                };
+                if sender.send(name, sample).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(local)
+    }
+}
+
+fn parse_sample(text: &str) -> Option<(String, InputSample)> {
+    let mut words = text.split_whitespace();
+    let name = words.next()?.to_string();
+    let time = match words.next()? {
+        "-" => None,
+        time => Some(time.parse().ok()?),
+    };
+    let value = words
+        .map(str::parse)
+        .collect::<Result<Vec<f64>, _>>()
+        .ok()?;
+    Some((name, InputSample { time, value }))
+}
+
+/// A set of [`InputPort`]s fed through an [`InputSender`], and written into the world before
+/// each tick.
+pub struct ControlInputs {
+    ports: Vec<InputPort>,
+    tx: flume::Sender<(String, InputSample)>,
+    rx: flume::Receiver<(String, InputSample)>,
+}
+
+impl Default for ControlInputs {
+    fn default() -> Self {
+        let (tx, rx) = flume::unbounded();
+        Self {
+            ports: vec![],
+            tx,
+            rx,
+        }
+    }
+}
+
+impl ControlInputs {
+    pub fn port(mut self, port: InputPort) -> Self {
+        self.ports.push(port);
+        self
+    }
+
+    pub fn sender(&self) -> InputSender {
+        InputSender {
+            tx: self.tx.clone(),
+        }
+    }
+
+    /// Takes the samples that have arrived, and writes each port's value at `time` into `world`.
+    ///
+    /// Ports are written on every tick once they've received a sample, so a system that clears
+    /// the component doesn't undo a held input.
+    pub fn apply(&mut self, world: &mut World, time: f64) -> Result<(), Error> {
+        for (name, sample) in self.rx.try_iter() {
+            let Some(port) = self.ports.iter_mut().find(|p| p.name == name) else {
+                tracing::warn!(%name, "sample for unknown input port");
+                continue;
+            };
+            port.push(sample.time.unwrap_or(time), sample.value);
+        }
+        for port in &mut self.ports {
+            if let Some(value) = port.value_at(time) {
+                set_value(world, port.component, port.entity, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Registers the inputs as a pre-tick hook, returning the sender that feeds them.
+    pub fn install(self, hooks: &mut TickHooks) -> InputSender {
+        let sender = self.sender();
+        let inputs = Arc::new(Mutex::new(self));
+        hooks.add_pre_tick(move |ctx| {
+            let mut inputs = inputs.lock().map_err(|_| Error::HookPoisoned)?;
+            inputs.apply(ctx.world, ctx.time.as_secs_f64())
+        });
+        sender
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_mass::{LinearForce, PointMass};
+    use impeller::ComponentExt;
+    use nox::tensor;
+
+    fn force(world: &World) -> Vec<f64> {
+        let col = world.column::<LinearForce>().unwrap();
+        col.typed_buf::<f64>().unwrap().to_vec()
+    }
+
+    #[test]
+    fn test_control_inputs() {
+        let mut world = World::default();
+        let entity = world
+            .spawn(PointMass::new(
+                tensor![0.0, 0.0, 0.0],
+                tensor![0.0, 0.0, 0.0],
+                1.0,
+            ))
+            .id();
+        let port = |name| InputPort::new(name, LinearForce::COMPONENT_ID, entity);
+        let mut inputs = ControlInputs::default().port(
+            port("force")
+                .policy(InputPolicy::Extrapolate)
+                .timeout(Duration::from_secs(1), vec![0.0; 3]),
+        );
+        let sender = inputs.sender();
+
+        // nothing received yet, the component is left alone
+        inputs.apply(&mut world, 0.0).unwrap();
+        assert_eq!(force(&world), [0.0; 3]);
+
+        sender.send_at("force", 0.0, vec![1.0, 0.0, 0.0]).unwrap();
+        sender.send_at("force", 0.5, vec![2.0, 0.0, 0.0]).unwrap();
+        inputs.apply(&mut world, 0.25).unwrap();
+        assert_eq!(force(&world), [1.0, 0.0, 0.0]);
+        inputs.apply(&mut world, 0.75).unwrap();
+        assert_eq!(force(&world), [2.5, 0.0, 0.0]);
+        // the controller went quiet
+        inputs.apply(&mut world, 1.75).unwrap();
+        assert_eq!(force(&world), [0.0; 3]);
+
+        let (name, sample) = parse_sample("force - 0.5 1 -2e-3").unwrap();
+        sender.send(name, sample).unwrap();
+        inputs.apply(&mut world, 2.0).unwrap();
+        assert_eq!(force(&world), [0.5, 1.0, -2e-3]);
+        assert!(parse_sample("force 1.0 x").is_none());
+
+        sender.send_at("force", 2.0, vec![1.0]).unwrap();
+        assert!(matches!(
+            inputs.apply(&mut world, 2.0),
+            Err(Error::ValueSizeMismatch)
+        ));
+    }
+}
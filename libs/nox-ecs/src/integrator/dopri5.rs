@@ -0,0 +1,245 @@
+use crate::globals::SimulationTimeStep;
+use crate::system::{CompiledSystem, System, SystemBuilder, SystemParam};
+use crate::{ComponentArray, ComponentGroup, Error, Query};
+use impeller::World;
+use nox::{Noxpr, NoxprScalarExt, Scalar};
+use smallvec::{smallvec, SmallVec};
+use std::ops::Add;
+use std::{marker::PhantomData, ops::Mul};
+
+use super::Integrator;
+
+/// An adaptive Dormand–Prince 5(4) integrator, the system form of [`nox::Dopri5`].
+///
+/// Each tick is integrated in up to `max_steps` attempts. An attempt is accepted or rejected by
+/// comparing its fifth-order solution against the embedded fourth-order one, with one step size
+/// shared by every entity, and every tick starts with a step as long as the tick. The attempts
+/// are traced like [`nox::Dopri5::integrate`], so every tick pays for all of them, each costing
+/// six evaluations of the pipe. The last attempt takes whatever is left of the tick without error
+/// control, so a tick always advances by exactly its time step.
+pub struct Dopri5<U, DU, Pipe> {
+    dt: Option<f64>,
+    solver: nox::Dopri5,
+    pipe: Pipe,
+    phantom_data: PhantomData<(U, DU)>,
+}
+
+impl<Pipe, U, DU> Dopri5<U, DU, Pipe> {
+    pub fn new(pipe: Pipe, dt: Option<f64>, solver: nox::Dopri5) -> Self {
+        Self {
+            dt,
+            solver,
+            pipe,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+pub trait Dopri5Ext {
+    fn dopri5<U, DU>(self, solver: nox::Dopri5) -> Dopri5<U, DU, Self>
+    where
+        Self: Sized;
+    fn dopri5_with_dt<U, DU>(self, dt: f64, solver: nox::Dopri5) -> Dopri5<U, DU, Self>
+    where
+        Self: Sized;
+}
+
+impl<Sys> Dopri5Ext for Sys
+where
+    Sys: System,
+{
+    fn dopri5<U, DU>(self, solver: nox::Dopri5) -> Dopri5<U, DU, Self>
+    where
+        Self: Sized,
+    {
+        Dopri5::new(self, None, solver)
+    }
+
+    fn dopri5_with_dt<U, DU>(self, dt: f64, solver: nox::Dopri5) -> Dopri5<U, DU, Self>
+    where
+        Self: Sized,
+    {
+        Dopri5::new(self, Some(dt), solver)
+    }
+}
+
+impl<Pipe, U, DU> System for Dopri5<U, DU, Pipe>
+where
+    Query<U>: SystemParam<Item = Query<U>> + Clone,
+    Query<DU>: SystemParam<Item = Query<DU>> + Clone,
+    U: Add<DU, Output = U> + ComponentGroup + for<'a> nox::FromBuilder<Item<'a> = U> + Send + Sync,
+    DU: Add<DU, Output = DU>
+        + ComponentGroup
+        + for<'a> nox::FromBuilder<Item<'a> = DU>
+        + Send
+        + Sync,
+    f64: Mul<DU, Output = DU>,
+    Scalar<f64>: Mul<DU, Output = DU>,
+    Pipe: System + Send + Sync,
+{
+    type Arg = ();
+    type Ret = ();
+
+    fn init(&self, builder: &mut SystemBuilder) -> Result<(), Error> {
+        self.pipe.init(builder)?;
+        ComponentArray::<SimulationTimeStep>::init(builder)?;
+        Query::<U>::init(builder)?;
+        Query::<DU>::init(builder)
+    }
+
+    fn compile(&self, world: &World) -> Result<CompiledSystem, Error> {
+        let mut builder = SystemBuilder::new(world);
+        let compiled_pipe = self.pipe.compile(world)?;
+        self.init(&mut builder)?;
+        let sim_dt = ComponentArray::<SimulationTimeStep>::param(&builder)?;
+        let dt = self.dt.map(Scalar::from).unwrap_or_else(|| sim_dt.get(0).0);
+
+        let eval = |u: &Query<U>, builder: &mut SystemBuilder| -> Result<Query<DU>, Error> {
+            u.insert_into_builder(builder);
+            compiled_pipe.clone().insert_into_builder(builder)?;
+            Query::<DU>::param(builder)
+        };
+
+        let solver = &self.solver;
+        let zero = Scalar::<f64>::from(0.0);
+        let one = Scalar::<f64>::from(1.0);
+        let h_min = Scalar::<f64>::from(solver.h_min);
+        let h_max = Scalar::<f64>::from(solver.h_max);
+        let [.., weights5] = nox::Dopri5::TABLEAU;
+        let weights4 = weights5
+            .iter()
+            .chain([&0.0])
+            .zip(nox::Dopri5::ERROR_WEIGHTS)
+            .map(|(b, e)| b - e)
+            .collect::<Vec<_>>();
+        let attempts = solver.max_steps.max(1);
+
+        let mut u = Query::<U>::param(&builder)?;
+        let mut k1 = eval(&u, &mut builder)?;
+        let mut t = zero.clone();
+        let mut h = dt.clamp(&h_min, &h_max);
+        for attempt in 0..attempts {
+            let remaining = &dt - &t;
+            let last = attempt + 1 == attempts;
+            let h_try = if last {
+                remaining.clone()
+            } else {
+                h.min(&remaining).max(&zero)
+            };
+            let mut k = vec![k1.clone()];
+            for row in &nox::Dopri5::TABLEAU[..5] {
+                let stage = advance(&u, &weighted_sum(row, &k)?, &h_try)?;
+                k.push(eval(&stage, &mut builder)?);
+            }
+            let y5 = advance(&u, &weighted_sum(weights5, &k)?, &h_try)?;
+            if last {
+                u = y5;
+                break;
+            }
+            let k7 = eval(&y5, &mut builder)?;
+            k.push(k7.clone());
+            let y4 = advance(&u, &weighted_sum(&weights4, &k)?, &h_try)?;
+            let err = error_norm(solver, &u, &y5, &y4);
+
+            let h_new = &h_try * nox::Dopri5::step_factor(&err);
+            // like `nox::Dopri5::integrate`, a step clipped to the end of the tick doesn't shrink
+            // the next one
+            let h_accepted = remaining.select_less(&h, &h_new.max(&h), &h_new);
+            // the step is rejected when `1 < err`
+            let reject = 1.0f64.constant().less(err.inner().clone());
+            t = one.select_less(&err, &t, &(&t + &h_try));
+            u = select(&reject, u, y5);
+            // the last stage is evaluated at the solution, so it's the first stage of the next step
+            k1 = select(&reject, k1, k7);
+            h = one
+                .select_less(&err, &h_new, &h_accepted)
+                .clamp(&h_min, &h_max);
+        }
+        u.insert_into_builder(&mut builder);
+        let mut compiled = builder.to_compiled_system()?;
+        // the pipe is inserted once per stage, but only traced once
+        compiled.systems = compiled_pipe.systems;
+        compiled.integrator = Some(Integrator::Dopri5(self.solver).name().to_string());
+        Ok(compiled)
+    }
+}
+
+/// Advances `u` by `h` along `slope`.
+fn advance<U, DU>(u: &Query<U>, slope: &Query<DU>, h: &Scalar<f64>) -> Result<Query<U>, Error>
+where
+    U: Add<DU, Output = U> + ComponentGroup + for<'a> nox::FromBuilder<Item<'a> = U>,
+    DU: ComponentGroup + for<'a> nox::FromBuilder<Item<'a> = DU>,
+    Scalar<f64>: Mul<DU, Output = DU>,
+{
+    let h = h.clone();
+    u.clone()
+        .join_query(slope.clone())
+        .map(move |u: U, slope: DU| u + h.clone() * slope)
+}
+
+fn weighted_sum<DU>(weights: &[f64], k: &[Query<DU>]) -> Result<Query<DU>, Error>
+where
+    DU: Add<DU, Output = DU> + ComponentGroup + for<'a> nox::FromBuilder<Item<'a> = DU>,
+    f64: Mul<DU, Output = DU>,
+{
+    let mut terms = weights.iter().zip(k).filter(|(w, _)| **w != 0.0);
+    let (&w, first) = terms.next().expect("every stage has a non-zero weight");
+    let mut sum = first.map(move |k: DU| w * k)?;
+    for (&w, k) in terms {
+        sum = sum
+            .join_query(k.clone())
+            .map(move |sum: DU, k: DU| sum + w * k)?;
+    }
+    Ok(sum)
+}
+
+/// The RMS of `y5 - y4` over every element of every entity's state, each scaled by
+/// `atol + rtol * max(|u|, |y5|)` like [`nox::Dopri5`] does.
+fn error_norm<U: ComponentGroup>(
+    solver: &nox::Dopri5,
+    u: &Query<U>,
+    y5: &Query<U>,
+    y4: &Query<U>,
+) -> Scalar<f64> {
+    let mut sum = 0.0f64.constant();
+    let mut count = 0;
+    for (i, shape) in shapes::<U>(y5.len).enumerate() {
+        let n = shape.iter().product::<i64>();
+        let u_abs = u.exprs[i].clone().abs();
+        let y_abs = y5.exprs[i].clone().abs();
+        let magnitude = u_abs.clone().less(y_abs.clone()).select(y_abs, u_abs);
+        let scale = solver.atol.constant().broadcast(shape.clone())
+            + solver.rtol.constant().broadcast(shape) * magnitude;
+        let ratio = ((y5.exprs[i].clone() - y4.exprs[i].clone()) / scale).reshape(smallvec![n]);
+        sum = sum + ratio.clone().dot(&ratio);
+        count += n;
+    }
+    let mean = (1.0 / count.max(1) as f64).constant() * sum;
+    Scalar::from_inner(mean.sqrt())
+}
+
+/// Keeps `rejected` where `reject` holds and `accepted` otherwise.
+fn select<G: ComponentGroup>(reject: &Noxpr, rejected: Query<G>, accepted: Query<G>) -> Query<G> {
+    let exprs = shapes::<G>(accepted.len)
+        .zip(rejected.exprs)
+        .zip(accepted.exprs)
+        .map(|((shape, rejected), accepted)| {
+            reject.clone().broadcast(shape).select(rejected, accepted)
+        })
+        .collect();
+    Query {
+        exprs,
+        entity_map: accepted.entity_map,
+        len: accepted.len,
+        phantom_data: PhantomData,
+    }
+}
+
+/// The shapes of the component arrays of `len` entities.
+fn shapes<G: ComponentGroup>(len: usize) -> impl Iterator<Item = SmallVec<[i64; 4]>> {
+    G::component_types().map(move |ty| {
+        std::iter::once(len as i64)
+            .chain(ty.shape.iter().copied())
+            .collect()
+    })
+}
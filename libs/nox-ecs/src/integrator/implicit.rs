@@ -0,0 +1,202 @@
+use crate::globals::SimulationTimeStep;
+use crate::system::{CompiledSystem, System, SystemBuilder, SystemParam};
+use crate::{ComponentArray, ComponentGroup, Error, Query};
+use impeller::World;
+use nox::{BackwardEuler, Const, Op, ReprMonad, Scalar, Vector};
+use std::marker::PhantomData;
+use std::sync::{Mutex, PoisonError};
+
+use super::Integrated;
+
+/// Backward Euler integrator for a single stiff component, `dx/dt = f(x)`.
+///
+/// Unlike [`crate::Integrator`], which advances a whole body, this only steps `X`, so a stiff
+/// thermal or chemical model can be piped next to rigid body dynamics and run at the same tick
+/// rate instead of forcing a tiny global step.
+///
+/// The Newton iterations run in a loop on the device, stopping early once the residual is below
+/// the solver's `tol`, see [`BackwardEuler::step_until_converged`]. A solver error surfaces when
+/// the system is compiled.
+pub fn backward_euler_with_dt<X, const N: usize>(
+    f: impl Fn(&Vector<f64, N>) -> Vector<f64, N> + Send + Sync + 'static,
+    dt: f64,
+    solver: BackwardEuler,
+) -> impl System<Arg = (), Ret = ()>
+where
+    Query<X>: SystemParam<Item = Query<X>> + Clone,
+    X: ComponentGroup + for<'a> nox::FromBuilder<Item<'a> = X>,
+    X: ReprMonad<Op, Elem = f64, Dim = Const<N>>,
+{
+    Integrated::new(
+        BackwardEulerSystem::new(f, Some(dt), solver),
+        "backward-euler",
+    )
+}
+
+/// Backward Euler integrator for a single stiff component, using the simulation time step.
+///
+/// See [`backward_euler_with_dt`].
+pub fn backward_euler<X, const N: usize>(
+    f: impl Fn(&Vector<f64, N>) -> Vector<f64, N> + Send + Sync + 'static,
+    solver: BackwardEuler,
+) -> impl System<Arg = (), Ret = ()>
+where
+    Query<X>: SystemParam<Item = Query<X>> + Clone,
+    X: ComponentGroup + for<'a> nox::FromBuilder<Item<'a> = X>,
+    X: ReprMonad<Op, Elem = f64, Dim = Const<N>>,
+{
+    Integrated::new(BackwardEulerSystem::new(f, None, solver), "backward-euler")
+}
+
+struct BackwardEulerSystem<X, F> {
+    f: F,
+    dt: Option<f64>,
+    solver: BackwardEuler,
+    phantom_data: PhantomData<X>,
+}
+
+impl<X, F> BackwardEulerSystem<X, F> {
+    fn new(f: F, dt: Option<f64>, solver: BackwardEuler) -> Self {
+        Self {
+            f,
+            dt,
+            solver,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<X, F, const N: usize> System for BackwardEulerSystem<X, F>
+where
+    F: Fn(&Vector<f64, N>) -> Vector<f64, N> + Send + Sync,
+    Query<X>: SystemParam<Item = Query<X>> + Clone,
+    X: ComponentGroup + for<'a> nox::FromBuilder<Item<'a> = X>,
+    X: ReprMonad<Op, Elem = f64, Dim = Const<N>>,
+{
+    type Arg = ();
+    type Ret = ();
+
+    fn init(&self, builder: &mut SystemBuilder) -> Result<(), Error> {
+        ComponentArray::<SimulationTimeStep>::init(builder)?;
+        Query::<X>::init(builder)
+    }
+
+    fn compile(&self, world: &World) -> Result<CompiledSystem, Error> {
+        let mut builder = SystemBuilder::new(world);
+        self.init(&mut builder)?;
+        let sim_dt = ComponentArray::<SimulationTimeStep>::param(&builder)?;
+        let dt = self.dt.map(Scalar::from).unwrap_or_else(|| sim_dt.get(0).0);
+        // `Query::map` takes an infallible function, so the solver's error is carried out of it
+        let failed = Mutex::new(None);
+        let query = Query::<X>::param(&builder)?.map(|x: X| {
+            step_component(&self.f, &self.solver, &x, &dt).unwrap_or_else(|err| {
+                *failed.lock().unwrap_or_else(PoisonError::into_inner) = Some(err);
+                x
+            })
+        })?;
+        if let Some(err) = failed.into_inner().unwrap_or_else(PoisonError::into_inner) {
+            return Err(err.into());
+        }
+        query.insert_into_builder(&mut builder);
+        builder.to_compiled_system()
+    }
+}
+
+fn step_component<X, const N: usize>(
+    f: &impl Fn(&Vector<f64, N>) -> Vector<f64, N>,
+    solver: &BackwardEuler,
+    x: &X,
+    dt: &Scalar<f64>,
+) -> Result<X, nox::Error>
+where
+    X: ReprMonad<Op, Elem = f64, Dim = Const<N>>,
+{
+    let x = Vector::from_inner(x.inner().clone());
+    let x = solver
+        .step_until_converged(|_, x| f(x), &0.0.into(), &x, dt)?
+        .y;
+    Ok(X::from_inner(x.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Archetype, Component, World, WorldExt};
+    use nox::{tensor, xla::ElementType, OwnedRepr};
+    use nox_ecs_macros::ReprMonad;
+
+    #[test]
+    fn test_backward_euler() {
+        #[derive(Clone, Component, ReprMonad)]
+        struct Temp<R: OwnedRepr = Op>(Vector<f64, 1, R>);
+
+        #[derive(Archetype)]
+        struct Body {
+            temp: Temp,
+        }
+
+        let mut world = World::default();
+        world.spawn(Body {
+            temp: Temp(tensor![1.0].into()),
+        });
+        // relaxes towards zero with a time constant far shorter than the step
+        let cool = |t: &Vector<f64, 1>| -1e4 * t;
+        let world = world
+            .builder()
+            .tick_pipeline(backward_euler_with_dt::<Temp, 1>(
+                cool,
+                0.1,
+                BackwardEuler::default(),
+            ))
+            .run();
+        let col = world.column::<Temp>().unwrap();
+        let temp = col.typed_buf::<f64>().unwrap()[0];
+        assert!((temp - 1.0 / 1001.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_backward_euler_tol() {
+        #[derive(Clone, Component, ReprMonad)]
+        struct Level<R: OwnedRepr = Op>(Vector<f64, 1, R>);
+
+        #[derive(Clone, Component, ReprMonad)]
+        struct Iterations<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+        #[derive(Archetype)]
+        struct Tank {
+            level: Level,
+            iterations: Iterations,
+        }
+
+        let mut world = World::default();
+        for level in [0.0, 2.0] {
+            world.spawn(Tank {
+                level: Level(tensor![level].into()),
+                iterations: Iterations(0.0.into()),
+            });
+        }
+        let solver = BackwardEuler::default().newton_iters(20).tol(1e-12);
+        let step = move |query: Query<(Level, Iterations)>| -> Query<(Level, Iterations)> {
+            query
+                .map(|level: Level, _: Iterations| {
+                    let drain = |_: &Scalar<f64>, l: &Vector<f64, 1>| -1e4 * l;
+                    let out = solver
+                        .step_until_converged(drain, &0.0.into(), &level.0, &0.1.into())
+                        .unwrap();
+                    let iterations = out.iterations.inner().convert(ElementType::F64);
+                    (Level(out.y), Iterations(Scalar::from_inner(iterations)))
+                })
+                .unwrap()
+        };
+        let world = world.builder().tick_pipeline(step).run();
+        let col = world.column::<Level>().unwrap();
+        let levels = col.typed_buf::<f64>().unwrap();
+        assert!((levels[1] - 2.0 / 1001.0).abs() < 1e-9);
+        // the empty tank's guess is already exact, so its lane stops before taking a step, and the
+        // drain is linear, so the other lane stops long before the iteration limit
+        let col = world.column::<Iterations>().unwrap();
+        let iterations = col.typed_buf::<f64>().unwrap();
+        assert_eq!(iterations[0], 0.0);
+        assert!(iterations[1] >= 1.0 && iterations[1] <= 3.0);
+    }
+}
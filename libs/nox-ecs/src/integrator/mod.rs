@@ -1,10 +1,58 @@
+mod dopri5;
+mod implicit;
 mod rk4;
 mod semi_implicit;
 
+pub use dopri5::*;
+pub use implicit::*;
 pub use rk4::*;
 pub use semi_implicit::*;
 
+use impeller::World;
+
+use crate::{CompiledSystem, Error, System, SystemBuilder};
+
 pub enum Integrator {
     Rk4,
     SemiImplicit,
+    /// Adaptive Dormand–Prince 5(4) within each tick, see [`Dopri5`].
+    Dopri5(nox::Dopri5),
+}
+
+impl Integrator {
+    /// The name recorded in [`CompiledSystem::integrator`], matching the Python `Integrator` names.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Integrator::Rk4 => "rk4",
+            Integrator::SemiImplicit => "semi-implicit",
+            Integrator::Dopri5(_) => "dopri5",
+        }
+    }
+}
+
+/// Records `name` as the integrator of the system it wraps, see [`CompiledSystem::integrator`].
+pub(crate) struct Integrated<S> {
+    system: S,
+    name: &'static str,
+}
+
+impl<S> Integrated<S> {
+    pub(crate) fn new(system: S, name: &'static str) -> Self {
+        Self { system, name }
+    }
+}
+
+impl<S: System> System for Integrated<S> {
+    type Arg = S::Arg;
+    type Ret = S::Ret;
+
+    fn init(&self, builder: &mut SystemBuilder) -> Result<(), Error> {
+        self.system.init(builder)
+    }
+
+    fn compile(&self, world: &World) -> Result<CompiledSystem, Error> {
+        let mut compiled = self.system.compile(world)?;
+        compiled.integrator = Some(self.name.to_string());
+        Ok(compiled)
+    }
 }
@@ -6,6 +6,8 @@ use nox::Scalar;
 use std::ops::Add;
 use std::{marker::PhantomData, ops::Mul};
 
+use super::Integrator;
+
 pub struct Rk4<U, DU, Pipe> {
     dt: Option<f64>,
     pipe: Pipe,
@@ -121,7 +123,11 @@ where
             })
             .unwrap();
         u.insert_into_builder(&mut builder);
-        builder.to_compiled_system()
+        let mut compiled = builder.to_compiled_system()?;
+        // the pipe is inserted once per stage, but only traced once
+        compiled.systems = compiled_pipe.systems;
+        compiled.integrator = Some(Integrator::Rk4.name().to_string());
+        Ok(compiled)
     }
 }
 
@@ -179,15 +185,21 @@ mod tests {
             v: V,
         }
 
-        let mut world = World::default();
-        world.spawn(Body {
+        let body = || Body {
             x: X(0.0.into()),
             v: V(10.0.into()),
-        });
+        };
+        let mut world = World::default();
+        world.spawn(body());
         let builder = world.builder().tick_pipeline(().rk4::<X, V>());
         let world = builder.run();
         let col = world.column::<X>().unwrap();
-        assert_eq!(col.typed_buf::<f64>().unwrap(), &[0.08333333])
+        assert_eq!(col.typed_buf::<f64>().unwrap(), &[0.08333333]);
+
+        let mut world = World::default();
+        world.spawn(body());
+        let exec = world.builder().tick_pipeline(().rk4::<X, V>()).build();
+        assert_eq!(exec.unwrap().tick_exec.integrator(), Some("rk4"));
     }
 
     #[test]
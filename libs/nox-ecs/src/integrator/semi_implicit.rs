@@ -1,6 +1,8 @@
 use crate::globals::SimulationTimeStep;
 use crate::{ComponentArray, ComponentGroup, ErasedSystem, IntoSystem, Query};
 use crate::{System, SystemParam};
+
+use super::{Integrated, Integrator};
 use core::ops::Add;
 use core::ops::Mul;
 use nox::Scalar;
@@ -27,7 +29,8 @@ where
 {
     let step_v = move |query: Query<(V, A)>| -> Query<V> { query.map(|v, a| v + dt * a).unwrap() };
     let step_x = move |query: Query<(X, V)>| -> Query<X> { query.map(|x, v| x + dt * v).unwrap() };
-    ErasedSystem::new(step_v.pipe(step_x))
+    let name = Integrator::SemiImplicit.name();
+    ErasedSystem::new(Integrated::new(step_v.pipe(step_x), name))
 }
 
 /// Semi-implicit Euler integrator, typically used when you need a sympletic integrator
@@ -58,7 +61,8 @@ where
         let dt = dt.get(0).0;
         query.map(|x, v| x + dt.clone() * v).unwrap()
     };
-    ErasedSystem::new(step_v.pipe(step_x))
+    let name = Integrator::SemiImplicit.name();
+    ErasedSystem::new(Integrated::new(step_v.pipe(step_x), name))
 }
 
 #[cfg(test)]
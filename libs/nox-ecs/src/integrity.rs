@@ -0,0 +1,210 @@
+//! Checksums of the files in recordings and checkpoints, so a partial write to flaky storage is
+//! caught when the directory is read back instead of surfacing as a confusing parse error.
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// The manifest written at the root of the directory it covers.
+pub const CHECKSUMS_FILE: &str = "checksums.json";
+const BLOCK_SIZE: u64 = 1 << 20;
+
+/// The length and per-block CRC-32s of every file under a directory.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Checksums {
+    pub block_size: u64,
+    /// Keyed by path relative to the directory, with `/` separators.
+    pub files: BTreeMap<String, FileChecksums>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FileChecksums {
+    pub len: u64,
+    pub blocks: Vec<u32>,
+}
+
+impl Checksums {
+    /// Checksums every file under `dir`, apart from an existing manifest.
+    pub fn of_dir(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+        let mut files = BTreeMap::new();
+        for path in files_under(dir)? {
+            let name = relative_name(dir, &path);
+            if name == CHECKSUMS_FILE {
+                continue;
+            }
+            files.insert(name, file_checksums(&path, BLOCK_SIZE)?);
+        }
+        Ok(Self {
+            block_size: BLOCK_SIZE,
+            files,
+        })
+    }
+
+    pub fn write(&self, dir: impl AsRef<Path>) -> Result<(), Error> {
+        let file = File::create(dir.as_ref().join(CHECKSUMS_FILE))?;
+        serde_json::to_writer(file, self)?;
+        Ok(())
+    }
+
+    /// Reads the manifest of `dir`, if it has one.
+    pub fn read(dir: impl AsRef<Path>) -> Result<Option<Self>, Error> {
+        let path = dir.as_ref().join(CHECKSUMS_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let buf = std::fs::read(&path)?;
+        let checksums = serde_json::from_slice(&buf).map_err(|err| Error::Corrupt {
+            path,
+            reason: format!("unreadable manifest: {err}"),
+        })?;
+        Ok(Some(checksums))
+    }
+
+    /// Checks every listed file under `dir`, failing on the first one that's missing, has the
+    /// wrong length, or has a block that doesn't match.
+    pub fn verify(&self, dir: impl AsRef<Path>) -> Result<(), Error> {
+        let dir = dir.as_ref();
+        for (name, expected) in &self.files {
+            let path = dir.join(name);
+            let corrupt = |reason: String| Error::Corrupt {
+                path: path.clone(),
+                reason,
+            };
+            if !path.exists() {
+                return Err(corrupt("missing".to_string()));
+            }
+            let found = file_checksums(&path, self.block_size)?;
+            if found.len != expected.len {
+                return Err(corrupt(format!(
+                    "expected {} bytes, found {}",
+                    expected.len, found.len
+                )));
+            }
+            let bad = expected
+                .blocks
+                .iter()
+                .zip(&found.blocks)
+                .position(|(a, b)| a != b);
+            if let Some(block) = bad {
+                let start = block as u64 * self.block_size;
+                let end = (start + self.block_size).min(expected.len);
+                return Err(corrupt(format!(
+                    "checksum mismatch in block {block} (bytes {start}..{end})"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes a manifest covering everything under `dir`, see [`Checksums`].
+pub fn write_checksums(dir: impl AsRef<Path>) -> Result<(), Error> {
+    let dir = dir.as_ref();
+    Checksums::of_dir(dir)?.write(dir)
+}
+
+/// Verifies the files under `dir` against its manifest. Directories written before checksums
+/// were added have no manifest, and pass.
+pub fn verify_dir(dir: impl AsRef<Path>) -> Result<(), Error> {
+    let dir = dir.as_ref();
+    match Checksums::read(dir)? {
+        Some(checksums) => checksums.verify(dir),
+        None => Ok(()),
+    }
+}
+
+fn file_checksums(path: &Path, block_size: u64) -> Result<FileChecksums, Error> {
+    let mut file = File::open(path)?;
+    let mut buf = vec![0; block_size as usize];
+    let mut len = 0;
+    let mut blocks = vec![];
+    loop {
+        let read = read_block(&mut file, &mut buf)?;
+        if read == 0 {
+            break;
+        }
+        len += read as u64;
+        blocks.push(crc32fast::hash(&buf[..read]));
+    }
+    Ok(FileChecksums { len, blocks })
+}
+
+/// Fills `buf` unless the file ends first, returning how much was read.
+fn read_block(file: &mut File, buf: &mut [u8]) -> Result<usize, Error> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn files_under(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = vec![];
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn relative_name(dir: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(dir).unwrap_or(path);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        std::fs::create_dir(dir.join("world")).unwrap();
+        let data = (0..3_000_000u32)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+        std::fs::write(dir.join("world/data.bin"), &data).unwrap();
+        std::fs::write(dir.join("metadata.json"), b"{}").unwrap();
+
+        // nothing to check against yet
+        verify_dir(dir).unwrap();
+        write_checksums(dir).unwrap();
+        let checksums = Checksums::read(dir).unwrap().unwrap();
+        assert_eq!(checksums.files["world/data.bin"].blocks.len(), 3);
+        verify_dir(dir).unwrap();
+
+        let mut flipped = data.clone();
+        flipped[2_500_000] ^= 1;
+        std::fs::write(dir.join("world/data.bin"), &flipped).unwrap();
+        let err = verify_dir(dir).unwrap_err().to_string();
+        assert!(err.contains("world/data.bin"), "{err}");
+        assert!(err.contains("block 2 (bytes 2097152..3000000)"), "{err}");
+
+        std::fs::write(dir.join("world/data.bin"), &data[..1000]).unwrap();
+        let err = verify_dir(dir).unwrap_err().to_string();
+        assert!(err.contains("expected 3000000 bytes, found 1000"), "{err}");
+
+        std::fs::remove_file(dir.join("world/data.bin")).unwrap();
+        assert!(matches!(verify_dir(dir), Err(Error::Corrupt { .. })));
+    }
+}
@@ -4,40 +4,78 @@ use impeller::well_known::{Color, EntityMetadata, Material, Mesh};
 use impeller::{Archetype, ComponentExt, ComponentId, ComponentType, EntityId, Handle};
 use nox::xla::{BufferArgsRef, HloModuleProto, PjRtBuffer, PjRtLoadedExecutable};
 use nox::{ArrayTy, Client, CompFn, Noxpr};
-use profile::Profiler;
 use serde::{Deserialize, Serialize};
 use smallvec::{smallvec, SmallVec};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fs::File;
 use std::iter::once;
 use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::{collections::BTreeMap, marker::PhantomData};
 
 pub use impeller;
 pub use nox;
 
+mod batch;
+mod bvh;
+mod clock;
+mod compile_cache;
 mod component;
+mod cosim;
+mod dense_output;
+mod determinism;
 mod dyn_array;
+mod events;
 mod globals;
+mod hierarchy;
 mod history;
+mod hooks;
 mod impeller_exec;
+mod input;
 mod integrator;
+mod integrity;
 mod profile;
 mod query;
+mod raycast;
+mod run_config;
+mod scratch;
 mod system;
+mod timestep;
+mod unit_quaternion;
 
 pub mod graph;
+pub mod point_mass;
 pub mod six_dof;
 
+pub use batch::*;
+pub use bvh::*;
+pub use clock::*;
+pub use compile_cache::*;
 pub use component::*;
+pub use cosim::*;
+pub use dense_output::*;
+pub use determinism::*;
 pub use dyn_array::*;
+pub use events::*;
 pub use globals::*;
-pub use impeller::{Buffers, ColumnRef, Entity, PolarsWorld, TimeStep, World};
+pub use hierarchy::*;
+pub use history::*;
+pub use hooks::*;
+pub use impeller::{Buffers, ColumnRef, Entity, PolarsWorld, Retention, TimeStep, World};
 pub use impeller_exec::*;
+pub use input::*;
 pub use integrator::*;
+pub use integrity::*;
+pub use profile::*;
 pub use query::*;
+pub use raycast::*;
+pub use run_config::*;
+pub use scratch::*;
 pub use system::*;
+pub use timestep::*;
+pub use unit_quaternion::*;
 
 pub use nox_ecs_macros::{Archetype, Component};
 
@@ -173,6 +211,8 @@ pub fn update_var(
 pub trait WorldExt {
     fn add_globals(&mut self);
     fn builder(self) -> WorldBuilder;
+    /// Casts a ray against every entity's shape, see [`raycast()`].
+    fn raycast(&self, origin: [f64; 3], dir: [f64; 3]) -> Option<RayHit>;
 }
 
 impl WorldExt for World {
@@ -187,6 +227,10 @@ impl WorldExt for World {
     fn builder(self) -> WorldBuilder {
         WorldBuilder::default().world(self)
     }
+
+    fn raycast(&self, origin: [f64; 3], dir: [f64; 3]) -> Option<RayHit> {
+        raycast::raycast(self, origin, dir, f64::INFINITY, &[])
+    }
 }
 
 pub struct WorldBuilder<Sys = (), StartupSys = ()> {
@@ -256,10 +300,17 @@ where
     }
 
     pub fn build(mut self) -> Result<WorldExec, Error> {
+        let start = &mut Instant::now();
         self.world.add_globals();
         let tick_exec = increment_sim_tick.pipe(self.pipe).build(&mut self.world)?;
         let startup_exec = self.startup_sys.build(&mut self.world)?;
-        let world_exec = WorldExec::new(self.world, tick_exec, Some(startup_exec));
+        let mut world_exec = WorldExec::new(self.world, tick_exec, Some(startup_exec));
+        let profiler = &mut world_exec.profiler;
+        profiler.observe_systems(&world_exec.tick_exec.metadata.systems);
+        if let Some(startup_exec) = &world_exec.startup_exec {
+            profiler.observe_systems(&startup_exec.metadata.systems);
+        }
+        profiler.build.observe(start);
         Ok(world_exec)
     }
 
@@ -300,6 +351,7 @@ pub trait SystemExt {
 
 impl<S: crate::system::System> SystemExt for S {
     fn build(self, world: &mut World) -> Result<Exec, Error> {
+        let _span = tracing::debug_span!("build").entered();
         let mut system_builder = SystemBuilder {
             vars: BTreeMap::default(),
             inputs: vec![],
@@ -310,12 +362,20 @@ impl<S: crate::system::System> SystemExt for S {
             computation,
             inputs,
             outputs,
+            systems,
+            integrator,
         } = self.compile(world)?;
+        // named constants become runtime arguments, so a rebuild that only retunes them produces the
+        // same HLO module and can reuse a cached executable
+        let (func, params) = computation.func.hoist_constants(inputs.len() as i64)?;
         let metadata = ExecMetadata {
             arg_ids: inputs,
             ret_ids: outputs,
+            systems,
+            params,
+            integrator,
         };
-        let computation = computation.func.build("exec")?.build()?;
+        let computation = func.build("exec")?.build()?;
         Ok(Exec::new(metadata, computation.to_hlo_module()))
     }
 }
@@ -324,6 +384,16 @@ impl<S: crate::system::System> SystemExt for S {
 pub struct ExecMetadata {
     pub arg_ids: Vec<ComponentId>,
     pub ret_ids: Vec<ComponentId>,
+    /// The systems traced into the executable, in the order they run.
+    #[serde(default)]
+    pub systems: Vec<SystemTrace>,
+    /// The named constants hoisted out of the traced systems, see [`nox::Noxpr::named_constant`],
+    /// passed after the components.
+    #[serde(default)]
+    pub params: Vec<(String, f64)>,
+    /// The integrators advancing the state, see [`CompiledSystem::integrator`].
+    #[serde(default)]
+    pub integrator: Option<String>,
 }
 
 pub trait ExecState: Clone {}
@@ -335,6 +405,7 @@ pub struct Uncompiled;
 pub struct Compiled {
     client: Client,
     exec: PjRtLoadedExecutable,
+    params: Arc<Vec<PjRtBuffer>>,
 }
 
 impl ExecState for Uncompiled {}
@@ -356,6 +427,28 @@ impl<S: ExecState> Exec<S> {
         std::fs::write(path.join("hlo.binpb"), self.hlo_module.to_bytes())?;
         Ok(())
     }
+
+    pub fn integrator(&self) -> Option<&str> {
+        self.metadata.integrator.as_deref()
+    }
+
+    pub fn hlo_module(&self) -> &HloModuleProto {
+        &self.hlo_module
+    }
+
+    /// The named constants hoisted out of the traced systems, see [`ExecMetadata::params`].
+    pub fn params(&self) -> &[(String, f64)] {
+        &self.metadata.params
+    }
+
+    /// The value of the named constant `name`, if a traced system uses it.
+    pub fn param(&self, name: &str) -> Option<f64> {
+        self.metadata
+            .params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|&(_, value)| value)
+    }
 }
 
 impl Exec {
@@ -370,10 +463,33 @@ impl Exec {
     pub fn compile(self, client: Client) -> Result<Exec<Compiled>, Error> {
         let comp = self.hlo_module.computation();
         let exec = client.compile(&comp)?;
+        self.into_compiled(client, exec)
+    }
+
+    /// Like [`Exec::compile`], but loads the executable from `cache` when it has already been compiled.
+    pub fn compile_cached(
+        self,
+        client: Client,
+        cache: &CompileCache,
+    ) -> Result<Exec<Compiled>, Error> {
+        let exec = cache.compile(&client, &self.hlo_module)?;
+        self.into_compiled(client, exec)
+    }
+
+    fn into_compiled(
+        self,
+        client: Client,
+        exec: PjRtLoadedExecutable,
+    ) -> Result<Exec<Compiled>, Error> {
+        let params = upload_params(&client, &self.metadata.params)?;
         Ok(Exec {
             metadata: self.metadata,
             hlo_module: self.hlo_module,
-            state: Compiled { client, exec },
+            state: Compiled {
+                client,
+                exec,
+                params,
+            },
         })
     }
 
@@ -387,12 +503,43 @@ impl Exec {
     }
 }
 
+fn upload_params(client: &Client, params: &[(String, f64)]) -> Result<Arc<Vec<PjRtBuffer>>, Error> {
+    let params = params
+        .iter()
+        .map(|(_, value)| client.copy_host_buffer(std::slice::from_ref(value), &[]))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Arc::new(params))
+}
+
 impl Exec<Compiled> {
+    /// Sets the named constants hoisted out of the traced systems, see [`ExecMetadata::params`],
+    /// leaving the ones not mentioned as they are.
+    ///
+    /// This retunes a compiled executable without tracing or compiling it again.
+    pub fn set_params<'a>(
+        &mut self,
+        params: impl IntoIterator<Item = (&'a str, f64)>,
+    ) -> Result<(), Error> {
+        let mut values = self.metadata.params.clone();
+        for (name, value) in params {
+            let Some((_, param)) = values.iter_mut().find(|(n, _)| n == name) else {
+                return Err(Error::UnknownParam(name.to_string()));
+            };
+            *param = value;
+        }
+        self.state.params = upload_params(&self.state.client, &values)?;
+        self.metadata.params = values;
+        Ok(())
+    }
+
     fn run(&mut self, client: &mut Buffers<PjRtBuffer>) -> Result<(), Error> {
         let mut buffers = BufferArgsRef::default().untuple_result(true);
         for id in &self.metadata.arg_ids {
             buffers.push(&client[id]);
         }
+        for param in self.state.params.iter() {
+            buffers.push(param);
+        }
         let ret_bufs = self.state.exec.execute_buffers(buffers)?;
         for (buf, comp_id) in ret_bufs.into_iter().zip(self.metadata.ret_ids.iter()) {
             client.insert(*comp_id, buf);
@@ -407,6 +554,9 @@ pub struct WorldExec<S: ExecState = Uncompiled> {
     pub tick_exec: Exec<S>,
     pub startup_exec: Option<Exec<S>>,
     pub profiler: Profiler,
+    pub hooks: TickHooks,
+    pub clock: Clock,
+    step_remainder: Duration,
 }
 
 impl<S: ExecState> WorldExec<S> {
@@ -417,6 +567,9 @@ impl<S: ExecState> WorldExec<S> {
             tick_exec,
             startup_exec,
             profiler: Default::default(),
+            hooks: Default::default(),
+            clock: Default::default(),
+            step_remainder: Duration::ZERO,
         }
     }
 
@@ -424,6 +577,16 @@ impl<S: ExecState> WorldExec<S> {
         self.world.tick
     }
 
+    /// The simulation time at the current tick.
+    pub fn time(&self) -> Duration {
+        self.world.sim_time_step.0.mul_f64(self.world.tick as f64)
+    }
+
+    /// The calendar instant of the current tick, from the clock's epoch.
+    pub fn epoch(&self) -> Epoch {
+        self.clock.epoch_at(self.time())
+    }
+
     pub fn fork(&self) -> Self {
         Self {
             world: self.world.clone(),
@@ -431,6 +594,9 @@ impl<S: ExecState> WorldExec<S> {
             tick_exec: self.tick_exec.clone(),
             startup_exec: self.startup_exec.clone(),
             profiler: self.profiler.clone(),
+            hooks: self.hooks.clone(),
+            clock: self.clock.clone(),
+            step_remainder: self.step_remainder,
         }
     }
 
@@ -438,18 +604,8 @@ impl<S: ExecState> WorldExec<S> {
         &self,
         component_id: ComponentId,
         tick: u64,
-    ) -> Option<ColumnRef<'_, &Vec<u8>>> {
-        if tick == self.world.tick {
-            return self.world.column_by_id(component_id);
-        }
-        let column = self.world.history.get(tick as usize)?.get(&component_id)?;
-        let (archetype_name, metadata) = self.world.component_map.get(&component_id)?;
-        let entities = self.world.entity_ids.get(archetype_name)?;
-        Some(ColumnRef {
-            column,
-            entities,
-            metadata,
-        })
+    ) -> Option<ColumnRef<'_, Cow<'_, [u8]>>> {
+        self.world.column_at_tick(component_id, tick)
     }
 
     pub fn write_to_dir(&mut self, dir: impl AsRef<Path>) -> Result<(), Error> {
@@ -461,18 +617,41 @@ impl<S: ExecState> WorldExec<S> {
             startup_exec.write_to_dir(dir.join("startup_exec"))?;
         }
         self.world.write_to_dir(&world_dir)?;
+        write_checksums(dir)?;
         self.profiler.write_to_dir.observe(start);
         Ok(())
     }
 }
 
 impl WorldExec<Uncompiled> {
-    pub fn compile(mut self, client: Client) -> Result<WorldExec<Compiled>, Error> {
+    pub fn compile(self, client: Client) -> Result<WorldExec<Compiled>, Error> {
+        self.compile_with(client, None)
+    }
+
+    /// Like [`WorldExec::compile`], but reuses executables stored in `cache` by earlier runs.
+    pub fn compile_cached(
+        self,
+        client: Client,
+        cache: &CompileCache,
+    ) -> Result<WorldExec<Compiled>, Error> {
+        self.compile_with(client, Some(cache))
+    }
+
+    fn compile_with(
+        mut self,
+        client: Client,
+        cache: Option<&CompileCache>,
+    ) -> Result<WorldExec<Compiled>, Error> {
+        let _span = tracing::debug_span!("compile").entered();
         let start = &mut Instant::now();
-        let tick_exec = self.tick_exec.compile(client.clone())?;
+        let compile = |exec: Exec, client: Client| match cache {
+            Some(cache) => exec.compile_cached(client, cache),
+            None => exec.compile(client),
+        };
+        let tick_exec = compile(self.tick_exec, client.clone())?;
         let startup_exec = self
             .startup_exec
-            .map(|exec| exec.compile(client))
+            .map(|exec| compile(exec, client))
             .transpose()?;
         self.profiler.compile.observe(start);
         Ok(WorldExec {
@@ -481,11 +660,15 @@ impl WorldExec<Uncompiled> {
             tick_exec,
             startup_exec,
             profiler: self.profiler,
+            hooks: self.hooks,
+            clock: self.clock,
+            step_remainder: self.step_remainder,
         })
     }
 
     pub fn read_from_dir(dir: impl AsRef<Path>) -> Result<WorldExec, Error> {
         let dir = dir.as_ref();
+        verify_dir(dir)?;
         let world_dir = dir.join("world");
         let tick_exec = Exec::read_from_dir(dir.join("tick_exec"))?;
         let startup_exec_path = dir.join("startup_exec");
@@ -501,6 +684,9 @@ impl WorldExec<Uncompiled> {
             tick_exec,
             startup_exec,
             profiler: Default::default(),
+            hooks: Default::default(),
+            clock: Default::default(),
+            step_remainder: Duration::ZERO,
         };
         Ok(world_exec)
     }
@@ -508,23 +694,54 @@ impl WorldExec<Uncompiled> {
 
 impl WorldExec<Compiled> {
     pub fn run(&mut self) -> Result<(), Error> {
+        let _span = tracing::debug_span!("tick", tick = self.world.tick).entered();
+        self.hooks.run_pre_tick(&mut self.world)?;
         let start = &mut Instant::now();
-        self.copy_to_client()?;
+        tracing::debug_span!("copy_to_client").in_scope(|| self.copy_to_client())?;
         self.profiler.copy_to_client.observe(start);
         if let Some(mut startup_exec) = self.startup_exec.take() {
             startup_exec.run(&mut self.client_buffers)?;
             self.copy_to_host()?;
+            self.world
+                .written
+                .extend(startup_exec.metadata.ret_ids.iter().copied());
         }
         self.world.ensure_history();
-        self.tick_exec.run(&mut self.client_buffers)?;
+        tracing::debug_span!("execute_buffers")
+            .in_scope(|| self.tick_exec.run(&mut self.client_buffers))?;
         self.profiler.execute_buffers.observe(start);
-        self.copy_to_host()?;
+        tracing::debug_span!("copy_to_host").in_scope(|| self.copy_to_host())?;
+        // only the outputs can differ from what the host held before the tick
+        self.world
+            .written
+            .extend(self.tick_exec.metadata.ret_ids.iter().copied());
         self.profiler.copy_to_host.observe(start);
         self.world.advance_tick();
         self.profiler.add_to_history.observe(start);
+        self.hooks.run_post_tick(&mut self.world)?;
         Ok(())
     }
 
+    /// Advances the simulation by `dt`, running as many ticks as fit into it, and returns the number of ticks run.
+    ///
+    /// Time shorter than a single tick is carried over to the next call, so an external clock can drive
+    /// the simulation with arbitrary intervals without drifting. `dt` is scaled by the clock's time
+    /// scale, and nothing runs while the clock is paused.
+    pub fn step(&mut self, dt: Duration) -> Result<u64, Error> {
+        let time_step = self.world.sim_time_step.0;
+        if time_step.is_zero() {
+            return Err(Error::ZeroTimeStep);
+        }
+        self.step_remainder += self.clock.sim_duration(dt);
+        let mut ticks = 0;
+        while self.step_remainder >= time_step {
+            self.run()?;
+            self.step_remainder -= time_step;
+            ticks += 1;
+        }
+        Ok(ticks)
+    }
+
     fn copy_to_client(&mut self) -> Result<(), Error> {
         let client = &self.tick_exec.state.client;
         for id in std::mem::take(&mut self.world.dirty_components) {
@@ -550,6 +767,11 @@ impl WorldExec<Compiled> {
     pub fn profile(&self) -> HashMap<&'static str, f64> {
         self.profiler.profile(self.world.sim_time_step.0)
     }
+
+    /// Returns the profile of every system, see [`SystemProfile`].
+    pub fn system_profile(&self) -> BTreeMap<String, SystemProfile> {
+        self.profiler.system_profile()
+    }
 }
 
 impl<C: Component> ComponentArray<C> {
@@ -617,6 +839,39 @@ pub enum Error {
     Impeller(#[from] impeller::Error),
     #[error("channel closed")]
     ChannelClosed,
+    #[error("tick hook panicked")]
+    HookPoisoned,
+    #[error("sim time step must be non-zero")]
+    ZeroTimeStep,
+    #[error("no run with id {0}")]
+    RunNotFound(u64),
+    #[error("entity {0:?} not found")]
+    EntityNotFound(EntityId),
+    #[error("entity {0:?} is attached to itself through its parents")]
+    ParentCycle(EntityId),
+    #[error("co-simulation: {0}")]
+    CoSim(String),
+    #[error("invalid system rate: {0}")]
+    InvalidRate(String),
+    #[error("unknown time scale {0}")]
+    UnknownTimeScale(String),
+    #[error("time {0} is outside the recorded ticks")]
+    TimeOutOfRange(f64),
+    #[error("quaternion in {component:?} of {entity:?} drifted to norm {norm}")]
+    QuaternionDrift {
+        component: ComponentId,
+        entity: EntityId,
+        norm: f64,
+    },
+    #[error("no named constant {0}")]
+    UnknownParam(String),
+    #[error("no metric {0}")]
+    UnknownMetric(String),
+    #[error("{} is corrupt: {reason}", .path.display())]
+    Corrupt {
+        path: std::path::PathBuf,
+        reason: String,
+    },
     #[error("io {0}")]
     Io(#[from] std::io::Error),
     #[error("serde_json {0}")]
@@ -816,6 +1071,374 @@ mod tests {
         assert_eq!(c.typed_buf::<f64>().unwrap(), &[4.0]);
     }
 
+    #[test]
+    fn test_compile_cache() {
+        #[derive(Component, ReprMonad)]
+        struct A<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+        fn tick(a: ComponentArray<A>) -> ComponentArray<A> {
+            a.map(|a: A| A(a.0 + 1.0)).unwrap()
+        }
+
+        let tempdir = tempfile::tempdir().unwrap();
+        let cache = CompileCache::new(tempdir.path());
+        let client = nox::Client::cpu().unwrap();
+        for _ in 0..2 {
+            let mut world = World::default();
+            world.spawn(A(0.0.into()));
+            let mut exec = world
+                .builder()
+                .tick_pipeline(tick)
+                .build()
+                .unwrap()
+                .compile_cached(client.clone(), &cache)
+                .unwrap();
+            exec.run().unwrap();
+            let c = exec.world.column::<A>().unwrap();
+            assert_eq!(c.typed_buf::<f64>().unwrap(), &[1.0]);
+        }
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1 });
+
+        // an entry whose stored key doesn't match is recompiled rather than loaded
+        let entry = std::fs::read_dir(tempdir.path())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let mut bytes = std::fs::read(&entry).unwrap();
+        bytes[0] ^= 1;
+        std::fs::write(&entry, bytes).unwrap();
+        let mut world = World::default();
+        world.spawn(A(0.0.into()));
+        world
+            .builder()
+            .tick_pipeline(tick)
+            .build()
+            .unwrap()
+            .compile_cached(client.clone(), &cache)
+            .unwrap();
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 2 });
+
+        let mut unoptimized = client.clone();
+        unoptimized.disable_optimizations();
+        let mut world = World::default();
+        world.spawn(A(0.0.into()));
+        let exec = world.builder().tick_pipeline(tick).build().unwrap();
+        let module = exec.tick_exec.hlo_module();
+        assert_ne!(
+            CompileCache::key(&client, module).unwrap(),
+            CompileCache::key(&unoptimized, module).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hoisted_params() {
+        #[derive(Component, ReprMonad)]
+        struct A<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+        let tempdir = tempfile::tempdir().unwrap();
+        let cache = CompileCache::new(tempdir.path());
+        let client = nox::Client::cpu().unwrap();
+        let build = |gain: f64| {
+            let tick = move |a: ComponentArray<A>| -> ComponentArray<A> {
+                // the unnamed 1.0 stays a constant when the gain is retuned
+                a.map(move |a: A| A(a.0 * 1.0 + Scalar::named("gain", gain)))
+                    .unwrap()
+            };
+            let mut world = World::default();
+            world.spawn(A(0.0.into()));
+            world
+                .builder()
+                .tick_pipeline(tick)
+                .build()
+                .unwrap()
+                .compile_cached(client.clone(), &cache)
+                .unwrap()
+        };
+
+        let mut exec = build(1.0);
+        assert_eq!(exec.tick_exec.params(), &[("gain".to_string(), 1.0)]);
+        exec.run().unwrap();
+        // only the gain changed, so the executable is reused
+        let mut exec = build(2.5);
+        exec.run().unwrap();
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1 });
+        let c = exec.world.column::<A>().unwrap();
+        assert_eq!(c.typed_buf::<f64>().unwrap(), &[2.5]);
+
+        exec.tick_exec.set_params([("gain", -1.0)]).unwrap();
+        assert_eq!(exec.tick_exec.param("gain"), Some(-1.0));
+        exec.run().unwrap();
+        let c = exec.world.column::<A>().unwrap();
+        assert_eq!(c.typed_buf::<f64>().unwrap(), &[1.5]);
+        assert!(matches!(
+            exec.tick_exec.set_params([("offset", 1.0)]),
+            Err(Error::UnknownParam(_))
+        ));
+    }
+
+    #[test]
+    fn test_par_pipe() {
+        #[derive(Component, ReprMonad)]
+        struct A<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+        #[derive(Component, ReprMonad)]
+        struct B<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+        #[derive(Component, ReprMonad)]
+        struct C<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+        #[derive(Archetype)]
+        struct Body {
+            a: A,
+            b: B,
+            c: C,
+        }
+
+        fn add_one(a: Query<A>) -> Query<B> {
+            a.map(|a: A| B(a.0 + 1.0)).unwrap()
+        }
+
+        fn double(b: Query<B>) -> Query<C> {
+            b.map(|b: B| C(b.0 * 2.0)).unwrap()
+        }
+
+        fn increment(a: Query<A>) -> Query<A> {
+            a.map(|a: A| A(a.0 + 10.0)).unwrap()
+        }
+
+        // `double` still sees the B written by `add_one`, even though they are traced concurrently
+        let mut world = add_one.par_pipe(double).par_pipe(increment).world();
+        world.spawn(Body {
+            a: A(1.0.into()),
+            b: B(0.0.into()),
+            c: C(0.0.into()),
+        });
+        let world = world.run();
+        let c = world.column::<C>().unwrap();
+        assert_eq!(c.typed_buf::<f64>().unwrap(), &[4.0]);
+        let a = world.column::<A>().unwrap();
+        assert_eq!(a.typed_buf::<f64>().unwrap(), &[11.0]);
+    }
+
+    #[test]
+    fn test_step() {
+        #[derive(Component, ReprMonad)]
+        struct A<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+        fn tick(a: ComponentArray<A>) -> ComponentArray<A> {
+            a.map(|a: A| A(a.0 + 1.0)).unwrap()
+        }
+
+        let mut world = World::default();
+        world.spawn(A(0.0.into()));
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(tick)
+            .sim_time_step(Duration::from_millis(10))
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        assert_eq!(exec.step(Duration::from_millis(25)).unwrap(), 2);
+        assert_eq!(exec.step(Duration::from_millis(5)).unwrap(), 1);
+        assert_eq!(exec.step(Duration::from_millis(5)).unwrap(), 0);
+        assert_eq!(exec.tick(), 3);
+        let c = exec.world.column::<A>().unwrap();
+        assert_eq!(c.typed_buf::<f64>().unwrap(), &[3.0]);
+    }
+
+    #[test]
+    fn test_system_profile() {
+        #[derive(Component, ReprMonad)]
+        struct A<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+        fn double(a: ComponentArray<A>) -> ComponentArray<A> {
+            a.map(|a: A| A(a.0 * 2.0)).unwrap()
+        }
+
+        fn increment(a: ComponentArray<A>) -> ComponentArray<A> {
+            a.map(|a: A| A(a.0 + 1.0)).unwrap()
+        }
+
+        let mut world = World::default();
+        world.spawn(A(1.0.into()));
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(double.pipe(increment))
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        exec.run().unwrap();
+
+        let profile = exec.system_profile();
+        let names = profile.keys().map(String::as_str).collect::<Vec<_>>();
+        assert!(names.iter().any(|n| n.ends_with("::double")));
+        assert!(names.iter().any(|n| n.ends_with("::increment")));
+        assert!(names.iter().any(|n| n.ends_with("::increment_sim_tick")));
+        // the phases after tracing are shared by the fused executable
+        let profiles = profile.values().collect::<Vec<_>>();
+        assert!(profiles
+            .iter()
+            .all(|p| p.fused_execute == profiles[0].fused_execute && p.fused_execute > 0.0));
+        assert!(exec.profiler.to_string().contains("trace:"));
+    }
+
+    #[test]
+    fn test_tick_hooks() {
+        #[derive(Component, ReprMonad)]
+        struct A<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+        fn tick(a: ComponentArray<A>) -> ComponentArray<A> {
+            a.map(|a: A| A(a.0 + 1.0)).unwrap()
+        }
+
+        let mut world = World::default();
+        world.spawn(A(0.0.into()));
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(tick)
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        exec.hooks.add_pre_tick(|ctx| {
+            if ctx.tick == 2 {
+                let mut col = ctx.world.column_mut::<A>().unwrap();
+                col.typed_buf_mut::<f64>().unwrap()[0] = 10.0;
+            }
+            Ok(())
+        });
+        let ticks = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let post_ticks = ticks.clone();
+        exec.hooks.add_post_tick(move |ctx| {
+            post_ticks.lock().unwrap().push(ctx.tick);
+            Ok(())
+        });
+        for _ in 0..4 {
+            exec.run().unwrap();
+        }
+        assert_eq!(*ticks.lock().unwrap(), vec![1, 2, 3, 4]);
+        let c = exec.world.column::<A>().unwrap();
+        assert_eq!(c.typed_buf::<f64>().unwrap(), &[12.0]);
+    }
+
+    #[test]
+    fn test_changed_since() {
+        #[derive(Component, ReprMonad)]
+        struct A<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+        #[derive(Component, ReprMonad)]
+        struct B<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+        #[derive(Archetype)]
+        struct Body {
+            a: A,
+            b: B,
+        }
+
+        // B is written back every tick, but only changes when the hook sets it
+        fn tick(q: Query<(A, B)>) -> Query<(A, B)> {
+            q.map(|a: A, b: B| (A(a.0 + 1.0), b)).unwrap()
+        }
+
+        let mut world = World::default();
+        world.spawn(Body {
+            a: A(0.0.into()),
+            b: B(1.0.into()),
+        });
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(tick)
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        for _ in 0..2 {
+            exec.run().unwrap();
+        }
+        let b = exec.world.column::<B>().unwrap();
+        assert!(!b.changed_since(0));
+        assert!(exec.world.column::<A>().unwrap().changed_since(1));
+
+        exec.hooks.add_pre_tick(|ctx| {
+            if ctx.tick == 2 {
+                let mut col = ctx.world.column_mut::<B>().unwrap();
+                col.typed_buf_mut::<f64>().unwrap()[0] = 5.0;
+            }
+            Ok(())
+        });
+        for _ in 0..3 {
+            exec.run().unwrap();
+        }
+        let b = exec.world.column::<B>().unwrap();
+        assert_eq!(b.last_changed, 3);
+        assert!(b.changed_since(2));
+        assert!(!b.changed_since(3));
+        let a = exec.world.column::<A>().unwrap();
+        assert_eq!(a.last_changed, 5);
+        // an older column knows when it last changed as of its own tick
+        let b = exec.column_at_tick(B::COMPONENT_ID, 4).unwrap();
+        assert_eq!(b.last_changed, 3);
+        let b = exec.column_at_tick(B::COMPONENT_ID, 2).unwrap();
+        assert_eq!(b.last_changed, 0);
+        assert!(!b.changed_since(0));
+    }
+
+    #[test]
+    fn test_element_major_history() {
+        #[derive(Component, ReprMonad)]
+        struct V<R: OwnedRepr = Op>(Vector<f64, 3, R>);
+
+        #[derive(Archetype)]
+        #[nox(element_major)]
+        struct Swarm {
+            v: V,
+        }
+
+        fn tick(q: Query<V>) -> Query<V> {
+            q.map(|v: V| V(v.0 + 1.0)).unwrap()
+        }
+
+        let mut world = World::default();
+        world.spawn(Swarm {
+            v: V(tensor![1.0, 2.0, 3.0].into()),
+        });
+        world.spawn(Swarm {
+            v: V(tensor![4.0, 5.0, 6.0].into()),
+        });
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(tick)
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        exec.run().unwrap();
+        let recorded = &exec.world.history[0][&V::COMPONENT_ID];
+        assert_eq!(
+            bytemuck::pod_collect_to_vec::<u8, f64>(recorded),
+            [1.0, 4.0, 2.0, 5.0, 3.0, 6.0]
+        );
+        let v = exec.column_at_tick(V::COMPONENT_ID, 0).unwrap();
+        assert_eq!(
+            v.typed_buf::<f64>().unwrap(),
+            &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]
+        );
+        let v = exec.column_at_tick(V::COMPONENT_ID, 1).unwrap();
+        assert_eq!(
+            v.typed_buf::<f64>().unwrap(),
+            &[2.0, 3.0, 4.0, 5.0, 6.0, 7.0]
+        );
+    }
+
     #[test]
     fn test_convert_to_df() {
         let mut world = World::default();
@@ -899,4 +1522,65 @@ mod tests {
         let new_world = World::read_from_dir(dir).unwrap();
         assert_eq!(world, new_world);
     }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_write_read_compressed() {
+        use impeller::compress::Compression;
+
+        #[derive(Component, ReprMonad)]
+        struct A<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+        #[derive(Component, ReprMonad)]
+        struct B<R: OwnedRepr = Op>(Vector<f64, 3, R>);
+
+        #[derive(Archetype)]
+        struct Body {
+            a: A,
+            b: B,
+        }
+
+        fn drift(q: Query<(A, B)>) -> Query<(A, B)> {
+            q.map(|a: A, b: B| (A(a.0 + 0.1), B(b.0 * 1.01))).unwrap()
+        }
+
+        let mut world = World::default();
+        for i in 0..3 {
+            world.spawn(Body {
+                a: A((i as f64).into()),
+                b: B(tensor![1.0, -2.0, 1e6].into()),
+            });
+        }
+        let compress = |world: &mut World, id: ComponentId, compression| {
+            let (_, metadata) = world.component_map.get_mut(&id).unwrap();
+            metadata.set_compression(compression);
+        };
+        compress(&mut world, A::COMPONENT_ID, Compression::ZSTD.xor());
+        compress(&mut world, B::COMPONENT_ID, Compression::LZ4);
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(drift)
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        for _ in 0..10 {
+            exec.run().unwrap();
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        exec.world.write_to_dir(dir).unwrap();
+        let read = World::read_from_dir(dir).unwrap();
+        let mut history = read.history.clone();
+        history.push(read.host.clone());
+        assert_eq!(history.len(), exec.world.history.len());
+        for (read, written) in history.iter().zip(&exec.world.history) {
+            assert_eq!(read[&A::COMPONENT_ID], written[&A::COMPONENT_ID]);
+            assert_eq!(read[&B::COMPONENT_ID], written[&B::COMPONENT_ID]);
+        }
+        let metadata = &read.component_map[&A::COMPONENT_ID].1;
+        assert_eq!(metadata.compression(), Some(Compression::ZSTD.xor()));
+    }
 }
@@ -0,0 +1,257 @@
+//! A 3-DOF point mass: translation only, driven by a linear force and a scalar mass.
+//!
+//! This is the double integrator most examples start from. [`point_mass`] wires force clearing,
+//! the effectors, `a = f / m` and the chosen integrator, the same way [`crate::six_dof::six_dof`]
+//! does for rigid bodies.
+use core::ops::{Add, Mul};
+use nox::{Op, OwnedRepr, Scalar, Vector};
+use nox_ecs::{system::IntoSystem, system::System, Query};
+use nox_ecs::{Archetype, Component};
+use nox_ecs_macros::{ComponentGroup, FromBuilder, ReprMonad};
+use std::sync::Arc;
+
+use crate::{
+    semi_implicit_euler, semi_implicit_euler_with_dt, ComponentArray, Dopri5Ext, ErasedSystem,
+    Integrator, Rk4Ext,
+};
+
+#[derive(Clone, Component, ReprMonad)]
+pub struct Position<R: OwnedRepr = Op>(pub Vector<f64, 3, R>);
+#[derive(Clone, Component, ReprMonad)]
+pub struct Velocity<R: OwnedRepr = Op>(pub Vector<f64, 3, R>);
+#[derive(Clone, Component, ReprMonad)]
+pub struct Acceleration<R: OwnedRepr = Op>(pub Vector<f64, 3, R>);
+#[derive(Clone, Component, ReprMonad)]
+pub struct LinearForce<R: OwnedRepr = Op>(pub Vector<f64, 3, R>);
+#[derive(Clone, Component, ReprMonad)]
+pub struct Mass<R: OwnedRepr = Op>(pub Scalar<f64, R>);
+
+#[derive(FromBuilder, ComponentGroup)]
+struct U {
+    x: Position,
+    v: Velocity,
+}
+
+#[derive(FromBuilder, ComponentGroup)]
+struct DU {
+    v: Velocity,
+    a: Acceleration,
+}
+
+impl Add<DU> for U {
+    type Output = U;
+
+    fn add(self, v: DU) -> Self::Output {
+        U {
+            x: Position(self.x.0 + v.v.0),
+            v: Velocity(self.v.0 + v.a.0),
+        }
+    }
+}
+
+impl Add for DU {
+    type Output = DU;
+
+    fn add(self, v: DU) -> Self::Output {
+        DU {
+            v: Velocity(self.v.0 + v.v.0),
+            a: Acceleration(self.a.0 + v.a.0),
+        }
+    }
+}
+
+impl Mul<DU> for Scalar<f64> {
+    type Output = DU;
+
+    fn mul(self, rhs: DU) -> Self::Output {
+        DU {
+            v: Velocity(&self * rhs.v.0),
+            a: Acceleration(&self * rhs.a.0),
+        }
+    }
+}
+
+impl Mul<DU> for f64 {
+    type Output = DU;
+
+    fn mul(self, rhs: DU) -> Self::Output {
+        DU {
+            v: Velocity(self * rhs.v.0),
+            a: Acceleration(self * rhs.a.0),
+        }
+    }
+}
+
+impl Add<Velocity> for Position {
+    type Output = Position;
+
+    fn add(self, v: Velocity) -> Self::Output {
+        Position(self.0 + v.0)
+    }
+}
+
+impl Add<Acceleration> for Velocity {
+    type Output = Velocity;
+
+    fn add(self, a: Acceleration) -> Self::Output {
+        Velocity(self.0 + a.0)
+    }
+}
+
+impl Mul<Velocity> for f64 {
+    type Output = Velocity;
+
+    fn mul(self, rhs: Velocity) -> Self::Output {
+        Velocity(self * rhs.0)
+    }
+}
+
+impl Mul<Velocity> for Scalar<f64> {
+    type Output = Velocity;
+
+    fn mul(self, rhs: Velocity) -> Self::Output {
+        Velocity(&self * rhs.0)
+    }
+}
+
+impl Mul<Acceleration> for f64 {
+    type Output = Acceleration;
+
+    fn mul(self, rhs: Acceleration) -> Self::Output {
+        Acceleration(self * rhs.0)
+    }
+}
+
+impl Mul<Acceleration> for Scalar<f64> {
+    type Output = Acceleration;
+
+    fn mul(self, rhs: Acceleration) -> Self::Output {
+        Acceleration(&self * rhs.0)
+    }
+}
+
+fn calc_accel(q: Query<(LinearForce, Mass)>) -> Query<Acceleration> {
+    q.map(|force: LinearForce, mass: Mass| Acceleration(force.0 / mass.0))
+        .unwrap()
+}
+
+fn clear_forces(q: ComponentArray<LinearForce>) -> ComponentArray<LinearForce> {
+    q.map(|_| LinearForce(Vector::zeros())).unwrap()
+}
+
+#[derive(Archetype)]
+pub struct PointMass {
+    pub pos: Position,
+    pub vel: Velocity,
+    pub accel: Acceleration,
+    pub force: LinearForce,
+    pub mass: Mass,
+}
+
+impl PointMass {
+    /// A point mass at `pos` moving with `vel`, with no force applied yet.
+    pub fn new(pos: impl Into<Vector<f64, 3>>, vel: impl Into<Vector<f64, 3>>, mass: f64) -> Self {
+        PointMass {
+            pos: Position(pos.into()),
+            vel: Velocity(vel.into()),
+            accel: Acceleration(Vector::zeros()),
+            force: LinearForce(Vector::zeros()),
+            mass: Mass(mass.into()),
+        }
+    }
+}
+
+pub fn point_mass_with_dt<Sys, M, A, R>(
+    effectors: impl FnOnce() -> Sys,
+    time_step: f64,
+    integrator: Integrator,
+) -> Arc<dyn System<Arg = (), Ret = ()> + Send + Sync>
+where
+    M: 'static,
+    A: 'static,
+    R: 'static,
+    Sys: IntoSystem<M, A, R> + 'static,
+    <Sys as IntoSystem<M, A, R>>::System: Send + Sync,
+{
+    let sys = clear_forces.pipe(effectors()).pipe(calc_accel);
+    match integrator {
+        Integrator::Rk4 => Arc::new(sys.rk4_with_dt::<U, DU>(time_step)),
+        Integrator::SemiImplicit => {
+            let integrate =
+                semi_implicit_euler_with_dt::<Position, Velocity, Acceleration>(time_step);
+            Arc::new(ErasedSystem::new(sys.pipe(integrate)))
+        }
+        Integrator::Dopri5(solver) => Arc::new(sys.dopri5_with_dt::<U, DU>(time_step, solver)),
+    }
+}
+
+pub fn point_mass<Sys, M, A, R>(
+    effectors: impl FnOnce() -> Sys,
+    integrator: Integrator,
+) -> Arc<dyn System<Arg = (), Ret = ()> + Send + Sync>
+where
+    M: 'static,
+    A: 'static,
+    R: 'static,
+    Sys: IntoSystem<M, A, R> + 'static,
+    <Sys as IntoSystem<M, A, R>>::System: Send + Sync,
+{
+    let sys = clear_forces.pipe(effectors()).pipe(calc_accel);
+    match integrator {
+        Integrator::Rk4 => Arc::new(sys.rk4::<U, DU>()),
+        Integrator::SemiImplicit => {
+            let integrate = semi_implicit_euler::<Position, Velocity, Acceleration>();
+            Arc::new(ErasedSystem::new(sys.pipe(integrate)))
+        }
+        Integrator::Dopri5(solver) => Arc::new(sys.dopri5::<U, DU>(solver)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{World, WorldExt};
+    use approx::assert_relative_eq;
+    use impeller::ComponentExt;
+    use nox::tensor;
+
+    #[test]
+    fn test_point_mass_constant_force() {
+        let gravity = |q: Query<(LinearForce, Mass)>| -> Query<LinearForce> {
+            q.map(|force: LinearForce, mass: Mass| {
+                let g: Vector<f64, 3> = tensor![0.0, 0.0, -9.81].into();
+                LinearForce(force.0 + mass.0 * g)
+            })
+            .unwrap()
+        };
+
+        for integrator in [Integrator::Rk4, Integrator::SemiImplicit] {
+            let mut world = World::default();
+            world.spawn(PointMass::new(
+                tensor![0.0, 0.0, 0.0],
+                tensor![1.0, 0.0, 0.0],
+                2.0,
+            ));
+            let client = nox::Client::cpu().unwrap();
+            let mut exec = world
+                .builder()
+                .tick_pipeline(point_mass(|| gravity, integrator))
+                .sim_time_step(std::time::Duration::from_secs_f64(0.01))
+                .build()
+                .unwrap()
+                .compile(client)
+                .unwrap();
+            for _ in 0..100 {
+                exec.run().unwrap();
+            }
+            let vel = exec.column_at_tick(Velocity::COMPONENT_ID, 100).unwrap();
+            let vel = vel.typed_buf::<f64>().unwrap();
+            assert_relative_eq!(vel[0], 1.0, epsilon = 1e-9);
+            assert_relative_eq!(vel[2], -9.81, epsilon = 1e-9);
+            let pos = exec.column_at_tick(Position::COMPONENT_ID, 100).unwrap();
+            let pos = pos.typed_buf::<f64>().unwrap();
+            assert_relative_eq!(pos[0], 1.0, epsilon = 1e-9);
+            assert_relative_eq!(pos[2], -9.81 / 2.0, epsilon = 0.1);
+        }
+    }
+}
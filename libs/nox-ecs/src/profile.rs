@@ -1,9 +1,11 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt,
     time::{Duration, Instant},
 };
 
+use crate::SystemTrace;
+
 #[derive(Default, Clone, Debug)]
 pub struct Profiler {
     pub build: RollingMean,
@@ -13,6 +15,37 @@ pub struct Profiler {
     pub execute_buffers: RollingMean,
     pub copy_to_host: RollingMean,
     pub add_to_history: RollingMean,
+    /// The time spent tracing each system, keyed by system name.
+    ///
+    /// The whole pipeline is compiled into a single executable, so tracing is the only stage
+    /// that can be attributed to an individual system.
+    pub systems: BTreeMap<String, RollingMean>,
+}
+
+/// The mean time, in milliseconds, of each phase of a system, see [`Profiler::system_profile`].
+///
+/// Only tracing is measured per system. The systems are fused into one executable, so the
+/// `fused_*` phases are those of the whole executable, and every system in it reports the same
+/// values for them.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SystemProfile {
+    pub trace: f64,
+    pub fused_compile: f64,
+    pub fused_execute: f64,
+    /// Copying inputs to the device and outputs back to the host.
+    pub fused_transfer: f64,
+}
+
+impl SystemProfile {
+    /// The phases keyed by name, e.g. for handing them to Python.
+    pub fn phases(&self) -> BTreeMap<&'static str, f64> {
+        BTreeMap::from([
+            ("trace", self.trace),
+            ("fused_compile", self.fused_compile),
+            ("fused_execute", self.fused_execute),
+            ("fused_transfer", self.fused_transfer),
+        ])
+    }
 }
 
 impl Profiler {
@@ -23,6 +56,32 @@ impl Profiler {
             + self.add_to_history.mean()
     }
 
+    pub fn observe_systems(&mut self, systems: &[SystemTrace]) {
+        for system in systems {
+            self.systems
+                .entry(system.name.clone())
+                .or_default()
+                .add_sample(system.trace_time);
+        }
+    }
+
+    /// Returns the profile of every system, keyed by system name.
+    pub fn system_profile(&self) -> BTreeMap<String, SystemProfile> {
+        let fused_transfer = self.copy_to_client.mean() + self.copy_to_host.mean();
+        self.systems
+            .iter()
+            .map(|(name, mean)| {
+                let profile = SystemProfile {
+                    trace: mean.mean(),
+                    fused_compile: self.compile.mean(),
+                    fused_execute: self.execute_buffers.mean(),
+                    fused_transfer,
+                };
+                (name.clone(), profile)
+            })
+            .collect()
+    }
+
     pub fn profile(&self, time_step: Duration) -> HashMap<&'static str, f64> {
         let tick_mean = self.tick_mean();
         let time_step = time_step.as_secs_f64() * 1000.0;
@@ -42,6 +101,29 @@ impl Profiler {
     }
 }
 
+impl fmt::Display for Profiler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let stages = [
+            ("build", &self.build),
+            ("compile", &self.compile),
+            ("copy_to_client", &self.copy_to_client),
+            ("execute_buffers", &self.execute_buffers),
+            ("copy_to_host", &self.copy_to_host),
+            ("add_to_history", &self.add_to_history),
+        ];
+        for (name, mean) in stages {
+            writeln!(f, "{:<16} {}", name, mean)?;
+        }
+        if !self.systems.is_empty() {
+            writeln!(f, "trace:")?;
+        }
+        for (name, mean) in &self.systems {
+            writeln!(f, "  {} {}", name, mean)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Default, Clone, Debug)]
 pub struct RollingMean {
     sum: Duration,
@@ -50,10 +132,13 @@ pub struct RollingMean {
 
 impl RollingMean {
     pub fn observe(&mut self, start: &mut Instant) {
-        let sample = start.elapsed();
+        self.add_sample(start.elapsed());
+        *start = Instant::now();
+    }
+
+    pub fn add_sample(&mut self, sample: Duration) {
         self.sum += sample;
         self.count += 1;
-        *start = Instant::now();
     }
 
     pub fn mean(&self) -> f64 {
@@ -371,7 +371,7 @@ impl<A: Component> ComponentArray<A> {
     }
 }
 
-fn filter_index(indexes: &[u32], buffer: &Noxpr) -> Noxpr {
+pub(crate) fn filter_index(indexes: &[u32], buffer: &Noxpr) -> Noxpr {
     let n = indexes.len();
     let indexes_lit = xla::Literal::vector(indexes);
     let indexes = Noxpr::constant(
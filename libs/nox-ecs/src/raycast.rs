@@ -0,0 +1,372 @@
+//! Ray casts and line-of-sight queries against the shapes entities are drawn with, for host code
+//! between ticks: visibility between satellites and ground stations, altimeters and lidar.
+//!
+//! Every entity with a `WorldPos` and a mesh is a target. Spheres, boxes and cylinders are hit
+//! exactly, mesh data triangle by triangle. Inside a compiled system, use the tensor queries in
+//! `nox` such as [`nox::segment_sphere_clearance`] against analytic colliders instead.
+//!
+//! [`raycast`] and [`line_of_sight`] gather the shapes for a single query. Code casting many rays,
+//! tick after tick, should keep a [`RayScene`] and refresh it instead, which only refits the
+//! bounds of the entities that moved.
+use std::collections::HashMap;
+
+use impeller::well_known::{Mesh, MeshInner};
+use impeller::{EntityId, Handle};
+use nox::{ArrayRepr, SpatialTransform, Tensor, Vector3};
+
+use crate::{Aabb, Bvh, World, WorldPos};
+
+type Pose = SpatialTransform<f64, ArrayRepr>;
+
+/// The closest shape a ray hit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    pub entity: EntityId,
+    /// The distance along the ray to the hit, in multiples of the ray's direction.
+    pub toi: f64,
+    pub point: [f64; 3],
+}
+
+/// The shapes of a world's entities, and a [`Bvh`] over them, kept between ray casts.
+pub struct RayScene {
+    targets: HashMap<EntityId, (Pose, Mesh)>,
+    bvh: Bvh,
+}
+
+impl RayScene {
+    pub fn new(world: &World) -> Self {
+        let mut scene = Self {
+            targets: HashMap::new(),
+            bvh: Bvh::default(),
+        };
+        scene.refresh(world);
+        scene
+    }
+
+    /// Catches up with `world`, refitting the bounds of the entities that moved and dropping the
+    /// ones that lost their shape. Call it after ticking the world.
+    pub fn refresh(&mut self, world: &World) {
+        let targets = targets(world);
+        for entity in self.targets.keys() {
+            if !targets.contains_key(entity) {
+                self.bvh.remove(*entity);
+            }
+        }
+        for (entity, (pos, mesh)) in &targets {
+            let center = pos.linear().parts().map(Tensor::into_buf);
+            self.bvh
+                .update(*entity, Aabb::from_sphere(center, bounding_radius(mesh)));
+        }
+        self.bvh.maintain();
+        self.targets = targets;
+    }
+
+    /// Casts a ray from `origin` along `dir` against the shapes of every entity not in `ignore`,
+    /// returning the closest hit no further than `max_toi`.
+    ///
+    /// A ray starting inside a box hits it at its origin; spheres and cylinders are hit where the
+    /// ray leaves them instead.
+    pub fn raycast(
+        &self,
+        origin: [f64; 3],
+        dir: [f64; 3],
+        max_toi: f64,
+        ignore: &[EntityId],
+    ) -> Option<RayHit> {
+        let (entity, toi) = self.bvh.ray_cast(origin, dir, max_toi, |entity| {
+            if ignore.contains(&entity) {
+                return None;
+            }
+            let (pos, mesh) = self.targets.get(&entity)?;
+            // shapes are drawn in the viewer's y-up frame, so the ray is taken there too
+            let inv = pos.angular().inverse();
+            let local_origin = to_y_up(&inv * (vec3(origin) - pos.linear()));
+            let local_dir = to_y_up(&inv * vec3(dir));
+            shape_toi(mesh, local_origin, local_dir)
+        })?;
+        let point = [0, 1, 2].map(|i| origin[i] + toi * dir[i]);
+        Some(RayHit { entity, toi, point })
+    }
+
+    /// Whether nothing blocks the straight line between the positions `world` holds for `from`
+    /// and `to`.
+    ///
+    /// The shapes of `from` and `to` themselves are ignored, so a ground station drawn as a dome
+    /// doesn't hide its own satellites.
+    pub fn line_of_sight(&self, world: &World, from: EntityId, to: EntityId) -> bool {
+        let Some(col) = world.column::<WorldPos>() else {
+            return false;
+        };
+        let mut poses = col
+            .typed_iter::<Pose>()
+            .filter(|(id, _)| *id == from || *id == to);
+        let (Some(a), Some(b)) = (poses.next(), poses.next()) else {
+            return false;
+        };
+        let (a, b) = if a.0 == from { (a.1, b.1) } else { (b.1, a.1) };
+        let origin = a.linear().parts().map(Tensor::into_buf);
+        let dir = (b.linear() - a.linear()).parts().map(Tensor::into_buf);
+        self.raycast(origin, dir, 1.0, &[from, to]).is_none()
+    }
+}
+
+/// Casts a single ray against `world`, see [`RayScene::raycast`].
+pub fn raycast(
+    world: &World,
+    origin: [f64; 3],
+    dir: [f64; 3],
+    max_toi: f64,
+    ignore: &[EntityId],
+) -> Option<RayHit> {
+    RayScene::new(world).raycast(origin, dir, max_toi, ignore)
+}
+
+/// Checks a single line of sight in `world`, see [`RayScene::line_of_sight`].
+pub fn line_of_sight(world: &World, from: EntityId, to: EntityId) -> bool {
+    RayScene::new(world).line_of_sight(world, from, to)
+}
+
+fn targets(world: &World) -> HashMap<EntityId, (Pose, Mesh)> {
+    let (Some(pos), Some(meshes)) = (world.column::<WorldPos>(), world.column::<Handle<Mesh>>())
+    else {
+        return HashMap::new();
+    };
+    let meshes = meshes
+        .entity_ids()
+        .zip(meshes.column.chunks_exact(8))
+        .filter_map(|(id, bytes)| {
+            let handle = u64::from_le_bytes(bytes.try_into().ok()?);
+            Some((id, world.assets.get(Handle::<Mesh>::new(handle))?))
+        })
+        .collect::<HashMap<_, _>>();
+    pos.typed_iter::<Pose>()
+        .filter_map(|(id, pos)| Some((id, (pos, meshes.get(&id)?.clone()))))
+        .collect()
+}
+
+fn vec3([x, y, z]: [f64; 3]) -> Vector3<f64, ArrayRepr> {
+    Vector3::new(x, y, z)
+}
+
+fn to_y_up(v: Vector3<f64, ArrayRepr>) -> [f64; 3] {
+    let [x, y, z] = v.parts().map(Tensor::into_buf);
+    [x, z, -y]
+}
+
+fn bounding_radius(mesh: &Mesh) -> f64 {
+    match &mesh.inner {
+        MeshInner::Sphere { radius, .. } => *radius as f64,
+        MeshInner::Box { x, y, z } => norm([*x, *y, *z].map(|s| s as f64 / 2.0)),
+        MeshInner::Cylinder { radius, height, .. } => (*radius as f64).hypot(*height as f64 / 2.0),
+        MeshInner::Data(data) => data
+            .positions
+            .iter()
+            .flatten()
+            .map(|p| norm(p.map(f64::from)))
+            .fold(0.0, f64::max),
+    }
+}
+
+/// The distance along `dir` at which a ray in the mesh's own frame hits it.
+fn shape_toi(mesh: &Mesh, origin: [f64; 3], dir: [f64; 3]) -> Option<f64> {
+    match &mesh.inner {
+        MeshInner::Sphere { radius, .. } => sphere_toi(origin, dir, *radius as f64),
+        MeshInner::Box { x, y, z } => {
+            let half = [*x, *y, *z].map(|s| s as f64 / 2.0);
+            Aabb::new(half.map(|h| -h), half).ray_toi(origin, dir, f64::INFINITY)
+        }
+        MeshInner::Cylinder { radius, height, .. } => {
+            cylinder_toi(origin, dir, *radius as f64, *height as f64 / 2.0)
+        }
+        MeshInner::Data(data) => {
+            let positions = data.positions.as_ref()?;
+            let indices = match &data.indices {
+                Some(indices) => indices.iter().map(|i| *i as usize).collect(),
+                None => (0..positions.len()).collect::<Vec<_>>(),
+            };
+            // only triangle lists can be hit, other topologies have no surface
+            if data.mesh_type != 3 {
+                return None;
+            }
+            indices
+                .chunks_exact(3)
+                .filter_map(|tri| {
+                    let [a, b, c] = [tri[0], tri[1], tri[2]]
+                        .map(|i| positions.get(i).map(|p| p.map(f64::from)));
+                    triangle_toi(origin, dir, [a?, b?, c?])
+                })
+                .min_by(f64::total_cmp)
+        }
+    }
+}
+
+fn sphere_toi(origin: [f64; 3], dir: [f64; 3], radius: f64) -> Option<f64> {
+    let a = dot(dir, dir);
+    let b = dot(origin, dir);
+    let c = dot(origin, origin) - radius * radius;
+    let disc = b * b - a * c;
+    if disc < 0.0 {
+        return None;
+    }
+    let sqrt = disc.sqrt();
+    [(-b - sqrt) / a, (-b + sqrt) / a]
+        .into_iter()
+        .find(|t| *t >= 0.0)
+}
+
+/// Hits a cylinder along the y axis, from `-half_height` to `half_height`.
+fn cylinder_toi(origin: [f64; 3], dir: [f64; 3], radius: f64, half_height: f64) -> Option<f64> {
+    let in_height = |t: f64| (origin[1] + t * dir[1]).abs() <= half_height;
+    let a = dir[0] * dir[0] + dir[2] * dir[2];
+    let b = origin[0] * dir[0] + origin[2] * dir[2];
+    let c = origin[0] * origin[0] + origin[2] * origin[2] - radius * radius;
+    let mut hits = vec![];
+    let disc = b * b - a * c;
+    if a > 0.0 && disc >= 0.0 {
+        let sqrt = disc.sqrt();
+        hits.extend(
+            [(-b - sqrt) / a, (-b + sqrt) / a]
+                .into_iter()
+                .filter(|t| in_height(*t)),
+        );
+    }
+    if dir[1] != 0.0 {
+        for cap in [-half_height, half_height] {
+            let t = (cap - origin[1]) / dir[1];
+            let [x, _, z] = [0, 1, 2].map(|i| origin[i] + t * dir[i]);
+            if x * x + z * z <= radius * radius {
+                hits.push(t);
+            }
+        }
+    }
+    hits.into_iter()
+        .filter(|t| *t >= 0.0)
+        .min_by(f64::total_cmp)
+}
+
+/// Möller–Trumbore ray-triangle intersection, hitting both faces.
+fn triangle_toi(origin: [f64; 3], dir: [f64; 3], [a, b, c]: [[f64; 3]; 3]) -> Option<f64> {
+    let ab = sub(b, a);
+    let ac = sub(c, a);
+    let p = cross(dir, ac);
+    let det = dot(ab, p);
+    if det.abs() < f64::EPSILON {
+        return None;
+    }
+    let ao = sub(origin, a);
+    let u = dot(ao, p) / det;
+    let q = cross(ao, ab);
+    let v = dot(dir, q) / det;
+    if u < 0.0 || v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    Some(dot(ac, q) / det).filter(|t| *t >= 0.0)
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn norm(a: [f64; 3]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use impeller::well_known::Material;
+    use nox::tensor;
+
+    fn at([x, y, z]: [f64; 3]) -> WorldPos {
+        WorldPos(nox::SpatialTransform {
+            inner: tensor![0.0, 0.0, 0.0, 1.0, x, y, z].into(),
+        })
+    }
+
+    fn spawn_shape(world: &mut World, mesh: Mesh, pos: [f64; 3]) -> EntityId {
+        let shape = world.insert_shape(mesh, Material::color(1.0, 1.0, 1.0));
+        world.spawn(at(pos)).insert(shape).id()
+    }
+
+    #[test]
+    fn test_raycast_shapes() {
+        let mut world = World::default();
+        let ball = spawn_shape(&mut world, Mesh::sphere(1.0, 8, 8), [10.0, 0.0, 0.0]);
+        // a long box lying along the sim's y axis, which is the viewer's -z axis
+        let wall = spawn_shape(&mut world, Mesh::cuboid(1.0, 1.0, 20.0), [5.0, 0.0, 0.0]);
+
+        let hit = raycast(&world, [0.0; 3], [1.0, 0.0, 0.0], f64::INFINITY, &[]).unwrap();
+        assert_eq!(hit.entity, wall);
+        assert!((hit.toi - 4.5).abs() < 1e-9);
+
+        let hit = raycast(&world, [0.0; 3], [1.0, 0.0, 0.0], f64::INFINITY, &[wall]).unwrap();
+        assert_eq!(hit.entity, ball);
+        assert!((hit.point[0] - 9.0).abs() < 1e-9);
+
+        let hit = raycast(
+            &world,
+            [5.0, 15.0, 0.0],
+            [0.0, -1.0, 0.0],
+            f64::INFINITY,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(hit.entity, wall);
+        assert!((hit.toi - 5.0).abs() < 1e-9);
+        assert!(raycast(&world, [5.0, 0.0, 9.0], [0.0, 0.0, -1.0], 1.0, &[]).is_none());
+        assert!(raycast(&world, [0.0; 3], [0.0, 0.0, 1.0], f64::INFINITY, &[]).is_none());
+    }
+
+    #[test]
+    fn test_ray_scene_refresh() {
+        let mut world = World::default();
+        let ball = spawn_shape(&mut world, Mesh::sphere(1.0, 8, 8), [10.0, 0.0, 0.0]);
+        let mut scene = RayScene::new(&world);
+        let hit = scene.raycast([0.0; 3], [1.0, 0.0, 0.0], f64::INFINITY, &[]);
+        assert_eq!(hit.map(|hit| hit.entity), Some(ball));
+
+        // move the ball off the ray
+        let moved = [0.0, 0.0, 0.0, 1.0, 10.0, 5.0, 0.0f64];
+        let col = world.column_mut::<WorldPos>().unwrap();
+        col.column.copy_from_slice(
+            &moved
+                .iter()
+                .flat_map(|x| x.to_le_bytes())
+                .collect::<Vec<_>>(),
+        );
+        assert!(scene
+            .raycast([0.0; 3], [1.0, 0.0, 0.0], f64::INFINITY, &[])
+            .is_some());
+        scene.refresh(&world);
+        assert!(scene
+            .raycast([0.0; 3], [1.0, 0.0, 0.0], f64::INFINITY, &[])
+            .is_none());
+        let hit = scene
+            .raycast([10.0, 0.0, 0.0], [0.0, 1.0, 0.0], f64::INFINITY, &[])
+            .unwrap();
+        assert!((hit.point[1] - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let mut world = World::default();
+        spawn_shape(&mut world, Mesh::sphere(6378e3, 32, 32), [0.0; 3]);
+        let station = world.spawn(at([6378.1e3, 0.0, 0.0])).id();
+        let overhead = world.spawn(at([7000e3, 0.0, 0.0])).id();
+        let behind = world.spawn(at([-7000e3, 0.0, 0.0])).id();
+        assert!(line_of_sight(&world, station, overhead));
+        assert!(!line_of_sight(&world, station, behind));
+    }
+}
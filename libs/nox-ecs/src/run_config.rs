@@ -0,0 +1,136 @@
+//! Records everything needed to reproduce a single run, so an outlier in a Monte Carlo batch can be re-run on its own.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use impeller::const_fnv1a_hash::fnv1a_hash_64;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// The file a [`RunConfig`] is stored in, inside a run's output directory.
+pub const RUN_CONFIG_FILE: &str = "sample.json";
+
+/// The metadata of one run of a batch, written next to its output.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunConfig {
+    pub run_id: u64,
+    /// The seed shared by every run of the batch, see [`RunConfig::sample_seed`].
+    pub seed: u64,
+    /// The simulation time step, in seconds.
+    pub time_step: f64,
+    pub integrator: Option<String>,
+    pub git_hash: Option<String>,
+    /// The values drawn for this run's Monte Carlo parameters, by name.
+    #[serde(default)]
+    pub draws: BTreeMap<String, f64>,
+    /// The stop condition that ended the run before its last tick, if any.
+    #[serde(default)]
+    pub stop_reason: Option<String>,
+    /// The [`Metric`](crate::Metric)s of the run's final state, by name.
+    #[serde(default)]
+    pub metrics: BTreeMap<String, f64>,
+}
+
+impl RunConfig {
+    pub fn new(run_id: u64, seed: u64, time_step: f64) -> Self {
+        Self {
+            run_id,
+            seed,
+            time_step,
+            ..Default::default()
+        }
+    }
+
+    pub fn integrator(mut self, integrator: impl Into<String>) -> Self {
+        self.integrator = Some(integrator.into());
+        self
+    }
+
+    /// Records the commit checked out in the current directory, if it is inside a git repository.
+    pub fn capture_git_hash(mut self) -> Self {
+        self.git_hash = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .and_then(|out| String::from_utf8(out.stdout).ok())
+            .map(|hash| hash.trim().to_string());
+        self
+    }
+
+    pub fn draw(&mut self, name: impl Into<String>, value: f64) {
+        self.draws.insert(name.into(), value);
+    }
+
+    /// The seed this run's world is seeded with, derived from the batch seed and the run id so every run
+    /// gets a different but reproducible stream of random numbers.
+    pub fn sample_seed(&self) -> u64 {
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&self.seed.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.run_id.to_le_bytes());
+        fnv1a_hash_64(&bytes, None)
+    }
+
+    pub fn write_to_dir(&self, dir: impl AsRef<Path>) -> Result<(), Error> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let file = std::fs::File::create(dir.join(RUN_CONFIG_FILE))?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    pub fn read_from_dir(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = std::fs::File::open(dir.as_ref().join(RUN_CONFIG_FILE))?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Finds the run with `run_id` among the run directories directly inside `batch_dir`, returning its
+    /// config and output directory.
+    pub fn find(batch_dir: impl AsRef<Path>, run_id: u64) -> Result<(Self, PathBuf), Error> {
+        for entry in std::fs::read_dir(batch_dir)? {
+            let dir = entry?.path();
+            if !dir.join(RUN_CONFIG_FILE).exists() {
+                continue;
+            }
+            let config = Self::read_from_dir(&dir)?;
+            if config.run_id == run_id {
+                return Ok((config, dir));
+            }
+        }
+        Err(Error::RunNotFound(run_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut configs = vec![];
+        for run_id in 0..3 {
+            let mut config = RunConfig::new(run_id, 7, 0.01).integrator("rk4");
+            config.draw("mass", 10.0 + run_id as f64);
+            config
+                .write_to_dir(dir.path().join(run_id.to_string()))
+                .unwrap();
+            configs.push(config);
+        }
+        assert_ne!(configs[0].sample_seed(), configs[1].sample_seed());
+        assert_eq!(
+            configs[1].sample_seed(),
+            RunConfig::new(1, 7, 0.01).sample_seed()
+        );
+
+        let (config, run_dir) = RunConfig::find(dir.path(), 2).unwrap();
+        assert_eq!(config, configs[2]);
+        assert_eq!(config.draws["mass"], 12.0);
+        assert!(run_dir.ends_with("2"));
+        assert!(matches!(
+            RunConfig::find(dir.path(), 3),
+            Err(Error::RunNotFound(3))
+        ));
+    }
+}
@@ -0,0 +1,153 @@
+//! A throwaway world with a single rigid body, for unit testing force models and controllers
+//! without setting up a full simulation.
+use std::time::Duration;
+
+use impeller::{Archetype, Component, ComponentExt, ComponentId, EntityId};
+use nox::{SpatialInertia, SpatialMotion, SpatialTransform};
+
+use crate::dense_output::entity_value;
+use crate::six_dof::{six_dof, Attitude, Body};
+use crate::system::IntoSystem;
+use crate::{Error, Integrator, TimeStep, World, WorldExt};
+
+/// Builds a one-entity world inline and steps it, like
+/// `Scratch::at_rest(SpatialInertia::from_mass(1.0)).run(|| gravity, 100)`.
+pub struct Scratch {
+    world: World,
+    entity: EntityId,
+    integrator: Integrator,
+}
+
+impl Scratch {
+    pub fn new(body: Body) -> Self {
+        let mut world = World::default();
+        let entity = world.spawn(body).id();
+        Self {
+            world,
+            entity,
+            integrator: Integrator::Rk4,
+        }
+    }
+
+    /// A body at the origin, not moving.
+    pub fn at_rest(inertia: SpatialInertia<f64>) -> Self {
+        Self::new(Body::new(
+            SpatialTransform::zero(),
+            SpatialMotion::zero(),
+            inertia,
+        ))
+    }
+
+    /// Adds components the effectors under test read or write, like a commanded torque.
+    pub fn with(mut self, archetype: impl Archetype + 'static) -> Self {
+        self.world.insert_with_id(archetype, self.entity);
+        self
+    }
+
+    pub fn time_step(mut self, time_step: Duration) -> Self {
+        self.world.sim_time_step = TimeStep(time_step);
+        self
+    }
+
+    pub fn integrator(mut self, integrator: Integrator) -> Self {
+        self.integrator = integrator;
+        self
+    }
+
+    pub fn entity(&self) -> EntityId {
+        self.entity
+    }
+
+    /// Runs the body through [`six_dof`] with `effectors` for `ticks` ticks on the CPU.
+    pub fn run<Sys, M, A, R>(
+        self,
+        effectors: impl FnOnce() -> Sys,
+        ticks: u64,
+    ) -> Result<Recording, Error>
+    where
+        M: 'static,
+        A: 'static,
+        R: 'static,
+        Sys: IntoSystem<M, A, R> + 'static,
+        <Sys as IntoSystem<M, A, R>>::System: Send + Sync,
+    {
+        let client = nox::Client::cpu()?;
+        let mut exec = self
+            .world
+            .builder()
+            .tick_pipeline(six_dof(effectors, self.integrator, Attitude::Quaternion))
+            .build()?
+            .compile(client)?;
+        for _ in 0..ticks {
+            exec.run()?;
+        }
+        Ok(Recording {
+            world: exec.world,
+            entity: self.entity,
+        })
+    }
+}
+
+/// The history of a [`Scratch`] run, from the initial state through the last tick.
+pub struct Recording {
+    pub world: World,
+    entity: EntityId,
+}
+
+impl Recording {
+    /// The simulation time of each recorded state.
+    pub fn times(&self) -> Vec<f64> {
+        let h = self.world.sim_time_step.0.as_secs_f64();
+        (0..=self.world.tick).map(|tick| tick as f64 * h).collect()
+    }
+
+    /// The value of an f64 component at each recorded state.
+    pub fn values(&self, id: ComponentId) -> Result<Vec<Vec<f64>>, Error> {
+        (0..=self.world.tick)
+            .map(|tick| entity_value(&self.world, id, self.entity, tick))
+            .collect()
+    }
+
+    pub fn component<C: Component + 'static>(&self) -> Result<Vec<Vec<f64>>, Error> {
+        self.values(C::COMPONENT_ID)
+    }
+
+    /// The value of an f64 component after the last tick.
+    pub fn last<C: Component + 'static>(&self) -> Result<Vec<f64>, Error> {
+        entity_value(&self.world, C::COMPONENT_ID, self.entity, self.world.tick)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::six_dof::{Force, Inertia};
+    use crate::{Query, WorldPos};
+    use nox::SpatialForce;
+
+    #[test]
+    fn test_scratch() {
+        fn gravity(q: Query<(Force, Inertia)>) -> Query<Force> {
+            q.map(|_: Force, inertia: Inertia| {
+                Force(SpatialForce::from_linear(
+                    nox::tensor![0.0, 0.0, -9.81] * inertia.0.mass(),
+                ))
+            })
+            .unwrap()
+        }
+
+        let recording = Scratch::at_rest(SpatialInertia::from_mass(2.0))
+            .time_step(Duration::from_secs_f64(0.1))
+            .run(|| gravity, 10)
+            .unwrap();
+        let times = recording.times();
+        let pos = recording.component::<WorldPos>().unwrap();
+        assert_eq!(pos.len(), 11);
+        assert_eq!(times.len(), 11);
+        for (t, pos) in times.iter().zip(&pos) {
+            assert!((pos[6] + 0.5 * 9.81 * t * t).abs() < 1e-9);
+        }
+        let pos = recording.last::<WorldPos>().unwrap();
+        assert!((pos[6] + 4.905).abs() < 1e-9);
+    }
+}
@@ -1,13 +1,15 @@
 use core::ops::{Add, Mul};
-use nox::{Op, OwnedRepr, Scalar, SpatialForce, SpatialInertia, SpatialMotion};
+use nox::{
+    Dcm, Op, OwnedRepr, Scalar, SpatialForce, SpatialInertia, SpatialMotion, SpatialTransform,
+};
 use nox_ecs::{system::IntoSystem, system::System, Query, WorldPos};
 use nox_ecs::{Archetype, Component};
 use nox_ecs_macros::{ComponentGroup, FromBuilder, ReprMonad};
 use std::sync::Arc;
 
 use crate::{
-    semi_implicit_euler, semi_implicit_euler_with_dt, ComponentArray, ErasedSystem, Integrator,
-    Rk4Ext,
+    semi_implicit_euler, semi_implicit_euler_with_dt, ComponentArray, Dopri5Ext, ErasedSystem,
+    Integrator, Rk4Ext,
 };
 
 #[derive(Component, ReprMonad)]
@@ -83,6 +85,51 @@ impl Mul<DU> for f64 {
     }
 }
 
+/// The RK4 state of a body whose attitude is propagated as a [`WorldDcm`].
+#[derive(FromBuilder, ComponentGroup)]
+struct UDcm {
+    x: WorldPos,
+    v: WorldVel,
+    dcm: WorldDcm,
+}
+
+impl Add<DU> for UDcm {
+    type Output = UDcm;
+
+    fn add(self, v: DU) -> Self::Output {
+        let XDcm { x, dcm } = XDcm {
+            x: self.x,
+            dcm: self.dcm,
+        } + v.v;
+        UDcm {
+            x,
+            v: WorldVel(self.v.0 + v.a.0),
+            dcm,
+        }
+    }
+}
+
+/// The semi-implicit Euler position of a body whose attitude is propagated as a [`WorldDcm`].
+#[derive(FromBuilder, ComponentGroup)]
+struct XDcm {
+    x: WorldPos,
+    dcm: WorldDcm,
+}
+
+impl Add<WorldVel> for XDcm {
+    type Output = XDcm;
+
+    fn add(self, v: WorldVel) -> Self::Output {
+        // the `WorldPos` attitude follows the matrix, so each stage sees the attitude it holds
+        let dcm = self.dcm.0.integrate_world(v.0.angular());
+        let linear = self.x.0.linear() + v.0.linear();
+        XDcm {
+            x: WorldPos(SpatialTransform::new(dcm.to_quaternion(), linear)),
+            dcm: WorldDcm(dcm),
+        }
+    }
+}
+
 impl Add<WorldVel> for WorldPos {
     type Output = WorldPos;
 
@@ -160,10 +207,40 @@ pub struct Body {
     pub mass: Inertia,
 }
 
+impl Body {
+    /// A rigid body with the given pose, velocity and inertia, and no force applied yet.
+    pub fn new(
+        pos: SpatialTransform<f64>,
+        vel: SpatialMotion<f64>,
+        inertia: SpatialInertia<f64>,
+    ) -> Self {
+        Body {
+            pos: WorldPos(pos),
+            vel: WorldVel(vel),
+            accel: WorldAccel(SpatialMotion::zero()),
+            force: Force(SpatialForce::zero()),
+            mass: Inertia(inertia),
+        }
+    }
+}
+
+/// How [`six_dof`] represents and propagates the attitude of the bodies it integrates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Attitude {
+    /// The quaternion in each body's `WorldPos`.
+    #[default]
+    Quaternion,
+    /// A [`WorldDcm`], which every integrated body must have, kept orthonormal by the integrator.
+    /// The attitude of the `WorldPos` follows it at every stage, so everything downstream keeps
+    /// working with quaternions.
+    Dcm,
+}
+
 pub fn six_dof_with_dt<Sys, M, A, R>(
     effectors: impl FnOnce() -> Sys,
     time_step: f64,
     integrator: Integrator,
+    attitude: Attitude,
 ) -> Arc<dyn System<Arg = (), Ret = ()> + Send + Sync>
 where
     M: 'static,
@@ -174,19 +251,31 @@ where
 {
     let sys = clear_forces.pipe(effectors()).pipe(calc_accel);
     //let sys = clear_forces.pipe(calc_accel);
-    match integrator {
-        Integrator::Rk4 => Arc::new(sys.rk4_with_dt::<U, DU>(time_step)),
-        Integrator::SemiImplicit => {
+    match (integrator, attitude) {
+        (Integrator::Rk4, Attitude::Quaternion) => Arc::new(sys.rk4_with_dt::<U, DU>(time_step)),
+        (Integrator::Rk4, Attitude::Dcm) => Arc::new(sys.rk4_with_dt::<UDcm, DU>(time_step)),
+        (Integrator::SemiImplicit, Attitude::Quaternion) => {
             let integrate =
                 semi_implicit_euler_with_dt::<WorldPos, WorldVel, WorldAccel>(time_step);
             Arc::new(ErasedSystem::new(sys.pipe(integrate)))
         }
+        (Integrator::SemiImplicit, Attitude::Dcm) => {
+            let integrate = semi_implicit_euler_with_dt::<XDcm, WorldVel, WorldAccel>(time_step);
+            Arc::new(ErasedSystem::new(sys.pipe(integrate)))
+        }
+        (Integrator::Dopri5(solver), Attitude::Quaternion) => {
+            Arc::new(sys.dopri5_with_dt::<U, DU>(time_step, solver))
+        }
+        (Integrator::Dopri5(solver), Attitude::Dcm) => {
+            Arc::new(sys.dopri5_with_dt::<UDcm, DU>(time_step, solver))
+        }
     }
 }
 
 pub fn six_dof<Sys, M, A, R>(
     effectors: impl FnOnce() -> Sys,
     integrator: Integrator,
+    attitude: Attitude,
 ) -> Arc<dyn System<Arg = (), Ret = ()> + Send + Sync>
 where
     M: 'static,
@@ -197,12 +286,36 @@ where
 {
     let sys = clear_forces.pipe(effectors()).pipe(calc_accel);
     //let sys = clear_forces.pipe(calc_accel);
-    match integrator {
-        Integrator::Rk4 => Arc::new(sys.rk4::<U, DU>()),
-        Integrator::SemiImplicit => {
+    match (integrator, attitude) {
+        (Integrator::Rk4, Attitude::Quaternion) => Arc::new(sys.rk4::<U, DU>()),
+        (Integrator::Rk4, Attitude::Dcm) => Arc::new(sys.rk4::<UDcm, DU>()),
+        (Integrator::SemiImplicit, Attitude::Quaternion) => {
             let integrate = semi_implicit_euler::<WorldPos, WorldVel, WorldAccel>();
             Arc::new(ErasedSystem::new(sys.pipe(integrate)))
         }
+        (Integrator::SemiImplicit, Attitude::Dcm) => {
+            let integrate = semi_implicit_euler::<XDcm, WorldVel, WorldAccel>();
+            Arc::new(ErasedSystem::new(sys.pipe(integrate)))
+        }
+        (Integrator::Dopri5(solver), Attitude::Quaternion) => Arc::new(sys.dopri5::<U, DU>(solver)),
+        (Integrator::Dopri5(solver), Attitude::Dcm) => Arc::new(sys.dopri5::<UDcm, DU>(solver)),
+    }
+}
+
+/// The attitude of a body as a direction cosine matrix, for models that propagate one instead of
+/// a quaternion, see [`Attitude::Dcm`].
+#[derive(Component, ReprMonad)]
+pub struct WorldDcm<R: OwnedRepr = Op>(pub Dcm<f64, R>);
+
+impl Clone for WorldDcm {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl WorldDcm {
+    pub fn from_pos(pos: &WorldPos) -> Self {
+        WorldDcm(Dcm::from(pos.0.angular()))
     }
 }
 
@@ -217,7 +330,6 @@ mod tests {
     use nox::tensor;
     use nox::ArrayRepr;
     use nox::Quaternion;
-    use nox::SpatialTransform;
     use nox::Vector3;
     use std::f64::consts::FRAC_PI_2;
 
@@ -246,7 +358,7 @@ mod tests {
         let client = nox::Client::cpu().unwrap();
         let mut exec = world
             .builder()
-            .tick_pipeline(six_dof(|| (), Integrator::Rk4))
+            .tick_pipeline(six_dof(|| (), Integrator::Rk4, Attitude::Quaternion))
             .sim_time_step(std::time::Duration::from_secs_f64(time_step))
             .build()
             .unwrap()
@@ -278,6 +390,104 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_dcm_attitude() {
+        let mut world = World::default();
+        let eye = tensor![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        world
+            .spawn(Body::new(
+                SpatialTransform::from_linear(tensor![1.0, 0.0, 0.0]),
+                SpatialMotion::from_angular(tensor![0.0, 0.0, 1.0]),
+                SpatialInertia::from_mass(1.0),
+            ))
+            .insert(WorldDcm(Dcm(eye.into())));
+        let time_step = 1.0 / 120.0;
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(six_dof(|| (), Integrator::Rk4, Attitude::Dcm))
+            .sim_time_step(std::time::Duration::from_secs_f64(time_step))
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        for _ in 0..120 {
+            exec.run().unwrap();
+        }
+        let column = exec.column_at_tick(WorldPos::COMPONENT_ID, 120).unwrap();
+        let (_, pos) = column
+            .typed_iter::<SpatialTransform<f64, ArrayRepr>>()
+            .next()
+            .unwrap();
+        // one radian about z, with the position left to the integrator
+        approx::assert_relative_eq!(
+            pos.inner,
+            tensor![
+                0.0,
+                0.0,
+                0.479425538604203,
+                0.8775825618903728,
+                1.0,
+                0.0,
+                0.0
+            ],
+            epsilon = 1e-5
+        )
+    }
+
+    #[test]
+    fn test_dopri5() {
+        // a spring pulling towards the origin, with a period of 2 pi seconds
+        fn spring(q: Query<(WorldPos, Force)>) -> Query<Force> {
+            q.map(|pos: WorldPos, _: Force| Force(SpatialForce::from_linear(-pos.0.linear())))
+                .unwrap()
+        }
+
+        let mut world = World::default();
+        world.spawn(Body::new(
+            SpatialTransform::from_linear(tensor![1.0, 0.0, 0.0]),
+            SpatialMotion::from_angular(tensor![0.0, 0.0, 1.0]),
+            SpatialInertia::from_mass(1.0),
+        ));
+        let solver = nox::Dopri5::default().max_steps(8);
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(six_dof(
+                || spring,
+                Integrator::Dopri5(solver),
+                Attitude::Quaternion,
+            ))
+            .sim_time_step(std::time::Duration::from_secs_f64(0.25))
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        assert_eq!(exec.tick_exec.integrator(), Some("dopri5"));
+        for _ in 0..4 {
+            exec.run().unwrap();
+        }
+        let column = exec.column_at_tick(WorldPos::COMPONENT_ID, 4).unwrap();
+        let (_, pos) = column
+            .typed_iter::<SpatialTransform<f64, ArrayRepr>>()
+            .next()
+            .unwrap();
+        // one radian about z, and cos(1) along x, from four quarter second ticks
+        approx::assert_relative_eq!(
+            pos.inner,
+            tensor![
+                0.0,
+                0.0,
+                0.479425538604203,
+                0.8775825618903728,
+                0.5403023058681398,
+                0.0,
+                0.0
+            ],
+            epsilon = 1e-6
+        )
+    }
+
     fn expect_angular_accel(
         client: &nox::Client,
         rot: Quaternion<f64, ArrayRepr>,
@@ -328,7 +538,11 @@ mod tests {
         let time_step = 1.0 / 120.0;
         let mut exec = world
             .builder()
-            .tick_pipeline(six_dof(|| constant_torque, Integrator::Rk4))
+            .tick_pipeline(six_dof(
+                || constant_torque,
+                Integrator::Rk4,
+                Attitude::Quaternion,
+            ))
             .sim_time_step(std::time::Duration::from_secs_f64(time_step))
             .build()
             .unwrap()
@@ -426,7 +640,11 @@ mod tests {
         let time_step = 1.0 / 1.0;
         let world = world
             .builder()
-            .tick_pipeline(six_dof(|| constant_force, Integrator::Rk4))
+            .tick_pipeline(six_dof(
+                || constant_force,
+                Integrator::Rk4,
+                Attitude::Quaternion,
+            ))
             .sim_time_step(std::time::Duration::from_secs_f64(time_step))
             .run();
         let column = world
@@ -1,9 +1,16 @@
-use std::{collections::BTreeMap, marker::PhantomData, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    marker::PhantomData,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use impeller::{ComponentId, World};
-use nox::{ArrayTy, Noxpr, NoxprComp, NoxprFn, NoxprId, NoxprTy};
+use impeller::{ComponentExt, ComponentId, World};
+use nox::{ArrayTy, Noxpr, NoxprComp, NoxprFn, NoxprId, NoxprScalarExt, NoxprTy};
+use serde::{Deserialize, Serialize};
+use smallvec::smallvec;
 
-use crate::{ComponentArray, Error};
+use crate::{ComponentArray, Error, SimulationTick};
 
 pub struct SystemBuilder<'a> {
     pub vars: BTreeMap<ComponentId, ComponentArray<()>>,
@@ -48,6 +55,8 @@ impl<'a> SystemBuilder<'a> {
             },
             inputs: self.inputs.iter().map(|(k, _)| k).copied().collect(),
             outputs: self.vars.keys().copied().collect(),
+            systems: vec![],
+            integrator: None,
         })
     }
 
@@ -95,6 +104,18 @@ pub struct CompiledSystem {
     pub computation: NoxprComp,
     pub inputs: Vec<ComponentId>,
     pub outputs: Vec<ComponentId>,
+    /// The systems merged into this one.
+    pub systems: Vec<SystemTrace>,
+    /// The integrators advancing the state, joined by `+` if there are several, e.g `rk4`.
+    pub integrator: Option<String>,
+}
+
+/// A system that was traced into a [`CompiledSystem`], along with the components it writes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SystemTrace {
+    pub name: String,
+    pub outputs: Vec<ComponentId>,
+    pub trace_time: Duration,
 }
 
 impl CompiledSystem {
@@ -207,6 +228,9 @@ where
     }
 
     fn compile(&self, world: &World) -> Result<CompiledSystem, Error> {
+        let name = std::any::type_name::<F>();
+        let _span = tracing::debug_span!("trace", system = name).entered();
+        let start = Instant::now();
         let mut builder = SystemBuilder::new(world);
         self.init(&mut builder)?;
         let output = (self.func)();
@@ -222,7 +246,13 @@ where
         Ok(CompiledSystem {
             computation,
             inputs: vec![],
-            outputs: component_ids,
+            outputs: component_ids.clone(),
+            systems: vec![SystemTrace {
+                name: name.to_string(),
+                outputs: component_ids,
+                trace_time: start.elapsed(),
+            }],
+            integrator: None,
         })
     }
 }
@@ -243,6 +273,36 @@ pub trait IntoSystem<Marker, Arg, Ret> {
             b: other.into_system(),
         }
     }
+
+    /// Runs the system once every `divisor` ticks, see [`Schedule`].
+    fn every(self, divisor: u64) -> Schedule<Self::System>
+    where
+        Self: Sized,
+    {
+        Schedule::new(self.into_system(), Rate::Divisor(divisor))
+    }
+
+    /// Runs the system at `rate`, see [`Schedule`].
+    fn at_rate(self, rate: Rate) -> Schedule<Self::System>
+    where
+        Self: Sized,
+    {
+        Schedule::new(self.into_system(), rate)
+    }
+
+    /// Like [`IntoSystem::pipe`], but traces the two systems in parallel, see [`ParPipe`].
+    fn par_pipe<M2, A2, R2, OtherSys: IntoSystem<M2, A2, R2>>(
+        self,
+        other: OtherSys,
+    ) -> ParPipe<Self::System, OtherSys::System>
+    where
+        Self: Sized,
+    {
+        ParPipe {
+            a: self.into_system(),
+            b: other.into_system(),
+        }
+    }
 }
 
 macro_rules! impl_system_param {
@@ -320,6 +380,9 @@ macro_rules! impl_system_param {
 
                     #[allow(non_snake_case)]
                     fn compile(&self, world: &World) -> Result<CompiledSystem, Error> {
+                        let name = std::any::type_name::<F>();
+                        let _span = tracing::debug_span!("trace", system = name).entered();
+                        let start = Instant::now();
                         let mut builder = SystemBuilder::new(world);
                         let builder = &mut builder;
                         self.init(builder)?;
@@ -350,7 +413,13 @@ macro_rules! impl_system_param {
                         Ok(CompiledSystem {
                             computation,
                             inputs,
-                            outputs,
+                            outputs: outputs.clone(),
+                            systems: vec![SystemTrace {
+                                name: name.to_string(),
+                                outputs,
+                                trace_time: start.elapsed(),
+                            }],
+                            integrator: None,
                         })
                     }
 
@@ -411,10 +480,23 @@ pub fn merge_compiled_systems(
     systems: impl IntoIterator<Item = CompiledSystem>,
     pipeline: &mut SystemBuilder,
 ) -> Result<CompiledSystem, Error> {
-    for system in systems {
+    let mut traces = vec![];
+    let mut integrator: Option<String> = None;
+    for mut system in systems {
+        traces.append(&mut system.systems);
+        for name in system.integrator.iter().flat_map(|name| name.split('+')) {
+            match &mut integrator {
+                Some(names) if names.split('+').any(|n| n == name) => {}
+                Some(names) => *names = format!("{}+{}", names, name),
+                None => integrator = Some(name.to_string()),
+            }
+        }
         system.insert_into_builder(pipeline)?;
     }
-    pipeline.to_compiled_system()
+    let mut compiled = pipeline.to_compiled_system()?;
+    compiled.systems = traces;
+    compiled.integrator = integrator;
+    Ok(compiled)
 }
 
 pub struct Pipe<A: System, B: System> {
@@ -428,8 +510,123 @@ impl<A: System, B: System> Pipe<A, B> {
     }
 }
 
+/// Runs `a` then `b` like [`Pipe`], but traces `a` on a scoped thread while `b` is traced on the current one.
+///
+/// Each system is traced against the world with its own [`SystemBuilder`], and the data `a` hands to `b` is only
+/// wired up when the traces are merged, so the two sides never wait on each other. Chaining `par_pipe` traces
+/// every system in the chain concurrently. Systems traced by Python take the GIL, so they gain nothing here, and
+/// must not be traced this way from a thread that already holds it.
+pub struct ParPipe<A: System, B: System> {
+    a: A,
+    b: B,
+}
+
+impl<A: System, B: System> ParPipe<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<A: System + Sync, B: System + Sync> System for ParPipe<A, B> {
+    type Arg = (A::Arg, B::Arg);
+    type Ret = (A::Ret, B::Ret);
+    fn init(&self, builder: &mut SystemBuilder) -> Result<(), Error> {
+        self.a.init(builder)?;
+        self.b.init(builder)
+    }
+
+    fn compile(&self, world: &World) -> Result<CompiledSystem, Error> {
+        let (a, b) = std::thread::scope(|scope| {
+            let a = scope.spawn(|| self.a.compile(world));
+            let b = self.b.compile(world);
+            let a = a
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            (a, b)
+        });
+        let (a, b) = (a?, b?);
+        let mut inner_builder = SystemBuilder::new(world);
+        self.init(&mut inner_builder)?;
+
+        merge_compiled_systems([a, b], &mut inner_builder)
+    }
+}
+
+/// How often a [`Schedule`]d system runs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rate {
+    /// Once every `n` ticks.
+    Divisor(u64),
+    /// Once per `period`, which has to be a whole number of sim time steps, give or take
+    /// [`Rate::TOLERANCE`].
+    Period(Duration),
+}
+
+impl Rate {
+    /// The relative error a [`Rate::Period`] may be off a whole number of time steps by, which
+    /// absorbs the rounding of periods and time steps like 1/30 s and 1/120 s to nanoseconds.
+    pub const TOLERANCE: f64 = 1e-6;
+
+    pub fn hz(frequency: f64) -> Self {
+        Rate::Period(Duration::from_secs_f64(1.0 / frequency))
+    }
+
+    /// The number of ticks between runs at `time_step`.
+    pub fn divisor(&self, time_step: Duration) -> Result<u64, Error> {
+        let divisor = match *self {
+            Rate::Divisor(n) => n,
+            Rate::Period(period) => {
+                if time_step.is_zero() {
+                    return Err(Error::ZeroTimeStep);
+                }
+                let ratio = period.as_secs_f64() / time_step.as_secs_f64();
+                let ticks = ratio.round();
+                if (ratio - ticks).abs() > Self::TOLERANCE * ratio {
+                    return Err(Error::InvalidRate(format!(
+                        "period {period:?} is not a multiple of the time step {time_step:?}"
+                    )));
+                }
+                ticks as u64
+            }
+        };
+        if divisor == 0 {
+            return Err(Error::InvalidRate(
+                "a system can't run zero ticks apart".into(),
+            ));
+        }
+        Ok(divisor)
+    }
+}
+
+/// Runs a system on a subset of ticks, like a controller at 100 Hz inside 1 kHz physics.
+///
+/// The system is still traced into the tick pipeline, but its outputs are only committed on the
+/// ticks it is scheduled for, and hold their last value in between. Ordering follows the pipeline
+/// as usual: a system sees whatever the systems before it wrote this tick, and the held outputs of
+/// slower systems. The first tick runs every schedule with a zero phase, and a phase of `k` delays
+/// the runs by `k` ticks, so rates sharing a divisor can be staggered.
+///
+/// Which tick it is comes from [`SimulationTick`], so schedules belong in the tick pipeline rather
+/// than the startup one.
 pub struct Schedule<A: System> {
     system: A,
+    rate: Rate,
+    phase: u64,
+}
+
+impl<A: System> Schedule<A> {
+    pub fn new(system: A, rate: Rate) -> Self {
+        Self {
+            system,
+            rate,
+            phase: 0,
+        }
+    }
+
+    pub fn phase(mut self, phase: u64) -> Self {
+        self.phase = phase;
+        self
+    }
 }
 
 impl<A: System> System for Schedule<A> {
@@ -440,7 +637,37 @@ impl<A: System> System for Schedule<A> {
     }
 
     fn compile(&self, world: &World) -> Result<CompiledSystem, Error> {
-        self.system.compile(world)
+        let divisor = self.rate.divisor(world.sim_time_step.0)?;
+        let compiled = self.system.compile(world)?;
+        if divisor == 1 {
+            return Ok(compiled);
+        }
+        let mut builder = SystemBuilder::new(world);
+        self.init(&mut builder)?;
+        let tick = builder.get_or_init_var(SimulationTick::COMPONENT_ID)?;
+        let mut held = Vec::with_capacity(compiled.outputs.len());
+        for id in &compiled.outputs {
+            held.push((*id, builder.get_or_init_var(*id)?.buffer));
+        }
+        let traces = compiled.systems.clone();
+        let integrator = compiled.integrator.clone();
+        compiled.insert_into_builder(&mut builder)?;
+
+        // the tick has already been incremented when the pipeline runs, so the first is tick one
+        let phase = self.phase % divisor;
+        let tick = tick.buffer.reshape(smallvec![]) + (divisor - 1 - phase).constant();
+        let rem = tick.clone() - (tick / divisor.constant()) * divisor.constant();
+        let run = rem.eq(0u64.constant());
+        for (id, old) in held {
+            let var = builder.vars.get_mut(&id).ok_or(Error::ComponentNotFound)?;
+            let shape = var.buffer.shape().ok_or(Error::ValueSizeMismatch)?;
+            let run = run.clone().broadcast_to(shape);
+            var.buffer = run.select(var.buffer.clone(), old);
+        }
+        let mut compiled = builder.to_compiled_system()?;
+        compiled.systems = traces;
+        compiled.integrator = integrator;
+        Ok(compiled)
     }
 }
 
@@ -478,6 +705,8 @@ impl System for () {
             computation: NoxprComp::new(func, NoxprTy::Tuple(vec![])),
             inputs: vec![],
             outputs: vec![],
+            systems: vec![],
+            integrator: None,
         })
     }
 }
@@ -509,3 +738,93 @@ impl<S: System> System for &S {
         (*self).compile(world)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Component, Query, WorldExt};
+    use nox::{Op, OwnedRepr, Scalar};
+    use nox_ecs_macros::{Archetype, ReprMonad};
+
+    #[derive(Component, ReprMonad)]
+    struct Fast<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+    #[derive(Component, ReprMonad)]
+    struct Slow<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+    #[derive(Component, ReprMonad)]
+    struct Staggered<R: OwnedRepr = Op>(Scalar<f64, R>);
+
+    #[derive(Archetype)]
+    struct Counters {
+        fast: Fast,
+        slow: Slow,
+        staggered: Staggered,
+    }
+
+    fn fast(q: Query<Fast>) -> Query<Fast> {
+        q.map(|f: Fast| Fast(f.0 + 1.0)).unwrap()
+    }
+
+    // copies the fast counter, to check what a slow system sees
+    fn slow(q: Query<(Fast, Slow)>) -> Query<Slow> {
+        q.map(|f: Fast, _: Slow| Slow(f.0)).unwrap()
+    }
+
+    fn staggered(q: Query<Staggered>) -> Query<Staggered> {
+        q.map(|s: Staggered| Staggered(s.0 + 1.0)).unwrap()
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut world = World::default();
+        world.spawn(Counters {
+            fast: Fast(0.0.into()),
+            slow: Slow(0.0.into()),
+            staggered: Staggered(0.0.into()),
+        });
+        let pipeline = fast
+            .pipe(slow.at_rate(Rate::hz(100.0)))
+            .pipe(Schedule::new(staggered.into_system(), Rate::Divisor(4)).phase(2));
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(pipeline)
+            .sim_time_step(Duration::from_millis(1))
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        let mut slow_values = vec![];
+        let mut staggered_values = vec![];
+        for _ in 0..12 {
+            exec.run().unwrap();
+            let slow = exec.world.column::<Slow>().unwrap();
+            slow_values.push(slow.typed_buf::<f64>().unwrap()[0]);
+            let staggered = exec.world.column::<Staggered>().unwrap();
+            staggered_values.push(staggered.typed_buf::<f64>().unwrap()[0]);
+        }
+        let fast = exec.world.column::<Fast>().unwrap();
+        assert_eq!(fast.typed_buf::<f64>().unwrap(), &[12.0]);
+        // runs on ticks 1 and 11, seeing the fast counter of the same tick
+        assert_eq!(slow_values[..3], [1.0, 1.0, 1.0]);
+        assert_eq!(slow_values[9..], [1.0, 11.0, 11.0]);
+        // runs on ticks 3, 7 and 11
+        assert_eq!(
+            staggered_values,
+            [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0, 3.0, 3.0]
+        );
+
+        let bad = Rate::Period(Duration::from_micros(1500));
+        assert!(bad.divisor(Duration::from_millis(1)).is_err());
+        assert_eq!(
+            Rate::hz(250.0).divisor(Duration::from_millis(1)).unwrap(),
+            4
+        );
+        // neither period is a whole number of nanoseconds
+        let dt = Duration::from_secs_f64(1.0 / 120.0);
+        assert_eq!(Rate::hz(30.0).divisor(dt).unwrap(), 4);
+        assert_eq!(Rate::hz(120.0).divisor(dt).unwrap(), 1);
+        assert!(Rate::hz(50.0).divisor(dt).is_err());
+    }
+}
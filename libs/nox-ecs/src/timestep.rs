@@ -0,0 +1,247 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use impeller::{ComponentExt, ComponentId};
+
+use crate::dense_output::entity_value;
+use crate::six_dof::WorldVel;
+use crate::{Error, TickHooks, World, WorldPos};
+
+/// A slice of `len` elements starting at `offset` of an f64 component.
+#[derive(Clone, Copy, Debug)]
+pub struct Elements {
+    pub component: ComponentId,
+    pub offset: usize,
+    pub len: usize,
+}
+
+impl Elements {
+    pub fn new(component: ComponentId, offset: usize, len: usize) -> Self {
+        Self {
+            component,
+            offset,
+            len,
+        }
+    }
+}
+
+/// Watches how well the time step resolves the motion, and recommends one from the data.
+///
+/// The local truncation error of a tick is estimated from the recorded states, as the gap between
+/// the change in a value and the trapezoidal integral of its derivative, `x1 - x0 - h (v0 + v1) / 2`.
+/// That gap grows with the cube of the step, so the step that would bring the worst error seen so
+/// far down to `tolerance` can be read straight off it. Constraint residuals, like the drift of a
+/// quaternion's norm from one, are tracked alongside.
+///
+/// By default the position of every body is checked against its velocity, and the attitude
+/// quaternion of `WorldPos` is checked for drift.
+#[derive(Clone, Debug)]
+pub struct TimestepAdvisor {
+    pairs: Vec<(Elements, Elements)>,
+    quaternions: Vec<(ComponentId, usize)>,
+    /// The acceptable local error per tick, in the units of the value.
+    pub tolerance: f64,
+    /// The acceptable drift of a unit quaternion's norm.
+    pub residual_tolerance: f64,
+}
+
+impl Default for TimestepAdvisor {
+    fn default() -> Self {
+        Self {
+            pairs: vec![(
+                Elements::new(WorldPos::COMPONENT_ID, 4, 3),
+                Elements::new(WorldVel::COMPONENT_ID, 3, 3),
+            )],
+            quaternions: vec![(WorldPos::COMPONENT_ID, 0)],
+            tolerance: 1e-6,
+            residual_tolerance: 1e-9,
+        }
+    }
+}
+
+/// What a [`TimestepAdvisor`] has observed so far.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TimestepReport {
+    pub ticks: u64,
+    /// The largest local error estimate of any tick.
+    pub max_error: f64,
+    /// The largest constraint residual of any tick.
+    pub max_residual: f64,
+    /// The step that keeps the worst local error within tolerance, when the data allows an estimate.
+    pub recommended_time_step: Option<Duration>,
+    /// How many substeps the current step would need to be split into to meet the recommendation.
+    pub substeps: u64,
+}
+
+impl TimestepReport {
+    pub fn within_tolerance(&self) -> bool {
+        self.substeps <= 1
+    }
+}
+
+impl TimestepAdvisor {
+    /// Checks that `value` changes as fast as `derivative` says it should.
+    pub fn pair(mut self, value: Elements, derivative: Elements) -> Self {
+        self.pairs.push((value, derivative));
+        self
+    }
+
+    /// Also checks the quaternion stored at element `offset` of the f64 component `id`.
+    pub fn quaternion(mut self, id: ComponentId, offset: usize) -> Self {
+        self.quaternions.push((id, offset));
+        self
+    }
+
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn residual_tolerance(mut self, tolerance: f64) -> Self {
+        self.residual_tolerance = tolerance;
+        self
+    }
+
+    /// Registers the advisor as a post-tick hook, returning a handle to its running report.
+    ///
+    /// A warning is logged the first time the error or a residual exceeds its tolerance.
+    pub fn install(self, hooks: &mut TickHooks) -> Arc<Mutex<TimestepReport>> {
+        let report = Arc::new(Mutex::new(TimestepReport::default()));
+        let shared = report.clone();
+        let mut warned = false;
+        hooks.add_post_tick(move |ctx| {
+            let mut report = shared.lock().map_err(|_| Error::HookPoisoned)?;
+            self.observe(ctx.world, &mut report)?;
+            if !warned
+                && (!report.within_tolerance() || report.max_residual > self.residual_tolerance)
+            {
+                warned = true;
+                tracing::warn!(
+                    tick = ctx.tick,
+                    max_error = report.max_error,
+                    max_residual = report.max_residual,
+                    recommended = ?report.recommended_time_step,
+                    "time step is too coarse"
+                );
+            }
+            Ok(())
+        });
+        report
+    }
+
+    /// Folds the last tick of `world` into `report`.
+    pub fn observe(&self, world: &World, report: &mut TimestepReport) -> Result<(), Error> {
+        if world.tick == 0 {
+            return Ok(());
+        }
+        let h = world.sim_time_step.0.as_secs_f64();
+        if h == 0.0 {
+            return Err(Error::ZeroTimeStep);
+        }
+        let (k0, k1) = (world.tick - 1, world.tick);
+        for (value, derivative) in &self.pairs {
+            let col = world
+                .column_by_id(value.component)
+                .ok_or(Error::ComponentNotFound)?;
+            for entity in col.entity_ids() {
+                let x0 = elements(world, value, entity, k0)?;
+                let x1 = elements(world, value, entity, k1)?;
+                let v0 = elements(world, derivative, entity, k0)?;
+                let v1 = elements(world, derivative, entity, k1)?;
+                if x0.len() != v0.len() {
+                    return Err(Error::ValueSizeMismatch);
+                }
+                let err = (0..x0.len())
+                    .map(|i| (x1[i] - x0[i] - 0.5 * h * (v0[i] + v1[i])).abs())
+                    .fold(0.0, f64::max);
+                report.max_error = report.max_error.max(err);
+            }
+        }
+        for &(id, offset) in &self.quaternions {
+            let col = world.column_by_id(id).ok_or(Error::ComponentNotFound)?;
+            for entity in col.entity_ids() {
+                let q = elements(world, &Elements::new(id, offset, 4), entity, k1)?;
+                let norm = q.iter().map(|x| x * x).sum::<f64>().sqrt();
+                report.max_residual = report.max_residual.max((norm - 1.0).abs());
+            }
+        }
+        report.ticks += 1;
+        if report.max_error > 0.0 {
+            // the estimate scales with h^3, keep a safety margin like an adaptive solver would
+            let step = 0.9 * h * (self.tolerance / report.max_error).cbrt();
+            let step = step.min(h * 10.0);
+            report.recommended_time_step = Some(Duration::from_secs_f64(step));
+            report.substeps = (h / step).ceil().max(1.0) as u64;
+        }
+        Ok(())
+    }
+}
+
+fn elements(
+    world: &World,
+    elements: &Elements,
+    entity: impeller::EntityId,
+    tick: u64,
+) -> Result<Vec<f64>, Error> {
+    let value = entity_value(world, elements.component, entity, tick)?;
+    value
+        .get(elements.offset..elements.offset + elements.len)
+        .map(<[f64]>::to_vec)
+        .ok_or(Error::ValueSizeMismatch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::six_dof::{six_dof, Attitude, Body, Force};
+    use crate::{Integrator, Query, WorldExt};
+    use nox::{tensor, SpatialForce, SpatialInertia, SpatialMotion, SpatialTransform};
+
+    fn report(time_step: f64) -> TimestepReport {
+        // a spring pulling towards the origin, with a period of 2 pi seconds
+        fn spring(q: Query<(WorldPos, Force)>) -> Query<Force> {
+            q.map(|pos: WorldPos, _: Force| Force(SpatialForce::from_linear(-pos.0.linear())))
+                .unwrap()
+        }
+
+        let mut world = World::default();
+        world.spawn(Body::new(
+            SpatialTransform::from_linear(tensor![1.0, 0.0, 0.0]),
+            SpatialMotion::zero(),
+            SpatialInertia::from_mass(1.0),
+        ));
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(six_dof(|| spring, Integrator::Rk4, Attitude::Quaternion))
+            .sim_time_step(Duration::from_secs_f64(time_step))
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        let report = TimestepAdvisor::default()
+            .tolerance(1e-6)
+            .install(&mut exec.hooks);
+        for _ in 0..10 {
+            exec.run().unwrap();
+        }
+        let report = report.lock().unwrap();
+        report.clone()
+    }
+
+    #[test]
+    fn test_timestep_advisor() {
+        let coarse = report(0.1);
+        assert_eq!(coarse.ticks, 10);
+        // h^3 / 12 for a unit amplitude oscillator
+        assert!(coarse.max_error > 1e-5 && coarse.max_error < 1e-4);
+        assert!(!coarse.within_tolerance());
+        let step = coarse.recommended_time_step.unwrap().as_secs_f64();
+        assert!(step < 0.1 && step > 0.01);
+        assert!(coarse.max_residual < 1e-9);
+
+        let fine = report(step);
+        assert!(fine.within_tolerance());
+        assert!(fine.max_error <= 1e-6);
+    }
+}
@@ -0,0 +1,201 @@
+use impeller::{ComponentExt, ComponentId, EntityId, PrimitiveTy};
+
+use crate::{Error, TickHooks, World, WorldPos};
+
+/// Keeps quaternion-valued components on the unit sphere during long runs.
+///
+/// Integrators that skip normalization, or accumulate rounding over millions of ticks, let a quaternion's
+/// norm drift away from one, which silently scales every rotation it is used for. Once installed on an exec
+/// with [`UnitQuaternionPolicy::install`], the policy checks the host copy of each component after every
+/// tick, failing the tick when a quaternion has drifted further than `tolerance`, and renormalizes the
+/// quaternions every `renormalize_every` ticks.
+///
+/// By default only `WorldPos` is checked, and validation is only on in debug builds.
+#[derive(Clone, Debug)]
+pub struct UnitQuaternionPolicy {
+    components: Vec<(ComponentId, usize)>,
+    pub renormalize_every: Option<u64>,
+    pub tolerance: Option<f64>,
+}
+
+impl Default for UnitQuaternionPolicy {
+    fn default() -> Self {
+        Self {
+            components: vec![(WorldPos::COMPONENT_ID, 0)],
+            renormalize_every: None,
+            tolerance: cfg!(debug_assertions).then_some(1e-6),
+        }
+    }
+}
+
+impl UnitQuaternionPolicy {
+    /// Also checks the quaternion stored at element `offset` of the f64 component `id`.
+    pub fn component(mut self, id: ComponentId, offset: usize) -> Self {
+        self.components.push((id, offset));
+        self
+    }
+
+    /// Renormalizes every `ticks` ticks, or never with `None`.
+    pub fn renormalize_every(mut self, ticks: Option<u64>) -> Self {
+        self.renormalize_every = ticks.filter(|&ticks| ticks > 0);
+        self
+    }
+
+    /// Fails a tick when a norm drifts from one by more than `tolerance`, or never with `None`.
+    pub fn tolerance(mut self, tolerance: Option<f64>) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Registers the policy as a post-tick hook.
+    pub fn install(self, hooks: &mut TickHooks) {
+        hooks.add_post_tick(move |ctx| self.apply(ctx.tick, &mut *ctx.world));
+    }
+
+    /// Validates, and when due renormalizes, the quaternions in `world` at `tick`.
+    pub fn apply(&self, tick: u64, world: &mut World) -> Result<(), Error> {
+        let renormalize = self
+            .renormalize_every
+            .is_some_and(|every| tick % every == 0);
+        for &(id, offset) in &self.components {
+            if let Some(tolerance) = self.tolerance {
+                validate_column(world, id, offset, tolerance)?;
+            }
+            if renormalize {
+                renormalize_column(world, id, offset)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn validate_column(
+    world: &World,
+    id: ComponentId,
+    offset: usize,
+    tolerance: f64,
+) -> Result<(), Error> {
+    let col = world.column_by_id(id).ok_or(Error::ComponentNotFound)?;
+    let stride = f64_stride(
+        col.metadata.component_type.primitive_ty,
+        &col.metadata.component_type.shape,
+        offset,
+    )?;
+    for (entity, value) in col.entity_ids().zip(col.column.chunks_exact(stride * 8)) {
+        let norm = norm(&quaternion(value, offset));
+        if (norm - 1.0).abs() > tolerance {
+            return Err(Error::QuaternionDrift {
+                component: id,
+                entity,
+                norm,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn f64_stride(primitive_ty: PrimitiveTy, shape: &[i64], offset: usize) -> Result<usize, Error> {
+    let stride = shape.iter().product::<i64>() as usize;
+    if primitive_ty != PrimitiveTy::F64 || offset + 4 > stride {
+        return Err(Error::ValueSizeMismatch);
+    }
+    Ok(stride)
+}
+
+fn quaternion(value: &[u8], offset: usize) -> [f64; 4] {
+    let mut q = [0.0; 4];
+    for (i, x) in q.iter_mut().enumerate() {
+        let start = (offset + i) * 8;
+        *x = f64::from_le_bytes(value[start..start + 8].try_into().unwrap());
+    }
+    q
+}
+
+fn norm(q: &[f64; 4]) -> f64 {
+    q.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+fn renormalize_column(world: &mut World, id: ComponentId, offset: usize) -> Result<(), Error> {
+    let mut col = world.column_by_id_mut(id).ok_or(Error::ComponentNotFound)?;
+    let stride = f64_stride(
+        col.metadata.component_type.primitive_ty,
+        &col.metadata.component_type.shape,
+        offset,
+    )?;
+    for value in col.column.chunks_exact_mut(stride * 8) {
+        let q = quaternion(value, offset);
+        let norm = norm(&q);
+        if norm == 0.0 {
+            continue;
+        }
+        for (i, x) in q.iter().enumerate() {
+            let start = (offset + i) * 8;
+            value[start..start + 8].copy_from_slice(&(x / norm).to_le_bytes());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::six_dof::{Body, Force, Inertia, WorldAccel, WorldVel};
+    use crate::{IntoSystemExt, WorldExt};
+    use nox::{tensor, SpatialForce, SpatialInertia, SpatialMotion, SpatialTransform};
+
+    fn drifted_world() -> World {
+        let mut world = World::default();
+        world.spawn(Body {
+            pos: WorldPos(SpatialTransform {
+                inner: tensor![0.0, 0.0, 0.0, 1.01, 1.0, 2.0, 3.0].into(),
+            }),
+            vel: WorldVel(SpatialMotion::zero()),
+            accel: WorldAccel(SpatialMotion::zero()),
+            force: Force(SpatialForce::zero()),
+            mass: Inertia(SpatialInertia::from_mass(1.0)),
+        });
+        world
+    }
+
+    #[test]
+    fn test_validate_and_renormalize() {
+        let mut world = drifted_world();
+        let policy = UnitQuaternionPolicy::default().tolerance(Some(1e-6));
+        let err = policy.apply(1, &mut world).unwrap_err();
+        let Error::QuaternionDrift { norm, .. } = err else {
+            panic!("expected drift, got {err}");
+        };
+        assert!((norm - 1.01).abs() < 1e-12);
+
+        let policy = policy.tolerance(None).renormalize_every(Some(2));
+        policy.apply(1, &mut world).unwrap();
+        let col = world.column::<WorldPos>().unwrap();
+        assert_eq!(col.typed_buf::<f64>().unwrap()[3], 1.01);
+
+        policy.apply(2, &mut world).unwrap();
+        let col = world.column::<WorldPos>().unwrap();
+        let pos = col.typed_buf::<f64>().unwrap();
+        assert!((pos[3] - 1.0).abs() < 1e-12);
+        assert_eq!(&pos[4..], &[1.0, 2.0, 3.0]);
+        assert!(world.dirty_components.contains(&WorldPos::COMPONENT_ID));
+    }
+
+    #[test]
+    fn test_install() {
+        fn noop(q: crate::Query<WorldPos>) -> crate::Query<WorldPos> {
+            q
+        }
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = noop
+            .world()
+            .world(drifted_world())
+            .build()
+            .unwrap()
+            .compile(client)
+            .unwrap();
+        UnitQuaternionPolicy::default()
+            .tolerance(Some(1e-6))
+            .install(&mut exec.hooks);
+        assert!(matches!(exec.run(), Err(Error::QuaternionDrift { .. })));
+    }
+}
@@ -2,13 +2,15 @@
 # ruff: noqa: F405
 
 import code
+import contextlib
+import contextvars
 import inspect
 import re
 import readline
 import rlcompleter
 import types
 import typing
-from dataclasses import dataclass
+from dataclasses import dataclass, field
 from typing import (
     Annotated,
     Any,
@@ -23,11 +25,13 @@ from typing import (
 )
 
 import jax
+import jax.numpy as jnp
 import numpy
 import pytest
 from jax.tree_util import tree_flatten, tree_unflatten
 from typing_extensions import TypeVarTuple, Unpack
 
+from .constants import Constants
 from .elodin import *
 
 __doc__ = elodin.__doc__
@@ -95,6 +99,70 @@ def system(func) -> System:
     return PyFnSystem(outer, input_ids, output_ids, edge_ids, func.__repr__()).system()
 
 
+@dataclass
+class DtypePolicy:
+    """
+    The precision systems compute floating point components in, applied when they are traced.
+
+    Components are still stored at the precision they were declared with; a system reads them
+    cast to the policy's precision and its outputs are cast back, so a world full of f64
+    components can run its systems in f32 on a GPU without being redeclared. Components listed in
+    `f64` keep full precision, such as positions in a large orbit, while those in `bf16` are
+    computed in bfloat16, for example the inputs and outputs of a learned controller.
+
+    Apply a policy with `World.build(..., dtype_policy=policy)`, or around any build or run with
+    `with policy:`.
+    """
+
+    default: str = "float32"
+    f64: tuple[Any, ...] = ()
+    bf16: tuple[Any, ...] = ()
+    _tokens: list[contextvars.Token] = field(default_factory=list, init=False, repr=False)
+
+    def dtype(self, component: Any) -> Any:
+        name = Component.name(component)
+        if name in {Component.name(c) for c in self.f64}:
+            return jnp.float64
+        if name in {Component.name(c) for c in self.bf16}:
+            return jnp.bfloat16
+        return jnp.dtype(self.default)
+
+    def __enter__(self) -> "DtypePolicy":
+        self._tokens.append(_dtype_policy.set(self))
+        return self
+
+    def __exit__(self, *_):
+        _dtype_policy.reset(self._tokens.pop())
+
+
+_dtype_policy: contextvars.ContextVar[Optional[DtypePolicy]] = contextvars.ContextVar(
+    "dtype_policy", default=None
+)
+
+
+def _storage_dtype(component: Any) -> Any:
+    ty = Metadata.of(component).ty.ty
+    if ty == PrimitiveType.F64:
+        return jnp.float64
+    if ty == PrimitiveType.F32:
+        return jnp.float32
+    return None
+
+
+def _compute_cast(buf: jax.Array, component: Any) -> jax.Array:
+    policy = _dtype_policy.get()
+    if policy is None or _storage_dtype(component) is None:
+        return buf
+    return buf.astype(policy.dtype(component))
+
+
+def _storage_cast(buf: jax.Array, component: Any) -> jax.Array:
+    dtype = _storage_dtype(component)
+    if dtype is None or buf.dtype == dtype:
+        return buf
+    return buf.astype(dtype)
+
+
 T = TypeVar("T")
 S = TypeVarTuple("S")
 A = TypeVarTuple("A")
@@ -113,7 +181,7 @@ class Query(Generic[Unpack[A]]):
         component_data: list[Metadata],
         component_classes: list[type[Any]],
     ):
-        self.bufs = inner.arrays()
+        self.bufs = [_compute_cast(buf, cls) for buf, cls in zip(inner.arrays(), component_classes)]
         self.inner = inner
         self.component_data = component_data
         self.component_classes = component_classes
@@ -123,9 +191,6 @@ class Query(Generic[Unpack[A]]):
         out_tps: Union[Tuple[Annotated[Any, Component], ...], Annotated[Any, Component]],
         f: Callable[[Unpack[A]], Union[Tuple[Unpack[S]], T]],
     ) -> "Query[Unpack[S]]":
-        out_tps_tuple: Tuple[Annotated[Any, Component], ...] = (
-            (out_tps,) if not isinstance(out_tps, tuple) else out_tps
-        )
         buf = jax.vmap(
             lambda b: f(
                 *[from_array(cls, x) for (x, cls) in zip(b, self.component_classes)]  # type: ignore
@@ -133,11 +198,48 @@ class Query(Generic[Unpack[A]]):
             in_axes=0,
             out_axes=0,
         )(self.bufs)
+        return self._outputs(out_tps, buf)
+
+    def map_with_id(
+        self,
+        out_tps: Union[Tuple[Annotated[Any, Component], ...], Annotated[Any, Component]],
+        f: Callable[..., Any],
+    ) -> "Query[Any]":
+        """
+        Like `map`, with each entity's id passed to `f` ahead of its components, e.g. to key
+        random noise so separate entities see independent draws.
+        """
+        buf = jax.vmap(
+            lambda id, b: f(
+                id,
+                *[
+                    from_array(cls, x)  # type: ignore
+                    for (x, cls) in zip(b, self.component_classes)
+                ],
+            ),
+            in_axes=0,
+            out_axes=0,
+        )(self.entity_ids(), self.bufs)
+        return self._outputs(out_tps, buf)
+
+    def entity_ids(self) -> jax.Array:
+        """The id of each entity in the query, in the order `map` visits them."""
+        return jnp.asarray(self.inner.entity_ids(), dtype=jnp.int64)
+
+    def _outputs(
+        self,
+        out_tps: Union[Tuple[Annotated[Any, Component], ...], Annotated[Any, Component]],
+        buf: Any,
+    ) -> "Query[Any]":
+        out_tps_tuple: Tuple[Annotated[Any, Component], ...] = (
+            (out_tps,) if not isinstance(out_tps, tuple) else out_tps
+        )
         (bufs, _) = tree_flatten(buf)
         inner = None
         component_data = []
         component_classes = []
         for out_tp, buf in zip(out_tps_tuple, bufs):
+            buf = _storage_cast(buf, out_tp)
             this_inner = self.inner.map(buf, Metadata.of(out_tp))  # type: ignore
             if inner is None:
                 inner = this_inner
@@ -303,7 +405,7 @@ class GraphQuery(Generic[E]):
             self.inner.map(
                 left_query.inner,
                 right_query.inner,
-                out_bufs[0],
+                _storage_cast(out_bufs[0], return_type),
                 component_data,
             ),
             [component_data],
@@ -319,6 +421,14 @@ class Archetype(Protocol):
     def archetype_name(cls) -> str:
         return snake_case_pattern.sub("_", cls.__name__).lower()
 
+    @classmethod
+    def layout(cls) -> str:
+        """
+        The layout the archetype's columns are recorded in: "entity_major", or "element_major" to
+        keep each element contiguous across entities.
+        """
+        return "entity_major"
+
     def component_data(self) -> list[Metadata]:
         return [Metadata.of(v) for v in typing.get_type_hints(self, include_extras=True).values()]
 
@@ -378,6 +488,22 @@ Inertia = Annotated[
     SpatialInertia,
     Component("inertia", metadata={"priority": 5}),
 ]
+Parent = Annotated[jax.Array, Component("parent", ComponentType.U64, metadata={"priority": 5})]
+AttachOffset = Annotated[
+    SpatialTransform,
+    Component(
+        "attach_offset",
+        metadata={"element_names": "q0,q1,q2,q3,x,y,z", "priority": 5},
+    ),
+]
+WorldDcm = Annotated[
+    jax.Array,
+    Component(
+        "world_dcm",
+        ComponentType(PrimitiveType.F64, (3, 3)),
+        metadata={"priority": 5},
+    ),
+]
 Seed = Annotated[jax.Array, Component("seed", ComponentType.U64, metadata={"priority": 5})]
 SimulationTick = Annotated[
     jax.Array, Component("simulation_tick", ComponentType.F64, metadata={"priority": 7})
@@ -455,7 +581,52 @@ class Scene(Archetype):
     glb: GlbAsset
 
 
+@dataclass
+class StopCondition:
+    """
+    Ends a batch run early once `predicate` holds, so runs stop as soon as their outcome is decided.
+
+    `predicate` is called after every tick with the values of `component` as a numpy array, one row
+    per entity, and the run stops if it's true for any of them:
+
+        el.StopCondition("landed", el.WorldPos, lambda pos: pos[:, 6] <= 0.0)
+
+    The `name` of the condition that stopped a run is recorded as its `stop_reason`.
+    """
+
+    name: str
+    component: Any
+    predicate: Callable[[numpy.ndarray], Any]
+
+    def _check(self, column) -> bool:
+        return bool(numpy.any(self.predicate(numpy.asarray(column.to_numpy()))))
+
+    def _as_tuple(self) -> Tuple[str, str, Callable[[Any], bool]]:
+        component = self.component
+        if not isinstance(component, str):
+            component = Component.name(component)
+        return (self.name, component, self._check)
+
+
 class World(WorldBuilder):
+    def build(
+        self,
+        system: System,
+        *args,
+        dtype_policy: Optional[DtypePolicy] = None,
+        constants: Optional[Constants] = None,
+        **kwargs,
+    ) -> Exec:
+        """
+        Compiles `system` against this world, tracing it under `dtype_policy` and with the physical
+        `constants` overridden, if given.
+        """
+        with contextlib.ExitStack() as stack:
+            for context in (dtype_policy, constants):
+                if context is not None:
+                    stack.enter_context(context)
+            return super().build(system, *args, **kwargs)
+
     def run(
         self,
         system: System,
@@ -464,7 +635,17 @@ class World(WorldBuilder):
         default_playback_speed: float = 1.0,
         max_ticks: Optional[int] = None,
         optimize: bool = False,
+        draws: Optional[dict[str, float]] = None,
+        stop_conditions: Optional[list[StopCondition]] = None,
+        time_limit: Optional[float] = None,
     ):
+        """
+        Runs the simulation as the command line asks. When run as a sample of a batch, `draws`
+        are the parameter values this run was given, recorded with it in `sample.json`.
+
+        A batch run ends before its last tick once any of `stop_conditions` holds, or after
+        `time_limit` seconds of simulation time.
+        """
         current_frame = inspect.currentframe()
         if current_frame is None:
             raise Exception("No current frame")
@@ -478,6 +659,9 @@ class World(WorldBuilder):
             default_playback_speed,
             max_ticks,
             optimize,
+            draws,
+            [condition._as_tuple() for condition in stop_conditions or []],
+            time_limit,
         )
         locals = frame.f_locals
         if addr is not None:
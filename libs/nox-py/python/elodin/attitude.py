@@ -0,0 +1,92 @@
+import typing as ty
+from dataclasses import dataclass
+
+import jax
+import jax.numpy as jnp
+
+# Quaternions are arrays of shape (..., 4), stored like `el.Quaternion` as [x, y, z, w].
+
+
+def multiply(a: jax.Array, b: jax.Array) -> jax.Array:
+    """The Hamilton product `a * b` of each pair of quaternions."""
+    av, aw = a[..., :3], a[..., 3:]
+    bv, bw = b[..., :3], b[..., 3:]
+    v = aw * bv + bw * av + jnp.cross(av, bv)
+    w = aw * bw - jnp.sum(av * bv, axis=-1, keepdims=True)
+    return jnp.concatenate([v, w], axis=-1)
+
+
+def conjugate(q: jax.Array) -> jax.Array:
+    """The conjugate of each quaternion, its inverse if it's a unit quaternion."""
+    return jnp.concatenate([-q[..., :3], q[..., 3:]], axis=-1)
+
+
+def rotation_vector(q: jax.Array) -> jax.Array:
+    """The axis-angle vector of each quaternion, taking the shorter of the two rotations."""
+    q = jnp.where(q[..., 3:] < 0.0, -q, q)
+    v, w = q[..., :3], q[..., 3]
+    sin_half = jnp.linalg.norm(v, axis=-1)
+    angle = 2.0 * jnp.arctan2(sin_half, w)
+    # angle / sin(angle / 2) tends to 2 for small rotations
+    safe = jnp.where(sin_half > 1e-12, sin_half, 1.0)
+    scale = jnp.where(sin_half > 1e-12, angle / safe, 2.0)
+    return v * scale[..., None]
+
+
+def average(quats: jax.Array, weights: ty.Optional[jax.Array] = None) -> jax.Array:
+    """
+    The (weighted) average attitude of `quats`, with shape `(n, 4)`.
+
+    Uses the eigenvalue method: the average is the eigenvector of the largest eigenvalue of
+    `sum(w q q^T)`, which minimizes the weighted sum of squared attitude errors and doesn't care
+    about the sign of each quaternion. The result has a non-negative scalar part.
+    """
+    quats = jnp.asarray(quats)
+    quats = quats / jnp.linalg.norm(quats, axis=-1, keepdims=True)
+    weights = jnp.ones(quats.shape[0]) if weights is None else jnp.asarray(weights)
+    m = jnp.einsum("n,ni,nj->ij", weights, quats, quats)
+    _, vectors = jnp.linalg.eigh(m)
+    mean = vectors[:, -1]
+    return jnp.where(mean[3] < 0.0, -mean, mean)
+
+
+def errors(quats: jax.Array, reference: jax.Array) -> jax.Array:
+    """The rotation vectors from `reference` to each of `quats`, in the reference frame."""
+    return rotation_vector(multiply(conjugate(jnp.asarray(reference)), jnp.asarray(quats)))
+
+
+@dataclass
+class Dispersion:
+    """Attitude dispersion of a set of quaternions about `mean`, with angles in radians."""
+
+    mean: jax.Array
+    # the rotation vector from `mean` to each sample
+    errors: jax.Array
+    covariance: jax.Array
+    rms_angle: jax.Array
+    max_angle: jax.Array
+
+    def angles(self) -> jax.Array:
+        return jnp.linalg.norm(self.errors, axis=-1)
+
+
+def dispersion(quats: jax.Array, reference: ty.Optional[jax.Array] = None) -> Dispersion:
+    """
+    Attitude statistics of `quats`, such as the final attitude of every Monte Carlo run.
+
+    The errors are measured from `reference` if given, and from the average attitude otherwise.
+    The covariance is the 3x3 covariance of the error rotation vectors, i.e of the attitude error
+    in the small angle approximation.
+    """
+    mean = average(quats) if reference is None else jnp.asarray(reference)
+    err = errors(quats, mean)
+    angles = jnp.linalg.norm(err, axis=-1)
+    centered = err - jnp.mean(err, axis=0)
+    covariance = centered.T @ centered / max(err.shape[0] - 1, 1)
+    return Dispersion(
+        mean=mean,
+        errors=err,
+        covariance=covariance,
+        rms_angle=jnp.sqrt(jnp.mean(angles**2)),
+        max_angle=jnp.max(angles),
+    )
@@ -0,0 +1,116 @@
+"""
+Exports the inputs and metrics of a batch campaign into a single SQLite file, so thousands of runs
+can be queried with SQL instead of loading every run directory. DuckDB reads the same file with
+`ATTACH 'campaign.db' (TYPE sqlite)`.
+
+The schema is:
+
+    runs(run_id INTEGER PRIMARY KEY, seed INTEGER, time_step REAL, integrator TEXT,
+         git_hash TEXT, dir TEXT, stop_reason TEXT)
+    inputs(run_id INTEGER, name TEXT, value REAL, PRIMARY KEY (run_id, name))
+    metrics(run_id INTEGER, name TEXT, value REAL, PRIMARY KEY (run_id, name))
+
+The seed is the batch's unsigned 64-bit seed reinterpreted as signed, as SQLite integers are, and
+`stop_reason` names the stop condition that ended a run early, or is null if it ran every tick.
+`inputs` holds the Monte Carlo draws of each run and `metrics` the values computed from its
+results, one row per name, so campaigns with different parameters share the schema. For example,
+the mean miss distance for each thruster setting is
+
+    SELECT i.value, avg(m.value) FROM inputs i JOIN metrics m USING (run_id)
+    WHERE i.name = 'thrust' AND m.name = 'miss' GROUP BY i.value
+"""
+
+import json
+import os
+import sqlite3
+import typing as ty
+
+import polars as pl
+
+import elodin as el
+
+Metric = ty.Callable[[pl.DataFrame], float]
+
+SCHEMA = """
+CREATE TABLE IF NOT EXISTS runs (
+    run_id INTEGER PRIMARY KEY,
+    seed INTEGER,
+    time_step REAL,
+    integrator TEXT,
+    git_hash TEXT,
+    dir TEXT,
+    stop_reason TEXT
+);
+CREATE TABLE IF NOT EXISTS inputs (
+    run_id INTEGER REFERENCES runs (run_id),
+    name TEXT,
+    value REAL,
+    PRIMARY KEY (run_id, name)
+);
+CREATE TABLE IF NOT EXISTS metrics (
+    run_id INTEGER REFERENCES runs (run_id),
+    name TEXT,
+    value REAL,
+    PRIMARY KEY (run_id, name)
+);
+"""
+
+
+def export_sqlite(
+    batch_dir: str, path: str, metrics: ty.Optional[dict[str, Metric]] = None
+) -> int:
+    """
+    Writes every run in `batch_dir` to the SQLite file at `path`, returning the number of runs.
+
+    Each of `metrics` is called with a run's results, as returned by `read_run_results`,
+    and must return a single number. The metrics a run recorded in its `sample.json` are exported
+    too, so runs that kept only their metrics are still included. Runs are read one at a time, so
+    only one run's results are held in memory. Runs already in the file are replaced, so a campaign
+    can be exported again as runs finish or metrics are added.
+    """
+    db = sqlite3.connect(path)
+    try:
+        with db:
+            return _export(db, batch_dir, metrics or {})
+    finally:
+        db.close()
+
+
+def _export(db: sqlite3.Connection, batch_dir: str, metrics: dict[str, Metric]) -> int:
+    db.executescript(SCHEMA)
+    count = 0
+    for entry in sorted(os.scandir(batch_dir), key=lambda entry: entry.name):
+        sample = os.path.join(entry.path, "sample.json")
+        if not entry.is_dir() or not os.path.exists(sample):
+            continue
+        with open(sample) as f:
+            config = json.load(f)
+        run_id = config["run_id"]
+        db.execute("DELETE FROM inputs WHERE run_id = ?", (run_id,))
+        db.execute("DELETE FROM metrics WHERE run_id = ?", (run_id,))
+        db.execute(
+            "INSERT OR REPLACE INTO runs VALUES (?, ?, ?, ?, ?, ?, ?)",
+            (
+                run_id,
+                config["seed"] - 2**64 if config["seed"] >= 2**63 else config["seed"],
+                config["time_step"],
+                config.get("integrator"),
+                config.get("git_hash"),
+                os.path.abspath(entry.path),
+                config.get("stop_reason"),
+            ),
+        )
+        db.executemany(
+            "INSERT INTO inputs VALUES (?, ?, ?)",
+            [(run_id, name, value) for name, value in config.get("draws", {}).items()],
+        )
+        run_metrics = dict(config.get("metrics", {}))
+        if metrics and os.path.isdir(os.path.join(entry.path, "world")):
+            df = el.read_run_results(entry.path)
+            run_metrics.update({name: float(f(df)) for name, f in metrics.items()})
+        db.executemany(
+            "INSERT INTO metrics VALUES (?, ?, ?)",
+            [(run_id, name, value) for name, value in run_metrics.items()],
+        )
+        count += 1
+    return count
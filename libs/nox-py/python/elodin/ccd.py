@@ -0,0 +1,132 @@
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+
+CcdRadius = ty.Annotated[
+    jax.Array, el.Component("ccd_radius", el.ComponentType.F64, metadata={"priority": -1})
+]
+
+
+@dataclass
+class Ccd(el.Archetype):
+    """
+    Opts an entity into continuous collision detection, treating it as a sphere of `ccd_radius`.
+    """
+
+    ccd_radius: CcdRadius = field(default_factory=lambda: jnp.float64(0.0))
+
+
+@dataclass
+class Plane:
+    """A half-space of points with `dot(normal, p) < offset`."""
+
+    normal: jax.Array
+    offset: float = 0.0
+
+    def distance(self, p: jax.Array) -> jax.Array:
+        n = jnp.asarray(self.normal)
+        return jnp.dot(p, n / jnp.linalg.norm(n)) - self.offset
+
+
+@dataclass
+class Sphere:
+    center: jax.Array
+    radius: float
+
+    def distance(self, p: jax.Array) -> jax.Array:
+        return jnp.linalg.norm(p - jnp.asarray(self.center)) - self.radius
+
+
+@dataclass
+class Box:
+    """An axis aligned box."""
+
+    center: jax.Array
+    half_extents: jax.Array
+
+    def distance(self, p: jax.Array) -> jax.Array:
+        q = jnp.abs(p - jnp.asarray(self.center)) - jnp.asarray(self.half_extents)
+        return jnp.linalg.norm(jnp.maximum(q, 0.0)) + jnp.minimum(jnp.max(q), 0.0)
+
+
+Collider = ty.Union[Plane, Sphere, Box]
+
+
+def time_of_impact(
+    colliders: ty.Sequence[Collider],
+    pos: jax.Array,
+    vel: jax.Array,
+    radius: jax.Array,
+    dt: jax.Array,
+    iterations: int = 16,
+) -> jax.Array:
+    """
+    Returns the time in `[0, dt]` at which a sphere moving from `pos` with constant `vel`
+    first touches one of the colliders, or `dt` if it doesn't.
+
+    Uses conservative advancement: every iteration moves the sphere forward by its distance to
+    the nearest collider, which can never step past the first contact.
+    """
+
+    def distance(p):
+        return jnp.min(jnp.stack([c.distance(p) for c in colliders])) - radius
+
+    speed = jnp.maximum(jnp.linalg.norm(vel), 1e-12)
+    t = jnp.float64(0.0)
+    for _ in range(iterations):
+        t = jnp.minimum(t + jnp.maximum(distance(pos + vel * t), 0.0) / speed, dt)
+    return t
+
+
+def system(
+    colliders: ty.Sequence[Collider],
+    restitution: float = 0.0,
+    iterations: int = 16,
+    tolerance: float = 1e-6,
+) -> el.System:
+    """
+    Returns a system that keeps entities with a `CcdRadius` from tunneling through the static
+    `colliders` within a tick.
+
+    The linear motion over the next tick is swept against the colliders, and on contact the
+    velocity into the surface is removed, or reflected when `restitution > 0`, at the contact
+    point. The position is moved back along the corrected velocity so that the integrator's step
+    passes through the contact point, rather than stopping the body where it started the tick.
+    Run it right before the integrator, so the corrected state is used for the step.
+    """
+    colliders = list(colliders)
+
+    def sdf(p):
+        return jnp.min(jnp.stack([c.distance(p) for c in colliders]))
+
+    @el.system
+    def ccd(
+        dt: el.Query[el.SimulationTimeStep],
+        q: el.Query[el.WorldPos, el.WorldVel, CcdRadius],
+    ) -> el.Query[el.WorldPos, el.WorldVel]:
+        step = dt[0]
+
+        def sweep(pos, vel, radius):
+            p = pos.linear()
+            v = vel.linear()
+            toi = time_of_impact(colliders, p, v, radius, step, iterations)
+            contact = p + v * toi
+            hit = sdf(contact) - radius < tolerance
+            normal = jax.grad(sdf)(contact)
+            normal = normal / jnp.maximum(jnp.linalg.norm(normal), 1e-12)
+            approach = jnp.dot(v, normal)
+            hit = hit & (approach < 0.0)
+            response = jnp.where(hit, (1.0 + restitution) * approach, 0.0)
+            v = v - response * normal
+            # the step covers contact - v * toi .. contact + v * (dt - toi)
+            p = jnp.where(hit, contact - v * toi, p)
+            pos = el.SpatialTransform(angular=pos.angular(), linear=p)
+            return pos, el.SpatialMotion(angular=vel.angular(), linear=v)
+
+        return q.map((el.WorldPos, el.WorldVel), sweep)
+
+    return ccd
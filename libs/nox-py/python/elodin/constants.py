@@ -0,0 +1,62 @@
+"""
+The physical constants shared by the force models and orbit helpers, in SI units.
+
+Models look them up with `current()` while their systems are traced, so a world can be built with
+different values for a sensitivity study without touching any model:
+
+    exec = world.build(system, constants=constants.DEFAULT.replace(mu_earth=3.9860e14))
+
+Helpers that run while a world is set up, like `TLE.state` or the scenario presets, read them
+when called, so wrap them in `with constants:` to override those too. The module-level names in
+other modules, like `sun.MU_SUN`, are the default values.
+
+`J2` and `EGM08` used to take the Earth's reference radius as a rounded 6.378e6 m. They now use
+`earth_radius`, the WGS84 6.378137e6 m, which changes their non-spherical terms by a few parts in
+1e5; pass `earth_radius=6.378e6` to reproduce older runs.
+"""
+
+import contextvars
+import dataclasses
+from dataclasses import dataclass, field
+
+
+@dataclass(frozen=True)
+class Constants:
+    gravitational_constant: float = 6.67430e-11  # m^3/(kg s^2), CODATA 2018
+    speed_of_light: float = 299792458.0  # m/s
+    standard_gravity: float = 9.80665  # m/s^2
+    au: float = 149597870700.0  # m
+    # WGS84 equatorial radius and flattening, with the matching EGM96 J2
+    mu_earth: float = 3.986004418e14  # m^3/s^2
+    earth_radius: float = 6.378137e6  # m
+    earth_flattening: float = 1 / 298.257223563
+    earth_j2: float = 1.08262668e-3
+    mu_sun: float = 1.32712440018e20  # m^3/s^2
+    sun_radius: float = 6.957e8  # m
+    mu_moon: float = 4.9028e12  # m^3/s^2
+    # solar radiation pressure on a perfect absorber at 1 AU
+    solar_pressure: float = 4.56e-6  # N/m^2
+    _tokens: list[contextvars.Token] = field(
+        default_factory=list, init=False, repr=False, compare=False
+    )
+
+    def replace(self, **overrides: float) -> "Constants":
+        """A copy with `overrides` applied, raising a `TypeError` for unknown names."""
+        return dataclasses.replace(self, **overrides)
+
+    def __enter__(self) -> "Constants":
+        self._tokens.append(_current.set(self))
+        return self
+
+    def __exit__(self, *_):
+        _current.reset(self._tokens.pop())
+
+
+DEFAULT = Constants()
+
+_current: contextvars.ContextVar[Constants] = contextvars.ContextVar("constants", default=DEFAULT)
+
+
+def current() -> Constants:
+    """The constants in effect, `DEFAULT` unless overridden for the world being built."""
+    return _current.get()
@@ -0,0 +1,160 @@
+"""
+Seeded generators for fields of small bodies, such as orbital debris shells or asteroid clouds,
+for sensor stress tests and collision avoidance scenarios.
+
+The same generator and seed always produce the same field:
+
+    field = debris.DebrisField(sources=[debris.Shell(count=500)], seed=7)
+    ids = field.spawn(world)
+"""
+
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import numpy as np
+
+import elodin as el
+from elodin import constants
+from elodin.tle import pqw_to_inertial
+
+EARTH_RADIUS = constants.DEFAULT.earth_radius
+
+Diameter = ty.Annotated[
+    jax.Array, el.Component("diameter", el.ComponentType.F64, metadata={"unit": "m"})
+]
+
+
+@dataclass
+class PowerLaw:
+    """
+    Diameters with the cumulative count above `d` falling off as `d^-exponent`, between
+    `min_diameter` and `max_diameter` in meters. An exponent of 1.6 roughly follows the NASA
+    breakup model for fragments, larger exponents give more small bodies.
+    """
+
+    min_diameter: float = 0.01
+    max_diameter: float = 1.0
+    exponent: float = 1.6
+
+    def sample(self, rng: np.random.Generator, count: int) -> np.ndarray:
+        # inverse transform sampling of the truncated Pareto distribution
+        u = rng.uniform(size=count)
+        lo, hi = self.min_diameter**-self.exponent, self.max_diameter**-self.exponent
+        return (lo - u * (lo - hi)) ** (-1.0 / self.exponent)
+
+
+@dataclass
+class Shell:
+    """
+    `count` bodies on orbits around the Earth with periapsis and apoapsis between `min_altitude`
+    and `max_altitude`, uniform inclinations in `inclination` and uniform node, periapsis and
+    anomaly angles. `mu` defaults to the Earth's from `constants`.
+    """
+
+    count: int = 100
+    min_altitude: float = 700e3
+    max_altitude: float = 900e3
+    inclination: tuple[float, float] = (0.0, np.pi)
+    mu: ty.Optional[float] = None
+
+    def sample(self, rng: np.random.Generator) -> tuple[np.ndarray, np.ndarray]:
+        c = constants.current()
+        mu = c.mu_earth if self.mu is None else self.mu
+        altitudes = rng.uniform(self.min_altitude, self.max_altitude, size=(self.count, 2))
+        r = c.earth_radius + altitudes
+        periapsis, apoapsis = r.min(axis=1), r.max(axis=1)
+        a = (periapsis + apoapsis) / 2.0
+        e = (apoapsis - periapsis) / (apoapsis + periapsis)
+        inc = rng.uniform(*self.inclination, size=self.count)
+        raan, argp, nu = rng.uniform(0.0, 2.0 * np.pi, size=(3, self.count))
+        p = a * (1.0 - e**2)
+        r = p / (1.0 + e * np.cos(nu))
+        pos, vel = [], []
+        for i in range(self.count):
+            rot = pqw_to_inertial(raan[i], inc[i], argp[i])
+            pos_pqw = r[i] * np.array([np.cos(nu[i]), np.sin(nu[i]), 0.0])
+            speed = np.sqrt(mu / p[i])
+            vel_pqw = speed * np.array([-np.sin(nu[i]), e[i] + np.cos(nu[i]), 0.0])
+            pos.append(rot @ pos_pqw)
+            vel.append(rot @ vel_pqw)
+        return np.array(pos), np.array(vel)
+
+
+@dataclass
+class Cloud:
+    """
+    `count` bodies spread uniformly through a sphere of `radius` around `center`, moving with
+    `velocity` plus an isotropic Gaussian spread of `velocity_std` per axis, like an asteroid
+    field or a fresh breakup.
+    """
+
+    count: int = 100
+    center: np.ndarray = field(default_factory=lambda: np.zeros(3))
+    radius: float = 1e3
+    velocity: np.ndarray = field(default_factory=lambda: np.zeros(3))
+    velocity_std: float = 0.0
+
+    def sample(self, rng: np.random.Generator) -> tuple[np.ndarray, np.ndarray]:
+        direction = rng.normal(size=(self.count, 3))
+        direction /= np.linalg.norm(direction, axis=1, keepdims=True)
+        r = self.radius * rng.uniform(size=(self.count, 1)) ** (1.0 / 3.0)
+        pos = np.asarray(self.center) + r * direction
+        vel = np.asarray(self.velocity) + self.velocity_std * rng.normal(size=(self.count, 3))
+        return pos, vel
+
+
+@dataclass
+class Debris(el.Archetype):
+    diameter: Diameter
+
+
+@dataclass
+class DebrisField:
+    """
+    Bodies drawn from every shell and cloud in `sources`, with diameters from `sizes` and masses
+    of solid spheres of `density` in kg/m^3.
+    """
+
+    sources: list[ty.Union[Shell, Cloud]] = field(default_factory=list)
+    sizes: PowerLaw = field(default_factory=PowerLaw)
+    density: float = 2700.0
+    seed: int = 0
+
+    def sample(self) -> tuple[np.ndarray, np.ndarray, np.ndarray, np.ndarray]:
+        """Returns the positions, velocities, diameters and masses of every body."""
+        rng = np.random.default_rng(self.seed)
+        states = [source.sample(rng) for source in self.sources]
+        if not states:
+            empty = np.zeros((0, 3))
+            return empty, empty, np.zeros(0), np.zeros(0)
+        pos = np.concatenate([pos for pos, _ in states])
+        vel = np.concatenate([vel for _, vel in states])
+        diameter = self.sizes.sample(rng, len(pos))
+        mass = self.density * np.pi / 6.0 * diameter**3
+        return pos, vel, diameter, mass
+
+    def spawn(self, world: el.World, shape: bool = False) -> list[el.EntityId]:
+        """
+        Spawns every body as an `el.Body` with `Debris`, named `debris_<n>`. With `shape`, they
+        are also drawn as spheres of their diameter.
+        """
+        pos, vel, diameter, mass = self.sample()
+        material = world.insert_asset(el.Material.color(0.5, 0.5, 0.5)) if shape else None
+        ids = []
+        for i in range(len(pos)):
+            radius = diameter[i] / 2.0
+            archetypes: list[el.Archetype] = [
+                el.Body(
+                    world_pos=el.SpatialTransform(linear=pos[i]),
+                    world_vel=el.SpatialMotion(linear=vel[i]),
+                    inertia=el.SpatialInertia(mass[i], np.full(3, 0.4 * mass[i] * radius**2)),
+                ),
+                Debris(np.float64(diameter[i])),
+            ]
+            if material is not None:
+                archetypes.append(
+                    el.Shape(world.insert_asset(el.Mesh.sphere(float(radius))), material)
+                )
+            ids.append(world.spawn(archetypes, name=f"debris_{i}"))
+        return ids
@@ -0,0 +1,93 @@
+import math
+import typing as ty
+from dataclasses import dataclass
+
+import jax
+import jax.numpy as jnp
+import numpy as np
+
+import elodin as el
+
+
+@dataclass
+class Delay:
+    """
+    A transport delay: `output` follows `input` `delay` seconds late, e.g sensor latency or an
+    actuator command delay.
+
+    The input is sampled once per tick into a `{name}_buffer` ring buffer, with the slot the next
+    sample goes in kept in `{name}_head`, so each tick writes a single sample however long the
+    delay is. Delays that aren't a whole number of ticks are linearly interpolated between the two
+    nearest samples. `dt` must match the rate the system runs at, and `shape` is the shape of the
+    signal.
+    """
+
+    name: str
+    input: ty.Any
+    output: ty.Any
+    delay: float
+    dt: float
+    shape: tuple[int, ...] = ()
+
+    def __post_init__(self):
+        if self.delay < 0.0:
+            raise ValueError("delay must be non-negative")
+        if el.Component.name(self.input) == el.Component.name(self.output):
+            raise ValueError("a delay can't write back into its own input")
+        ticks = self.delay / self.dt
+        # snap delays that are a whole number of ticks up to floating point error
+        if math.isclose(ticks, round(ticks), abs_tol=1e-9):
+            ticks = float(round(ticks))
+        self.ticks = int(math.floor(ticks))
+        self.frac = ticks - self.ticks
+        self.len = self.ticks + (2 if self.frac > 0.0 else 1)
+        self.Buffer = ty.Annotated[
+            jax.Array,
+            el.Component(
+                f"{self.name}_buffer",
+                el.ComponentType(el.PrimitiveType.F64, (self.len, *self.shape)),
+                metadata={"delay_ticks": str(ticks)},
+            ),
+        ]
+        self.Head = ty.Annotated[jax.Array, el.Component(f"{self.name}_head", el.ComponentType.U64)]
+
+    @classmethod
+    def from_ticks(
+        cls, name: str, input: ty.Any, output: ty.Any, ticks: int, shape: tuple[int, ...] = ()
+    ) -> "Delay":
+        """A delay of a whole number of ticks, whatever rate the system runs at."""
+        return cls(name, input, output, delay=float(ticks), dt=1.0, shape=shape)
+
+    def archetype(self, initial: ty.Optional[jax.Array] = None) -> el.C:
+        """The delay buffer, filled with `initial` so the output holds it until the delay passes."""
+        value = np.zeros(self.shape) if initial is None else np.asarray(initial, dtype=np.float64)
+        buffer = np.broadcast_to(value, (self.len, *self.shape)).copy()
+        return el.C((self.Buffer, self.Head), (buffer, np.uint64(0)))
+
+    def step(
+        self, x: jax.Array, buffer: jax.Array, head: jax.Array
+    ) -> tuple[jax.Array, jax.Array, jax.Array]:
+        """
+        Writes `x` into the buffer at `head`, returning the delayed value, the new buffer and the
+        next head.
+        """
+        buffer = buffer.at[head].set(x)
+
+        def ago(ticks: int) -> jax.Array:
+            return buffer[(head + (self.len - ticks)) % self.len]
+
+        out = ago(self.ticks)
+        if self.frac > 0.0:
+            out = (1.0 - self.frac) * out + self.frac * ago(self.ticks + 1)
+        return out, buffer, (head + 1) % self.len
+
+    def system(self) -> el.System:
+        buffer_ty, head_ty = self.Buffer, self.Head
+        query = el.Query[self.input, self.output, buffer_ty, head_ty]  # type: ignore
+        out_tys = (self.output, buffer_ty, head_ty)
+
+        @el.system
+        def transport_delay(q: query) -> el.Query[out_tys]:  # type: ignore
+            return q.map(out_tys, lambda x, _, buffer, head: self.step(x, buffer, head))
+
+        return transport_delay
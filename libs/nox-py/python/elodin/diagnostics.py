@@ -0,0 +1,131 @@
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+from elodin import constants
+
+KineticEnergy = ty.Annotated[
+    jax.Array, el.Component("kinetic_energy", el.ComponentType.F64, metadata={"unit": "J"})
+]
+PotentialEnergy = ty.Annotated[
+    jax.Array, el.Component("potential_energy", el.ComponentType.F64, metadata={"unit": "J"})
+]
+TotalEnergy = ty.Annotated[
+    jax.Array, el.Component("total_energy", el.ComponentType.F64, metadata={"unit": "J"})
+]
+LinearMomentum = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "linear_momentum",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "unit": "kg m/s"},
+    ),
+]
+AngularMomentum = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "angular_momentum",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "unit": "kg m^2/s"},
+    ),
+]
+
+Potential = ty.Callable[[el.WorldPos, el.Inertia], jax.Array]
+
+
+@dataclass
+class Diagnostics(el.Archetype):
+    """
+    Holds the totals computed by `system`, spawn it once as its own entity.
+    """
+
+    kinetic_energy: KineticEnergy = field(default_factory=lambda: jnp.float64(0.0))
+    potential_energy: PotentialEnergy = field(default_factory=lambda: jnp.float64(0.0))
+    total_energy: TotalEnergy = field(default_factory=lambda: jnp.float64(0.0))
+    linear_momentum: LinearMomentum = field(default_factory=lambda: jnp.zeros(3))
+    angular_momentum: AngularMomentum = field(default_factory=lambda: jnp.zeros(3))
+
+
+def uniform_gravity(g: ty.Optional[float] = None) -> Potential:
+    """
+    The potential energy `m * g * z` of a uniform gravity field pointing down the z axis, with
+    standard gravity unless `g` is set.
+    """
+
+    def potential(pos: el.WorldPos, inertia: el.Inertia) -> jax.Array:
+        accel = constants.current().standard_gravity if g is None else g
+        return inertia.mass() * accel * pos.linear()[2]
+
+    return potential
+
+
+def point_mass_gravity(mu: float) -> Potential:
+    """The potential energy `-mu * m / r` of the gravity field of a point mass at the origin."""
+
+    def potential(pos: el.WorldPos, inertia: el.Inertia) -> jax.Array:
+        return -mu * inertia.mass() / jnp.linalg.norm(pos.linear())
+
+    return potential
+
+
+def body_state(
+    pos: el.WorldPos, vel: el.WorldVel, inertia: el.Inertia
+) -> tuple[jax.Array, jax.Array, jax.Array]:
+    """
+    Returns the kinetic energy, linear momentum, and angular momentum about the world origin
+    of a body.
+
+    The body's origin is assumed to be its center of mass.
+    """
+    rot = pos.angular()
+    mass = inertia.mass()
+    v = vel.linear()
+    omega = vel.angular()
+    # the inertia is diagonal in the body frame, so rotate the angular velocity into it and back
+    spin = rot @ (inertia.inertia_diag() * (rot.inverse() @ omega))
+    kinetic = 0.5 * mass * jnp.dot(v, v) + 0.5 * jnp.dot(omega, spin)
+    linear = mass * v
+    angular = spin + jnp.cross(pos.linear(), linear)
+    return kinetic, linear, angular
+
+
+def system(potential: ty.Optional[Potential] = None) -> el.System:
+    """
+    Returns a system that sums the energy and momentum of every body into the `Diagnostics`
+    entity each tick.
+
+    `potential` computes the potential energy of a single body, e.g `uniform_gravity()`. Without
+    it the potential energy is zero. Run it after the integrator, so the totals describe the
+    state at the end of the tick.
+    """
+
+    @el.system
+    def diagnostics(
+        bodies: el.Query[el.WorldPos, el.WorldVel, el.Inertia],
+        out: el.Query[KineticEnergy, PotentialEnergy, TotalEnergy, LinearMomentum, AngularMomentum],
+    ) -> el.Query[KineticEnergy, PotentialEnergy, TotalEnergy, LinearMomentum, AngularMomentum]:
+        def body(bufs):
+            pos, vel, inertia = [
+                el.from_array(cls, buf) for (buf, cls) in zip(bufs, bodies.component_classes)
+            ]
+            kinetic, linear, angular = body_state(pos, vel, inertia)
+            pot = jnp.float64(0.0) if potential is None else potential(pos, inertia)
+            return kinetic, pot, linear, angular
+
+        kinetic, pot, linear, angular = jax.vmap(body)(bodies.bufs)
+        totals = (
+            jnp.sum(kinetic),
+            jnp.sum(pot),
+            jnp.sum(kinetic) + jnp.sum(pot),
+            jnp.sum(linear, axis=0),
+            jnp.sum(angular, axis=0),
+        )
+        return out.map(
+            (KineticEnergy, PotentialEnergy, TotalEnergy, LinearMomentum, AngularMomentum),
+            lambda *_: totals,
+        )
+
+    return diagnostics
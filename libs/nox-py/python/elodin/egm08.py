@@ -3,6 +3,8 @@
 import jax
 from jax import numpy as jnp
 
+from elodin import constants
+
 jax.config.update("jax_enable_x64", True)
 
 
@@ -17,8 +19,9 @@ class EGM08:
         c_bar_path="",
         s_bar_path="",
     ):
-        self.r_ref = 6.378e6  # Earth's equatorial radius in meters
-        self.mu_earth = 3.986004418e14  # Earth's gravitational parameter in m^3/s^2
+        c = constants.current()
+        self.r_ref = c.earth_radius
+        self.mu_earth = c.mu_earth
 
         self.max_degree = max_degree
 
@@ -4,6 +4,7 @@ from collections.abc import Sequence
 from typing import (
     Annotated,
     Any,
+    Callable,
     ClassVar,
     List,
     Optional,
@@ -25,6 +26,11 @@ class PrimitiveType:
 class Integrator:
     Rk4: Integrator
     SemiImplicit: Integrator
+    Dopri5: Integrator
+
+class Attitude:
+    Quaternion: Attitude
+    Dcm: Attitude
 
 class ComponentType:
     def __init__(self, ty: PrimitiveType, shape: Tuple[int, ...]): ...
@@ -51,6 +57,7 @@ class WorldBuilder:
         self,
         archetypes: Asset | Archetype | list[Archetype],
         name: Optional[str] = None,
+        parent: Optional[EntityId] = None,
     ) -> EntityId: ...
     def insert(self, id: EntityId, archetypes: Asset | Archetype | Sequence[Archetype]): ...
     def insert_asset(self, asset: Asset) -> Handle: ...
@@ -62,6 +69,9 @@ class WorldBuilder:
         default_playback_speed: float = 1.0,
         max_ticks: Optional[int] = None,
         optimize: bool = False,
+        draws: Optional[dict[str, float]] = None,
+        stop_conditions: Sequence[Tuple[str, str, Callable[[Any], bool]]] = [],
+        time_limit: Optional[float] = None,
     ): ...
     def serve(
         self,
@@ -222,6 +232,7 @@ class Metadata:
 class QueryInner:
     def join_query(self, other: QueryInner) -> QueryInner: ...
     def arrays(self) -> list[jax.Array]: ...
+    def entity_ids(self) -> list[int]: ...
     def map(self, ty: jax.Array, f: Metadata) -> Any: ...
     @staticmethod
     def from_builder(
@@ -259,8 +270,13 @@ class Component:
         name: str,
         ty: Optional[ComponentType] = None,
         asset: bool = False,
-        metadata: dict[str, str | bool | int] = {},
-    ): ...
+        metadata: dict[str, str | bool | int | float] = {},
+    ):
+        """
+        Besides "priority" and "element_names", `metadata` accepts a "unit", a display "label",
+        the expected "min" and "max" of the component's values, and a "quantize" step that float
+        values are rounded to in recordings and telemetry.
+        """
     @staticmethod
     def id(component: Any) -> str:
         """
@@ -280,7 +296,33 @@ class Impeller:
 
 class Exec:
     def run(self, ticks: int = 1, show_progress: bool = True): ...
+    def step(self, dt: float) -> int: ...
+    def pause(self): ...
+    def resume(self): ...
+    @property
+    def paused(self) -> bool: ...
+    @property
+    def time_scale(self) -> float: ...
+    @time_scale.setter
+    def time_scale(self, time_scale: float): ...
+    def time(self) -> float: ...
+    def epoch_jd(self, scale: str = "tt") -> float: ...
+    def set_epoch_jd(self, jd: float, scale: str = "utc"): ...
     def profile(self) -> dict[str, float]: ...
+    def system_profile(self) -> dict[str, dict[str, float]]: ...
+    def set_retention(
+        self, every: int = 1, keep_first: int = 0, threshold: Optional[float] = None
+    ): ...
+    def entity_path(self, entity_id: EntityId) -> str: ...
+    def find_entity(self, path: str) -> Optional[EntityId]: ...
+    def raycast(
+        self,
+        origin: list[float],
+        dir: list[float],
+        max_toi: float = float("inf"),
+        ignore: list[EntityId] = [],
+    ) -> Optional[tuple[EntityId, float, list[float]]]: ...
+    def line_of_sight(self, a: EntityId, b: EntityId) -> bool: ...
     def write_to_dir(self, path: str): ...
     def history(self) -> pl.DataFrame: ...
     def column_array(self, name: str) -> pl.Series: ...
@@ -329,6 +371,18 @@ class Glb:
     def __init__(self, path: str): ...
     def bytes(self) -> bytes: ...
 
+class Heightfield:
+    def __init__(
+        self,
+        heights: jax.typing.ArrayLike,
+        spacing: tuple[float, float],
+        origin: tuple[float, float] = (0.0, 0.0),
+        geodetic_origin: Optional[tuple[float, float, float]] = None,
+    ): ...
+    def mesh(self) -> Mesh: ...
+    def asset_name(self) -> str: ...
+    def bytes(self) -> bytes: ...
+
 class BodyAxes:
     def __init__(self, entity: EntityId, scale: float = 1.0): ...
     def asset_name(self) -> str: ...
@@ -361,17 +415,44 @@ class Line3d:
     def asset_name(self) -> str: ...
     def bytes(self) -> bytes: ...
 
+class Capture:
+    def __init__(
+        self,
+        viewport: Panel,
+        output: str,
+        ticks: Optional[list[int]] = None,
+        every: int = 1,
+        start: int = 0,
+        end: Optional[int] = None,
+        width: int = 1280,
+        height: int = 720,
+        fps: Optional[int] = None,
+    ):
+        """
+        Renders `viewport` offscreen in the viewer at each of `ticks`, or every `every` ticks from
+        `start` to `end`, writing `<output>/<tick>.png`. With `fps`, the frames are also encoded
+        into `<output>.mp4` after the last one, which requires `ffmpeg`.
+        """
+    def asset_name(self) -> str: ...
+    def bytes(self) -> bytes: ...
+
 def six_dof(
     time_step: float | None = None,
     sys: Any = None,
     integrator: Integrator = Integrator.Rk4,
+    attitude: Attitude = Attitude.Quaternion,
 ) -> System: ...
+def attach() -> System: ...
 def read_batch_results(path: str) -> Tuple[list[pl.DataFrame], list[int]]: ...
+def read_run_results(path: str) -> pl.DataFrame: ...
+def verify_dir(path: str): ...
 def skew(arr: jax.Array) -> jax.Array: ...
 
 class System:
     def pipe(self, other: System) -> System: ...
     def __or__(self, other: System) -> System: ...
+    def every(self, divisor: int, phase: int = 0) -> System: ...
+    def at_hz(self, frequency: float, phase: int = 0) -> System: ...
 
 class PyFnSystem:
     def __init__(
@@ -0,0 +1,89 @@
+"""
+Components for monitoring the health of a state estimator live, alongside helpers to compute them.
+
+A filter system writes its measurement residual, normalized innovation squared (NIS) and, where
+the truth is known, normalized estimation error squared (NEES) to a `FilterHealth` entity each
+update. Like any other component they are recorded and streamed, so they can be plotted against
+the bounds from `chi2_interval` during a run.
+
+`ukf.UKFState` keeps the `residual` and `nis` of its last update, and computes the NEES of its
+estimate with `nees(truth)`; roci's MEKF streams the same components through `mekf::Health`. With
+`ukf.innovate`, the innovation is `z - z_hat` and its covariance is the returned measurement
+covariance.
+"""
+
+import typing as ty
+from dataclasses import dataclass, field
+from statistics import NormalDist
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+
+Residual = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "residual",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "priority": 12},
+    ),
+]
+Nis = ty.Annotated[jax.Array, el.Component("nis", el.ComponentType.F64, metadata={"priority": 12})]
+Nees = ty.Annotated[
+    jax.Array, el.Component("nees", el.ComponentType.F64, metadata={"priority": 12})
+]
+
+
+@dataclass
+class FilterHealth(el.Archetype):
+    """
+    The consistency statistics of one filter. The residual holds a three-axis measurement, such
+    as a magnetometer or sun sensor reading; filters of other measurements can still record their
+    NIS here and declare their own residual component.
+
+    A unit-vector measurement, like a sun sensor's, only has two degrees of freedom, since its
+    residual is always orthogonal to the vector, so its NIS should be checked against
+    `chi2_interval(2)` rather than 3.
+    """
+
+    residual: Residual = field(default_factory=lambda: jnp.zeros(3))
+    nis: Nis = field(default_factory=lambda: jnp.float64(0.0))
+    nees: Nees = field(default_factory=lambda: jnp.float64(0.0))
+
+
+def nis(residual: jax.Array, covar: jax.Array) -> jax.Array:
+    """
+    The normalized innovation squared of a measurement residual with innovation covariance
+    `covar`. For a consistent filter it is chi-squared distributed with as many degrees of freedom
+    as the measurement has elements.
+    """
+    return residual @ jnp.linalg.solve(covar, residual)
+
+
+def nees(x_hat: jax.Array, covar: jax.Array, truth: jax.Array) -> jax.Array:
+    """
+    The normalized estimation error squared of the estimate `x_hat` with covariance `covar`. For a
+    consistent filter it is chi-squared distributed with as many degrees of freedom as the state
+    has elements.
+    """
+    error = truth - x_hat
+    return error @ jnp.linalg.solve(covar, error)
+
+
+def chi2_interval(dof: int, confidence: float = 0.95, samples: int = 1) -> tuple[float, float]:
+    """
+    The two-sided interval that the mean of `samples` chi-squared values with `dof` degrees of
+    freedom falls in with probability `confidence`, i.e the bounds a consistent filter's averaged
+    NIS or NEES should stay within.
+
+    Uses the Wilson-Hilferty approximation, which is within a few percent for any `dof * samples`
+    above 2.
+    """
+    k = dof * samples
+    tail = NormalDist().inv_cdf(0.5 + confidence / 2.0)
+
+    def quantile(z: float) -> float:
+        return k * (1.0 - 2.0 / (9.0 * k) + z * (2.0 / (9.0 * k)) ** 0.5) ** 3 / samples
+
+    return max(quantile(-tail), 0.0), quantile(tail)
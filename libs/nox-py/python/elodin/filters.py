@@ -0,0 +1,147 @@
+import typing as ty
+from dataclasses import dataclass
+
+import jax
+import jax.numpy as jnp
+import numpy as np
+
+import elodin as el
+
+
+@dataclass
+class TransferFunction:
+    """
+    A discrete transfer function `(b0 + b1 z^-1 + ... ) / (1 + a1 z^-1 + ...)`.
+
+    `den` includes the leading coefficient, which doesn't have to be 1; both polynomials are
+    normalized by it.
+    """
+
+    num: ty.Sequence[float]
+    den: ty.Sequence[float]
+
+    def __post_init__(self):
+        if len(self.den) == 0 or self.den[0] == 0.0:
+            raise ValueError("the leading denominator coefficient must be non-zero")
+        order = max(len(self.num), len(self.den))
+        num = np.zeros(order)
+        den = np.zeros(order)
+        num[: len(self.num)] = self.num
+        den[: len(self.den)] = self.den
+        self.b = num / den[0]
+        self.a = den / den[0]
+
+    @property
+    def order(self) -> int:
+        return len(self.b) - 1
+
+    def dc_gain(self) -> float:
+        return float(np.sum(self.b) / np.sum(self.a))
+
+    def step(self, x: jax.Array, state: jax.Array) -> tuple[jax.Array, jax.Array]:
+        """
+        Filters one sample, returning the output and the next state.
+
+        Implemented in transposed direct form II, so `state` holds `order` delayed values per
+        element of `x`. A zeroth order function is a plain gain, and ignores its state.
+        """
+        y = self.b[0] * x + state[0]
+        if self.order == 0:
+            return y, state
+        next_state = [
+            self.b[i + 1] * x - self.a[i + 1] * y + (state[i + 1] if i + 1 < self.order else 0)
+            for i in range(self.order)
+        ]
+        return y, jnp.stack(next_state)
+
+
+def first_order_low_pass(cutoff: float, dt: float) -> TransferFunction:
+    """A single pole low-pass filter with a cutoff of `cutoff` Hz, sampled every `dt` seconds."""
+    alpha = dt / (dt + 1.0 / (2.0 * np.pi * cutoff))
+    return TransferFunction([alpha], [1.0, alpha - 1.0])
+
+
+def _biquad_omega(freq: float, dt: float) -> tuple[float, float]:
+    w = 2.0 * np.pi * freq * dt
+    return np.cos(w), np.sin(w)
+
+
+def low_pass(cutoff: float, dt: float, q: float = 1.0 / np.sqrt(2.0)) -> TransferFunction:
+    """A second order low-pass biquad, Butterworth by default."""
+    cos, sin = _biquad_omega(cutoff, dt)
+    alpha = sin / (2.0 * q)
+    b = [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0]
+    return TransferFunction(b, [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+
+
+def notch(freq: float, dt: float, q: float = 10.0) -> TransferFunction:
+    """
+    A biquad that removes `freq` Hz, e.g a structural mode, with a stop band about `freq / q`
+    wide.
+    """
+    cos, sin = _biquad_omega(freq, dt)
+    alpha = sin / (2.0 * q)
+    b = [1.0, -2.0 * cos, 1.0]
+    return TransferFunction(b, [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+
+
+@dataclass
+class Filter:
+    """
+    Runs `tf` over the `input` component of every entity once per tick, writing to `output`.
+
+    `shape` is the shape of the signal, e.g `(3,)` for a vector. The filter state lives in its own
+    `{name}_state` component, spawn it with `archetype()`.
+    """
+
+    name: str
+    tf: TransferFunction
+    input: ty.Any
+    output: ty.Any
+    shape: tuple[int, ...] = ()
+
+    def __post_init__(self):
+        self.State = ty.Annotated[
+            jax.Array,
+            el.Component(
+                f"{self.name}_state",
+                el.ComponentType(el.PrimitiveType.F64, (max(self.tf.order, 1), *self.shape)),
+            ),
+        ]
+
+    def archetype(self, initial: ty.Optional[jax.Array] = None) -> el.C:
+        """
+        The filter state, settled on `initial` so the output starts there instead of ramping up
+        from zero.
+        """
+        state = np.zeros((max(self.tf.order, 1), *self.shape))
+        if initial is not None:
+            # steady state of the transposed direct form for a constant input x with output y = g x
+            x = np.asarray(initial, dtype=np.float64)
+            y = self.tf.dc_gain() * x
+            for i in reversed(range(self.tf.order)):
+                below = state[i + 1] if i + 1 < self.tf.order else 0.0
+                state[i] = self.tf.b[i + 1] * x - self.tf.a[i + 1] * y + below
+        return el.C(self.State, state)
+
+    def system(self) -> el.System:
+        tf = self.tf
+        state_ty = self.State
+        if el.Component.name(self.input) == el.Component.name(self.output):
+            query = el.Query[self.input, state_ty]  # type: ignore
+
+            def step(x, state):
+                return tf.step(x, state)
+        else:
+            query = el.Query[self.input, self.output, state_ty]  # type: ignore
+
+            def step(x, _, state):
+                return tf.step(x, state)
+
+        out_tys = (self.output, state_ty)
+
+        @el.system
+        def discrete_filter(q: query) -> el.Query[out_tys]:  # type: ignore
+            return q.map(out_tys, step)
+
+        return discrete_filter
@@ -0,0 +1,123 @@
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+import numpy as np
+
+import elodin as el
+
+Guard = ty.Callable[..., jax.Array]
+Action = ty.Callable[..., ty.Any]
+
+
+@dataclass
+class Transition:
+    """
+    Moves the machine from `src` to `dst` once `guard` holds.
+
+    `guard` is called with the machine's input components and returns a boolean. A `src` of
+    `None` matches any state, which is handy for fault transitions like entering a safe mode.
+    """
+
+    src: ty.Optional[str]
+    dst: str
+    guard: Guard
+
+
+@dataclass
+class StateMachine:
+    """
+    A finite state machine, stored per entity as a `{name}_state` component holding the index of
+    the current state.
+
+    Each tick the first transition (in declaration order) whose source matches and whose guard
+    holds is taken, so at most one transition fires per tick. Taking a transition runs the exit
+    action of the old state and then the entry action of the new one. Actions are called with the
+    input components and return new values for `outputs`, everything else is left untouched.
+
+    The state names are stored in the state component's metadata, and every transition increments
+    the `{name}_transitions` counter, so mode changes show up in telemetry.
+    """
+
+    name: str
+    states: list[str]
+    transitions: list[Transition]
+    inputs: tuple[ty.Any, ...] = ()
+    outputs: tuple[ty.Any, ...] = ()
+    on_entry: dict[str, Action] = field(default_factory=dict)
+    on_exit: dict[str, Action] = field(default_factory=dict)
+
+    def __post_init__(self):
+        if len(set(self.states)) != len(self.states):
+            raise ValueError(f"state machine {self.name} has duplicate states")
+        for state in [t.dst for t in self.transitions] + list(self.on_entry) + list(self.on_exit):
+            self.index(state)
+        for t in self.transitions:
+            if t.src is not None:
+                self.index(t.src)
+        self.State = ty.Annotated[
+            jax.Array,
+            el.Component(
+                f"{self.name}_state",
+                el.ComponentType.U64,
+                metadata={"states": ",".join(self.states)},
+            ),
+        ]
+        self.Transitions = ty.Annotated[
+            jax.Array, el.Component(f"{self.name}_transitions", el.ComponentType.U64)
+        ]
+
+    def index(self, state: str) -> int:
+        """Returns the value of the state component for `state`."""
+        try:
+            return self.states.index(state)
+        except ValueError:
+            raise ValueError(f"state machine {self.name} has no state {state}") from None
+
+    def archetype(self, initial: ty.Optional[str] = None) -> el.C:
+        """The components to spawn on an entity, starting in `initial` or the first state."""
+        state = self.index(initial if initial is not None else self.states[0])
+        return el.C((self.State, self.Transitions), (np.uint64(state), np.uint64(0)))
+
+    def system(self) -> el.System:
+        extra = tuple(o for o in self.outputs if o not in self.inputs)
+        components = (self.State, self.Transitions, *self.inputs, *extra)
+        out_tys = (self.State, self.Transitions, *self.outputs)
+
+        def step(state, count, *values):
+            inputs = values[: len(self.inputs)]
+            columns = self.inputs + extra
+            outputs = tuple(values[columns.index(o)] for o in self.outputs)
+
+            next_state = state
+            fired = jnp.bool_(False)
+            for t in self.transitions:
+                matches = True if t.src is None else state == self.index(t.src)
+                take = ~fired & matches & t.guard(*inputs)
+                next_state = jnp.where(take, jnp.uint64(self.index(t.dst)), next_state)
+                fired = fired | take
+
+            def apply(outputs, actions, active_state):
+                for name, action in actions.items():
+                    run = fired & (active_state == self.index(name))
+                    new = action(*inputs)
+                    new = new if isinstance(new, tuple) else (new,)
+                    outputs = jax.tree_util.tree_map(
+                        lambda a, b: jnp.where(run, a, b), new, outputs
+                    )
+                return outputs
+
+            outputs = apply(outputs, self.on_exit, state)
+            outputs = apply(outputs, self.on_entry, next_state)
+            count = count + fired.astype(count.dtype)
+            return (next_state, count, *outputs)
+
+        query = el.Query[components]  # type: ignore
+        out_query = el.Query[out_tys]  # type: ignore
+
+        def fsm(q: query) -> out_query:  # type: ignore
+            return q.map(out_tys, step)
+
+        fsm.__name__ = self.name
+        return el.system(fsm)
@@ -0,0 +1,320 @@
+import math
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+
+# IGRF reference radius in meters
+IGRF_RADIUS = 6371.2e3
+IGRF13_EPOCH = 2020.0
+
+# IGRF-13 main field (nT) and secular variation (nT/year) at 2020.0, as (n, m, g, h, g_dot, h_dot),
+# truncated to degree 4. That keeps the field within a few percent of the full model in low
+# Earth orbit, where the higher degrees fall off quickly; pass the full table to `Igrf` if needed.
+IGRF13: list[tuple[int, int, float, float, float, float]] = [
+    (1, 0, -29404.8, 0.0, 5.7, 0.0),
+    (1, 1, -1450.9, 4652.5, 7.4, -25.9),
+    (2, 0, -2499.6, 0.0, -11.0, 0.0),
+    (2, 1, 2982.0, -2991.6, -7.0, -30.2),
+    (2, 2, 1677.0, -734.6, -2.1, -22.4),
+    (3, 0, 1363.2, 0.0, 2.2, 0.0),
+    (3, 1, -2381.2, -82.1, -5.9, 6.0),
+    (3, 2, 1236.2, 241.9, 3.1, -1.1),
+    (3, 3, 525.7, -543.4, -12.0, 0.5),
+    (4, 0, 903.0, 0.0, -1.2, 0.0),
+    (4, 1, 809.5, 281.9, -1.6, -0.1),
+    (4, 2, 86.3, -158.4, -5.9, 6.5),
+    (4, 3, -309.4, 199.7, 5.2, 3.6),
+    (4, 4, 48.0, -349.7, -5.1, -5.0),
+]
+
+
+def _legendre(degree: int, cos: jax.Array, sin: jax.Array):
+    """Schmidt semi-normalized Legendre functions and their derivatives wrt colatitude."""
+    p = {(0, 0): jnp.ones_like(cos)}
+    dp = {(0, 0): jnp.zeros_like(cos)}
+    for n in range(1, degree + 1):
+        scale = 1.0 if n == 1 else math.sqrt(1.0 - 1.0 / (2.0 * n))
+        p[(n, n)] = scale * sin * p[(n - 1, n - 1)]
+        dp[(n, n)] = scale * (cos * p[(n - 1, n - 1)] + sin * dp[(n - 1, n - 1)])
+        for m in range(n):
+            k = math.sqrt((n - 1) ** 2 - m**2) if n - 2 >= m else 0.0
+            p2 = p.get((n - 2, m), 0.0)
+            dp2 = dp.get((n - 2, m), 0.0)
+            norm = math.sqrt(n**2 - m**2)
+            p[(n, m)] = ((2 * n - 1) * cos * p[(n - 1, m)] - k * p2) / norm
+            dp[(n, m)] = (
+                (2 * n - 1) * (cos * dp[(n - 1, m)] - sin * p[(n - 1, m)]) - k * dp2
+            ) / norm
+    return p, dp
+
+
+class Igrf:
+    """
+    A spherical harmonic geomagnetic field model, IGRF-13 unless given other coefficients.
+
+    `coefficients` are `(n, m, g, h, g_dot, h_dot)` rows in nT and nT/year, valid around `epoch`
+    (a decimal year).
+    """
+
+    def __init__(
+        self,
+        coefficients: ty.Sequence[tuple[int, int, float, float, float, float]] = IGRF13,
+        epoch: float = IGRF13_EPOCH,
+        radius: float = IGRF_RADIUS,
+    ):
+        self.coefficients = list(coefficients)
+        self.epoch = epoch
+        self.radius = radius
+        self.degree = max(n for n, *_ in self.coefficients)
+
+    def field_ecef(self, pos: jax.Array, year: float = IGRF13_EPOCH) -> jax.Array:
+        """
+        The magnetic field in nT at Earth-fixed positions `pos` in meters, with shape `(..., 3)`,
+        in the same frame.
+        """
+        pos = jnp.asarray(pos)
+        x, y, z = pos[..., 0], pos[..., 1], pos[..., 2]
+        r = jnp.linalg.norm(pos, axis=-1)
+        rho = jnp.sqrt(x**2 + y**2)
+        cos_theta, sin_theta = z / r, rho / r
+        phi = jnp.arctan2(y, x)
+        p, dp = _legendre(self.degree, cos_theta, sin_theta)
+        # B_phi divides by sin(theta), which vanishes on the polar axis
+        safe_sin = jnp.maximum(sin_theta, 1e-12)
+
+        b_r = jnp.zeros_like(r)
+        b_theta = jnp.zeros_like(r)
+        b_phi = jnp.zeros_like(r)
+        dt = year - self.epoch
+        for n, m, g, h, g_dot, h_dot in self.coefficients:
+            g, h = g + g_dot * dt, h + h_dot * dt
+            ratio = (self.radius / r) ** (n + 2)
+            cos_m, sin_m = jnp.cos(m * phi), jnp.sin(m * phi)
+            b_r += (n + 1) * ratio * (g * cos_m + h * sin_m) * p[(n, m)]
+            b_theta -= ratio * (g * cos_m + h * sin_m) * dp[(n, m)]
+            b_phi += ratio * m * (g * sin_m - h * cos_m) * p[(n, m)] / safe_sin
+
+        cos_phi, sin_phi = x / jnp.maximum(rho, 1e-12), y / jnp.maximum(rho, 1e-12)
+        cos_phi = jnp.where(rho > 0.0, cos_phi, 1.0)
+        r_hat = jnp.stack([sin_theta * cos_phi, sin_theta * sin_phi, cos_theta], axis=-1)
+        theta_hat = jnp.stack([cos_theta * cos_phi, cos_theta * sin_phi, -sin_theta], axis=-1)
+        phi_hat = jnp.stack([-sin_phi, cos_phi, jnp.zeros_like(r)], axis=-1)
+        return b_r[..., None] * r_hat + b_theta[..., None] * theta_hat + b_phi[..., None] * phi_hat
+
+    def field_eci(self, pos: jax.Array, jd: jax.Array) -> jax.Array:
+        """
+        The magnetic field in nT at inertial positions `pos` in meters, at Julian date `jd`, in
+        the inertial frame.
+        """
+        rot = earth_rotation(jd)
+        pos_ecef = jnp.einsum("...ij,...j->...i", rot, jnp.asarray(pos))
+        b_ecef = self.field_ecef(pos_ecef, decimal_year(jd))
+        return jnp.einsum("...ji,...j->...i", rot, b_ecef)
+
+
+J2000 = 2451545.0
+
+
+def decimal_year(jd: jax.Array) -> jax.Array:
+    return 2000.0 + (jnp.asarray(jd) - J2000) / 365.25
+
+
+def gmst(jd: jax.Array) -> jax.Array:
+    """Greenwich mean sidereal time in radians, treating UTC as UT1."""
+    degrees = 280.46061837 + 360.98564736629 * (jnp.asarray(jd) - J2000)
+    return jnp.deg2rad(jnp.mod(degrees, 360.0))
+
+
+def earth_rotation(jd: jax.Array) -> jax.Array:
+    """The inertial to Earth-fixed rotation, ignoring precession and nutation."""
+    angle = gmst(jd)
+    cos, sin = jnp.cos(angle), jnp.sin(angle)
+    zero, one = jnp.zeros_like(angle), jnp.ones_like(angle)
+    return jnp.stack(
+        [
+            jnp.stack([cos, sin, zero], axis=-1),
+            jnp.stack([-sin, cos, zero], axis=-1),
+            jnp.stack([zero, zero, one], axis=-1),
+        ],
+        axis=-2,
+    )
+
+
+_default = Igrf()
+
+
+def field_ecef(pos: jax.Array, year: float = IGRF13_EPOCH) -> jax.Array:
+    """The IGRF-13 field in nT at Earth-fixed positions in meters, see `Igrf.field_ecef`."""
+    return _default.field_ecef(pos, year)
+
+
+def field_eci(pos: jax.Array, jd: jax.Array) -> jax.Array:
+    """The IGRF-13 field in nT at inertial positions in meters, see `Igrf.field_eci`."""
+    return _default.field_eci(pos, jd)
+
+
+MagneticField = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "magnetic_field",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "priority": 15},
+    ),
+]
+MagnetometerReading = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "magnetometer",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "priority": 15},
+    ),
+]
+
+
+@dataclass
+class MagnetometerSensor(el.Archetype):
+    """The true inertial field and the body-frame magnetometer reading, both in nT."""
+
+    magnetic_field: MagneticField = field(default_factory=lambda: jnp.zeros(3))
+    magnetometer: MagnetometerReading = field(default_factory=lambda: jnp.zeros(3))
+
+
+@dataclass
+class Magnetometer:
+    """
+    A three-axis magnetometer measuring the IGRF field at each body's position.
+
+    World positions are taken as inertial, in meters, with the simulation starting at Julian date
+    `epoch_jd`. Readings are in the body frame, with a constant `bias` and white noise of standard
+    deviation `noise_std` added, both in nT. The noise is keyed on `seed`, the tick, and the body's
+    entity id, so separate bodies see independent noise.
+    """
+
+    epoch_jd: float = J2000
+    noise_std: float = 0.0
+    bias: jax.Array = field(default_factory=lambda: jnp.zeros(3))
+    seed: int = 0
+    model: Igrf = field(default_factory=Igrf)
+
+    def system(self) -> el.System:
+        @el.system
+        def magnetic_field(
+            tick: el.Query[el.SimulationTick],
+            dt: el.Query[el.SimulationTimeStep],
+            q: el.Query[el.WorldPos],
+        ) -> el.Query[MagneticField]:
+            jd = self.epoch_jd + tick[0] * dt[0] / 86400.0
+            return q.map(MagneticField, lambda pos: self.model.field_eci(pos.linear(), jd))
+
+        @el.system
+        def magnetometer(
+            tick: el.Query[el.SimulationTick],
+            q: el.Query[el.WorldPos, MagneticField],
+        ) -> el.Query[MagnetometerReading]:
+            key = jax.random.fold_in(jax.random.key(self.seed), tick[0].astype(jnp.int64))
+
+            def sense(id: jax.Array, pos: el.SpatialTransform, b: jax.Array) -> jax.Array:
+                body_key = jax.random.fold_in(key, id)
+                noise = self.noise_std * jax.random.normal(body_key, shape=(3,))
+                return pos.angular().inverse() @ b + jnp.asarray(self.bias) + noise
+
+            return q.map_with_id(MagnetometerReading, sense)
+
+        return magnetic_field.pipe(magnetometer)
+
+
+DipoleCommand = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "dipole_command",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "priority": 14},
+    ),
+]
+
+
+@dataclass
+class Magnetorquer:
+    """
+    Three orthogonal torque rods, producing the torque `m × B` from a body-frame dipole command in
+    A m^2, each axis clamped to `max_dipole`.
+
+    The field is read from `MagneticField`, so the `Magnetometer` system has to run earlier in the
+    pipeline, and each body needs a `MagnetometerSensor` and a `DipoleCommand`.
+    """
+
+    max_dipole: float = 0.2
+
+    def dipole(self, command: jax.Array) -> jax.Array:
+        return jnp.clip(command, -self.max_dipole, self.max_dipole)
+
+    def torque(self, command: jax.Array, field_body: jax.Array) -> jax.Array:
+        """The body-frame torque in N m of `command` in a body-frame field in nT."""
+        return jnp.cross(self.dipole(command), field_body * 1e-9)
+
+    def system(self) -> el.System:
+        @el.map
+        def magnetic_torque(
+            command: DipoleCommand, b: MagneticField, pos: el.WorldPos, f: el.Force
+        ) -> el.Force:
+            rot = pos.angular()
+            torque = self.torque(command, rot.inverse() @ b)
+            return f + el.SpatialForce(torque=rot @ torque)
+
+        return magnetic_torque
+
+
+PreviousMagnetometerReading = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "magnetometer_previous",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "priority": 14},
+    ),
+]
+
+
+@dataclass
+class Detumbler(el.Archetype):
+    """The dipole a `BDot` controller commands, and the reading it differentiates against."""
+
+    dipole_command: DipoleCommand = field(default_factory=lambda: jnp.zeros(3))
+    magnetometer_previous: PreviousMagnetometerReading = field(default_factory=lambda: jnp.zeros(3))
+
+
+@dataclass
+class BDot:
+    """
+    The B-dot detumble law `m = -gain dB/dt`, with the field rate taken as the difference of
+    successive body-frame magnetometer readings in T/s and the dipole clamped by `torquer`.
+
+    Since the field turns in the body frame as fast as the body tumbles, commanding against its
+    rate bleeds off the angular velocity across the field. Runs after `Magnetometer` and before
+    the `Magnetorquer` system, with the bodies also holding a `Detumbler`.
+    """
+
+    gain: float = 1e5
+    torquer: Magnetorquer = field(default_factory=Magnetorquer)
+
+    def system(self) -> el.System:
+        @el.system
+        def bdot(
+            dt: el.Query[el.SimulationTimeStep],
+            q: el.Query[MagnetometerReading, PreviousMagnetometerReading],
+        ) -> el.Query[DipoleCommand, PreviousMagnetometerReading]:
+            step = dt[0]
+
+            def command(b: jax.Array, previous: jax.Array):
+                b_dot = (b - previous) * 1e-9 / step
+                m = self.torquer.dipole(-self.gain * b_dot)
+                # there's nothing to differentiate against before the first reading
+                m = jnp.where(jnp.any(previous != 0.0), m, jnp.zeros(3))
+                return m, b
+
+            return q.map((DipoleCommand, PreviousMagnetometerReading), command)
+
+        return bdot
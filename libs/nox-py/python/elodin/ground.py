@@ -0,0 +1,202 @@
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+from elodin import constants
+from elodin.geomag import J2000, earth_rotation
+
+# WGS84
+EARTH_A = constants.DEFAULT.earth_radius
+EARTH_F = constants.DEFAULT.earth_flattening
+EARTH_E2 = EARTH_F * (2 - EARTH_F)
+BOLTZMANN_DB = -228.6  # dBW/K/Hz
+C = constants.DEFAULT.speed_of_light
+
+StationLla = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "station_lla",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "lat,lon,alt", "unit": "rad,rad,m"},
+    ),
+]
+MinElevation = ty.Annotated[
+    jax.Array, el.Component("min_elevation", el.ComponentType.F64, metadata={"unit": "rad"})
+]
+LookAngles = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "look_angles",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "az,el,range", "unit": "rad,rad,m", "priority": 12},
+    ),
+]
+Visible = ty.Annotated[
+    jax.Array, el.Component("visible", el.ComponentType.F64, metadata={"priority": 12})
+]
+LinkMargin = ty.Annotated[
+    jax.Array,
+    el.Component("link_margin", el.ComponentType.F64, metadata={"unit": "dB", "priority": 12}),
+]
+
+
+def lla_to_ecef(lla: jax.Array) -> jax.Array:
+    """The Earth-fixed position in meters of a WGS84 geodetic latitude, longitude and altitude."""
+    lat, lon, alt = lla[..., 0], lla[..., 1], lla[..., 2]
+    c = constants.current()
+    e2 = c.earth_flattening * (2 - c.earth_flattening)
+    n = c.earth_radius / jnp.sqrt(1 - e2 * jnp.sin(lat) ** 2)
+    return jnp.stack(
+        [
+            (n + alt) * jnp.cos(lat) * jnp.cos(lon),
+            (n + alt) * jnp.cos(lat) * jnp.sin(lon),
+            (n * (1 - e2) + alt) * jnp.sin(lat),
+        ],
+        axis=-1,
+    )
+
+
+def ecef_to_enu(lla: jax.Array) -> jax.Array:
+    """The rotation from Earth-fixed axes to the local east, north, up axes at `lla`."""
+    lat, lon = lla[..., 0], lla[..., 1]
+    slat, clat, slon, clon = jnp.sin(lat), jnp.cos(lat), jnp.sin(lon), jnp.cos(lon)
+    zero = jnp.zeros_like(lat)
+    return jnp.stack(
+        [
+            jnp.stack([-slon, clon, zero], axis=-1),
+            jnp.stack([-slat * clon, -slat * slon, clat], axis=-1),
+            jnp.stack([clat * clon, clat * slon, slat], axis=-1),
+        ],
+        axis=-2,
+    )
+
+
+def look_angles(lla: jax.Array, sat_ecef: jax.Array) -> jax.Array:
+    """
+    The azimuth (clockwise from north), elevation and range from a station at `lla` to an
+    Earth-fixed position.
+    """
+    enu = ecef_to_enu(lla) @ (sat_ecef - lla_to_ecef(lla))
+    rng = jnp.linalg.norm(enu)
+    az = jnp.mod(jnp.arctan2(enu[0], enu[1]), 2 * jnp.pi)
+    return jnp.stack([az, jnp.arcsin(enu[2] / rng), rng])
+
+
+@dataclass
+class LinkBudget:
+    """
+    A simple downlink budget. The margin is the received Eb/N0 above `required_ebn0_db`, after
+    free space loss at the slant range and a lump of `losses_db` for atmosphere, pointing and
+    polarization.
+    """
+
+    frequency: float = 2.2e9  # Hz
+    eirp_dbw: float = 10.0
+    g_over_t_dbk: float = 15.0
+    data_rate: float = 1e6  # bits/s
+    required_ebn0_db: float = 9.6
+    losses_db: float = 3.0
+
+    def free_space_loss_db(self, rng: jax.Array) -> jax.Array:
+        wavelength = constants.current().speed_of_light / self.frequency
+        return 20 * jnp.log10(4 * jnp.pi * rng / wavelength)
+
+    def margin_db(self, rng: jax.Array) -> jax.Array:
+        ebn0 = (
+            self.eirp_dbw
+            - self.free_space_loss_db(rng)
+            - self.losses_db
+            + self.g_over_t_dbk
+            - BOLTZMANN_DB
+            - 10 * jnp.log10(self.data_rate)
+        )
+        return ebn0 - self.required_ebn0_db
+
+
+@dataclass
+class GroundStation(el.Archetype):
+    """
+    A station fixed to the rotating Earth. `world_pos` is kept at its inertial position by
+    `Visibility`, so it can be drawn and used for ray casts like any other entity.
+    """
+
+    station_lla: StationLla
+    min_elevation: MinElevation = field(default_factory=lambda: jnp.float64(0.0))
+    world_pos: el.WorldPos = field(default_factory=el.SpatialTransform)
+
+    @staticmethod
+    def from_degrees(lat: float, lon: float, alt: float = 0.0, min_elevation: float = 10.0):
+        return GroundStation(
+            station_lla=jnp.array([jnp.deg2rad(lat), jnp.deg2rad(lon), alt]),
+            min_elevation=jnp.deg2rad(min_elevation),
+        )
+
+
+@dataclass
+class GroundLink(el.Archetype):
+    """
+    The look angles from, and link margin to, the station that sees a satellite best this tick,
+    i.e the one it is highest above the elevation mask of. `visible` is 1 while it is above the
+    mask, and the margin is only meaningful then.
+    """
+
+    look_angles: LookAngles = field(default_factory=lambda: jnp.zeros(3))
+    visible: Visible = field(default_factory=lambda: jnp.float64(0.0))
+    link_margin: LinkMargin = field(default_factory=lambda: jnp.float64(0.0))
+
+
+@dataclass
+class Visibility:
+    """
+    Computes a `GroundLink` for every satellite against every `GroundStation` each tick.
+
+    World positions are taken as inertial, in meters, with the simulation starting at Julian date
+    `epoch_jd`. The Earth is a WGS84 ellipsoid without terrain, so a satellite above the mask is
+    always in view; check other occluders with `Exec.line_of_sight` between ticks.
+    """
+
+    epoch_jd: float = J2000
+    link: LinkBudget = field(default_factory=LinkBudget)
+
+    def system(self) -> el.System:
+        def rotation(tick, dt) -> jax.Array:
+            return earth_rotation(self.epoch_jd + tick[0] * dt[0] / 86400.0)
+
+        @el.system
+        def station_pos(
+            tick: el.Query[el.SimulationTick],
+            dt: el.Query[el.SimulationTimeStep],
+            q: el.Query[StationLla, el.WorldPos],
+        ) -> el.Query[el.WorldPos]:
+            to_inertial = rotation(tick, dt).T
+            return q.map(
+                el.WorldPos,
+                lambda lla, pos: el.SpatialTransform(
+                    linear=to_inertial @ lla_to_ecef(lla), angular=pos.angular()
+                ),
+            )
+
+        @el.system
+        def ground_link(
+            tick: el.Query[el.SimulationTick],
+            dt: el.Query[el.SimulationTimeStep],
+            stations: el.Query[StationLla, MinElevation],
+            q: el.Query[el.WorldPos, LookAngles],
+        ) -> el.Query[LookAngles, Visible, LinkMargin]:
+            to_ecef = rotation(tick, dt)
+            lla, mask = stations.bufs
+
+            def link(pos: el.WorldPos, _: jax.Array):
+                sat = to_ecef @ pos.linear()
+                angles = jax.vmap(look_angles, in_axes=(0, None))(lla, sat)
+                best = jnp.argmax(angles[:, 1] - mask)
+                angles = angles[best]
+                visible = (angles[1] >= mask[best]).astype(jnp.float64)
+                return angles, visible, self.link.margin_db(angles[2])
+
+            return q.map((LookAngles, Visible, LinkMargin), link)
+
+        return station_pos.pipe(ground_link)
@@ -0,0 +1,169 @@
+"""
+Hydrodynamic effectors for underwater and surface vehicles: buoyancy from the displaced volume,
+quadratic drag with per-axis coefficients, and the added mass of the water a body drags along
+when it accelerates.
+
+The water surface is the plane `z = surface` of the world frame, with z pointing up. A hull is
+modelled as a column of `hull_height` centered on the body's origin, so the submerged fraction of
+its volume, and with it every force here, grows linearly from the bottom of the hull to the top.
+A `hull_height` of zero makes a body either fully submerged or not at all, which suits a
+submarine. Ships float where the buoyancy of the submerged fraction balances their weight.
+"""
+
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+from elodin import constants
+
+DisplacedVolume = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "displaced_volume",
+        el.ComponentType.F64,
+        metadata={"unit": "m^3", "priority": 16},
+    ),
+]
+HullHeight = ty.Annotated[
+    jax.Array,
+    el.Component("hull_height", el.ComponentType.F64, metadata={"unit": "m", "priority": 16}),
+]
+CenterOfBuoyancy = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "center_of_buoyancy",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "unit": "m", "priority": 15},
+    ),
+]
+HydroDrag = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "hydro_drag",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "unit": "N s^2/m^2", "priority": 15},
+    ),
+]
+HydroAngularDrag = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "hydro_angular_drag",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "unit": "N m s^2/rad^2", "priority": 15},
+    ),
+]
+AddedMass = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "added_mass",
+        el.ComponentType(el.PrimitiveType.F64, (6,)),
+        metadata={"element_names": "τx,τy,τz,x,y,z", "priority": 15},
+    ),
+]
+SubmergedFraction = ty.Annotated[
+    jax.Array,
+    el.Component("submerged_fraction", el.ComponentType.F64, metadata={"priority": 14}),
+]
+
+
+@dataclass
+class Hull(el.Archetype):
+    """
+    The hydrodynamic properties of a body. Drag coefficients and added mass are per body axis,
+    with drag forces of `-c * v * |v|`. The added mass holds the rotational terms in kg m^2,
+    followed by the translational terms in kg, like a `SpatialForce`.
+    """
+
+    displaced_volume: DisplacedVolume = field(default_factory=lambda: jnp.float64(1.0))
+    hull_height: HullHeight = field(default_factory=lambda: jnp.float64(0.0))
+    center_of_buoyancy: CenterOfBuoyancy = field(default_factory=lambda: jnp.zeros(3))
+    hydro_drag: HydroDrag = field(default_factory=lambda: jnp.zeros(3))
+    hydro_angular_drag: HydroAngularDrag = field(default_factory=lambda: jnp.zeros(3))
+    added_mass: AddedMass = field(default_factory=lambda: jnp.zeros(6))
+    submerged_fraction: SubmergedFraction = field(default_factory=lambda: jnp.float64(0.0))
+
+
+def submerged_fraction(depth: jax.Array, hull_height: jax.Array) -> jax.Array:
+    """The fraction of a hull whose center is `depth` below the surface that is under water."""
+    return jnp.clip(depth / jnp.maximum(hull_height, 1e-9) + 0.5, 0.0, 1.0)
+
+
+def quadratic_drag(vel: jax.Array, coefficients: jax.Array) -> jax.Array:
+    return -coefficients * vel * jnp.abs(vel)
+
+
+@dataclass
+class Hydrodynamics:
+    """
+    Buoyancy and drag on every body with a `Hull`, in water of `density` in kg/m^3 flowing with
+    the world frame `current` in m/s.
+
+    Buoyancy lifts the submerged fraction of the displaced volume, acting at the center of
+    buoyancy so a hull whose center of buoyancy sits above its center of mass rights itself.
+    Drag opposes the body's motion through the water and shrinks with the submerged fraction.
+    """
+
+    density: float = 1025.0
+    surface: float = 0.0
+    current: jax.Array = field(default_factory=lambda: jnp.zeros(3))
+
+    def system(self) -> el.System:
+        @el.system
+        def hydrodynamics(
+            q: el.Query[
+                el.WorldPos,
+                el.WorldVel,
+                DisplacedVolume,
+                HullHeight,
+                CenterOfBuoyancy,
+                HydroDrag,
+                HydroAngularDrag,
+                el.Force,
+            ],
+        ) -> el.Query[el.Force, SubmergedFraction]:
+            g = constants.current().standard_gravity
+
+            def apply(pos, vel, volume, height, center, drag, angular_drag, f):
+                rot = pos.angular()
+                fraction = submerged_fraction(self.surface - pos.linear()[2], height)
+                lift = jnp.array([0.0, 0.0, self.density * g * volume * fraction])
+                flow = rot.inverse() @ (vel.linear() - jnp.asarray(self.current))
+                spin = rot.inverse() @ vel.angular()
+                force = lift + rot @ (fraction * quadratic_drag(flow, drag))
+                torque = jnp.cross(rot @ center, lift)
+                torque = torque + rot @ (fraction * quadratic_drag(spin, angular_drag))
+                return f + el.SpatialForce(torque=torque, linear=force), fraction
+
+            return q.map((el.Force, SubmergedFraction), apply)
+
+        return hydrodynamics
+
+    def added_mass_system(self) -> el.System:
+        """
+        Scales the net force on every body with a `Hull` along each body axis by `M / (M + Ma)`,
+        where `Ma` is the added mass of its submerged fraction, so the body accelerates as if
+        `(M + Ma) a = F`. Pipe it after every effector and before the integrator.
+
+        The cross-coupling of added mass with the body's rotation, like the Munk moment, is left
+        out.
+        """
+
+        @el.system
+        def added_mass(
+            q: el.Query[el.WorldPos, el.Inertia, AddedMass, SubmergedFraction, el.Force],
+        ) -> el.Query[el.Force]:
+            def apply(pos, inertia, added, fraction, f):
+                rot = pos.angular()
+                body = rot.inverse() @ f
+                mass = jnp.broadcast_to(inertia.mass(), (3,))
+                rigid = jnp.concatenate([inertia.inertia_diag(), mass])
+                scale = rigid / (rigid + fraction * added)
+                torque, force = body.torque() * scale[:3], body.force() * scale[3:]
+                return rot @ el.SpatialForce(torque=torque, linear=force)
+
+            return q.map(el.Force, apply)
+
+        return added_mass
@@ -1,12 +1,15 @@
 import numpy as np
 from numpy import linalg as la
 
+from elodin import constants
+
 
 class J2:
     def __init__(self):
-        self.r_ref = 6.378e6  # Earth's equatorial radius in meters
-        self.mu_earth = 3.986004418e14  # Earth's gravitational parameter in m^3/s^2
-        self.J2 = 1.08262668e-3
+        c = constants.current()
+        self.r_ref = c.earth_radius
+        self.mu_earth = c.mu_earth
+        self.J2 = c.earth_j2
 
     def compute_field(self, x, y, z, mass):
         r = np.array([z, y, z])
@@ -0,0 +1,126 @@
+"""
+Navigation state kept next to the truth, for running estimators inside the simulation.
+
+By convention a body's true state stays in `world_pos` and `world_vel`, owned by the integrator,
+while everything on board only ever reads and writes its estimate in `nav_pos` and `nav_vel`. A
+filter updates the estimate from sensor readings, `dead_reckoning` propagates it between updates,
+and `system` writes the estimation error, truth minus estimate, every tick so it's recorded and
+plotted like any other component:
+
+    w.spawn([el.Body(...), navigation.NavState(nav_pos=initial_guess)], name="sat")
+    sys = el.six_dof(...) | navigation.dead_reckoning() | my_filter | navigation.system()
+    w.spawn(navigation.panel(sat))
+"""
+
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+from elodin.attitude import conjugate, multiply, rotation_vector
+
+NavPos = ty.Annotated[
+    el.SpatialTransform,
+    el.Component(
+        "nav_pos",
+        metadata={"element_names": "q0,q1,q2,q3,x,y,z", "priority": 13},
+    ),
+]
+NavVel = ty.Annotated[
+    el.SpatialMotion,
+    el.Component(
+        "nav_vel",
+        metadata={"element_names": "ωx,ωy,ωz,x,y,z", "priority": 13},
+    ),
+]
+NavPosError = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "nav_pos_error",
+        el.ComponentType(el.PrimitiveType.F64, (6,)),
+        metadata={"element_names": "θx,θy,θz,x,y,z", "unit": "rad,m", "priority": 12},
+    ),
+]
+NavVelError = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "nav_vel_error",
+        el.ComponentType(el.PrimitiveType.F64, (6,)),
+        metadata={"element_names": "ωx,ωy,ωz,x,y,z", "unit": "rad/s,m/s", "priority": 12},
+    ),
+]
+
+
+@dataclass
+class NavState(el.Archetype):
+    """A body's estimated pose and velocity, and their errors against the truth."""
+
+    nav_pos: NavPos = field(default_factory=el.SpatialTransform)
+    nav_vel: NavVel = field(default_factory=el.SpatialMotion)
+    nav_pos_error: NavPosError = field(default_factory=lambda: jnp.zeros(6))
+    nav_vel_error: NavVelError = field(default_factory=lambda: jnp.zeros(6))
+
+
+def pose_error(truth: el.SpatialTransform, estimate: el.SpatialTransform) -> jax.Array:
+    """
+    The attitude error, as the rotation vector from the estimated attitude to the true one in the
+    body frame, followed by the position error, truth minus estimate.
+    """
+    q = multiply(conjugate(estimate.angular().vector()), truth.angular().vector())
+    return jnp.concatenate([rotation_vector(q), truth.linear() - estimate.linear()])
+
+
+def motion_error(truth: el.SpatialMotion, estimate: el.SpatialMotion) -> jax.Array:
+    """The angular and then linear velocity error, truth minus estimate."""
+    return jnp.concatenate(
+        [truth.angular() - estimate.angular(), truth.linear() - estimate.linear()]
+    )
+
+
+def system() -> el.System:
+    """
+    Writes the estimation error of every `NavState` body each tick. Run it after the integrator
+    and the estimators, so truth and estimate describe the same instant.
+    """
+
+    @el.system
+    def navigation_error(
+        q: el.Query[el.WorldPos, el.WorldVel, NavPos, NavVel],
+    ) -> el.Query[NavPosError, NavVelError]:
+        def errors(pos, vel, nav_pos, nav_vel):
+            return pose_error(pos, nav_pos), motion_error(vel, nav_vel)
+
+        return q.map((NavPosError, NavVelError), errors)
+
+    return navigation_error
+
+
+def dead_reckoning() -> el.System:
+    """
+    Propagates every estimate by its own velocity over a tick, holding the velocity constant, so
+    the estimate drifts from the truth between measurement updates.
+    """
+
+    @el.system
+    def dead_reckoning(
+        dt: el.Query[el.SimulationTimeStep],
+        q: el.Query[NavPos, NavVel],
+    ) -> el.Query[NavPos]:
+        def propagate(pos: el.SpatialTransform, vel: el.SpatialMotion) -> el.SpatialTransform:
+            rot = pos.angular()
+            att = rot.integrate_body(rot.inverse() @ vel.angular() * dt[0])
+            return el.SpatialTransform(angular=att, linear=pos.linear() + vel.linear() * dt[0])
+
+        return q.map(NavPos, propagate)
+
+    return dead_reckoning
+
+
+def panel(entity: el.EntityId, name: str = "navigation error") -> el.Panel:
+    """Graphs of the position and velocity errors of `entity`, ready to spawn."""
+    return el.Panel.vsplit(
+        el.Panel.graph(el.GraphEntity(entity, NavPosError), name=f"{name}: pose"),
+        el.Panel.graph(el.GraphEntity(entity, NavVelError), name=f"{name}: velocity"),
+    )
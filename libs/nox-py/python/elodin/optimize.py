@@ -0,0 +1,210 @@
+"""
+Scalar objectives computed from each run's results, for design optimization loops such as tuning
+controller gains.
+
+An objective takes a run's results, the data frame `Exec.history` and `read_batch_results`
+return, and reduces it to a single number where lower is better; negate one to maximize it. They
+can be evaluated over the runs of a finished batch, to hand to an external optimizer or to find
+the trade-offs between them:
+
+    objectives = {"miss": miss_distance, "fuel": fuel_used}
+    runs = optimize.evaluate_batch("results", objectives)
+    front = optimize.pareto_front(runs, ["miss", "fuel"])
+
+Or a `Study` drives the simulator directly, building and running a world for every set of
+parameters it's asked about:
+
+    def simulate(params: dict[str, float]) -> el.Exec:
+        exec = make_world(**params).build(system)
+        exec.run(2000)
+        return exec
+
+    study = optimize.Study(simulate, objectives)
+    best = study.minimize("miss", {"kp": 1.0, "kd": 0.1})
+    scipy.optimize.minimize(study.function("miss", ["kp", "kd"]), [1.0, 0.1])
+
+Every evaluation is kept in `study.evaluations`, so a study can be searched with one objective and
+its Pareto front taken over several.
+"""
+
+import json
+import os
+import typing as ty
+from dataclasses import dataclass, field
+
+import numpy as np
+import polars as pl
+
+import elodin as el
+
+Objective = ty.Callable[[pl.DataFrame], float]
+
+
+@dataclass
+class Evaluation:
+    """The objectives of one run, and the parameters it was run with."""
+
+    params: dict[str, float]
+    objectives: dict[str, float]
+    run_id: ty.Optional[int] = None
+    stop_reason: ty.Optional[str] = None
+
+
+def _objectives(df: pl.DataFrame, objectives: dict[str, Objective]) -> dict[str, float]:
+    return {name: float(f(df)) for name, f in objectives.items()}
+
+
+def evaluate_batch(batch_dir: str, objectives: dict[str, Objective]) -> list[Evaluation]:
+    """
+    Evaluates `objectives` over every run in `batch_dir`, in run order. The parameters of each run
+    are the draws recorded in its `sample.json`. Runs are read one at a time, so only one run's
+    results are held in memory. Runs that recorded only their metrics are skipped.
+    """
+    evaluations = []
+    for entry in os.scandir(batch_dir):
+        sample = os.path.join(entry.path, "sample.json")
+        if not entry.is_dir() or not os.path.exists(sample):
+            continue
+        if not os.path.isdir(os.path.join(entry.path, "world")):
+            continue
+        with open(sample) as f:
+            config = json.load(f)
+        evaluation = Evaluation(
+            params=config.get("draws", {}),
+            objectives=_objectives(el.read_run_results(entry.path), objectives),
+            run_id=config["run_id"],
+            stop_reason=config.get("stop_reason"),
+        )
+        evaluations.append(evaluation)
+    return sorted(evaluations, key=lambda evaluation: evaluation.run_id)
+
+
+def _dominates(a: np.ndarray, b: np.ndarray) -> bool:
+    return bool(np.all(a <= b) and np.any(a < b))
+
+
+def pareto_front(evaluations: ty.Sequence[Evaluation], names: list[str]) -> list[Evaluation]:
+    """
+    The evaluations no other one beats on every objective in `names`, minimizing each, in their
+    original order.
+    """
+    points = [np.array([e.objectives[name] for name in names]) for e in evaluations]
+    return [
+        evaluation
+        for evaluation, point in zip(evaluations, points)
+        if not any(_dominates(other, point) for other in points)
+    ]
+
+
+def nelder_mead(
+    f: ty.Callable[[np.ndarray], float],
+    x0: ty.Sequence[float],
+    step: ty.Union[float, ty.Sequence[float]] = 0.1,
+    max_evals: int = 200,
+    tol: float = 1e-8,
+) -> ty.Tuple[np.ndarray, float]:
+    """
+    Minimizes `f` without gradients, starting from a simplex around `x0` with sides of `step`,
+    and returns the best point found and its value. `f` is called at most `max_evals` times, and
+    the search ends early once the values across the simplex are within `tol` of each other.
+    """
+    if max_evals < 1:
+        raise ValueError("max_evals must be at least 1")
+    x0 = np.asarray(x0, dtype=np.float64)
+    n = len(x0)
+    simplex = np.vstack([x0, x0 + np.diag(np.broadcast_to(step, (n,)))])[:max_evals]
+    values = np.array([f(x) for x in simplex])
+    evals = len(simplex)
+    while evals < max_evals:
+        order = np.argsort(values)
+        simplex, values = simplex[order], values[order]
+        if values[-1] - values[0] <= tol:
+            break
+        centroid = simplex[:-1].mean(axis=0)
+        worst = simplex[-1].copy()
+        reflected = centroid + (centroid - worst)
+        reflected_value = f(reflected)
+        evals += 1
+        if reflected_value < values[0]:
+            simplex[-1], values[-1] = reflected, reflected_value
+            if evals < max_evals:
+                expanded = centroid + 2.0 * (centroid - worst)
+                expanded_value = f(expanded)
+                evals += 1
+                if expanded_value < reflected_value:
+                    simplex[-1], values[-1] = expanded, expanded_value
+        elif reflected_value < values[-2]:
+            simplex[-1], values[-1] = reflected, reflected_value
+        elif evals < max_evals:
+            contracted = centroid + 0.5 * (worst - centroid)
+            contracted_value = f(contracted)
+            evals += 1
+            if contracted_value < values[-1]:
+                simplex[-1], values[-1] = contracted, contracted_value
+            else:
+                # shrink towards the best point, as far as the budget allows
+                for i in range(1, min(n + 1, 1 + max_evals - evals)):
+                    simplex[i] = simplex[0] + 0.5 * (simplex[i] - simplex[0])
+                    values[i] = f(simplex[i])
+                    evals += 1
+    best = np.argmin(values)
+    return simplex[best], float(values[best])
+
+
+@dataclass
+class Study:
+    """
+    Evaluates `objectives` on the results of `simulate`, which builds and runs a world for a set of
+    named parameters and returns its `Exec`.
+    """
+
+    simulate: ty.Callable[[dict[str, float]], el.Exec]
+    objectives: dict[str, Objective]
+    evaluations: list[Evaluation] = field(default_factory=list)
+
+    def evaluate(self, params: dict[str, float]) -> Evaluation:
+        exec = self.simulate(params)
+        evaluation = Evaluation(dict(params), _objectives(exec.history(), self.objectives))
+        self.evaluations.append(evaluation)
+        return evaluation
+
+    def function(self, name: str, parameters: list[str]) -> ty.Callable[[np.ndarray], float]:
+        """
+        Objective `name` as a function of a vector holding `parameters` in order, the form
+        external optimizers expect.
+        """
+
+        def f(x: np.ndarray) -> float:
+            params = {p: float(v) for p, v in zip(parameters, x, strict=True)}
+            return self.evaluate(params).objectives[name]
+
+        return f
+
+    def minimize(
+        self,
+        name: str,
+        x0: dict[str, float],
+        step: ty.Union[float, dict[str, float]] = 0.1,
+        max_evals: int = 200,
+        tol: float = 1e-8,
+    ) -> Evaluation:
+        """
+        Searches for the parameters minimizing objective `name` with Nelder-Mead, starting from
+        `x0`, and returns the best evaluation. `step` sets the initial search scale, per parameter
+        if it's a dict; see `nelder_mead` for the rest.
+        """
+        parameters = list(x0)
+        if isinstance(step, dict):
+            step = [step[p] for p in parameters]
+        start = len(self.evaluations)
+        nelder_mead(
+            self.function(name, parameters),
+            [x0[p] for p in parameters],
+            step,
+            max_evals,
+            tol,
+        )
+        return min(self.evaluations[start:], key=lambda e: e.objectives[name])
+
+    def pareto_front(self, names: list[str]) -> list[Evaluation]:
+        return pareto_front(self.evaluations, names)
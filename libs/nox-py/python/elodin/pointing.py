@@ -0,0 +1,196 @@
+"""
+Attitude errors against commanded pointing frames, ready to feed an attitude controller.
+
+A commanded frame aligns a body `primary_axis` exactly with a target direction, and turns about
+it to bring a `secondary_axis` as close as it can to a second direction. The error is the
+rotation vector from the commanded attitude to the current one, in the body frame, so a PD law is
+just `torque = -kp * error - kd * rate_error`.
+"""
+
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+from elodin.attitude import conjugate, multiply, rotation_vector
+from elodin.geomag import J2000
+from elodin.sun import sun_position
+
+AttitudeCommand = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "attitude_command",
+        el.ComponentType(el.PrimitiveType.F64, (4,)),
+        metadata={"element_names": "x,y,z,w", "priority": 14},
+    ),
+]
+AttitudeError = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "attitude_error",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "unit": "rad", "priority": 14},
+    ),
+]
+RateError = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "rate_error",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "unit": "rad/s", "priority": 14},
+    ),
+]
+
+MODES = ("nadir", "sun", "velocity", "inertial")
+
+
+@dataclass
+class PointingError(el.Archetype):
+    attitude_command: AttitudeCommand = field(
+        default_factory=lambda: jnp.array([0.0, 0.0, 0.0, 1.0])
+    )
+    attitude_error: AttitudeError = field(default_factory=lambda: jnp.zeros(3))
+    rate_error: RateError = field(default_factory=lambda: jnp.zeros(3))
+
+
+def _unit(v: jax.Array) -> jax.Array:
+    return v / jnp.linalg.norm(v)
+
+
+def _from_matrix(m: jax.Array) -> jax.Array:
+    # Shepperd's method, dividing by the largest of the four candidate pivots
+    (m00, m01, m02), (m10, m11, m12), (m20, m21, m22) = m
+    candidates = jnp.array(
+        [
+            [m21 - m12, m02 - m20, m10 - m01, 1.0 + m00 + m11 + m22],
+            [1.0 + m00 - m11 - m22, m01 + m10, m02 + m20, m21 - m12],
+            [m01 + m10, 1.0 - m00 + m11 - m22, m12 + m21, m02 - m20],
+            [m02 + m20, m12 + m21, 1.0 - m00 - m11 + m22, m10 - m01],
+        ]
+    )
+    pivot = jnp.argmax(jnp.array([m00 + m11 + m22, m00, m11, m22]))
+    q = candidates[pivot]
+    return q / jnp.linalg.norm(q)
+
+
+def align(
+    primary_axis: jax.Array,
+    primary_target: jax.Array,
+    secondary_axis: jax.Array,
+    secondary_target: jax.Array,
+) -> jax.Array:
+    """
+    The attitude, as an `[x, y, z, w]` quaternion rotating body vectors into the world frame, that
+    points the body `primary_axis` along `primary_target` and brings `secondary_axis` as close to
+    `secondary_target` as it can. The targets must not be parallel.
+    """
+    b1 = _unit(jnp.asarray(primary_axis, dtype=jnp.float64))
+    b2 = _unit(jnp.cross(b1, jnp.asarray(secondary_axis, dtype=jnp.float64)))
+    w1 = _unit(jnp.asarray(primary_target))
+    w2 = _unit(jnp.cross(w1, jnp.asarray(secondary_target)))
+    body = jnp.stack([b1, b2, jnp.cross(b1, b2)], axis=1)
+    world = jnp.stack([w1, w2, jnp.cross(w1, w2)], axis=1)
+    return _from_matrix(world @ body.T)
+
+
+def error_quaternion(q: jax.Array, command: jax.Array) -> jax.Array:
+    """The rotation from the `command` attitude to `q`, with a non-negative scalar part."""
+    err = multiply(conjugate(jnp.asarray(command)), jnp.asarray(q))
+    return jnp.where(err[..., 3:] < 0.0, -err, err)
+
+
+def attitude_error(q: jax.Array, command: jax.Array) -> jax.Array:
+    """
+    The axis-angle vector of the rotation from `command` to `q`, in radians. The rotation's axis
+    has the same coordinates in either frame, so this is also the error in the body frame.
+    """
+    return rotation_vector(error_quaternion(q, command))
+
+
+def nadir_frame(pos: jax.Array, vel: jax.Array) -> ty.Tuple[jax.Array, jax.Array]:
+    """The nadir direction, with the velocity as the secondary target."""
+    return -pos, vel
+
+
+def velocity_frame(pos: jax.Array, vel: jax.Array) -> ty.Tuple[jax.Array, jax.Array]:
+    """The velocity direction, with nadir as the secondary target."""
+    return vel, -pos
+
+
+def sun_frame(pos: jax.Array, sun_pos: jax.Array) -> ty.Tuple[jax.Array, jax.Array]:
+    """The direction to the Sun, with nadir as the secondary target."""
+    return sun_pos - pos, -pos
+
+
+def orbit_rate(pos: jax.Array, vel: jax.Array) -> jax.Array:
+    """The inertial angular velocity of the radial direction, followed by nadir and ram frames."""
+    return jnp.cross(pos, vel) / jnp.dot(pos, pos)
+
+
+@dataclass
+class Pointing:
+    """
+    Writes the commanded attitude of every `PointingError` body each tick, along with its attitude
+    and rate errors in the body frame.
+
+    `mode` is one of:
+
+    - `"nadir"`, pointing `primary_axis` at the center of the Earth and `secondary_axis` along the
+      velocity.
+    - `"sun"`, pointing `primary_axis` at the Sun and `secondary_axis` towards nadir, with world
+      positions taken as Earth-centered inertial and the simulation starting at `epoch_jd`.
+    - `"velocity"`, pointing `primary_axis` along the velocity and `secondary_axis` towards nadir.
+    - `"inertial"`, pointing `primary_axis` along the fixed direction `target` and `secondary_axis`
+      towards `secondary_target`.
+
+    Nadir and velocity frames turn with the orbit, which is fed forward into the rate error; the
+    Sun and inertial frames are taken as fixed.
+    """
+
+    mode: str = "nadir"
+    primary_axis: jax.Array = field(default_factory=lambda: jnp.array([0.0, 0.0, 1.0]))
+    secondary_axis: jax.Array = field(default_factory=lambda: jnp.array([1.0, 0.0, 0.0]))
+    target: jax.Array = field(default_factory=lambda: jnp.array([1.0, 0.0, 0.0]))
+    secondary_target: jax.Array = field(default_factory=lambda: jnp.array([0.0, 0.0, 1.0]))
+    epoch_jd: float = J2000
+
+    def __post_init__(self):
+        if self.mode not in MODES:
+            raise ValueError(f"unknown pointing mode {self.mode}")
+
+    def command(
+        self, pos: jax.Array, vel: jax.Array, sun_pos: jax.Array
+    ) -> ty.Tuple[jax.Array, jax.Array]:
+        """The commanded attitude and its inertial angular velocity."""
+        if self.mode == "nadir":
+            targets, rate = nadir_frame(pos, vel), orbit_rate(pos, vel)
+        elif self.mode == "velocity":
+            targets, rate = velocity_frame(pos, vel), orbit_rate(pos, vel)
+        elif self.mode == "sun":
+            targets, rate = sun_frame(pos, sun_pos), jnp.zeros(3)
+        else:
+            targets = jnp.asarray(self.target), jnp.asarray(self.secondary_target)
+            rate = jnp.zeros(3)
+        primary, secondary = targets
+        return align(self.primary_axis, primary, self.secondary_axis, secondary), rate
+
+    def system(self) -> el.System:
+        @el.system
+        def pointing(
+            tick: el.Query[el.SimulationTick],
+            dt: el.Query[el.SimulationTimeStep],
+            q: el.Query[el.WorldPos, el.WorldVel, AttitudeCommand],
+        ) -> el.Query[AttitudeCommand, AttitudeError, RateError]:
+            sun_pos = sun_position(self.epoch_jd + tick[0] * dt[0] / 86400.0)
+
+            def errors(pos: el.WorldPos, vel: el.WorldVel, _: jax.Array):
+                rot = pos.angular()
+                command, rate = self.command(pos.linear(), vel.linear(), sun_pos)
+                body_rate = rot.inverse() @ (vel.angular() - rate)
+                return command, attitude_error(rot.vector(), command), body_rate
+
+            return q.map((AttitudeCommand, AttitudeError, RateError), errors)
+
+        return pointing
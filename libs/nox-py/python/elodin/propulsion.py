@@ -0,0 +1,216 @@
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+from elodin import constants
+
+# standard gravity in m/s^2, used to convert Isp to exhaust velocity
+G0 = constants.DEFAULT.standard_gravity
+
+Throttle = ty.Annotated[
+    jax.Array, el.Component("throttle", el.ComponentType.F64, metadata={"priority": 20})
+]
+AmbientPressure = ty.Annotated[
+    jax.Array,
+    el.Component("ambient_pressure", el.ComponentType.F64, metadata={"priority": 19}),
+]
+PropellantMass = ty.Annotated[
+    jax.Array,
+    el.Component("propellant_mass", el.ComponentType.F64, metadata={"priority": 18}),
+]
+MassFlow = ty.Annotated[
+    jax.Array, el.Component("mass_flow", el.ComponentType.F64, metadata={"priority": 17})
+]
+EngineThrust = ty.Annotated[
+    jax.Array,
+    el.Component("engine_thrust", el.ComponentType.F64, metadata={"priority": 17}),
+]
+
+
+@dataclass
+class Propulsion(el.Archetype):
+    throttle: Throttle = field(default_factory=lambda: jnp.float64(0.0))
+    ambient_pressure: AmbientPressure = field(default_factory=lambda: jnp.float64(0.0))
+    propellant_mass: PropellantMass = field(default_factory=lambda: jnp.float64(0.0))
+    mass_flow: MassFlow = field(default_factory=lambda: jnp.float64(0.0))
+    engine_thrust: EngineThrust = field(default_factory=lambda: jnp.float64(0.0))
+
+
+@dataclass
+class Engine:
+    """
+    A throttleable engine whose specific impulse is tabulated against throttle and ambient pressure.
+
+    `isp_table` has shape `(len(throttle_table), len(pressure_table))` and is in seconds,
+    pressures are in Pa. Below `min_throttle` the engine shuts off, and it stops producing
+    thrust once the propellant is exhausted.
+    """
+
+    throttle_table: jax.Array
+    pressure_table: jax.Array
+    isp_table: jax.Array
+    max_mass_flow: float  # kg/s at full throttle
+    min_throttle: float = 0.0
+    thrust_axis: jax.Array = field(default_factory=lambda: jnp.array([0.0, 0.0, 1.0]))
+    # position of the nozzle relative to the center of mass, in the body frame
+    nozzle_offset: jax.Array = field(default_factory=lambda: jnp.zeros(3))
+
+    def isp(self, throttle: jax.Array, pressure: jax.Array) -> jax.Array:
+        # bilinear interpolation, first along the pressure axis of every row, then across throttle
+        by_throttle = jax.vmap(lambda row: jnp.interp(pressure, self.pressure_table, row))(
+            jnp.asarray(self.isp_table)
+        )
+        return jnp.interp(throttle, self.throttle_table, by_throttle)
+
+    def mass_flow(self, throttle: jax.Array, propellant: jax.Array) -> jax.Array:
+        throttle = jnp.clip(throttle, 0.0, 1.0)
+        throttle = jnp.where(throttle < self.min_throttle, 0.0, throttle)
+        return jnp.where(propellant > 0.0, throttle * self.max_mass_flow, 0.0)
+
+    def thrust(self, throttle: jax.Array, pressure: jax.Array, propellant: jax.Array) -> jax.Array:
+        return self.mass_flow(throttle, propellant) * G0 * self.isp(throttle, pressure)
+
+    def system(self) -> el.System:
+        """
+        Returns a system that computes mass flow and thrust, applies the thrust to the body,
+        and removes the burned propellant from the body's mass.
+
+        The rotational inertia is scaled with the mass, which assumes the propellant
+        is distributed like the rest of the body.
+        """
+        axis = jnp.asarray(self.thrust_axis) / jnp.linalg.norm(jnp.asarray(self.thrust_axis))
+        offset = jnp.asarray(self.nozzle_offset)
+
+        @el.map
+        def engine(
+            throttle: Throttle, pressure: AmbientPressure, propellant: PropellantMass
+        ) -> tuple[MassFlow, EngineThrust]:
+            return self.mass_flow(throttle, propellant), self.thrust(throttle, pressure, propellant)
+
+        @el.map
+        def apply_thrust(thrust: EngineThrust, pos: el.WorldPos, f: el.Force) -> el.Force:
+            force = axis * thrust
+            torque = jnp.cross(offset, force)
+            rot = pos.angular()
+            return f + el.SpatialForce(torque=rot @ torque, linear=rot @ force)
+
+        @el.system
+        def deplete(
+            dt: el.Query[el.SimulationTimeStep],
+            q: el.Query[MassFlow, PropellantMass, el.Inertia],
+        ) -> el.Query[PropellantMass, el.Inertia]:
+            step = dt[0]
+
+            def burn(mass_flow, propellant, inertia):
+                burned = jnp.minimum(mass_flow * step, propellant)
+                mass = inertia.mass()
+                new_mass = mass - burned
+                new_inertia = el.SpatialInertia(new_mass, inertia.inertia_diag() * new_mass / mass)
+                return propellant - burned, new_inertia
+
+            return q.map((PropellantMass, el.Inertia), burn)
+
+        return engine.pipe(apply_thrust).pipe(deplete)
+
+
+ThrustCommand = ty.Annotated[
+    jax.Array,
+    el.Component("thrust_command", el.ComponentType.F64, metadata={"priority": 20}),
+]
+ThrusterThrust = ty.Annotated[
+    jax.Array,
+    el.Component("thruster_thrust", el.ComponentType.F64, metadata={"priority": 17}),
+]
+
+
+@dataclass
+class ThrusterState(el.Archetype):
+    """Thruster components; don't spawn it alongside `Propulsion`, as both hold the propellant."""
+
+    thrust_command: ThrustCommand = field(default_factory=lambda: jnp.float64(0.0))
+    thruster_thrust: ThrusterThrust = field(default_factory=lambda: jnp.float64(0.0))
+    propellant_mass: PropellantMass = field(default_factory=lambda: jnp.float64(0.0))
+
+
+@dataclass
+class Thruster:
+    """
+    A fixed-axis thruster driven by a thrust command in N, such as an RCS jet.
+
+    Commands are clamped to `max_thrust`, and dropped when they're below `min_thrust` or would
+    deliver less than `min_impulse_bit` (N s) over a tick. The delivered thrust follows the command
+    with a first order lag of time constant `rise_time`, and is cut once the propellant runs out.
+    Propellant burns at `thrust / (isp * G0)`, the rocket equation's mass flow.
+    """
+
+    isp: float  # s
+    max_thrust: float
+    min_thrust: float = 0.0
+    min_impulse_bit: float = 0.0
+    rise_time: float = 0.0
+    thrust_axis: jax.Array = field(default_factory=lambda: jnp.array([0.0, 0.0, 1.0]))
+    # position of the nozzle relative to the center of mass, in the body frame
+    nozzle_offset: jax.Array = field(default_factory=lambda: jnp.zeros(3))
+
+    def command(self, command: jax.Array, dt: jax.Array) -> jax.Array:
+        command = jnp.clip(command, 0.0, self.max_thrust)
+        dropped = (command < self.min_thrust) | (command * dt < self.min_impulse_bit)
+        return jnp.where(dropped, 0.0, command)
+
+    def thrust(
+        self, command: jax.Array, prev: jax.Array, propellant: jax.Array, dt: jax.Array
+    ) -> jax.Array:
+        target = self.command(command, dt)
+        if self.rise_time > 0.0:
+            thrust = prev + (1.0 - jnp.exp(-dt / self.rise_time)) * (target - prev)
+        else:
+            thrust = target
+        return jnp.where(propellant > 0.0, thrust, 0.0)
+
+    def system(self) -> el.System:
+        """
+        Returns a system that updates the delivered thrust, applies it to the body, and removes the
+        burned propellant from the body's mass, scaling the rotational inertia like `Engine`.
+        """
+        axis = jnp.asarray(self.thrust_axis) / jnp.linalg.norm(jnp.asarray(self.thrust_axis))
+        offset = jnp.asarray(self.nozzle_offset)
+        exhaust_vel = self.isp * G0
+
+        @el.system
+        def respond(
+            dt: el.Query[el.SimulationTimeStep],
+            q: el.Query[ThrustCommand, ThrusterThrust, PropellantMass],
+        ) -> el.Query[ThrusterThrust]:
+            step = dt[0]
+            return q.map(
+                ThrusterThrust,
+                lambda command, prev, propellant: self.thrust(command, prev, propellant, step),
+            )
+
+        @el.map
+        def apply_thrust(thrust: ThrusterThrust, pos: el.WorldPos, f: el.Force) -> el.Force:
+            force = axis * thrust
+            torque = jnp.cross(offset, force)
+            rot = pos.angular()
+            return f + el.SpatialForce(torque=rot @ torque, linear=rot @ force)
+
+        @el.system
+        def deplete(
+            dt: el.Query[el.SimulationTimeStep],
+            q: el.Query[ThrusterThrust, PropellantMass, el.Inertia],
+        ) -> el.Query[PropellantMass, el.Inertia]:
+            step = dt[0]
+
+            def burn(thrust, propellant, inertia):
+                burned = jnp.minimum(thrust / exhaust_vel * step, propellant)
+                mass = inertia.mass()
+                new_mass = mass - burned
+                new_inertia = el.SpatialInertia(new_mass, inertia.inertia_diag() * new_mass / mass)
+                return propellant - burned, new_inertia
+
+            return q.map((PropellantMass, el.Inertia), burn)
+
+        return respond.pipe(apply_thrust).pipe(deplete)
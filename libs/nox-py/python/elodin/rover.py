@@ -0,0 +1,121 @@
+"""
+Wheel-ground contact for rover mobility studies: a spring-damper suspension under each wheel,
+pressing it into the terrain, and slip-based friction between the tire and the ground.
+
+The terrain is anything with a `sample(xy) -> (height, normal)` method giving the ground height
+and its upward unit normal below a world position, with z pointing up. `FlatGround` is a level
+plane, and `elodin.terrain.Heightfield` follows a grid of elevations.
+"""
+
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+
+DriveCommand = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "drive_command",
+        el.ComponentType(el.PrimitiveType.F64, (2,)),
+        metadata={"element_names": "left,right", "unit": "m/s", "priority": 16},
+    ),
+]
+
+
+@dataclass
+class RoverDrive(el.Archetype):
+    """The commanded rim speed of the wheels on each side of a skid-steered rover, in m/s."""
+
+    drive_command: DriveCommand = field(default_factory=lambda: jnp.zeros(2))
+
+
+class Terrain(ty.Protocol):
+    def sample(self, xy: jax.Array) -> ty.Tuple[jax.Array, jax.Array]: ...
+
+
+@dataclass
+class FlatGround:
+    height: float = 0.0
+
+    def sample(self, xy: jax.Array) -> ty.Tuple[jax.Array, jax.Array]:
+        return jnp.float64(self.height), jnp.array([0.0, 0.0, 1.0])
+
+
+def friction(slip: jax.Array, load: jax.Array, mu: float, slip_velocity: float) -> jax.Array:
+    """
+    The friction force the ground exerts on a tire under `load` N, where `slip` is the rim speed
+    less the velocity of the wheel over the ground in m/s. It grows linearly with the slip up to
+    `slip_velocity`, and is the Coulomb friction `mu * load` beyond it.
+    """
+    speed = jnp.linalg.norm(slip)
+    return mu * load * slip / jnp.maximum(speed, slip_velocity)
+
+
+@dataclass
+class WheelContact:
+    """
+    Ground contact forces on every body with a `RoverDrive`, from wheels of `radius` m hanging
+    below the body-frame points in the rows of `mounts`. Wheels mounted on the +y side are driven
+    by the left command.
+
+    Each suspension runs along the body -z axis, with a spring of `stiffness` N/m and a damper of
+    `damping` N s/m that are unloaded at `rest_length` m. The wheels are speed controlled, so they
+    turn at the commanded rim speed whatever the load, and the slip between the rim and the ground
+    drives the rover forward, while sideways slip resists skidding. The mass and travel of the
+    wheels themselves are left out.
+    """
+
+    mounts: jax.Array
+    radius: float
+    stiffness: float
+    damping: float
+    rest_length: float
+    mu: float = 0.8
+    slip_velocity: float = 0.05
+    terrain: Terrain = field(default_factory=FlatGround)
+
+    def wheel(self, mount, pos, vel, command):
+        """The world frame force and torque about the body origin of the wheel below `mount`."""
+        rot = pos.angular()
+        down = rot @ jnp.array([0.0, 0.0, -1.0])
+        arm = rot @ mount
+        height, normal = self.terrain.sample((pos.linear() + arm)[:2])
+        # distance from the mount to the ground along the suspension
+        reach = (pos.linear()[2] + arm[2] - height) / jnp.maximum(-down[2], 1e-6)
+        compression = jnp.maximum(self.rest_length + self.radius - reach, 0.0)
+        contact = arm + down * jnp.minimum(reach, self.rest_length + self.radius)
+        point_vel = vel.linear() + jnp.cross(vel.angular(), contact)
+        rate = -jnp.dot(point_vel, normal)
+        load = (self.stiffness * compression + self.damping * rate) * (compression > 0.0)
+        load = jnp.maximum(load, 0.0)
+
+        heading = rot @ jnp.array([1.0, 0.0, 0.0])
+        forward = heading - jnp.dot(heading, normal) * normal
+        forward = forward / jnp.maximum(jnp.linalg.norm(forward), 1e-9)
+        lateral = jnp.cross(normal, forward)
+        rim = jnp.where(mount[1] > 0.0, command[0], command[1])
+        slip = jnp.array([rim - jnp.dot(point_vel, forward), -jnp.dot(point_vel, lateral)])
+        traction = friction(slip, load, self.mu, self.slip_velocity)
+
+        force = load * normal + traction[0] * forward + traction[1] * lateral
+        return force, jnp.cross(contact, force)
+
+    def system(self) -> el.System:
+        mounts = jnp.asarray(self.mounts, dtype=jnp.float64)
+
+        @el.system
+        def wheel_contact(
+            q: el.Query[el.WorldPos, el.WorldVel, DriveCommand, el.Force],
+        ) -> el.Query[el.Force]:
+            def apply(pos, vel, command, f):
+                for mount in mounts:
+                    force, torque = self.wheel(mount, pos, vel, command)
+                    f = f + el.SpatialForce(torque=torque, linear=force)
+                return f
+
+            return q.map(el.Force, apply)
+
+        return wheel_contact
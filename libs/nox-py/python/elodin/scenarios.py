@@ -0,0 +1,321 @@
+"""
+Ready-made, parameterized simulation setups to start from instead of a blank world.
+
+Every preset returns a `Scenario` holding the world and the system that drives it, built only from
+the public `elodin` API so they double as worked examples:
+
+    scenario = scenarios.detumble(initial_rate=[0.2, -0.1, 0.15])
+    exec = scenario.build()
+    exec.run(1000)
+"""
+
+import typing as ty
+from dataclasses import dataclass
+
+import jax
+import jax.numpy as jnp
+import numpy as np
+
+import elodin as el
+from elodin import constants
+
+MU_EARTH = constants.DEFAULT.mu_earth
+EARTH_RADIUS = constants.DEFAULT.earth_radius
+J2 = constants.DEFAULT.earth_j2
+STANDARD_GRAVITY = constants.DEFAULT.standard_gravity
+
+
+@dataclass
+class Scenario:
+    world: el.World
+    system: el.System
+    sim_time_step: float
+
+    def build(self, **kwargs) -> el.Exec:
+        return self.world.build(self.system, sim_time_step=self.sim_time_step, **kwargs)
+
+    def run(self, **kwargs):
+        return self.world.run(self.system, sim_time_step=self.sim_time_step, **kwargs)
+
+
+def _j2_gravity(pos: jax.Array, mass: jax.Array) -> jax.Array:
+    c = constants.current()
+    r = jnp.linalg.norm(pos)
+    z2 = (pos[2] / r) ** 2
+    k = 1.5 * c.earth_j2 * (c.earth_radius / r) ** 2
+    scale = jnp.array([1.0 - k * (5.0 * z2 - 1.0)] * 2 + [1.0 - k * (5.0 * z2 - 3.0)])
+    return -c.mu_earth * mass * pos * scale / r**3
+
+
+def _clip_norm(v: jax.Array, max_norm: float) -> jax.Array:
+    norm = jnp.linalg.norm(v)
+    return v * jnp.minimum(1.0, max_norm / jnp.maximum(norm, 1e-12))
+
+
+def _circular_orbit(radius: float, inclination: float) -> tuple[np.ndarray, np.ndarray]:
+    speed = np.sqrt(constants.current().mu_earth / radius)
+    pos = np.array([radius, 0.0, 0.0])
+    vel = speed * np.array([0.0, np.cos(inclination), np.sin(inclination)])
+    return pos, vel
+
+
+# marks bodies pulled by `_orbit_gravity`
+OrbitGravity = ty.Annotated[jax.Array, el.Component("orbit_gravity", el.ComponentType.F64)]
+
+
+@el.map
+def _orbit_gravity(
+    _: OrbitGravity, force: el.Force, pos: el.WorldPos, inertia: el.Inertia
+) -> el.Force:
+    return force + el.SpatialForce(linear=_j2_gravity(pos.linear(), inertia.mass()))
+
+
+StationKeepingRadius = ty.Annotated[
+    jax.Array, el.Component("station_keeping_radius", el.ComponentType.F64)
+]
+
+
+def leo_station_keeping(
+    altitude: float = 500e3,
+    inclination: float = np.deg2rad(51.6),
+    mass: float = 100.0,
+    max_thrust: float = 0.5,
+    bandwidth: float = 2e-3,
+    sim_time_step: float = 1.0,
+) -> Scenario:
+    """
+    A satellite in a circular low Earth orbit under J2, with a radial thruster holding its orbit
+    radius at `altitude` against the J2 oscillation.
+
+    The controller is a critically damped PD loop with a natural frequency of `bandwidth` rad/s,
+    saturated at `max_thrust` newtons.
+    """
+    radius = constants.current().earth_radius + altitude
+    kp, kd = bandwidth**2, 2.0 * bandwidth
+
+    @el.map
+    def hold_radius(
+        target: StationKeepingRadius,
+        force: el.Force,
+        pos: el.WorldPos,
+        vel: el.WorldVel,
+        inertia: el.Inertia,
+    ) -> el.Force:
+        r = pos.linear()
+        r_hat = r / jnp.linalg.norm(r)
+        error = jnp.linalg.norm(r) - target
+        rate = jnp.dot(vel.linear(), r_hat)
+        thrust = jnp.clip(-inertia.mass() * (kp * error + kd * rate), -max_thrust, max_thrust)
+        return force + el.SpatialForce(linear=thrust * r_hat)
+
+    pos, vel = _circular_orbit(radius, inclination)
+    world = el.World()
+    world.spawn(
+        [
+            el.Body(
+                world_pos=el.SpatialTransform(linear=pos),
+                world_vel=el.SpatialMotion(linear=vel),
+                inertia=el.SpatialInertia(mass),
+            ),
+            el.C((OrbitGravity, StationKeepingRadius), (np.float64(0.0), np.float64(radius))),
+        ],
+        name="Satellite",
+    )
+    system = el.six_dof(sim_time_step, _orbit_gravity.pipe(hold_radius))
+    return Scenario(world, system, sim_time_step)
+
+
+DetumbleGain = ty.Annotated[jax.Array, el.Component("detumble_gain", el.ComponentType.F64)]
+
+
+def detumble(
+    initial_rate: ty.Sequence[float] = (0.2, -0.1, 0.15),
+    inertia: ty.Sequence[float] = (0.04, 0.05, 0.03),
+    mass: float = 4.0,
+    gain: float = 0.01,
+    max_torque: float = 1e-3,
+    sim_time_step: float = 1.0 / 60.0,
+) -> Scenario:
+    """
+    A tumbling small satellite spinning at `initial_rate` rad/s, brought to rest by a rate damping
+    torque `-gain * ω` limited to `max_torque` N m, as an ideal stand-in for a B-dot or reaction
+    wheel controller.
+    """
+
+    @el.map
+    def damp_rate(gain: DetumbleGain, force: el.Force, vel: el.WorldVel) -> el.Force:
+        torque = _clip_norm(-gain * vel.angular(), max_torque)
+        return force + el.SpatialForce(torque=torque)
+
+    world = el.World()
+    world.spawn(
+        [
+            el.Body(
+                world_vel=el.SpatialMotion(angular=np.asarray(initial_rate, dtype=np.float64)),
+                inertia=el.SpatialInertia(mass, np.asarray(inertia, dtype=np.float64)),
+            ),
+            el.C(DetumbleGain, np.float64(gain)),
+        ],
+        name="Satellite",
+    )
+    return Scenario(world, el.six_dof(sim_time_step, damp_rate), sim_time_step)
+
+
+RendezvousEdge = ty.Annotated[el.Edge, el.Component("rendezvous_edge", el.ComponentType.Edge)]
+
+
+@dataclass
+class Rendezvous(el.Archetype):
+    """Steers the chaser, the left of the edge, towards the target, the right of the edge."""
+
+    edge: RendezvousEdge
+
+
+def rendezvous(
+    separation: float = 200.0,
+    standoff: float = 10.0,
+    altitude: float = 400e3,
+    chaser_mass: float = 50.0,
+    target_mass: float = 400.0,
+    max_thrust: float = 1.0,
+    bandwidth: float = 0.05,
+    sim_time_step: float = 0.1,
+) -> Scenario:
+    """
+    A chaser starting `separation` meters behind a target in a circular orbit, closing to a hold
+    point `standoff` meters behind it with a PD controller on the relative position and velocity.
+    """
+    kp, kd = bandwidth**2, 2.0 * bandwidth
+
+    @el.system
+    def approach(
+        graph: el.GraphQuery[RendezvousEdge],
+        query: el.Query[el.WorldPos, el.WorldVel, el.Inertia],
+    ) -> el.Query[el.Force]:
+        def steer(force, pos, vel, inertia, target_pos, target_vel, _):
+            target_v = target_vel.linear()
+            hold = target_pos.linear() - standoff * target_v / jnp.linalg.norm(target_v)
+            error = pos.linear() - hold
+            rate = vel.linear() - target_v
+            thrust = _clip_norm(-inertia.mass() * (kp * error + kd * rate), max_thrust)
+            return force + el.SpatialForce(linear=thrust)
+
+        return graph.edge_fold(query, query, el.Force, el.SpatialForce(), steer)
+
+    pos, vel = _circular_orbit(constants.current().earth_radius + altitude, 0.0)
+    along_track = vel / np.linalg.norm(vel)
+    world = el.World()
+    target = world.spawn(
+        [
+            el.Body(
+                world_pos=el.SpatialTransform(linear=pos),
+                world_vel=el.SpatialMotion(linear=vel),
+                inertia=el.SpatialInertia(target_mass),
+            ),
+            el.C(OrbitGravity, np.float64(0.0)),
+        ],
+        name="Target",
+    )
+    chaser = world.spawn(
+        [
+            el.Body(
+                world_pos=el.SpatialTransform(linear=pos - separation * along_track),
+                world_vel=el.SpatialMotion(linear=vel),
+                inertia=el.SpatialInertia(chaser_mass),
+            ),
+            el.C(OrbitGravity, np.float64(0.0)),
+        ],
+        name="Chaser",
+    )
+    world.spawn(Rendezvous(el.Edge(chaser, target)), name="Chaser -> Target")
+    system = el.six_dof(sim_time_step, approach.pipe(_orbit_gravity))
+    return Scenario(world, system, sim_time_step)
+
+
+PendulumLink = ty.Annotated[el.Edge, el.Component("pendulum_link", el.ComponentType.Edge)]
+PendulumAnchor = ty.Annotated[
+    jax.Array, el.Component("pendulum_anchor", el.ComponentType(el.PrimitiveType.F64, (3,)))
+]
+
+
+@dataclass
+class Link(el.Archetype):
+    link: PendulumLink
+
+
+def pendulum_stack(
+    count: int = 3,
+    length: float = 1.0,
+    mass: float = 1.0,
+    initial_angle: float = np.deg2rad(30.0),
+    stiffness: float = 1e4,
+    damping: float = 1.0,
+    sim_time_step: float = 1.0 / 1000.0,
+) -> Scenario:
+    """
+    `count` point masses hanging from a fixed anchor at the origin, each joined to the next by a
+    stiff damped spring of rest length `length`, released at `initial_angle` from vertical.
+
+    The springs stand in for rigid rods, so `sim_time_step` has to resolve their natural frequency
+    `sqrt(stiffness / mass)`.
+    """
+    if count < 1:
+        raise ValueError("a pendulum stack needs at least one mass")
+    gravity = np.array([0.0, 0.0, -constants.current().standard_gravity])
+
+    def spring(pos, vel, other_pos, other_vel):
+        d = other_pos - pos
+        dist = jnp.linalg.norm(d)
+        u = d / dist
+        return (stiffness * (dist - length) + damping * jnp.dot(other_vel - vel, u)) * u
+
+    @el.map
+    def hang(
+        anchor: PendulumAnchor, force: el.Force, pos: el.WorldPos, vel: el.WorldVel
+    ) -> el.Force:
+        f = spring(pos.linear(), vel.linear(), anchor, jnp.zeros(3))
+        return force + el.SpatialForce(linear=f)
+
+    @el.map
+    def uniform_gravity(force: el.Force, inertia: el.Inertia) -> el.Force:
+        return force + el.SpatialForce(linear=inertia.mass() * gravity)
+
+    @el.system
+    def links(
+        graph: el.GraphQuery[PendulumLink], query: el.Query[el.WorldPos, el.WorldVel]
+    ) -> el.Query[el.Force]:
+        def pull(force, pos, vel, other_pos, other_vel):
+            f = spring(pos.linear(), vel.linear(), other_pos.linear(), other_vel.linear())
+            return force + el.SpatialForce(linear=f)
+
+        return graph.edge_fold(query, query, el.Force, el.SpatialForce(), pull)
+
+    world = el.World()
+    direction = np.array([np.sin(initial_angle), 0.0, -np.cos(initial_angle)])
+    bobs = []
+    for i in range(count):
+        components = [
+            el.Body(
+                world_pos=el.SpatialTransform(linear=(i + 1) * length * direction),
+                inertia=el.SpatialInertia(mass),
+            )
+        ]
+        if i == 0:
+            components.append(el.C(PendulumAnchor, np.zeros(3)))
+        bobs.append(world.spawn(components, name=f"Bob {i}"))
+    for a, b in zip(bobs, bobs[1:]):
+        world.spawn(Link(el.Edge(a, b)), name="Link")
+        world.spawn(Link(el.Edge(b, a)), name="Link")
+
+    effectors = hang.pipe(uniform_gravity)
+    if count > 1:
+        effectors = links.pipe(effectors)
+    return Scenario(world, el.six_dof(sim_time_step, effectors), sim_time_step)
+
+
+PRESETS: dict[str, ty.Callable[..., Scenario]] = {
+    "leo_station_keeping": leo_station_keeping,
+    "detumble": detumble,
+    "rendezvous": rendezvous,
+    "pendulum_stack": pendulum_stack,
+}
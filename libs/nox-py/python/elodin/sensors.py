@@ -0,0 +1,167 @@
+"""
+Attitude sensor models for estimator development: a star tracker measuring the attitude of its
+own frame, and a rate gyro measuring the angular velocity about its axes.
+
+Each sensor is mounted on the body by a `mounting` quaternion, stored as [x, y, z, w], that
+rotates sensor-frame vectors into the body frame. An estimator recovers the body attitude from a
+star tracker reading `q` as `q * mounting.inverse()`. World positions are taken as Earth-centered
+inertial, like the other orbit models. Noise is keyed on each sensor's `seed`, the tick, and the
+body's entity id, so separate bodies see independent noise.
+"""
+
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+from elodin import constants
+from elodin.geomag import J2000
+from elodin.sun import sun_position
+
+StarTrackerReading = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "star_tracker",
+        el.ComponentType(el.PrimitiveType.F64, (4,)),
+        metadata={"element_names": "x,y,z,w", "priority": 15},
+    ),
+]
+StarTrackerValid = ty.Annotated[
+    jax.Array,
+    el.Component("star_tracker_valid", el.ComponentType.F64, metadata={"priority": 15}),
+]
+GyroReading = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "gyro",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "unit": "rad/s", "priority": 15},
+    ),
+]
+GyroBias = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "gyro_bias",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "unit": "rad/s", "priority": 14},
+    ),
+]
+
+
+@dataclass
+class StarTrackerSensor(el.Archetype):
+    star_tracker: StarTrackerReading = field(
+        default_factory=lambda: jnp.array([0.0, 0.0, 0.0, 1.0])
+    )
+    star_tracker_valid: StarTrackerValid = field(default_factory=lambda: jnp.float64(0.0))
+
+
+@dataclass
+class GyroSensor(el.Archetype):
+    """The gyro reading, and the drifting part of its bias in the sensor frame."""
+
+    gyro: GyroReading = field(default_factory=lambda: jnp.zeros(3))
+    gyro_bias: GyroBias = field(default_factory=lambda: jnp.zeros(3))
+
+
+def _cone_clear(boresight: jax.Array, target: jax.Array, half_angle: jax.Array) -> jax.Array:
+    """Whether the unit `target` direction is more than `half_angle` off the boresight."""
+    return jnp.dot(boresight, target) < jnp.cos(half_angle)
+
+
+@dataclass
+class StarTracker:
+    """
+    A star tracker looking along its +z axis, reading the attitude of its frame with white noise
+    of `noise_std` rad about each sensor axis. Noise about the boresight is usually several times
+    larger than across it.
+
+    The tracker drops out while the Sun is within `sun_exclusion` rad of the boresight, or the
+    Earth's limb is within `earth_exclusion` rad of it. A dropped reading holds its last value
+    with `star_tracker_valid` cleared. The simulation starts at Julian date `epoch_jd`.
+    """
+
+    mounting: jax.Array = field(default_factory=lambda: jnp.array([0.0, 0.0, 0.0, 1.0]))
+    noise_std: jax.Array = field(default_factory=lambda: jnp.zeros(3))
+    sun_exclusion: float = 0.5
+    earth_exclusion: float = 0.35
+    epoch_jd: float = J2000
+    seed: int = 0
+
+    def system(self) -> el.System:
+        @el.system
+        def star_tracker(
+            tick: el.Query[el.SimulationTick],
+            dt: el.Query[el.SimulationTimeStep],
+            q: el.Query[el.WorldPos, StarTrackerReading],
+        ) -> el.Query[StarTrackerReading, StarTrackerValid]:
+            sun_pos = sun_position(self.epoch_jd + tick[0] * dt[0] / 86400.0)
+            key = jax.random.fold_in(jax.random.key(self.seed), tick[0].astype(jnp.int64))
+            earth_radius = constants.current().earth_radius
+
+            def sense(id: jax.Array, pos: el.SpatialTransform, last: jax.Array):
+                attitude = pos.angular() * el.Quaternion(jnp.asarray(self.mounting))
+                boresight = attitude @ jnp.array([0.0, 0.0, 1.0])
+                r = pos.linear()
+                to_sun = (sun_pos - r) / jnp.linalg.norm(sun_pos - r)
+                nadir = -r / jnp.linalg.norm(r)
+                limb = jnp.arcsin(jnp.minimum(earth_radius / jnp.linalg.norm(r), 1.0))
+                valid = _cone_clear(boresight, to_sun, self.sun_exclusion) & _cone_clear(
+                    boresight, nadir, limb + self.earth_exclusion
+                )
+                body_key = jax.random.fold_in(key, id)
+                noise = jnp.asarray(self.noise_std) * jax.random.normal(body_key, shape=(3,))
+                reading = attitude.integrate_body(noise).vector()
+                return jnp.where(valid, reading, last), valid.astype(jnp.float64)
+
+            return q.map_with_id((StarTrackerReading, StarTrackerValid), sense)
+
+        return star_tracker
+
+
+@dataclass
+class Gyro:
+    """
+    A three-axis rate gyro, reading the body's inertial angular velocity about the sensor axes.
+
+    The reading carries the constant turn-on `bias`, a drifting bias, and angle random walk:
+    white rate noise with a density of `angle_random_walk` rad/√s. The drift models bias
+    instability as a first-order Gauss-Markov process with a steady-state standard deviation of
+    `bias_instability` rad/s and a correlation time of `bias_correlation_time` seconds.
+    """
+
+    mounting: jax.Array = field(default_factory=lambda: jnp.array([0.0, 0.0, 0.0, 1.0]))
+    bias: jax.Array = field(default_factory=lambda: jnp.zeros(3))
+    angle_random_walk: float = 0.0
+    bias_instability: float = 0.0
+    bias_correlation_time: float = 1000.0
+    seed: int = 0
+
+    def system(self) -> el.System:
+        @el.system
+        def gyro(
+            tick: el.Query[el.SimulationTick],
+            dt: el.Query[el.SimulationTimeStep],
+            q: el.Query[el.WorldPos, el.WorldVel, GyroBias],
+        ) -> el.Query[GyroReading, GyroBias]:
+            key = jax.random.fold_in(jax.random.key(self.seed), tick[0].astype(jnp.int64))
+            decay = jnp.exp(-dt[0] / self.bias_correlation_time)
+            drift_std = self.bias_instability * jnp.sqrt(1.0 - decay**2)
+            white_std = self.angle_random_walk / jnp.sqrt(dt[0])
+
+            def sense(
+                id: jax.Array, pos: el.SpatialTransform, vel: el.SpatialMotion, drift: jax.Array
+            ):
+                attitude = pos.angular() * el.Quaternion(jnp.asarray(self.mounting))
+                rate = attitude.inverse() @ vel.angular()
+                body_key = jax.random.fold_in(key, id)
+                drift_noise, white_noise = jax.random.normal(body_key, shape=(2, 3))
+                drift = decay * drift + drift_std * drift_noise
+                reading = rate + jnp.asarray(self.bias) + drift + white_std * white_noise
+                return reading, drift
+
+            return q.map_with_id((GyroReading, GyroBias), sense)
+
+        return gyro
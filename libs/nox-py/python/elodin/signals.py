@@ -0,0 +1,79 @@
+import typing as ty
+from dataclasses import dataclass
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+
+
+@dataclass
+class Route:
+    """
+    Copies the value of the `src` component into the `dst` component of the same entity, passing
+    it through optional blocks along the way: `select` picks an element (or several, given a list)
+    out of a vector source, then the value is scaled by `gain`, shifted by `offset`, and clamped
+    to `[min, max]`.
+
+    `gain` and `offset` can be scalars or arrays matching the selected value's shape.
+    """
+
+    src: ty.Any
+    dst: ty.Any
+    select: ty.Optional[ty.Union[int, ty.Sequence[int]]] = None
+    gain: ty.Union[float, jax.Array] = 1.0
+    offset: ty.Union[float, jax.Array] = 0.0
+    min: ty.Optional[ty.Union[float, jax.Array]] = None
+    max: ty.Optional[ty.Union[float, jax.Array]] = None
+
+    def apply(self, value: jax.Array) -> jax.Array:
+        if self.select is not None:
+            select = self.select if isinstance(self.select, int) else jnp.array(self.select)
+            value = value[select]
+        value = value * self.gain + self.offset
+        if self.min is not None or self.max is not None:
+            value = jnp.clip(value, self.min, self.max)
+        return value
+
+    def system(self) -> el.System:
+        if el.Component.name(self.src) == el.Component.name(self.dst):
+            raise ValueError(f"route from {el.Component.name(self.src)} into itself")
+        route = self
+
+        @el.system
+        def signal_route(q: el.Query[route.src, route.dst]) -> el.Query[route.dst]:  # type: ignore
+            return q.map(route.dst, lambda src, _: route.apply(src))
+
+        return signal_route
+
+
+def from_config(
+    config: ty.Iterable[dict[str, ty.Any]], components: ty.Iterable[ty.Any]
+) -> list[Route]:
+    """
+    Builds routes from plain data, such as a parsed TOML or JSON file. Each entry names its
+    `src` and `dst` components, which are looked up by name in `components`; the remaining keys
+    are passed to `Route` as is.
+    """
+    by_name = {el.Component.name(c): c for c in components}
+    routes = []
+    for entry in config:
+        entry = dict(entry)
+        for key in ("src", "dst"):
+            name = entry[key]
+            if name not in by_name:
+                raise ValueError(f"unknown component {name} in signal route")
+            entry[key] = by_name[name]
+        routes.append(Route(**entry))
+    return routes
+
+
+def system(routes: ty.Iterable[Route]) -> el.System:
+    """Runs every route in order, so a route can read the output of an earlier one."""
+    systems = [route.system() for route in routes]
+    if not systems:
+        raise ValueError("no signal routes")
+    sys = systems[0]
+    for s in systems[1:]:
+        sys = sys.pipe(s)
+    return sys
@@ -0,0 +1,286 @@
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+from elodin import constants
+from elodin.geomag import J2000
+
+AU = constants.DEFAULT.au
+SUN_RADIUS = constants.DEFAULT.sun_radius
+EARTH_RADIUS = constants.DEFAULT.earth_radius
+MU_SUN = constants.DEFAULT.mu_sun
+MU_MOON = constants.DEFAULT.mu_moon
+SOLAR_PRESSURE = constants.DEFAULT.solar_pressure
+
+SUNLIT = 0
+PENUMBRA = 1
+UMBRA = 2
+
+
+def sun_position(jd: jax.Array) -> jax.Array:
+    """
+    The position of the Sun relative to the Earth in meters, in the inertial frame, at Julian
+    date `jd`.
+
+    Uses the low precision solar coordinates from the Astronomical Almanac, good to about 0.01
+    degrees between 1950 and 2050.
+    """
+    n = jnp.asarray(jd) - J2000
+    mean_lon = jnp.deg2rad(280.460 + 0.9856474 * n)
+    anomaly = jnp.deg2rad(357.528 + 0.9856003 * n)
+    ecliptic_lon = mean_lon + jnp.deg2rad(1.915 * jnp.sin(anomaly) + 0.020 * jnp.sin(2 * anomaly))
+    obliquity = jnp.deg2rad(23.439 - 0.0000004 * n)
+    au = constants.current().au
+    dist = au * (1.00014 - 0.01671 * jnp.cos(anomaly) - 0.00014 * jnp.cos(2 * anomaly))
+    return dist[..., None] * jnp.stack(
+        [
+            jnp.cos(ecliptic_lon),
+            jnp.cos(obliquity) * jnp.sin(ecliptic_lon),
+            jnp.sin(obliquity) * jnp.sin(ecliptic_lon),
+        ],
+        axis=-1,
+    )
+
+
+def moon_position(jd: jax.Array) -> jax.Array:
+    """
+    The position of the Moon relative to the Earth in meters, in the inertial frame, at Julian
+    date `jd`.
+
+    Uses the truncated lunar series from Montenbruck & Gill (3.47-3.49), good to about 10 arc
+    minutes in longitude and 500 km in distance.
+    """
+    t = (jnp.asarray(jd) - J2000) / 36525.0
+    deg = jnp.deg2rad
+    mean_lon = deg(218.31617 + 481267.88088 * t - 1.3972 * t)
+    mm = deg(134.96292 + 477198.86753 * t)  # the Moon's mean anomaly
+    ms = deg(357.52543 + 35999.04944 * t)  # the Sun's mean anomaly
+    f = deg(93.27283 + 483202.01873 * t)  # mean argument of latitude
+    d = deg(297.85027 + 445267.11135 * t)  # mean elongation from the Sun
+    sin = jnp.sin
+    lon = mean_lon + deg(
+        (
+            22640 * sin(mm)
+            + 769 * sin(2 * mm)
+            - 4586 * sin(mm - 2 * d)
+            + 2370 * sin(2 * d)
+            - 668 * sin(ms)
+            - 412 * sin(2 * f)
+            - 212 * sin(2 * mm - 2 * d)
+            - 206 * sin(mm + ms - 2 * d)
+            + 192 * sin(mm + 2 * d)
+            - 165 * sin(ms - 2 * d)
+            + 148 * sin(mm - ms)
+            - 125 * sin(d)
+            - 110 * sin(mm + ms)
+            - 55 * sin(2 * f - 2 * d)
+        )
+        / 3600.0
+    )
+    lat = deg(
+        (
+            18520 * sin(f + lon - mean_lon + deg((412 * sin(2 * f) + 541 * sin(ms)) / 3600.0))
+            - 526 * sin(f - 2 * d)
+            + 44 * sin(mm + f - 2 * d)
+            - 31 * sin(-mm + f - 2 * d)
+            - 25 * sin(-2 * mm + f)
+            - 23 * sin(ms + f - 2 * d)
+            + 21 * sin(-mm + f)
+            + 11 * sin(-ms + f - 2 * d)
+        )
+        / 3600.0
+    )
+    cos = jnp.cos
+    dist = 1e3 * (
+        385000
+        - 20905 * cos(mm)
+        - 3699 * cos(2 * d - mm)
+        - 2956 * cos(2 * d)
+        - 570 * cos(2 * mm)
+        + 246 * cos(2 * mm - 2 * d)
+        - 205 * cos(ms - 2 * d)
+        - 171 * cos(mm + 2 * d)
+        - 152 * cos(mm + ms - 2 * d)
+    )
+    # ecliptic to equatorial coordinates
+    obliquity = deg(23.43929111)
+    x = dist * cos(lat) * cos(lon)
+    y = dist * cos(lat) * sin(lon)
+    z = dist * sin(lat)
+    return jnp.stack(
+        [
+            x,
+            cos(obliquity) * y - sin(obliquity) * z,
+            sin(obliquity) * y + cos(obliquity) * z,
+        ],
+        axis=-1,
+    )
+
+
+def third_body_accel(pos: jax.Array, body_pos: jax.Array, mu: float) -> jax.Array:
+    """
+    The perturbing acceleration from a body at `body_pos` with gravitational parameter `mu` on
+    an object at `pos`, both relative to the Earth, which is itself accelerated by the body.
+    """
+    pos, body_pos = jnp.asarray(pos), jnp.asarray(body_pos)
+    rel = body_pos - pos
+    rel_norm = jnp.linalg.norm(rel, axis=-1, keepdims=True)
+    body_norm = jnp.linalg.norm(body_pos, axis=-1, keepdims=True)
+    return mu * (rel / rel_norm**3 - body_pos / body_norm**3)
+
+
+def sun_vector(pos: jax.Array, jd: jax.Array) -> jax.Array:
+    """The unit vector from inertial positions `pos` to the Sun."""
+    rel = sun_position(jd) - jnp.asarray(pos)
+    return rel / jnp.linalg.norm(rel, axis=-1, keepdims=True)
+
+
+def illumination(
+    pos: jax.Array, sun_pos: jax.Array, radius: ty.Optional[float] = None
+) -> jax.Array:
+    """
+    The fraction of the Sun's disk visible from `pos`, 1 in full sunlight and 0 in the umbra.
+
+    Models the occulting body at the origin as a sphere of `radius`, the Earth's radius unless
+    set, and the Sun as a disk, with the penumbra being the area of the disk left uncovered
+    (Montenbruck & Gill's conical model).
+    """
+    if radius is None:
+        radius = constants.current().earth_radius
+    pos, sun_pos = jnp.asarray(pos), jnp.asarray(sun_pos)
+    to_sun = sun_pos - pos
+    dist_sun = jnp.linalg.norm(to_sun, axis=-1)
+    dist_body = jnp.linalg.norm(pos, axis=-1)
+    # apparent radii of the Sun and the body, and the angle between their centers
+    a = jnp.arcsin(jnp.clip(constants.current().sun_radius / dist_sun, -1.0, 1.0))
+    b = jnp.arcsin(jnp.clip(radius / dist_body, -1.0, 1.0))
+    cos_c = -jnp.sum(pos * to_sun, axis=-1) / (dist_body * dist_sun)
+    c = jnp.arccos(jnp.clip(cos_c, -1.0, 1.0))
+
+    # area of the Sun's disk covered by a partial overlap
+    safe_c = jnp.maximum(c, 1e-12)
+    x = (c**2 + a**2 - b**2) / (2.0 * safe_c)
+    y = jnp.sqrt(jnp.maximum(a**2 - x**2, 0.0))
+    overlap = (
+        a**2 * jnp.arccos(jnp.clip(x / a, -1.0, 1.0))
+        + b**2 * jnp.arccos(jnp.clip((c - x) / b, -1.0, 1.0))
+        - c * y
+    )
+    partial = 1.0 - overlap / (jnp.pi * a**2)
+    annular = 1.0 - b**2 / a**2
+    return jnp.where(
+        c >= a + b,
+        1.0,
+        jnp.where(c <= b - a, 0.0, jnp.where(c <= a - b, annular, partial)),
+    )
+
+
+def eclipse_state(
+    pos: jax.Array, sun_pos: jax.Array, radius: ty.Optional[float] = None
+) -> jax.Array:
+    """Whether `pos` is `SUNLIT`, in the `PENUMBRA`, or in the `UMBRA`, see `illumination`."""
+    light = illumination(pos, sun_pos, radius)
+    return jnp.where(light >= 1.0, SUNLIT, jnp.where(light <= 0.0, UMBRA, PENUMBRA))
+
+
+SrpArea = ty.Annotated[
+    jax.Array, el.Component("srp_area", el.ComponentType.F64, metadata={"priority": 16})
+]
+SrpCoefficient = ty.Annotated[
+    jax.Array,
+    el.Component("srp_coefficient", el.ComponentType.F64, metadata={"priority": 15}),
+]
+Illumination = ty.Annotated[
+    jax.Array, el.Component("illumination", el.ComponentType.F64, metadata={"priority": 14})
+]
+
+
+@dataclass
+class SrpBody(el.Archetype):
+    """
+    The Sun-facing cross-sectional area of a body in m^2, and its radiation pressure coefficient,
+    from 1 for a perfect absorber to 2 for a perfect mirror.
+    """
+
+    srp_area: SrpArea = field(default_factory=lambda: jnp.float64(1.0))
+    srp_coefficient: SrpCoefficient = field(default_factory=lambda: jnp.float64(1.3))
+    illumination: Illumination = field(default_factory=lambda: jnp.float64(1.0))
+
+
+@dataclass
+class SolarRadiationPressure:
+    """
+    A cannonball solar radiation pressure force on every body with an `SrpBody`, scaled by the
+    inverse square of the distance to the Sun and by the fraction of the Sun left visible by the
+    Earth's shadow, which is also written to `Illumination`.
+
+    World positions are taken as Earth-centered inertial, in meters, with the simulation starting
+    at Julian date `epoch_jd`. The shadow is cast by a sphere of `occulting_radius`, the Earth's
+    radius unless set.
+    """
+
+    epoch_jd: float = J2000
+    occulting_radius: ty.Optional[float] = None
+
+    def system(self) -> el.System:
+        @el.system
+        def srp(
+            tick: el.Query[el.SimulationTick],
+            dt: el.Query[el.SimulationTimeStep],
+            q: el.Query[el.WorldPos, SrpArea, SrpCoefficient, el.Force],
+        ) -> el.Query[el.Force, Illumination]:
+            sun_pos = sun_position(self.epoch_jd + tick[0] * dt[0] / 86400.0)
+            c = constants.current()
+            radius = c.earth_radius if self.occulting_radius is None else self.occulting_radius
+
+            def apply(pos, area, cr, f):
+                r = pos.linear()
+                light = illumination(r, sun_pos, radius)
+                to_sun = sun_pos - r
+                dist = jnp.linalg.norm(to_sun)
+                pressure = c.solar_pressure * (c.au / dist) ** 2
+                force = -pressure * cr * area * light * to_sun / dist
+                return f + el.SpatialForce(linear=force), light
+
+            return q.map((el.Force, Illumination), apply)
+
+        return srp
+
+
+@dataclass
+class ThirdBodyGravity:
+    """
+    The gravitational pull of the Sun and Moon on every body, as perturbations relative to the
+    Earth, which matter for high orbits like GEO. World positions are Earth-centered inertial.
+    """
+
+    epoch_jd: float = J2000
+    sun: bool = True
+    moon: bool = True
+
+    def system(self) -> el.System:
+        @el.system
+        def third_body(
+            tick: el.Query[el.SimulationTick],
+            dt: el.Query[el.SimulationTimeStep],
+            q: el.Query[el.WorldPos, el.Inertia, el.Force],
+        ) -> el.Query[el.Force]:
+            jd = self.epoch_jd + tick[0] * dt[0] / 86400.0
+            bodies = []
+            if self.sun:
+                bodies.append((sun_position(jd), constants.current().mu_sun))
+            if self.moon:
+                bodies.append((moon_position(jd), constants.current().mu_moon))
+
+            def apply(pos, inertia, f):
+                accel = jnp.zeros(3)
+                for body_pos, mu in bodies:
+                    accel = accel + third_body_accel(pos.linear(), body_pos, mu)
+                return f + el.SpatialForce(linear=inertia.mass() * accel)
+
+            return q.map(el.Force, apply)
+
+        return third_body
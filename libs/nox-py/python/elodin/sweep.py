@@ -0,0 +1,101 @@
+"""
+Grid sweeps over explicit parameter values, for when every combination should be run rather than
+random Monte Carlo draws.
+
+A sweep runs on the same batch runner as a Monte Carlo campaign. Each run is the sim script
+launched with `batch --run-id <i>`, which looks up its own values with `current()` and passes them
+to `World.run` to be recorded in its `sample.json`. `read_batch_results` and
+`campaign.export_sqlite` then see them like any other draws:
+
+    sweep = ParameterSweep().add("mass", [1.0, 2.0]).linspace("thrust", 0.0, 10.0, 5)
+    params = sweep.current()
+    w = make_world(**params)
+    w.run(system, draws=params)
+
+The ten runs are launched with the commands from `sweep.commands("sim.py", "results")`.
+"""
+
+import itertools
+import math
+import os
+import sys
+import typing as ty
+from dataclasses import dataclass, field
+
+import numpy as np
+
+
+@dataclass
+class ParameterSweep:
+    """The cartesian product of a list of values for each parameter, in the order they're added."""
+
+    parameters: dict[str, list[float]] = field(default_factory=dict)
+
+    def add(self, name: str, values: ty.Iterable[float]) -> "ParameterSweep":
+        values = [float(v) for v in values]
+        if name in self.parameters:
+            raise ValueError(f"parameter {name} is already swept")
+        if not values:
+            raise ValueError(f"parameter {name} has no values")
+        self.parameters[name] = values
+        return self
+
+    def linspace(self, name: str, start: float, stop: float, num: int) -> "ParameterSweep":
+        """Sweeps `num` evenly spaced values from `start` to `stop`, inclusive."""
+        return self.add(name, np.linspace(start, stop, num))
+
+    def arange(self, name: str, start: float, stop: float, step: float) -> "ParameterSweep":
+        """Sweeps from `start` in increments of `step`, stopping before `stop`."""
+        return self.add(name, np.arange(start, stop, step))
+
+    def __len__(self) -> int:
+        return math.prod(len(values) for values in self.parameters.values())
+
+    def __getitem__(self, run_id: int) -> dict[str, float]:
+        """The values of run `run_id`, with the last parameter added varying fastest."""
+        if not 0 <= run_id < len(self):
+            raise IndexError(f"run {run_id} is outside a sweep of {len(self)} runs")
+        values = {}
+        for name, options in reversed(self.parameters.items()):
+            run_id, i = divmod(run_id, len(options))
+            values[name] = options[i]
+        return {name: values[name] for name in self.parameters}
+
+    def __iter__(self) -> ty.Iterator[dict[str, float]]:
+        for combination in itertools.product(*self.parameters.values()):
+            yield dict(zip(self.parameters, combination))
+
+    def current(self, argv: ty.Optional[list[str]] = None) -> dict[str, float]:
+        """
+        The values of the run the script was launched as, from its `--run-id` argument, or of the
+        first run outside a batch.
+        """
+        argv = sys.argv if argv is None else argv
+        run_id = 0
+        for i, arg in enumerate(argv):
+            if arg == "--run-id" and i + 1 < len(argv):
+                run_id = int(argv[i + 1])
+            elif arg.startswith("--run-id="):
+                run_id = int(arg.split("=", 1)[1])
+        return self[run_id]
+
+    def commands(
+        self, script: str, output_dir: str, ticks: int = 1000, seed: int = 0
+    ) -> list[list[str]]:
+        """The batch command for every run, each writing into `output_dir/<run id>`."""
+        return [
+            [
+                sys.executable,
+                script,
+                "batch",
+                "--run-id",
+                str(run_id),
+                "--seed",
+                str(seed),
+                "--ticks",
+                str(ticks),
+                "--output",
+                os.path.join(output_dir, str(run_id)),
+            ]
+            for run_id in range(len(self))
+        ]
@@ -0,0 +1,132 @@
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+from elodin import constants
+from elodin.tle import MU_EARTH
+
+BPlaneCoords = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "b_plane",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "b_t,b_r,miss", "unit": "m", "priority": 12},
+    ),
+]
+EntryState = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "entry_interface",
+        el.ComponentType(el.PrimitiveType.F64, (2,)),
+        metadata={"element_names": "speed,fpa", "unit": "m/s,rad", "priority": 12},
+    ),
+]
+
+
+def b_plane(
+    pos: jax.Array, vel: jax.Array, mu: float, pole: jax.Array = jnp.array([0.0, 0.0, 1.0])
+) -> jax.Array:
+    """
+    The B-plane coordinates `[B·T, B·R]` of a hyperbolic approach, relative to the body at the
+    origin.
+
+    T lies in the plane of `pole`'s equator, usually the body's equator or the ecliptic, and R
+    completes the frame with the incoming asymptote S. The result is NaN for closed orbits.
+    """
+    r = jnp.linalg.norm(pos)
+    v_sq = jnp.dot(vel, vel)
+    h = jnp.cross(pos, vel)
+    h_hat = h / jnp.linalg.norm(h)
+    ecc_vec = ((v_sq - mu / r) * pos - jnp.dot(pos, vel) * vel) / mu
+    e = jnp.linalg.norm(ecc_vec)
+    e_hat = ecc_vec / e
+    # the incoming asymptote
+    s_hat = e_hat / e + jnp.sqrt(1.0 - 1.0 / e**2) * jnp.cross(h_hat, e_hat)
+    # the semi-minor axis of the hyperbola is the miss distance
+    b = mu / (v_sq - 2.0 * mu / r) * jnp.sqrt(e**2 - 1.0)
+    b_vec = b * jnp.cross(s_hat, h_hat)
+    t_hat = jnp.cross(s_hat, pole)
+    t_hat = t_hat / jnp.linalg.norm(t_hat)
+    r_hat = jnp.cross(s_hat, t_hat)
+    return jnp.stack([jnp.dot(b_vec, t_hat), jnp.dot(b_vec, r_hat)])
+
+
+def entry_conditions(pos: jax.Array, vel: jax.Array, mu: float, radius: float) -> jax.Array:
+    """
+    The speed and flight-path angle the current conic reaches the entry interface `radius` with,
+    the angle being negative when descending.
+
+    The result is NaN if the conic never comes down to `radius`.
+    """
+    r = jnp.linalg.norm(pos)
+    speed = jnp.sqrt(jnp.dot(vel, vel) + 2.0 * mu * (1.0 / radius - 1.0 / r))
+    h = jnp.linalg.norm(jnp.cross(pos, vel))
+    cos_fpa = h / (radius * speed)
+    fpa = -jnp.arccos(jnp.minimum(cos_fpa, 1.0))
+    # allow for rounding when the conic just grazes the interface
+    return jnp.stack([speed, jnp.where(cos_fpa <= 1.0 + 1e-9, fpa, jnp.nan)])
+
+
+@dataclass
+class BPlaneDiagnostics(el.Archetype):
+    b_plane: BPlaneCoords = field(default_factory=lambda: jnp.zeros(3))
+
+
+@dataclass
+class EntryDiagnostics(el.Archetype):
+    entry_interface: EntryState = field(default_factory=lambda: jnp.zeros(2))
+
+
+@dataclass
+class BPlane:
+    """
+    Computes the B-plane coordinates of every `BPlaneDiagnostics` body each tick, along with its
+    miss distance from `target`, `[B·T, B·R]` in meters. Recorded across Monte Carlo runs this
+    gives the targeting dispersion directly. `mu` defaults to the Earth's from `constants`.
+    """
+
+    mu: ty.Optional[float] = None
+    pole: jax.Array = field(default_factory=lambda: jnp.array([0.0, 0.0, 1.0]))
+    target: jax.Array = field(default_factory=lambda: jnp.zeros(2))
+
+    def system(self) -> el.System:
+        @el.system
+        def b_plane_diagnostics(
+            q: el.Query[el.WorldPos, el.WorldVel, BPlaneCoords],
+        ) -> el.Query[BPlaneCoords]:
+            mu = constants.current().mu_earth if self.mu is None else self.mu
+
+            def coords(pos: el.WorldPos, vel: el.WorldVel, _: jax.Array) -> jax.Array:
+                b = b_plane(pos.linear(), vel.linear(), mu, self.pole)
+                return jnp.append(b, jnp.linalg.norm(b - self.target))
+
+            return q.map(BPlaneCoords, coords)
+
+        return b_plane_diagnostics
+
+
+@dataclass
+class EntryInterface:
+    """
+    Computes the conditions every `EntryDiagnostics` body will reach the entry interface
+    `radius` with each tick, assuming it coasts on its current conic until then.
+    """
+
+    radius: float
+    mu: ty.Optional[float] = None
+
+    def system(self) -> el.System:
+        @el.system
+        def entry_diagnostics(
+            q: el.Query[el.WorldPos, el.WorldVel, EntryState],
+        ) -> el.Query[EntryState]:
+            mu = constants.current().mu_earth if self.mu is None else self.mu
+            return q.map(
+                EntryState,
+                lambda pos, vel, _: entry_conditions(pos.linear(), vel.linear(), mu, self.radius),
+            )
+
+        return entry_diagnostics
@@ -0,0 +1,71 @@
+"""
+Terrain heightfields: elevations on a regular grid in the world's x/y plane, with z up.
+
+`Heightfield.sample` interpolates the grid bilinearly with jax ops, so it can be used inside
+systems, like the wheel contact in `elodin.rover`, and differentiated with `jax.grad`. The same
+grid can be inserted as the well-known `Heightfield` asset, and meshed for the viewer:
+
+    field = terrain.Heightfield(heights, spacing=(1.0, 1.0))
+    world.spawn(el.Shape(world.insert_asset(field.mesh()), world.insert_asset(material)))
+"""
+
+import typing as ty
+from dataclasses import dataclass
+
+import jax
+import jax.numpy as jnp
+import numpy as np
+
+import elodin as el
+
+
+@dataclass
+class Heightfield:
+    """
+    `heights` in meters, with one row per y sample and one column per x sample, starting at
+    `origin` and stepping by `spacing` along x and y. Positions past the grid take the height of
+    its nearest edge. `geodetic_origin` is the latitude and longitude in radians and the altitude
+    in meters of the world origin, if the terrain is tied to a map.
+    """
+
+    heights: np.ndarray
+    spacing: ty.Tuple[float, float]
+    origin: ty.Tuple[float, float] = (0.0, 0.0)
+    geodetic_origin: ty.Optional[ty.Tuple[float, float, float]] = None
+
+    def __post_init__(self):
+        self.heights = np.asarray(self.heights, dtype=np.float64)
+        if self.heights.ndim != 2 or min(self.heights.shape) < 2:
+            raise ValueError("a heightfield needs at least 2x2 samples")
+
+    def _cell(self, xy: jax.Array) -> ty.Tuple[jax.Array, jax.Array, jax.Array]:
+        """The corner heights of the cell under `xy`, and the position within it."""
+        rows, cols = self.heights.shape
+        grid = (jnp.asarray(xy) - jnp.asarray(self.origin)) / jnp.asarray(self.spacing)
+        inside = (grid >= 0.0) & (grid <= jnp.array([cols - 1, rows - 1]))
+        grid = jnp.clip(grid, 0.0, jnp.array([cols - 1, rows - 1]))
+        corner = jnp.minimum(jnp.floor(grid), jnp.array([cols - 2, rows - 2])).astype(jnp.int32)
+        frac = grid - corner
+        h = jnp.asarray(self.heights)
+        i, j = corner[0], corner[1]
+        corners = jnp.array([[h[j, i], h[j, i + 1]], [h[j + 1, i], h[j + 1, i + 1]]])
+        return corners, frac, inside
+
+    def sample(self, xy: jax.Array) -> ty.Tuple[jax.Array, jax.Array]:
+        """The height and upward unit normal of the terrain below `xy`."""
+        corners, (fx, fy), inside = self._cell(xy)
+        bottom = corners[0, 0] + fx * (corners[0, 1] - corners[0, 0])
+        top = corners[1, 0] + fx * (corners[1, 1] - corners[1, 0])
+        dx = (1 - fy) * (corners[0, 1] - corners[0, 0]) + fy * (corners[1, 1] - corners[1, 0])
+        slope = jnp.where(inside, jnp.array([dx, top - bottom]) / jnp.asarray(self.spacing), 0.0)
+        normal = jnp.array([-slope[0], -slope[1], 1.0])
+        return bottom + fy * (top - bottom), normal / jnp.linalg.norm(normal)
+
+    def height(self, xy: jax.Array) -> jax.Array:
+        return self.sample(xy)[0]
+
+    def asset(self) -> el.Heightfield:
+        return el.Heightfield(self.heights, self.spacing, self.origin, self.geodetic_origin)
+
+    def mesh(self) -> el.Mesh:
+        return self.asset().mesh()
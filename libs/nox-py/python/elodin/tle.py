@@ -0,0 +1,155 @@
+from dataclasses import dataclass
+from typing import List, Optional, Tuple
+
+import numpy as np
+
+from elodin import constants
+
+MU_EARTH = constants.DEFAULT.mu_earth
+SECONDS_PER_DAY = 86400.0
+
+
+class TLEError(ValueError):
+    pass
+
+
+def _checksum(line: str) -> int:
+    total = 0
+    for c in line[:68]:
+        if c.isdigit():
+            total += int(c)
+        elif c == "-":
+            total += 1
+    return total % 10
+
+
+def _implied_decimal(field: str) -> float:
+    # fields such as " 12345-3" encode 0.12345e-3
+    field = field.strip()
+    if not field:
+        return 0.0
+    sign = -1.0 if field[0] == "-" else 1.0
+    field = field.lstrip("+-")
+    mantissa, exponent = field[:-2], field[-2:]
+    return sign * float("0." + mantissa.strip()) * 10.0 ** int(exponent)
+
+
+@dataclass
+class TLE:
+    name: Optional[str]
+    satnum: int
+    epoch_year: int
+    epoch_day: float
+    bstar: float
+    inclination: float  # rad
+    raan: float  # rad
+    eccentricity: float
+    arg_of_perigee: float  # rad
+    mean_anomaly: float  # rad
+    mean_motion: float  # rad/s
+
+    @staticmethod
+    def parse(line1: str, line2: str, name: Optional[str] = None) -> "TLE":
+        line1 = line1.rstrip()
+        line2 = line2.rstrip()
+        if len(line1) < 69 or len(line2) < 69:
+            raise TLEError("tle lines must be 69 characters long")
+        if line1[0] != "1" or line2[0] != "2":
+            raise TLEError("tle lines must start with 1 and 2")
+        for line in (line1, line2):
+            if _checksum(line) != int(line[68]):
+                raise TLEError(f"checksum mismatch: {line}")
+        satnum = int(line1[2:7])
+        if int(line2[2:7]) != satnum:
+            raise TLEError("tle lines describe different satellites")
+        year = int(line1[18:20])
+        year += 2000 if year < 57 else 1900
+        return TLE(
+            name=name.strip() if name is not None else None,
+            satnum=satnum,
+            epoch_year=year,
+            epoch_day=float(line1[20:32]),
+            bstar=_implied_decimal(line1[53:61]),
+            inclination=np.deg2rad(float(line2[8:16])),
+            raan=np.deg2rad(float(line2[17:25])),
+            eccentricity=float("0." + line2[26:33].strip()),
+            arg_of_perigee=np.deg2rad(float(line2[34:42])),
+            mean_anomaly=np.deg2rad(float(line2[43:51])),
+            mean_motion=float(line2[52:63]) * 2.0 * np.pi / SECONDS_PER_DAY,
+        )
+
+    def semi_major_axis(self) -> float:
+        return (constants.current().mu_earth / self.mean_motion**2) ** (1.0 / 3.0)
+
+    def state(self, dt: float = 0.0) -> Tuple[np.ndarray, np.ndarray]:
+        """
+        Returns the inertial position (m) and velocity (m/s) `dt` seconds after the element epoch.
+
+        The mean elements are propagated as a two-body Keplerian orbit, this does not
+        model the drag and secular perturbation terms of the full SGP4 propagator, so
+        it is intended for initializing entities near the element epoch.
+        """
+        e = self.eccentricity
+        a = self.semi_major_axis()
+        m = self.mean_anomaly + self.mean_motion * dt
+        ecc_anomaly = m
+        for _ in range(32):
+            delta = (ecc_anomaly - e * np.sin(ecc_anomaly) - m) / (1.0 - e * np.cos(ecc_anomaly))
+            ecc_anomaly -= delta
+            if abs(delta) < 1e-12:
+                break
+        cos_e = np.cos(ecc_anomaly)
+        sin_e = np.sin(ecc_anomaly)
+        b = a * np.sqrt(1.0 - e**2)
+        r = a * (1.0 - e * cos_e)
+        pos_pqw = np.array([a * (cos_e - e), b * sin_e, 0.0])
+        speed = np.sqrt(constants.current().mu_earth * a) / r
+        vel_pqw = speed * np.array([-sin_e, np.sqrt(1.0 - e**2) * cos_e, 0.0])
+        rot = pqw_to_inertial(self.raan, self.inclination, self.arg_of_perigee)
+        return rot @ pos_pqw, rot @ vel_pqw
+
+    def spatial(self, dt: float = 0.0):
+        """
+        Returns the initial `(SpatialTransform, SpatialMotion)` pair for spawning an entity from this element set.
+        """
+        import elodin as el
+
+        pos, vel = self.state(dt)
+        return el.SpatialTransform(linear=pos), el.SpatialMotion(linear=vel)
+
+
+def pqw_to_inertial(raan: float, inc: float, argp: float) -> np.ndarray:
+    """
+    The rotation from an orbit's perifocal frame, with x towards periapsis and z along the
+    angular momentum, to the inertial frame, given its Keplerian angles in radians.
+    """
+    cr, sr = np.cos(raan), np.sin(raan)
+    ci, si = np.cos(inc), np.sin(inc)
+    cw, sw = np.cos(argp), np.sin(argp)
+    return np.array(
+        [
+            [cr * cw - sr * sw * ci, -cr * sw - sr * cw * ci, sr * si],
+            [sr * cw + cr * sw * ci, -sr * sw + cr * cw * ci, -cr * si],
+            [sw * si, cw * si, ci],
+        ]
+    )
+
+
+def parse_tles(text: str) -> List[TLE]:
+    """
+    Parses a block of two or three line element sets, such as the files served by Celestrak.
+    """
+    lines = [line for line in text.splitlines() if line.strip()]
+    tles = []
+    name = None
+    i = 0
+    while i < len(lines):
+        line = lines[i]
+        if line.startswith("1 ") and i + 1 < len(lines) and lines[i + 1].startswith("2 "):
+            tles.append(TLE.parse(line, lines[i + 1], name))
+            name = None
+            i += 2
+        else:
+            name = line.removeprefix("0 ")
+            i += 1
+    return tles
@@ -0,0 +1,167 @@
+import typing as ty
+from dataclasses import dataclass, field
+
+import jax
+import jax.numpy as jnp
+
+import elodin as el
+from elodin import geomag
+
+WheelMomentum = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "wheel_momentum",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "priority": 16},
+    ),
+]
+WheelTorqueCommand = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "wheel_torque_command",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "priority": 16},
+    ),
+]
+DesaturationTorque = ty.Annotated[
+    jax.Array,
+    el.Component(
+        "desaturation_torque",
+        el.ComponentType(el.PrimitiveType.F64, (3,)),
+        metadata={"element_names": "x,y,z", "priority": 15},
+    ),
+]
+Desaturating = ty.Annotated[
+    jax.Array, el.Component("desaturating", el.ComponentType.F64, metadata={"priority": 15})
+]
+
+
+@dataclass
+class ReactionWheels(el.Archetype):
+    """
+    The body-frame momentum stored in the wheels in N m s, the torque the attitude controller
+    wants on the body in N m, and the external torque a momentum dump is applying.
+    """
+
+    wheel_momentum: WheelMomentum = field(default_factory=lambda: jnp.zeros(3))
+    wheel_torque_command: WheelTorqueCommand = field(default_factory=lambda: jnp.zeros(3))
+    desaturation_torque: DesaturationTorque = field(default_factory=lambda: jnp.zeros(3))
+    desaturating: Desaturating = field(default_factory=lambda: jnp.float64(0.0))
+
+
+@dataclass
+class ReactionWheelArray:
+    """
+    Reaction wheels spinning about the body-frame unit vectors in the rows of `axes`, each limited
+    to `max_torque` N m and `max_momentum` N m s.
+
+    The body torque command is spread over the wheels with the pseudo-inverse, so only the total
+    momentum is tracked and redundant wheels share the load evenly. The wheels react against the
+    body, which also feels the gyroscopic torque `-ω × h` of the stored momentum. While a momentum
+    dump is under way the wheels also soak up its torque, so the body's attitude is held.
+    """
+
+    axes: jax.Array = field(default_factory=lambda: jnp.eye(3))
+    max_torque: float = 0.01
+    max_momentum: float = 0.05
+
+    def system(self) -> el.System:
+        axes = jnp.asarray(self.axes)
+        axes = axes / jnp.linalg.norm(axes, axis=1, keepdims=True)
+        distribute = jnp.linalg.pinv(axes.T)
+
+        @el.system
+        def spin(
+            dt: el.Query[el.SimulationTimeStep],
+            q: el.Query[
+                WheelTorqueCommand,
+                DesaturationTorque,
+                WheelMomentum,
+                el.WorldPos,
+                el.WorldVel,
+                el.Force,
+            ],
+        ) -> el.Query[WheelMomentum, el.Force]:
+            step = dt[0]
+
+            def react(command, dump, h, pos, vel, f):
+                rot = pos.angular()
+                omega = rot.inverse() @ vel.angular()
+                # the wheels spin up against the torque they put on the body
+                rate = jnp.clip(distribute @ (dump - command), -self.max_torque, self.max_torque)
+                h_wheels = distribute @ h
+                next_wheels = jnp.clip(
+                    h_wheels + rate * step, -self.max_momentum, self.max_momentum
+                )
+                h_dot = axes.T @ (next_wheels - h_wheels) / step
+                torque = -h_dot - jnp.cross(omega, h)
+                return axes.T @ next_wheels, f + el.SpatialForce(torque=rot @ torque)
+
+            return q.map((WheelMomentum, el.Force), react)
+
+        return spin
+
+
+@dataclass
+class MomentumManagement:
+    """
+    Dumps the momentum stored in reaction wheels down towards `target` (N m s, body frame), with an
+    external torque the wheels then absorb.
+
+    With `mode="magnetic"` the dump commands a `geomag.Magnetorquer` through the cross product
+    law `m = gain (Δh × B) / |B|^2`, which runs continuously but can't remove momentum along the
+    field. With `mode="thruster"` a torque `-gain Δh`, clamped to `max_thruster_torque`, is fired
+    as an ideal couple once `|Δh|` passes `start`, and held until it falls below `stop`.
+
+    Runs before `ReactionWheelArray`, and for magnetic dumping after `geomag.Magnetometer` and
+    before the `Magnetorquer` system, with the bodies also holding a `geomag.DipoleCommand`.
+    """
+
+    gain: float = 0.01
+    target: jax.Array = field(default_factory=lambda: jnp.zeros(3))
+    mode: str = "magnetic"
+    torquer: geomag.Magnetorquer = field(default_factory=geomag.Magnetorquer)
+    max_thruster_torque: float = 0.01
+    start: float = 0.04
+    stop: float = 0.01
+
+    def __post_init__(self):
+        if self.mode not in ("magnetic", "thruster"):
+            raise ValueError(f"unknown momentum management mode {self.mode}")
+
+    def magnetic(self) -> el.System:
+        target = jnp.asarray(self.target)
+
+        @el.map
+        def dump(
+            h: WheelMomentum, b: geomag.MagneticField, pos: el.WorldPos
+        ) -> tuple[geomag.DipoleCommand, DesaturationTorque]:
+            b_body = pos.angular().inverse() @ b
+            excess = h - target
+            b2 = jnp.maximum(jnp.dot(b_body, b_body), 1e-12)
+            # the field is in nT, so dividing by |B|^2 leaves the dipole scaled down by 1e9
+            m = self.torquer.dipole(self.gain * jnp.cross(excess, b_body) / b2 * 1e9)
+            return m, self.torquer.torque(m, b_body)
+
+        return dump
+
+    def thruster(self) -> el.System:
+        target = jnp.asarray(self.target)
+
+        @el.map
+        def dump(
+            h: WheelMomentum, active: Desaturating, pos: el.WorldPos, f: el.Force
+        ) -> tuple[DesaturationTorque, Desaturating, el.Force]:
+            excess = h - target
+            size = jnp.linalg.norm(excess)
+            on = jnp.where(active > 0.5, size > self.stop, size > self.start)
+            torque = -self.gain * excess
+            limit = self.max_thruster_torque / jnp.maximum(size * self.gain, 1e-12)
+            torque = jnp.where(on, torque * jnp.minimum(1.0, limit), 0.0)
+            firing = el.SpatialForce(torque=pos.angular() @ torque)
+            return torque, jnp.where(on, 1.0, 0.0), f + firing
+
+        return dump
+
+    def system(self) -> el.System:
+        return self.magnetic() if self.mode == "magnetic" else self.thruster()
@@ -0,0 +1,90 @@
+"""
+Named force contributions, so a misbehaving effector can be told apart from the others.
+
+Each effector registered with a `Wrenches` accumulator adds its force to `el.Force` as usual, and
+also writes it to its own `wrench_<name>` component, which is recorded and streamed like any other
+component:
+
+    wrenches = Wrenches("gravity", "drag")
+
+    @wrenches.effector("gravity")
+    def gravity(inertia: el.Inertia) -> el.SpatialForce:
+        return el.SpatialForce(linear=inertia.mass() * jnp.array([0.0, 0.0, -9.81]))
+
+    world.spawn([el.Body(...), *wrenches.archetypes()])
+
+The bookkeeping is skipped when `ELODIN_WRENCH_ACCOUNTING=0` is set, or `enabled=False` is passed,
+leaving effectors that only write `el.Force`.
+"""
+
+import inspect
+import os
+import typing as ty
+
+import elodin as el
+
+
+def accounting_enabled() -> bool:
+    return os.environ.get("ELODIN_WRENCH_ACCOUNTING", "1") != "0"
+
+
+def contribution(name: str) -> ty.Any:
+    """The component holding the force contributed by the effector called `name`."""
+    return ty.Annotated[
+        el.SpatialForce,
+        el.Component(
+            f"wrench_{name}",
+            metadata={"element_names": "τx,τy,τz,x,y,z", "priority": 4},
+        ),
+    ]
+
+
+class Wrenches:
+    def __init__(self, *names: str, enabled: ty.Optional[bool] = None):
+        self.names = list(names)
+        self.enabled = accounting_enabled() if enabled is None else enabled
+
+    def component(self, name: str) -> ty.Any:
+        if name not in self.names:
+            raise KeyError(f"unknown wrench contribution {name!r}")
+        return contribution(name)
+
+    def archetypes(self) -> list[el.C]:
+        """The zeroed contribution components to spawn on every body the effectors act on."""
+        if not self.enabled or not self.names:
+            return []
+        tys = tuple(contribution(name) for name in self.names)
+        return [el.C(tys, tuple(el.SpatialForce() for _ in self.names))]
+
+    def effector(self, name: str) -> ty.Callable[[ty.Callable[..., el.SpatialForce]], el.System]:
+        """
+        Turns a function of components returning a `SpatialForce` into a system that adds the force
+        to `el.Force`, and records it as the `name` contribution when accounting is enabled.
+        """
+        out = (el.Force, self.component(name)) if self.enabled else el.Force
+        enabled = self.enabled
+
+        def wrap(func: ty.Callable[..., el.SpatialForce]) -> el.System:
+            inputs = tuple(p.annotation for p in inspect.signature(func).parameters.values())
+            query = el.Query[(el.Force, *inputs)]  # type: ignore
+
+            def apply(force: el.SpatialForce, *args):
+                wrench = func(*args)
+                return (force + wrench, wrench) if enabled else force + wrench
+
+            @el.system
+            def inner(q: query) -> el.Query[out]:  # type: ignore
+                return q.map(out, apply)
+
+            return inner
+
+        return wrap
+
+    def latest(self, exec: el.Exec) -> dict[str, ty.Any]:
+        """The latest contribution of every effector, with one row per entity."""
+        if not self.enabled:
+            return {}
+        return {
+            name: exec.column_array(el.Component.id(contribution(name))).to_numpy()
+            for name in self.names
+        }
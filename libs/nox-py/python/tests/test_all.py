@@ -4,6 +4,7 @@ from dataclasses import dataclass
 import elodin as el
 import jax
 import jax.numpy as np
+import pytest
 from elodin import ukf
 from jax import random
 
@@ -75,6 +76,54 @@ def test_six_dof():
     assert np.allclose(x.to_numpy()[0][4:], np.array([0.01666667, 0.0, 0.0]))
 
 
+def test_six_dof_dopri5():
+    w = el.World()
+    w.spawn(
+        el.Body(
+            world_pos=el.SpatialTransform(linear=np.array([0.0, 0.0, 0.0])),
+            world_vel=el.SpatialMotion(angular=np.array([0.0, 0.0, 1.0])),
+            inertia=el.SpatialInertia(1.0),
+        )
+    )
+    sys = el.six_dof(0.25, integrator=el.Integrator.Dopri5)
+    exec = w.build(sys)
+    exec.run(4)
+    x = exec.column_array(el.Component.id(el.WorldPos))
+    assert np.allclose(
+        x.to_numpy()[0],
+        np.array([0.0, 0.0, 0.479425538604203, 0.8775825618903728, 0.0, 0.0, 0.0]),
+        atol=1e-6,
+    )
+
+
+def test_six_dof_dcm():
+    @dataclass
+    class DcmBody(el.Archetype):
+        world_dcm: el.WorldDcm
+
+    w = el.World()
+    w.spawn(
+        [
+            el.Body(
+                world_pos=el.SpatialTransform(linear=np.array([1.0, 0.0, 0.0])),
+                world_vel=el.SpatialMotion(angular=np.array([0.0, 0.0, 1.0])),
+                inertia=el.SpatialInertia(1.0),
+            ),
+            DcmBody(np.eye(3)),
+        ]
+    )
+    sys = el.six_dof(1.0 / 120.0, attitude=el.Attitude.Dcm)
+    exec = w.build(sys)
+    exec.run(120)
+    x = exec.column_array(el.Component.id(el.WorldPos))
+    # one radian about z, with the attitude of the `WorldPos` following the DCM
+    assert np.allclose(
+        x.to_numpy()[0],
+        np.array([0.0, 0.0, 0.479425538604203, 0.8775825618903728, 1.0, 0.0, 0.0]),
+        atol=1e-5,
+    )
+
+
 def test_spatial_integration():
     @el.map
     def integrate_velocity(world_pos: el.WorldPos, world_vel: el.WorldVel) -> el.WorldPos:
@@ -466,9 +515,1361 @@ def test_ukf_simple_linear():
     )
 
     # Update state with measurements
+    assert state.nis is None
+    nis = []
     for z in zs:
         state.update(z, prop_fn, measure_fn)
+        nis.append(state.nis)
 
     # Check final state estimate
     expected_x_hat = np.array([48.9118168, 9.96293597, 48.89106226, 9.95283274])
     assert np.isclose(state.x_hat, expected_x_hat, rtol=1e-6).all()
+    assert state.residual.shape == (2,)
+    assert all(n >= 0.0 for n in nis)
+    assert np.isclose(state.nees(state.x_hat), 0.0)
+
+
+def test_tle_state():
+    from elodin.tle import parse_tles
+
+    tles = parse_tles(
+        """ISS (ZARYA)
+1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537"""
+    )
+    assert len(tles) == 1
+    iss = tles[0]
+    assert iss.name == "ISS (ZARYA)"
+    assert iss.satnum == 25544
+    assert iss.epoch_year == 2008
+    assert np.isclose(iss.bstar, -0.11606e-4)
+    pos, vel = iss.state()
+    # ~350 km altitude circular orbit
+    assert 6.6e6 < np.linalg.norm(pos) < 6.8e6
+    assert 7.6e3 < np.linalg.norm(vel) < 7.8e3
+    assert np.isclose(np.dot(pos, vel) / (np.linalg.norm(pos) * np.linalg.norm(vel)), 0.0, atol=1e-3)
+
+
+def test_engine_mass_flow():
+    from elodin import propulsion
+
+    engine = propulsion.Engine(
+        throttle_table=np.array([0.0, 1.0]),
+        pressure_table=np.array([0.0, 101325.0]),
+        isp_table=np.array([[280.0, 250.0], [320.0, 290.0]]),
+        max_mass_flow=2.0,
+    )
+    w = el.World()
+    w.spawn(
+        [
+            el.Body(inertia=el.SpatialInertia(mass=100.0)),
+            propulsion.Propulsion(throttle=np.float64(1.0), propellant_mass=np.float64(10.0)),
+        ]
+    )
+    exec = w.build(engine.system())
+    exec.run()
+    thrust = exec.column_array(el.Component.name(propulsion.EngineThrust))
+    propellant = exec.column_array(el.Component.name(propulsion.PropellantMass))
+    assert np.isclose(thrust[0], 2.0 * propulsion.G0 * 320.0)
+    assert np.isclose(propellant[0], 10.0 - 2.0 / 120.0)
+
+
+def test_ccd_thin_wall():
+    from elodin import ccd
+
+    wall = ccd.Box(center=np.array([5.0, 0.0, 0.0]), half_extents=np.array([0.01, 10.0, 10.0]))
+    w = el.World()
+    w.spawn(
+        [
+            el.Body(world_vel=el.SpatialMotion(linear=np.array([1000.0, 0.0, 0.0]))),
+            ccd.Ccd(ccd_radius=np.float64(0.1)),
+        ]
+    )
+    exec = w.build(ccd.system([wall]).pipe(el.six_dof(0.1)), sim_time_step=0.1)
+    exec.run()
+    vel = exec.column_array(el.Component.name(el.WorldVel))
+    pos = exec.column_array(el.Component.name(el.WorldPos))
+    assert np.isclose(vel[0][3], 0.0)
+    # stopped against the wall, not where the tick started
+    assert np.isclose(pos[0][4], 5.0 - 0.01 - 0.1, atol=1e-6)
+
+
+def test_energy_momentum_diagnostics():
+    from elodin import diagnostics
+
+    w = el.World()
+    w.spawn(
+        el.Body(
+            world_pos=el.SpatialTransform(linear=np.array([0.0, 1.0, 3.0])),
+            world_vel=el.SpatialMotion(linear=np.array([1.0, 0.0, 0.0])),
+            inertia=el.SpatialInertia(mass=2.0),
+        )
+    )
+    w.spawn(diagnostics.Diagnostics())
+    sys = el.six_dof(0.01).pipe(diagnostics.system(diagnostics.uniform_gravity()))
+    exec = w.build(sys, sim_time_step=0.01)
+    exec.run(10)
+
+    def column(component):
+        return exec.column_array(el.Component.name(component))[0]
+
+    assert np.isclose(column(diagnostics.KineticEnergy), 1.0)
+    assert np.isclose(column(diagnostics.PotentialEnergy), 2.0 * 9.80665 * 3.0)
+    assert np.isclose(
+        column(diagnostics.TotalEnergy),
+        column(diagnostics.KineticEnergy) + column(diagnostics.PotentialEnergy),
+    )
+    assert np.allclose(column(diagnostics.LinearMomentum), [2.0, 0.0, 0.0])
+    # r x p with r = (0.1, 1, 3) after ten ticks and p = (2, 0, 0)
+    assert np.allclose(column(diagnostics.AngularMomentum), [0.0, 6.0, -2.0])
+
+
+def test_state_machine():
+    from elodin import fsm
+
+    Elapsed = ty.Annotated[jax.Array, el.Component("fsm_elapsed", el.ComponentType.F64)]
+    Throttle = ty.Annotated[jax.Array, el.Component("fsm_throttle", el.ComponentType.F64)]
+
+    @el.map
+    def elapse(t: Elapsed) -> Elapsed:
+        return t + 1.0
+
+    machine = fsm.StateMachine(
+        name="mission",
+        states=["idle", "burn", "coast"],
+        transitions=[
+            fsm.Transition("idle", "burn", lambda t, throttle: t >= 2.0),
+            fsm.Transition("burn", "coast", lambda t, throttle: t >= 4.0),
+        ],
+        inputs=(Elapsed, Throttle),
+        outputs=(Throttle,),
+        on_entry={"burn": lambda t, throttle: 1.0 + 0.0 * throttle},
+        on_exit={"burn": lambda t, throttle: 0.0 * throttle},
+    )
+    w = el.World()
+    w.spawn([machine.archetype(), el.C((Elapsed, Throttle), (np.float64(0.0), np.float64(0.0)))])
+    exec = w.build(elapse.pipe(machine.system()))
+
+    def column(component):
+        return exec.column_array(el.Component.name(component))[0]
+
+    exec.run(3)
+    assert column(machine.State) == machine.index("burn")
+    assert column(Throttle) == 1.0
+    exec.run(3)
+    assert column(machine.State) == machine.index("coast")
+    assert column(Throttle) == 0.0
+    assert column(machine.Transitions) == 2
+
+
+def test_signal_routes():
+    from elodin import signals
+
+    Command = ty.Annotated[
+        jax.Array, el.Component("route_command", el.ComponentType(el.PrimitiveType.F64, (3,)))
+    ]
+    Actuator = ty.Annotated[jax.Array, el.Component("route_actuator", el.ComponentType.F64)]
+    Gimbal = ty.Annotated[
+        jax.Array, el.Component("route_gimbal", el.ComponentType(el.PrimitiveType.F64, (2,)))
+    ]
+
+    routes = signals.from_config(
+        [
+            {
+                "src": "route_command",
+                "dst": "route_actuator",
+                "select": 0,
+                "gain": 2.0,
+                "offset": 1.0,
+            },
+            {"src": "route_command", "dst": "route_gimbal", "select": [1, 2], "max": 0.5},
+        ],
+        [Command, Actuator, Gimbal],
+    )
+    w = el.World()
+    w.spawn(
+        el.C(
+            (Command, Actuator, Gimbal),
+            (np.array([3.0, 0.25, 2.0]), np.float64(0.0), np.zeros(2)),
+        )
+    )
+    exec = w.build(signals.system(routes))
+    exec.run()
+    assert np.isclose(exec.column_array(el.Component.name(Actuator))[0], 7.0)
+    assert np.allclose(exec.column_array(el.Component.name(Gimbal))[0], [0.25, 0.5])
+
+
+def test_discrete_filters():
+    from elodin import filters
+
+    dt = 0.001
+    notch = filters.notch(50.0, dt, q=2.0)
+    state = np.zeros(notch.order)
+    out = []
+    for i in range(2000):
+        y, state = notch.step(np.sin(2.0 * np.pi * 50.0 * i * dt), state)
+        out.append(y)
+    assert np.max(np.abs(np.array(out[-200:]))) < 1e-2
+
+    Raw = ty.Annotated[
+        jax.Array, el.Component("filter_raw", el.ComponentType(el.PrimitiveType.F64, (3,)))
+    ]
+    Smooth = ty.Annotated[
+        jax.Array, el.Component("filter_smooth", el.ComponentType(el.PrimitiveType.F64, (3,)))
+    ]
+    settled = filters.Filter("settled", filters.low_pass(5.0, dt), Raw, Smooth, shape=(3,))
+    w = el.World()
+    w.spawn(
+        [
+            el.C((Raw, Smooth), (np.array([1.0, 2.0, 3.0]), np.zeros(3))),
+            settled.archetype(initial=np.array([1.0, 2.0, 3.0])),
+        ]
+    )
+    exec = w.build(settled.system(), sim_time_step=dt)
+    exec.run(10)
+    assert np.allclose(exec.column_array(el.Component.name(Smooth))[0], [1.0, 2.0, 3.0])
+
+
+def test_transport_delay():
+    from elodin import delay
+
+    Command = ty.Annotated[jax.Array, el.Component("delay_command", el.ComponentType.F64)]
+    Applied = ty.Annotated[jax.Array, el.Component("delay_applied", el.ComponentType.F64)]
+
+    @el.map
+    def ramp(c: Command) -> Command:
+        return c + 1.0
+
+    Late = ty.Annotated[jax.Array, el.Component("delay_late", el.ComponentType.F64)]
+
+    dt = 0.01
+    latency = delay.Delay("actuator", Command, Applied, delay=0.025, dt=dt)
+    assert latency.ticks == 2 and np.isclose(latency.frac, 0.5)
+    late = delay.Delay.from_ticks("late", Command, Late, ticks=4)
+    w = el.World()
+    signals = el.C((Command, Applied, Late), (np.float64(0.0), np.float64(0.0), np.float64(0.0)))
+    w.spawn([signals, latency.archetype(), late.archetype()])
+    exec = w.build(ramp.pipe(latency.system()).pipe(late.system()), sim_time_step=dt)
+    exec.run(6)
+    assert np.isclose(exec.column_array(el.Component.name(Applied))[0], 3.5)
+    # the ring buffer has wrapped around, and still reads the sample from four ticks ago
+    assert np.isclose(exec.column_array(el.Component.name(Late))[0], 2.0)
+
+
+def test_attitude_statistics():
+    from elodin import attitude
+
+    def about_z(angle):
+        return np.array([0.0, 0.0, np.sin(angle / 2), np.cos(angle / 2)])
+
+    # q and -q are the same attitude, so flipping one sample doesn't change the average
+    quats = np.stack([about_z(0.1), about_z(0.3), -about_z(0.2)])
+    mean = attitude.average(quats)
+    assert np.allclose(mean, about_z(0.2), atol=1e-6)
+
+    stats = attitude.dispersion(quats)
+    assert np.allclose(stats.errors[:, 2], np.array([-0.1, 0.1, 0.0]), atol=1e-6)
+    assert np.isclose(stats.max_angle, 0.1, atol=1e-6)
+    assert np.isclose(stats.rms_angle, np.sqrt(0.02 / 3), atol=1e-6)
+    assert np.isclose(stats.covariance[2, 2], 0.01, atol=1e-6)
+    assert np.allclose(stats.covariance[:2, :2], 0.0, atol=1e-9)
+
+    weighted = attitude.average(quats, weights=np.array([1.0, 0.0, 0.0]))
+    assert np.allclose(weighted, about_z(0.1), atol=1e-6)
+
+
+def test_geomag_and_sun():
+    from elodin import geomag, sun
+
+    # the dipole term dominates: the field points north at the equator and down in the north
+    r = geomag.IGRF_RADIUS
+    equator = geomag.field_ecef(np.array([r, 0.0, 0.0]))
+    assert equator[2] > 2.0e4 and abs(equator[0]) < equator[2]
+    positions = np.array([[0.0, 0.0, r], [0.0, 0.0, -r]])
+    poles = geomag.field_ecef(positions)
+    assert poles.shape == (2, 3)
+    assert poles[0, 2] < -4.0e4 and poles[1, 2] < -4.0e4
+    assert np.all(np.isfinite(poles))
+    magnitude = np.linalg.norm(geomag.field_ecef(np.array([r + 500e3, 0.0, 0.0])))
+    assert magnitude < np.linalg.norm(equator)
+
+    # near the March equinox the Sun sits along +x
+    jd = 2460389.625  # 2024-03-20 03:00 UTC
+    s = sun.sun_position(jd)
+    assert np.isclose(np.linalg.norm(s), sun.AU, rtol=0.02)
+    assert s[0] / np.linalg.norm(s) > 0.999
+
+    orbit = sun.EARTH_RADIUS + 500e3
+    sat = np.array([[orbit, 0.0, 0.0], [-orbit, 0.0, 0.0], [0.0, orbit, 0.0]])
+    light = sun.illumination(sat, s)
+    assert np.allclose(light, np.array([1.0, 0.0, 1.0]))
+    assert np.array_equal(sun.eclipse_state(sat, s), np.array([sun.SUNLIT, sun.UMBRA, sun.SUNLIT]))
+    # grazing the shadow boundary puts the spacecraft in the penumbra
+    u = s / np.linalg.norm(s)
+    perp = np.cross(u, np.array([0.0, 0.0, 1.0]))
+    edge = -orbit * u + sun.EARTH_RADIUS * perp / np.linalg.norm(perp)
+    assert 0.0 < sun.illumination(edge, s) < 1.0
+
+
+def test_scenarios():
+    from elodin import scenarios
+
+    exec = scenarios.detumble(initial_rate=[0.2, -0.1, 0.15]).build()
+    exec.run(600)
+    rate = exec.column_array(el.Component.id(el.WorldVel)).to_numpy()[0][:3]
+    assert np.linalg.norm(rate) < 0.5 * np.linalg.norm(np.array([0.2, -0.1, 0.15]))
+
+    exec = scenarios.pendulum_stack(count=2, length=1.0).build()
+    exec.run(200)
+    pos = exec.column_array(el.Component.id(el.WorldPos)).to_numpy()
+    top, bottom = pos[0][4:], pos[1][4:]
+    assert np.isclose(np.linalg.norm(top), 1.0, rtol=0.05)
+    assert np.isclose(np.linalg.norm(bottom - top), 1.0, rtol=0.05)
+
+    assert set(scenarios.PRESETS) == {
+        "leo_station_keeping",
+        "detumble",
+        "rendezvous",
+        "pendulum_stack",
+    }
+
+
+def test_wrench_accounting():
+    from elodin.wrench import Wrenches
+
+    def run(enabled: bool):
+        wrenches = Wrenches("gravity", "drag", enabled=enabled)
+
+        @wrenches.effector("gravity")
+        def gravity(inertia: el.Inertia) -> el.SpatialForce:
+            return el.SpatialForce(linear=inertia.mass() * np.array([0.0, 0.0, -9.81]))
+
+        @wrenches.effector("drag")
+        def drag(vel: el.WorldVel) -> el.SpatialForce:
+            return el.SpatialForce(linear=-0.5 * vel.linear())
+
+        w = el.World()
+        w.spawn(
+            [
+                el.Body(
+                    world_vel=el.SpatialMotion(linear=np.array([2.0, 0.0, 0.0])),
+                    inertia=el.SpatialInertia(2.0),
+                ),
+                *wrenches.archetypes(),
+            ]
+        )
+        exec = w.build(el.six_dof(sys=gravity.pipe(drag)))
+        exec.run(1)
+        return wrenches, exec
+
+    wrenches, exec = run(True)
+    latest = wrenches.latest(exec)
+    assert np.allclose(latest["gravity"][0], [0.0, 0.0, 0.0, 0.0, 0.0, -19.62])
+    assert latest["drag"][0][3] < 0.0
+    force = exec.column_array(el.Component.id(el.Force)).to_numpy()[0]
+    assert np.allclose(force, latest["gravity"][0] + latest["drag"][0])
+
+    wrenches, exec = run(False)
+    assert wrenches.archetypes() == [] and wrenches.latest(exec) == {}
+    force = exec.column_array(el.Component.id(el.Force)).to_numpy()[0]
+    assert np.isclose(force[5], -19.62)
+
+
+def test_thruster():
+    from elodin import propulsion
+
+    thruster = propulsion.Thruster(isp=200.0, max_thrust=10.0, min_impulse_bit=0.05, rise_time=0.05)
+    w = el.World()
+    for command in [20.0, 1.0]:
+        w.spawn(
+            [
+                el.Body(inertia=el.SpatialInertia(mass=10.0)),
+                propulsion.ThrusterState(
+                    thrust_command=np.float64(command), propellant_mass=np.float64(1.0)
+                ),
+            ]
+        )
+    exec = w.build(thruster.system())
+    exec.run()
+    dt = 1.0 / 120.0
+    thrust = exec.column_array(el.Component.name(propulsion.ThrusterThrust))
+    propellant = exec.column_array(el.Component.name(propulsion.PropellantMass))
+    # the first command saturates and lags, the second is below the minimum impulse bit
+    expected = 10.0 * (1.0 - np.exp(-dt / 0.05))
+    assert np.isclose(thrust[0], expected)
+    assert thrust[1] == 0.0
+    assert np.isclose(propellant[0], 1.0 - expected / (200.0 * propulsion.G0) * dt)
+    assert propellant[1] == 1.0
+
+
+def test_magnetometer():
+    from elodin import geomag
+
+    def run(magnetometer: geomag.Magnetometer):
+        pos = np.array([7.0e6, 0.0, 0.0])
+        w = el.World()
+        # co-located bodies, whose noise still has to be independent
+        for _ in range(2):
+            w.spawn(
+                [
+                    el.Body(world_pos=el.SpatialTransform(linear=pos)),
+                    geomag.MagnetometerSensor(),
+                ]
+            )
+        exec = w.build(magnetometer.system())
+        exec.run()
+        field = exec.column_array(el.Component.name(geomag.MagneticField)).to_numpy()
+        reading = exec.column_array(el.Component.name(geomag.MagnetometerReading)).to_numpy()
+        return field, reading
+
+    field, reading = run(geomag.Magnetometer(bias=np.array([10.0, 0.0, 0.0])))
+    # ~20000 nT at 600 km altitude
+    assert 1e4 < np.linalg.norm(field[0]) < 4e4
+    assert np.allclose(reading[0], field[0] + np.array([10.0, 0.0, 0.0]))
+
+    field, reading = run(geomag.Magnetometer(noise_std=50.0))
+    noise = reading - field
+    assert not np.allclose(noise[0], noise[1])
+    assert np.all(np.abs(noise) < 500.0)
+
+
+def test_solar_radiation_pressure():
+    from elodin import sun
+
+    jd = 2460389.625  # near the March equinox, with the Sun along +x
+    moon = sun.moon_position(jd)
+    assert 3.5e8 < np.linalg.norm(moon) < 4.1e8
+
+    # third body pull on a GEO satellite is of order 1e-5 m/s^2
+    geo = np.array([0.0, 4.2164e7, 0.0])
+    accel = sun.third_body_accel(geo, moon, sun.MU_MOON)
+    assert 1e-6 < np.linalg.norm(accel) < 3e-5
+
+    orbit = sun.EARTH_RADIUS + 500e3
+    w = el.World()
+    for x in [orbit, -orbit]:
+        w.spawn(
+            [
+                el.Body(world_pos=el.SpatialTransform(linear=np.array([x, 0.0, 0.0]))),
+                sun.SrpBody(srp_area=np.float64(2.0), srp_coefficient=np.float64(1.5)),
+            ]
+        )
+    srp = sun.SolarRadiationPressure(epoch_jd=jd)
+    exec = w.build(srp.system())
+    exec.run()
+    force = exec.column_array(el.Component.id(el.Force)).to_numpy()
+    light = exec.column_array(el.Component.name(sun.Illumination)).to_numpy()
+    # pushed away from the Sun on the day side, nothing in the Earth's shadow
+    assert np.allclose(light, [1.0, 0.0])
+    assert np.isclose(force[0][3], -sun.SOLAR_PRESSURE * 1.5 * 2.0, rtol=0.05)
+    assert np.allclose(force[1], 0.0)
+
+
+def test_clock():
+    w = el.World()
+    w.spawn(el.Body())
+    exec = w.build(el.six_dof(0.125), sim_time_step=0.125)
+    exec.set_epoch_jd(2460389.625, "utc")
+    exec.time_scale = 4.0
+    assert exec.step(0.5) == 16
+    assert exec.time() == 2.0
+    assert np.isclose(exec.epoch_jd("utc"), 2460389.625 + 2.0 / 86400.0)
+    # TT runs 69.184 seconds ahead of UTC since the 2017 leap second
+    offset = (exec.epoch_jd("tt") - exec.epoch_jd("utc")) * 86400.0
+    assert np.isclose(offset, 69.184, atol=1e-3)
+
+    exec.pause()
+    assert exec.paused and exec.step(1.0) == 0
+    exec.resume()
+    assert exec.step(0.03125) == 1
+    try:
+        exec.epoch_jd("gps")
+        assert False, "expected an unknown time scale to be rejected"
+    except ValueError:
+        pass
+
+
+def test_multi_rate():
+    @el.map
+    def fast(x: X) -> X:
+        return x + 1.0
+
+    @el.map
+    def slow(x: X, y: Y) -> Y:
+        return x
+
+    @dataclass
+    class Test(el.Archetype):
+        x: X
+        y: Y
+
+    w = el.World()
+    w.spawn(Test(np.array([0.0]), np.array([0.0])))
+    exec = w.build(fast | slow.at_hz(25.0), sim_time_step=0.01)
+    seen = []
+    for _ in range(9):
+        exec.run()
+        seen.append(exec.column_array(el.Component.id(Y))[0])
+    # runs every fourth tick, starting with the first
+    assert seen == [1.0, 1.0, 1.0, 1.0, 5.0, 5.0, 5.0, 5.0, 9.0]
+    w = el.World()
+    w.spawn(Test(np.array([0.0]), np.array([0.0])))
+    try:
+        w.build(slow.at_hz(30.0), sim_time_step=0.01)
+        assert False, "expected a period that isn't a whole number of steps to be rejected"
+    except ValueError:
+        pass
+
+
+def test_momentum_management():
+    from elodin import geomag, wheels
+
+    def run(manager: wheels.MomentumManagement, h0):
+        w = el.World()
+        w.spawn(
+            [
+                el.Body(
+                    world_pos=el.SpatialTransform(linear=np.array([7.0e6, 0.0, 0.0])),
+                    inertia=el.SpatialInertia(4.0, np.array([0.04, 0.05, 0.03])),
+                ),
+                geomag.MagnetometerSensor(),
+                el.C(geomag.DipoleCommand, np.zeros(3)),
+                wheels.ReactionWheels(wheel_momentum=np.array(h0)),
+            ]
+        )
+        sys = (
+            geomag.Magnetometer().system()
+            | manager.system()
+            | wheels.ReactionWheelArray().system()
+            | manager.torquer.system()
+        )
+        exec = w.build(el.six_dof(1.0, sys), sim_time_step=1.0)
+        exec.run(300)
+        h = exec.column_array(el.Component.name(wheels.WheelMomentum)).to_numpy()[0]
+        rate = exec.column_array(el.Component.id(el.WorldVel)).to_numpy()[0][:3]
+        active = exec.column_array(el.Component.name(wheels.Desaturating)).to_numpy()[0]
+        b = exec.column_array(el.Component.name(geomag.MagneticField)).to_numpy()[0]
+        return h, rate, active, b / np.linalg.norm(b)
+
+    # only the momentum across the field can be dumped, the body's attitude stays put
+    torquer = geomag.Magnetorquer(max_dipole=50.0)
+    h0 = np.array([0.03, 0.02, 0.005])
+    h, rate, _, b = run(wheels.MomentumManagement(torquer=torquer), h0)
+    across = np.linalg.norm(h - np.dot(h, b) * b)
+    assert across < 0.1 * np.linalg.norm(h0 - np.dot(h0, b) * b)
+    assert np.isclose(np.dot(h, b), np.dot(h0, b), atol=1e-3)
+    assert np.linalg.norm(rate) < 1e-6
+
+    thruster = wheels.MomentumManagement(mode="thruster")
+    h, rate, active, _ = run(thruster, [0.045, 0.0, 0.0])
+    assert 0.009 < np.linalg.norm(h) <= 0.01 and active == 0.0
+    assert np.linalg.norm(rate) < 1e-6
+    try:
+        wheels.MomentumManagement(mode="drag")
+        assert False, "expected an unknown mode to be rejected"
+    except ValueError:
+        pass
+
+
+def test_bdot_detumble():
+    from elodin import geomag
+
+    omega0 = np.array([0.05, 0.03, -0.04])
+    w = el.World()
+    w.spawn(
+        [
+            el.Body(
+                world_pos=el.SpatialTransform(linear=np.array([7.0e6, 0.0, 0.0])),
+                world_vel=el.SpatialMotion(angular=omega0),
+                inertia=el.SpatialInertia(4.0, np.array([0.05, 0.05, 0.05])),
+            ),
+            geomag.MagnetometerSensor(),
+            geomag.Detumbler(),
+        ]
+    )
+    torquer = geomag.Magnetorquer(max_dipole=5.0)
+    sys = (
+        geomag.Magnetometer().system()
+        | geomag.BDot(gain=1e6, torquer=torquer).system()
+        | torquer.system()
+    )
+    exec = w.build(el.six_dof(1.0, sys), sim_time_step=1.0)
+    exec.run(600)
+    omega = exec.column_array(el.Component.id(el.WorldVel)).to_numpy()[0][:3]
+    b = exec.column_array(el.Component.name(geomag.MagneticField)).to_numpy()[0]
+    b = b / np.linalg.norm(b)
+
+    # the spin across the field is damped out, the spin about it can't be touched
+    across = np.linalg.norm(omega - np.dot(omega, b) * b)
+    assert across < 0.1 * np.linalg.norm(omega0 - np.dot(omega0, b) * b)
+    assert np.isclose(np.dot(omega, b), np.dot(omega0, b), atol=2e-3)
+    m = exec.column_array(el.Component.name(geomag.DipoleCommand)).to_numpy()[0]
+    assert np.all(np.abs(m) <= 5.0)
+
+
+def test_retention():
+    @el.map
+    def count(x: X) -> X:
+        return x + 1.0
+
+    @el.map
+    def spike(x: X) -> Y:
+        return np.where(x == 15.0, 100.0, 0.0)
+
+    @dataclass
+    class Test(el.Archetype):
+        x: X
+        y: Y
+
+    w = el.World()
+    w.spawn(Test(np.array([0.0]), np.array([0.0])))
+    exec = w.build(count | spike)
+    exec.set_retention(every=10, threshold=50.0)
+    exec.run(30)
+    df = exec.history()
+    # the spike and the tick after it survive the decimation
+    assert sorted(df["tick"].unique().to_list()) == [0, 10, 15, 16, 20, 30]
+
+
+def test_verify_dir():
+    import os
+    import tempfile
+
+    @el.map
+    def bump(x: X) -> X:
+        return x + 1.0
+
+    @dataclass
+    class Test(el.Archetype):
+        x: X
+
+    w = el.World()
+    w.spawn(Test(np.array([1.0])))
+    exec = w.build(bump)
+    exec.run(5)
+    with tempfile.TemporaryDirectory() as dir:
+        exec.write_to_dir(dir)
+        el.verify_dir(dir)
+        path = os.path.join(dir, "world", "metadata.json")
+        with open(path, "ab") as f:
+            f.write(b" ")
+        try:
+            el.verify_dir(dir)
+            assert False, "expected the modified file to be caught"
+        except OSError as err:
+            assert "metadata.json" in str(err)
+
+
+def test_entity_hierarchy():
+    @el.map
+    def bump(x: X) -> X:
+        return x + 1.0
+
+    @dataclass
+    class Test(el.Archetype):
+        x: X
+
+    w = el.World()
+    sat = w.spawn(Test(np.array([1.0])), name="sat1")
+    panel = w.spawn(Test(np.array([2.0])), name="solar_panel_left", parent=sat)
+    exec = w.build(bump)
+    exec.run(1)
+    assert exec.entity_path(sat) == "sat1"
+    assert exec.entity_path(panel) == "sat1/solar_panel_left"
+    found = exec.find_entity("sat1/solar_panel_left")
+    assert found is not None and str(found) == str(panel)
+    assert exec.find_entity("sat1/solar_panel_right") is None
+    parents = exec.column_array("parent")
+    assert parents.to_list() == [int(str(sat))]
+
+
+def test_attach():
+    @dataclass
+    class Mount(el.Archetype):
+        world_pos: el.WorldPos
+        attach_offset: el.AttachOffset
+
+    w = el.World()
+    body = w.spawn(
+        el.Body(
+            world_pos=el.SpatialTransform(linear=np.array([0.0, 0.0, 0.0])),
+            world_vel=el.SpatialMotion(linear=np.array([1.0, 0.0, 0.0])),
+            inertia=el.SpatialInertia(1.0),
+        )
+    )
+    w.spawn(
+        Mount(
+            el.SpatialTransform(linear=np.array([0.0, 0.0, 0.0])),
+            el.SpatialTransform(linear=np.array([0.0, 0.5, 0.0])),
+        ),
+        name="antenna",
+        parent=body,
+    )
+    exec = w.build(el.six_dof(1.0 / 60.0) | el.attach())
+    exec.run(60)
+    x = exec.column_array(el.Component.id(el.WorldPos)).to_numpy()
+    assert np.allclose(x[0][4:], np.array([1.0, 0.0, 0.0]))
+    assert np.allclose(x[1][4:], np.array([1.0, 0.5, 0.0]))
+
+
+def test_raycast():
+    w = el.World()
+    earth = w.spawn(
+        [
+            el.Body(world_pos=el.SpatialTransform(linear=np.array([0.0, 0.0, 0.0]))),
+            el.Shape(
+                w.insert_asset(el.Mesh.sphere(6378e3)),
+                w.insert_asset(el.Material.color(0.0, 0.0, 1.0)),
+            ),
+        ]
+    )
+
+    def at(x: float) -> el.Body:
+        return el.Body(world_pos=el.SpatialTransform(linear=np.array([x, 0.0, 0.0])))
+
+    station = w.spawn(at(6378.1e3))
+    overhead = w.spawn(at(7000e3))
+    behind = w.spawn(at(-7000e3))
+    exec = w.build(el.six_dof(1.0 / 60.0))
+    exec.run(1)
+    hit = exec.raycast([7000e3, 0.0, 0.0], [-1.0, 0.0, 0.0])
+    assert hit is not None
+    entity, toi, point = hit
+    assert str(entity) == str(earth)
+    assert np.isclose(toi, 622e3)
+    assert np.allclose(point, [6378e3, 0.0, 0.0])
+    assert exec.raycast([7000e3, 0.0, 0.0], [1.0, 0.0, 0.0]) is None
+    # rays can pass through the entities they're told to ignore
+    assert exec.raycast([7000e3, 0.0, 0.0], [-1.0, 0.0, 0.0], ignore=[earth]) is None
+    assert exec.line_of_sight(station, overhead)
+    assert not exec.line_of_sight(station, behind)
+
+
+def test_ground_station():
+    from elodin import geomag, ground
+
+    lla = np.array([0.0, 0.0, 0.0])
+    assert np.allclose(ground.lla_to_ecef(lla), np.array([ground.EARTH_A, 0.0, 0.0]))
+    angles = ground.look_angles(lla, np.array([ground.EARTH_A + 500e3, 0.0, 0.0]))
+    assert np.allclose(angles[1:], np.array([np.pi / 2, 500e3]))
+    angles = ground.look_angles(lla, np.array([ground.EARTH_A - 10e3, 0.0, 1000e3]))
+    assert np.isclose(angles[0], 0.0) and angles[1] < 0.0
+
+    # ~2.2 GHz over 1000 km is ~159 dB of free space loss
+    assert np.isclose(ground.LinkBudget().free_space_loss_db(1000e3), 159.3, atol=0.1)
+
+    jd = 2451545.0
+    to_inertial = geomag.earth_rotation(jd).T
+    w = el.World()
+    w.spawn(ground.GroundStation.from_degrees(0.0, 0.0, min_elevation=10.0))
+    for x in [ground.EARTH_A + 500e3, -(ground.EARTH_A + 500e3)]:
+        pos = to_inertial @ np.array([x, 0.0, 0.0])
+        w.spawn([el.Body(world_pos=el.SpatialTransform(linear=pos)), ground.GroundLink()])
+    exec = w.build(ground.Visibility(epoch_jd=jd).system())
+    exec.run()
+    visible = exec.column_array(el.Component.name(ground.Visible)).to_numpy()
+    margin = exec.column_array(el.Component.name(ground.LinkMargin)).to_numpy()
+    angles = exec.column_array(el.Component.name(ground.LookAngles)).to_numpy()
+    assert visible.tolist() == [1.0, 0.0]
+    assert np.isclose(angles[0][1], np.pi / 2, atol=1e-3)
+    assert np.isclose(angles[0][2], 500e3, rtol=1e-3)
+    assert margin[0] > 0.0
+
+
+def test_debris_field():
+    from elodin import debris
+
+    field = debris.DebrisField(
+        sources=[
+            debris.Shell(count=50, min_altitude=700e3, max_altitude=900e3),
+            debris.Cloud(count=20, center=np.array([1e7, 0.0, 0.0]), radius=1e3),
+        ],
+        seed=3,
+    )
+    pos, vel, diameter, mass = field.sample()
+    assert pos.shape == (70, 3) and vel.shape == (70, 3)
+    alt = np.linalg.norm(pos[:50], axis=1) - debris.EARTH_RADIUS
+    assert np.all((alt >= 700e3 - 1.0) & (alt <= 900e3 + 1.0))
+    assert np.all(np.linalg.norm(pos[50:] - np.array([1e7, 0.0, 0.0]), axis=1) <= 1e3)
+    assert np.all((diameter >= 0.01) & (diameter <= 1.0))
+    assert np.allclose(mass, 2700.0 * np.pi / 6.0 * diameter**3)
+
+    again = field.sample()
+    assert np.array_equal(again[0], pos) and np.array_equal(again[2], diameter)
+    other = debris.DebrisField(sources=field.sources, seed=4).sample()
+    assert not np.array_equal(other[0], pos)
+
+    w = el.World()
+    ids = debris.DebrisField(sources=[debris.Shell(count=5)], seed=1).spawn(w)
+    assert len(ids) == 5
+    exec = w.build(el.six_dof(1.0))
+    exec.run(1)
+    assert len(exec.column_array(el.Component.name(debris.Diameter))) == 5
+
+
+def test_filter_health():
+    from elodin import estimation
+
+    covar = np.array([[3.0, 1.0], [1.0, 2.0]])
+    assert np.isclose(estimation.nis(np.array([1.0, 0.0]), covar), 0.4)
+    assert np.isclose(estimation.nees(np.zeros(2), covar, np.array([0.0, 1.0])), 0.6)
+    low, high = estimation.chi2_interval(3)
+    assert np.isclose(low, 0.216, atol=0.05) and np.isclose(high, 9.348, atol=0.05)
+    low, high = estimation.chi2_interval(3, samples=100)
+    assert low < 3.0 < high and high - low < 1.0
+
+    @el.system
+    def health(
+        q: el.Query[estimation.Residual],
+    ) -> el.Query[estimation.Residual, estimation.Nis]:
+        residual = np.array([0.1, -0.2, 0.0])
+        return q.map(
+            (estimation.Residual, estimation.Nis),
+            lambda _: (residual, estimation.nis(residual, 0.01 * np.eye(3))),
+        )
+
+    w = el.World()
+    w.spawn(estimation.FilterHealth(), name="filter")
+    exec = w.build(health)
+    exec.run()
+    nis = exec.column_array(el.Component.name(estimation.Nis)).to_numpy()
+    assert np.isclose(nis[0], 5.0)
+
+
+def test_targeting_diagnostics():
+    from elodin import targeting
+
+    mu, b0, v_inf = targeting.MU_EARTH, 20e6, 3e3
+    # far out on an approach in the equatorial plane, then over the pole
+    bt, br = targeting.b_plane(np.array([-1e10, b0, 0.0]), np.array([v_inf, 0.0, 0.0]), mu)
+    assert np.isclose(abs(bt), b0, rtol=1e-2) and abs(br) < 1e-3 * b0
+    bt, br = targeting.b_plane(np.array([-1e10, 0.0, b0]), np.array([v_inf, 0.0, 0.0]), mu)
+    assert abs(bt) < 1e-3 * b0 and np.isclose(abs(br), b0, rtol=1e-2)
+    circular = targeting.b_plane(np.array([7e6, 0.0, 0.0]), np.array([0.0, 7.5e3, 0.0]), mu)
+    assert np.all(np.isnan(circular))
+
+    r = 7e6
+    v = np.sqrt(mu / r)
+    speed, fpa = targeting.entry_conditions(np.array([r, 0.0, 0.0]), np.array([0.0, v, 0.0]), mu, r)
+    assert np.isclose(speed, v) and np.isclose(fpa, 0.0, atol=1e-6)
+    speed, fpa = targeting.entry_conditions(
+        np.array([r, 0.0, 0.0]), np.array([-1e3, 0.0, 0.0]), mu, r - 100e3
+    )
+    assert np.isclose(fpa, -np.pi / 2)
+    missed = targeting.entry_conditions(np.array([r, 0.0, 0.0]), np.array([0.0, v, 0.0]), mu, 6e6)
+    assert np.isnan(missed[1])
+
+    w = el.World()
+    w.spawn(
+        [
+            el.Body(
+                world_pos=el.SpatialTransform(linear=np.array([-1e10, b0, 0.0])),
+                world_vel=el.SpatialMotion(linear=np.array([v_inf, 0.0, 0.0])),
+            ),
+            targeting.BPlaneDiagnostics(),
+        ]
+    )
+    target = np.array([b0, 0.0])
+    exec = w.build(targeting.BPlane(target=target).system())
+    exec.run()
+    b = exec.column_array(el.Component.name(targeting.BPlaneCoords)).to_numpy()[0]
+    assert np.isclose(b[2], np.linalg.norm(b[:2] - target))
+
+
+def test_capture():
+    viewport = el.Panel.viewport(pos=[0.0, -10.0, 2.0], looking_at=[0.0, 0.0, 0.0])
+    capture = el.Capture(viewport, "captures/orbit", every=10, end=100, fps=30)
+    assert capture.asset_name() == "capture"
+    w = el.World()
+    w.spawn(capture, name="capture")
+    w.spawn(el.Capture(viewport, "captures/stills", ticks=[0, 50]), name="stills")
+
+    with pytest.raises(ValueError):
+        el.Capture(el.Panel.graph(), "captures/graph")
+    with pytest.raises(ValueError):
+        el.Capture(viewport, "captures/endless", fps=30)
+
+
+def test_campaign_export():
+    import json
+    import os
+    import sqlite3
+    import tempfile
+
+    from elodin import campaign
+
+    @el.map
+    def bump(x: X) -> X:
+        return x + 1.0
+
+    @dataclass
+    class Test(el.Archetype):
+        x: X
+
+    w = el.World()
+    w.spawn(Test(np.array([1.0])))
+    exec = w.build(bump)
+    exec.run(5)
+    with tempfile.TemporaryDirectory() as dir:
+        for run_id in range(2):
+            run_dir = os.path.join(dir, str(run_id))
+            exec.write_to_dir(run_dir)
+            sample = {"run_id": run_id, "seed": 2**64 - 1, "time_step": 0.01}
+            sample["draws"] = {"thrust": 10.0 * run_id, "mass": 1.0}
+            if run_id == 1:
+                sample["stop_reason"] = "landed"
+            with open(os.path.join(run_dir, "sample.json"), "w") as f:
+                json.dump(sample, f)
+
+        db = os.path.join(dir, "campaign.db")
+        metrics = {"rows": lambda df: df.height}
+        assert campaign.export_sqlite(dir, db, metrics) == 2
+        assert campaign.export_sqlite(dir, db, metrics) == 2
+        with sqlite3.connect(db) as conn:
+            assert conn.execute("SELECT count(*) FROM inputs").fetchone() == (4,)
+            assert conn.execute("SELECT seed FROM runs WHERE run_id = 1").fetchone() == (-1,)
+            stops = conn.execute("SELECT stop_reason FROM runs ORDER BY run_id").fetchall()
+            assert stops == [(None,), ("landed",)]
+            rows = conn.execute(
+                "SELECT i.value, m.value FROM inputs i JOIN metrics m USING (run_id) "
+                "WHERE i.name = 'thrust' ORDER BY i.value"
+            ).fetchall()
+        assert len(rows) == 2 and rows[0][0] == 0.0 and rows[1][0] == 10.0
+
+
+def test_pointing():
+    from elodin import pointing
+
+    pos, vel = np.array([7e6, 0.0, 0.0]), np.array([0.0, 7.5e3, 0.0])
+    command = pointing.align(np.array([0.0, 0.0, 1.0]), -pos, np.array([1.0, 0.0, 0.0]), vel)
+    rot = el.Quaternion(command)
+    assert np.allclose(rot @ np.array([0.0, 0.0, 1.0]), [-1.0, 0.0, 0.0], atol=1e-9)
+    assert np.allclose(rot @ np.array([1.0, 0.0, 0.0]), [0.0, 1.0, 0.0], atol=1e-9)
+    # a small yaw off the commanded attitude shows up about the body z axis
+    yaw = el.Quaternion.from_axis_angle(np.array([0.0, 0.0, 1.0]), np.array(0.1))
+    err = pointing.attitude_error((rot * yaw).vector(), command)
+    assert np.allclose(err, [0.0, 0.0, 0.1], atol=1e-9)
+    assert np.allclose(pointing.attitude_error(command, command), 0.0, atol=1e-9)
+
+    w = el.World()
+    w.spawn(
+        [
+            el.Body(
+                world_pos=el.SpatialTransform(linear=pos, angular=rot * yaw),
+                world_vel=el.SpatialMotion(linear=vel),
+            ),
+            pointing.PointingError(),
+        ]
+    )
+    exec = w.build(pointing.Pointing(mode="nadir").system())
+    exec.run()
+    err = exec.column_array(el.Component.name(pointing.AttitudeError)).to_numpy()[0]
+    rate = exec.column_array(el.Component.name(pointing.RateError)).to_numpy()[0]
+    assert np.allclose(err, [0.0, 0.0, 0.1], atol=1e-6)
+    # the body isn't turning, so it lags the orbit rate about the orbit normal
+    assert np.isclose(np.linalg.norm(rate), 7.5e3 / 7e6)
+    with pytest.raises(ValueError):
+        pointing.Pointing(mode="zenith")
+
+
+def test_dtype_policy():
+    traced = []
+
+    @el.map
+    def nudge(x: X) -> X:
+        traced.append(x.dtype)
+        return x + 1e-9
+
+    @dataclass
+    class Test(el.Archetype):
+        x: X
+
+    def run(policy):
+        w = el.World()
+        w.spawn(Test(np.array([1.0])))
+        exec = w.build(nudge, dtype_policy=policy)
+        exec.run()
+        return exec.column_array(el.Component.name(X)).to_numpy()[0]
+
+    # f32 can't hold the nudge, but the result is still stored as f64
+    x = run(el.DtypePolicy())
+    assert traced[-1] == np.float32 and x.dtype == np.float64 and x == 1.0
+    x = run(el.DtypePolicy(f64=(X,)))
+    assert traced[-1] == np.float64 and x > 1.0
+    run(el.DtypePolicy(bf16=(X,)))
+    assert traced[-1] == np.bfloat16
+    with el.DtypePolicy():
+        run(None)
+    assert traced[-1] == np.float32
+    run(None)
+    assert traced[-1] == np.float64
+
+
+def test_constants_override():
+    from elodin import constants, targeting, tle
+
+    assert constants.current() is constants.DEFAULT
+    assert targeting.MU_EARTH == constants.DEFAULT.mu_earth
+    heavy = constants.DEFAULT.replace(mu_earth=2.0 * constants.DEFAULT.mu_earth)
+    with pytest.raises(TypeError):
+        constants.DEFAULT.replace(mu_mars=4.28e13)
+
+    def entry_speed(overrides):
+        w = el.World()
+        w.spawn(
+            [
+                el.Body(
+                    world_pos=el.SpatialTransform(linear=np.array([7e6, 0.0, 0.0])),
+                    world_vel=el.SpatialMotion(linear=np.array([0.0, 7e3, 0.0])),
+                ),
+                targeting.EntryDiagnostics(),
+            ]
+        )
+        system = targeting.EntryInterface(radius=6.5e6).system()
+        exec = w.build(system, constants=overrides)
+        exec.run()
+        return exec.column_array(el.Component.name(targeting.EntryState)).to_numpy()[0][0]
+
+    mu, energy = constants.DEFAULT.mu_earth, 2.0 * (1.0 / 6.5e6 - 1.0 / 7e6)
+    assert np.isclose(entry_speed(None), np.sqrt(7e3**2 + energy * mu))
+    assert np.isclose(entry_speed(heavy), np.sqrt(7e3**2 + energy * 2.0 * mu))
+    assert constants.current() is constants.DEFAULT
+
+    (iss,) = tle.parse_tles(
+        """1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537"""
+    )
+    a = iss.semi_major_axis()
+    # helpers used while setting up a world read the constants when called
+    with heavy:
+        assert np.isclose(iss.semi_major_axis() ** 3, 2.0 * a**3)
+        # each instance keeps its own tokens, so overrides nest
+        light = constants.DEFAULT.replace(mu_earth=0.5 * constants.DEFAULT.mu_earth)
+        with light:
+            assert constants.current() is light
+        assert constants.current() is heavy
+    assert constants.current() is constants.DEFAULT
+
+
+def test_hydrodynamics():
+    from elodin import constants, hydro
+
+    w = el.World()
+    w.spawn(
+        [
+            el.Body(
+                world_vel=el.SpatialMotion(linear=np.array([2.0, 0.0, 0.0])),
+                inertia=el.SpatialInertia(mass=500.0),
+            ),
+            hydro.Hull(
+                hull_height=np.float64(2.0),
+                hydro_drag=np.array([10.0, 0.0, 0.0]),
+                added_mass=np.array([0.0, 0.0, 0.0, 500.0, 0.0, 0.0]),
+            ),
+        ]
+    )
+    water = hydro.Hydrodynamics()
+    exec = w.build(water.system().pipe(water.added_mass_system()))
+    exec.run()
+    force = exec.column_array(el.Component.name(el.Force)).to_numpy()[0]
+    fraction = exec.column_array(el.Component.name(hydro.SubmergedFraction)).to_numpy()[0]
+    # a hull centered on the surface is half under water
+    assert np.isclose(fraction, 0.5)
+    assert np.isclose(force[5], 1025.0 * constants.DEFAULT.standard_gravity * 0.5)
+    # the drag on the submerged half, driving the rigid mass and half the added mass
+    assert np.isclose(force[3], -10.0 * 2.0**2 * 0.5 * 500.0 / 750.0)
+    assert np.allclose(force[:3], 0.0)
+
+
+def test_wheel_contact():
+    from elodin import rover
+
+    w = el.World()
+    w.spawn(
+        [
+            el.Body(
+                world_pos=el.SpatialTransform(linear=np.array([0.0, 0.0, 0.4])),
+                inertia=el.SpatialInertia(mass=100.0),
+            ),
+            rover.RoverDrive(drive_command=np.array([1.0, 1.0])),
+        ]
+    )
+    contact = rover.WheelContact(
+        mounts=np.array([[1.0, 0.5, 0.0], [1.0, -0.5, 0.0], [-1.0, 0.5, 0.0], [-1.0, -0.5, 0.0]]),
+        radius=0.2,
+        stiffness=2500.0,
+        damping=100.0,
+        rest_length=0.3,
+        mu=0.5,
+    )
+    exec = w.build(contact.system())
+    exec.run()
+    force = exec.column_array(el.Component.name(el.Force)).to_numpy()[0]
+    # each suspension is compressed by 0.1 m
+    assert np.isclose(force[5], 4 * 250.0)
+    # the wheels spin at 1 m/s while the rover is still, so friction is saturated
+    assert np.isclose(force[3], 0.5 * 4 * 250.0)
+    assert np.isclose(force[4], 0.0)
+    # traction below the center of mass pitches the nose up
+    assert np.allclose(force[:3], [0.0, -4 * 0.4 * 125.0, 0.0])
+
+
+def test_heightfield():
+    from elodin import terrain
+
+    xs, ys = np.arange(3) * 2.0, np.arange(4) * 1.0
+    field = terrain.Heightfield(10.0 + 0.5 * xs[None, :] + 0.25 * ys[:, None], spacing=(2.0, 1.0))
+    h, n = field.sample(np.array([1.0, 1.5]))
+    assert np.isclose(h, 10.875)
+    assert np.allclose(n, np.array([-0.5, -0.25, 1.0]) / np.sqrt(1.3125))
+    assert np.allclose(jax.grad(field.height)(np.array([3.0, 2.5])), [0.5, 0.25])
+    # past the grid the height is held at the nearest edge
+    h, n = field.sample(np.array([-5.0, -5.0]))
+    assert np.isclose(h, 10.0) and np.allclose(n, [0.0, 0.0, 1.0])
+
+    asset = field.asset()
+    assert asset.asset_name() == "heightfield" and len(asset.bytes()) > 0
+    assert field.mesh().asset_name() == "mesh"
+    with pytest.raises(ValueError):
+        terrain.Heightfield(np.zeros((2, 2)), spacing=(-1.0, 1.0)).asset()
+
+
+def test_attitude_sensors():
+    from elodin import sensors
+
+    def run(system, archetype, vel=np.zeros(3), bodies=1):
+        w = el.World()
+        for _ in range(bodies):
+            w.spawn(
+                [
+                    el.Body(
+                        world_pos=el.SpatialTransform(linear=np.array([7e6, 0.0, 0.0])),
+                        world_vel=el.SpatialMotion(angular=vel),
+                    ),
+                    archetype,
+                ]
+            )
+        exec = w.build(system)
+        exec.run()
+        return exec
+
+    # with the boresight along +z, neither the Sun nor the Earth is in view
+    tracker = sensors.StarTracker(noise_std=np.array([1e-4, 1e-4, 1e-3]))
+    exec = run(tracker.system(), sensors.StarTrackerSensor())
+    reading = exec.column_array(el.Component.name(sensors.StarTrackerReading)).to_numpy()[0]
+    valid = exec.column_array(el.Component.name(sensors.StarTrackerValid)).to_numpy()[0]
+    assert valid == 1.0
+    assert 0.0 < np.linalg.norm(reading[:3]) < 1e-2
+
+    # pointed at the Earth, the tracker drops out and holds its last reading
+    down = el.Quaternion.from_axis_angle(np.array([0.0, 1.0, 0.0]), -np.pi / 2).vector()
+    exec = run(sensors.StarTracker(mounting=down).system(), sensors.StarTrackerSensor())
+    reading = exec.column_array(el.Component.name(sensors.StarTrackerReading)).to_numpy()[0]
+    valid = exec.column_array(el.Component.name(sensors.StarTrackerValid)).to_numpy()[0]
+    assert valid == 0.0
+    assert np.allclose(reading, [0.0, 0.0, 0.0, 1.0])
+
+    # a gyro mounted with its x axis along the body z axis
+    mounting = el.Quaternion.from_axis_angle(np.array([0.0, 1.0, 0.0]), -np.pi / 2).vector()
+    gyro = sensors.Gyro(mounting=mounting, bias=np.array([1e-3, 0.0, 0.0]))
+    exec = run(gyro.system(), sensors.GyroSensor(), vel=np.array([0.0, 0.0, 0.1]))
+    reading = exec.column_array(el.Component.name(sensors.GyroReading)).to_numpy()[0]
+    assert np.allclose(reading, [0.101, 0.0, 0.0])
+
+    # co-located gyros, whose noise still has to be independent
+    gyro = sensors.Gyro(angle_random_walk=1e-4, bias_instability=1e-5, bias_correlation_time=10.0)
+    exec = run(gyro.system(), sensors.GyroSensor(), bodies=2)
+    readings = exec.column_array(el.Component.name(sensors.GyroReading)).to_numpy()
+    biases = exec.column_array(el.Component.name(sensors.GyroBias)).to_numpy()
+    reading, bias = readings[0], biases[0]
+    assert np.all(bias != 0.0) and np.all(np.abs(bias) < 1e-4)
+    assert np.all(reading != bias) and np.all(np.abs(reading) < 1e-1)
+    assert np.all(readings[0] != readings[1]) and np.all(biases[0] != biases[1])
+
+
+def test_navigation_state():
+    from elodin import navigation
+
+    def about_z(angle):
+        return el.Quaternion.from_axis_angle(np.array([0.0, 0.0, 1.0]), angle)
+
+    w = el.World()
+    sat = w.spawn(
+        [
+            el.Body(
+                world_pos=el.SpatialTransform(linear=np.array([1.0, 0.0, 0.0])),
+                world_vel=el.SpatialMotion(
+                    angular=np.array([0.0, 0.0, 0.1]), linear=np.array([1.0, 0.0, 0.0])
+                ),
+            ),
+            navigation.NavState(
+                nav_pos=el.SpatialTransform(angular=about_z(0.05)),
+                nav_vel=el.SpatialMotion(
+                    angular=np.array([0.0, 0.0, 0.1]), linear=np.array([1.0, 0.0, 0.0])
+                ),
+            ),
+        ]
+    )
+    w.spawn(navigation.panel(sat))
+    exec = w.build(navigation.dead_reckoning() | navigation.system(), sim_time_step=0.1)
+    exec.run()
+    pos_error = exec.column_array(el.Component.name(navigation.NavPosError)).to_numpy()[0]
+    vel_error = exec.column_array(el.Component.name(navigation.NavVelError)).to_numpy()[0]
+    # the truth is held still, while the estimate moved 0.1 m and turned 0.01 rad
+    assert np.allclose(pos_error, [0.0, 0.0, -0.06, 0.9, 0.0, 0.0], atol=1e-6)
+    assert np.allclose(vel_error, 0.0)
+
+
+def test_parameter_sweep():
+    import itertools
+    import os
+
+    from elodin import sweep
+
+    grid = sweep.ParameterSweep().add("mass", [1.0, 2.0]).linspace("thrust", 0.0, 10.0, 3)
+    assert len(grid) == 6
+    assert grid[4] == {"mass": 2.0, "thrust": 5.0}
+    combos = itertools.product([1.0, 2.0], [0.0, 5.0, 10.0])
+    assert list(grid) == [{"mass": m, "thrust": t} for m, t in combos]
+    assert grid.current(["sim.py", "batch", "--run-id", "3"]) == grid[3]
+    assert grid.current(["sim.py", "batch", "--run-id=5"]) == grid[5]
+    assert grid.current(["sim.py"]) == grid[0]
+    with pytest.raises(IndexError):
+        grid[6]
+    with pytest.raises(ValueError):
+        grid.arange("mass", 0.0, 1.0, 0.5)
+
+    commands = grid.commands("sim.py", "results", ticks=10)
+    assert len(commands) == 6
+    assert commands[5][2:6] == ["batch", "--run-id", "5", "--seed"]
+    assert commands[5][-1] == os.path.join("results", "5")
+
+
+def test_stop_condition():
+    import polars as pl
+
+    landed = el.StopCondition("landed", el.WorldPos, lambda pos: pos[:, 6] <= 0.0)
+    name, component, check = landed._as_tuple()
+    assert (name, component) == ("landed", "world_pos")
+    above = pl.Series([[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 10.0], [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 5.0]])
+    below = pl.Series([[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 10.0], [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, -1.0]])
+    assert not check(above.cast(pl.Array(pl.Float64, 7)))
+    assert check(below.cast(pl.Array(pl.Float64, 7)))
+
+    late = el.StopCondition("late", "time", lambda t: t > 1.0)
+    assert late._as_tuple()[1] == "time"
+
+
+def test_optimize():
+    import json
+    import os
+    import tempfile
+
+    from elodin import optimize
+
+    @el.map
+    def bump(x: X) -> X:
+        return x + 1.0
+
+    @dataclass
+    class Test(el.Archetype):
+        x: X
+
+    def simulate(params):
+        w = el.World()
+        w.spawn(Test(np.array([params["x0"]])))
+        exec = w.build(bump)
+        exec.run(3)
+        return exec
+
+    objectives = {
+        "miss": lambda df: (df["x"].max() - 10.0) ** 2,
+        "start": lambda df: abs(df["x"].min()),
+    }
+    study = optimize.Study(simulate, objectives)
+    best = study.minimize("miss", {"x0": 0.0}, step=1.0, max_evals=60)
+    assert abs(best.params["x0"] - 7.0) < 1e-2
+    assert best.objectives["miss"] < 1e-4
+    assert len(study.evaluations) <= 60
+
+    front = study.pareto_front(["miss", "start"])
+    assert best in front
+    for e in front:
+        assert not any(
+            o.objectives["miss"] < e.objectives["miss"]
+            and o.objectives["start"] < e.objectives["start"]
+            for o in study.evaluations
+        )
+
+    x, value = optimize.nelder_mead(lambda x: (x[0] - 1.0) ** 2 + (x[1] + 2.0) ** 2, [0.0, 0.0])
+    assert abs(x[0] - 1.0) < 1e-3 and abs(x[1] + 2.0) < 1e-3 and value < 1e-6
+
+    # the budget is a hard limit, even partway through a shrink or the initial simplex
+    for max_evals in [1, 2, 5, 17, 40]:
+        calls = []
+
+        def counted(x):
+            calls.append(x)
+            return float(np.sum(np.abs(x - 3.0)))
+
+        optimize.nelder_mead(counted, [0.0, 0.0, 0.0], max_evals=max_evals)
+        assert len(calls) <= max_evals
+
+    with tempfile.TemporaryDirectory() as dir:
+        for run_id, x0 in enumerate([7.0, 2.0]):
+            run_dir = os.path.join(dir, str(run_id))
+            simulate({"x0": x0}).write_to_dir(run_dir)
+            sample = {"run_id": run_id, "seed": 0, "time_step": 0.01, "draws": {"x0": x0}}
+            with open(os.path.join(run_dir, "sample.json"), "w") as f:
+                json.dump(sample, f)
+        runs = optimize.evaluate_batch(dir, objectives)
+    assert [run.run_id for run in runs] == [0, 1]
+    assert runs[0].params == {"x0": 7.0} and runs[0].objectives["miss"] == 0.0
+    assert optimize.pareto_front(runs, ["miss", "start"]) == runs
+
+
+def test_python_system_trace_time():
+    import time
+
+    @el.system
+    def slow(q: el.Query[X]) -> el.Query[X]:
+        # only runs while the system is traced
+        time.sleep(0.02)
+        return q.map(X, lambda x: x + 1.0)
+
+    @dataclass
+    class Test(el.Archetype):
+        x: X
+
+    w = el.World()
+    w.spawn(Test(np.array([1.0])))
+    exec = w.build(slow)
+    times = [p["trace"] for name, p in exec.system_profile().items() if "slow" in name]
+    assert len(times) == 1 and times[0] >= 20.0
+
+
+def test_maneuver():
+    from elodin import maneuver
+
+    mu = 3.986004418e14
+    r1, r2 = 6678e3, 42164e3
+    dv1, dv2, tof = maneuver.hohmann(r1, r2, mu)
+    a = (r1 + r2) / 2.0
+    assert np.isclose(dv1, np.sqrt(mu * (2.0 / r1 - 1.0 / a)) - np.sqrt(mu / r1))
+    assert np.isclose(dv2, np.sqrt(mu / r2) - np.sqrt(mu * (2.0 / r2 - 1.0 / a)))
+    assert np.isclose(tof, np.pi * np.sqrt(a**3 / mu))
+    *_, bi_tof = maneuver.bi_elliptic(r1, 15.0 * r1, 30.0 * r1, mu)
+    assert bi_tof > tof
+
+    # a quarter of a circular orbit
+    r = 7000e3
+    v = np.sqrt(mu / r)
+    quarter = 0.5 * np.pi * np.sqrt(r**3 / mu)
+    v1, v2 = maneuver.lambert([r, 0.0, 0.0], [0.0, r, 0.0], quarter, mu)
+    assert np.allclose(v1, [0.0, v, 0.0], atol=1e-3)
+    assert np.allclose(v2, [-v, 0.0, 0.0], atol=1e-3)
+    with pytest.raises(ValueError):
+        maneuver.lambert([r, 0.0, 0.0], [-r, 0.0, 0.0], quarter, mu)
+
+    vel = maneuver.apply_impulse([r, 0.0, 0.0], [0.0, v, 0.0], [0.0, 100.0, 0.0])
+    assert np.allclose(vel, [0.0, v + 100.0, 0.0])
@@ -1,6 +1,7 @@
 use crate::*;
 
-use impeller::ArchetypeName;
+use impeller::{ArchetypeName, ColumnLayout};
+use pyo3::exceptions::PyValueError;
 
 use numpy::PyUntypedArray;
 
@@ -8,6 +9,7 @@ pub struct Archetype<'py> {
     pub component_data: Vec<Metadata>,
     pub arrays: Vec<&'py PyUntypedArray>,
     pub archetype_name: ArchetypeName,
+    pub layout: ColumnLayout,
 }
 
 impl Archetype<'_> {
@@ -30,10 +32,22 @@ impl<'s> FromPyObject<'s> for Archetype<'s> {
             .extract::<Vec<Metadata>>()?;
         let arrays = archetype.call_method0("arrays")?;
         let arrays = arrays.extract::<Vec<&numpy::PyUntypedArray>>()?;
+        let layout = if archetype.hasattr("layout")? {
+            match archetype.call_method0("layout")?.extract::<&str>()? {
+                "entity_major" => ColumnLayout::EntityMajor,
+                "element_major" => ColumnLayout::ElementMajor,
+                layout => {
+                    return Err(PyValueError::new_err(format!("unknown layout {layout:?}")));
+                }
+            }
+        } else {
+            ColumnLayout::EntityMajor
+        };
         Ok(Self {
             component_data,
             arrays,
             archetype_name,
+            layout,
         })
     }
 }
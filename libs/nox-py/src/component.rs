@@ -38,6 +38,9 @@ impl Component {
                     TagValue::Bool(f)
                 } else if let Ok(v) = v.extract::<i64>(py) {
                     TagValue::Int(v)
+                } else if let Ok(v) = v.extract::<f64>(py) {
+                    // matches the string form `Metadata::set_bounds` stores "min" and "max" in
+                    TagValue::String(v.to_string())
                 } else {
                     TagValue::Unit
                 };
@@ -1,6 +1,6 @@
 use nox_ecs::nox;
 use pyo3::{
-    exceptions::{PyRuntimeError, PyValueError},
+    exceptions::{PyIOError, PyRuntimeError, PyValueError},
     PyErr,
 };
 
@@ -33,12 +33,21 @@ pub enum Error {
 impl From<Error> for PyErr {
     fn from(value: Error) -> Self {
         match value {
+            Error::Nox(err @ (nox::Error::InvalidTable | nox::Error::LambertUndefined)) => {
+                PyValueError::new_err(err.to_string())
+            }
             Error::NoxEcs(nox_ecs::Error::ComponentNotFound) => {
                 PyValueError::new_err("component not found")
             }
             Error::NoxEcs(nox_ecs::Error::ValueSizeMismatch) => {
                 PyValueError::new_err("value size mismatch")
             }
+            Error::NoxEcs(
+                err @ (nox_ecs::Error::UnknownTimeScale(_) | nox_ecs::Error::InvalidRate(_)),
+            ) => PyValueError::new_err(err.to_string()),
+            Error::NoxEcs(err @ nox_ecs::Error::Corrupt { .. }) => {
+                PyIOError::new_err(err.to_string())
+            }
             Error::NoxEcs(nox_ecs::Error::PyO3(err)) | Error::PyErr(err) => err,
             err => PyRuntimeError::new_err(err.to_string()),
         }
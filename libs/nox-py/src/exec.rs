@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::*;
 
@@ -9,6 +9,8 @@ use pyo3_polars::{PyDataFrame, PySeries};
 #[pyclass]
 pub struct Exec {
     pub exec: nox_ecs::WorldExec<Compiled>,
+    /// The shapes ray casts are run against, and the tick they were gathered at.
+    pub ray_scene: Option<(u64, nox_ecs::RayScene)>,
 }
 
 #[pymethods]
@@ -40,10 +42,120 @@ impl Exec {
         Ok(())
     }
 
+    /// Advances the simulation by `dt` seconds, returning the number of ticks that were run.
+    pub fn step(&mut self, py: Python<'_>, dt: f64) -> Result<u64, Error> {
+        if !dt.is_finite() || dt < 0.0 {
+            return Err(Error::PyErr(PyValueError::new_err(
+                "dt must be a non-negative number of seconds",
+            )));
+        }
+        let ticks = self.exec.step(std::time::Duration::from_secs_f64(dt))?;
+        py.check_signals()?;
+        Ok(ticks)
+    }
+
+    /// Stops `step` from advancing the simulation until `resume` is called. `run` still ticks.
+    pub fn pause(&mut self) {
+        self.exec.clock.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.exec.clock.paused = false;
+    }
+
+    #[getter]
+    pub fn paused(&self) -> bool {
+        self.exec.clock.paused
+    }
+
+    /// Simulated seconds per second passed to `step`.
+    #[getter]
+    pub fn time_scale(&self) -> f64 {
+        self.exec.clock.time_scale
+    }
+
+    #[setter]
+    pub fn set_time_scale(&mut self, time_scale: f64) -> Result<(), Error> {
+        if !time_scale.is_finite() || time_scale < 0.0 {
+            return Err(Error::PyErr(PyValueError::new_err(
+                "time_scale must be a non-negative number",
+            )));
+        }
+        self.exec.clock.time_scale = time_scale;
+        Ok(())
+    }
+
+    /// The simulation time at the current tick, in seconds.
+    pub fn time(&self) -> f64 {
+        self.exec.time().as_secs_f64()
+    }
+
+    /// The Julian date of the current tick in `scale`, one of "utc", "tai" or "tt".
+    #[pyo3(signature = (scale="tt"))]
+    pub fn epoch_jd(&self, scale: &str) -> Result<f64, Error> {
+        Ok(self.exec.epoch().jd(scale.parse()?))
+    }
+
+    /// Sets the Julian date of tick zero, in `scale`.
+    #[pyo3(signature = (jd, scale="utc"))]
+    pub fn set_epoch_jd(&mut self, jd: f64, scale: &str) -> Result<(), Error> {
+        self.exec.clock.epoch = nox_ecs::Epoch::from_jd(jd, scale.parse()?);
+        Ok(())
+    }
+
     pub fn profile(&self) -> HashMap<&'static str, f64> {
         self.exec.profile()
     }
 
+    pub fn system_profile(&self) -> BTreeMap<String, BTreeMap<&'static str, f64>> {
+        self.exec
+            .system_profile()
+            .into_iter()
+            .map(|(name, profile)| (name, profile.phases()))
+            .collect()
+    }
+
+    /// Records only the first `keep_first` ticks and every `every`th tick after them, along with
+    /// any tick where a value moved by more than `threshold` since the last recorded one.
+    #[pyo3(signature = (every=1, keep_first=0, threshold=None))]
+    pub fn set_retention(&mut self, every: u64, keep_first: u64, threshold: Option<f64>) {
+        self.exec.world.retention = nox_ecs::Retention {
+            keep_first,
+            every,
+            threshold,
+        };
+    }
+
+    /// The names of the entity and its ancestors, joined with `/`.
+    pub fn entity_path(&self, entity_id: EntityId) -> String {
+        nox_ecs::entity_path(&self.exec.world, entity_id.inner)
+    }
+
+    pub fn find_entity(&self, path: &str) -> Option<EntityId> {
+        nox_ecs::find_entity(&self.exec.world, path).map(|inner| EntityId { inner })
+    }
+
+    /// Casts a ray against the shapes of every entity not in `ignore`, returning the closest one
+    /// hit, the distance to it in multiples of `dir`, and the point it was hit at.
+    #[pyo3(signature = (origin, dir, max_toi=f64::INFINITY, ignore=vec![]))]
+    pub fn raycast(
+        &mut self,
+        origin: [f64; 3],
+        dir: [f64; 3],
+        max_toi: f64,
+        ignore: Vec<EntityId>,
+    ) -> Option<(EntityId, f64, [f64; 3])> {
+        let ignore = ignore.iter().map(|id| id.inner).collect::<Vec<_>>();
+        ray_scene(&mut self.ray_scene, &self.exec.world)
+            .raycast(origin, dir, max_toi, &ignore)
+            .map(|hit| (EntityId { inner: hit.entity }, hit.toi, hit.point))
+    }
+
+    pub fn line_of_sight(&mut self, a: EntityId, b: EntityId) -> bool {
+        let world = &self.exec.world;
+        ray_scene(&mut self.ray_scene, world).line_of_sight(world, a.inner, b.inner)
+    }
+
     pub fn write_to_dir(&mut self, path: String) -> Result<(), Error> {
         self.exec.write_to_dir(path).map_err(Error::from)
     }
@@ -65,3 +177,17 @@ impl Exec {
         Ok(PySeries(series))
     }
 }
+
+/// The ray cast scene in `cache`, gathered from `world` on first use and refreshed whenever the
+/// world ticked since.
+fn ray_scene<'a>(
+    cache: &'a mut Option<(u64, nox_ecs::RayScene)>,
+    world: &nox_ecs::World,
+) -> &'a nox_ecs::RayScene {
+    let (tick, scene) = cache.get_or_insert_with(|| (world.tick, nox_ecs::RayScene::new(world)));
+    if *tick != world.tick {
+        scene.refresh(world);
+        *tick = world.tick;
+    }
+    scene
+}
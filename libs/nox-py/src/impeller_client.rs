@@ -1,5 +1,7 @@
 use bytes::Bytes;
-use impeller::{client::AsyncClient, ColumnPayload, Packet, Payload, StreamId};
+use impeller::{
+    access::Credentials, client::SecureClient, ColumnPayload, Packet, Payload, StreamId,
+};
 use pyo3::prelude::*;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::sync::Mutex;
@@ -14,7 +16,7 @@ pub struct Impeller {
 
 pub struct ImpellerInner {
     addr: SocketAddr,
-    client: Option<impeller::client::TcpClient>,
+    client: Option<SecureClient>,
 }
 
 #[pymethods]
@@ -61,12 +63,12 @@ impl ImpellerInner {
         Self { addr, client: None }
     }
 
-    async fn client(&mut self) -> Result<&mut impeller::client::TcpClient, Error> {
+    async fn client(&mut self) -> Result<&mut SecureClient, Error> {
         if let Some(ref mut client) = self.client {
             Ok(client)
         } else {
-            let stream = tokio::net::TcpStream::connect(self.addr).await?;
-            let client = AsyncClient::from_stream(stream);
+            let credentials = Credentials::from_env()?;
+            let client = SecureClient::connect(self.addr, &credentials).await?;
             Ok(self.client.insert(client))
         }
     }
@@ -93,7 +95,7 @@ impl ImpellerInner {
 }
 
 async fn send_inner(
-    client: &mut impeller::client::TcpClient,
+    client: &mut SecureClient,
     entity_id: EntityId,
     component_data: &[Metadata],
     arrays: &[Bytes],
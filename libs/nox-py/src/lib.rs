@@ -26,6 +26,7 @@ mod exec;
 mod graph;
 mod impeller_client;
 mod linalg;
+mod maneuver;
 mod query;
 mod s10;
 mod spatial;
@@ -71,6 +72,8 @@ impl PyUntypedArrayExt for PyUntypedArray {
 pub enum Integrator {
     Rk4,
     SemiImplicit,
+    /// Adaptive Dormand–Prince 5(4), in up to four attempts per tick.
+    Dopri5,
 }
 
 impl FromStr for Integrator {
@@ -80,6 +83,7 @@ impl FromStr for Integrator {
         match s {
             "rk4" => Ok(Integrator::Rk4),
             "semi-implicit" => Ok(Integrator::SemiImplicit),
+            "dopri5" => Ok(Integrator::Dopri5),
             _ => Err(Error::PyErr(PyValueError::new_err("unknown integrator"))),
         }
     }
@@ -90,29 +94,62 @@ impl From<Integrator> for nox_ecs::Integrator {
         match integrator {
             Integrator::Rk4 => nox_ecs::Integrator::Rk4,
             Integrator::SemiImplicit => nox_ecs::Integrator::SemiImplicit,
+            Integrator::Dopri5 => nox_ecs::Integrator::Dopri5(nox::Dopri5::default().max_steps(4)),
+        }
+    }
+}
+
+/// How [`six_dof`] represents the attitude of the bodies it integrates, see
+/// [`nox_ecs::six_dof::Attitude`].
+#[pyclass]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Attitude {
+    Quaternion,
+    /// A `WorldDcm` direction cosine matrix, which every integrated body must have.
+    Dcm,
+}
+
+impl From<Attitude> for nox_ecs::six_dof::Attitude {
+    fn from(attitude: Attitude) -> Self {
+        match attitude {
+            Attitude::Quaternion => nox_ecs::six_dof::Attitude::Quaternion,
+            Attitude::Dcm => nox_ecs::six_dof::Attitude::Dcm,
         }
     }
 }
 
 #[pyfunction]
-#[pyo3(signature = (time_step = None, sys = None, integrator = Integrator::Rk4))]
-pub fn six_dof(time_step: Option<f64>, sys: Option<System>, integrator: Integrator) -> System {
+#[pyo3(signature = (time_step = None, sys = None, integrator = Integrator::Rk4, attitude = Attitude::Quaternion))]
+pub fn six_dof(
+    time_step: Option<f64>,
+    sys: Option<System>,
+    integrator: Integrator,
+    attitude: Attitude,
+) -> System {
     let integrator = integrator.into();
+    let attitude = attitude.into();
     let sys: Arc<dyn nox_ecs::System<Arg = (), Ret = ()> + Send + Sync> =
         if let Some(dt) = time_step {
             if let Some(sys) = sys {
-                nox_ecs::six_dof::six_dof_with_dt(|| sys, dt, integrator)
+                nox_ecs::six_dof::six_dof_with_dt(|| sys, dt, integrator, attitude)
             } else {
-                nox_ecs::six_dof::six_dof_with_dt(|| (), dt, integrator)
+                nox_ecs::six_dof::six_dof_with_dt(|| (), dt, integrator, attitude)
             }
         } else if let Some(sys) = sys {
-            nox_ecs::six_dof::six_dof(|| sys, integrator)
+            nox_ecs::six_dof::six_dof(|| sys, integrator, attitude)
         } else {
-            nox_ecs::six_dof::six_dof(|| (), integrator)
+            nox_ecs::six_dof::six_dof(|| (), integrator, attitude)
         };
     System { inner: sys }
 }
 
+/// Carries entities with an attach offset along with their parent, see [`nox_ecs::attach`].
+#[pyfunction]
+pub fn attach() -> System {
+    use nox_ecs::IntoSystem;
+    System::new(nox_ecs::attach.into_system())
+}
+
 #[pyfunction]
 pub fn read_batch_results(path: String) -> Result<(Vec<PyDataFrame>, Vec<String>), Error> {
     let sample_dirs = walkdir::WalkDir::new(path)
@@ -122,19 +159,33 @@ pub fn read_batch_results(path: String) -> Result<(Vec<PyDataFrame>, Vec<String>
         .filter(|entry| entry.file_type().is_file())
         .filter(|entry| entry.file_name() == "sample.json")
         .filter_map(|entry| entry.path().parent().map(Path::to_path_buf))
+        .filter(|dir| dir.join("world").exists())
         .collect::<HashSet<_>>();
     let mut dfs = Vec::default();
     let mut ids = Vec::default();
     for sample_dir in sample_dirs {
         let dir_name = sample_dir.file_name().unwrap().to_string_lossy();
         ids.push(dir_name.to_string());
-        let world = PolarsWorld::read_from_dir(sample_dir)?;
-        let df = world.join_archetypes()?;
-        dfs.push(PyDataFrame(df));
+        dfs.push(read_run_results(sample_dir.to_string_lossy().into_owned())?);
     }
     Ok((dfs, ids))
 }
 
+/// Reads the results of a single run of a batch, the directory holding its `sample.json`, so a
+/// large batch can be processed one run at a time.
+#[pyfunction]
+pub fn read_run_results(path: String) -> Result<PyDataFrame, Error> {
+    let world = PolarsWorld::read_from_dir(path)?;
+    Ok(PyDataFrame(world.join_archetypes()?))
+}
+
+/// Checks a directory written by `Exec.write_to_dir` against its checksums, raising an `OSError`
+/// naming the first corrupt file.
+#[pyfunction]
+pub fn verify_dir(path: String) -> Result<(), Error> {
+    nox_ecs::verify_dir(path).map_err(Error::from)
+}
+
 #[pyfunction]
 pub fn _get_cache_dir() -> PyResult<String> {
     let directory = directories::ProjectDirs::from("systems", "elodin", "elodin-cli")
@@ -176,18 +227,25 @@ pub fn elodin(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Color>()?;
     m.add_class::<Panel>()?;
     m.add_class::<Integrator>()?;
+    m.add_class::<Attitude>()?;
     m.add_class::<GraphEntity>()?;
     m.add_class::<Glb>()?;
+    m.add_class::<Heightfield>()?;
     m.add_class::<Line3d>()?;
+    m.add_class::<Capture>()?;
     m.add_class::<PyFnSystem>()?;
     m.add_class::<QueryMetadata>()?;
     m.add_class::<SystemBuilder>()?;
     m.add_class::<System>()?;
     m.add_function(wrap_pyfunction!(six_dof, m)?)?;
+    m.add_function(wrap_pyfunction!(attach, m)?)?;
     m.add_function(wrap_pyfunction!(read_batch_results, m)?)?;
+    m.add_function(wrap_pyfunction!(read_run_results, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_dir, m)?)?;
     m.add_function(wrap_pyfunction!(skew, m)?)?;
     m.add_function(wrap_pyfunction!(_get_cache_dir, m)?)?;
     ukf::register(m)?;
+    maneuver::register(m)?;
     s10::register(m)?;
     Ok(())
 }
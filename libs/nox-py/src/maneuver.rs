@@ -0,0 +1,87 @@
+//! Host-side bindings for `nox::maneuver`, for mission-design scripts that plan transfers before
+//! building a world.
+use nox_ecs::nox::{self, ArrayRepr, Vector};
+use pyo3::prelude::*;
+
+use crate::Error;
+
+type Vector3 = Vector<f64, 3, ArrayRepr>;
+
+/// The burns and flight time of a Hohmann transfer between circular orbits of radius `r1` and
+/// `r2`, as `(dv1, dv2, time_of_flight)`. The burns are signed along the velocity.
+#[pyfunction]
+pub fn hohmann(r1: f64, r2: f64, mu: f64) -> (f64, f64, f64) {
+    let transfer = nox::Hohmann::<f64, ArrayRepr>::new(r1, r2, mu);
+    (
+        transfer.dv1.into_buf(),
+        transfer.dv2.into_buf(),
+        transfer.time_of_flight.into_buf(),
+    )
+}
+
+/// The burns and flight time of a bi-elliptic transfer between circular orbits of radius `r1` and
+/// `r2` through the intermediate radius `rb`, as `(dv1, dv2, dv3, time_of_flight)`.
+#[pyfunction]
+pub fn bi_elliptic(r1: f64, r2: f64, rb: f64, mu: f64) -> (f64, f64, f64, f64) {
+    let transfer = nox::BiElliptic::<f64, ArrayRepr>::new(r1, r2, rb, mu);
+    (
+        transfer.dv1.into_buf(),
+        transfer.dv2.into_buf(),
+        transfer.dv3.into_buf(),
+        transfer.time_of_flight.into_buf(),
+    )
+}
+
+/// The velocities at `r1` and `r2` of the single revolution transfer taking `time_of_flight`
+/// seconds between them.
+#[pyfunction]
+#[pyo3(signature = (r1, r2, time_of_flight, mu, prograde=true, tol=1e-9, max_iters=200))]
+pub fn lambert(
+    r1: [f64; 3],
+    r2: [f64; 3],
+    time_of_flight: f64,
+    mu: f64,
+    prograde: bool,
+    tol: f64,
+    max_iters: usize,
+) -> Result<([f64; 3], [f64; 3]), Error> {
+    let (v1, v2) = nox::Lambert::default()
+        .prograde(prograde)
+        .tol(tol)
+        .max_iters(max_iters)
+        .solve(
+            &Vector3::from_buf(r1),
+            &Vector3::from_buf(r2),
+            time_of_flight,
+            mu,
+        )?;
+    Ok((v1.into_buf(), v2.into_buf()))
+}
+
+/// The rotation from the radial, transverse, normal (RTN) frame of a body at `pos` moving at
+/// `vel` to the inertial frame.
+#[pyfunction]
+pub fn rtn_to_inertial(pos: [f64; 3], vel: [f64; 3]) -> [[f64; 3]; 3] {
+    nox::rtn_to_inertial(&Vector3::from_buf(pos), &Vector3::from_buf(vel)).into_buf()
+}
+
+/// The inertial velocity after an impulsive burn `dv_rtn`, given in the body's RTN frame.
+#[pyfunction]
+pub fn apply_impulse(pos: [f64; 3], vel: [f64; 3], dv_rtn: [f64; 3]) -> [f64; 3] {
+    nox::apply_impulse(
+        &Vector3::from_buf(pos),
+        &Vector3::from_buf(vel),
+        &Vector3::from_buf(dv_rtn),
+    )
+    .into_buf()
+}
+
+pub fn register(parent_module: &Bound<'_, PyModule>) -> PyResult<()> {
+    let child = PyModule::new_bound(parent_module.py(), "maneuver")?;
+    child.add_function(wrap_pyfunction!(hohmann, &child)?)?;
+    child.add_function(wrap_pyfunction!(bi_elliptic, &child)?)?;
+    child.add_function(wrap_pyfunction!(lambert, &child)?)?;
+    child.add_function(wrap_pyfunction!(rtn_to_inertial, &child)?)?;
+    child.add_function(wrap_pyfunction!(apply_impulse, &child)?)?;
+    parent_module.add_submodule(&child)
+}
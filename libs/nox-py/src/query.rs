@@ -125,6 +125,17 @@ impl QueryInner {
             .collect()
     }
 
+    /// The id of each entity, in the order of the query's rows.
+    pub fn entity_ids(&self) -> Vec<u64> {
+        let mut ids = vec![0; self.query.len];
+        for (id, index) in &self.query.entity_map {
+            if let Some(slot) = ids.get_mut(*index) {
+                *slot = id.0;
+            }
+        }
+        ids
+    }
+
     pub fn join_query(&self, other: &QueryInner) -> QueryInner {
         let query = join_query(self.query.clone(), other.query.clone());
         let metadata = self
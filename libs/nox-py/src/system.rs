@@ -4,7 +4,7 @@ use impeller::World;
 use nox_ecs::{
     graph::{EdgeComponent, GraphQuery, TotalEdge},
     nox::{jax::JaxNoxprFn, NoxprComp, NoxprFn, NoxprNode, NoxprTy},
-    CompiledSystem, Exec, ExecMetadata, SystemParam,
+    CompiledSystem, Exec, ExecMetadata, SystemParam, SystemTrace,
 };
 use pyo3::types::{IntoPyDict, PyBytes, PyTuple};
 use std::{collections::HashMap, sync::Arc};
@@ -16,7 +16,6 @@ pub struct PyFnSystem {
     input_ids: Vec<ComponentId>,
     output_ids: Vec<ComponentId>,
     edge_ids: Vec<ComponentId>,
-    #[allow(dead_code)]
     name: String,
 }
 
@@ -61,6 +60,7 @@ impl nox_ecs::System for PyFnSystem {
     }
 
     fn compile(&self, world: &World) -> Result<nox_ecs::CompiledSystem, nox_ecs::Error> {
+        let start = std::time::Instant::now();
         let sys = self.sys.clone();
         let mut input_ids = self.input_ids.clone();
         let output_ids = self.output_ids.clone();
@@ -109,17 +109,49 @@ impl nox_ecs::System for PyFnSystem {
         } else {
             NoxprTy::Tuple(tys.collect())
         };
+        let arg_shapes = input_ids
+            .iter()
+            .map(|&id| {
+                let col = builder
+                    .world
+                    .column_by_id(id)
+                    .ok_or(nox_ecs::Error::ComponentNotFound)?;
+                let ty = &col.metadata.component_type;
+                let shape = std::iter::once(col.len() as i64)
+                    .chain(ty.shape.iter().copied())
+                    .collect::<Vec<_>>();
+                Ok((shape, nox::jax::dtype(&ty.primitive_ty.element_type())?))
+            })
+            .collect::<Result<Vec<_>, nox_ecs::Error>>()?;
         let func = Python::with_gil(|py| {
             let func = sys.call1(py, (py_builder,))?;
             let jax = py.import_bound("jax").unwrap();
             let jit_args = [("keep_unused", true)].into_py_dict_bound(py);
-            Ok::<_, pyo3::PyErr>(jax.call_method("jit", (func,), Some(&jit_args))?.into())
+            let func = jax.call_method("jit", (func,), Some(&jit_args))?;
+            // jit only traces once the whole pipeline is lowered, so trace the system here to
+            // attribute the time to it; lowering the pipeline then reuses the cached trace
+            let args = arg_shapes
+                .iter()
+                .map(|(shape, dtype)| {
+                    let shape = PyTuple::new_bound(py, shape);
+                    jax.call_method1("ShapeDtypeStruct", (shape, *dtype))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let eval_args = PyTuple::new_bound(py, std::iter::once(func.clone()).chain(args));
+            jax.call_method1("eval_shape", eval_args)?;
+            Ok::<_, pyo3::PyErr>(func.into())
         })?;
         let func = NoxprFn::new(vec![], Noxpr::jax(func));
         Ok(CompiledSystem {
             computation: NoxprComp::new(func, ty),
             inputs: input_ids,
             outputs: output_ids,
+            systems: vec![SystemTrace {
+                name: self.name.clone(),
+                outputs: self.output_ids.clone(),
+                trace_time: start.elapsed(),
+            }],
+            integrator: None,
         })
     }
 }
@@ -185,6 +217,9 @@ def build_expr(jit, args):
             ExecMetadata {
                 arg_ids: self.inputs.clone(),
                 ret_ids: self.outputs.clone(),
+                systems: self.systems.clone(),
+                params: vec![],
+                integrator: self.integrator.clone(),
             },
             hlo_module,
         );
@@ -237,6 +272,25 @@ impl System {
     pub fn __or__(&self, other: System) -> System {
         self.pipe(other)
     }
+
+    #[pyo3(signature = (divisor, phase = 0))]
+    pub fn every(&self, divisor: u64, phase: u64) -> System {
+        let rate = nox_ecs::Rate::Divisor(divisor);
+        System::new(nox_ecs::Schedule::new(self.clone(), rate).phase(phase))
+    }
+
+    #[pyo3(signature = (frequency, phase = 0))]
+    pub fn at_hz(&self, frequency: f64, phase: u64) -> Result<System, Error> {
+        if !frequency.is_finite() || frequency <= 0.0 {
+            return Err(Error::PyErr(PyValueError::new_err(
+                "frequency must be a positive number",
+            )));
+        }
+        let rate = nox_ecs::Rate::hz(frequency);
+        Ok(System::new(
+            nox_ecs::Schedule::new(self.clone(), rate).phase(phase),
+        ))
+    }
 }
 
 impl nox_ecs::System for System {
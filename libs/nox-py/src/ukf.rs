@@ -119,6 +119,12 @@ pub struct UKFState {
     prop_covar: PyObject,
     #[pyo3(get, set)]
     noise_covar: PyObject,
+    /// The residual of the last update's measurement, `None` before the first update.
+    #[pyo3(get)]
+    residual: Option<PyObject>,
+    /// The normalized innovation squared of the last update, `None` before the first update.
+    #[pyo3(get)]
+    nis: Option<PyObject>,
     config: UncheckedMerweConfig,
 }
 
@@ -146,6 +152,8 @@ impl UKFState {
             covar,
             prop_covar,
             noise_covar,
+            residual: None,
+            nis: None,
         })
     }
 
@@ -178,21 +186,33 @@ impl UKFState {
         let noise_covar: Tensor<f64, (Dyn, Dyn), Op> =
             Tensor::from_inner(Noxpr::jax(self.noise_covar.clone()));
 
-        let state = UncheckedState {
+        let (state, innovation) = UncheckedState {
             x_hat,
             covar,
             prop_covar,
             noise_covar,
         }
-        .update::<Dyn>(self.config, z, prop_fn_wrapper, measure_fn_wrapper)
+        .update_with_innovation::<Dyn>(self.config, z, prop_fn_wrapper, measure_fn_wrapper)
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
         self.x_hat = state.x_hat.inner().to_jax()?;
         self.covar = state.covar.inner().to_jax()?;
         self.prop_covar = state.prop_covar.inner().to_jax()?;
         self.noise_covar = state.noise_covar.inner().to_jax()?;
+        self.nis = Some(innovation.nis()?.inner().to_jax()?);
+        self.residual = Some(innovation.residual.inner().to_jax()?);
 
         Ok(())
     }
+
+    /// The normalized estimation error squared of the current estimate against the true state.
+    fn nees(&self, truth: PyObject) -> Result<PyObject, Error> {
+        let truth: Tensor<f64, Dyn, Op> = Tensor::from_inner(Noxpr::jax(truth));
+        let x_hat: Tensor<f64, Dyn, Op> = Tensor::from_inner(Noxpr::jax(self.x_hat.clone()));
+        let covar: Tensor<f64, (Dyn, Dyn), Op> = Tensor::from_inner(Noxpr::jax(self.covar.clone()));
+        let error = truth - x_hat;
+        let nees = error.dot(&covar.try_inverse()?.dot(&error));
+        nees.inner().to_jax().map_err(Error::from)
+    }
 }
 
 pub fn register(parent_module: &Bound<'_, PyModule>) -> PyResult<()> {
@@ -208,3 +208,72 @@ impl Line3d {
         Ok(PyBufBytes { bytes })
     }
 }
+
+#[pyclass]
+pub struct Capture {
+    inner: impeller::well_known::Capture,
+}
+
+#[pymethods]
+impl Capture {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (viewport, output, ticks = None, every = 1, start = 0, end = None, width = 1280, height = 720, fps = None))]
+    pub fn new(
+        viewport: Panel,
+        output: String,
+        ticks: Option<Vec<u64>>,
+        every: u64,
+        start: u64,
+        end: Option<u64>,
+        width: u32,
+        height: u32,
+        fps: Option<u32>,
+    ) -> PyResult<Self> {
+        use impeller::well_known::{CaptureFormat, CaptureTicks};
+        let impeller::well_known::Panel::Viewport(viewport) = viewport.inner else {
+            return Err(PyValueError::new_err("capture needs a viewport panel"));
+        };
+        let ticks = match (ticks, end) {
+            (Some(ticks), _) => CaptureTicks::List(ticks),
+            (None, Some(end)) => CaptureTicks::Every {
+                start,
+                step: every.max(1),
+                end,
+            },
+            (None, None) if fps.is_none() => CaptureTicks::Every {
+                start,
+                step: every.max(1),
+                end: u64::MAX,
+            },
+            (None, None) => {
+                return Err(PyValueError::new_err(
+                    "a video capture needs `ticks` or an `end` tick",
+                ))
+            }
+        };
+        let format = match fps {
+            Some(fps) => CaptureFormat::Mp4 { fps },
+            None => CaptureFormat::Png,
+        };
+        Ok(Self {
+            inner: impeller::well_known::Capture {
+                viewport,
+                width,
+                height,
+                ticks,
+                output,
+                format,
+            },
+        })
+    }
+
+    pub fn asset_name(&self) -> &'static str {
+        impeller::well_known::Capture::ASSET_NAME
+    }
+
+    pub fn bytes(&self) -> Result<PyBufBytes, Error> {
+        let bytes = postcard::to_allocvec(&self.inner).unwrap().into();
+        Ok(PyBufBytes { bytes })
+    }
+}
@@ -2,8 +2,10 @@ mod gizmos;
 mod gui;
 mod metadata;
 mod pbr;
+mod terrain;
 
 pub use gizmos::*;
 pub use gui::*;
 pub use metadata::*;
 pub use pbr::*;
+pub use terrain::*;
@@ -0,0 +1,51 @@
+use crate::*;
+
+use nox_ecs::impeller;
+use nox_ecs::impeller::Asset;
+use numpy::PyReadonlyArray2;
+
+#[pyclass]
+#[derive(Clone)]
+pub struct Heightfield {
+    pub inner: impeller::well_known::Heightfield,
+}
+
+#[pymethods]
+impl Heightfield {
+    #[new]
+    #[pyo3(signature = (heights, spacing, origin = [0.0, 0.0], geodetic_origin = None))]
+    pub fn new(
+        heights: PyReadonlyArray2<'_, f64>,
+        spacing: [f64; 2],
+        origin: [f64; 2],
+        geodetic_origin: Option<[f64; 3]>,
+    ) -> Result<Self, Error> {
+        let heights = heights.as_array();
+        let cols = heights.ncols();
+        let mut inner = impeller::well_known::Heightfield::new(
+            origin,
+            spacing,
+            cols,
+            heights.iter().copied().collect(),
+        )?;
+        if let Some([lat, lon, alt]) = geodetic_origin {
+            inner = inner.with_geodetic_origin(lat, lon, alt);
+        }
+        Ok(Self { inner })
+    }
+
+    pub fn mesh(&self) -> Mesh {
+        Mesh {
+            inner: self.inner.mesh(),
+        }
+    }
+
+    pub fn bytes(&self) -> Result<PyBufBytes, Error> {
+        let bytes = postcard::to_allocvec(&self.inner).unwrap().into();
+        Ok(PyBufBytes { bytes })
+    }
+
+    pub fn asset_name(&self) -> &'static str {
+        impeller::well_known::Heightfield::ASSET_NAME
+    }
+}
@@ -6,7 +6,13 @@ use nox_ecs::{
     impeller, increment_sim_tick, nox, spawn_tcp_server, IntoSystem, System as _, TimeStep, World,
     WorldExt,
 };
-use std::{collections::HashMap, net::SocketAddr, path::PathBuf, time};
+use pyo3_polars::PySeries;
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    path::PathBuf,
+    time,
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -24,6 +30,31 @@ pub enum Args {
         #[arg(default_value = "0.0.0.0:2240")]
         addr: SocketAddr,
     },
+    /// Runs the simulation headless for a fixed number of ticks, then prints a summary.
+    Batch {
+        #[arg(long, default_value = "1000")]
+        ticks: u64,
+        /// Overrides the simulation time step, in seconds.
+        #[arg(long)]
+        dt: Option<f64>,
+        /// The directory the final state is written to.
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Writes a checkpoint into the output directory every N ticks.
+        #[arg(long)]
+        checkpoint_interval: Option<u64>,
+        /// Streams telemetry to clients connecting to this address while running.
+        #[arg(long)]
+        stream: Option<SocketAddr>,
+        /// Records this run as a sample of a batch, seeding it from `--seed` and the run id.
+        #[arg(long)]
+        run_id: Option<u64>,
+        /// Re-runs the sample `--run-id` of the batch in this directory, with its recorded seed and time step.
+        #[arg(long, requires = "run_id")]
+        rerun: Option<PathBuf>,
+    },
     #[clap(hide = true)]
     Bench {
         #[arg(long, default_value = "1000")]
@@ -41,6 +72,7 @@ pub struct WorldBuilder {
 impl WorldBuilder {
     fn insert_entity_id(&mut self, archetype: &Archetype, entity_id: EntityId) {
         let archetype_name = archetype.archetype_name;
+        self.world.layouts.insert(archetype_name, archetype.layout);
         let columns = archetype.component_data.iter().cloned();
         for metadata in columns {
             let id = metadata.component_id();
@@ -85,7 +117,12 @@ impl WorldBuilder {
         Self::default()
     }
 
-    pub fn spawn(&mut self, spawnable: Spawnable, name: Option<String>) -> Result<EntityId, Error> {
+    pub fn spawn(
+        &mut self,
+        spawnable: Spawnable,
+        name: Option<String>,
+        parent: Option<EntityId>,
+    ) -> Result<EntityId, Error> {
         let entity_id = EntityId {
             inner: impeller::EntityId(self.world.entity_len),
         };
@@ -96,6 +133,10 @@ impl WorldBuilder {
             let metadata = self.world.insert_asset(metadata.inner);
             self.world.insert_with_id(metadata, entity_id.inner);
         }
+        if let Some(parent) = parent {
+            let parent = nox_ecs::Parent::new(parent.inner);
+            self.world.insert_with_id(parent, entity_id.inner);
+        }
         Ok(entity_id)
     }
 
@@ -126,6 +167,7 @@ impl WorldBuilder {
                     component_data: vec![Metadata { inner: metadata }],
                     arrays: vec![],
                     archetype_name,
+                    layout: Default::default(),
                 };
 
                 self.insert_entity_id(&archetype, entity_id);
@@ -220,6 +262,9 @@ impl WorldBuilder {
         default_playback_speed = 1.0,
         max_ticks = None,
         optimize = false,
+        draws = None,
+        stop_conditions = Vec::new(),
+        time_limit = None,
     ))]
     pub fn run(
         &mut self,
@@ -230,6 +275,9 @@ impl WorldBuilder {
         default_playback_speed: f64,
         max_ticks: Option<u64>,
         optimize: bool,
+        draws: Option<BTreeMap<String, f64>>,
+        stop_conditions: Vec<(String, String, PyObject)>,
+        time_limit: Option<f64>,
     ) -> Result<Option<String>, Error> {
         let _ = tracing_subscriber::fmt::fmt()
             .with_env_filter(
@@ -307,6 +355,71 @@ impl WorldBuilder {
                 std::fs::write(&plan_path, toml)?;
                 Ok(None)
             }
+            Args::Batch {
+                ticks,
+                dt,
+                output,
+                seed,
+                checkpoint_interval,
+                stream,
+                run_id,
+                rerun,
+            } => {
+                let config = match (run_id, rerun) {
+                    (Some(run_id), Some(batch_dir)) => {
+                        Some(nox_ecs::RunConfig::find(batch_dir, run_id)?.0)
+                    }
+                    (Some(run_id), None) => {
+                        let mut config = nox_ecs::RunConfig::new(
+                            run_id,
+                            seed.unwrap_or_default(),
+                            dt.unwrap_or(sim_time_step),
+                        )
+                        .capture_git_hash();
+                        config.draws.extend(draws.unwrap_or_default());
+                        Some(config)
+                    }
+                    _ => None,
+                };
+                let dt = config
+                    .as_ref()
+                    .map(|config| config.time_step)
+                    .or(dt)
+                    .unwrap_or(sim_time_step);
+                let exec = self.build_uncompiled(
+                    py,
+                    sys,
+                    dt,
+                    run_time_step,
+                    default_playback_speed,
+                    max_ticks,
+                )?;
+                let mut client = nox::Client::cpu()?;
+                if !optimize {
+                    client.disable_optimizations();
+                }
+                let mut stop_conditions: Vec<_> = stop_conditions
+                    .into_iter()
+                    .map(|(name, component, predicate)| stop_condition(name, component, predicate))
+                    .collect();
+                if let Some(limit) = time_limit {
+                    let limit = time::Duration::from_secs_f64(limit);
+                    stop_conditions.push(nox_ecs::StopCondition::time_limit(limit));
+                }
+                let batch = nox_ecs::Batch {
+                    ticks,
+                    seed: seed.filter(|_| config.is_none()),
+                    output,
+                    checkpoint_interval,
+                    config,
+                    stream,
+                    stop_conditions,
+                    ..Default::default()
+                };
+                let summary = batch.run(exec, client)?;
+                print!("{}", summary);
+                Ok(None)
+            }
             Args::Bench { ticks } => {
                 let mut exec = self.build(
                     py,
@@ -330,6 +443,9 @@ impl WorldBuilder {
                 println!("compile time:         {:.3} ms", profile["compile"]);
                 println!("write_to_dir time:    {:.3} ms", profile["write_to_dir"]);
                 println!("real_time_factor:     {:.3}", profile["real_time_factor"]);
+                for (name, phases) in exec.system_profile() {
+                    println!("trace {} time: {:.3} ms", name, phases["trace"]);
+                }
                 Ok(None)
             }
         }
@@ -367,7 +483,10 @@ impl WorldBuilder {
             client.disable_optimizations();
         }
         let exec = exec.compile(client.clone())?;
-        Ok(Exec { exec })
+        Ok(Exec {
+            exec,
+            ray_scene: None,
+        })
     }
 }
 
@@ -401,7 +520,26 @@ impl WorldBuilder {
         let tick_exec = xla_exec.compile_hlo_module(py, &world)?;
 
         let mut exec = nox_ecs::WorldExec::new(world, tick_exec, None);
+        exec.profiler.observe_systems(&xla_exec.systems);
         exec.profiler.build.observe(&mut start);
         Ok(exec)
     }
 }
+
+/// Wraps a Python `predicate`, called with the column of `component` and returning whether the run
+/// should stop, as a batch stop condition.
+fn stop_condition(name: String, component: String, predicate: PyObject) -> nox_ecs::StopCondition {
+    let id = ComponentId::new(&component);
+    nox_ecs::StopCondition::new(name, move |world| {
+        let series = world
+            .column_by_id(id)
+            .ok_or(nox_ecs::Error::ComponentNotFound)?
+            .series()?;
+        let stop = Python::with_gil(|py| {
+            predicate
+                .call1(py, (PySeries(series),))?
+                .extract::<bool>(py)
+        })?;
+        Ok(stop)
+    })
+}
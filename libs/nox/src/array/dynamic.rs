@@ -21,7 +21,7 @@ impl<T: Elem, S> DynArray<T, S> {
 
 impl<T: Elem> DynArray<T, Vec<T>> {
     pub fn from_shape_vec(shape: SmallVec<[usize; 4]>, storage: Vec<T>) -> Option<Self> {
-        let expected_len: usize = shape.iter().copied().sum();
+        let expected_len: usize = shape.iter().copied().product();
         if expected_len != storage.len() {
             return None;
         }
@@ -45,7 +45,7 @@ impl<T: Elem> ArrayBuf<T> for DynArray<T, Vec<T>> {
     }
 
     fn default(dims: &[usize]) -> Self {
-        let len: usize = dims.iter().copied().sum();
+        let len: usize = dims.iter().copied().product();
         let strides = crate::utils::calculate_strides(dims).collect::<SmallVec<[usize; 4]>>();
 
         let shape = SmallVec::from_slice(dims);
@@ -1,8 +1,8 @@
 //! Provides a local, non-XLA backend for operating on Tensors.
 use crate::{
-    AddDim, BroadcastDim, BroadcastedDim, ConstDim, DefaultMap, DefaultMappedDim, Dim, DottedDim,
-    Elem, Error, Field, OwnedRepr, RealField, ReplaceDim, ReplaceMappedDim, Repr, ScalarDim,
-    TensorDim,
+    AddDim, BroadcastDim, BroadcastedDim, CastLossyFrom, ConstDim, DefaultMap, DefaultMappedDim,
+    Dim, DottedDim, Elem, Error, Field, OwnedRepr, RealField, ReplaceDim, ReplaceMappedDim, Repr,
+    ScalarDim, TensorDim,
 };
 use crate::{Const, Dyn, ShapeConstraint};
 use alloc::{vec, vec::Vec};
@@ -333,12 +333,12 @@ macro_rules! impl_op {
 
                 match d1.as_ref().len().cmp(&d2.as_ref().len()) {
                     cmp::Ordering::Less | cmp::Ordering::Equal => {
-                        let mut out: Array<T1, BroadcastedDim<D1, D2>> =
-                            Array::zeroed(d2.as_ref());
                         let mut broadcast_dims = d2.clone();
                         if !cobroadcast_dims(broadcast_dims.as_mut(), d1.as_ref()) {
                             todo!("handle unbroadcastble dims {:?} {:?}", broadcast_dims.as_mut(), d1.as_ref());
                         }
+                        let mut out: Array<T1, BroadcastedDim<D1, D2>> =
+                            Array::zeroed(broadcast_dims.as_ref());
                         for ((a, b), out) in self
                             .broadcast_iter(broadcast_dims.clone())
                             .unwrap()
@@ -350,12 +350,12 @@ macro_rules! impl_op {
                         out
                     }
                     cmp::Ordering::Greater => {
-                        let mut out: Array<T1, BroadcastedDim<D1, D2>> =
-                            Array::zeroed(d2.as_ref());
                         let mut broadcast_dims = d1.clone();
                         if !cobroadcast_dims(broadcast_dims.as_mut(), d2.as_ref()) {
                             todo!("handle unbroadcastble dims {:?} {:?}", broadcast_dims.as_mut(), d2.as_ref());
                         }
+                        let mut out: Array<T1, BroadcastedDim<D1, D2>> =
+                            Array::zeroed(broadcast_dims.as_ref());
                         for ((b, a), out) in b
                             .broadcast_iter(broadcast_dims.clone())
                             .unwrap()
@@ -410,7 +410,7 @@ impl<T1: Elem, D1: Dim> Array<T1, D1> {
         <ShapeConstraint as BroadcastDim<D1, D2>>::Output: ArrayDim,
         D2: Dim + ConstDim,
     {
-        todo!()
+        self.broadcast_with_shape::<D2>(D2::const_dim())
     }
 
     pub fn broadcast_with_shape<D2>(
@@ -423,10 +423,10 @@ impl<T1: Elem, D1: Dim> Array<T1, D1> {
         D2: Dim,
     {
         let d1 = D1::array_shape(&self.buf);
-        let mut out: Array<T1, BroadcastedDim<D1, D2>> = Array::zeroed(broadcast_dims.as_mut());
         if !cobroadcast_dims(broadcast_dims.as_mut(), d1.as_ref()) {
             todo!("handle broadcastable dims");
         }
+        let mut out: Array<T1, BroadcastedDim<D1, D2>> = Array::zeroed(broadcast_dims.as_ref());
         for (a, out) in self
             .broadcast_iter(broadcast_dims)
             .unwrap()
@@ -466,6 +466,8 @@ macro_rules! impl_unary_op {
 }
 
 impl_unary_op!(RealField, sqrt);
+impl_unary_op!(RealField, exp);
+impl_unary_op!(RealField, ln);
 impl_unary_op!(RealField, sin);
 impl_unary_op!(RealField, cos);
 impl_unary_op!(RealField, abs);
@@ -473,6 +475,22 @@ impl_unary_op!(RealField, abs);
 impl_unary_op!(RealField, acos);
 impl_unary_op!(RealField, asin);
 
+impl<T1: Elem, D1: Dim> Array<T1, D1> {
+    /// Converts each element with [`CastLossyFrom`].
+    pub fn cast<T2: CastLossyFrom<T1>>(&self) -> Array<T2, D1> {
+        let d1 = D1::array_shape(&self.buf);
+        let mut out: Array<T2, D1> = Array::zeroed(d1.as_ref());
+        self.buf
+            .as_buf()
+            .iter()
+            .zip(out.buf.as_mut_buf().iter_mut())
+            .for_each(|(a, out)| {
+                *out = T2::cast_lossy_from(*a);
+            });
+        out
+    }
+}
+
 impl<T1: Elem, D1: Dim> Array<T1, D1> {
     pub fn neg(&self) -> Array<T1, D1>
     where
@@ -943,6 +961,58 @@ impl<T1: Elem, D1: Dim> Array<T1, D1> {
         out
     }
 
+    /// Takes the elementwise maximum of two arrays of the same shape.
+    pub fn max(&self, other: &Self) -> Self
+    where
+        T1: RealField,
+    {
+        let mut out = self.clone();
+        out.buf
+            .as_mut_buf()
+            .iter_mut()
+            .zip(other.buf.as_buf().iter())
+            .for_each(|(a, b)| {
+                *a = RealField::max(*a, *b);
+            });
+        out
+    }
+
+    /// Takes the elementwise minimum of two arrays of the same shape.
+    pub fn min(&self, other: &Self) -> Self
+    where
+        T1: RealField,
+    {
+        let mut out = self.clone();
+        out.buf
+            .as_mut_buf()
+            .iter_mut()
+            .zip(other.buf.as_buf().iter())
+            .for_each(|(a, b)| {
+                *a = RealField::min(*a, *b);
+            });
+        out
+    }
+
+    /// Picks `on_true` where `self < other` and `on_false` elsewhere, over arrays of the same
+    /// shape.
+    pub fn select_less(&self, other: &Self, on_true: &Self, on_false: &Self) -> Self
+    where
+        T1: PartialOrd,
+    {
+        let mut out = on_false.clone();
+        out.buf
+            .as_mut_buf()
+            .iter_mut()
+            .zip(self.buf.as_buf().iter().zip(other.buf.as_buf().iter()))
+            .zip(on_true.buf.as_buf().iter())
+            .for_each(|((out, (a, b)), t)| {
+                if a < b {
+                    *out = *t;
+                }
+            });
+        out
+    }
+
     pub fn atan2(&self, other: &Self) -> Self
     where
         T1: RealField,
@@ -1746,6 +1816,14 @@ mod tests {
         assert_eq!(a.abs(), array![[1.0, 2.0], [3.0, 4.0]]);
     }
 
+    #[test]
+    fn test_max_min() {
+        let a = array![1.0, -2.0, 3.0];
+        let b = array![0.0, 1.0, 4.0];
+        assert_eq!(a.max(&b), array![1.0, 1.0, 4.0]);
+        assert_eq!(a.min(&b), array![0.0, -2.0, 3.0]);
+    }
+
     #[test]
     fn test_atan2() {
         let x = array![3.0, -3.0];
@@ -1842,6 +1920,29 @@ mod tests {
         assert_eq!(c, expected)
     }
 
+    #[test]
+    fn test_dyn_shape() {
+        let a = array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]].to_dyn();
+        assert_eq!(a.buf.shape(), &[2, 3]);
+        assert_eq!(a.buf.as_buf(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn test_dyn_add_broadcast() {
+        let a = array![1.0, 2.0, 3.0].to_dyn();
+        let b = array![10.0].to_dyn();
+        let c = b.add(&a);
+        assert_eq!(c.buf.shape(), &[3]);
+        assert_eq!(c.buf.as_buf(), &[11.0, 12.0, 13.0]);
+    }
+
+    #[test]
+    fn test_broadcast() {
+        let a = array![1.0, 2.0];
+        let out: Array<f64, (Const<3>, Const<2>)> = a.broadcast::<(Const<3>, Const<2>)>();
+        assert_eq!(out.buf, [[1.0, 2.0]; 3]);
+    }
+
     #[test]
     fn test_map() {
         let a = array![[1.0, 2.0], [5.0, 8.0], [9.0, 9.0]];
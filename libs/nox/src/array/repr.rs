@@ -152,6 +152,14 @@ impl OwnedRepr for ArrayRepr {
         arg.sqrt()
     }
 
+    fn exp<T1: Field + RealField, D1: Dim>(arg: &Self::Inner<T1, D1>) -> Self::Inner<T1, D1> {
+        arg.exp()
+    }
+
+    fn log<T1: Field + RealField, D1: Dim>(arg: &Self::Inner<T1, D1>) -> Self::Inner<T1, D1> {
+        arg.ln()
+    }
+
     fn atan2<T1: Field + RealField, D1: Dim>(
         left: &Self::Inner<T1, D1>,
         right: &Self::Inner<T1, D1>,
@@ -171,6 +179,29 @@ impl OwnedRepr for ArrayRepr {
         arg.abs()
     }
 
+    fn max<T1: Field + RealField, D1: Dim>(
+        left: &Self::Inner<T1, D1>,
+        right: &Self::Inner<T1, D1>,
+    ) -> Self::Inner<T1, D1> {
+        left.max(right)
+    }
+
+    fn min<T1: Field + RealField, D1: Dim>(
+        left: &Self::Inner<T1, D1>,
+        right: &Self::Inner<T1, D1>,
+    ) -> Self::Inner<T1, D1> {
+        left.min(right)
+    }
+
+    fn select_less<T1: Field + RealField + PartialOrd, D1: Dim>(
+        left: &Self::Inner<T1, D1>,
+        right: &Self::Inner<T1, D1>,
+        on_true: &Self::Inner<T1, D1>,
+        on_false: &Self::Inner<T1, D1>,
+    ) -> Self::Inner<T1, D1> {
+        left.select_less(right, on_true, on_false)
+    }
+
     fn copy_fixed_slice<T1: Field, D1: Dim, D2: Dim + ConstDim>(
         arg: &Self::Inner<T1, D1>,
         offsets: &[usize],
@@ -232,6 +263,12 @@ impl OwnedRepr for ArrayRepr {
         arg.clone()
     }
 
+    fn cast<T1: Field, T2: CastLossyFrom<T1>, D1: Dim>(
+        arg: &Self::Inner<T1, D1>,
+    ) -> Self::Inner<T2, D1> {
+        arg.cast()
+    }
+
     fn try_cholesky<T1: RealField, D1: Dim + SquareDim>(
         arg: &Self::Inner<T1, D1>,
     ) -> Result<Self::Inner<T1, D1>, Error> {
@@ -0,0 +1,216 @@
+//! Direction cosine matrices, an attitude representation for models that propagate a 3x3 rotation
+//! matrix instead of a quaternion.
+use core::ops::Mul;
+
+use crate::{
+    Const, DefaultRepr, Matrix3, OwnedRepr, Quaternion, RealField, ReprMonad, TensorItem, Vector,
+    MRP,
+};
+
+/// A rotation stored as the 3x3 matrix taking body frame vectors to the world frame, the same
+/// convention as rotating a vector by a [`Quaternion`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dcm<T: TensorItem, P: OwnedRepr = DefaultRepr>(
+    #[cfg_attr(
+        feature = "serde",
+        serde(bound(
+            deserialize = "Matrix3<T, P>: serde::Deserialize<'de>",
+            serialize = "Matrix3<T, P>: serde::Serialize"
+        ))
+    )]
+    pub Matrix3<T, P>,
+);
+
+impl<T: TensorItem, R: OwnedRepr> Clone for Dcm<T, R>
+where
+    R::Inner<T::Elem, (Const<3>, Const<3>)>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: TensorItem, R: OwnedRepr> ReprMonad<R> for Dcm<T, R> {
+    type Elem = T::Elem;
+
+    type Dim = (Const<3>, Const<3>);
+
+    type Map<N: OwnedRepr> = Dcm<T, N>;
+
+    fn map<N: OwnedRepr>(
+        self,
+        func: impl Fn(R::Inner<Self::Elem, Self::Dim>) -> N::Inner<Self::Elem, Self::Dim>,
+    ) -> Self::Map<N> {
+        Dcm(self.0.map(func))
+    }
+
+    fn into_inner(self) -> R::Inner<Self::Elem, Self::Dim> {
+        self.0.into_inner()
+    }
+
+    fn inner(&self) -> &R::Inner<Self::Elem, Self::Dim> {
+        self.0.inner()
+    }
+
+    fn from_inner(inner: R::Inner<Self::Elem, Self::Dim>) -> Self {
+        Dcm(Matrix3::from_inner(inner))
+    }
+}
+
+impl<T: RealField, R: OwnedRepr> Dcm<T, R> {
+    pub fn identity() -> Self {
+        Dcm(Matrix3::eye())
+    }
+
+    /// The inverse rotation, which for an orthonormal matrix is its transpose.
+    pub fn inverse(&self) -> Self {
+        Dcm(self.0.transpose())
+    }
+
+    /// Pulls the matrix back towards the nearest rotation, undoing the drift that integration
+    /// accumulates, with one step of the iteration `C (3I - CᵀC) / 2`.
+    ///
+    /// The error left after a step is roughly the square of the error before it.
+    pub fn orthonormalize(&self) -> Self {
+        let three = T::one::<R>() + T::two::<R>();
+        let ctc = self.0.transpose().dot(&self.0);
+        let correction = Matrix3::<T, R>::eye() * three - ctc;
+        Dcm(self.0.dot(&correction) / T::two::<R>())
+    }
+
+    /// Rotates by `world_delta`, an angular velocity in the world frame times the time step, like
+    /// `SpatialTransform + SpatialMotion` does for quaternions.
+    pub fn integrate_world(&self, world_delta: Vector<T, 3, R>) -> Self {
+        Dcm(delta_rotation(world_delta).dot(&self.0)).orthonormalize()
+    }
+
+    /// Rotates by `body_delta`, an angular velocity in the body frame times the time step.
+    pub fn integrate_body(&self, body_delta: Vector<T, 3, R>) -> Self {
+        Dcm(self.0.dot(&delta_rotation(body_delta))).orthonormalize()
+    }
+
+    /// Converts to a quaternion through modified Rodrigues parameters, which works in traced code
+    /// but is singular for rotations of 180 degrees.
+    pub fn to_quaternion(&self) -> Quaternion<T, R> {
+        Quaternion::from(MRP::from_rot_matrix(self.0.clone()))
+    }
+}
+
+/// The third order expansion of the matrix exponential of `[delta×]`, which after
+/// orthonormalization only errs in the rotation angle at fifth order.
+fn delta_rotation<T: RealField, R: OwnedRepr>(delta: Vector<T, 3, R>) -> Matrix3<T, R> {
+    let k = delta.skew();
+    let k2 = k.dot(&k) / T::two::<R>();
+    let k3 = k2.dot(&k) / (T::one::<R>() + T::two::<R>());
+    Matrix3::eye() + k + k2 + k3
+}
+
+impl<'a, T: RealField, R: OwnedRepr> From<&'a Quaternion<T, R>> for Dcm<T, R> {
+    fn from(quat: &'a Quaternion<T, R>) -> Self {
+        let [x, y, z, w] = quat.parts();
+        let one = T::one::<R>();
+        let two = T::two::<R>();
+        let (xx, yy, zz) = (&x * &x, &y * &y, &z * &z);
+        let (xy, xz, yz) = (&x * &y, &x * &z, &y * &z);
+        let (xw, yw, zw) = (&x * &w, &y * &w, &z * &w);
+        Dcm(Matrix3::from_rows([
+            Vector::from_arr([
+                &one - &two * (&yy + &zz),
+                &two * (&xy - &zw),
+                &two * (&xz + &yw),
+            ]),
+            Vector::from_arr([
+                &two * (&xy + &zw),
+                &one - &two * (&xx + &zz),
+                &two * (&yz - &xw),
+            ]),
+            Vector::from_arr([
+                &two * (&xz - &yw),
+                &two * (&yz + &xw),
+                &one - &two * (&xx + &yy),
+            ]),
+        ]))
+    }
+}
+
+impl<T: RealField, R: OwnedRepr> From<Quaternion<T, R>> for Dcm<T, R> {
+    fn from(quat: Quaternion<T, R>) -> Self {
+        Dcm::from(&quat)
+    }
+}
+
+impl<T: RealField, R: OwnedRepr> Mul<Vector<T, 3, R>> for Dcm<T, R> {
+    type Output = Vector<T, 3, R>;
+
+    fn mul(self, rhs: Vector<T, 3, R>) -> Self::Output {
+        self.0.dot(&rhs)
+    }
+}
+
+impl<'a, T: RealField, R: OwnedRepr> Mul<Vector<T, 3, R>> for &'a Dcm<T, R> {
+    type Output = Vector<T, 3, R>;
+
+    fn mul(self, rhs: Vector<T, 3, R>) -> Self::Output {
+        self.0.dot(&rhs)
+    }
+}
+
+impl<T: RealField, R: OwnedRepr> Mul for Dcm<T, R> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Dcm(self.0.dot(&rhs.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::{tensor, ArrayRepr, Vector3};
+
+    use super::*;
+
+    #[test]
+    fn test_dcm_quaternion_round_trip() {
+        let q: Quaternion<f64, ArrayRepr> =
+            Quaternion::from_axis_angle(tensor![1.0, 2.0, 3.0].normalize(), 0.7);
+        let dcm = Dcm::from(&q);
+        let v: Vector3<f64, ArrayRepr> = tensor![0.3, -1.0, 2.0];
+        assert_relative_eq!((&dcm * v).inner(), (q.clone() * v).inner(), epsilon = 1e-12);
+        assert_relative_eq!(dcm.to_quaternion().0.inner(), q.0.inner(), epsilon = 1e-12);
+        assert_relative_eq!(
+            Quaternion::from_rot_mat(dcm.0.clone()).0.inner(),
+            q.0.inner(),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_dcm_integrate() {
+        // a quarter turn about z in small steps lands x on y, and stays orthonormal
+        let mut dcm: Dcm<f64, ArrayRepr> = Dcm::identity();
+        let steps = 1000;
+        let delta = tensor![0.0, 0.0, core::f64::consts::FRAC_PI_2 / steps as f64];
+        for _ in 0..steps {
+            dcm = dcm.integrate_world(delta);
+        }
+        let x = &dcm * tensor![1.0, 0.0, 0.0];
+        assert_relative_eq!(x.inner(), tensor![0.0, 1.0, 0.0].inner(), epsilon = 1e-9);
+        let ctc = dcm.0.transpose().dot(&dcm.0);
+        assert_relative_eq!(
+            ctc.inner(),
+            Matrix3::<f64, ArrayRepr>::eye().inner(),
+            epsilon = 1e-12
+        );
+
+        // a badly scaled matrix converges on the rotation
+        let skewed = Dcm::<f64, ArrayRepr>(Matrix3::eye() * 1.1);
+        let fixed = skewed.orthonormalize().orthonormalize().orthonormalize();
+        assert_relative_eq!(
+            fixed.0.inner(),
+            Matrix3::<f64, ArrayRepr>::eye().inner(),
+            epsilon = 1e-6
+        );
+    }
+}
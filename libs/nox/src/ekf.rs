@@ -0,0 +1,207 @@
+//! Provides the building blocks of an extended Kalman filter.
+//!
+//! The filter steps are plain tensor functions, so the dynamics and measurement models can be any
+//! traceable nox function and the whole filter can run inside a system.
+use crate::{DefaultRepr, Error, Matrix, OwnedRepr, RealField, Scalar, TensorItem, Vector};
+#[cfg(feature = "noxpr")]
+use crate::{Noxpr, Op};
+#[cfg(feature = "noxpr")]
+use smallvec::smallvec;
+
+/// The estimate of an extended Kalman filter, a state vector and its covariance.
+pub struct State<T: TensorItem, const N: usize, R: OwnedRepr = DefaultRepr> {
+    pub x_hat: Vector<T, N, R>,
+    pub covar: Matrix<T, N, N, R>,
+}
+
+impl<T: RealField, const N: usize, R: OwnedRepr> State<T, N, R> {
+    pub fn new(x_hat: Vector<T, N, R>, covar: Matrix<T, N, N, R>) -> Self {
+        Self { x_hat, covar }
+    }
+
+    /// Propagates the estimate through `dynamics`, where `jacobian` is the jacobian of the dynamics at the prior estimate.
+    pub fn predict(
+        self,
+        dynamics: impl FnOnce(Vector<T, N, R>) -> Vector<T, N, R>,
+        jacobian: &Matrix<T, N, N, R>,
+        process_noise: &Matrix<T, N, N, R>,
+    ) -> Self {
+        let covar = jacobian.dot(&self.covar).dot(&jacobian.transpose()) + process_noise;
+        let x_hat = dynamics(self.x_hat);
+        Self { x_hat, covar }
+    }
+
+    /// Like [`State::predict`], but approximates the jacobian of `dynamics` with [`jacobian`].
+    pub fn predict_numeric(
+        self,
+        dynamics: impl Fn(Vector<T, N, R>) -> Vector<T, N, R>,
+        process_noise: &Matrix<T, N, N, R>,
+        eps: T,
+    ) -> Self {
+        let f_jac = jacobian(&dynamics, &self.x_hat, eps);
+        self.predict(dynamics, &f_jac, process_noise)
+    }
+
+    /// Corrects the estimate with the measurement `z`.
+    ///
+    /// `measure` maps a state to its expected measurement, and `jacobian` is the jacobian of `measure` at the current estimate.
+    /// The covariance is updated with the Joseph form, which keeps it symmetric positive definite in the face of rounding error.
+    pub fn update<const M: usize>(
+        self,
+        z: Vector<T, M, R>,
+        measure: impl FnOnce(Vector<T, N, R>) -> Vector<T, M, R>,
+        jacobian: &Matrix<T, M, N, R>,
+        noise_covar: &Matrix<T, M, M, R>,
+    ) -> Result<Self, Error> {
+        let Innovation { residual, covar } = self.innovation(z, measure, jacobian, noise_covar);
+        let kalman_gain = self
+            .covar
+            .dot(&jacobian.transpose())
+            .dot(&covar.try_inverse()?);
+        let x_hat = self.x_hat + kalman_gain.dot(&residual);
+        let i_kh = Matrix::<T, N, N, R>::eye() - kalman_gain.dot(jacobian);
+        let covar = i_kh.dot(&self.covar).dot(&i_kh.transpose())
+            + kalman_gain.dot(noise_covar).dot(&kalman_gain.transpose());
+        Ok(Self { x_hat, covar })
+    }
+
+    /// Like [`State::update`], but approximates the jacobian of `measure` with [`jacobian`].
+    pub fn update_numeric<const M: usize>(
+        self,
+        z: Vector<T, M, R>,
+        measure: impl Fn(Vector<T, N, R>) -> Vector<T, M, R>,
+        noise_covar: &Matrix<T, M, M, R>,
+        eps: T,
+    ) -> Result<Self, Error> {
+        let h_jac = jacobian(&measure, &self.x_hat, eps);
+        self.update(z, measure, &h_jac, noise_covar)
+    }
+
+    /// Computes the innovation of the measurement `z` against the current estimate, without
+    /// correcting it.
+    ///
+    /// The arguments are the same as [`State::update`], so the innovation can be recorded next to
+    /// each update to monitor the health of the filter.
+    pub fn innovation<const M: usize>(
+        &self,
+        z: Vector<T, M, R>,
+        measure: impl FnOnce(Vector<T, N, R>) -> Vector<T, M, R>,
+        jacobian: &Matrix<T, M, N, R>,
+        noise_covar: &Matrix<T, M, M, R>,
+    ) -> Innovation<T, M, R> {
+        let residual = z - measure(self.x_hat.clone());
+        let covar = jacobian.dot(&self.covar).dot(&jacobian.transpose()) + noise_covar;
+        Innovation { residual, covar }
+    }
+
+    /// Computes the normalized estimation error squared (NEES) of the estimate against the true
+    /// state.
+    ///
+    /// Only available where the truth is known, such as in simulation. For a consistent filter it
+    /// is chi-squared distributed with `N` degrees of freedom.
+    pub fn nees(&self, truth: &Vector<T, N, R>) -> Result<Scalar<T, R>, Error> {
+        let error = truth - &self.x_hat;
+        Ok(error.dot(&self.covar.try_inverse()?.dot(&error)))
+    }
+}
+
+/// The difference between a measurement and the measurement expected from the current estimate,
+/// along with the covariance the filter predicts for it.
+pub struct Innovation<T: TensorItem, const M: usize, R: OwnedRepr = DefaultRepr> {
+    pub residual: Vector<T, M, R>,
+    pub covar: Matrix<T, M, M, R>,
+}
+
+impl<T: RealField, const M: usize, R: OwnedRepr> Innovation<T, M, R> {
+    /// Computes the normalized innovation squared (NIS), the residual's squared Mahalanobis
+    /// length.
+    ///
+    /// For a consistent filter it is chi-squared distributed with `M` degrees of freedom, so a
+    /// mean well above `M` means the filter is overconfident, and one well below means it is
+    /// too pessimistic.
+    pub fn nis(&self) -> Result<Scalar<T, R>, Error> {
+        Ok(self
+            .residual
+            .dot(&self.covar.try_inverse()?.dot(&self.residual)))
+    }
+}
+
+/// Approximates the jacobian of `f` at `x` using central differences with a step size of `eps`.
+pub fn jacobian<T: RealField, const N: usize, const M: usize, R: OwnedRepr>(
+    f: impl Fn(Vector<T, N, R>) -> Vector<T, M, R>,
+    x: &Vector<T, N, R>,
+    eps: T,
+) -> Matrix<T, M, N, R> {
+    let basis = Matrix::<T, N, N, R>::eye();
+    let step = Scalar::<T, R>::from(eps);
+    let two_step = T::two::<R>() * &step;
+    let cols = core::array::from_fn(|i| {
+        let delta = basis.row(i) * &step;
+        (f(x + &delta) - f(x - &delta)) / &two_step
+    });
+    Matrix::from_cols(cols)
+}
+
+/// Computes the jacobian of the traced `f` at `x` exactly, with a forward-mode pass through `f`
+/// for every column, see [`Noxpr::jvp`].
+#[cfg(feature = "noxpr")]
+pub fn forward_jacobian<const N: usize, const M: usize>(
+    f: impl Fn(Vector<f64, N, Op>) -> Vector<f64, M, Op>,
+    x: &Vector<f64, N, Op>,
+) -> Result<Matrix<f64, M, N, Op>, Error> {
+    let y = f(x.clone()).inner;
+    let basis = Matrix::<f64, N, N, Op>::eye();
+    let cols = (0..N)
+        .map(|i| {
+            let col = y.jvp(&x.inner, basis.row(i).inner)?;
+            Ok(col.reshape(smallvec![M as i64, 1]))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(Matrix::from_inner(Noxpr::concat_in_dim(cols, 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::{tensor, ArrayRepr};
+
+    #[test]
+    fn test_jacobian() {
+        let f = |v: Vector<f64, 2, ArrayRepr>| {
+            let [a, b] = v.parts();
+            Vector::from_arr([&a * &b, a.sin()])
+        };
+        let out = jacobian(f, &tensor![0.5, 2.0], 1e-6);
+        assert_relative_eq!(
+            out,
+            tensor![[2.0, 0.5], [0.5f64.cos(), 0.0]],
+            epsilon = 1e-8
+        );
+    }
+
+    #[test]
+    fn test_predict_update() {
+        let dynamics: Matrix<f64, 2, 2, ArrayRepr> = tensor![[1.0, 1.0], [0.0, 1.0]];
+        let state = State::new(tensor![0.0, 1.0], Matrix::eye());
+        let state = state.predict(|x| dynamics.dot(&x), &dynamics, &Matrix::zeros());
+        assert_relative_eq!(state.x_hat, tensor![1.0, 1.0]);
+        assert_relative_eq!(state.covar, tensor![[2.0, 1.0], [1.0, 1.0]]);
+
+        let innovation = state.innovation(tensor![2.0, 1.0], |x| x, &Matrix::eye(), &Matrix::eye());
+        assert_relative_eq!(innovation.residual, tensor![1.0, 0.0]);
+        assert_relative_eq!(innovation.nis().unwrap().into_buf(), 0.4, epsilon = 1e-12);
+        assert_relative_eq!(
+            state.nees(&tensor![1.0, 2.0]).unwrap().into_buf(),
+            2.0,
+            epsilon = 1e-12
+        );
+
+        let state = state
+            .update_numeric(tensor![2.0, 1.0], |x| x, &Matrix::eye(), 1e-6)
+            .unwrap();
+        assert_relative_eq!(state.x_hat, tensor![1.6, 1.2], epsilon = 1e-8);
+        assert_relative_eq!(state.covar, tensor![[0.6, 0.2], [0.2, 0.4]], epsilon = 1e-8);
+    }
+}
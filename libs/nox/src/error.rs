@@ -37,6 +37,38 @@ pub enum Error {
     #[error("out of bounds access")]
     OutOfBoundsAccess,
 
+    /// Error when a lookup table's breakpoints aren't increasing or don't match its values.
+    #[error("invalid lookup table")]
+    InvalidTable,
+
+    /// Error when an adaptive solver needs more steps than it is allowed.
+    #[error("ode solver exceeded {0} steps")]
+    StepLimitExceeded(usize),
+
+    /// Error when an adaptive solver's step size shrinks below its minimum.
+    #[error("ode solver step size underflow")]
+    StepSizeUnderflow,
+
+    /// Error when a Lambert problem has no unique transfer plane, e.g the positions are opposite.
+    #[error("lambert transfer is undefined")]
+    LambertUndefined,
+
+    /// Error when the Lambert solver doesn't converge within its iteration limit.
+    #[error("lambert solver did not converge in {0} iterations")]
+    LambertNoConvergence(usize),
+
+    /// Error when a jacobian sparsity pattern doesn't match the shape of the function it's used on.
+    #[error("jacobian sparsity pattern doesn't match the function's shape")]
+    SparsityShapeMismatch,
+
+    /// Error when a sparse solve meets a row without a stored diagonal entry.
+    #[error("row {0} has no diagonal entry")]
+    MissingDiagonal(usize),
+
+    /// Error when constants sharing a parameter name have different values.
+    #[error("named constant {0} has conflicting values")]
+    ConflictingParam(String),
+
     /// Error propagated from Python operations via PyO3.
     #[cfg(feature = "jax")]
     #[error("pyo3 error {0}")]
@@ -54,6 +86,18 @@ pub enum Error {
     #[error("all scan arguments must have the same first dim")]
     ScanShapeMismatch,
 
+    /// Error when a while loop function's arguments don't match the state and inputs.
+    #[error("while loop functions must take the state followed by the loop's inputs")]
+    WhileWrongArgCount,
+
+    /// Error when the type of a while loop's state or one of its inputs can't be determined.
+    #[error("while loop state and inputs must have a known type")]
+    WhileUnknownState,
+
+    /// Error when a tangent reaches an op forward-mode differentiation doesn't support.
+    #[error("can't differentiate through {0}")]
+    UnsupportedJvp(&'static str),
+
     /// Error when matrix inversion failed
     #[error("matrix inversion failed with {0} arg illegal")]
     InvertFailed(i32),
@@ -64,11 +64,14 @@ pub trait RealField:
     Elem + Field + Neg<Output = Self> + faer::SimpleEntity + faer::ComplexField
 {
     fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
     fn cos(self) -> Self;
     fn sin(self) -> Self;
     fn abs(self) -> Self;
     fn atan2(self, other: Self) -> Self;
     fn max(self, other: Self) -> Self;
+    fn min(self, other: Self) -> Self;
     fn copysign(self, sign: Self) -> Self;
     fn neg_one() -> Self;
     fn acos(self) -> Self;
@@ -83,6 +86,14 @@ macro_rules! impl_real_field {
                 self.sqrt()
             }
 
+            fn exp(self) -> Self {
+                self.exp()
+            }
+
+            fn ln(self) -> Self {
+                self.ln()
+            }
+
             fn cos(self) -> Self {
                 self.cos()
             }
@@ -103,6 +114,10 @@ macro_rules! impl_real_field {
                 self.max(other)
             }
 
+            fn min(self, other: Self) -> Self {
+                self.min(other)
+            }
+
             fn copysign(self, sign: Self) -> Self {
                 self.copysign(sign)
             }
@@ -130,6 +145,14 @@ macro_rules! impl_real_field {
                 libm::Libm::<$t>::sqrt(self)
             }
 
+            fn exp(self) -> Self {
+                libm::Libm::<$t>::exp(self)
+            }
+
+            fn ln(self) -> Self {
+                libm::Libm::<$t>::log(self)
+            }
+
             fn cos(self) -> Self {
                 libm::Libm::<$t>::cos(self)
             }
@@ -150,6 +173,10 @@ macro_rules! impl_real_field {
                 libm::Libm::<$t>::fmax(self, other)
             }
 
+            fn min(self, other: Self) -> Self {
+                libm::Libm::<$t>::fmin(self, other)
+            }
+
             fn copysign(self, sign: Self) -> Self {
                 libm::Libm::<$t>::copysign(self, sign)
             }
@@ -231,3 +258,46 @@ impl_real_closed_field!(i64, 0, 1, 2);
 impl_real_closed_field!(u16, 0, 1, 2);
 impl_real_closed_field!(u32, 0, 1, 2);
 impl_real_closed_field!(u64, 0, 1, 2);
+
+/// A conversion between element types that is exact for every value, such as `f32` to `f64`.
+///
+/// Conversions that can lose information only implement [`CastLossyFrom`], so
+/// [`Tensor::cast`](crate::Tensor::cast) rejects them at compile time and
+/// [`Tensor::cast_lossy`](crate::Tensor::cast_lossy) has to be asked for instead.
+pub trait CastFrom<T>: CastLossyFrom<T> {}
+
+/// A conversion between element types that may round, truncate or wrap, like `as`.
+///
+/// On the host this is `as`. Compiled code converts with XLA, which rounds and wraps the same way
+/// but leaves NaN and out of range floats converted to integers unspecified.
+pub trait CastLossyFrom<T>: Field {
+    fn cast_lossy_from(value: T) -> Self;
+}
+
+macro_rules! impl_cast_lossy_from {
+    ($from:ty => $($to:ty),*) => {
+        $(
+            impl CastLossyFrom<$from> for $to {
+                fn cast_lossy_from(value: $from) -> Self {
+                    value as $to
+                }
+            }
+        )*
+    };
+}
+
+impl_cast_lossy_from!(f32 => f32, f64, i32, u64);
+impl_cast_lossy_from!(f64 => f32, f64, i32, u64);
+impl_cast_lossy_from!(i32 => f32, f64, i32, u64);
+impl_cast_lossy_from!(u64 => f32, f64, i32, u64);
+
+macro_rules! impl_cast_from {
+    ($from:ty => $($to:ty),*) => {
+        $(impl CastFrom<$from> for $to {})*
+    };
+}
+
+impl_cast_from!(f32 => f32, f64);
+impl_cast_from!(f64 => f64);
+impl_cast_from!(i32 => i32, f64);
+impl_cast_from!(u64 => u64);
@@ -0,0 +1,195 @@
+//! Compile-time reference frame tags for spatial quantities.
+//!
+//! Wrapping a [`SpatialMotion`] or [`SpatialForce`] in [`InFrame`] records which frame it is expressed
+//! in, so adding a body-frame force to a world-frame one, or handing a body-frame velocity to code
+//! expecting world coordinates, fails to compile. The only way to move a quantity between frames is a
+//! [`FrameTransform`]. The tags are zero sized, so the wrappers cost nothing at runtime.
+use core::marker::PhantomData;
+use core::ops::{Add, Mul};
+
+use crate::{DefaultRepr, OwnedRepr, RealField, SpatialForce, SpatialMotion, SpatialTransform};
+
+/// A reference frame tag.
+pub trait Frame {}
+
+/// The inertial frame that world positions and velocities are expressed in.
+pub struct WorldFrame;
+
+/// The frame attached to a rigid body, centered on its center of mass.
+pub struct BodyFrame;
+
+impl Frame for WorldFrame {}
+impl Frame for BodyFrame {}
+
+/// A spatial quantity `S` expressed in frame `F`.
+pub struct InFrame<S, F: Frame> {
+    inner: S,
+    frame: PhantomData<F>,
+}
+
+impl<S, F: Frame> InFrame<S, F> {
+    /// Tags `inner` as being expressed in `F`; it's up to the caller that this is actually true.
+    pub fn new(inner: S) -> Self {
+        InFrame {
+            inner,
+            frame: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Drops the frame tag.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Clone, F: Frame> Clone for InFrame<S, F> {
+    fn clone(&self) -> Self {
+        InFrame::new(self.inner.clone())
+    }
+}
+
+impl<S: Copy, F: Frame> Copy for InFrame<S, F> {}
+
+impl<S: core::fmt::Debug, F: Frame> core::fmt::Debug for InFrame<S, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("InFrame")
+            .field("inner", &self.inner)
+            .field("frame", &core::any::type_name::<F>())
+            .finish()
+    }
+}
+
+impl<S: Add<Output = S>, F: Frame> Add for InFrame<S, F> {
+    type Output = InFrame<S, F>;
+
+    fn add(self, rhs: InFrame<S, F>) -> Self::Output {
+        InFrame::new(self.inner + rhs.inner)
+    }
+}
+
+/// The pose of frame `Src` expressed in frame `Dst`, e.g a body's world position is a
+/// `FrameTransform<T, BodyFrame, WorldFrame>`.
+///
+/// Motions and forces are only rotated when changing frames, as both frames share the point they are
+/// taken about; the linear part only matters when composing poses.
+pub struct FrameTransform<T: RealField, Src: Frame, Dst: Frame, R: OwnedRepr = DefaultRepr> {
+    inner: SpatialTransform<T, R>,
+    frames: PhantomData<(Src, Dst)>,
+}
+
+impl<T: RealField, Src: Frame, Dst: Frame, R: OwnedRepr> FrameTransform<T, Src, Dst, R> {
+    pub fn new(pose: SpatialTransform<T, R>) -> Self {
+        FrameTransform {
+            inner: pose,
+            frames: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &SpatialTransform<T, R> {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> SpatialTransform<T, R> {
+        self.inner
+    }
+
+    /// The pose of `Dst` expressed in `Src`.
+    pub fn inverse(&self) -> FrameTransform<T, Dst, Src, R> {
+        let angular = self.inner.angular().inverse();
+        let linear = -(&angular * self.inner.linear());
+        FrameTransform::new(SpatialTransform::new(angular, linear))
+    }
+}
+
+impl<T: RealField, Src: Frame, Dst: Frame, R: OwnedRepr> Clone for FrameTransform<T, Src, Dst, R>
+where
+    SpatialTransform<T, R>: Clone,
+{
+    fn clone(&self) -> Self {
+        FrameTransform::new(self.inner.clone())
+    }
+}
+
+impl<T: RealField, A: Frame, Src: Frame, Dst: Frame, R: OwnedRepr> Mul<FrameTransform<T, A, Src, R>>
+    for FrameTransform<T, Src, Dst, R>
+{
+    type Output = FrameTransform<T, A, Dst, R>;
+
+    fn mul(self, rhs: FrameTransform<T, A, Src, R>) -> Self::Output {
+        FrameTransform::new(self.inner * rhs.inner)
+    }
+}
+
+impl<T: RealField, Src: Frame, Dst: Frame, R: OwnedRepr> Mul<InFrame<SpatialMotion<T, R>, Src>>
+    for FrameTransform<T, Src, Dst, R>
+{
+    type Output = InFrame<SpatialMotion<T, R>, Dst>;
+
+    fn mul(self, rhs: InFrame<SpatialMotion<T, R>, Src>) -> Self::Output {
+        InFrame::new(self.inner.angular() * rhs.inner)
+    }
+}
+
+impl<T: RealField, Src: Frame, Dst: Frame, R: OwnedRepr> Mul<InFrame<SpatialForce<T, R>, Src>>
+    for FrameTransform<T, Src, Dst, R>
+{
+    type Output = InFrame<SpatialForce<T, R>, Dst>;
+
+    fn mul(self, rhs: InFrame<SpatialForce<T, R>, Src>) -> Self::Output {
+        InFrame::new(self.inner.angular() * rhs.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::{tensor, ArrayRepr, Quaternion};
+
+    #[test]
+    fn test_frame_transform() {
+        // a body yawed 90 degrees, sitting at x = 1
+        let pose = SpatialTransform::new(
+            Quaternion::<f64, ArrayRepr>::from_axis_angle(
+                tensor![0.0, 0.0, 1.0],
+                90f64.to_radians(),
+            ),
+            tensor![1.0, 0.0, 0.0],
+        );
+        let body_to_world = FrameTransform::<_, BodyFrame, WorldFrame, _>::new(pose);
+
+        let thrust =
+            InFrame::<_, BodyFrame>::new(SpatialForce::from_linear(tensor![10.0, 0.0, 0.0]));
+        let drag =
+            InFrame::<_, WorldFrame>::new(SpatialForce::from_linear(tensor![0.0, -2.0, 0.0]));
+        let total = body_to_world.clone() * thrust + drag;
+        assert_relative_eq!(
+            total.into_inner().force(),
+            tensor![0.0, 8.0, 0.0],
+            epsilon = 1e-12
+        );
+
+        let world_vel = InFrame::<_, WorldFrame>::new(SpatialMotion::<f64, ArrayRepr>::new(
+            tensor![0.0, 0.0, 1.0],
+            tensor![0.0, 3.0, 0.0],
+        ));
+        let body_vel = body_to_world.inverse() * world_vel;
+        assert_relative_eq!(
+            body_vel.inner().linear(),
+            tensor![3.0, 0.0, 0.0],
+            epsilon = 1e-12
+        );
+
+        let identity = body_to_world.inverse() * body_to_world;
+        assert_relative_eq!(
+            identity.into_inner().inner,
+            SpatialTransform::<f64, ArrayRepr>::zero().inner,
+            epsilon = 1e-12
+        );
+    }
+}
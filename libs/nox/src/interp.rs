@@ -0,0 +1,141 @@
+//! Lookup tables with multilinear interpolation.
+use alloc::vec::Vec;
+
+use crate::{Error, OwnedRepr, Scalar};
+
+/// A table of values sampled on an `N`-dimensional rectilinear grid, interpolated linearly
+/// between breakpoints and held constant past the ends of each axis.
+///
+/// Evaluation only uses elementwise tensor ops, so a table can be looked up inside a compiled
+/// system; every grid value contributes a term weighted by the distance to its breakpoints.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearTable<const N: usize> {
+    axes: Vec<Vec<f64>>,
+    values: Vec<f64>,
+}
+
+pub type Table1 = LinearTable<1>;
+pub type Table2 = LinearTable<2>;
+pub type Table3 = LinearTable<3>;
+
+impl<const N: usize> LinearTable<N> {
+    /// Builds a table from strictly increasing breakpoints for each axis, and the values at every
+    /// grid point in row-major order, with the last axis varying fastest.
+    pub fn new(axes: [Vec<f64>; N], values: Vec<f64>) -> Result<Self, Error> {
+        for axis in &axes {
+            let increasing = axis.windows(2).all(|w| w[0] < w[1]);
+            if axis.is_empty() || !increasing {
+                return Err(Error::InvalidTable);
+            }
+        }
+        if values.len() != axes.iter().map(Vec::len).product::<usize>() {
+            return Err(Error::InvalidTable);
+        }
+        Ok(Self {
+            axes: axes.into(),
+            values,
+        })
+    }
+
+    pub fn axes(&self) -> &[Vec<f64>] {
+        &self.axes
+    }
+
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Interpolates the table at `point`, which has one coordinate per axis.
+    pub fn eval<R: OwnedRepr>(&self, point: [Scalar<f64, R>; N]) -> Scalar<f64, R> {
+        let mut layer: Vec<Scalar<f64, R>> = self.values.iter().map(|&v| v.into()).collect();
+        for (axis, x) in self.axes.iter().zip(point).rev() {
+            let weights = axis_weights(axis, &x);
+            layer = layer
+                .chunks_exact(axis.len())
+                .map(|chunk| {
+                    chunk
+                        .iter()
+                        .zip(&weights)
+                        .map(|(v, w)| v * w)
+                        .reduce(|acc, term| acc + term)
+                        .expect("axes are never empty")
+                })
+                .collect();
+        }
+        layer.pop().expect("a table has at least one value")
+    }
+}
+
+impl LinearTable<1> {
+    pub fn new_1d(x: Vec<f64>, values: Vec<f64>) -> Result<Self, Error> {
+        Self::new([x], values)
+    }
+}
+
+/// The hat-function weight of each breakpoint at `x`; at most two are non-zero and they sum to one.
+fn axis_weights<R: OwnedRepr>(axis: &[f64], x: &Scalar<f64, R>) -> Vec<Scalar<f64, R>> {
+    let last = axis.len() - 1;
+    let x = x.clamp(&axis[0].into(), &axis[last].into());
+    let one: Scalar<f64, R> = 1.0.into();
+    let zero: Scalar<f64, R> = 0.0.into();
+    (0..=last)
+        .map(|i| {
+            let rising = (i > 0).then(|| {
+                let (lo, hi) = (axis[i - 1], axis[i]);
+                (1.0 / (hi - lo)) * (&x - Scalar::<f64, R>::from(lo))
+            });
+            let falling = (i < last).then(|| {
+                let (lo, hi) = (axis[i], axis[i + 1]);
+                (1.0 / (hi - lo)) * (Scalar::<f64, R>::from(hi) - &x)
+            });
+            let w = match (rising, falling) {
+                (Some(r), Some(f)) => r.min(&f),
+                (Some(w), None) | (None, Some(w)) => w,
+                (None, None) => return one.clone(),
+            };
+            w.clamp(&zero, &one)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArrayRepr;
+    use alloc::vec;
+
+    fn eval<const N: usize>(table: &LinearTable<N>, point: [f64; N]) -> f64 {
+        table.eval::<ArrayRepr>(point.map(Scalar::from)).into_buf()
+    }
+
+    #[test]
+    fn test_linear_table() {
+        let table = Table1::new_1d(vec![0.0, 1.0, 3.0], vec![0.0, 10.0, 30.0]).unwrap();
+        assert_eq!(eval(&table, [0.5]), 5.0);
+        assert_eq!(eval(&table, [2.0]), 20.0);
+        assert_eq!(eval(&table, [-1.0]), 0.0);
+        assert_eq!(eval(&table, [4.0]), 30.0);
+
+        // f(x, y) = x + 2y is reproduced exactly by bilinear interpolation
+        let xs = vec![0.0, 1.0, 2.0];
+        let ys = vec![0.0, 0.5];
+        let values = xs
+            .iter()
+            .flat_map(|x| ys.iter().map(move |y| x + 2.0 * y))
+            .collect();
+        let table = Table2::new([xs, ys], values).unwrap();
+        approx::assert_relative_eq!(eval(&table, [1.5, 0.25]), 2.0);
+        approx::assert_relative_eq!(eval(&table, [0.25, 1.0]), 1.25);
+
+        let table = Table3::new(
+            [vec![0.0, 1.0], vec![0.0, 1.0], vec![0.0, 1.0]],
+            (0..8).map(f64::from).collect(),
+        )
+        .unwrap();
+        approx::assert_relative_eq!(eval(&table, [0.5, 0.5, 0.5]), 3.5);
+
+        assert!(Table1::new_1d(vec![0.0, 0.0], vec![1.0, 2.0]).is_err());
+        assert!(Table2::new([vec![0.0], vec![0.0, 1.0]], vec![1.0]).is_err());
+    }
+}
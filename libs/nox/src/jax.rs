@@ -122,6 +122,7 @@ impl JaxTracer {
             NoxprNode::Sqrt(op) => self.visit_unary_lax(op, "sqrt")?,
             NoxprNode::Neg(op) => self.visit_unary_lax(op, "neg")?,
             NoxprNode::Log(op) => self.visit_unary_lax(op, "log")?,
+            NoxprNode::Exp(op) => self.visit_unary_lax(op, "exp")?,
             NoxprNode::Sin(op) => self.visit_unary_lax(op, "sin")?,
             NoxprNode::Cos(op) => self.visit_unary_lax(op, "cos")?,
             NoxprNode::Asin(op) => self.visit_unary_lax(op, "asin")?,
@@ -256,6 +257,16 @@ impl JaxTracer {
                         .map_err(Error::PyO3)
                 })?
             }
+            NoxprNode::While(w) => {
+                let initial_state = self.visit(&w.initial_state)?;
+                let cond = self.visit_fn(&w.cond);
+                let body = self.visit_fn(&w.body);
+                Python::with_gil(|py| {
+                    self.lax
+                        .call_method1(py, "while_loop", (cond, body, initial_state))
+                        .map_err(Error::PyO3)
+                })?
+            }
             NoxprNode::Jax(o) => o.clone(),
             NoxprNode::Convert(conv) => {
                 let expr = self.visit(&conv.arg)?;
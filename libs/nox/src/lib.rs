@@ -8,29 +8,48 @@ extern crate alloc;
 extern crate lapack_src as _;
 
 pub mod array;
+mod dcm;
 mod dim;
 mod error;
 mod fields;
+mod frame;
+mod interp;
+mod maneuver;
 mod matrix;
 mod mrp;
+mod multibody;
+mod ode;
+mod orbit;
 mod quaternion;
+mod ray;
 mod repr;
 mod scalar;
+mod sparse;
 mod spatial;
 mod tensor;
 mod vector;
 
+pub mod ekf;
 pub mod utils;
 
 pub use array::prelude::*;
+pub use dcm::*;
 pub use dim::*;
 pub use error::*;
 pub use fields::*;
+pub use frame::*;
+pub use interp::*;
+pub use maneuver::*;
 pub use matrix::*;
 pub use mrp::*;
+pub use multibody::*;
+pub use ode::*;
+pub use orbit::*;
 pub use quaternion::*;
+pub use ray::*;
 pub use repr::*;
 pub use scalar::*;
+pub use sparse::*;
 pub use spatial::*;
 pub use tensor::*;
 pub use vector::*;
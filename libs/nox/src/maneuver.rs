@@ -0,0 +1,360 @@
+//! Provides impulsive maneuver planning: Hohmann and bi-elliptic transfers between circular
+//! orbits, a Lambert solver for point-to-point transfers, and helpers for applying burns to a
+//! Cartesian state or to [`OrbitalElements`].
+//!
+//! The transfer and burn helpers are tensor operations, so they can be used inside a compiled
+//! guidance system. The Lambert solver iterates to convergence, so like [`crate::Dopri5::solve`]
+//! it runs on the host.
+use crate::{
+    ArrayRepr, DefaultRepr, Error, Matrix3, OrbitalElements, OwnedRepr, RealField, Scalar,
+    SpatialMotion, TensorItem, Vector,
+};
+
+/// A Hohmann transfer between coplanar circular orbits.
+///
+/// The burns are signed along the velocity, so they are negative when lowering the orbit.
+pub struct Hohmann<T: TensorItem, R: OwnedRepr = DefaultRepr> {
+    /// The burn leaving the initial orbit.
+    pub dv1: Scalar<T, R>,
+    /// The burn circularizing at the target orbit.
+    pub dv2: Scalar<T, R>,
+    /// The time spent on the transfer ellipse, half of its period.
+    pub time_of_flight: Scalar<T, R>,
+}
+
+impl<T: RealField, R: OwnedRepr> Hohmann<T, R> {
+    /// Computes the transfer from a circular orbit of radius `r1` to one of radius `r2`, around a
+    /// central body with gravitational parameter `mu`.
+    pub fn new(
+        r1: impl Into<Scalar<T, R>>,
+        r2: impl Into<Scalar<T, R>>,
+        mu: impl Into<Scalar<T, R>>,
+    ) -> Self {
+        let (r1, r2, mu) = (r1.into(), r2.into(), mu.into());
+        let a = (&r1 + &r2) / T::two::<R>();
+        let dv1 = transfer_speed(&mu, &r1, &a) - circular_speed(&mu, &r1);
+        let dv2 = circular_speed(&mu, &r2) - transfer_speed(&mu, &r2, &a);
+        Hohmann {
+            dv1,
+            dv2,
+            time_of_flight: half_period(&mu, &a),
+        }
+    }
+
+    /// Returns the total delta-v magnitude of both burns.
+    pub fn total_dv(&self) -> Scalar<T, R> {
+        self.dv1.abs() + self.dv2.abs()
+    }
+}
+
+/// A bi-elliptic transfer between coplanar circular orbits, through an intermediate apoapsis.
+///
+/// For a target more than about 11.94 times the initial radius this can take less delta-v than
+/// a [`Hohmann`] transfer, at the cost of a much longer flight. The burns are signed along the
+/// velocity.
+pub struct BiElliptic<T: TensorItem, R: OwnedRepr = DefaultRepr> {
+    /// The burn raising apoapsis to the intermediate radius.
+    pub dv1: Scalar<T, R>,
+    /// The burn at the intermediate radius moving periapsis to the target radius.
+    pub dv2: Scalar<T, R>,
+    /// The burn circularizing at the target orbit.
+    pub dv3: Scalar<T, R>,
+    /// The time spent on both transfer ellipses.
+    pub time_of_flight: Scalar<T, R>,
+}
+
+impl<T: RealField, R: OwnedRepr> BiElliptic<T, R> {
+    /// Computes the transfer from a circular orbit of radius `r1` to one of radius `r2` through
+    /// the intermediate radius `rb`, around a central body with gravitational parameter `mu`.
+    pub fn new(
+        r1: impl Into<Scalar<T, R>>,
+        r2: impl Into<Scalar<T, R>>,
+        rb: impl Into<Scalar<T, R>>,
+        mu: impl Into<Scalar<T, R>>,
+    ) -> Self {
+        let (r1, r2, rb, mu) = (r1.into(), r2.into(), rb.into(), mu.into());
+        let a1 = (&r1 + &rb) / T::two::<R>();
+        let a2 = (&r2 + &rb) / T::two::<R>();
+        let dv1 = transfer_speed(&mu, &r1, &a1) - circular_speed(&mu, &r1);
+        let dv2 = transfer_speed(&mu, &rb, &a2) - transfer_speed(&mu, &rb, &a1);
+        let dv3 = circular_speed(&mu, &r2) - transfer_speed(&mu, &r2, &a2);
+        BiElliptic {
+            dv1,
+            dv2,
+            dv3,
+            time_of_flight: half_period(&mu, &a1) + half_period(&mu, &a2),
+        }
+    }
+
+    /// Returns the total delta-v magnitude of all three burns.
+    pub fn total_dv(&self) -> Scalar<T, R> {
+        self.dv1.abs() + self.dv2.abs() + self.dv3.abs()
+    }
+}
+
+fn circular_speed<T: RealField, R: OwnedRepr>(mu: &Scalar<T, R>, r: &Scalar<T, R>) -> Scalar<T, R> {
+    (mu / r).sqrt()
+}
+
+/// The vis-viva speed at radius `r` on an orbit with semi-major axis `a`.
+fn transfer_speed<T: RealField, R: OwnedRepr>(
+    mu: &Scalar<T, R>,
+    r: &Scalar<T, R>,
+    a: &Scalar<T, R>,
+) -> Scalar<T, R> {
+    (T::two::<R>() * mu / r - mu / a).sqrt()
+}
+
+fn half_period<T: RealField, R: OwnedRepr>(mu: &Scalar<T, R>, a: &Scalar<T, R>) -> Scalar<T, R> {
+    let pi: Scalar<T, R> = T::neg_one().acos().into();
+    pi * (a * a * a / mu).sqrt()
+}
+
+/// Computes the rotation from the radial, transverse, normal (RTN) frame of an orbiting body to
+/// the inertial frame.
+///
+/// The columns are the unit radial direction, the in-plane direction perpendicular to it along
+/// the motion, and the orbit normal. For a circular orbit the transverse axis is the velocity.
+pub fn rtn_to_inertial<T: RealField, R: OwnedRepr>(
+    pos: &Vector<T, 3, R>,
+    vel: &Vector<T, 3, R>,
+) -> Matrix3<T, R> {
+    let radial = pos.normalize();
+    let normal = pos.cross(vel).normalize();
+    let transverse = normal.cross(&radial);
+    Matrix3::from_cols([radial, transverse, normal])
+}
+
+/// Applies an impulsive burn `dv_rtn`, given in the RTN frame of the body, returning the new
+/// inertial velocity.
+pub fn apply_impulse<T: RealField, R: OwnedRepr>(
+    pos: &Vector<T, 3, R>,
+    vel: &Vector<T, 3, R>,
+    dv_rtn: &Vector<T, 3, R>,
+) -> Vector<T, 3, R> {
+    vel + rtn_to_inertial(pos, vel).dot(dv_rtn)
+}
+
+impl<T: RealField, R: OwnedRepr> SpatialMotion<T, R> {
+    /// Returns this motion with an inertial delta-v added to the linear velocity.
+    pub fn with_impulse(&self, dv: impl Into<Vector<T, 3, R>>) -> Self {
+        SpatialMotion::new(self.angular(), self.linear() + dv.into())
+    }
+}
+
+impl<T: RealField, R: OwnedRepr> OrbitalElements<T, R> {
+    /// Applies an impulsive burn `dv_rtn`, given in the RTN frame at the current true anomaly,
+    /// returning the elements of the resulting orbit.
+    pub fn apply_impulse(&self, dv_rtn: &Vector<T, 3, R>, mu: impl Into<Scalar<T, R>>) -> Self {
+        let mu = mu.into();
+        let (pos, vel) = self.to_cartesian(mu.clone());
+        let vel = apply_impulse(&pos, &vel, dv_rtn);
+        Self::from_cartesian(pos, vel, mu)
+    }
+}
+
+/// A universal-variable Lambert solver, finding the orbit that connects two positions in a given
+/// time of flight.
+///
+/// Only single revolution transfers are solved. The transfer angle is taken as the prograde one,
+/// i.e the one moving counter-clockwise about +Z, unless `prograde` is false.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lambert {
+    pub prograde: bool,
+    pub tol: f64,
+    pub max_iters: usize,
+}
+
+impl Default for Lambert {
+    fn default() -> Self {
+        Self {
+            prograde: true,
+            tol: 1e-9,
+            max_iters: 200,
+        }
+    }
+}
+
+impl Lambert {
+    pub fn prograde(mut self, prograde: bool) -> Self {
+        self.prograde = prograde;
+        self
+    }
+
+    pub fn tol(mut self, tol: f64) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    pub fn max_iters(mut self, max_iters: usize) -> Self {
+        self.max_iters = max_iters;
+        self
+    }
+
+    /// Returns the velocities at `r1` and `r2` of the transfer taking `time_of_flight` seconds
+    /// between them, around a central body with gravitational parameter `mu`.
+    ///
+    /// The bracket on the universal variable is bisected, which is slow but can't diverge. `tol`
+    /// is relative to `time_of_flight`.
+    pub fn solve(
+        &self,
+        r1: &Vector<f64, 3, ArrayRepr>,
+        r2: &Vector<f64, 3, ArrayRepr>,
+        time_of_flight: f64,
+        mu: f64,
+    ) -> Result<(Vector<f64, 3, ArrayRepr>, Vector<f64, 3, ArrayRepr>), Error> {
+        let r1_norm = r1.norm().into_buf();
+        let r2_norm = r2.norm().into_buf();
+        let cos_dnu = (r1.dot(r2).into_buf() / (r1_norm * r2_norm)).clamp(-1.0, 1.0);
+        let [_, _, h_z] = r1.cross(r2).into_buf();
+        let short_way = (h_z >= 0.0) == self.prograde;
+        let dir = if short_way { 1.0 } else { -1.0 };
+        let a = dir * (r1_norm * r2_norm * (1.0 + cos_dnu)).sqrt();
+        if a == 0.0 || time_of_flight <= 0.0 {
+            return Err(Error::LambertUndefined);
+        }
+
+        let (mut psi_low, mut psi_up) = (
+            -4.0 * core::f64::consts::PI,
+            4.0 * core::f64::consts::PI.powi(2),
+        );
+        let mut psi = 0.0;
+        for _ in 0..self.max_iters {
+            let (c2, c3) = stumpff(psi);
+            let y = r1_norm + r2_norm + a * (psi * c3 - 1.0) / c2.sqrt();
+            if y < 0.0 {
+                // y grows with psi, so the solution lies above any psi that makes it negative
+                psi_low = psi;
+                psi = (psi_low + psi_up) / 2.0;
+                continue;
+            }
+            let chi = (y / c2).sqrt();
+            let dt = (chi.powi(3) * c3 + a * y.sqrt()) / mu.sqrt();
+            if (dt - time_of_flight).abs() <= self.tol * time_of_flight {
+                let f = 1.0 - y / r1_norm;
+                let g = a * (y / mu).sqrt();
+                let g_dot = 1.0 - y / r2_norm;
+                let v1 = (r2 - Scalar::from(f) * r1) / Scalar::from(g);
+                let v2 = (Scalar::from(g_dot) * r2 - r1) / Scalar::from(g);
+                return Ok((v1, v2));
+            }
+            if dt <= time_of_flight {
+                psi_low = psi;
+            } else {
+                psi_up = psi;
+            }
+            psi = (psi_low + psi_up) / 2.0;
+        }
+        Err(Error::LambertNoConvergence(self.max_iters))
+    }
+}
+
+/// The Stumpff functions `c2` and `c3` of the universal variable `psi`.
+fn stumpff(psi: f64) -> (f64, f64) {
+    if psi > 1e-6 {
+        let s = psi.sqrt();
+        ((1.0 - s.cos()) / psi, (s - s.sin()) / (s * psi))
+    } else if psi < -1e-6 {
+        let s = (-psi).sqrt();
+        ((s.cosh() - 1.0) / -psi, (s.sinh() - s) / (s * -psi))
+    } else {
+        (0.5, 1.0 / 6.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    const MU_EARTH: f64 = 3.986004418e14;
+
+    #[test]
+    fn test_hohmann_leo_to_geo() {
+        let transfer = Hohmann::<f64, ArrayRepr>::new(6678e3, 42164e3, MU_EARTH);
+        assert_relative_eq!(transfer.dv1.into_buf(), 2425.7, epsilon = 1.0);
+        assert_relative_eq!(transfer.dv2.into_buf(), 1466.8, epsilon = 1.0);
+        assert_relative_eq!(transfer.time_of_flight.into_buf(), 18_990.0, epsilon = 10.0);
+
+        let down = Hohmann::<f64, ArrayRepr>::new(42164e3, 6678e3, MU_EARTH);
+        assert!(down.dv1.into_buf() < 0.0);
+        assert_relative_eq!(
+            down.total_dv().into_buf(),
+            transfer.total_dv().into_buf(),
+            max_relative = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_bi_elliptic_beats_hohmann() {
+        let (r1, r2) = (7000e3, 7000e3 * 20.0);
+        let hohmann = Hohmann::<f64, ArrayRepr>::new(r1, r2, MU_EARTH);
+        let bi_elliptic = BiElliptic::<f64, ArrayRepr>::new(r1, r2, r2 * 3.0, MU_EARTH);
+        assert!(bi_elliptic.total_dv().into_buf() < hohmann.total_dv().into_buf());
+        assert!(bi_elliptic.time_of_flight.into_buf() > hohmann.time_of_flight.into_buf());
+
+        // with the intermediate radius at the target, it degenerates into a Hohmann transfer
+        let degenerate = BiElliptic::<f64, ArrayRepr>::new(r1, r2, r2, MU_EARTH);
+        assert_relative_eq!(degenerate.dv2.into_buf(), 0.0, epsilon = 1e-9);
+        assert_relative_eq!(
+            degenerate.total_dv().into_buf(),
+            hohmann.total_dv().into_buf(),
+            max_relative = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_impulse_raises_apoapsis() {
+        let (r1, r2) = (7000e3, 9000e3);
+        let transfer = Hohmann::<f64, ArrayRepr>::new(r1, r2, MU_EARTH);
+        let circular = OrbitalElements::<f64, ArrayRepr>::new(r1, 0.0, 0.3, 0.2, 0.0, 1.0);
+        let dv = Vector::new(0.0, transfer.dv1.into_buf(), 0.0);
+        let elements = circular.apply_impulse(&dv, MU_EARTH);
+        let a = elements.semi_major_axis().into_buf();
+        let e = elements.eccentricity().into_buf();
+        assert_relative_eq!(a * (1.0 + e), r2, max_relative = 1e-9);
+        assert_relative_eq!(a * (1.0 - e), r1, max_relative = 1e-9);
+        assert_relative_eq!(elements.inclination().into_buf(), 0.3, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_lambert_matches_kepler() {
+        let elements = OrbitalElements::<f64, ArrayRepr>::new(8000e3, 0.2, 0.4, 0.3, 0.5, 0.1);
+        let (r1, v1) = elements.to_cartesian(MU_EARTH);
+        // propagate to a later true anomaly through Kepler's equation
+        let e: f64 = 0.2;
+        let mean_anomaly = |nu: f64| {
+            let ecc = 2.0 * (((1.0 - e) / (1.0 + e)).sqrt() * (nu / 2.0).tan()).atan();
+            ecc - e * ecc.sin()
+        };
+        let n = (MU_EARTH / 8000e3_f64.powi(3)).sqrt();
+        let time_of_flight = (mean_anomaly(2.0) - mean_anomaly(0.1)) / n;
+        let later = OrbitalElements::<f64, ArrayRepr>::new(8000e3, 0.2, 0.4, 0.3, 0.5, 2.0);
+        let (r2, v2) = later.to_cartesian(MU_EARTH);
+
+        let (out_v1, out_v2) = Lambert::default()
+            .prograde(r1.cross(&v1).into_buf()[2] >= 0.0)
+            .solve(&r1, &r2, time_of_flight, MU_EARTH)
+            .unwrap();
+        assert_relative_eq!(out_v1, v1, max_relative = 1e-6);
+        assert_relative_eq!(out_v2, v2, max_relative = 1e-6);
+    }
+
+    #[test]
+    fn test_lambert_errors() {
+        let r1 = Vector::<f64, 3, ArrayRepr>::new(7000e3, 0.0, 0.0);
+        let r2 = Vector::new(-7000e3, 0.0, 0.0);
+        let err = Lambert::default()
+            .solve(&r1, &r2, 3000.0, MU_EARTH)
+            .unwrap_err();
+        assert!(matches!(err, Error::LambertUndefined));
+
+        let r2 = Vector::new(0.0, 7000e3, 0.0);
+        let err = Lambert::default()
+            .max_iters(3)
+            .solve(&r1, &r2, 3000.0, MU_EARTH)
+            .unwrap_err();
+        assert!(matches!(err, Error::LambertNoConvergence(3)));
+    }
+}
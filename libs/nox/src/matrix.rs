@@ -3,14 +3,15 @@ use core::marker::PhantomData;
 
 use crate::Const;
 use crate::{
-    DefaultRepr, Dim, Error, NonScalarDim, NonTupleDim, OwnedRepr, RealField, SquareDim, Tensor,
-    Vector,
+    DefaultRepr, Dim, Error, NonScalarDim, NonTupleDim, OwnedRepr, RealField, Scalar, SquareDim,
+    Tensor, Vector,
 };
 
 /// Type alias for a tensor that specifically represents a matrix.
 pub type Matrix<T, const R: usize, const C: usize, P = DefaultRepr> =
     Tensor<T, (Const<R>, Const<C>), P>;
 
+pub type Matrix2<T, R = DefaultRepr> = Matrix<T, 2, 2, R>;
 pub type Matrix3<T, R = DefaultRepr> = Matrix<T, 3, 3, R>;
 pub type Matrix3x6<T, R = DefaultRepr> = Matrix<T, 3, 6, R>;
 pub type Matrix4<T, R = DefaultRepr> = Matrix<T, 4, 4, R>;
@@ -27,6 +28,18 @@ impl<const R: usize, const C: usize, T: RealField, Rep: OwnedRepr> Matrix<T, R,
             phantom: PhantomData,
         }
     }
+
+    /// Creates a matrix from an array of column vectors.
+    pub fn from_cols(cols: [Vector<T, R, Rep>; C]) -> Self {
+        Matrix::<T, C, R, Rep>::from_rows(cols).transpose()
+    }
+}
+
+impl<const N: usize, T: RealField, Rep: OwnedRepr> Matrix<T, N, N, Rep> {
+    /// Computes the trace, the sum of the diagonal elements.
+    pub fn trace(&self) -> Scalar<T, Rep> {
+        (0..N).fold(T::zero(), |acc, i| acc + self.get([i, i]))
+    }
 }
 
 impl<T: RealField, D: Dim, R: OwnedRepr> Tensor<T, (D, D), R>
@@ -106,7 +119,7 @@ impl<T: RealField, R: OwnedRepr> Matrix3<T, R> {
 #[cfg(test)]
 mod tests {
 
-    use crate::{tensor, Client, CompFn, Vector, Vector3};
+    use crate::{tensor, ArrayRepr, Client, CompFn, Vector, Vector3};
 
     use super::*;
 
@@ -269,6 +282,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_cols() {
+        let m: Matrix<f64, 3, 2, ArrayRepr> =
+            Matrix::from_cols([tensor![1.0, 2.0, 3.0], tensor![4.0, 5.0, 6.0]]);
+        assert_eq!(m, tensor![[1.0, 4.0], [2.0, 5.0], [3.0, 6.0]]);
+    }
+
+    #[test]
+    fn test_trace() {
+        let identity = Matrix3::<f64, ArrayRepr>::eye();
+        assert_eq!(identity.trace().into_buf(), 3.0);
+        let m = Matrix3::<f64, ArrayRepr>::from_diag(tensor![1.0, 4.0, 8.0]);
+        assert_eq!(
+            m,
+            tensor![[1.0, 0.0, 0.0], [0.0, 4.0, 0.0], [0.0, 0.0, 8.0]]
+        );
+        assert_eq!(m.trace().into_buf(), 13.0);
+        assert_eq!(m.transpose().dot(&identity), m);
+    }
+
     // #[test]
     // fn test_inverse() {
     //     let a = tensor![[1.0, 2.0], [3.0, 4.0]];
@@ -1,6 +1,6 @@
 use core::ops::Add;
 
-use crate::{Const, Field, Matrix, OwnedRepr, Quaternion, RealField, Scalar, Vector};
+use crate::{Const, Field, Matrix, Matrix3, OwnedRepr, Quaternion, RealField, Scalar, Vector};
 
 /// Modified Rodrigues Parameters
 pub struct MRP<T: Field, R: OwnedRepr>(pub Vector<T, 3, R>);
@@ -70,6 +70,48 @@ impl<T: RealField, R: OwnedRepr> MRP<T, R> {
     }
 }
 
+impl<T: RealField, R: OwnedRepr> MRP<T, R> {
+    /// The shadow set `-σ / |σ|²`, which describes the same attitude going the other way round.
+    pub fn shadow(&self) -> Self {
+        MRP(-&self.0 / self.0.norm_squared())
+    }
+
+    /// The jacobian of [`MRP::shadow`], for mapping a covariance across a switch with `S P Sᵀ`.
+    pub fn shadow_jacobian(&self) -> Matrix3<T, R> {
+        let s2 = self.0.norm_squared();
+        let outer = Matrix::from_cols([0, 1, 2].map(|i| &self.0 * self.0.get(i)));
+        (T::two::<R>() * outer - Matrix3::eye() * &s2) / (&s2 * &s2)
+    }
+
+    /// The rate of change of the parameters for a body frame angular velocity `omega`,
+    /// `¼ ((1 - σ²) ω + 2 σ × ω + 2 σ (σ · ω))`.
+    pub fn kinematics(&self, omega: &Vector<T, 3, R>) -> Vector<T, 3, R> {
+        let sigma = &self.0;
+        let s2 = sigma.norm_squared();
+        let two = T::two::<R>();
+        let rate = omega * (T::one::<R>() - s2)
+            + sigma.cross(omega) * &two
+            + sigma * sigma.dot(omega) * &two;
+        rate / (&two * &two)
+    }
+}
+
+impl<R: OwnedRepr> MRP<f64, R> {
+    /// Switches to the shadow set when `|σ| > 1`, keeping the parameters away from their
+    /// singularity at a full turn.
+    pub fn switch(&self) -> Self {
+        let s2 = self.0.norm_squared().broadcast::<Const<3>>();
+        let one = Scalar::<f64, R>::from(1.0).broadcast::<Const<3>>();
+        MRP(one.select_less(&s2, &self.shadow().0, &self.0))
+    }
+
+    /// Integrates a body frame angular velocity over `dt` with an Euler step, then switches to
+    /// the shadow set if needed.
+    pub fn integrate_body(&self, omega: &Vector<f64, 3, R>, dt: f64) -> Self {
+        MRP(&self.0 + self.kinematics(omega) * dt).switch()
+    }
+}
+
 impl<T: RealField, R: OwnedRepr> Add for MRP<T, R> {
     type Output = Self;
 
@@ -123,6 +165,54 @@ mod tests {
         // value from rot2mrp
     }
 
+    #[test]
+    fn test_mrp_shadow_set() {
+        // 270 degrees about z is past the singularity of the short set
+        let q: Quaternion<f64, ArrayRepr> =
+            Quaternion::from_axis_angle(Vector3::z_axis(), 270.0f64.to_radians());
+        let mrp = q.mrp();
+        assert!(mrp.0.norm().into_buf() > 1.0);
+        let short = mrp.switch();
+        assert!(short.0.norm().into_buf() < 1.0);
+        assert_relative_eq!(short.0.inner(), mrp.shadow().0.inner());
+        assert_relative_eq!(short.shadow().0.inner(), mrp.0.inner(), epsilon = 1e-12);
+        // both sets describe the same attitude, with quaternions of opposite sign
+        let back = Quaternion::from(&short);
+        assert_relative_eq!((-back.0).inner(), q.0.inner(), epsilon = 1e-12);
+        assert_relative_eq!(q.mrp().switch().switch().0.inner(), short.0.inner());
+        // the switch is exact on either side of the unit sphere
+        let inside = MRP::<f64, ArrayRepr>::new(1.0 - 1e-9, 0.0, 0.0);
+        assert_eq!(inside.switch().0.inner(), inside.0.inner());
+        let outside = MRP::<f64, ArrayRepr>::new(1.0 + 1e-9, 0.0, 0.0);
+        assert_eq!(outside.switch().0.inner(), outside.shadow().0.inner());
+
+        let sigma = MRP::<f64, ArrayRepr>::new(0.1, -0.2, 0.3);
+        let jac = sigma.shadow_jacobian();
+        let eps = 1e-7;
+        let columns = [0, 1, 2].map(|i| {
+            let mut nudge = [0.0; 3];
+            nudge[i] = eps;
+            let nudged = MRP(sigma.0 + Vector3::<f64, ArrayRepr>::from_buf(nudge)).shadow();
+            (nudged.0 - sigma.shadow().0) * (1.0 / eps)
+        });
+        let numeric = Matrix3::from_cols(columns);
+        assert_relative_eq!(numeric.inner(), jac.inner(), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_mrp_integrate() {
+        // a full turn about x in small steps, switching sets on the way
+        let omega: Vector3<f64, ArrayRepr> = tensor![1.0, 0.0, 0.0];
+        let steps = 10000;
+        let dt = 2.0 * core::f64::consts::PI / steps as f64;
+        let mut sigma = MRP::<f64, ArrayRepr>::default();
+        for _ in 0..steps {
+            sigma = sigma.integrate_body(&omega, dt);
+            assert!(sigma.0.norm().into_buf() <= 1.0 + 1e-9);
+        }
+        assert!(sigma.0.norm().into_buf() < 1e-2);
+    }
+
     #[test]
     fn mrp_from_quat() {
         let q: Quaternion<f64, ArrayRepr> =
@@ -0,0 +1,552 @@
+//! Tree-structured multibody dynamics, using Featherstone's articulated-body algorithm (ABA).
+//!
+//! Motion vectors are `[ω, v]` and force vectors `[τ, f]`, the same layout as [`SpatialMotion`](crate::SpatialMotion) and
+//! [`SpatialForce`], and every link's quantities are expressed in that link's joint frame. The shape of the tree is
+//! plain data, so the recursions unroll into straight-line tensor code when traced and run the same in a compiled
+//! system as they do on the CPU.
+use alloc::vec::Vec;
+
+use crate::{Matrix, Matrix3, Matrix6, OwnedRepr, RealField, Scalar, SpatialForce, Vector};
+
+/// The kind of single degree of freedom joint connecting a link to its parent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JointKind {
+    /// Rotates about the joint axis by `q` radians.
+    Revolute,
+    /// Slides along the joint axis by `q` meters.
+    Prismatic,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Joint<T> {
+    pub kind: JointKind,
+    /// The unit axis of the joint, in the joint frame.
+    pub axis: [T; 3],
+}
+
+impl<T: RealField> Joint<T> {
+    pub fn revolute(axis: [T; 3]) -> Self {
+        Joint {
+            kind: JointKind::Revolute,
+            axis,
+        }
+    }
+
+    pub fn prismatic(axis: [T; 3]) -> Self {
+        Joint {
+            kind: JointKind::Prismatic,
+            axis,
+        }
+    }
+
+    /// The motion subspace of the joint, the spatial velocity of a unit joint rate.
+    pub(crate) fn subspace<R: OwnedRepr>(&self) -> Vector<T, 6, R> {
+        let axis = vec3(self.axis);
+        match self.kind {
+            JointKind::Revolute => axis.concat(Vector::<T, 3, R>::zeros()),
+            JointKind::Prismatic => Vector::<T, 3, R>::zeros().concat(axis),
+        }
+    }
+
+    /// The transform from the parent side of the joint to the child side at position `q`.
+    pub(crate) fn transform<R: OwnedRepr>(&self, q: &Scalar<T, R>) -> Matrix6<T, R> {
+        let axis = vec3::<T, R>(self.axis);
+        match self.kind {
+            JointKind::Revolute => {
+                // the coordinate transform is the transpose of the rotation by q
+                let (sin, cos) = (q.sin(), q.cos());
+                let rot = Matrix3::<T, R>::eye() * &cos
+                    + axis.outer(&axis) * &(T::one::<R>() - &cos)
+                    - axis.skew() * &sin;
+                plucker(&rot, &Vector::zeros())
+            }
+            JointKind::Prismatic => plucker(&Matrix3::eye(), &(axis * q)),
+        }
+    }
+}
+
+/// The mass properties of a link.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinkInertia<T> {
+    pub mass: T,
+    /// The center of mass, in the link's joint frame.
+    pub com: [T; 3],
+    /// The inertia tensor about the center of mass, in the link's joint frame.
+    pub inertia: [[T; 3]; 3],
+}
+
+impl<T: RealField> LinkInertia<T> {
+    /// A point mass at `com`.
+    pub fn point(mass: T, com: [T; 3]) -> Self {
+        let zero = [T::zero_prim(); 3];
+        LinkInertia {
+            mass,
+            com,
+            inertia: [zero; 3],
+        }
+    }
+
+    /// The 6x6 spatial inertia about the joint frame origin.
+    pub(crate) fn spatial<R: OwnedRepr>(&self) -> Matrix6<T, R> {
+        let mass = Scalar::<T, R>::from(self.mass);
+        let c = vec3::<T, R>(self.com).skew();
+        let rot = mat3::<T, R>(self.inertia) + c.dot(&c.transpose()) * &mass;
+        let coupling = c.clone() * &mass;
+        block(
+            &rot,
+            &coupling,
+            &coupling.transpose(),
+            &(Matrix3::eye() * &mass),
+        )
+    }
+}
+
+/// A link of a [`Multibody`] tree.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Link<T> {
+    /// The link this one hangs from, or `None` for links attached to the fixed base.
+    pub parent: Option<usize>,
+    pub joint: Joint<T>,
+    /// The position of the joint in the parent's frame (or the world frame for the base). The joint frame has the
+    /// same orientation as the parent's frame when the joint is at zero.
+    pub offset: [T; 3],
+    pub inertia: LinkInertia<T>,
+}
+
+/// The velocity-dependent terms of a link, found on the way out from the base.
+pub(crate) struct LinkState<T: RealField, R: OwnedRepr> {
+    /// The transform from the parent's frame to this link's frame.
+    pub xup: Matrix6<T, R>,
+    pub subspace: Vector<T, 6, R>,
+    pub vel: Vector<T, 6, R>,
+    /// The velocity-product acceleration.
+    pub bias_accel: Vector<T, 6, R>,
+}
+
+/// A kinematic tree of rigid links with one degree of freedom per joint, attached to a fixed base.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Multibody<T> {
+    links: Vec<Link<T>>,
+    /// The gravitational acceleration, in the base frame.
+    pub gravity: [T; 3],
+}
+
+impl<T: RealField> Multibody<T> {
+    pub fn new(gravity: [T; 3]) -> Self {
+        Multibody {
+            links: Vec::new(),
+            gravity,
+        }
+    }
+
+    /// Adds a link, returning its index, which is also the index of its joint in the joint vectors.
+    ///
+    /// Parents have to be added before their children.
+    pub fn add_link(
+        &mut self,
+        parent: Option<usize>,
+        joint: Joint<T>,
+        offset: [T; 3],
+        inertia: LinkInertia<T>,
+    ) -> usize {
+        if let Some(parent) = parent {
+            assert!(
+                parent < self.links.len(),
+                "link {parent} has to be added before its children"
+            );
+        }
+        self.links.push(Link {
+            parent,
+            joint,
+            offset,
+            inertia,
+        });
+        self.links.len() - 1
+    }
+
+    pub fn links(&self) -> &[Link<T>] {
+        &self.links
+    }
+
+    /// The number of degrees of freedom, one per joint.
+    pub fn dof(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Computes the joint accelerations from the joint positions `q`, rates `qd`, and forces `tau`.
+    pub fn forward_dynamics<const N: usize, R: OwnedRepr>(
+        &self,
+        q: &Vector<T, N, R>,
+        qd: &Vector<T, N, R>,
+        tau: &Vector<T, N, R>,
+    ) -> Vector<T, N, R> {
+        self.forward_dynamics_with_forces(q, qd, tau, &[])
+    }
+
+    /// Like [`Multibody::forward_dynamics`], with an `external` force acting on each link, expressed in that link's
+    /// frame. `external` is either empty or has one force per link.
+    pub fn forward_dynamics_with_forces<const N: usize, R: OwnedRepr>(
+        &self,
+        q: &Vector<T, N, R>,
+        qd: &Vector<T, N, R>,
+        tau: &Vector<T, N, R>,
+        external: &[SpatialForce<T, R>],
+    ) -> Vector<T, N, R> {
+        assert!(
+            external.is_empty() || external.len() == N,
+            "expected one external force per link"
+        );
+        let states = self.kinematics(q, qd);
+        let tau = tau.parts();
+
+        let mut art_inertia: Vec<Matrix6<T, R>> = self
+            .links
+            .iter()
+            .map(|link| link.inertia.spatial())
+            .collect();
+        let mut bias_force: Vec<Vector<T, 6, R>> = states
+            .iter()
+            .zip(&art_inertia)
+            .map(|(state, inertia)| crf(&state.vel).dot(&inertia.dot(&state.vel)))
+            .collect();
+        for (bias, force) in bias_force.iter_mut().zip(external) {
+            *bias = &*bias - &force.inner;
+        }
+
+        // articulated inertias and bias forces, accumulated from the leaves in
+        let mut u: Vec<Vector<T, 6, R>> = (0..N).map(|_| Vector::zeros()).collect();
+        let mut d: Vec<Scalar<T, R>> = (0..N).map(|_| T::zero()).collect();
+        let mut u_tau: Vec<Scalar<T, R>> = (0..N).map(|_| T::zero()).collect();
+        for i in (0..N).rev() {
+            let state = &states[i];
+            u[i] = art_inertia[i].dot(&state.subspace);
+            d[i] = state.subspace.dot(&u[i]);
+            u_tau[i] = &tau[i] - state.subspace.dot(&bias_force[i]);
+            if let Some(parent) = self.links[i].parent {
+                let inertia = &art_inertia[i] - u[i].outer(&u[i]) / &d[i];
+                let bias =
+                    &bias_force[i] + inertia.dot(&state.bias_accel) + &u[i] * &(&u_tau[i] / &d[i]);
+                let xup_t = state.xup.transpose();
+                art_inertia[parent] = &art_inertia[parent] + xup_t.dot(&inertia).dot(&state.xup);
+                bias_force[parent] = &bias_force[parent] + xup_t.dot(&bias);
+            }
+        }
+
+        // accelerations, from the base out; the base accelerating upwards stands in for gravity
+        let base_accel: Vector<T, 6, R> =
+            Vector::<T, 3, R>::zeros().concat(-vec3::<T, R>(self.gravity));
+        let mut accel: Vec<Vector<T, 6, R>> = Vec::with_capacity(N);
+        let mut qdd: Vec<Scalar<T, R>> = Vec::with_capacity(N);
+        for (i, state) in states.iter().enumerate() {
+            let parent_accel = match self.links[i].parent {
+                Some(parent) => &accel[parent],
+                None => &base_accel,
+            };
+            let a = state.xup.dot(parent_accel) + &state.bias_accel;
+            let joint_accel = (&u_tau[i] - u[i].dot(&a)) / &d[i];
+            accel.push(a + &state.subspace * &joint_accel);
+            qdd.push(joint_accel);
+        }
+        Vector::from_scalars(qdd)
+    }
+
+    /// Computes the joint forces needed to produce the joint accelerations `qdd`, using the
+    /// recursive Newton-Euler algorithm (RNEA).
+    pub fn inverse_dynamics<const N: usize, R: OwnedRepr>(
+        &self,
+        q: &Vector<T, N, R>,
+        qd: &Vector<T, N, R>,
+        qdd: &Vector<T, N, R>,
+    ) -> Vector<T, N, R> {
+        self.inverse_dynamics_with_forces(q, qd, qdd, &[])
+    }
+
+    /// Like [`Multibody::inverse_dynamics`], with an `external` force acting on each link, see
+    /// [`Multibody::forward_dynamics_with_forces`].
+    pub fn inverse_dynamics_with_forces<const N: usize, R: OwnedRepr>(
+        &self,
+        q: &Vector<T, N, R>,
+        qd: &Vector<T, N, R>,
+        qdd: &Vector<T, N, R>,
+        external: &[SpatialForce<T, R>],
+    ) -> Vector<T, N, R> {
+        assert!(
+            external.is_empty() || external.len() == N,
+            "expected one external force per link"
+        );
+        let states = self.kinematics(q, qd);
+        let qdd = qdd.parts();
+        let base_accel: Vector<T, 6, R> =
+            Vector::<T, 3, R>::zeros().concat(-vec3::<T, R>(self.gravity));
+
+        let mut accel: Vec<Vector<T, 6, R>> = Vec::with_capacity(N);
+        let mut forces: Vec<Vector<T, 6, R>> = Vec::with_capacity(N);
+        for (i, (link, state)) in self.links.iter().zip(&states).enumerate() {
+            let parent_accel = match link.parent {
+                Some(parent) => &accel[parent],
+                None => &base_accel,
+            };
+            let a = state.xup.dot(parent_accel) + &state.subspace * &qdd[i] + &state.bias_accel;
+            let inertia = link.inertia.spatial::<R>();
+            let mut force = inertia.dot(&a) + crf(&state.vel).dot(&inertia.dot(&state.vel));
+            if let Some(external) = external.get(i) {
+                force = force - &external.inner;
+            }
+            accel.push(a);
+            forces.push(force);
+        }
+
+        let mut tau: Vec<Scalar<T, R>> = (0..N).map(|_| T::zero()).collect();
+        for i in (0..N).rev() {
+            tau[i] = states[i].subspace.dot(&forces[i]);
+            if let Some(parent) = self.links[i].parent {
+                forces[parent] = &forces[parent] + states[i].xup.transpose().dot(&forces[i]);
+            }
+        }
+        Vector::from_scalars(tau)
+    }
+
+    /// The joint forces that hold the tree still against gravity and the velocity-product terms, so
+    /// `tau = M(q) qdd + bias_forces(q, qd)`.
+    pub fn bias_forces<const N: usize, R: OwnedRepr>(
+        &self,
+        q: &Vector<T, N, R>,
+        qd: &Vector<T, N, R>,
+    ) -> Vector<T, N, R> {
+        self.inverse_dynamics(q, qd, &Vector::zeros())
+    }
+
+    /// Computes the joint-space mass matrix `M(q)` with the composite rigid-body algorithm (CRBA).
+    pub fn mass_matrix<const N: usize, R: OwnedRepr>(
+        &self,
+        q: &Vector<T, N, R>,
+    ) -> Matrix<T, N, N, R> {
+        let states = self.kinematics(q, &Vector::zeros());
+
+        // composite inertias of each subtree, accumulated from the leaves in
+        let mut composite: Vec<Matrix6<T, R>> = self
+            .links
+            .iter()
+            .map(|link| link.inertia.spatial())
+            .collect();
+        for i in (0..N).rev() {
+            if let Some(parent) = self.links[i].parent {
+                let xup = &states[i].xup;
+                composite[parent] =
+                    &composite[parent] + xup.transpose().dot(&composite[i]).dot(xup);
+            }
+        }
+
+        let mut h: Vec<Scalar<T, R>> = (0..N * N).map(|_| T::zero()).collect();
+        for i in 0..N {
+            let mut force = composite[i].dot(&states[i].subspace);
+            h[i * N + i] = states[i].subspace.dot(&force);
+            let mut j = i;
+            while let Some(parent) = self.links[j].parent {
+                force = states[j].xup.transpose().dot(&force);
+                j = parent;
+                let entry = states[j].subspace.dot(&force);
+                h[i * N + j] = entry.clone();
+                h[j * N + i] = entry;
+            }
+        }
+        Matrix::from_scalars(h)
+    }
+
+    /// Finds each link's transform from its parent, velocity, and velocity-product acceleration.
+    pub(crate) fn kinematics<const N: usize, R: OwnedRepr>(
+        &self,
+        q: &Vector<T, N, R>,
+        qd: &Vector<T, N, R>,
+    ) -> Vec<LinkState<T, R>> {
+        assert_eq!(
+            N,
+            self.links.len(),
+            "expected one joint coordinate per link"
+        );
+        let (q, qd) = (q.parts(), qd.parts());
+        let mut states: Vec<LinkState<T, R>> = Vec::with_capacity(N);
+        for (i, link) in self.links.iter().enumerate() {
+            let subspace = link.joint.subspace::<R>();
+            let xup = link
+                .joint
+                .transform(&q[i])
+                .dot(&plucker(&Matrix3::eye(), &vec3(link.offset)));
+            let joint_vel = &subspace * &qd[i];
+            let (vel, bias_accel) = match link.parent {
+                Some(parent) => {
+                    let vel = xup.dot(&states[parent].vel) + &joint_vel;
+                    let bias_accel = crm(&vel).dot(&joint_vel);
+                    (vel, bias_accel)
+                }
+                None => (joint_vel, Vector::zeros()),
+            };
+            states.push(LinkState {
+                xup,
+                subspace,
+                vel,
+                bias_accel,
+            });
+        }
+        states
+    }
+}
+
+fn vec3<T: RealField, R: OwnedRepr>(v: [T; 3]) -> Vector<T, 3, R> {
+    Vector::from_scalars(v.map(Scalar::from))
+}
+
+fn mat3<T: RealField, R: OwnedRepr>(m: [[T; 3]; 3]) -> Matrix3<T, R> {
+    Matrix::from_rows(m.map(vec3::<T, R>))
+}
+
+/// Assembles a 6x6 matrix from its 3x3 blocks.
+fn block<T: RealField, R: OwnedRepr>(
+    a: &Matrix3<T, R>,
+    b: &Matrix3<T, R>,
+    c: &Matrix3<T, R>,
+    d: &Matrix3<T, R>,
+) -> Matrix6<T, R> {
+    Matrix::from_rows(core::array::from_fn(|i| {
+        let (left, right): (Vector<T, 3, R>, Vector<T, 3, R>) = if i < 3 {
+            (a.row(i), b.row(i))
+        } else {
+            (c.row(i - 3), d.row(i - 3))
+        };
+        left.concat(right)
+    }))
+}
+
+/// The motion transform into a frame rotated by the coordinate transform `rot` and displaced by `pos`.
+pub(crate) fn plucker<T: RealField, R: OwnedRepr>(
+    rot: &Matrix3<T, R>,
+    pos: &Vector<T, 3, R>,
+) -> Matrix6<T, R> {
+    block(rot, &Matrix3::zeros(), &-rot.dot(&pos.skew()), rot)
+}
+
+/// The spatial cross product operator for motion vectors, `crm(v) m = v × m`.
+pub(crate) fn crm<T: RealField, R: OwnedRepr>(v: &Vector<T, 6, R>) -> Matrix6<T, R> {
+    let angular: Vector<T, 3, R> = v.fixed_slice(&[0]);
+    let linear: Vector<T, 3, R> = v.fixed_slice(&[3]);
+    let w = angular.skew();
+    block(&w, &Matrix3::zeros(), &linear.skew(), &w)
+}
+
+/// The spatial cross product operator for force vectors, `crf(v) f = v ×* f`.
+pub(crate) fn crf<T: RealField, R: OwnedRepr>(v: &Vector<T, 6, R>) -> Matrix6<T, R> {
+    -crm(v).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::{tensor, ArrayRepr};
+
+    const G: f64 = 9.81;
+
+    fn double_pendulum(m1: f64, l1: f64, m2: f64, l2: f64) -> Multibody<f64> {
+        let mut model = Multibody::new([0.0, 0.0, -G]);
+        let axis = [0.0, 1.0, 0.0];
+        let upper = model.add_link(
+            None,
+            Joint::revolute(axis),
+            [0.0; 3],
+            LinkInertia::point(m1, [0.0, 0.0, -l1]),
+        );
+        model.add_link(
+            Some(upper),
+            Joint::revolute(axis),
+            [0.0, 0.0, -l1],
+            LinkInertia::point(m2, [0.0, 0.0, -l2]),
+        );
+        model
+    }
+
+    #[test]
+    fn test_pendulum() {
+        let mut model = Multibody::new([0.0, 0.0, -G]);
+        model.add_link(
+            None,
+            Joint::revolute([0.0, 1.0, 0.0]),
+            [0.0; 3],
+            LinkInertia::point(2.0, [0.0, 0.0, -0.5]),
+        );
+        let q: Vector<f64, 1, ArrayRepr> = tensor![0.4];
+        let qdd = model.forward_dynamics(&q, &tensor![1.5], &tensor![0.0]);
+        assert_relative_eq!(qdd.into_buf()[0], -G / 0.5 * 0.4f64.sin(), epsilon = 1e-10);
+
+        // a prismatic joint along the vertical just falls
+        let mut model = Multibody::new([0.0, 0.0, -G]);
+        model.add_link(
+            None,
+            Joint::prismatic([0.0, 0.0, 1.0]),
+            [0.0; 3],
+            LinkInertia::point(3.0, [0.1, 0.0, 0.0]),
+        );
+        let q: Vector<f64, 1, ArrayRepr> = tensor![2.0];
+        let qdd = model.forward_dynamics(&q, &tensor![-1.0], &tensor![3.0]);
+        assert_relative_eq!(qdd.into_buf()[0], -G + 1.0, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_double_pendulum() {
+        let (m1, l1, m2, l2) = (1.0, 1.0, 0.5, 0.8);
+        let model = double_pendulum(m1, l1, m2, l2);
+        let (q1, q2) = (0.3, -0.5);
+        let q: Vector<f64, 2, ArrayRepr> = tensor![q1, q2];
+        let qdd = model.forward_dynamics(&q, &tensor![0.0, 0.0], &tensor![0.0, 0.0]);
+
+        // M qdd = -G for point masses at rest, with q2 measured relative to the upper link
+        let m11 = (m1 + m2) * l1 * l1 + m2 * l2 * l2 + 2.0 * m2 * l1 * l2 * q2.cos();
+        let m12 = m2 * l2 * l2 + m2 * l1 * l2 * q2.cos();
+        let m22 = m2 * l2 * l2;
+        let g1 = (m1 + m2) * G * l1 * q1.sin() + m2 * G * l2 * (q1 + q2).sin();
+        let g2 = m2 * G * l2 * (q1 + q2).sin();
+        let det = m11 * m22 - m12 * m12;
+        let expected = [(-g1 * m22 + g2 * m12) / det, (g1 * m12 - g2 * m11) / det];
+        let qdd = qdd.into_buf();
+        assert_relative_eq!(qdd[0], expected[0], epsilon = 1e-9);
+        assert_relative_eq!(qdd[1], expected[1], epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_inverse_dynamics() {
+        let (m1, l1, m2, l2) = (1.0, 1.0, 0.5, 0.8);
+        let model = double_pendulum(m1, l1, m2, l2);
+        let (q1, q2) = (0.3, -0.5);
+        let q: Vector<f64, 2, ArrayRepr> = tensor![q1, q2];
+
+        let m = model.mass_matrix(&q).into_buf();
+        let m11 = (m1 + m2) * l1 * l1 + m2 * l2 * l2 + 2.0 * m2 * l1 * l2 * q2.cos();
+        let m12 = m2 * l2 * l2 + m2 * l1 * l2 * q2.cos();
+        let m22 = m2 * l2 * l2;
+        assert_relative_eq!(m[0][0], m11, epsilon = 1e-9);
+        assert_relative_eq!(m[0][1], m12, epsilon = 1e-9);
+        assert_relative_eq!(m[1][0], m12, epsilon = 1e-9);
+        assert_relative_eq!(m[1][1], m22, epsilon = 1e-9);
+
+        // at rest the bias forces are just gravity
+        let bias = model.bias_forces(&q, &tensor![0.0, 0.0]).into_buf();
+        assert_relative_eq!(
+            bias[0],
+            (m1 + m2) * G * l1 * q1.sin() + m2 * G * l2 * (q1 + q2).sin(),
+            epsilon = 1e-9
+        );
+        assert_relative_eq!(bias[1], m2 * G * l2 * (q1 + q2).sin(), epsilon = 1e-9);
+
+        // RNEA inverts ABA, and agrees with M qdd + bias
+        let qd: Vector<f64, 2, ArrayRepr> = tensor![0.7, -1.2];
+        let qdd: Vector<f64, 2, ArrayRepr> = tensor![0.25, 2.0];
+        let tau = model.inverse_dynamics(&q, &qd, &qdd);
+        let out = model.forward_dynamics(&q, &qd, &tau).into_buf();
+        assert_relative_eq!(out[0], 0.25, epsilon = 1e-9);
+        assert_relative_eq!(out[1], 2.0, epsilon = 1e-9);
+        let expected = model.mass_matrix(&q).dot(&qdd) + model.bias_forces(&q, &qd);
+        let (tau, expected) = (tau.into_buf(), expected.into_buf());
+        assert_relative_eq!(tau[0], expected[0], epsilon = 1e-9);
+        assert_relative_eq!(tau[1], expected[1], epsilon = 1e-9);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     xla::ElementType, ArrayTy, BinaryOp, CompFn, DefaultMap, DefaultMappedDim, Dim,
-    DotDimensionNums, Error, Noxpr, NoxprFn, NoxprId, NoxprNode, NoxprTy, ReplaceDim, ReprMonad,
-    Tensor, TensorItem,
+    DotDimensionNums, Error, Noxpr, NoxprFn, NoxprId, NoxprNode, NoxprScalarExt, NoxprTy,
+    ParamExpr, ReplaceDim, ReplacementTracer, ReprMonad, Tensor, TensorItem, While,
 };
 use core::{
     iter,
@@ -138,6 +138,7 @@ impl BatchTracer {
             NoxprNode::Sqrt(e) => self.visit_unary_op(e, Noxpr::sqrt)?,
             NoxprNode::Neg(e) => self.visit_unary_op(e, Noxpr::neg)?,
             NoxprNode::Log(e) => self.visit_unary_op(e, Noxpr::log)?,
+            NoxprNode::Exp(e) => self.visit_unary_op(e, Noxpr::exp)?,
             NoxprNode::Sin(e) => self.visit_unary_op(e, Noxpr::sin)?,
             NoxprNode::Cos(e) => self.visit_unary_op(e, Noxpr::cos)?,
             NoxprNode::Abs(e) => self.visit_unary_op(e, Noxpr::abs)?,
@@ -443,13 +444,17 @@ impl BatchTracer {
             NoxprNode::Jax(_) => {
                 unimplemented!()
             }
-            NoxprNode::GetTupleElement(g) => {
-                let NoxprNode::Tuple(elems) = g.expr.deref() else {
-                    return Err(Error::UnbatchableArgument);
-                };
-                let expr = elems.get(g.index).ok_or(Error::UnbatchableArgument)?;
-                self.visit(expr)?
-            }
+            NoxprNode::GetTupleElement(g) => match g.expr.deref() {
+                NoxprNode::Tuple(elems) => {
+                    let expr = elems.get(g.index).ok_or(Error::UnbatchableArgument)?;
+                    self.visit(expr)?
+                }
+                // every element of a batched while loop's state is batched along the same axis
+                NoxprNode::While(_) => self
+                    .visit(&g.expr)?
+                    .map_expr(|expr| expr.get_tuple_element(g.index)),
+                _ => return Err(Error::UnbatchableArgument),
+            },
             NoxprNode::Scan(s) => {
                 let BatchAxis::Mapped { size: out_size, .. } = self.out_axis else {
                     panic!();
@@ -572,6 +577,28 @@ impl BatchTracer {
                     }
                 }
             }
+            NoxprNode::While(w) => {
+                let NoxprNode::Tuple(elems) = w.initial_state.deref() else {
+                    return Err(Error::UnbatchableArgument);
+                };
+                let init = elems
+                    .iter()
+                    .map(|e| self.visit(e))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                if init.iter().all(|e| e.batch_axis == BatchAxis::NotMapped) {
+                    let init = init.into_iter().map(|e| e.inner).collect();
+                    BatchedExpr {
+                        inner: Noxpr::new(NoxprNode::While(While {
+                            initial_state: Noxpr::tuple(init),
+                            cond: w.cond.clone(),
+                            body: w.body.clone(),
+                        })),
+                        batch_axis: BatchAxis::NotMapped,
+                    }
+                } else {
+                    self.visit_while(w, init)?
+                }
+            }
             NoxprNode::Convert(c) => {
                 let arg = self.visit(&c.arg)?;
                 BatchedExpr {
@@ -620,6 +647,102 @@ impl BatchTracer {
         Ok(op)
     }
 
+    /// Batches a while loop by running it until every lane has stopped, leaving the state of
+    /// lanes that stopped early untouched.
+    fn visit_while(&self, w: &While, init: Vec<BatchedExpr>) -> Result<BatchedExpr, Error> {
+        let BatchAxis::Mapped { size, .. } = self.out_axis else {
+            return Err(Error::UnbatchableArgument);
+        };
+        let axis = BatchAxis::Mapped { index: 0, size };
+        let init = init
+            .into_iter()
+            .map(|e| {
+                e.move_batch_axis(axis.clone())
+                    .ok_or(Error::UnbatchableArgument)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let tys = init
+            .iter()
+            .map(|e| e.inner.ty().ok_or(Error::UnbatchableArgument))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let param = Noxpr::parameter(0, NoxprTy::Tuple(tys), "batched_while_state".to_string());
+
+        // traces `func` with its tuple parameter swapped for the batched one
+        let batch_fn = |func: &NoxprFn| -> Result<(BatchTracer, Noxpr), Error> {
+            let [arg] = &func.args[..] else {
+                return Err(Error::WhileWrongArgCount);
+            };
+            let NoxprNode::Param(ParamExpr {
+                ty: NoxprTy::Tuple(tys),
+                number,
+                name,
+            }) = arg.deref()
+            else {
+                return Err(Error::UnbatchableArgument);
+            };
+            let elems = tys
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| Noxpr::parameter(*number, ty.clone(), format!("{name}_{i}")))
+                .collect::<Vec<_>>();
+            let cache = iter::once((arg.id(), Noxpr::tuple(elems.clone()))).collect();
+            let inner = ReplacementTracer { cache }.visit(&func.inner);
+            let mut tracer = BatchTracer::new(axis.clone());
+            for (i, elem) in elems.iter().enumerate() {
+                let batched = BatchedExpr {
+                    inner: param.get_tuple_element(i),
+                    batch_axis: axis.clone(),
+                };
+                tracer.cache.insert(elem.id(), batched);
+            }
+            Ok((tracer, inner))
+        };
+        let running = || -> Result<Noxpr, Error> {
+            let (mut tracer, inner) = batch_fn(&w.cond)?;
+            let running = tracer.visit(&inner)?;
+            let running = running.move_batch_axis(axis.clone());
+            Ok(running.ok_or(Error::UnbatchableArgument)?.inner)
+        };
+
+        // keeps looping while any lane is running
+        let ones = 1.0f64
+            .constant()
+            .broadcast_in_dim(smallvec![size as i64], smallvec![]);
+        let count = running()?.convert(ElementType::F64).dot(&ones);
+        let cond = NoxprFn::new(vec![param.clone()], 0.0f64.constant().less(count));
+
+        let running = running()?;
+        let (mut tracer, inner) = batch_fn(&w.body)?;
+        let NoxprNode::Tuple(next) = inner.deref() else {
+            return Err(Error::UnbatchableArgument);
+        };
+        let next = next
+            .iter()
+            .enumerate()
+            .map(|(i, next)| {
+                let next = tracer
+                    .visit(next)?
+                    .move_batch_axis(axis.clone())
+                    .ok_or(Error::UnbatchableArgument)?
+                    .inner;
+                let shape = next.shape().ok_or(Error::UnbatchableArgument)?;
+                let running = running.clone().broadcast_in_dim(shape, smallvec![0]);
+                Ok(running.select(next, param.get_tuple_element(i)))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let body = NoxprFn::new(vec![param.clone()], Noxpr::tuple(next));
+
+        let initial_state = Noxpr::tuple(init.into_iter().map(|e| e.inner).collect());
+        Ok(BatchedExpr {
+            inner: Noxpr::new(NoxprNode::While(While {
+                initial_state,
+                cond,
+                body,
+            })),
+            batch_axis: axis,
+        })
+    }
+
     /// Specifically handles the dot-general operation in a batched context.
     fn visit_dot_general(
         &mut self,
@@ -33,6 +33,11 @@ impl Client {
         self.compile_options.disable_optimizations();
     }
 
+    /// The options every computation is compiled with.
+    pub fn compile_options(&self) -> &xla::CompileOptions {
+        &self.compile_options
+    }
+
     /// Compiles an XLA computation into a kernel using the client's compile options.
     pub fn compile(
         &self,
@@ -42,6 +47,12 @@ impl Client {
             .compile_with_options(comp, self.compile_options.clone())
     }
 
+    /// Loads a serialized executable using the client's compile options.
+    pub fn load_executable(&self, bytes: &[u8]) -> Result<xla::PjRtLoadedExecutable, xla::Error> {
+        self.pjrt_client
+            .deserialize_executable(bytes, self.compile_options.clone())
+    }
+
     /// Creates a new `Client` using the default CPU backend.
     pub fn cpu() -> Result<Self, Error> {
         xla::PjRtClient::cpu().map(Client::new).map_err(Error::from)
@@ -0,0 +1,95 @@
+//! Renders `Noxpr` graphs in the Graphviz DOT format.
+use std::{collections::HashMap, fmt::Write, ops::Deref};
+
+use crate::{Noxpr, NoxprFn, NoxprId, NoxprNode};
+
+impl Noxpr {
+    /// Renders the expression graph in the Graphviz DOT format, with one node per operation labeled with its type and shape.
+    ///
+    /// Shared subexpressions are only rendered once, so the output reflects what is actually traced.
+    pub fn to_dot(&self) -> String {
+        let mut printer = DotPrinter::default();
+        printer.visit(self);
+        printer.finish()
+    }
+}
+
+impl NoxprFn {
+    /// Renders the function body in the Graphviz DOT format, see [`Noxpr::to_dot`].
+    pub fn to_dot(&self) -> String {
+        self.inner.to_dot()
+    }
+}
+
+#[derive(Default)]
+struct DotPrinter {
+    ids: HashMap<NoxprId, usize>,
+    out: String,
+}
+
+impl DotPrinter {
+    fn visit(&mut self, expr: &Noxpr) -> usize {
+        if let Some(num) = self.ids.get(&expr.id()) {
+            return *num;
+        }
+        let operands = expr
+            .operands()
+            .into_iter()
+            .map(|e| self.visit(e))
+            .collect::<Vec<_>>();
+        let num = self.ids.len();
+        self.ids.insert(expr.id(), num);
+
+        let mut label = expr.name().to_string();
+        match expr.deref() {
+            NoxprNode::Param(p) => write!(label, " {}", p.name).unwrap(),
+            NoxprNode::Constant(c) => write!(label, " [{}]", c.summary()).unwrap(),
+            NoxprNode::GetTupleElement(g) => write!(label, " .{}", g.index).unwrap(),
+            _ => {}
+        }
+        match (expr.element_type(), expr.shape()) {
+            (Some(ty), Some(shape)) => write!(label, "\n{:?}{:?}", ty, &shape[..]).unwrap(),
+            _ => label.push_str("\ntuple"),
+        }
+        let label = label
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        writeln!(self.out, "  n{} [label=\"{}\"];", num, label).unwrap();
+        for operand in operands {
+            writeln!(self.out, "  n{} -> n{};", operand, num).unwrap();
+        }
+        num
+    }
+
+    fn finish(self) -> String {
+        format!("digraph noxpr {{\n  node [shape=box];\n{}}}\n", self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+    use xla::ElementType;
+
+    use crate::{ArrayTy, Noxpr, NoxprTy};
+
+    #[test]
+    fn test_to_dot() {
+        let ty = ArrayTy::new(ElementType::F64, smallvec![3]);
+        let a = Noxpr::parameter(0, NoxprTy::ArrayTy(ty.clone()), "a".to_string());
+        let c = Noxpr::constant(xla::Literal::vector(&[1.0f64, 2.0, 3.0]), ty);
+        let sum = a + c;
+        let out = sum.clone() * sum;
+
+        let dot = out.to_dot();
+        assert!(dot.starts_with("digraph noxpr {"));
+        assert_eq!(dot.matches("label=").count(), 4);
+        assert_eq!(dot.matches("->").count(), 4);
+        assert!(dot.contains("Constant [1, 2, 3]\\nF64[3]"));
+
+        let text = out.to_string();
+        assert!(text.contains("var_0 + var_1"));
+        assert!(text.contains("var_2 * var_2"));
+    }
+}
@@ -0,0 +1,324 @@
+//! Forward-mode differentiation of traced expressions.
+use std::{collections::HashMap, ops::Deref};
+
+use xla::ElementType;
+
+use crate::{
+    Concat, Error, Gather, Noxpr, NoxprId, NoxprNode, NoxprScalarExt, NoxprTy, Select, Slice,
+};
+
+impl Noxpr {
+    /// Computes the jacobian-vector product of `self` with respect to `wrt`, the derivative of
+    /// `self` in the direction `tangent`, which must have the shape of `wrt`.
+    ///
+    /// The derivative is traced alongside the primal expression, so it's exact and costs about as
+    /// much as evaluating `self` again. Parts of `self` that don't depend on `wrt` get a zero
+    /// tangent. Loops, calls and cholesky decompositions can't be differentiated through yet, and
+    /// return [`Error::UnsupportedJvp`] if `wrt` flows into them.
+    pub fn jvp(&self, wrt: &Noxpr, tangent: Noxpr) -> Result<Noxpr, Error> {
+        let mut tracer = JvpTracer {
+            cache: std::iter::once((wrt.id(), Some(tangent))).collect(),
+        };
+        match tracer.visit(self)? {
+            Some(tangent) => Ok(tangent),
+            None => zeros_like(self),
+        }
+    }
+}
+
+/// Pushes tangents forward through an expression, with `None` standing in for a zero tangent so
+/// the parts of the graph that don't depend on the seed stay untouched.
+struct JvpTracer {
+    cache: HashMap<NoxprId, Option<Noxpr>>,
+}
+
+impl JvpTracer {
+    fn visit(&mut self, expr: &Noxpr) -> Result<Option<Noxpr>, Error> {
+        if let Some(tangent) = self.cache.get(&expr.id()) {
+            return Ok(tangent.clone());
+        }
+        let tangent = match expr.deref() {
+            NoxprNode::Param(_)
+            | NoxprNode::Constant(_)
+            | NoxprNode::Iota(_)
+            | NoxprNode::And(_)
+            | NoxprNode::Or(_)
+            | NoxprNode::GreaterOrEqual(_)
+            | NoxprNode::LessOrEqual(_)
+            | NoxprNode::Less(_)
+            | NoxprNode::Equal(_) => None,
+            #[cfg(feature = "jax")]
+            NoxprNode::Jax(_) => None,
+
+            NoxprNode::Tuple(elems) => {
+                let tangents = self.visit_all(elems)?;
+                or_zeros(elems, tangents)?.map(Noxpr::tuple)
+            }
+            NoxprNode::GetTupleElement(g) => self
+                .visit(&g.expr)?
+                .map(|tangent| tangent.get_tuple_element(g.index)),
+
+            NoxprNode::Add(b) => {
+                let (lhs, rhs) = (self.visit(&b.lhs)?, self.visit(&b.rhs)?);
+                sum(lhs, rhs)
+            }
+            NoxprNode::Sub(b) => {
+                let (lhs, rhs) = (self.visit(&b.lhs)?, self.visit(&b.rhs)?);
+                sum(lhs, rhs.map(|rhs| -rhs))
+            }
+            NoxprNode::Mul(b) => {
+                let lhs = self.visit(&b.lhs)?.map(|t| t * b.rhs.clone());
+                let rhs = self.visit(&b.rhs)?.map(|t| b.lhs.clone() * t);
+                sum(lhs, rhs)
+            }
+            NoxprNode::Div(b) => {
+                // d(a / b) = (da - (a / b) db) / b
+                let lhs = self.visit(&b.lhs)?;
+                let rhs = self.visit(&b.rhs)?.map(|t| -(expr.clone() * t));
+                sum(lhs, rhs).map(|t| t / b.rhs.clone())
+            }
+            NoxprNode::Atan2(b) => {
+                // d atan2(y, x) = (x dy - y dx) / (x^2 + y^2)
+                let (y, x) = (&b.lhs, &b.rhs);
+                let dy = self.visit(y)?.map(|t| x.clone() * t);
+                let dx = self.visit(x)?.map(|t| -(y.clone() * t));
+                let norm = x.clone() * x.clone() + y.clone() * y.clone();
+                sum(dy, dx).map(|t| t / norm)
+            }
+            NoxprNode::Dot(b) => {
+                let lhs = self.visit(&b.lhs)?.map(|t| t.dot(&b.rhs));
+                let rhs = self.visit(&b.rhs)?.map(|t| b.lhs.clone().dot(&t));
+                sum(lhs, rhs)
+            }
+            NoxprNode::DotGeneral(d) => {
+                let lhs = self
+                    .visit(&d.lhs)?
+                    .map(|t| t.dot_general(d.rhs.clone(), d.dimensions.clone()));
+                let rhs = self
+                    .visit(&d.rhs)?
+                    .map(|t| d.lhs.clone().dot_general(t, d.dimensions.clone()));
+                sum(lhs, rhs)
+            }
+
+            NoxprNode::Sqrt(a) => self.visit(a)?.map(|t| t / (expr.clone() + expr.clone())),
+            NoxprNode::Neg(a) => self.visit(a)?.map(|t| -t),
+            NoxprNode::Log(a) => self.visit(a)?.map(|t| t / a.clone()),
+            NoxprNode::Exp(a) => self.visit(a)?.map(|t| t * expr.clone()),
+            NoxprNode::Sin(a) => self.visit(a)?.map(|t| t * a.clone().cos()),
+            NoxprNode::Cos(a) => self.visit(a)?.map(|t| -(t * a.clone().sin())),
+            NoxprNode::Abs(a) => match self.visit(a)? {
+                Some(t) => {
+                    let negative = a.clone().less(scalar_like(a, 0.0)?);
+                    Some(negative.select(-t.clone(), t))
+                }
+                None => None,
+            },
+            NoxprNode::Asin(a) => match self.visit(a)? {
+                Some(t) => Some(t / (scalar_like(a, 1.0)? - a.clone() * a.clone()).sqrt()),
+                None => None,
+            },
+            NoxprNode::Acos(a) => match self.visit(a)? {
+                Some(t) => Some(-(t / (scalar_like(a, 1.0)? - a.clone() * a.clone()).sqrt())),
+                None => None,
+            },
+
+            NoxprNode::Concat(c) => {
+                let tangents = self.visit_all(&c.nodes)?;
+                or_zeros(&c.nodes, tangents)?.map(|nodes| {
+                    Noxpr::new(NoxprNode::Concat(Concat {
+                        nodes,
+                        dimension: c.dimension,
+                    }))
+                })
+            }
+            NoxprNode::Reshape(r) => self.visit(&r.expr)?.map(|t| t.reshape(r.new_sizes.clone())),
+            NoxprNode::Broadcast(b) => self.visit(&b.expr)?.map(|t| t.broadcast(b.sizes.clone())),
+            NoxprNode::BroadcastInDim(b) => self
+                .visit(&b.expr)?
+                .map(|t| t.broadcast_in_dim(b.sizes.clone(), b.broadcast_dims.clone())),
+            NoxprNode::Transpose(t) => self
+                .visit(&t.expr)?
+                .map(|tangent| tangent.transpose(t.permutation.clone())),
+
+            NoxprNode::Gather(g) => self.visit(&g.expr)?.map(|t| {
+                Noxpr::new(NoxprNode::Gather(Gather {
+                    expr: t,
+                    indices: g.indices.clone(),
+                    offset_dims: g.offset_dims.clone(),
+                    collapsed_slice_dims: g.collapsed_slice_dims.clone(),
+                    start_index_map: g.start_index_map.clone(),
+                    slice_sizes: g.slice_sizes.clone(),
+                    index_vector_dim: g.index_vector_dim,
+                }))
+            }),
+            NoxprNode::Slice(s) => self.visit(&s.expr)?.map(|t| {
+                Noxpr::new(NoxprNode::Slice(Slice {
+                    expr: t,
+                    start_indices: s.start_indices.clone(),
+                    stop_indices: s.stop_indices.clone(),
+                    strides: s.strides.clone(),
+                }))
+            }),
+            NoxprNode::DynamicSlice(d) => self
+                .visit(&d.expr)?
+                .map(|t| t.dynamic_slice(d.start_indices.clone(), d.size_indices.clone())),
+            NoxprNode::DynamicUpdateSlice(d) => {
+                let nodes = [d.expr.clone(), d.update.clone()];
+                let tangents = self.visit_all(&nodes)?;
+                or_zeros(&nodes, tangents)?.map(|tangents| {
+                    let [expr, update] = <[Noxpr; 2]>::try_from(tangents)
+                        .expect("dynamic update slice has two operands");
+                    expr.dynamic_update_slice(d.start_indices.clone(), update)
+                })
+            }
+
+            NoxprNode::Select(s) => {
+                let nodes = [s.on_true.clone(), s.on_false.clone()];
+                let tangents = self.visit_all(&nodes)?;
+                or_zeros(&nodes, tangents)?.map(|tangents| {
+                    let [on_true, on_false] =
+                        <[Noxpr; 2]>::try_from(tangents).expect("select has two branches");
+                    Noxpr::new(NoxprNode::Select(Select {
+                        cond: s.cond.clone(),
+                        on_true,
+                        on_false,
+                    }))
+                })
+            }
+            NoxprNode::Convert(c) => match c.ty {
+                ElementType::F32 | ElementType::F64 => self.visit(&c.arg)?.map(|t| t.convert(c.ty)),
+                _ => None,
+            },
+            NoxprNode::LuInverse(lu) => {
+                // d(A^-1) = -A^-1 dA A^-1
+                self.visit(&lu.arg)?
+                    .map(|t| -(expr.clone().dot(&t).dot(expr)))
+            }
+
+            NoxprNode::Scan(s) => {
+                let mut args = s.inputs.clone();
+                args.push(s.initial_state.clone());
+                self.unsupported(expr, &args)?
+            }
+            NoxprNode::While(w) => {
+                self.unsupported(expr, std::slice::from_ref(&w.initial_state))?
+            }
+            NoxprNode::Call(c) => self.unsupported(expr, &c.args)?,
+            NoxprNode::Cholesky(c) => self.unsupported(expr, std::slice::from_ref(&c.arg))?,
+        };
+        // binary ops broadcast their operands, so a tangent carried over from one side may need
+        // to be broadcast up to the shape of the result
+        let tangent = match (tangent, expr.shape()) {
+            (Some(t), Some(shape)) if t.shape().is_some_and(|s| s != shape) => {
+                Some(t.broadcast_to(shape))
+            }
+            (tangent, _) => tangent,
+        };
+        self.cache.insert(expr.id(), tangent.clone());
+        Ok(tangent)
+    }
+
+    fn visit_all(&mut self, exprs: &[Noxpr]) -> Result<Vec<Option<Noxpr>>, Error> {
+        exprs.iter().map(|expr| self.visit(expr)).collect()
+    }
+
+    /// Fails if a tangent reaches one of `args`, the operands of a node that can't be
+    /// differentiated through.
+    fn unsupported(&mut self, expr: &Noxpr, args: &[Noxpr]) -> Result<Option<Noxpr>, Error> {
+        if self.visit_all(args)?.iter().any(Option::is_some) {
+            return Err(Error::UnsupportedJvp(expr.name()));
+        }
+        Ok(None)
+    }
+}
+
+/// Fills in zeros for the missing tangents of `exprs`, unless every one of them is missing.
+fn or_zeros(exprs: &[Noxpr], tangents: Vec<Option<Noxpr>>) -> Result<Option<Vec<Noxpr>>, Error> {
+    if tangents.iter().all(Option::is_none) {
+        return Ok(None);
+    }
+    exprs
+        .iter()
+        .zip(tangents)
+        .map(|(expr, tangent)| tangent.map_or_else(|| zeros_like(expr), Ok))
+        .collect::<Result<Vec<_>, Error>>()
+        .map(Some)
+}
+
+fn sum(lhs: Option<Noxpr>, rhs: Option<Noxpr>) -> Option<Noxpr> {
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => Some(lhs + rhs),
+        (lhs, rhs) => lhs.or(rhs),
+    }
+}
+
+/// A scalar constant of `expr`'s element type.
+fn scalar_like(expr: &Noxpr, value: f64) -> Result<Noxpr, Error> {
+    match expr.element_type().ok_or(Error::IncompatibleDType)? {
+        ElementType::F64 => Ok(value.constant()),
+        ty => Ok(value.constant().convert(ty)),
+    }
+}
+
+fn zeros_like(expr: &Noxpr) -> Result<Noxpr, Error> {
+    zeros(&expr.ty().ok_or(Error::IncompatibleDType)?)
+}
+
+fn zeros(ty: &NoxprTy) -> Result<Noxpr, Error> {
+    match ty {
+        NoxprTy::Tuple(tys) => Ok(Noxpr::tuple(
+            tys.iter().map(zeros).collect::<Result<_, Error>>()?,
+        )),
+        NoxprTy::ArrayTy(ty) => {
+            let zero = match ty.element_type {
+                ElementType::F64 => 0.0f64.constant(),
+                element_type => 0.0f64.constant().convert(element_type),
+            };
+            Ok(zero.broadcast(ty.shape.clone()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use smallvec::smallvec;
+
+    use super::*;
+    use crate::{ekf::forward_jacobian, tensor, ArrayTy, Client, CompFn, Matrix, Op, Vector};
+
+    #[test]
+    fn test_jvp() {
+        // f(x) = [x0 x1, sin(x0) / x1, sqrt(|x1|)]
+        fn df(x: Vector<f64, 2, Op>) -> Matrix<f64, 3, 2, Op> {
+            let f = |x: Vector<f64, 2, Op>| {
+                let [x0, x1] = x.parts();
+                Vector::from_scalars([&x0 * &x1, x0.sin() / &x1, x1.abs().sqrt()])
+            };
+            forward_jacobian(f, &x).unwrap()
+        }
+
+        let client = Client::cpu().unwrap();
+        let exec = df.build().unwrap().compile(&client).unwrap();
+        let (x0, x1) = (0.5f64, -2.0f64);
+        let out = exec.run(&client, tensor![x0, x1]).unwrap().to_host();
+        let expected = tensor![
+            [x1, x0],
+            [x0.cos() / x1, -x0.sin() / (x1 * x1)],
+            [0.0, -0.5 / x1.abs().sqrt()]
+        ];
+        assert_relative_eq!(out, expected, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_jvp_unsupported() {
+        let scalar = NoxprTy::ArrayTy(ArrayTy::new(ElementType::F64, smallvec![]));
+        let x = Noxpr::parameter(0, scalar.clone(), "x".into());
+        let other = Noxpr::parameter(1, scalar, "y".into());
+        let y = other.cholesky(false) + x.clone() * x.clone();
+        // the cholesky doesn't depend on x, so it's left alone
+        assert!(y.jvp(&x, 1.0.constant()).is_ok());
+        let err = y.jvp(&other, 1.0.constant());
+        assert!(matches!(err, Err(Error::UnsupportedJvp("Cholesky"))));
+    }
+}
@@ -3,7 +3,9 @@ mod builder;
 mod client;
 mod comp;
 mod comp_fn;
+mod dot;
 mod exec;
+mod jvp;
 mod node;
 mod repr;
 mod scalar;
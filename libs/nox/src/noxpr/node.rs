@@ -2,6 +2,7 @@
 use std::{
     collections::HashMap,
     fmt::Display,
+    iter::once,
     ops::{Add, Deref, Div, Mul, Neg, Sub},
     sync::Arc,
 };
@@ -46,6 +47,7 @@ pub enum NoxprNode {
     Sqrt(Noxpr),
     Neg(Noxpr),
     Log(Noxpr),
+    Exp(Noxpr),
     Sin(Noxpr),
     Cos(Noxpr),
     Abs(Noxpr),
@@ -70,6 +72,7 @@ pub enum NoxprNode {
 
     // Control Flow
     Scan(Scan),
+    While(While),
     Select(Select),
 
     // Cast
@@ -91,6 +94,34 @@ pub struct Constant {
     pub data: xla::Literal, // NOTE: it might make more sense to use the xla independent store below
     // pub data: SmallVec<[u8; size_of::<f64>()]>,
     pub ty: ArrayTy,
+    /// Set for constants the user marked as tunable, see [`Noxpr::named_constant`].
+    pub name: Option<String>,
+}
+
+impl Constant {
+    /// Returns a short summary of the constant's values, eliding the middle of large constants.
+    pub fn summary(&self) -> String {
+        fn join<T: Display>(vals: &[T]) -> String {
+            if vals.len() <= 6 {
+                return vals.iter().join(", ");
+            }
+            format!(
+                "{}, ..., {}",
+                vals[..3].iter().join(", "),
+                vals[vals.len() - 3..].iter().join(", ")
+            )
+        }
+        let vals = match self.ty.element_type {
+            ElementType::F64 => self.data.typed_buf::<f64>().map(join),
+            ElementType::F32 => self.data.typed_buf::<f32>().map(join),
+            ElementType::S64 => self.data.typed_buf::<i64>().map(join),
+            ElementType::S32 => self.data.typed_buf::<i32>().map(join),
+            ElementType::U64 => self.data.typed_buf::<u64>().map(join),
+            ElementType::U32 => self.data.typed_buf::<u32>().map(join),
+            _ => return "..".to_string(),
+        };
+        vals.unwrap_or_else(|_| "?".to_string())
+    }
 }
 
 impl std::fmt::Debug for Constant {
@@ -465,6 +496,55 @@ pub struct Scan {
     pub scan_fn: NoxprFn,
 }
 
+/// Represents a while loop, applying `body` to the state for as long as `cond` holds.
+///
+/// Both functions take the state as their only parameter, numbered zero. [`Noxpr::while_loop`]
+/// builds the state as a flat tuple of arrays, so the loop can be batched one array at a time.
+#[derive(Debug, Clone)]
+pub struct While {
+    pub initial_state: Noxpr,
+    pub cond: NoxprFn,
+    pub body: NoxprFn,
+}
+
+fn tuple_leaves(ty: &NoxprTy, out: &mut Vec<NoxprTy>) {
+    match ty {
+        NoxprTy::Tuple(tys) => tys.iter().for_each(|ty| tuple_leaves(ty, out)),
+        NoxprTy::ArrayTy(_) => out.push(ty.clone()),
+    }
+}
+
+fn flatten_tuple(expr: Noxpr, ty: &NoxprTy, out: &mut Vec<Noxpr>) {
+    match ty {
+        NoxprTy::Tuple(tys) => {
+            for (i, ty) in tys.iter().enumerate() {
+                flatten_tuple(expr.get_tuple_element(i), ty, out);
+            }
+        }
+        NoxprTy::ArrayTy(_) => out.push(expr),
+    }
+}
+
+fn unflatten_tuple(leaves: &mut impl Iterator<Item = Noxpr>, ty: &NoxprTy) -> Noxpr {
+    match ty {
+        NoxprTy::Tuple(tys) => {
+            Noxpr::tuple(tys.iter().map(|ty| unflatten_tuple(leaves, ty)).collect())
+        }
+        NoxprTy::ArrayTy(_) => leaves.next().expect("a leaf for every array in the type"),
+    }
+}
+
+/// The outcome of [`Noxpr::while_loop`].
+#[derive(Debug, Clone)]
+pub struct WhileLoop {
+    /// The state the loop stopped at.
+    pub state: Noxpr,
+    /// The number of times the body ran, as an `S64` scalar.
+    pub iterations: Noxpr,
+    /// Whether the state converged, rather than the loop running out of iterations.
+    pub converged: Noxpr,
+}
+
 /// Represents a scan operation, a form of reduction across one dimension.
 #[derive(Debug, Clone)]
 pub struct Select {
@@ -572,6 +652,11 @@ impl Noxpr {
         Self::new(NoxprNode::Log(self))
     }
 
+    /// Creates an exponential transformation of the `Noxpr`.
+    pub fn exp(self) -> Self {
+        Self::new(NoxprNode::Exp(self))
+    }
+
     /// Creates a square root transformation of the `Noxpr`.
 
     pub fn sqrt(self) -> Self {
@@ -603,7 +688,24 @@ impl Noxpr {
 
     /// Creates a constant `Noxpr` from a given literal and type.
     pub fn constant(data: xla::Literal, ty: ArrayTy) -> Self {
-        Self::new(NoxprNode::Constant(Constant { data, ty }))
+        Self::new(NoxprNode::Constant(Constant {
+            data,
+            ty,
+            name: None,
+        }))
+    }
+
+    /// Creates a scalar `f64` constant named `name`, which [`NoxprFn::hoist_constants`] turns into a
+    /// runtime parameter so it can be retuned without tracing again.
+    pub fn named_constant(name: impl Into<String>, value: f64) -> Self {
+        Self::new(NoxprNode::Constant(Constant {
+            data: value.literal(),
+            ty: ArrayTy {
+                element_type: ElementType::F64,
+                shape: smallvec![],
+            },
+            name: Some(name.into()),
+        }))
     }
 
     /// Combines multiple `Noxpr` into a tuple.
@@ -756,6 +858,95 @@ impl Noxpr {
         }))
     }
 
+    /// Applies `body` to the state until `converged` holds for it, running the body at most
+    /// `max_iters` times.
+    ///
+    /// Like [`Noxpr::scan`]'s function, `converged` and `body` each take the state followed by
+    /// `inputs`, the values captured from outside the loop, which stay the same every iteration.
+    /// They may only close over constants, anything else has to be passed in as an input. The
+    /// check runs before every step, so a state that has already converged is returned untouched.
+    /// A loop that runs out of iterations, like a Newton solve that diverged, is reported through
+    /// [`WhileLoop::converged`] rather than as an error, as the trip count is only known
+    /// on-device.
+    pub fn while_loop(
+        inputs: Vec<Noxpr>,
+        initial_state: Noxpr,
+        converged: NoxprFn,
+        body: NoxprFn,
+        max_iters: i64,
+    ) -> Result<WhileLoop, Error> {
+        if converged.args.len() != inputs.len() + 1 || body.args.len() != inputs.len() + 1 {
+            return Err(Error::WhileWrongArgCount);
+        }
+        let state_ty = initial_state.ty().ok_or(Error::WhileUnknownState)?;
+        let input_tys = inputs
+            .iter()
+            .map(|input| input.ty().ok_or(Error::WhileUnknownState))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let scalar = || {
+            NoxprTy::ArrayTy(ArrayTy {
+                element_type: ElementType::S64,
+                shape: smallvec![],
+            })
+        };
+        // the loop carries (iterations, done, ..state, ..inputs), with done as an S64 flag since
+        // there's no logical not to flip `converged` with, and the state and inputs flattened so
+        // the loop can be batched one array at a time
+        let mut tys = vec![scalar(), scalar()];
+        tuple_leaves(&state_ty, &mut tys);
+        let state_end = tys.len();
+        input_tys.iter().for_each(|ty| tuple_leaves(ty, &mut tys));
+        let param = Noxpr::parameter(0, NoxprTy::Tuple(tys.clone()), "while_state".to_string());
+        let state = |tuple: &Noxpr| {
+            let mut leaves = (2..state_end).map(|i| tuple.get_tuple_element(i));
+            unflatten_tuple(&mut leaves, &state_ty)
+        };
+        let mut leaves = (state_end..tys.len()).map(|i| param.get_tuple_element(i));
+        let captured = input_tys
+            .iter()
+            .map(|ty| unflatten_tuple(&mut leaves, ty))
+            .collect::<Vec<_>>();
+        let done = |state: Noxpr, inputs: &[Noxpr]| {
+            let args = once(state)
+                .chain(inputs.iter().cloned())
+                .collect::<Vec<_>>();
+            converged.apply(&args).convert(ElementType::S64)
+        };
+
+        let iterations = param.get_tuple_element(0);
+        let cond = NoxprFn::new(
+            vec![param.clone()],
+            iterations
+                .clone()
+                .less(max_iters.constant())
+                .and(param.get_tuple_element(1).eq(0i64.constant())),
+        );
+        let args = once(state(&param))
+            .chain(captured.iter().cloned())
+            .collect::<Vec<_>>();
+        let next = body.apply(&args);
+        let mut tuple = vec![iterations + 1i64.constant(), done(next.clone(), &captured)];
+        flatten_tuple(next, &state_ty, &mut tuple);
+        tuple.extend((state_end..tys.len()).map(|i| param.get_tuple_element(i)));
+        let body = NoxprFn::new(vec![param], Noxpr::tuple(tuple));
+
+        let mut tuple = vec![0i64.constant(), done(initial_state.clone(), &inputs)];
+        flatten_tuple(initial_state, &state_ty, &mut tuple);
+        for (input, ty) in inputs.into_iter().zip(&input_tys) {
+            flatten_tuple(input, ty, &mut tuple);
+        }
+        let out = Noxpr::new(NoxprNode::While(While {
+            initial_state: Noxpr::tuple(tuple),
+            cond,
+            body,
+        }));
+        Ok(WhileLoop {
+            state: state(&out),
+            iterations: out.get_tuple_element(0),
+            converged: out.get_tuple_element(1).eq(1i64.constant()),
+        })
+    }
+
     /// Retrieves the type of the expression, which might be useful for type-checking or transformations.
     pub fn ty(&self) -> Option<NoxprTy> {
         match self.deref() {
@@ -887,7 +1078,7 @@ impl Noxpr {
                 let tys = t.iter().map(Noxpr::ty).collect::<Option<Vec<_>>>()?;
                 Some(NoxprTy::Tuple(tys))
             }
-            NoxprNode::Log(l) => l.ty(),
+            NoxprNode::Log(l) | NoxprNode::Exp(l) => l.ty(),
             NoxprNode::Broadcast(b) => {
                 let NoxprTy::ArrayTy(in_ty) = b.expr.ty()? else {
                     return None;
@@ -964,6 +1155,7 @@ impl Noxpr {
                 ty.get(g.index).cloned()
             }
             NoxprNode::Scan(s) => s.initial_state.ty(),
+            NoxprNode::While(w) => w.initial_state.ty(),
             #[cfg(feature = "jax")]
             NoxprNode::Jax(o) => pyo3::Python::with_gil(|py| {
                 let shape = o.getattr(py, "shape").ok()?.extract::<Vec<i64>>(py).ok()?;
@@ -1019,6 +1211,7 @@ impl Noxpr {
             NoxprNode::Sqrt(expr)
             | NoxprNode::Neg(expr)
             | NoxprNode::Log(expr)
+            | NoxprNode::Exp(expr)
             | NoxprNode::Sin(expr)
             | NoxprNode::Cos(expr)
             | NoxprNode::Abs(expr) => expr.element_type(),
@@ -1046,9 +1239,14 @@ impl Noxpr {
                     }
                     None
                 }
+                NoxprNode::While(w) => match w.initial_state.deref() {
+                    NoxprNode::Tuple(elems) => elems.get(g.index)?.element_type(),
+                    _ => None,
+                },
                 _ => None,
             },
             NoxprNode::Scan(s) => s.initial_state.element_type(),
+            NoxprNode::While(w) => w.initial_state.element_type(),
             #[cfg(feature = "jax")]
             NoxprNode::Jax(o) => pyo3::Python::with_gil(|py| {
                 let element_type = o
@@ -1148,7 +1346,7 @@ impl Noxpr {
             NoxprNode::DynamicSlice(dynamic_slice) => Some(dynamic_slice.size_indices.clone()),
             NoxprNode::Reshape(reshape) => Some(reshape.new_sizes.clone()),
             NoxprNode::Tuple(_) => None,
-            NoxprNode::Log(l) => l.shape(),
+            NoxprNode::Log(l) | NoxprNode::Exp(l) => l.shape(),
             NoxprNode::Broadcast(b) => {
                 let in_shape = b.expr.shape()?;
                 let mut out_shape = b.sizes.clone();
@@ -1194,6 +1392,7 @@ impl Noxpr {
             NoxprNode::DynamicUpdateSlice(d) => d.expr.shape(),
             NoxprNode::GetTupleElement(g) => get_tuple_shape(g.index, &g.expr.node),
             NoxprNode::Scan(s) => s.initial_state.shape(),
+            NoxprNode::While(w) => w.initial_state.shape(),
             #[cfg(feature = "jax")]
             NoxprNode::Jax(o) => pyo3::Python::with_gil(|py| {
                 use pyo3::prelude::PyAnyMethods;
@@ -1282,6 +1481,7 @@ impl Noxpr {
             NoxprNode::Sqrt(_) => "Sqrt",
             NoxprNode::Neg(_) => "Neg",
             NoxprNode::Log(_) => "Log",
+            NoxprNode::Exp(_) => "Exp",
             NoxprNode::Concat(_) => "Concat",
             NoxprNode::Reshape(_) => "Reshape",
             NoxprNode::Broadcast(_) => "Broadcast",
@@ -1292,6 +1492,7 @@ impl Noxpr {
             NoxprNode::DynamicSlice(_) => "DynamicSlice",
             NoxprNode::DynamicUpdateSlice(_) => "DynamicUpdateSlice",
             NoxprNode::Scan(_) => "Scan",
+            NoxprNode::While(_) => "While",
             #[cfg(feature = "jax")]
             NoxprNode::Jax(_) => "Jax",
             NoxprNode::Sin(_) => "Sin",
@@ -1307,6 +1508,58 @@ impl Noxpr {
         }
     }
 
+    /// Returns the expressions this node directly depends on.
+    pub fn operands(&self) -> Vec<&Noxpr> {
+        match self.deref() {
+            NoxprNode::Param(_) | NoxprNode::Constant(_) | NoxprNode::Iota(_) => vec![],
+            #[cfg(feature = "jax")]
+            NoxprNode::Jax(_) => vec![],
+            NoxprNode::Tuple(t) => t.iter().collect(),
+            NoxprNode::GetTupleElement(g) => vec![&g.expr],
+            NoxprNode::Add(b)
+            | NoxprNode::Sub(b)
+            | NoxprNode::Mul(b)
+            | NoxprNode::Div(b)
+            | NoxprNode::And(b)
+            | NoxprNode::Or(b)
+            | NoxprNode::GreaterOrEqual(b)
+            | NoxprNode::LessOrEqual(b)
+            | NoxprNode::Less(b)
+            | NoxprNode::Equal(b)
+            | NoxprNode::Atan2(b)
+            | NoxprNode::Dot(b) => vec![&b.lhs, &b.rhs],
+            NoxprNode::DotGeneral(d) => vec![&d.lhs, &d.rhs],
+            NoxprNode::Sqrt(e)
+            | NoxprNode::Neg(e)
+            | NoxprNode::Log(e)
+            | NoxprNode::Exp(e)
+            | NoxprNode::Sin(e)
+            | NoxprNode::Cos(e)
+            | NoxprNode::Abs(e)
+            | NoxprNode::Acos(e)
+            | NoxprNode::Asin(e) => vec![e],
+            NoxprNode::Concat(c) => c.nodes.iter().collect(),
+            NoxprNode::Reshape(r) => vec![&r.expr],
+            NoxprNode::Broadcast(b) => vec![&b.expr],
+            NoxprNode::BroadcastInDim(b) => vec![&b.expr],
+            NoxprNode::Transpose(t) => vec![&t.expr],
+            NoxprNode::Gather(g) => vec![&g.expr, &g.indices],
+            NoxprNode::Slice(s) => vec![&s.expr],
+            NoxprNode::DynamicSlice(d) => once(&d.expr).chain(&d.start_indices).collect(),
+            NoxprNode::DynamicUpdateSlice(d) => [&d.expr, &d.update]
+                .into_iter()
+                .chain(&d.start_indices)
+                .collect(),
+            NoxprNode::Scan(s) => s.inputs.iter().chain(once(&s.initial_state)).collect(),
+            NoxprNode::While(w) => vec![&w.initial_state],
+            NoxprNode::Select(s) => vec![&s.cond, &s.on_true, &s.on_false],
+            NoxprNode::Convert(c) => vec![&c.arg],
+            NoxprNode::Call(c) => c.args.iter().collect(),
+            NoxprNode::Cholesky(c) => vec![&c.arg],
+            NoxprNode::LuInverse(l) => vec![&l.arg],
+        }
+    }
+
     /// Expands the rank of an expression to a higher dimensionality, typically used in broadcasting scenarios.
     pub fn expand_rank(self, rank: usize) -> Option<Noxpr> {
         let in_shape = self.shape()?;
@@ -1381,6 +1634,7 @@ fn get_tuple_shape(index: usize, expr: &NoxprNode) -> Option<SmallVec<[i64; 4]>>
             }),
             _ => None,
         },
+        NoxprNode::While(w) => get_tuple_shape(index, w.initial_state.deref()),
         _ => None,
     }
 }
@@ -1515,6 +1769,10 @@ impl XlaTracer {
                 let expr = self.visit(expr)?;
                 expr.log()
             }
+            NoxprNode::Exp(expr) => {
+                let expr = self.visit(expr)?;
+                expr.exp()
+            }
             NoxprNode::Neg(expr) => {
                 let expr = self.visit(expr)?;
                 expr.neg()
@@ -1713,6 +1971,15 @@ impl XlaTracer {
                 let out = cond.stmt_while(&scan_fn, &initial_state);
                 out.get_tuple_element(last_elem as i64)
             }
+            NoxprNode::While(w) => {
+                if w.cond.args.len() != 1 || w.body.args.len() != 1 {
+                    return Err(Error::WhileWrongArgCount);
+                }
+                let cond = w.cond.build("while_cond")?.build()?;
+                let body = w.body.build("while_body")?.build()?;
+                let initial_state = self.visit(&w.initial_state)?;
+                cond.stmt_while(&body, &initial_state)
+            }
             NoxprNode::Convert(c) => {
                 let arg = self.visit(&c.arg)?;
                 arg.convert_element_type(c.ty.primitive_type())
@@ -1777,6 +2044,17 @@ impl NoxprFn {
         Self { args, inner }
     }
 
+    /// Inlines the function, substituting `args` for its parameters in order.
+    pub fn apply(&self, args: &[Noxpr]) -> Noxpr {
+        let cache = self
+            .args
+            .iter()
+            .zip(args)
+            .map(|(param, arg)| (param.id(), arg.clone()))
+            .collect();
+        ReplacementTracer { cache }.visit(&self.inner)
+    }
+
     /// Builds an XLA operation based on the `NoxprFn` definition.
     pub fn build(&self, name: &str) -> Result<XlaOp, Error> {
         let mut tracer = XlaTracer::new(name);
@@ -1819,6 +2097,52 @@ impl NoxprFn {
         Ok(final_fn)
     }
 
+    /// Replaces every constant created with [`Noxpr::named_constant`] with a parameter, numbered from
+    /// `first_param`, and returns the names and values of the hoisted constants in parameter order.
+    ///
+    /// Constants sharing a name share a parameter, and must have the same value. Functions that only
+    /// differ in the values of their named constants hoist to the same function, so they can share one
+    /// compiled executable and have those values passed in at runtime. Unnamed constants, and named ones
+    /// inside nested computations like a scan body, are left in place.
+    pub fn hoist_constants(&self, first_param: i64) -> Result<(Self, Vec<(String, f64)>), Error> {
+        let mut values: Vec<(String, f64)> = vec![];
+        let mut params: HashMap<String, (Noxpr, f64)> = HashMap::new();
+        let mut cache = HashMap::new();
+        let mut visited = std::collections::HashSet::new();
+        let mut stack: Vec<&Noxpr> = self.args.iter().chain(once(&self.inner)).rev().collect();
+        while let Some(expr) = stack.pop() {
+            if !visited.insert(expr.id()) {
+                continue;
+            }
+            if let NoxprNode::Constant(c) = expr.deref() {
+                let Some(name) = &c.name else {
+                    continue;
+                };
+                let value = c.data.typed_buf::<f64>()?[0];
+                let (param, hoisted) = params.entry(name.clone()).or_insert_with(|| {
+                    let param = Noxpr::parameter(
+                        first_param + values.len() as i64,
+                        NoxprTy::ArrayTy(c.ty.clone()),
+                        name.clone(),
+                    );
+                    values.push((name.clone(), value));
+                    (param, value)
+                });
+                if hoisted.to_bits() != value.to_bits() {
+                    return Err(Error::ConflictingParam(name.clone()));
+                }
+                cache.insert(expr.id(), param.clone());
+                continue;
+            }
+            stack.extend(expr.operands().into_iter().rev());
+        }
+        if values.is_empty() {
+            return Ok((self.clone(), values));
+        }
+        let mut tracer = ReplacementTracer { cache };
+        Ok((tracer.visit_fn(self), values))
+    }
+
     /// Pretty prints the function's structure into a formatted string.
     pub fn pretty_print(
         &self,
@@ -1903,6 +2227,7 @@ impl ReplacementTracer {
             NoxprNode::Sqrt(s) => Noxpr::new(NoxprNode::Sqrt(self.visit(s))),
             NoxprNode::Neg(n) => Noxpr::new(NoxprNode::Neg(self.visit(n))),
             NoxprNode::Log(l) => Noxpr::new(NoxprNode::Log(self.visit(l))),
+            NoxprNode::Exp(e) => Noxpr::new(NoxprNode::Exp(self.visit(e))),
             NoxprNode::Sin(s) => Noxpr::new(NoxprNode::Sin(self.visit(s))),
             NoxprNode::Cos(c) => Noxpr::new(NoxprNode::Cos(self.visit(c))),
             NoxprNode::Abs(a) => Noxpr::new(NoxprNode::Abs(self.visit(a))),
@@ -1963,6 +2288,11 @@ impl ReplacementTracer {
                 initial_state: self.visit(&s.initial_state),
                 scan_fn: s.scan_fn.clone(),
             })),
+            NoxprNode::While(w) => Noxpr::new(NoxprNode::While(While {
+                initial_state: self.visit(&w.initial_state),
+                cond: w.cond.clone(),
+                body: w.body.clone(),
+            })),
             #[cfg(feature = "jax")]
             NoxprNode::Jax(j) => Noxpr::new(NoxprNode::Jax(j.clone())),
             NoxprNode::Convert(c) => {
@@ -2090,7 +2420,7 @@ impl PrettyPrintTracer {
                 let num = self.print_var(id, writer)?;
                 write!(writer, "constant(")?;
                 c.ty.pretty_print(writer)?;
-                write!(writer, ")")?;
+                write!(writer, ", [{}])", c.summary())?;
                 Ok(num)
             }
             NoxprNode::Iota(i) => {
@@ -2161,6 +2491,12 @@ impl PrettyPrintTracer {
                 write!(writer, "log(var_{})", arg)?;
                 Ok(num)
             }
+            NoxprNode::Exp(e) => {
+                let arg = self.visit(e, writer)?;
+                let num = self.print_var(id, writer)?;
+                write!(writer, "exp(var_{})", arg)?;
+                Ok(num)
+            }
             NoxprNode::Sin(s) => {
                 let arg = self.visit(s, writer)?;
                 let num = self.print_var(id, writer)?;
@@ -2298,13 +2634,28 @@ impl PrettyPrintTracer {
                 write!(writer, ")")?;
                 Ok(num)
             }
+            NoxprNode::While(w) => {
+                let init = self.visit(&w.initial_state, writer)?;
+                let num = self.print_var(id, writer)?;
+                write!(writer, "while(init = var_{}, cond = ", init)?;
+                w.cond.pretty_print(self, writer)?;
+                write!(writer, ", body = ")?;
+                w.body.pretty_print(self, writer)?;
+                write!(writer, ")")?;
+                Ok(num)
+            }
             #[cfg(feature = "jax")]
             NoxprNode::Jax(j) => {
                 let num = self.print_var(id, writer)?;
                 write!(writer, "jax({:?})", j)?;
                 Ok(num)
             }
-            NoxprNode::Convert(c) => self.visit(&c.arg, writer),
+            NoxprNode::Convert(c) => {
+                let arg = self.visit(&c.arg, writer)?;
+                let num = self.print_var(id, writer)?;
+                write!(writer, "convert(var_{}, {:?})", arg, c.ty)?;
+                Ok(num)
+            }
             NoxprNode::Select(s) => {
                 let cond = self.visit(&s.cond, writer)?;
                 let on_true = self.visit(&s.on_true, writer)?;
@@ -2312,13 +2663,23 @@ impl PrettyPrintTracer {
                 let num = self.print_var(id, writer)?;
                 write!(
                     writer,
-                    "select(cond = var_{}, on_true = var_{}, on_false = var_{}",
+                    "select(cond = var_{}, on_true = var_{}, on_false = var_{})",
                     cond, on_true, on_false
                 )?;
                 Ok(num)
             }
-            NoxprNode::Call(_) => {
+            NoxprNode::Call(c) => {
+                let args = c
+                    .args
+                    .iter()
+                    .map(|e| self.visit(e, writer))
+                    .collect::<Result<Vec<_>, _>>()?;
                 let num = self.print_var(id, writer)?;
+                write!(writer, "call(")?;
+                for arg in args {
+                    write!(writer, "var_{}, ", arg)?;
+                }
+                write!(writer, ")")?;
                 Ok(num)
             }
             NoxprNode::Cholesky(c) => {
@@ -2351,7 +2712,14 @@ impl PrettyPrintTracer {
         let lhs = self.visit(&op.lhs, writer)?;
         let rhs = self.visit(&op.rhs, writer)?;
         let num = self.print_var(id, writer)?;
-        write!(writer, "{} {} {}", lhs, op_label, rhs)?;
+        write!(writer, "var_{} {} var_{}", lhs, op_label, rhs)?;
+        // scalars are broadcast on purpose all the time, mismatched non-scalar shapes are more often a mistake
+        let (lhs_shape, rhs_shape) = (op.lhs.shape(), op.rhs.shape());
+        if let (Some(lhs_shape), Some(rhs_shape)) = (lhs_shape, rhs_shape) {
+            if lhs_shape != rhs_shape && !lhs_shape.is_empty() && !rhs_shape.is_empty() {
+                write!(writer, " /* broadcast */")?;
+            }
+        }
         Ok(num)
     }
 }
@@ -2616,4 +2984,96 @@ mod tests {
             .to_host();
         assert_eq!(out, tensor![3.0, 8.0, 13.0])
     }
+
+    #[test]
+    fn test_hoist_constants() {
+        use super::*;
+
+        let scalar = |ty| ArrayTy::new(ty, smallvec![]);
+        let x = Noxpr::parameter(0, NoxprTy::ArrayTy(scalar(ElementType::F64)), "x".into());
+        let gain = |k: f64, offset: f64| {
+            let gain = Noxpr::named_constant("gain", k);
+            let body = x.clone() * gain.clone() + Noxpr::named_constant("offset", offset) + gain;
+            NoxprFn::new(vec![], body * 2.0.constant())
+        };
+
+        let (hoisted, values) = gain(2.0, 3.0).hoist_constants(1).unwrap();
+        assert_eq!(values, vec![("gain".into(), 2.0), ("offset".into(), 3.0)]);
+        // equal values stay separate parameters, so the structure doesn't depend on them
+        let (same, same_values) = gain(2.0, 2.0).hoist_constants(1).unwrap();
+        assert_eq!(
+            same_values,
+            vec![("gain".into(), 2.0), ("offset".into(), 2.0)]
+        );
+        assert_eq!(hoisted.to_string(), same.to_string());
+        // unnamed constants stay in place
+        assert!(hoisted.to_string().contains("constant("));
+        hoisted.build("gain").unwrap().build().unwrap();
+
+        let conflicting = NoxprFn::new(
+            vec![],
+            Noxpr::named_constant("gain", 1.0) + Noxpr::named_constant("gain", 2.0),
+        );
+        assert!(matches!(
+            conflicting.hoist_constants(1),
+            Err(Error::ConflictingParam(name)) if name == "gain"
+        ));
+    }
+
+    #[test]
+    fn test_kepler_while_loop() {
+        use super::*;
+
+        // solves Kepler's equation, E - e sin E = M, with the state carrying E and M captured as
+        // an input
+        fn solve(m: Scalar<f64>, max_iters: i64) -> Vector<f64, 3> {
+            let params = || {
+                let scalar = NoxprTy::ArrayTy(ArrayTy::new(ElementType::F64, smallvec![]));
+                vec![
+                    Noxpr::parameter(0, scalar.clone(), "e".into()),
+                    Noxpr::parameter(1, scalar, "m".into()),
+                ]
+            };
+            let residual = |args: &[Noxpr]| {
+                let e = args[0].clone();
+                e.clone() - 0.3.constant() * e.sin() - args[1].clone()
+            };
+            let args = params();
+            let tolerance = residual(&args).abs().less(1e-12.constant());
+            let converged = NoxprFn::new(args, tolerance);
+            let args = params();
+            let e = args[0].clone();
+            let step = residual(&args) / (1.0.constant() - 0.3.constant() * e.clone().cos());
+            let body = NoxprFn::new(args, e - step);
+
+            let inputs = vec![m.inner.clone()];
+            let out = Noxpr::while_loop(inputs, m.inner, converged, body, max_iters).unwrap();
+            let parts = [
+                out.state,
+                out.iterations.convert(ElementType::F64),
+                out.converged.convert(ElementType::F64),
+            ];
+            let parts = parts.into_iter().map(|p| p.reshape(smallvec![1])).collect();
+            Vector::from_inner(Noxpr::concat_in_dim(parts, 0))
+        }
+        fn solve_converged(m: Scalar<f64>) -> Vector<f64, 3> {
+            solve(m, 50)
+        }
+        fn solve_stalled(m: Scalar<f64>) -> Vector<f64, 3> {
+            solve(m, 2)
+        }
+
+        let client = Client::cpu().unwrap();
+        let m = 1.2;
+        let exec = solve_converged.build().unwrap().compile(&client).unwrap();
+        let out = exec.run(&client, Scalar::from(m)).unwrap().to_host();
+        let [e, iterations, converged] = out.into_buf();
+        assert!((e - 0.3 * e.sin() - m).abs() < 1e-12);
+        assert!(iterations > 0.0 && iterations < 50.0);
+        assert_eq!(converged, 1.0);
+
+        let exec = solve_stalled.build().unwrap().compile(&client).unwrap();
+        let out = exec.run(&client, Scalar::from(m)).unwrap().to_host();
+        assert_eq!(&out.into_buf()[1..], &[2.0, 0.0]);
+    }
 }
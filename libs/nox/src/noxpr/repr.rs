@@ -2,9 +2,9 @@ use core::ops::{Add, Div, Mul, Sub};
 
 use crate::array::dims::*;
 use crate::{
-    AddDim, ArrayTy, BroadcastDim, BroadcastedDim, ConstDim, DefaultMap, DefaultMappedDim, Dim,
-    DotDim, Elem, Error, Field, Noxpr, NoxprFn, NoxprTy, OwnedRepr, RealField, ReplaceDim, Repr,
-    ShapeConstraint,
+    AddDim, ArrayTy, BroadcastDim, BroadcastedDim, CastLossyFrom, ConstDim, DefaultMap,
+    DefaultMappedDim, Dim, DotDim, Elem, Error, Field, Noxpr, NoxprFn, NoxprTy, OwnedRepr,
+    RealField, ReplaceDim, Repr, ShapeConstraint,
 };
 
 use smallvec::{smallvec, SmallVec};
@@ -241,6 +241,14 @@ impl OwnedRepr for Op {
         arg.clone().sqrt()
     }
 
+    fn exp<T1: Field + RealField, D1: Dim>(arg: &Self::Inner<T1, D1>) -> Self::Inner<T1, D1> {
+        arg.clone().exp()
+    }
+
+    fn log<T1: Field + RealField, D1: Dim>(arg: &Self::Inner<T1, D1>) -> Self::Inner<T1, D1> {
+        arg.clone().log()
+    }
+
     fn atan2<T1: Field + RealField, D1: Dim>(
         left: &Self::Inner<T1, D1>,
         right: &Self::Inner<T1, D1>,
@@ -260,6 +268,36 @@ impl OwnedRepr for Op {
         arg.clone().abs()
     }
 
+    fn max<T1: Field + RealField, D1: Dim>(
+        left: &Self::Inner<T1, D1>,
+        right: &Self::Inner<T1, D1>,
+    ) -> Self::Inner<T1, D1> {
+        left.clone()
+            .less(right.clone())
+            .select(right.clone(), left.clone())
+    }
+
+    fn min<T1: Field + RealField, D1: Dim>(
+        left: &Self::Inner<T1, D1>,
+        right: &Self::Inner<T1, D1>,
+    ) -> Self::Inner<T1, D1> {
+        right
+            .clone()
+            .less(left.clone())
+            .select(right.clone(), left.clone())
+    }
+
+    fn select_less<T1: Field + RealField + PartialOrd, D1: Dim>(
+        left: &Self::Inner<T1, D1>,
+        right: &Self::Inner<T1, D1>,
+        on_true: &Self::Inner<T1, D1>,
+        on_false: &Self::Inner<T1, D1>,
+    ) -> Self::Inner<T1, D1> {
+        left.clone()
+            .less(right.clone())
+            .select(on_true.clone(), on_false.clone())
+    }
+
     fn copy_fixed_slice<T1: Field, D1: Dim, D2: Dim + ConstDim>(
         arg: &Self::Inner<T1, D1>,
         offsets: &[usize],
@@ -386,6 +424,15 @@ impl OwnedRepr for Op {
         arg.clone()
     }
 
+    fn cast<T1: Field, T2: CastLossyFrom<T1>, D1: Dim>(
+        arg: &Self::Inner<T1, D1>,
+    ) -> Self::Inner<T2, D1> {
+        if T1::ELEMENT_TY == T2::ELEMENT_TY {
+            return arg.clone();
+        }
+        arg.clone().convert(T2::ELEMENT_TY)
+    }
+
     fn try_cholesky<T1: RealField, D1: Dim + SquareDim>(
         arg: &Self::Inner<T1, D1>,
     ) -> Result<Self::Inner<T1, D1>, Error> {
@@ -146,8 +146,10 @@ impl<T: Field, D: Dim> Tensor<T, D, Op> {
     }
 }
 
-impl<T: TensorItem, D: Dim> Tensor<T, D, Op> {
-    pub fn log(&self) -> Self {
-        Self::from_inner(self.inner.clone().log())
+impl Scalar<f64, Op> {
+    /// A tunable scalar, passed in at runtime under `name` rather than baked into the executable,
+    /// see [`Noxpr::named_constant`].
+    pub fn named(name: impl Into<String>, value: f64) -> Self {
+        Self::from_inner(Noxpr::named_constant(name, value))
     }
 }
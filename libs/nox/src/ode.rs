@@ -0,0 +1,595 @@
+//! Adaptive Dormand–Prince 5(4) integration with dense output.
+use alloc::vec::Vec;
+
+#[cfg(feature = "noxpr")]
+use crate::{
+    ekf::forward_jacobian, xla::ElementType, ArrayTy, Noxpr, NoxprFn, NoxprScalarExt, NoxprTy, Op,
+};
+use crate::{
+    ekf::jacobian, ArrayRepr, Const, Error, JacobianSparsity, Matrix, OwnedRepr, Scalar, Vector,
+};
+#[cfg(feature = "noxpr")]
+use smallvec::smallvec;
+
+const C: [f64; 6] = [1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+
+const A: [&[f64]; 6] = [
+    &[1.0 / 5.0],
+    &[3.0 / 40.0, 9.0 / 40.0],
+    &[44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0],
+    &[
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+    ],
+    &[
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+    ],
+    // the last stage is evaluated at the fifth-order solution (FSAL)
+    &[
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ],
+];
+
+/// Difference between the fifth and fourth order weights.
+const E: [f64; 7] = [
+    71.0 / 57600.0,
+    0.0,
+    -71.0 / 16695.0,
+    71.0 / 1920.0,
+    -17253.0 / 339200.0,
+    22.0 / 525.0,
+    -1.0 / 40.0,
+];
+
+/// Dense output weights, from Hairer, Nørsett & Wanner.
+const D: [f64; 7] = [
+    -12715105075.0 / 11282082432.0,
+    0.0,
+    87487479700.0 / 32700410799.0,
+    -10690763975.0 / 1880347072.0,
+    701980252875.0 / 199316789632.0,
+    -1453857185.0 / 822651844.0,
+    69997945.0 / 29380423.0,
+];
+
+/// An adaptive Dormand–Prince 5(4) solver for non-stiff problems such as orbit propagation.
+///
+/// [`Dopri5::solve`] runs on the host, accepting and rejecting steps until it reaches the end
+/// time and recording dense output for every accepted step. [`Dopri5::integrate`] is the traced
+/// form: it unrolls exactly `max_steps` attempts, masking out rejected steps and the attempts left
+/// over once the end time is reached, so it can be compiled into a system.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dopri5 {
+    pub rtol: f64,
+    pub atol: f64,
+    pub h_min: f64,
+    pub h_max: f64,
+    pub max_steps: usize,
+}
+
+impl Default for Dopri5 {
+    fn default() -> Self {
+        Self {
+            rtol: 1e-9,
+            atol: 1e-9,
+            h_min: 1e-9,
+            h_max: f64::INFINITY,
+            max_steps: 32,
+        }
+    }
+}
+
+struct Step<const N: usize, R: OwnedRepr> {
+    k: Vec<Vector<f64, N, R>>,
+    y1: Vector<f64, N, R>,
+    err: Scalar<f64, R>,
+}
+
+impl Dopri5 {
+    /// The Runge–Kutta matrix, one row for each stage after the first. The last row holds the
+    /// fifth-order weights, since the last stage is evaluated at the solution.
+    pub const TABLEAU: [&'static [f64]; 6] = A;
+
+    /// The fifth-order weights minus the embedded fourth-order ones, for all seven stages.
+    pub const ERROR_WEIGHTS: [f64; 7] = E;
+
+    /// The factor to scale a step with the scaled error `err` by, `0.9 / err^(1/5)` limited to
+    /// `[0.2, 10]`.
+    pub fn step_factor<R: OwnedRepr>(err: &Scalar<f64, R>) -> Scalar<f64, R> {
+        step_factor(err)
+    }
+
+    pub fn rtol(mut self, rtol: f64) -> Self {
+        self.rtol = rtol;
+        self
+    }
+
+    pub fn atol(mut self, atol: f64) -> Self {
+        self.atol = atol;
+        self
+    }
+
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Takes a single step of size `h`, returning the stages, the fifth-order solution and the
+    /// RMS of the error estimate scaled by the tolerances.
+    fn step<const N: usize, R: OwnedRepr>(
+        &self,
+        f: &impl Fn(&Scalar<f64, R>, &Vector<f64, N, R>) -> Vector<f64, N, R>,
+        t: &Scalar<f64, R>,
+        y: &Vector<f64, N, R>,
+        h: &Scalar<f64, R>,
+    ) -> Step<N, R> {
+        let mut k = Vec::with_capacity(7);
+        k.push(f(t, y));
+        let mut y1 = y.clone();
+        for (c, row) in C.iter().zip(A) {
+            let slope = weighted_sum(row, &k);
+            y1 = y + h * &slope;
+            let ti = t + *c * h;
+            k.push(f(&ti, &y1));
+        }
+        let err = h * &weighted_sum(&E, &k);
+        let scale = Scalar::<f64, R>::from(self.atol) + self.rtol * y.abs().max(&y1.abs());
+        let ratio = err / scale;
+        let err = ((1.0 / N as f64) * ratio.dot(&ratio)).sqrt();
+        Step { k, y1, err }
+    }
+
+    /// Integrates `dy/dt = f(t, y)` from `t0` to `t1` on the host, starting with a step of `h0`.
+    ///
+    /// Only forward integration is supported, so `t1` must not be before `t0`.
+    pub fn solve<const N: usize>(
+        &self,
+        f: impl Fn(&Scalar<f64, ArrayRepr>, &Vector<f64, N, ArrayRepr>) -> Vector<f64, N, ArrayRepr>,
+        t0: f64,
+        y0: Vector<f64, N, ArrayRepr>,
+        t1: f64,
+        h0: f64,
+    ) -> Result<Solution<N>, Error> {
+        let mut t = t0;
+        let mut y = y0;
+        let mut h = h0.clamp(self.h_min, self.h_max);
+        let mut steps = Vec::new();
+        let mut rejected = 0;
+        let mut attempts = 0;
+        while t < t1 {
+            if attempts == self.max_steps {
+                return Err(Error::StepLimitExceeded(self.max_steps));
+            }
+            attempts += 1;
+            let clipped = t1 - t < h;
+            let h_try = if clipped { t1 - t } else { h };
+            let step = self.step(&f, &t.into(), &y, &h_try.into());
+            let err = step.err.into_buf();
+            let h_new = h_try * step_factor::<ArrayRepr>(&step.err).into_buf();
+            if err <= 1.0 {
+                let dense = DenseStep::new(t, h_try, &y, &step);
+                y = step.y1;
+                t = if clipped { t1 } else { t + h_try };
+                steps.push(dense);
+                h = if clipped { h.max(h_new) } else { h_new };
+            } else {
+                if h_try <= self.h_min {
+                    return Err(Error::StepSizeUnderflow);
+                }
+                rejected += 1;
+                h = h_new;
+            }
+            h = h.clamp(self.h_min, self.h_max);
+        }
+        Ok(Solution {
+            t,
+            y,
+            h,
+            steps,
+            rejected,
+        })
+    }
+
+    /// Integrates `dy/dt = f(t, y)` from `t0` towards `t1` with exactly `max_steps` attempts,
+    /// returning the time reached, the state there, and the step size to start the next call with.
+    ///
+    /// Every attempt is traced, with a select keeping or dropping each step, so the reached time
+    /// falls short of `t1` if the attempts run out; compare it against `t1` to detect that.
+    pub fn integrate<const N: usize, R: OwnedRepr>(
+        &self,
+        f: impl Fn(&Scalar<f64, R>, &Vector<f64, N, R>) -> Vector<f64, N, R>,
+        t0: Scalar<f64, R>,
+        y0: Vector<f64, N, R>,
+        t1: Scalar<f64, R>,
+        h0: Scalar<f64, R>,
+    ) -> (Scalar<f64, R>, Vector<f64, N, R>, Scalar<f64, R>) {
+        let zero = Scalar::<f64, R>::from(0.0);
+        let one = Scalar::<f64, R>::from(1.0);
+        let h_min = Scalar::<f64, R>::from(self.h_min);
+        let h_max = Scalar::<f64, R>::from(self.h_max);
+        let mut t = t0;
+        let mut y = y0;
+        let mut h = h0.clamp(&h_min, &h_max);
+        for _ in 0..self.max_steps {
+            let remaining = &t1 - &t;
+            let h_try = h.min(&remaining).max(&zero);
+            let step = self.step(&f, &t, &y, &h_try);
+            let h_new = &h_try * step_factor(&step.err);
+            // like `solve`, a step clipped to the end time doesn't shrink the next one
+            let h_accepted = remaining.select_less(&h, &h_new.max(&h), &h_new);
+            // the step is rejected when `1 < err`
+            t = one.select_less(&step.err, &t, &(&t + &h_try));
+            y = one.broadcast::<Const<N>>().select_less(
+                &step.err.broadcast::<Const<N>>(),
+                &y,
+                &step.y1,
+            );
+            h = one
+                .select_less(&step.err, &h_new, &h_accepted)
+                .clamp(&h_min, &h_max);
+        }
+        (t, y, h)
+    }
+}
+
+/// The factor to scale a step by given its error, `0.9 / err^(1/5)` limited to `[0.2, 10]`.
+fn step_factor<R: OwnedRepr>(err: &Scalar<f64, R>) -> Scalar<f64, R> {
+    let err = err.max(&1e-10.into());
+    let fac = 0.9 * (-0.2 * err.log()).exp();
+    fac.clamp(&0.2.into(), &10.0.into())
+}
+
+fn weighted_sum<const N: usize, R: OwnedRepr>(
+    weights: &[f64],
+    k: &[Vector<f64, N, R>],
+) -> Vector<f64, N, R> {
+    weights
+        .iter()
+        .zip(k)
+        .filter(|(w, _)| **w != 0.0)
+        .map(|(w, k)| *w * k)
+        .reduce(|acc, term| acc + term)
+        .expect("every stage has a non-zero weight")
+}
+
+/// The continuous extension of one accepted step.
+#[derive(Clone, Debug)]
+pub struct DenseStep<const N: usize> {
+    pub t: f64,
+    pub h: f64,
+    rcont: [Vector<f64, N, ArrayRepr>; 5],
+}
+
+impl<const N: usize> DenseStep<N> {
+    fn new(t: f64, h: f64, y0: &Vector<f64, N, ArrayRepr>, step: &Step<N, ArrayRepr>) -> Self {
+        let k = &step.k;
+        let ydiff = &step.y1 - y0;
+        let bspl = h * &k[0] - &ydiff;
+        let rcont4 = &ydiff - h * &k[6] - &bspl;
+        let rcont5 = h * weighted_sum(&D, k);
+        Self {
+            t,
+            h,
+            rcont: [y0.clone(), ydiff, bspl, rcont4, rcont5],
+        }
+    }
+
+    /// Evaluates the interpolant at `t`, which should lie within the step.
+    pub fn eval(&self, t: f64) -> Vector<f64, N, ArrayRepr> {
+        let theta = (t - self.t) / self.h;
+        let theta1 = 1.0 - theta;
+        let [r1, r2, r3, r4, r5] = &self.rcont;
+        r1 + theta * (r2 + theta1 * (r3 + theta * (r4 + theta1 * r5)))
+    }
+}
+
+/// The result of [`Dopri5::solve`].
+#[derive(Clone, Debug)]
+pub struct Solution<const N: usize> {
+    pub t: f64,
+    pub y: Vector<f64, N, ArrayRepr>,
+    /// The step size to continue the integration with.
+    pub h: f64,
+    pub steps: Vec<DenseStep<N>>,
+    pub rejected: usize,
+}
+
+impl<const N: usize> Solution<N> {
+    /// Interpolates the solution at `t`, or returns `None` outside the integrated interval.
+    pub fn sample(&self, t: f64) -> Option<Vector<f64, N, ArrayRepr>> {
+        let first = self.steps.first()?;
+        if t < first.t || t > self.t {
+            return None;
+        }
+        let i = self.steps.partition_point(|step| step.t <= t);
+        Some(self.steps[i.saturating_sub(1)].eval(t))
+    }
+}
+
+/// Backward Euler for stiff problems, which stays stable at step sizes far beyond the fastest time
+/// constant where explicit methods blow up.
+///
+/// Each step solves `y1 = y0 + h f(t + h, y1)` with Newton iterations, starting from an explicit
+/// Euler guess. [`BackwardEuler::step`] runs a fixed number of them with a central difference
+/// jacobian of `f` with step `eps`, so it also works on the host. The traced
+/// [`BackwardEuler::step_until_converged`] instead stops once the norm of the residual is smaller
+/// than `tol`, and differentiates `f` exactly with forward-mode autodiff, only through the
+/// entries in `sparsity` if it's set, which keeps the jacobian of a large, loosely coupled system
+/// down to a few passes through `f`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackwardEuler {
+    pub newton_iters: usize,
+    pub eps: f64,
+    pub tol: f64,
+    pub sparsity: Option<JacobianSparsity>,
+}
+
+impl Default for BackwardEuler {
+    fn default() -> Self {
+        Self {
+            newton_iters: 4,
+            eps: 1e-7,
+            tol: 1e-9,
+            sparsity: None,
+        }
+    }
+}
+
+impl BackwardEuler {
+    pub fn newton_iters(mut self, newton_iters: usize) -> Self {
+        self.newton_iters = newton_iters;
+        self
+    }
+
+    pub fn tol(mut self, tol: f64) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    pub fn sparsity(mut self, sparsity: JacobianSparsity) -> Self {
+        self.sparsity = Some(sparsity);
+        self
+    }
+
+    /// Advances `y` at time `t` by `h`.
+    pub fn step<const N: usize, R: OwnedRepr>(
+        &self,
+        f: impl Fn(&Scalar<f64, R>, &Vector<f64, N, R>) -> Vector<f64, N, R>,
+        t: &Scalar<f64, R>,
+        y: &Vector<f64, N, R>,
+        h: &Scalar<f64, R>,
+    ) -> Result<Vector<f64, N, R>, Error> {
+        let t1 = t + h;
+        let mut z = y + h * &f(&t1, y);
+        for _ in 0..self.newton_iters {
+            let residual = &z - y - h * &f(&t1, &z);
+            let jac = jacobian(|x| f(&t1, &x), &z, self.eps);
+            let lhs = Matrix::<f64, N, N, R>::eye() - h * &jac;
+            z = &z - lhs.try_inverse()?.dot(&residual);
+        }
+        Ok(z)
+    }
+}
+
+/// The outcome of [`BackwardEuler::step_until_converged`].
+#[cfg(feature = "noxpr")]
+pub struct NewtonStep<const N: usize> {
+    pub y: Vector<f64, N, Op>,
+    /// The number of Newton iterations run.
+    pub iterations: Scalar<i64, Op>,
+    /// A boolean scalar, false if the residual was still above `tol` after `newton_iters`
+    /// iterations.
+    pub converged: Noxpr,
+}
+
+#[cfg(feature = "noxpr")]
+impl BackwardEuler {
+    /// Like [`BackwardEuler::step`], but runs the Newton iterations in a loop on the device that
+    /// stops as soon as the norm of the residual `y1 - y0 - h f(t + h, y1)` is below `tol`, or
+    /// after `newton_iters` iterations. A `tol` of zero always runs all of them.
+    ///
+    /// `f` is traced into the loop, so like the functions passed to [`Noxpr::while_loop`] it may
+    /// only close over constants; `t`, `y`, and `h` are passed into the loop as inputs.
+    pub fn step_until_converged<const N: usize>(
+        &self,
+        f: impl Fn(&Scalar<f64, Op>, &Vector<f64, N, Op>) -> Vector<f64, N, Op>,
+        t: &Scalar<f64, Op>,
+        y: &Vector<f64, N, Op>,
+        h: &Scalar<f64, Op>,
+    ) -> Result<NewtonStep<N>, Error> {
+        // the loop carries y1, with y0, t + h, and h as inputs
+        let params = || {
+            let vector = NoxprTy::ArrayTy(ArrayTy::new(ElementType::F64, smallvec![N as i64]));
+            let scalar = NoxprTy::ArrayTy(ArrayTy::new(ElementType::F64, smallvec![]));
+            vec![
+                Noxpr::parameter(0, vector.clone(), "y1".into()),
+                Noxpr::parameter(1, vector, "y0".into()),
+                Noxpr::parameter(2, scalar.clone(), "t1".into()),
+                Noxpr::parameter(3, scalar, "h".into()),
+            ]
+        };
+        let unpack = |params: &[Noxpr]| {
+            (
+                Vector::<f64, N, Op>::from_inner(params[0].clone()),
+                Vector::<f64, N, Op>::from_inner(params[1].clone()),
+                Scalar::<f64, Op>::from_inner(params[2].clone()),
+                Scalar::<f64, Op>::from_inner(params[3].clone()),
+            )
+        };
+
+        let args = params();
+        let (z, y0, t1, h1) = unpack(&args);
+        let residual = &z - &y0 - &h1 * &f(&t1, &z);
+        let converged = residual.norm().inner.less(self.tol.constant());
+        let converged = NoxprFn::new(args, converged);
+
+        let args = params();
+        let (z, y0, t1, h1) = unpack(&args);
+        let residual = &z - &y0 - &h1 * &f(&t1, &z);
+        let jac = match &self.sparsity {
+            Some(sparsity) => sparsity.jacobian(|x| f(&t1, &x), &z)?,
+            None => forward_jacobian(|x| f(&t1, &x), &z)?,
+        };
+        let lhs = Matrix::<f64, N, N, Op>::eye() - &h1 * &jac;
+        let z = &z - lhs.try_inverse()?.dot(&residual);
+        let body = NoxprFn::new(args, z.inner);
+
+        let t1 = t + h;
+        let z = y + h * &f(&t1, y);
+        let inputs = vec![y.inner.clone(), t1.inner, h.inner.clone()];
+        let out = Noxpr::while_loop(inputs, z.inner, converged, body, self.newton_iters as i64)?;
+        Ok(NewtonStep {
+            y: Vector::from_inner(out.state),
+            iterations: Scalar::from_inner(out.iterations),
+            converged: out.converged,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor;
+    use core::f64::consts::PI;
+
+    fn oscillator<R: OwnedRepr>(_: &Scalar<f64, R>, y: &Vector<f64, 2, R>) -> Vector<f64, 2, R> {
+        let [x, v] = y.parts();
+        Vector::from_scalars([v, -&x])
+    }
+
+    #[test]
+    fn test_dopri5_solve() {
+        let solver = Dopri5::default().rtol(1e-10).atol(1e-10).max_steps(1000);
+        let sol = solver
+            .solve(oscillator, 0.0, tensor![1.0, 0.0], 2.0 * PI, 0.1)
+            .unwrap();
+        assert_eq!(sol.t, 2.0 * PI);
+        let [x, v] = sol.y.into_buf();
+        approx::assert_relative_eq!(x, 1.0, epsilon = 1e-7);
+        approx::assert_relative_eq!(v, 0.0, epsilon = 1e-7);
+
+        let [x, v] = sol.sample(PI / 3.0).unwrap().into_buf();
+        approx::assert_relative_eq!(x, 0.5, epsilon = 1e-6);
+        approx::assert_relative_eq!(v, -(PI / 3.0).sin(), epsilon = 1e-6);
+        assert!(sol.sample(7.0).is_none());
+
+        let err = Dopri5::default()
+            .max_steps(3)
+            .solve(oscillator, 0.0, tensor![1.0, 0.0], 100.0, 0.1)
+            .unwrap_err();
+        assert!(matches!(err, Error::StepLimitExceeded(3)));
+    }
+
+    #[test]
+    fn test_dopri5_integrate() {
+        let solver = Dopri5::default().rtol(1e-8).atol(1e-8).max_steps(200);
+        let (t, y, h) = solver.integrate::<2, ArrayRepr>(
+            oscillator,
+            0.0.into(),
+            tensor![1.0, 0.0],
+            PI.into(),
+            0.1.into(),
+        );
+        approx::assert_relative_eq!(t.into_buf(), PI);
+        let [x, v] = y.into_buf();
+        approx::assert_relative_eq!(x, -1.0, epsilon = 1e-6);
+        approx::assert_relative_eq!(v, 0.0, epsilon = 1e-6);
+        assert!(h.into_buf() > 0.0);
+
+        // too few attempts stop short of the end time instead of failing
+        let (t, _, _) = Dopri5::default().max_steps(2).integrate::<2, ArrayRepr>(
+            oscillator,
+            0.0.into(),
+            tensor![1.0, 0.0],
+            PI.into(),
+            0.1.into(),
+        );
+        assert!(t.into_buf() < PI);
+    }
+
+    #[test]
+    fn test_backward_euler() {
+        // y' = -1e4 y is unstable for explicit Euler at this step size
+        let decay = |_: &Scalar<f64, ArrayRepr>, y: &Vector<f64, 1, ArrayRepr>| -1e4 * y;
+        let solver = BackwardEuler::default();
+        let mut y = tensor![1.0];
+        for _ in 0..3 {
+            y = solver.step(decay, &0.0.into(), &y, &0.1.into()).unwrap();
+        }
+        let [y] = y.into_buf();
+        approx::assert_relative_eq!(y, 1001f64.powi(-3), max_relative = 1e-6);
+
+        // y' = -y^3 has the implicit step y1 + h y1^3 = y0; at y0 = 2, h = 1 that gives y1 = 1
+        let cubic = |_: &Scalar<f64, ArrayRepr>, y: &Vector<f64, 1, ArrayRepr>| {
+            let cube = y * y * y;
+            -1.0 * cube
+        };
+        let y = solver
+            .newton_iters(10)
+            .step(cubic, &0.0.into(), &tensor![2.0], &1.0.into())
+            .unwrap();
+        approx::assert_relative_eq!(y.into_buf()[0], 1.0, epsilon = 1e-9);
+    }
+
+    #[cfg(feature = "noxpr")]
+    #[test]
+    fn test_backward_euler_until_converged() {
+        use crate::{Client, CompFn};
+
+        fn step(y: Vector<f64, 1, Op>) -> Vector<f64, 2, Op> {
+            let cubic = |_: &Scalar<f64, Op>, y: &Vector<f64, 1, Op>| -1.0 * (y * y * y);
+            let solver = BackwardEuler::default().newton_iters(50).tol(1e-12);
+            let out = solver
+                .step_until_converged(cubic, &0.0.into(), &y, &1.0.into())
+                .unwrap();
+            let iterations = Scalar::from_inner(out.iterations.inner.convert(ElementType::F64));
+            let [y] = out.y.parts();
+            Vector::from_scalars([y, iterations])
+        }
+
+        let client = Client::cpu().unwrap();
+        let exec = step.build().unwrap().compile(&client).unwrap();
+        let out = exec.run(&client, tensor![2.0]).unwrap().to_host();
+        let [y, iterations] = out.into_buf();
+        approx::assert_relative_eq!(y, 1.0, epsilon = 1e-9);
+        // the explicit Euler guess is far off, but the loop stops well short of the limit
+        assert!(iterations > 1.0 && iterations < 50.0);
+    }
+
+    #[cfg(feature = "noxpr")]
+    #[test]
+    fn test_backward_euler_sparse() {
+        use crate::{Client, CompFn};
+
+        // two uncoupled cubic decays, so both columns are differenced in a single pair of calls
+        fn step(y: Vector<f64, 2, Op>) -> Vector<f64, 2, Op> {
+            let cubic = |_: &Scalar<f64, Op>, y: &Vector<f64, 2, Op>| -1.0 * (y * y * y);
+            let sparsity = JacobianSparsity::from_blocks(2, 1, [(0, 0), (1, 1)]).unwrap();
+            assert_eq!(sparsity.color_count(), 1);
+            let solver = BackwardEuler::default()
+                .newton_iters(50)
+                .tol(1e-12)
+                .sparsity(sparsity);
+            solver
+                .step_until_converged(cubic, &0.0.into(), &y, &1.0.into())
+                .unwrap()
+                .y
+        }
+
+        let client = Client::cpu().unwrap();
+        let exec = step.build().unwrap().compile(&client).unwrap();
+        let out = exec.run(&client, tensor![2.0, 0.0]).unwrap().to_host();
+        approx::assert_relative_eq!(out, tensor![1.0, 0.0], epsilon = 1e-9);
+    }
+}
@@ -0,0 +1,238 @@
+//! Provides Keplerian orbital elements, along with conversions to and from Cartesian state.
+//!
+//! The conversions are written entirely in terms of tensor operations, so they can be traced and differentiated
+//! like any other nox function. This lets guidance laws operate in element space while the dynamics integrate in Cartesian space.
+use crate::{
+    Const, DefaultRepr, Field, Matrix3, OwnedRepr, RealField, ReprMonad, Scalar, SpatialMotion,
+    SpatialTransform, Tensor, TensorItem, Vector,
+};
+
+/// Classical Keplerian orbital elements, stored as a 6D vector of
+/// `[semi-major axis, eccentricity, inclination, RAAN, argument of periapsis, true anomaly]`.
+///
+/// All angles are in radians. The elements are singular for circular (argument of periapsis) and
+/// equatorial (RAAN) orbits, in those cases the conversions remain finite but the split between the angles is arbitrary.
+pub struct OrbitalElements<T: TensorItem, R: OwnedRepr = DefaultRepr> {
+    pub inner: Vector<T, 6, R>,
+}
+
+impl<T: Field, R: OwnedRepr> Clone for OrbitalElements<T, R>
+where
+    Vector<T, 6, R>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Field, R: OwnedRepr> core::fmt::Debug for OrbitalElements<T, R>
+where
+    R::Inner<T, Const<6>>: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("OrbitalElements").field(&self.inner).finish()
+    }
+}
+
+impl<T: TensorItem + RealField, R: OwnedRepr> ReprMonad<R> for OrbitalElements<T, R> {
+    type Elem = T;
+    type Dim = Const<6>;
+    type Map<N: OwnedRepr> = OrbitalElements<T, N>;
+
+    fn map<N: OwnedRepr>(
+        self,
+        func: impl Fn(R::Inner<Self::Elem, Self::Dim>) -> N::Inner<Self::Elem, Self::Dim>,
+    ) -> Self::Map<N> {
+        OrbitalElements {
+            inner: Tensor::from_inner(func(self.inner.inner)),
+        }
+    }
+
+    fn into_inner(self) -> R::Inner<Self::Elem, Self::Dim> {
+        self.inner.inner
+    }
+
+    fn inner(&self) -> &R::Inner<Self::Elem, Self::Dim> {
+        &self.inner.inner
+    }
+
+    fn from_inner(inner: R::Inner<Self::Elem, Self::Dim>) -> Self {
+        OrbitalElements {
+            inner: Tensor::from_inner(inner),
+        }
+    }
+}
+
+impl<T: TensorItem + RealField, R: OwnedRepr> OrbitalElements<T, R> {
+    /// Constructs a new set of orbital elements from the individual elements.
+    pub fn new(
+        semi_major_axis: impl Into<Scalar<T, R>>,
+        eccentricity: impl Into<Scalar<T, R>>,
+        inclination: impl Into<Scalar<T, R>>,
+        raan: impl Into<Scalar<T, R>>,
+        arg_of_periapsis: impl Into<Scalar<T, R>>,
+        true_anomaly: impl Into<Scalar<T, R>>,
+    ) -> Self {
+        let inner = Vector::from_arr([
+            semi_major_axis.into(),
+            eccentricity.into(),
+            inclination.into(),
+            raan.into(),
+            arg_of_periapsis.into(),
+            true_anomaly.into(),
+        ]);
+        OrbitalElements { inner }
+    }
+
+    /// Computes the orbital elements of a body with the given inertial position and velocity,
+    /// orbiting a central body with gravitational parameter `mu`.
+    pub fn from_cartesian(
+        pos: Vector<T, 3, R>,
+        vel: Vector<T, 3, R>,
+        mu: impl Into<Scalar<T, R>>,
+    ) -> Self {
+        let mu = mu.into();
+        let r = pos.norm();
+        let v_sq = vel.norm_squared();
+        let h = pos.cross(&vel);
+        let h_norm = h.norm();
+        let h_hat = &h / &h_norm;
+        let [hx, hy, hz] = h.parts();
+        // the node vector is z × h
+        let node = Vector::from_arr([-&hy, hx.clone(), T::zero()]);
+        let ecc_vec = ((&v_sq - &mu / &r) * &pos - pos.dot(&vel) * &vel) / &mu;
+
+        let e = ecc_vec.norm();
+        let energy = &v_sq / T::two::<R>() - &mu / &r;
+        let a = -(&mu / (T::two::<R>() * energy));
+        let inclination = (hz / &h_norm).acos();
+        let raan = hx.atan2(&-hy);
+        // atan2 based forms are used for the remaining angles, as they are quadrant-correct without branching
+        let arg_of_periapsis = h_hat.dot(&node.cross(&ecc_vec)).atan2(&node.dot(&ecc_vec));
+        let true_anomaly = h_hat.dot(&ecc_vec.cross(&pos)).atan2(&ecc_vec.dot(&pos));
+        Self::new(a, e, inclination, raan, arg_of_periapsis, true_anomaly)
+    }
+
+    /// Computes the orbital elements from the linear parts of a spatial transform and spatial motion.
+    pub fn from_spatial(
+        pos: &SpatialTransform<T, R>,
+        vel: &SpatialMotion<T, R>,
+        mu: impl Into<Scalar<T, R>>,
+    ) -> Self {
+        Self::from_cartesian(pos.linear(), vel.linear(), mu)
+    }
+
+    /// Returns the semi-major axis.
+    pub fn semi_major_axis(&self) -> Scalar<T, R> {
+        self.inner.get(0)
+    }
+
+    /// Returns the eccentricity.
+    pub fn eccentricity(&self) -> Scalar<T, R> {
+        self.inner.get(1)
+    }
+
+    /// Returns the inclination in radians.
+    pub fn inclination(&self) -> Scalar<T, R> {
+        self.inner.get(2)
+    }
+
+    /// Returns the right ascension of the ascending node in radians.
+    pub fn raan(&self) -> Scalar<T, R> {
+        self.inner.get(3)
+    }
+
+    /// Returns the argument of periapsis in radians.
+    pub fn arg_of_periapsis(&self) -> Scalar<T, R> {
+        self.inner.get(4)
+    }
+
+    /// Returns the true anomaly in radians.
+    pub fn true_anomaly(&self) -> Scalar<T, R> {
+        self.inner.get(5)
+    }
+
+    /// Computes the inertial position and velocity described by these elements, for a central body with gravitational parameter `mu`.
+    pub fn to_cartesian(&self, mu: impl Into<Scalar<T, R>>) -> (Vector<T, 3, R>, Vector<T, 3, R>) {
+        let mu = mu.into();
+        let [a, e, inclination, raan, arg_of_periapsis, true_anomaly] = self.inner.parts();
+        let semi_latus_rectum = &a * (T::one::<R>() - &e * &e);
+        let sin_nu = true_anomaly.sin();
+        let cos_nu = true_anomaly.cos();
+        let r = &semi_latus_rectum / (T::one::<R>() + &e * &cos_nu);
+        let pos_pqw = Vector::from_arr([&r * &cos_nu, &r * &sin_nu, T::zero()]);
+        let vel_scale = (&mu / &semi_latus_rectum).sqrt();
+        let vel_pqw =
+            Vector::from_arr([-(&vel_scale * &sin_nu), vel_scale * (e + cos_nu), T::zero()]);
+        let rot = perifocal_to_inertial(&raan, &inclination, &arg_of_periapsis);
+        (rot.dot(&pos_pqw), rot.dot(&vel_pqw))
+    }
+
+    /// Computes the spatial transform and spatial motion described by these elements.
+    /// The angular parts of both are left as identity and zero respectively.
+    pub fn to_spatial(
+        &self,
+        mu: impl Into<Scalar<T, R>>,
+    ) -> (SpatialTransform<T, R>, SpatialMotion<T, R>) {
+        let (pos, vel) = self.to_cartesian(mu);
+        (
+            SpatialTransform::from_linear(pos),
+            SpatialMotion::from_linear(vel),
+        )
+    }
+}
+
+/// Computes the rotation from the perifocal (PQW) frame to the inertial frame, i.e `R3(-raan) R1(-i) R3(-argp)`.
+fn perifocal_to_inertial<T: RealField, R: OwnedRepr>(
+    raan: &Scalar<T, R>,
+    inclination: &Scalar<T, R>,
+    arg_of_periapsis: &Scalar<T, R>,
+) -> Matrix3<T, R> {
+    let (cr, sr) = (raan.cos(), raan.sin());
+    let (ci, si) = (inclination.cos(), inclination.sin());
+    let (cw, sw) = (arg_of_periapsis.cos(), arg_of_periapsis.sin());
+    Matrix3::from_rows([
+        Vector::from_arr([
+            &cr * &cw - &sr * &sw * &ci,
+            -(&cr * &sw) - &sr * &cw * &ci,
+            &sr * &si,
+        ]),
+        Vector::from_arr([
+            &sr * &cw + &cr * &sw * &ci,
+            -(&sr * &sw) + &cr * &cw * &ci,
+            -(&cr * &si),
+        ]),
+        Vector::from_arr([&sw * &si, &cw * &si, ci]),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::ArrayRepr;
+
+    const MU_EARTH: f64 = 3.986004418e14;
+
+    #[test]
+    fn test_elements_round_trip() {
+        let elements = OrbitalElements::<f64, ArrayRepr>::new(7000e3, 0.1, 0.5, 1.0, 0.7, 2.0);
+        let (pos, vel) = elements.to_cartesian(MU_EARTH);
+        let out = OrbitalElements::from_cartesian(pos, vel, MU_EARTH);
+        assert_relative_eq!(out.inner, elements.inner, max_relative = 1e-9);
+    }
+
+    #[test]
+    fn test_periapsis_state() {
+        let elements = OrbitalElements::<f64, ArrayRepr>::new(7000e3, 0.1, 0.5, 1.0, 0.7, 0.0);
+        let (pos, vel) = elements.to_cartesian(MU_EARTH);
+        let r_p = 7000e3 * (1.0 - 0.1);
+        let v_p = (MU_EARTH / 7000e3 * (1.0 + 0.1) / (1.0 - 0.1)).sqrt();
+        assert_relative_eq!(pos.norm().into_buf(), r_p, max_relative = 1e-12);
+        assert_relative_eq!(vel.norm().into_buf(), v_p, max_relative = 1e-12);
+        assert_relative_eq!(pos.dot(&vel).into_buf(), 0.0, epsilon = 1e-3);
+    }
+}
@@ -81,6 +81,53 @@ where
     }
 }
 
+/// The rotation sequence used when converting between quaternions and euler angles.
+///
+/// Sequences are intrinsic, so [`EulerOrder::ZYX`] rotates about z, then the new y axis, then the new x axis,
+/// which is the common aerospace yaw-pitch-roll convention.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EulerOrder {
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+    XYX,
+    XZX,
+    YXY,
+    YZY,
+    ZXZ,
+    ZYZ,
+}
+
+impl EulerOrder {
+    /// Returns the axes of the sequence as indices, where x, y, and z are 0, 1, and 2.
+    pub const fn axes(self) -> [usize; 3] {
+        match self {
+            EulerOrder::XYZ => [0, 1, 2],
+            EulerOrder::XZY => [0, 2, 1],
+            EulerOrder::YXZ => [1, 0, 2],
+            EulerOrder::YZX => [1, 2, 0],
+            EulerOrder::ZXY => [2, 0, 1],
+            EulerOrder::ZYX => [2, 1, 0],
+            EulerOrder::XYX => [0, 1, 0],
+            EulerOrder::XZX => [0, 2, 0],
+            EulerOrder::YXY => [1, 0, 1],
+            EulerOrder::YZY => [1, 2, 1],
+            EulerOrder::ZXZ => [2, 0, 2],
+            EulerOrder::ZYZ => [2, 1, 2],
+        }
+    }
+
+    /// Returns true for proper euler sequences, where the first and last axis are the same.
+    pub const fn is_proper(self) -> bool {
+        let [i, _, k] = self.axes();
+        i == k
+    }
+}
+
 impl<T: RealField, R: OwnedRepr> Quaternion<T, R> {
     /// Constructs a new quaternion from individual scalar components.
     pub fn new(
@@ -97,26 +144,72 @@ impl<T: RealField, R: OwnedRepr> Quaternion<T, R> {
         Quaternion(inner)
     }
 
-    // Constructs a new quaternion from euler angles
-    pub fn from_euler(
-        angles: Vector<T, 3, R>,
-        // roll: impl Into<Scalar<T, R>>,
-        // pitch: impl Into<Scalar<T, R>>,
-        // yaw: impl Into<Scalar<T, R>>,
+    /// Constructs a unit quaternion from components that may not have unit norm, normalizing them.
+    pub fn new_normalize(
+        w: impl Into<Scalar<T, R>>,
+        x: impl Into<Scalar<T, R>>,
+        y: impl Into<Scalar<T, R>>,
+        z: impl Into<Scalar<T, R>>,
     ) -> Self {
-        let [roll, pitch, yaw] = angles.parts();
-        let cr = &(&roll / T::two()).cos();
-        let sr = &(&roll / T::two()).sin();
-        let cp = &(&pitch / T::two()).cos();
-        let sp = &(&pitch / T::two()).sin();
-        let cy = &(&yaw / T::two()).cos();
-        let sy = &(&yaw / T::two()).sin();
-        let w = cr * cp * cy + sr * sp * sy;
-        let x = sr * cp * cy - cr * sp * sy;
-        let y = cr * sp * cy + sr * cp * sy;
-        let z = cr * cp * sy - sr * sp * cy;
-        let inner = Vector::from_arr([x, y, z, w]);
-        Quaternion(inner)
+        Self::new(w, x, y, z).normalize()
+    }
+
+    /// Constructs a unit quaternion from an `[x, y, z, w]` vector, normalizing it.
+    pub fn from_vector_normalize(v: Vector<T, 4, R>) -> Self {
+        Quaternion(v).normalize()
+    }
+
+    /// Constructs a new quaternion from euler angles, given in the same order as the rotation sequence.
+    pub fn from_euler(order: EulerOrder, angles: Vector<T, 3, R>) -> Self {
+        let [a, b, c] = angles.parts();
+        let [i, j, k] = order.axes();
+        Self::from_elemental(i, a) * Self::from_elemental(j, b) * Self::from_elemental(k, c)
+    }
+
+    /// Creates a quaternion representing a rotation about a single coordinate axis.
+    fn from_elemental(axis: usize, angle: Scalar<T, R>) -> Self {
+        let half_angle = angle / T::two::<R>();
+        let mut parts = [T::zero::<R>(), T::zero(), T::zero(), half_angle.cos()];
+        parts[axis] = half_angle.sin();
+        Quaternion(Vector::from_arr(parts))
+    }
+
+    /// Converts the quaternion to euler angles for the given rotation sequence.
+    ///
+    /// The first and last angles are in `[-pi, pi]`. The middle angle is in `[-pi/2, pi/2]` for Tait-Bryan sequences
+    /// and `[0, pi]` for proper euler sequences. At gimbal lock the split between the first and last angle is arbitrary,
+    /// but the angles still describe the same rotation.
+    pub fn to_euler(&self, order: EulerOrder) -> Vector<T, 3, R> {
+        // Implements the direct method from Bernardes & Viollet (2022), "Quaternion to Euler angles conversion:
+        // A direct, general and computationally efficient method". It is formulated for extrinsic sequences, so the
+        // sequence is reversed here and the outer angles are swapped back at the end.
+        let [k, j, i] = order.axes();
+        let proper = order.is_proper();
+        let k = if proper { 3 - i - j } else { k };
+        let even = (i + 1) % 3 == j;
+        let q = self.parts();
+        let w = &q[3];
+        let d = if even { q[k].clone() } else { -&q[k] };
+        let (a, b, c, d) = if proper {
+            (w.clone(), q[i].clone(), q[j].clone(), d)
+        } else {
+            (w - &q[j], &q[i] + &d, &q[j] + w, d - &q[i])
+        };
+        let cd_sq = &c * &c + &d * &d;
+        let ab_sq = &a * &a + &b * &b;
+        let middle = if proper {
+            T::two::<R>() * cd_sq.sqrt().atan2(&ab_sq.sqrt())
+        } else {
+            // equivalent to `2 atan2(|cd|, |ab|) - pi / 2`, written without needing a pi constant
+            (&cd_sq - &ab_sq).atan2(&(T::two::<R>() * (cd_sq * ab_sq).sqrt()))
+        };
+        let half_sum = b.atan2(&a);
+        let half_diff = d.atan2(&c);
+        let first = &half_sum - &half_diff;
+        let third = half_sum + half_diff;
+        let third = if proper || even { third } else { -third };
+        let wrap = |angle: Scalar<T, R>| angle.sin().atan2(&angle.cos());
+        Vector::from_arr([wrap(third), middle, wrap(first)])
     }
 
     /// Creates a unit quaternion with no rotation.
@@ -144,6 +237,11 @@ impl<T: RealField, R: OwnedRepr> Quaternion<T, R> {
         Quaternion(&self.0 / self.0.norm())
     }
 
+    /// How far the quaternion's norm has drifted from one, `|‖q‖ - 1|`.
+    pub fn norm_error(&self) -> Scalar<T, R> {
+        (self.0.norm() - T::one::<R>()).abs()
+    }
+
     /// Computes the inverse of the quaternion.
     pub fn inverse(&self) -> Self {
         // TODO: Check for division by zero
@@ -188,6 +286,14 @@ impl<T: RealField, R: OwnedRepr> Quaternion<T, R> {
 }
 
 impl<T: RealField> Quaternion<T, ArrayRepr> {
+    /// Returns true if the norm is within `tolerance` of one.
+    pub fn is_unit(&self, tolerance: T) -> bool
+    where
+        T: PartialOrd,
+    {
+        self.norm_error().into_buf() <= tolerance
+    }
+
     pub fn from_rot_mat(mat: Matrix3<T, ArrayRepr>) -> Self {
         let m00 = mat.get([0, 0]).into_buf();
         let m01 = mat.get([0, 1]).into_buf();
@@ -405,4 +511,71 @@ mod tests {
             epsilon = 1e-8,
         );
     }
+
+    #[test]
+    fn test_euler_round_trip() {
+        let orders = [
+            EulerOrder::XYZ,
+            EulerOrder::XZY,
+            EulerOrder::YXZ,
+            EulerOrder::YZX,
+            EulerOrder::ZXY,
+            EulerOrder::ZYX,
+            EulerOrder::XYX,
+            EulerOrder::XZX,
+            EulerOrder::YXY,
+            EulerOrder::YZY,
+            EulerOrder::ZXZ,
+            EulerOrder::ZYZ,
+        ];
+        for order in orders {
+            let angles: Vector3<f64, ArrayRepr> = if order.is_proper() {
+                tensor![0.3, 1.2, -2.5]
+            } else {
+                tensor![0.3, -1.2, 2.5]
+            };
+            let q = Quaternion::from_euler(order, angles);
+            assert_relative_eq!(q.to_euler(order), angles, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_euler_yaw_pitch_roll() {
+        let (yaw, pitch, roll) = (0.4, -0.2, 1.1);
+        let q =
+            Quaternion::<f64, ArrayRepr>::from_euler(EulerOrder::ZYX, tensor![yaw, pitch, roll]);
+        let expected = Quaternion::from_axis_angle(Vector3::z_axis(), yaw)
+            * Quaternion::from_axis_angle(Vector3::y_axis(), pitch)
+            * Quaternion::from_axis_angle(Vector3::x_axis(), roll);
+        assert_relative_eq!(q.0, expected.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_unit_constructors() {
+        let q = Quaternion::<f64, ArrayRepr>::new_normalize(2.0, 0.0, 0.0, 2.0);
+        assert_relative_eq!(
+            q.0,
+            tensor![
+                0.0,
+                0.0,
+                std::f64::consts::FRAC_1_SQRT_2,
+                std::f64::consts::FRAC_1_SQRT_2
+            ],
+            epsilon = 1e-12
+        );
+        assert!(q.is_unit(1e-12));
+
+        let drifted = Quaternion::<f64, ArrayRepr>(tensor![0.0, 0.0, 0.0, 1.001]);
+        assert_relative_eq!(drifted.norm_error().into_buf(), 0.001, epsilon = 1e-12);
+        assert!(!drifted.is_unit(1e-6));
+        assert!(Quaternion::from_vector_normalize(drifted.0).is_unit(1e-12));
+    }
+
+    #[test]
+    fn test_euler_gimbal_lock() {
+        let angles: Vector3<f64, ArrayRepr> = tensor![0.5, std::f64::consts::FRAC_PI_2, 0.25];
+        let q = Quaternion::from_euler(EulerOrder::ZYX, angles);
+        let out = Quaternion::from_euler(EulerOrder::ZYX, q.to_euler(EulerOrder::ZYX));
+        assert_relative_eq!(q.0.dot(&out.0).abs().into_buf(), 1.0, epsilon = 1e-9);
+    }
 }
@@ -0,0 +1,115 @@
+//! Ray and line-of-sight queries against analytic shapes, for visibility checks between a
+//! satellite and a ground station, altimeters and lidar beams.
+//!
+//! These are written entirely in tensor operations, so they can run inside a compiled system. As
+//! tensors can't branch, they return signed quantities instead of options: a clearance that is
+//! negative when the shape is in the way, and a time of impact that only means something when the
+//! ray actually hits.
+use crate::{OwnedRepr, RealField, Scalar, Vector};
+
+/// Computes how far the segment from `a` to `b` passes outside a sphere, i.e the distance from
+/// the sphere's surface to the closest point on the segment.
+///
+/// The sphere blocks the line of sight between `a` and `b` exactly when this is negative.
+pub fn segment_sphere_clearance<T: RealField, R: OwnedRepr>(
+    a: &Vector<T, 3, R>,
+    b: &Vector<T, 3, R>,
+    center: &Vector<T, 3, R>,
+    radius: impl Into<Scalar<T, R>>,
+) -> Scalar<T, R> {
+    let ab = b - a;
+    let t = (ab.dot(&(center - a)) / ab.norm_squared()).clamp(&T::zero(), &T::one());
+    let closest = a + t * ab;
+    (center - closest).norm() - radius.into()
+}
+
+/// Computes the distance along the unit direction `dir` from `origin` to the surface of a sphere.
+///
+/// If the ray misses, this is the distance to the ray's closest approach instead, so check
+/// [`ray_sphere_clearance`] before using it.
+pub fn ray_sphere_toi<T: RealField, R: OwnedRepr>(
+    origin: &Vector<T, 3, R>,
+    dir: &Vector<T, 3, R>,
+    center: &Vector<T, 3, R>,
+    radius: impl Into<Scalar<T, R>>,
+) -> Scalar<T, R> {
+    let radius = radius.into();
+    let offset = origin - center;
+    let b = dir.dot(&offset);
+    let disc = &b * &b - offset.norm_squared() + &radius * &radius;
+    -b - disc.max(&T::zero()).sqrt()
+}
+
+/// Computes how far the ray from `origin` along the unit direction `dir` passes outside a sphere.
+///
+/// Negative when the ray hits the sphere, spheres behind the origin are never hit.
+pub fn ray_sphere_clearance<T: RealField, R: OwnedRepr>(
+    origin: &Vector<T, 3, R>,
+    dir: &Vector<T, 3, R>,
+    center: &Vector<T, 3, R>,
+    radius: impl Into<Scalar<T, R>>,
+) -> Scalar<T, R> {
+    let t = dir.dot(&(center - origin)).max(&T::zero());
+    let closest = origin + t * dir;
+    (center - closest).norm() - radius.into()
+}
+
+/// Computes the distance along `dir` from `origin` to the plane of points with
+/// `dot(normal, p) = offset`, negative if the plane is behind the ray.
+pub fn ray_plane_toi<T: RealField, R: OwnedRepr>(
+    origin: &Vector<T, 3, R>,
+    dir: &Vector<T, 3, R>,
+    normal: &Vector<T, 3, R>,
+    offset: impl Into<Scalar<T, R>>,
+) -> Scalar<T, R> {
+    (offset.into() - normal.dot(origin)) / normal.dot(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::ArrayRepr;
+
+    #[test]
+    fn test_line_of_sight_past_earth() {
+        let earth = Vector::<f64, 3, ArrayRepr>::zeros();
+        let radius = 6378e3;
+        let station = Vector::new(radius, 0.0, 0.0);
+        let overhead = Vector::new(radius + 500e3, 0.0, 0.0);
+        let behind = Vector::new(-(radius + 500e3), 0.0, 0.0);
+        let clear = segment_sphere_clearance(&station, &overhead, &earth, radius - 1.0);
+        assert_relative_eq!(clear.into_buf(), 1.0, epsilon = 1e-6);
+        let blocked = segment_sphere_clearance(&station, &behind, &earth, radius - 1.0);
+        assert_relative_eq!(blocked.into_buf(), -(radius - 1.0), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_ray_sphere() {
+        let center = Vector::<f64, 3, ArrayRepr>::new(0.0, 0.0, 10.0);
+        let origin = Vector::zeros();
+        let dir = Vector::z_axis();
+        assert_relative_eq!(ray_sphere_toi(&origin, &dir, &center, 2.0).into_buf(), 8.0);
+        assert!(ray_sphere_clearance(&origin, &dir, &center, 2.0).into_buf() < 0.0);
+
+        let dir = Vector::x_axis();
+        assert_relative_eq!(
+            ray_sphere_clearance(&origin, &dir, &center, 2.0).into_buf(),
+            8.0
+        );
+        let dir = -Vector::z_axis();
+        assert_relative_eq!(
+            ray_sphere_clearance(&origin, &dir, &center, 2.0).into_buf(),
+            8.0
+        );
+    }
+
+    #[test]
+    fn test_ray_plane() {
+        let origin = Vector::<f64, 3, ArrayRepr>::new(0.0, 0.0, 100.0);
+        let dir = Vector::new(0.0, 0.6, -0.8);
+        let toi = ray_plane_toi(&origin, &dir, &Vector::z_axis(), 0.0);
+        assert_relative_eq!(toi.into_buf(), 125.0, epsilon = 1e-9);
+    }
+}
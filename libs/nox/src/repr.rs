@@ -3,8 +3,8 @@ use core::ops::{Add, Div, Mul, Neg, Sub};
 
 use crate::array::prelude::*;
 use crate::{
-    AddDim, BroadcastDim, BroadcastedDim, ConstDim, DefaultMap, DefaultMappedDim, Dim, DotDim,
-    Elem, Error, Field, RealField, ReplaceDim, ShapeConstraint,
+    AddDim, BroadcastDim, BroadcastedDim, CastLossyFrom, ConstDim, DefaultMap, DefaultMappedDim,
+    Dim, DotDim, Elem, Error, Field, RealField, ReplaceDim, ShapeConstraint,
 };
 
 pub trait Repr {
@@ -125,12 +125,21 @@ pub trait OwnedRepr: Repr {
 
     fn scalar_from_const<T1: Field>(value: T1) -> Self::Inner<T1, ()>;
 
+    /// Converts each element to another element type.
+    fn cast<T1: Field, T2: CastLossyFrom<T1>, D1: Dim>(
+        arg: &Self::Inner<T1, D1>,
+    ) -> Self::Inner<T2, D1>;
+
     fn neg<T1, D1: Dim>(arg: &Self::Inner<T1, D1>) -> Self::Inner<T1, D1>
     where
         T1: Field + Neg<Output = T1>;
 
     fn sqrt<T1: Field + RealField, D1: Dim>(arg: &Self::Inner<T1, D1>) -> Self::Inner<T1, D1>;
 
+    fn exp<T1: Field + RealField, D1: Dim>(arg: &Self::Inner<T1, D1>) -> Self::Inner<T1, D1>;
+
+    fn log<T1: Field + RealField, D1: Dim>(arg: &Self::Inner<T1, D1>) -> Self::Inner<T1, D1>;
+
     fn atan2<T1: Field + RealField, D1: Dim>(
         left: &Self::Inner<T1, D1>,
         right: &Self::Inner<T1, D1>,
@@ -145,6 +154,27 @@ pub trait OwnedRepr: Repr {
 
     fn abs<T1: Field + RealField, D1: Dim>(arg: &Self::Inner<T1, D1>) -> Self::Inner<T1, D1>;
 
+    /// Takes the elementwise maximum of two tensors of the same shape.
+    fn max<T1: Field + RealField, D1: Dim>(
+        left: &Self::Inner<T1, D1>,
+        right: &Self::Inner<T1, D1>,
+    ) -> Self::Inner<T1, D1>;
+
+    /// Takes the elementwise minimum of two tensors of the same shape.
+    fn min<T1: Field + RealField, D1: Dim>(
+        left: &Self::Inner<T1, D1>,
+        right: &Self::Inner<T1, D1>,
+    ) -> Self::Inner<T1, D1>;
+
+    /// Picks `on_true` where `left < right` and `on_false` elsewhere, elementwise over tensors of
+    /// the same shape.
+    fn select_less<T1: Field + RealField + PartialOrd, D1: Dim>(
+        left: &Self::Inner<T1, D1>,
+        right: &Self::Inner<T1, D1>,
+        on_true: &Self::Inner<T1, D1>,
+        on_false: &Self::Inner<T1, D1>,
+    ) -> Self::Inner<T1, D1>;
+
     fn copy_fixed_slice<T1: Field, D1: Dim, D2: Dim + ConstDim>(
         arg: &Self::Inner<T1, D1>,
         offsets: &[usize],
@@ -0,0 +1,561 @@
+//! Provides a compressed sparse row (CSR) matrix for large, mostly empty linear systems.
+//!
+//! Only the sparsity pattern is stored on the host, the non-zero values are regular scalars, so a
+//! [`CsrMatrix`] can be built from traced values and its products are traced like any other op.
+//!
+//! [`JacobianSparsity`] records which outputs of a function depend on which inputs, so the
+//! jacobian of a large, loosely coupled system can be differentiated one group of independent
+//! columns at a time instead of one column at a time.
+use alloc::{vec, vec::Vec};
+
+#[cfg(feature = "noxpr")]
+use crate::{xla, xla::ElementType, ArrayTy, Noxpr, Op};
+use crate::{
+    ArrayRepr, DefaultRepr, Error, Field, Matrix, OwnedRepr, RealField, Scalar, TensorItem, Vector,
+};
+#[cfg(feature = "noxpr")]
+use smallvec::smallvec;
+
+/// A `ROWS x COLS` matrix in compressed sparse row form.
+pub struct CsrMatrix<
+    T: TensorItem,
+    const ROWS: usize,
+    const COLS: usize,
+    R: OwnedRepr = DefaultRepr,
+> {
+    row_offsets: Vec<usize>,
+    col_indices: Vec<usize>,
+    values: Vec<Scalar<T, R>>,
+}
+
+impl<T: TensorItem, const ROWS: usize, const COLS: usize, R: OwnedRepr> Clone
+    for CsrMatrix<T, ROWS, COLS, R>
+where
+    Scalar<T, R>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            row_offsets: self.row_offsets.clone(),
+            col_indices: self.col_indices.clone(),
+            values: self.values.clone(),
+        }
+    }
+}
+
+impl<T: Field, const ROWS: usize, const COLS: usize, R: OwnedRepr> CsrMatrix<T, ROWS, COLS, R> {
+    /// Builds a matrix from `(row, col, value)` entries in any order. Duplicate entries are summed.
+    pub fn from_triplets(
+        entries: impl IntoIterator<Item = (usize, usize, Scalar<T, R>)>,
+    ) -> Result<Self, Error> {
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        if entries
+            .iter()
+            .any(|(row, col, _)| *row >= ROWS || *col >= COLS)
+        {
+            return Err(Error::OutOfBoundsAccess);
+        }
+        entries.sort_by_key(|(row, col, _)| (*row, *col));
+
+        let mut row_offsets = Vec::with_capacity(ROWS + 1);
+        let mut col_indices: Vec<usize> = Vec::with_capacity(entries.len());
+        let mut values: Vec<Scalar<T, R>> = Vec::with_capacity(entries.len());
+        row_offsets.push(0);
+        let mut last = None;
+        for (row, col, value) in entries {
+            while row_offsets.len() <= row {
+                row_offsets.push(col_indices.len());
+            }
+            if last == Some((row, col)) {
+                let sum = values.pop().expect("duplicate entry must follow an entry") + value;
+                values.push(sum);
+            } else {
+                col_indices.push(col);
+                values.push(value);
+            }
+            last = Some((row, col));
+        }
+        while row_offsets.len() <= ROWS {
+            row_offsets.push(col_indices.len());
+        }
+        Ok(Self {
+            row_offsets,
+            col_indices,
+            values,
+        })
+    }
+
+    /// Returns the number of stored entries.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn row_offsets(&self) -> &[usize] {
+        &self.row_offsets
+    }
+
+    pub fn col_indices(&self) -> &[usize] {
+        &self.col_indices
+    }
+
+    pub fn values(&self) -> &[Scalar<T, R>] {
+        &self.values
+    }
+
+    /// Returns an iterator over the `(col, value)` entries stored in `row`.
+    pub fn row(&self, row: usize) -> impl Iterator<Item = (usize, &Scalar<T, R>)> {
+        let range = self.row_offsets[row]..self.row_offsets[row + 1];
+        self.col_indices[range.clone()]
+            .iter()
+            .copied()
+            .zip(&self.values[range])
+    }
+
+    /// Expands the matrix into a dense matrix.
+    pub fn to_dense(&self) -> Matrix<T, ROWS, COLS, R> {
+        let mut dense = (0..ROWS * COLS).map(|_| T::zero::<R>()).collect::<Vec<_>>();
+        for row in 0..ROWS {
+            for (col, value) in self.row(row) {
+                dense[row * COLS + col] = value.clone();
+            }
+        }
+        Matrix::from_scalars(dense)
+    }
+}
+
+impl<T: Field, const ROWS: usize, const COLS: usize> CsrMatrix<T, ROWS, COLS, ArrayRepr> {
+    /// Computes the product of the matrix with the dense vector `x`.
+    pub fn matvec(&self, x: &Vector<T, COLS, ArrayRepr>) -> Vector<T, ROWS, ArrayRepr> {
+        let x = x.parts();
+        Vector::from_scalars((0..ROWS).map(|row| {
+            self.row(row)
+                .fold(T::zero::<ArrayRepr>(), |acc, (col, value)| {
+                    acc + value * &x[col]
+                })
+        }))
+    }
+}
+
+#[cfg(feature = "noxpr")]
+impl<T: Field, const ROWS: usize, const COLS: usize> CsrMatrix<T, ROWS, COLS, Op> {
+    /// Computes the product of the matrix with the dense vector `x`.
+    ///
+    /// The rows are padded with zeros to the length of the longest one, then the values and the
+    /// elements of `x` they multiply are each gathered into a `ROWS x width` array and the products
+    /// summed along the rows, so the trace is a fixed handful of ops however many entries are
+    /// stored.
+    pub fn matvec(&self, x: &Vector<T, COLS, Op>) -> Vector<T, ROWS, Op> {
+        let width = (0..ROWS)
+            .map(|row| self.row_offsets[row + 1] - self.row_offsets[row])
+            .max()
+            .unwrap_or(0);
+        if width == 0 {
+            return Vector::zeros();
+        }
+        // padding points at a zero appended to the values, and at any element of `x`
+        let pad = self.nnz() as u32;
+        let mut value_indices = Vec::with_capacity(ROWS * width);
+        let mut col_indices = Vec::with_capacity(ROWS * width);
+        for row in 0..ROWS {
+            let range = self.row_offsets[row]..self.row_offsets[row + 1];
+            for i in range.start..range.start + width {
+                if i < range.end {
+                    value_indices.push(i as u32);
+                    col_indices.push(self.col_indices[i] as u32);
+                } else {
+                    value_indices.push(pad);
+                    col_indices.push(0);
+                }
+            }
+        }
+        let values = self
+            .values
+            .iter()
+            .chain([&T::zero::<Op>()])
+            .map(|value| value.inner.clone().reshape(smallvec![1]))
+            .collect();
+        let values = gather(Noxpr::concat_in_dim(values, 0), &value_indices);
+        let x = gather(x.inner.clone(), &col_indices);
+        let products = (values * x).reshape(smallvec![ROWS as i64, width as i64]);
+        let ones = T::one::<Op>().inner.broadcast(smallvec![width as i64]);
+        Vector::from_inner(products.dot(&ones))
+    }
+}
+
+impl<T: RealField, const N: usize, R: OwnedRepr> CsrMatrix<T, N, N, R> {
+    /// Approximately solves `self * x = b` with `iterations` sweeps of Gauss-Seidel, starting from `guess`.
+    ///
+    /// Gauss-Seidel converges for strictly diagonally dominant or symmetric positive definite matrices,
+    /// such as the conductance matrices of thermal and electrical networks. Every diagonal entry must be
+    /// stored and non-zero, a row without one is an [`Error::MissingDiagonal`].
+    ///
+    /// The iteration count is fixed so the solve can be traced, but each row depends on the rows
+    /// updated before it in the same sweep, so the trace holds one op per stored entry per sweep.
+    /// That suits small networks of up to a few hundred entries; larger traced systems are better
+    /// served by an iteration built on [`CsrMatrix::matvec`], whose trace doesn't grow with the
+    /// entries.
+    pub fn solve(
+        &self,
+        b: &Vector<T, N, R>,
+        guess: &Vector<T, N, R>,
+        iterations: usize,
+    ) -> Result<Vector<T, N, R>, Error> {
+        let diags = (0..N)
+            .map(|row| {
+                self.row(row)
+                    .find(|(col, _)| *col == row)
+                    .map(|(_, value)| value)
+                    .ok_or(Error::MissingDiagonal(row))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let b = b.parts();
+        let mut x = guess.parts();
+        for _ in 0..iterations {
+            for (row, diag) in diags.iter().enumerate() {
+                let mut acc = b[row].clone();
+                for (col, value) in self.row(row) {
+                    if col != row {
+                        acc = acc - value * &x[col];
+                    }
+                }
+                x[row] = acc / *diag;
+            }
+        }
+        Ok(Vector::from_scalars(x))
+    }
+}
+
+/// The non-zero pattern of the jacobian of a function from `cols` inputs to `rows` outputs, along
+/// with a coloring of its columns.
+///
+/// Columns that never share a non-zero row get the same color, so seeding all of them at once
+/// still leaves every entry readable from the derivative. A world whose entities each interact
+/// with a handful of neighbours then needs a few function evaluations per jacobian, however many
+/// entities there are, rather than one per state element. The pattern is sized at runtime, so it
+/// can be built once the entities are known, e.g. from the edges of a graph query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JacobianSparsity {
+    rows: usize,
+    cols: usize,
+    row_offsets: Vec<usize>,
+    col_indices: Vec<usize>,
+    colors: Vec<usize>,
+    color_count: usize,
+}
+
+impl JacobianSparsity {
+    /// Builds the `rows x cols` pattern from the `(row, col)` entries that can be non-zero, in any
+    /// order.
+    pub fn from_entries(
+        rows: usize,
+        cols: usize,
+        entries: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Result<Self, Error> {
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        if entries
+            .iter()
+            .any(|(row, col)| *row >= rows || *col >= cols)
+        {
+            return Err(Error::OutOfBoundsAccess);
+        }
+        entries.sort_unstable();
+        entries.dedup();
+
+        let mut row_offsets = Vec::with_capacity(rows + 1);
+        row_offsets.push(0);
+        for (i, (row, _)) in entries.iter().enumerate() {
+            while row_offsets.len() <= *row {
+                row_offsets.push(i);
+            }
+        }
+        while row_offsets.len() <= rows {
+            row_offsets.push(entries.len());
+        }
+        let col_indices = entries.iter().map(|(_, col)| *col).collect::<Vec<_>>();
+
+        // greedy coloring of the column intersection graph, where two columns conflict when they
+        // share a row
+        let mut col_rows = vec![Vec::new(); cols];
+        for (row, col) in &entries {
+            col_rows[*col].push(*row);
+        }
+        let mut colors = vec![usize::MAX; cols];
+        let mut taken = Vec::new();
+        for col in 0..cols {
+            taken.clear();
+            for row in &col_rows[col] {
+                let cols = &col_indices[row_offsets[*row]..row_offsets[*row + 1]];
+                taken.extend(cols.iter().map(|other| colors[*other]));
+            }
+            let mut color = 0;
+            while taken.contains(&color) {
+                color += 1;
+            }
+            colors[col] = color;
+        }
+        let color_count = colors.iter().map(|color| color + 1).max().unwrap_or(0);
+        Ok(Self {
+            rows,
+            cols,
+            row_offsets,
+            col_indices,
+            colors,
+            color_count,
+        })
+    }
+
+    /// Builds the pattern of a system of `entities` with `block` state elements each, where every
+    /// `(i, j)` interaction means entity `i`'s outputs depend on entity `j`'s inputs.
+    ///
+    /// An entity's outputs only depend on its own inputs if `(i, i)` is listed too.
+    pub fn from_blocks(
+        entities: usize,
+        block: usize,
+        interactions: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Result<Self, Error> {
+        let size = entities * block;
+        Self::from_entries(
+            size,
+            size,
+            interactions.into_iter().flat_map(|(i, j)| {
+                (0..block * block).map(move |k| (i * block + k / block, j * block + k % block))
+            }),
+        )
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the number of entries that can be non-zero.
+    pub fn nnz(&self) -> usize {
+        self.col_indices.len()
+    }
+
+    /// Returns the color of every column.
+    pub fn colors(&self) -> &[usize] {
+        &self.colors
+    }
+
+    /// Returns the number of colors, which is the number of forward-mode passes
+    /// [`JacobianSparsity::jacobian`] costs.
+    pub fn color_count(&self) -> usize {
+        self.color_count
+    }
+}
+
+#[cfg(feature = "noxpr")]
+impl JacobianSparsity {
+    /// Computes the jacobian of the traced `f` at `x` with a forward-mode pass per color, seeding
+    /// every column of the color at once, see [`Noxpr::jvp`].
+    ///
+    /// Entries outside the pattern are assumed to be zero. If `f` does depend on them, their
+    /// derivatives are added into the entries of the same row and color.
+    pub fn jacobian<const ROWS: usize, const COLS: usize>(
+        &self,
+        f: impl Fn(Vector<f64, COLS, Op>) -> Vector<f64, ROWS, Op>,
+        x: &Vector<f64, COLS, Op>,
+    ) -> Result<Matrix<f64, ROWS, COLS, Op>, Error> {
+        if ROWS != self.rows || COLS != self.cols {
+            return Err(Error::SparsityShapeMismatch);
+        }
+        // each color's derivative is spread back over its columns with an outer product, and the
+        // sum masked down to the pattern, so the trace grows with the colors, not the entries
+        let mut mask = vec![0.0; ROWS * COLS];
+        for row in 0..ROWS {
+            for col in &self.col_indices[self.row_offsets[row]..self.row_offsets[row + 1]] {
+                mask[row * COLS + col] = 1.0;
+            }
+        }
+        let mask = constant(&mask).reshape(smallvec![ROWS as i64, COLS as i64]);
+        let y = f(x.clone()).inner;
+        let mut jac = Matrix::<f64, ROWS, COLS, Op>::zeros();
+        for color in 0..self.color_count {
+            let seed = self
+                .colors
+                .iter()
+                .map(|c| if *c == color { 1.0 } else { 0.0 })
+                .collect::<Vec<_>>();
+            let seed = Vector::<f64, COLS, Op>::from_inner(constant(&seed));
+            let derivative =
+                Vector::<f64, ROWS, Op>::from_inner(y.jvp(&x.inner, seed.inner.clone())?);
+            jac = jac + derivative.outer(&seed);
+        }
+        Ok(jac * Matrix::from_inner(mask))
+    }
+}
+
+#[cfg(feature = "noxpr")]
+fn constant(values: &[f64]) -> Noxpr {
+    let ty = ArrayTy::new(ElementType::F64, smallvec![values.len() as i64]);
+    Noxpr::constant(xla::Literal::vector(values), ty)
+}
+
+/// Gathers the elements of the vector `values` at `indices`.
+#[cfg(feature = "noxpr")]
+fn gather(values: Noxpr, indices: &[u32]) -> Noxpr {
+    let n = indices.len() as i64;
+    let ty = ArrayTy::new(ElementType::U32, smallvec![n]);
+    let indices = Noxpr::constant(xla::Literal::vector(indices), ty)
+        .broadcast_in_dim(smallvec![n, 1], smallvec![0]);
+    values.gather(
+        indices,
+        smallvec![],
+        smallvec![0],
+        smallvec![0],
+        smallvec![1],
+        1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::{tensor, ArrayRepr};
+
+    fn tridiagonal() -> CsrMatrix<f64, 3, 3, ArrayRepr> {
+        CsrMatrix::from_triplets([
+            (2, 2, 4.0.into()),
+            (0, 0, 4.0.into()),
+            (0, 1, (-1.0).into()),
+            (1, 0, (-1.0).into()),
+            (1, 1, 4.0.into()),
+            (1, 2, (-1.0).into()),
+            (2, 1, (-1.0).into()),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_from_triplets() {
+        let m = tridiagonal();
+        assert_eq!(m.nnz(), 7);
+        assert_eq!(m.row_offsets(), &[0, 2, 5, 7]);
+        assert_eq!(m.col_indices(), &[0, 1, 0, 1, 2, 1, 2]);
+        assert_eq!(
+            m.to_dense(),
+            tensor![[4.0, -1.0, 0.0], [-1.0, 4.0, -1.0], [0.0, -1.0, 4.0]]
+        );
+
+        let m = CsrMatrix::<f64, 2, 3, ArrayRepr>::from_triplets([
+            (1, 2, 1.0.into()),
+            (1, 2, 2.0.into()),
+        ])
+        .unwrap();
+        assert_eq!(m.row_offsets(), &[0, 0, 1]);
+        assert_eq!(m.to_dense(), tensor![[0.0, 0.0, 0.0], [0.0, 0.0, 3.0]]);
+
+        let err = CsrMatrix::<f64, 2, 2, ArrayRepr>::from_triplets([(2, 0, 1.0.into())]);
+        assert!(matches!(err, Err(Error::OutOfBoundsAccess)));
+    }
+
+    #[test]
+    fn test_matvec_solve() {
+        let m = tridiagonal();
+        let b = m.matvec(&tensor![1.0, 2.0, 3.0]);
+        assert_eq!(b, tensor![2.0, 4.0, 10.0]);
+        let x = m.solve(&b, &Vector::zeros(), 25).unwrap();
+        assert_relative_eq!(x, tensor![1.0, 2.0, 3.0], epsilon = 1e-10);
+
+        let m = CsrMatrix::<f64, 2, 2, ArrayRepr>::from_triplets([
+            (0, 0, 2.0.into()),
+            (1, 0, 1.0.into()),
+        ])
+        .unwrap();
+        let err = m.solve(&tensor![1.0, 1.0], &Vector::zeros(), 5);
+        assert!(matches!(err, Err(Error::MissingDiagonal(1))));
+    }
+
+    #[cfg(feature = "noxpr")]
+    #[test]
+    fn test_traced_matvec() {
+        use crate::{Client, CompFn, Op};
+
+        fn matvec(values: Vector<f64, 4, Op>, x: Vector<f64, 3, Op>) -> Vector<f64, 3, Op> {
+            let values = values.parts();
+            // the middle row is empty and the others have different lengths
+            let m = CsrMatrix::<f64, 3, 3, Op>::from_triplets([
+                (0, 0, values[0].clone()),
+                (0, 2, values[1].clone()),
+                (2, 1, values[2].clone()),
+                (2, 0, values[3].clone()),
+            ])
+            .unwrap();
+            m.matvec(&x)
+        }
+
+        let client = Client::cpu().unwrap();
+        let exec = matvec.build().unwrap().compile(&client).unwrap();
+        let out = exec
+            .run(&client, tensor![1.0, 2.0, 3.0, 4.0], tensor![1.0, 2.0, 3.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, tensor![7.0, 0.0, 10.0]);
+    }
+
+    #[test]
+    fn test_jacobian_coloring() {
+        let sparsity = JacobianSparsity::from_blocks(4, 2, chain_interactions()).unwrap();
+        assert_eq!((sparsity.rows(), sparsity.cols()), (8, 8));
+        assert_eq!(sparsity.nnz(), 40);
+        assert_eq!(sparsity.color_count(), 6);
+        for (col, color) in sparsity.colors().iter().enumerate() {
+            assert_eq!(*color, col % 6);
+        }
+
+        let err = JacobianSparsity::from_blocks(2, 2, [(0, 2)]);
+        assert!(matches!(err, Err(Error::OutOfBoundsAccess)));
+    }
+
+    /// A chain of four entities coupled to their neighbours.
+    fn chain_interactions() -> impl Iterator<Item = (usize, usize)> {
+        (0..4).flat_map(|i: usize| (i.saturating_sub(1)..(i + 2).min(4)).map(move |j| (i, j)))
+    }
+
+    /// The derivatives of the chain, with two states per entity.
+    fn chain<R: OwnedRepr>(x: Vector<f64, 8, R>) -> Vector<f64, 8, R> {
+        let x = x.parts();
+        Vector::from_scalars((0..8).map(|i| {
+            let entity = i / 2;
+            let mut out = &x[i] * &x[i];
+            if entity > 0 {
+                out = out + x[i - 2].sin();
+            }
+            if entity < 3 {
+                out = out + &x[i + 2] * &x[i ^ 1];
+            }
+            out
+        }))
+    }
+
+    #[cfg(feature = "noxpr")]
+    #[test]
+    fn test_sparse_jacobian() {
+        use crate::{Client, CompFn, Op};
+
+        fn jac(x: Vector<f64, 8, Op>) -> Matrix<f64, 8, 8, Op> {
+            let sparsity = JacobianSparsity::from_blocks(4, 2, chain_interactions()).unwrap();
+            sparsity.jacobian(chain, &x).unwrap()
+        }
+
+        let client = Client::cpu().unwrap();
+        let exec = jac.build().unwrap().compile(&client).unwrap();
+        let x = tensor![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
+        let out = exec.run(&client, x).unwrap().to_host();
+        assert_relative_eq!(
+            out,
+            crate::ekf::jacobian(chain::<ArrayRepr>, &x, 1e-6),
+            epsilon = 1e-8
+        );
+
+        let sparsity = JacobianSparsity::from_blocks(2, 2, [(0, 1)]).unwrap();
+        let x = Vector::<f64, 8, Op>::zeros();
+        let err = sparsity.jacobian(chain, &x);
+        assert!(matches!(err, Err(Error::SparsityShapeMismatch)));
+    }
+}
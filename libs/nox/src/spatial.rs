@@ -233,6 +233,11 @@ impl<T: RealField, R: OwnedRepr> SpatialForce<T, R> {
         self.inner.fixed_slice(&[3])
     }
 
+    /// Computes the power this force delivers to a body moving with `motion`, i.e `torque · ω + force · v`.
+    pub fn dot(&self, motion: &SpatialMotion<T, R>) -> Scalar<T, R> {
+        self.inner.dot(&motion.inner)
+    }
+
     /// Creates a zero spatial force.
     pub fn zero() -> Self {
         SpatialForce {
@@ -344,6 +349,14 @@ impl<T: TensorItem + RealField, R: OwnedRepr> SpatialInertia<T, R> {
     pub fn mass(&self) -> Scalar<T, R> {
         self.inner.fixed_slice::<Const<1>>(&[6]).reshape()
     }
+
+    /// Computes the kinetic energy `0.5 * vᵀ I v` of a body with this inertia moving with `motion`.
+    pub fn kinetic_energy(&self, motion: &SpatialMotion<T, R>) -> Scalar<T, R> {
+        let (angular, linear) = (motion.angular(), motion.linear());
+        let force: Vector<T, 3, R> = self.mass() * &linear - self.momentum().cross(&angular);
+        let torque = self.inertia_diag() * &angular + self.momentum().cross(&linear);
+        (force.dot(&linear) + torque.dot(&angular)) / T::two()
+    }
 }
 
 impl<T: TensorItem + RealField, R: OwnedRepr> Div<SpatialInertia<T, R>> for SpatialForce<T, R> {
@@ -465,6 +478,11 @@ impl<T: RealField, R: OwnedRepr> SpatialMotion<T, R> {
         SpatialMotion::new(ang_vel, vel)
     }
 
+    /// Computes the norm of the spatial motion, treating the angular and linear parts as one 6D vector.
+    pub fn norm(&self) -> Scalar<T, R> {
+        self.inner.norm()
+    }
+
     /// Computes the cross product of two spatial motions.
     pub fn cross(&self, other: &Self) -> Self {
         let ang_vel = self.angular().cross(&other.angular());
@@ -622,6 +640,30 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_spatial_energy() {
+        let motion =
+            SpatialMotion::<f64, ArrayRepr>::new(tensor![0.0, 0.0, 2.0], tensor![3.0, 0.0, 4.0]);
+        assert_eq!(motion.norm().into_buf(), 29f64.sqrt());
+
+        let force =
+            SpatialForce::<f64, ArrayRepr>::new(tensor![0.0, 0.0, 1.5], tensor![2.0, 0.0, 0.0]);
+        assert_eq!(force.dot(&motion).into_buf(), 3.0 + 6.0);
+
+        let inertia = SpatialInertia::<f64, ArrayRepr>::new(
+            tensor![1.0, 1.0, 0.5],
+            tensor![0.0, 0.0, 0.0],
+            2.0,
+        );
+        // 0.5 * m * v² + 0.5 * I * ω²
+        assert_eq!(inertia.kinetic_energy(&motion).into_buf(), 25.0 + 1.0);
+        // matches vᵀ (I v) / 2 through the spatial inertia product
+        assert_relative_eq!(
+            (inertia.clone() * motion.clone()).dot(&motion).into_buf() / 2.0,
+            inertia.kinetic_energy(&motion).into_buf()
+        );
+    }
+
     #[test]
     fn test_spatial_transform_add() {
         let a = SpatialTransform::new(
@@ -1,8 +1,8 @@
 //! Provides the core functionality for manipulating tensors.
 use crate::array::prelude::*;
 use crate::{
-    Const, DefaultRepr, Dim, Dyn, Elem, Error, Field, OwnedRepr, RealField, Repr, ReprMonad,
-    Scalar, ShapeConstraint,
+    CastFrom, CastLossyFrom, Const, DefaultRepr, Dim, Dyn, Elem, Error, Field, OwnedRepr,
+    RealField, Repr, ReprMonad, Scalar, ShapeConstraint,
 };
 use approx::{AbsDiffEq, RelativeEq};
 use core::iter::Sum;
@@ -88,6 +88,14 @@ impl<T: RealField, D: Dim, R: OwnedRepr> Tensor<T, D, R> {
         Self::from_inner(R::sqrt(&self.inner))
     }
 
+    pub fn exp(&self) -> Self {
+        Self::from_inner(R::exp(&self.inner))
+    }
+
+    pub fn log(&self) -> Self {
+        Self::from_inner(R::log(&self.inner))
+    }
+
     pub fn sin(&self) -> Self {
         Self::from_inner(R::sin(&self.inner))
     }
@@ -112,6 +120,39 @@ impl<T: RealField, D: Dim, R: OwnedRepr> Tensor<T, D, R> {
         Self::from_inner(R::atan2(&self.inner, &other.inner))
     }
 
+    /// Takes the elementwise maximum of `self` and `other`.
+    pub fn max(&self, other: &Self) -> Self {
+        Self::from_inner(R::max(&self.inner, &other.inner))
+    }
+
+    /// Takes the elementwise minimum of `self` and `other`.
+    pub fn min(&self, other: &Self) -> Self {
+        Self::from_inner(R::min(&self.inner, &other.inner))
+    }
+
+    /// Picks `on_true` where `self < other` and `on_false` elsewhere, elementwise.
+    pub fn select_less(&self, other: &Self, on_true: &Self, on_false: &Self) -> Self
+    where
+        T: PartialOrd,
+    {
+        Self::from_inner(R::select_less(
+            &self.inner,
+            &other.inner,
+            &on_true.inner,
+            &on_false.inner,
+        ))
+    }
+
+    /// Limits every element to the range `[min, max]`.
+    pub fn clamp(&self, min: &Self, max: &Self) -> Self {
+        self.max(min).min(max)
+    }
+
+    /// Limits every element to `[-limit, limit]`, like an actuator with a symmetric range.
+    pub fn saturate(&self, limit: &Self) -> Self {
+        self.clamp(&-limit, limit)
+    }
+
     pub fn try_lu_inverse(&self) -> Result<Self, Error>
     where
         D: SquareDim,
@@ -120,6 +161,22 @@ impl<T: RealField, D: Dim, R: OwnedRepr> Tensor<T, D, R> {
     }
 }
 
+impl<T: RealField, D: Dim, R: OwnedRepr> Tensor<T, D, R>
+where
+    ShapeConstraint: BroadcastDim<D, D, Output = D>,
+{
+    /// Zeroes every element within `width` of zero, and moves the rest `width` closer to zero so
+    /// the output stays continuous.
+    pub fn deadband(&self, width: &Self) -> Self {
+        self.max(width) + self.min(&-width)
+    }
+
+    /// Moves from `prev` toward `self` by at most `max_delta` per element, e.g a slew rate times the time step.
+    pub fn rate_limit(&self, prev: &Self, max_delta: &Self) -> Self {
+        prev.clone() + (self.clone() - prev.clone()).saturate(max_delta)
+    }
+}
+
 impl<T: TensorItem, D: Dim, R: OwnedRepr> Tensor<T, D, R> {
     pub fn from_inner(inner: R::Inner<T::Elem, D>) -> Self {
         Self {
@@ -139,6 +196,18 @@ impl<T: Field, D: Dim + NonScalarDim + ConstDim, R: OwnedRepr> Tensor<T, D, R> {
     }
 }
 
+impl<T: Field, D: Dim, R: OwnedRepr> Tensor<T, D, R> {
+    /// Converts each element to `U`, which only compiles where every value converts exactly.
+    pub fn cast<U: CastFrom<T>>(&self) -> Tensor<U, D, R> {
+        Tensor::from_inner(R::cast::<T, U, D>(&self.inner))
+    }
+
+    /// Converts each element to `U`, rounding, truncating or wrapping values it can't hold.
+    pub fn cast_lossy<U: CastLossyFrom<T>>(&self) -> Tensor<U, D, R> {
+        Tensor::from_inner(R::cast::<T, U, D>(&self.inner))
+    }
+}
+
 impl<T: TensorItem, D: Dim, R: OwnedRepr> Tensor<T, D, R> {
     pub fn inner(&self) -> &R::Inner<T::Elem, D> {
         &self.inner
@@ -1186,3 +1255,35 @@ impl<T: TensorItem, R: OwnedRepr, D: Dim> ReprMonad<R> for Tensor<T, D, R> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_cast() {
+        let v: Vector<f32, 3, ArrayRepr> = tensor![1.5, -2.0, 3.25];
+        assert_eq!(v.cast::<f64>(), tensor![1.5, -2.0, 3.25]);
+        let v: Vector<f64, 3, ArrayRepr> = tensor![1.7, -2.2, 1e10];
+        assert_eq!(v.cast_lossy::<i32>(), tensor![1, -2, i32::MAX]);
+        assert_eq!(v.cast_lossy::<f32>(), tensor![1.7f32, -2.2, 1e10]);
+
+        let client = Client::cpu().unwrap();
+        let comp = (|v: Vector<f32, 3>| 2.0 * v.cast::<f64>()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, tensor![1.5f32, -2.0, 3.25])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, tensor![3.0, -4.0, 6.5]);
+
+        let comp = (|v: Vector<f64, 3>| v.cast_lossy::<i32>()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, tensor![1.7, -2.2, 3.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, tensor![1, -2, 3]);
+    }
+}
@@ -162,6 +162,34 @@ mod tests {
         assert_eq!(out, tensor![2.0, 4.0, 6.0]);
     }
 
+    #[test]
+    fn test_vector_actuator_limits() {
+        let client = Client::cpu().unwrap();
+        let comp = (|v: Vector<f64, 4>, limit: Vector<f64, 4>| v.saturate(&limit))
+            .build()
+            .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(
+                &client,
+                tensor![2.0, -0.25, 0.75, -3.0],
+                tensor![1.0, 1.0, 1.0, 1.0],
+            )
+            .unwrap()
+            .to_host();
+        assert_eq!(out, tensor![1.0, -0.25, 0.75, -1.0]);
+
+        let v: Vector<f64, 4, ArrayRepr> = tensor![2.0, -0.25, 0.75, -3.0];
+        let band = tensor![0.5, 0.5, 0.5, 0.5];
+        assert_eq!(v.deadband(&band), tensor![1.5, 0.0, 0.25, -2.5]);
+        let prev = tensor![0.0, 0.0, 0.5, -1.0];
+        assert_eq!(v.rate_limit(&prev, &band), tensor![0.5, -0.25, 0.75, -1.5]);
+        assert_eq!(
+            v.clamp(&tensor![-1.0, 0.0, 0.0, -1.0], &tensor![1.0, 2.0, 0.5, 1.0]),
+            tensor![1.0, 0.0, 0.5, -1.0]
+        );
+    }
+
     #[test]
     fn test_vector_dot() {
         let client = Client::cpu().unwrap();
@@ -3,6 +3,7 @@ use crate::{
     XlaComputation,
 };
 use cpp::{cpp, cpp_class};
+use cxx::{CxxString, UniquePtr};
 use std::pin::Pin;
 
 cpp! {{
@@ -480,6 +481,56 @@ impl PjRtClient {
         Ok(exec)
     }
 
+    /// Loads an executable serialized with [`PjRtLoadedExecutable::serialize`].
+    ///
+    /// The executable must have been compiled by a client of the same platform and version.
+    pub fn deserialize_executable(
+        &self,
+        bytes: &[u8],
+        options: CompileOptions,
+    ) -> Result<PjRtLoadedExecutable> {
+        let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
+        let bytes_ptr = bytes.as_ptr();
+        let bytes_len = bytes.len();
+        let mut options = options.0;
+        let exec = unsafe {
+            cpp!([self as "std::shared_ptr<PjRtClient>*", bytes_ptr as "const char*", bytes_len as "size_t", mut options as "CompileOptions", out_status as "Status*"] -> PjRtLoadedExecutable as "std::shared_ptr<PjRtLoadedExecutable>" {
+                auto client = *self;
+                auto status = client->DeserializeExecutable(absl::string_view(bytes_ptr, bytes_len), options);
+                if (status.ok()) {
+                    return std::shared_ptr(std::move(status.value()));
+                }else{
+                    *out_status = Status(status.status());
+                    return std::shared_ptr<PjRtLoadedExecutable>();
+                }
+            })
+        };
+        out_status.to_result()?;
+        if exec.is_null() {
+            let backtrace = std::backtrace::Backtrace::capture().to_string();
+            return Err(Error::XlaError {
+                msg: "Unexpected null pointer".to_string(),
+                backtrace,
+            });
+        }
+        Ok(exec)
+    }
+
+    /// Returns the platform name and version of the client, e.g `("cpu", "<unknown>")`.
+    pub fn platform(&self) -> (String, String) {
+        let name = unsafe {
+            cpp!([self as "const std::shared_ptr<PjRtClient>*"] -> UniquePtr<CxxString> as "std::unique_ptr<std::string>" {
+                return std::make_unique<std::string>((*self)->platform_name());
+            })
+        };
+        let version = unsafe {
+            cpp!([self as "const std::shared_ptr<PjRtClient>*"] -> UniquePtr<CxxString> as "std::unique_ptr<std::string>" {
+                return std::make_unique<std::string>((*self)->platform_version());
+            })
+        };
+        (name.to_string(), version.to_string())
+    }
+
     pub fn compile_with_default_options(
         &self,
         comp: &XlaComputation,
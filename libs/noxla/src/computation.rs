@@ -95,4 +95,22 @@ impl CompileOptions {
             })
         };
     }
+
+    /// Serializes the options, so executables compiled with different options can be told apart.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let raw = &self.0;
+        let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
+        let bytes = unsafe {
+            cpp!([raw as "const CompileOptions*", out_status as "Status*"] -> UniquePtr<CxxString> as "std::unique_ptr<std::string>" {
+                auto proto = raw->ToProto();
+                if (!proto.ok()) {
+                    *out_status = proto.status();
+                    return std::make_unique<std::string>();
+                }
+                return std::make_unique<std::string>(proto->SerializeAsString());
+            })
+        };
+        out_status.to_result()?;
+        Ok(bytes.as_bytes().to_vec())
+    }
 }
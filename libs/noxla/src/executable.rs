@@ -1,6 +1,7 @@
 use crate::{BufferArgs, PjRtBuffer, Result, Status};
 
 use cpp::{cpp, cpp_class};
+use cxx::{CxxString, UniquePtr};
 
 use std::pin::Pin;
 
@@ -30,6 +31,24 @@ impl PjRtLoadedExecutable {
         }
     }
 
+    /// Serializes the compiled executable, so it can be reloaded with [`crate::PjRtClient::deserialize_executable`].
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
+        let bytes = unsafe {
+            cpp!([self as "const std::shared_ptr<PjRtLoadedExecutable>*", out_status as "Status*"] -> UniquePtr<CxxString> as "std::unique_ptr<std::string>" {
+                auto status = (*self)->SerializeExecutable();
+                if (status.ok()) {
+                    return std::make_unique<std::string>(std::move(status.value()));
+                }else{
+                    *out_status = Status(status.status());
+                    return std::make_unique<std::string>();
+                }
+            })
+        };
+        out_status.to_result()?;
+        Ok(bytes.as_bytes().to_vec())
+    }
+
     pub fn execute_buffers(&self, buffers: impl BufferArgs) -> Result<Vec<PjRtBuffer>> {
         let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
         let untuple_result = buffers.untuple_result();
@@ -60,6 +60,9 @@ pub use native_type::*;
 pub use op::*;
 pub use shape::*;
 
+/// The version of this crate, which pins the XLA extension it's built against.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 extern crate lapack_src as _;
 
 #[derive(Debug, Copy, Clone)]
@@ -79,7 +79,7 @@ impl Default for State<ArrayRepr> {
 #[cfg(test)]
 mod tests {
     use approx::assert_relative_eq;
-    use nox::Quaternion;
+    use nox::{EulerOrder, Quaternion};
 
     use crate::tests::test_mag_readings;
 
@@ -117,7 +117,7 @@ mod tests {
                 let beta = beta as f64 * std::f64::consts::PI * 2.0 / 8.0;
                 for theta in 0..8 {
                     let theta = theta as f64 * std::f64::consts::PI * 2.0 / 8.0;
-                    let rot = Quaternion::from_euler(tensor![alpha, beta, theta]);
+                    let rot = Quaternion::from_euler(EulerOrder::ZYX, tensor![theta, beta, alpha]);
                     let z = rot * tensor![20.0, 0.0, 0.0] + offset;
                     readings.push(z);
                 }
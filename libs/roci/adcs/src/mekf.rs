@@ -81,6 +81,14 @@ pub struct State {
     pub omega: Vector<f64, 3, ArrayRepr>,
     pub yqy: Matrix6<f64, ArrayRepr>,
     pub dt: f64,
+    /// The residual of only the last measurement of the last update, after the corrections of the
+    /// measurements before it. The other measurements' residuals are folded into `nis`.
+    pub last_residual: Vector<f64, 3, ArrayRepr>,
+    /// The normalized innovation squared of the last update, summed over its measurements.
+    ///
+    /// Each measurement is a unit vector, so for a consistent filter it is chi-squared distributed
+    /// with two degrees of freedom per measurement.
+    pub nis: f64,
 }
 
 impl State {
@@ -100,6 +108,8 @@ impl State {
             omega: Default::default(),
             yqy,
             dt,
+            last_residual: Default::default(),
+            nis: 0.0,
         }
     }
 
@@ -116,11 +126,14 @@ impl State {
             omega,
             yqy,
             dt,
+            ..
         } = self;
         let omega = omega - b_hat;
         let q_hat = propagate_quaternion(q_hat, omega, dt);
         let mut p = propagate_state_covariance(p, omega, yqy, dt);
         let mut delta_x_hat: Vector<f64, 6, ArrayRepr> = Vector::zeros();
+        let mut last_residual = Vector::zeros();
+        let mut nis = 0.0;
         for ((reference, measured_body), sigma) in references
             .into_iter()
             .zip(measured_bodys.into_iter())
@@ -139,7 +152,10 @@ impl State {
             let k = p.dot(&h_trans.dot(&s));
             p = (Matrix::<f64, 6, 6, ArrayRepr>::eye() - k.dot(&h)).dot(&p);
             let d: Vector<f64, 3, ArrayRepr> = h.dot(&delta_x_hat);
-            delta_x_hat = delta_x_hat + k.dot(&(e - d));
+            let residual = e - d;
+            nis += residual.dot(&s.dot(&residual)).into_buf();
+            delta_x_hat = delta_x_hat + k.dot(&residual);
+            last_residual = residual;
         }
         let delta_alpha: Vector<f64, 3, ArrayRepr> = delta_x_hat.fixed_slice(&[0]);
         let delta_beta: Vector<f64, 3, ArrayRepr> = delta_x_hat.fixed_slice(&[3]);
@@ -152,6 +168,8 @@ impl State {
             omega,
             yqy,
             dt,
+            last_residual,
+            nis,
         }
     }
 }
@@ -186,6 +204,76 @@ impl State {
     }
 }
 
+/// The consistency statistics of the last update of a [`State`], streamed by roci as the
+/// `residual` and `nis` components of entity `ENTITY_ID`, the same components the simulation's
+/// `estimation.FilterHealth` archetype records. The `residual` is the state's
+/// [`State::last_residual`].
+#[cfg(feature = "roci")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Health<const ENTITY_ID: u64> {
+    pub residual: [f64; 3],
+    pub nis: f64,
+}
+
+#[cfg(feature = "roci")]
+impl State {
+    pub fn health<const ENTITY_ID: u64>(&self) -> Health<ENTITY_ID> {
+        Health {
+            residual: self.last_residual.into_buf(),
+            nis: self.nis,
+        }
+    }
+}
+
+#[cfg(feature = "roci")]
+mod health {
+    use roci::impeller::{
+        ComponentId, ComponentValue, ComponentValueDim, ConstComponent, EntityId, ValueRepr,
+    };
+    use roci::{Componentize, Decomponentize};
+
+    use super::Health;
+
+    const RESIDUAL: ComponentId = ComponentId::new("residual");
+    const NIS: ComponentId = ComponentId::new("nis");
+
+    impl<const ENTITY_ID: u64> Componentize for Health<ENTITY_ID> {
+        fn sink_columns(&self, output: &mut impl Decomponentize) {
+            let entity_id = EntityId(ENTITY_ID);
+            output.apply_value(
+                RESIDUAL,
+                entity_id,
+                self.residual.fixed_dim_component_value(),
+            );
+            output.apply_value(NIS, entity_id, self.nis.fixed_dim_component_value());
+        }
+
+        const MAX_SIZE: usize = <[f64; 3]>::MAX_SIZE + f64::MAX_SIZE;
+    }
+
+    impl<const ENTITY_ID: u64> Decomponentize for Health<ENTITY_ID> {
+        fn apply_value<D: ComponentValueDim>(
+            &mut self,
+            component_id: ComponentId,
+            entity_id: EntityId,
+            value: ComponentValue<'_, D>,
+        ) {
+            if entity_id != EntityId(ENTITY_ID) {
+                return;
+            }
+            if component_id == RESIDUAL {
+                if let Some(residual) = <[f64; 3]>::from_component_value(value) {
+                    self.residual = residual;
+                }
+            } else if component_id == NIS {
+                if let Some(nis) = f64::from_component_value(value) {
+                    self.nis = nis;
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use approx::assert_relative_eq;
@@ -277,6 +365,8 @@ mod tests {
             state = state.estimate_attitude([body_a, body_b], [ref_a, ref_b], [0.03, 0.03]);
         }
         assert_relative_eq!(state.q_hat.0, q.0, epsilon = 1e-3);
+        // noise-free measurements should sit well inside the expected 4 degrees of freedom
+        assert!(state.nis < 4.0);
         for _ in 0..120 {
             q = q.integrate_body(tensor![1.0 / 120.0, 0.0, 0.0]);
             let body_a = (q.inverse() * ref_a).normalize();
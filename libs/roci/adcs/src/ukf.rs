@@ -1,8 +1,8 @@
 #![allow(clippy::type_complexity)]
 
 use nox::{
-    BaseBroadcastDim, BroadcastDim, Const, Dim, Error, Matrix, NonScalarDim, NonTupleDim,
-    OwnedRepr, Scalar, ShapeConstraint, SquareDim, Tensor, Vector,
+    BaseBroadcastDim, BroadcastDim, Const, Dim, DotDim, DottedDim, Error, Matrix, NonScalarDim,
+    NonTupleDim, OwnedRepr, Scalar, ShapeConstraint, SquareDim, Tensor, Vector,
 };
 
 pub fn unscented_transform<N, S, R>(
@@ -269,12 +269,35 @@ where
     ShapeConstraint: BroadcastDim<Z, Z, Output = Z>,
 {
     pub fn update<S>(
-        mut self,
+        self,
         config: UncheckedMerweConfig,
         z: Tensor<f64, Z, R>,
         prop_fn: impl Fn(Tensor<f64, N, R>) -> Tensor<f64, N, R>,
         measure_fn: impl Fn(Tensor<f64, N, R>, Tensor<f64, Z, R>) -> Tensor<f64, Z, R>,
     ) -> Result<Self, Error>
+    where
+        S: Dim + NonTupleDim + NonScalarDim,
+        (S, S): SquareDim<SideDim = S>,
+        (S, N): Dim,
+        (N, S): Dim,
+        (S, Z): Dim,
+        (Z, S): Dim,
+        (N, Z): Dim,
+        (Z, N): Dim,
+    {
+        self.update_with_innovation::<S>(config, z, prop_fn, measure_fn)
+            .map(|(state, _)| state)
+    }
+
+    /// Like [`UncheckedState::update`], but also returns the innovation of `z`, so the health of
+    /// the filter can be recorded next to each update.
+    pub fn update_with_innovation<S>(
+        mut self,
+        config: UncheckedMerweConfig,
+        z: Tensor<f64, Z, R>,
+        prop_fn: impl Fn(Tensor<f64, N, R>) -> Tensor<f64, N, R>,
+        measure_fn: impl Fn(Tensor<f64, N, R>, Tensor<f64, Z, R>) -> Tensor<f64, Z, R>,
+    ) -> Result<(Self, Innovation<Z, R>), Error>
     where
         S: Dim + NonTupleDim + NonScalarDim,
         (S, S): SquareDim<SideDim = S>,
@@ -310,8 +333,43 @@ where
         let y = z - z_hat;
         self.x_hat = x_hat + kalman_gain.dot(&y);
         self.covar = covar - kalman_gain.dot(&z_covar.dot(&kalman_gain.transpose()));
+        let innovation = Innovation {
+            residual: y,
+            covar: z_covar,
+        };
 
-        Ok(self)
+        Ok((self, innovation))
+    }
+}
+
+/// The difference between a measurement and the measurement predicted from the prior estimate,
+/// along with the covariance the filter expected for it.
+pub struct Innovation<Z: Dim, R: OwnedRepr>
+where
+    (Z, Z): Dim,
+{
+    pub residual: Tensor<f64, Z, R>,
+    pub covar: Tensor<f64, (Z, Z), R>,
+}
+
+impl<Z: Dim, R: OwnedRepr> Innovation<Z, R>
+where
+    Z: NonScalarDim + NonTupleDim,
+    (Z, Z): Dim + SquareDim<SideDim = Z>,
+{
+    /// Computes the normalized innovation squared (NIS), the residual's squared Mahalanobis
+    /// length.
+    ///
+    /// For a consistent filter it is chi-squared distributed with as many degrees of freedom as
+    /// the measurement has elements.
+    pub fn nis(&self) -> Result<Tensor<f64, DottedDim<Z, Z>, R>, Error>
+    where
+        ShapeConstraint: DotDim<Z, Z>,
+        DottedDim<Z, Z>: Dim,
+    {
+        Ok(self
+            .residual
+            .dot(&self.covar.try_inverse()?.dot(&self.residual)))
     }
 }
 
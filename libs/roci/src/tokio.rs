@@ -4,6 +4,7 @@ use std::{
 };
 
 use impeller::{
+    access::{Access, Credentials},
     bytes::{Bytes, BytesMut},
     client::{AsyncClient, Demux, Msg, TcpWriter},
     ser_de::ColumnValue,
@@ -44,6 +45,9 @@ pub struct Server {
     incoming_rx: RxChannel,
     outgoing_filter: HashSet<(Option<ComponentId>, Option<EntityId>)>,
     metadata: HashMap<ComponentId, Metadata>,
+    access: Access,
+    /// Connections that have yet to present the token the server's [`Access`] requires.
+    unauthenticated: HashSet<ConnectionId>,
 }
 
 enum Event {
@@ -58,6 +62,7 @@ pub fn tcp_listen<D: DriverMode>(
     metadata: impl IntoIterator<Item = Metadata>,
 ) -> (Tx<D>, Rx<D>) {
     let (server, tx, rx) = Server::with_capacity(addr, filters, 1024, metadata);
+    let server = server.with_access(Access::token_from_env());
     std::thread::spawn(move || {
         let rt = Runtime::new().unwrap();
         rt.block_on(server.run())
@@ -108,12 +113,28 @@ impl Server {
                 stream_map: Default::default(),
                 outgoing_filter,
                 metadata,
+                access: Access::default(),
+                unauthenticated: Default::default(),
             },
             incoming_tx,
             outgoing_rx,
         )
     }
 
+    /// Turns away clients that don't open with the right token, see [`Access`]. The server
+    /// doesn't speak TLS, so only the token is used.
+    pub fn with_access(mut self, access: Access) -> Self {
+        self.access = access;
+        self
+    }
+
+    fn disconnect(&mut self, id: ConnectionId) {
+        self.subscriptions.remove(&id);
+        self.write_halves.remove(&id);
+        self.stream_map.remove(&id);
+        self.unauthenticated.remove(&id);
+    }
+
     pub async fn run(mut self) -> Result<(), Error> {
         let listener = TcpListener::bind(self.addr).await?;
         loop {
@@ -127,6 +148,9 @@ impl Server {
                     let id = ConnectionId(fastrand::u64(..));
                     let (rx, tx) = stream.into_split();
                     self.subscriptions.insert(id, vec![]);
+                    if self.access.token.is_some() {
+                        self.unauthenticated.insert(id);
+                    }
                     self.write_halves.insert(
                         id,
                         AsyncClient::new(FramedWrite::new(tx, LengthDelimitedCodec::new())),
@@ -176,6 +200,13 @@ impl Server {
     async fn process_msg(&mut self, id: ConnectionId, buf: BytesMut) -> Result<(), Error> {
         let buf = buf.freeze();
         let packet = Packet::parse(buf)?;
+        if self.unauthenticated.remove(&id) {
+            if !self.access.admits(&packet) {
+                warn!("rejected connection");
+                self.disconnect(id);
+            }
+            return Ok(());
+        }
         let msg = self.demux.handle(packet)?;
         match msg {
             Msg::Control(ControlMsg::Subscribe { query }) => {
@@ -315,6 +346,7 @@ pub fn tcp_connect<D: DriverMode>(
     metadata: impl IntoIterator<Item = Metadata>,
 ) -> (Tx<D>, Rx<D>) {
     let (client, tx, rx) = Client::new(addr, filters, 1024, metadata);
+    let client = client.with_credentials(Credentials::token_from_env());
     std::thread::spawn(move || {
         let rt = Runtime::new().unwrap();
         if let Err(err) = rt.block_on(client.run()) {
@@ -340,6 +372,7 @@ pub struct Client {
     outgoing_rx: RxChannel,
     streams: HashMap<ComponentId, StreamId>,
     metadata: HashMap<ComponentId, Metadata>,
+    credentials: Credentials,
 }
 
 impl Client {
@@ -364,12 +397,19 @@ impl Client {
                 outgoing_rx,
                 metadata,
                 streams: HashMap::new(),
+                credentials: Credentials::default(),
             },
             outgoing_tx,
             incoming_rx,
         )
     }
 
+    /// The token to present to servers that require one. TLS isn't supported.
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
     pub async fn run(mut self) -> Result<(), Error> {
         #[derive(Debug)]
         enum Event {
@@ -381,6 +421,9 @@ impl Client {
         let (rx, tx) = tcp_stream.into_split();
         let mut tx = AsyncClient::new(FramedWrite::new(tx, LengthDelimitedCodec::new()));
         let mut rx = AsyncClient::new(FramedRead::new(rx, LengthDelimitedCodec::new()));
+        if let Some(packet) = self.credentials.auth_packet::<Bytes>() {
+            tx.send(packet).await?;
+        }
         tx.send(Packet::<Payload<Bytes>>::control(ControlMsg::Connect))
             .await?;
         for query in self.queries.into_iter() {
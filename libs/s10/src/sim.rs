@@ -69,9 +69,11 @@ impl SimRecipe {
         let client = self.client()?;
         let exec = self.build_with_client(client).await?;
         let (tx, rx) = flume::unbounded();
+        let access = impeller::access::Access::from_env().map_err(nox_ecs::Error::from)?;
         let server = impeller::server::TcpServer::bind(tx, self.addr)
             .await
-            .map_err(nox_ecs::Error::from)?;
+            .map_err(nox_ecs::Error::from)?
+            .with_access(access);
         let exec = tokio::task::spawn_blocking(move || {
             run_exec(exec, rx, cancel_token, std::iter::empty()).map(|_| ())
         });
@@ -91,9 +93,11 @@ impl SimRecipe {
                 .to_path_buf()
         };
         let (tx, rx) = flume::unbounded();
+        let access = impeller::access::Access::from_env().map_err(nox_ecs::Error::from)?;
         let server = impeller::server::TcpServer::bind(tx, self.addr)
             .await
-            .map_err(nox_ecs::Error::from)?;
+            .map_err(nox_ecs::Error::from)?
+            .with_access(access);
         let connections = Arc::new(Mutex::new(vec![]));
         let client = self.client()?;
         let watch = watch(